@@ -36,10 +36,9 @@ use reth_interfaces::{
 };
 use reth_network::{error::NetworkError, NetworkConfig, NetworkHandle, NetworkManager};
 use reth_network_api::NetworkInfo;
-use reth_primitives::{stage::StageId, BlockHashOrNumber, ChainSpec, Head, SealedHeader, H256};
+use reth_primitives::{BlockHashOrNumber, ChainSpec, Head, SealedHeader, H256};
 use reth_provider::{
-    BlockHashReader, BlockReader, CanonStateSubscriptions, HeaderProvider, ProviderFactory,
-    StageCheckpointReader,
+    BlockReader, CanonStateSubscriptions, DatabaseProvider, HeaderProvider, ProviderFactory,
 };
 use reth_revm::Factory;
 use reth_revm_inspectors::stack::Hook;
@@ -517,30 +516,7 @@ impl Command {
     }
 
     fn lookup_head(&self, db: Arc<DatabaseEnv>) -> Result<Head, reth_interfaces::Error> {
-        let factory = ProviderFactory::new(db, self.chain.clone());
-        let provider = factory.provider()?;
-
-        let head = provider.get_stage_checkpoint(StageId::Finish)?.unwrap_or_default().block_number;
-
-        let header = provider
-            .header_by_number(head)?
-            .expect("the header for the latest block is missing, database is corrupt");
-
-        let total_difficulty = provider
-            .header_td_by_number(head)?
-            .expect("the total difficulty for the latest block is missing, database is corrupt");
-
-        let hash = provider
-            .block_hash(head)?
-            .expect("the hash for the latest block is missing, database is corrupt");
-
-        Ok(Head {
-            number: head,
-            hash,
-            difficulty: header.difficulty,
-            total_difficulty,
-            timestamp: header.timestamp,
-        })
+        DatabaseProvider::new_checked(db.tx()?, self.chain.clone())?.head()
     }
 
     /// Attempt to look up the block number for the tip hash in the database.
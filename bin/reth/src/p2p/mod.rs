@@ -1,10 +1,6 @@
 //! P2P Debugging tool
 use crate::{
-    args::{
-        get_secret_key,
-        utils::{chain_spec_value_parser, hash_or_num_value_parser},
-        DiscoveryArgs,
-    },
+    args::{get_secret_key, utils::chain_spec_value_parser, DiscoveryArgs},
     dirs::{DataDirPath, MaybePlatformPath},
     utils::get_single_header,
 };
@@ -87,13 +83,11 @@ pub enum Subcommands {
     /// Download block header
     Header {
         /// The header number or hash
-        #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
     },
     /// Download block body
     Body {
         /// The block number or hash
-        #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
     },
 }
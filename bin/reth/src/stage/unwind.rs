@@ -120,6 +120,16 @@ mod tests {
         let cmd = Command::parse_from(["reth", "--datadir", "dir", "to-block", "100"]);
         assert_eq!(cmd.command, Subcommands::ToBlock { target: BlockHashOrNumber::Number(100) });
 
+        let cmd = Command::parse_from(["reth", "--datadir", "dir", "to-block", "0x64"]);
+        assert_eq!(cmd.command, Subcommands::ToBlock { target: BlockHashOrNumber::Number(100) });
+
+        let hash = "0x000000000000000000000000000000000000000000000000000000000000dead";
+        let cmd = Command::parse_from(["reth", "--datadir", "dir", "to-block", hash]);
+        assert_eq!(
+            cmd.command,
+            Subcommands::ToBlock { target: BlockHashOrNumber::Hash(hash.parse().unwrap()) }
+        );
+
         let cmd = Command::parse_from(["reth", "--datadir", "dir", "num-blocks", "100"]);
         assert_eq!(cmd.command, Subcommands::NumBlocks { amount: 100 });
     }
@@ -11,7 +11,7 @@ use reth_interfaces::p2p::{
     headers::client::{HeadersClient, HeadersRequest},
     priority::Priority,
 };
-use reth_primitives::{BlockHashOrNumber, ChainSpec, HeadersDirection, SealedHeader};
+use reth_primitives::{BlockHashOrNumber, ChainSpec, SealedHeader};
 use std::{
     env::VarError,
     path::{Path, PathBuf},
@@ -27,7 +27,7 @@ pub async fn get_single_header<Client>(
 where
     Client: HeadersClient,
 {
-    let request = HeadersRequest { direction: HeadersDirection::Rising, limit: 1, start: id };
+    let request = HeadersRequest::rising(id, 1);
 
     let (peer_id, response) =
         client.get_headers_with_priority(request, Priority::High).await?.split();
@@ -1088,12 +1088,14 @@ mod tests {
     use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseEnv};
     use reth_interfaces::test_utils::TestConsensus;
     use reth_primitives::{
-        proofs::EMPTY_ROOT, stage::StageCheckpoint, ChainSpecBuilder, H256, MAINNET,
+        proofs::EMPTY_ROOT,
+        stage::{StageCheckpoint, StageId},
+        ChainSpecBuilder, H256, MAINNET,
     };
     use reth_provider::{
         post_state::PostState,
         test_utils::{blocks::BlockChainTestData, TestExecutorFactory},
-        BlockWriter, ProviderFactory,
+        BlockWriter, ProviderFactory, StageCheckpointWriter,
     };
     use std::{collections::HashSet, sync::Arc};
 
@@ -1121,7 +1123,8 @@ mod tests {
         genesis.header.header.number = 10;
         genesis.header.header.state_root = EMPTY_ROOT;
         let factory = ProviderFactory::new(&db, MAINNET.clone());
-        let provider = factory.provider_rw().unwrap();
+        // the genesis is stored at height 10, the blocks below are only canonical hashes
+        let provider = factory.provider_rw().unwrap().allow_block_gaps();
 
         provider.insert_block(genesis, None).unwrap();
 
@@ -1132,10 +1135,7 @@ mod tests {
                 .put::<tables::CanonicalHeaders>(i, H256([100 + i as u8; 32]))
                 .unwrap();
         }
-        provider
-            .tx_ref()
-            .put::<tables::SyncStage>("Finish".to_string(), StageCheckpoint::new(10))
-            .unwrap();
+        provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(10)).unwrap();
         provider.commit().unwrap();
     }
 
@@ -6,9 +6,7 @@ use reth_interfaces::p2p::{
     headers::client::{HeadersClient, HeadersFut, HeadersRequest},
     priority::Priority,
 };
-use reth_primitives::{
-    BlockBody, BlockHashOrNumber, Header, HeadersDirection, PeerId, WithPeerId, H256,
-};
+use reth_primitives::{BlockBody, BlockHashOrNumber, Header, PeerId, WithPeerId, H256};
 use std::fmt::Debug;
 use tracing::{trace, warn};
 
@@ -34,7 +32,7 @@ impl AutoSealClient {
         let HeadersRequest { start, limit, direction } = request;
         let mut headers = Vec::new();
 
-        let mut block: BlockHashOrNumber = match start {
+        let block: BlockHashOrNumber = match start {
             BlockHashOrNumber::Hash(start) => start.into(),
             BlockHashOrNumber::Number(num) => {
                 if let Some(hash) = storage.block_hash(num) {
@@ -46,20 +44,12 @@ impl AutoSealClient {
             }
         };
 
-        for _ in 0..limit {
+        let mut next = (limit > 0).then_some(HeadersRequest { start: block, limit, direction });
+        while let Some(request) = next.take() {
             // fetch from storage
-            if let Some(header) = storage.header_by_hash_or_number(block) {
-                match direction {
-                    HeadersDirection::Falling => block = header.parent_hash.into(),
-                    HeadersDirection::Rising => {
-                        let next = header.number + 1;
-                        block = next.into()
-                    }
-                }
-                headers.push(header);
-            } else {
-                break
-            }
+            let Some(header) = storage.header_by_hash_or_number(request.start) else { break };
+            next = request.next_request(&header, 1);
+            headers.push(header);
         }
 
         trace!(target: "consensus::auto", ?headers, "returning headers");
@@ -628,14 +628,8 @@ where
         head: SealedHeader,
         update: &ForkchoiceState,
     ) -> Result<(), reth_interfaces::Error> {
-        let mut head_block = Head {
-            number: head.number,
-            hash: head.hash,
-            difficulty: head.difficulty,
-            timestamp: head.timestamp,
-            // NOTE: this will be set later
-            total_difficulty: Default::default(),
-        };
+        // NOTE: the total difficulty will be set later
+        let mut head_block = Head::from_sealed_header(&head, U256::ZERO);
 
         // we update the the tracked header first
         self.blockchain.set_canonical_head(head);
@@ -1553,7 +1547,7 @@ mod tests {
                     .expect("failed to create tree"),
             );
             let shareable_db = ProviderFactory::new(db.clone(), self.chain_spec.clone());
-            let latest = self.chain_spec.genesis_header().seal_slow();
+            let latest = self.chain_spec.sealed_genesis_header();
             let blockchain_provider = BlockchainProvider::with_latest(shareable_db, tree, latest);
             let (mut engine, handle) = BeaconConsensusEngine::new(
                 NoopFullBlockClient::default(),
@@ -1744,6 +1738,7 @@ mod tests {
         use super::*;
         use reth_db::{tables, transaction::DbTxMut};
         use reth_interfaces::test_utils::{generators, generators::random_block};
+        use reth_provider::BlockIdReader;
         use reth_rpc_types::engine::ForkchoiceUpdateError;
 
         #[tokio::test]
@@ -1811,8 +1806,8 @@ mod tests {
 
             let forkchoice = ForkchoiceState {
                 head_block_hash: block1.hash,
-                finalized_block_hash: block1.hash,
-                ..Default::default()
+                safe_block_hash: block1.hash,
+                finalized_block_hash: genesis.hash,
             };
 
             let result = env.send_forkchoice_updated(forkchoice).await.unwrap();
@@ -1822,6 +1817,11 @@ mod tests {
             ));
             assert_eq!(result, expected_result);
             assert_matches!(engine_rx.try_recv(), Err(TryRecvError::Empty));
+
+            // the safe and finalized blocks are persisted
+            let factory = ProviderFactory::new(env.db.clone(), chain_spec);
+            assert_eq!(factory.safe_block_num_hash(), Ok(Some(block1.num_hash())));
+            assert_eq!(factory.finalized_block_num_hash(), Ok(Some(genesis.num_hash())));
         }
 
         #[tokio::test]
@@ -1936,11 +1936,11 @@ mod tests {
             block1.header.difficulty = U256::from(1);
 
             // a second pre-merge block
-            let mut block2 = random_block(&mut rng, 1, Some(genesis.hash), None, Some(0));
+            let mut block2 = random_block(&mut rng, 2, Some(block1.hash), None, Some(0));
             block2.header.difficulty = U256::from(1);
 
             // a transition block
-            let mut block3 = random_block(&mut rng, 1, Some(genesis.hash), None, Some(0));
+            let mut block3 = random_block(&mut rng, 3, Some(block2.hash), None, Some(0));
             block3.header.difficulty = U256::from(1);
 
             insert_blocks(
@@ -0,0 +1,383 @@
+use crate::p2p::{
+    bodies::client::{BodiesClient, BodiesFut},
+    download::DownloadClient,
+    error::PeerRequestResult,
+    headers::client::{HeadersClient, HeadersFut, HeadersRequest},
+    priority::Priority,
+};
+use futures::{Future, FutureExt};
+use parking_lot::Mutex;
+use reth_primitives::{PeerId, H256};
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+use tracing::debug;
+
+/// How many peers a composite client remembers the serving client of.
+const MAX_TRACKED_PEERS: usize = 1024;
+
+/// Index of the primary client of a [FallbackClient] in its tracked peers.
+const PRIMARY: usize = 0;
+
+/// Index of the fallback client of a [FallbackClient] in its tracked peers.
+const FALLBACK: usize = 1;
+
+/// Remembers which client of a composite client served the last response of a peer, so that
+/// reports about the peer reach the client that is connected to it.
+#[derive(Debug, Clone, Default)]
+struct ResponsePeers {
+    inner: Arc<Mutex<ResponsePeersInner>>,
+}
+
+#[derive(Debug, Default)]
+struct ResponsePeersInner {
+    /// The index of the client that served the last response of the peer.
+    clients: HashMap<PeerId, usize>,
+    /// The tracked peers, oldest first.
+    order: VecDeque<PeerId>,
+}
+
+impl ResponsePeers {
+    /// Records that the client served a response of the peer.
+    fn insert(&self, peer_id: PeerId, client: usize) {
+        let mut inner = self.inner.lock();
+        if inner.clients.insert(peer_id, client).is_none() {
+            inner.order.push_back(peer_id);
+            if inner.order.len() > MAX_TRACKED_PEERS {
+                let oldest = inner.order.pop_front().expect("not empty");
+                inner.clients.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the client that served the last response of the peer.
+    fn client(&self, peer_id: &PeerId) -> Option<usize> {
+        self.inner.lock().clients.get(peer_id).copied()
+    }
+}
+
+/// A request of a composite client that records which client served the response.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct TrackedRequest<Fut> {
+    fut: Fut,
+    peers: ResponsePeers,
+    client: usize,
+}
+
+impl<Fut, T> Future for TrackedRequest<Fut>
+where
+    Fut: Future<Output = PeerRequestResult<T>> + Unpin,
+{
+    type Output = PeerRequestResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let response = ready!(this.fut.poll_unpin(cx));
+        if let Ok(response) = &response {
+            this.peers.insert(response.peer_id(), this.client);
+        }
+        Poll::Ready(response)
+    }
+}
+
+/// A client that sends requests to a primary client and fails over to a fallback client, e.g. a
+/// trusted RPC bridge next to the devp2p network.
+///
+/// A request goes to the fallback client if the primary client has no connected peers, or if it
+/// fails or returns an empty response to a non-empty request. Reports about a peer are routed to
+/// the client that served the response of the peer, reports about peers whose serving client is
+/// not tracked are dropped.
+#[derive(Debug, Clone)]
+pub struct FallbackClient<A, B> {
+    primary: A,
+    fallback: B,
+    peers: ResponsePeers,
+}
+
+impl<A, B> FallbackClient<A, B> {
+    /// Creates a client that fails over from `primary` to `fallback`.
+    pub fn new(primary: A, fallback: B) -> Self {
+        Self { primary, fallback, peers: ResponsePeers::default() }
+    }
+
+    /// Returns the primary client.
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Returns the fallback client.
+    pub fn fallback(&self) -> &B {
+        &self.fallback
+    }
+
+    fn track<Fut>(&self, fut: Fut, client: usize) -> TrackedRequest<Fut> {
+        TrackedRequest { fut, peers: self.peers.clone(), client }
+    }
+}
+
+impl<A, B> DownloadClient for FallbackClient<A, B>
+where
+    A: DownloadClient,
+    B: DownloadClient,
+{
+    fn report_bad_message(&self, peer_id: PeerId) {
+        match self.peers.client(&peer_id) {
+            Some(PRIMARY) => self.primary.report_bad_message(peer_id),
+            Some(_) => self.fallback.report_bad_message(peer_id),
+            // the client that served the peer is unknown or forgotten, blaming a client that
+            // never served it would penalize the peer there
+            None => debug!(target: "downloaders", %peer_id, "Dropped report of untracked peer"),
+        }
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.primary.num_connected_peers() + self.fallback.num_connected_peers()
+    }
+}
+
+impl<A, B> HeadersClient for FallbackClient<A, B>
+where
+    A: HeadersClient + Clone + 'static,
+    B: HeadersClient + Clone + 'static,
+{
+    type Output = HeadersFut;
+
+    fn get_headers_with_priority(
+        &self,
+        request: HeadersRequest,
+        priority: Priority,
+    ) -> Self::Output {
+        let this = self.clone();
+        Box::pin(async move {
+            if this.primary.num_connected_peers() > 0 {
+                let primary = this.primary.get_headers_with_priority(request.clone(), priority);
+                match this.track(primary, PRIMARY).await {
+                    Ok(headers) if !headers.data().is_empty() || request.limit == 0 => {
+                        return Ok(headers)
+                    }
+                    Ok(_) => {
+                        debug!(target: "downloaders", ?request, "Primary returned no headers")
+                    }
+                    Err(err) => {
+                        debug!(target: "downloaders", %err, ?request, "Primary failed")
+                    }
+                }
+            }
+            let fallback = this.fallback.get_headers_with_priority(request, priority);
+            this.track(fallback, FALLBACK).await
+        })
+    }
+}
+
+impl<A, B> BodiesClient for FallbackClient<A, B>
+where
+    A: BodiesClient + Clone + 'static,
+    B: BodiesClient + Clone + 'static,
+{
+    type Output = BodiesFut;
+
+    fn get_block_bodies_with_priority(
+        &self,
+        hashes: Vec<H256>,
+        priority: Priority,
+    ) -> Self::Output {
+        let this = self.clone();
+        Box::pin(async move {
+            if this.primary.num_connected_peers() > 0 {
+                let primary = this.primary.get_block_bodies_with_priority(hashes.clone(), priority);
+                match this.track(primary, PRIMARY).await {
+                    Ok(bodies) if !bodies.data().is_empty() || hashes.is_empty() => {
+                        return Ok(bodies)
+                    }
+                    Ok(_) => {
+                        debug!(target: "downloaders", len=hashes.len(), "No bodies from primary")
+                    }
+                    Err(err) => {
+                        debug!(target: "downloaders", %err, len=hashes.len(), "Primary failed")
+                    }
+                }
+            }
+            let fallback = this.fallback.get_block_bodies_with_priority(hashes, priority);
+            this.track(fallback, FALLBACK).await
+        })
+    }
+}
+
+/// A client that spreads requests over several clients of the same kind in turn.
+///
+/// The downloaders split the ranges they download into requests of a limited size, so every
+/// client serves a share of the chunks. Clients without connected peers are skipped while any
+/// other client has peers. Reports about a peer are routed to the client that served the
+/// response of the peer.
+#[derive(Debug, Clone)]
+pub struct RoundRobinClient<C> {
+    clients: Arc<Vec<C>>,
+    next: Arc<AtomicUsize>,
+    peers: ResponsePeers,
+}
+
+impl<C: DownloadClient> RoundRobinClient<C> {
+    /// Creates a client spreading requests over the clients.
+    ///
+    /// # Panics
+    ///
+    /// If there are no clients.
+    pub fn new(clients: Vec<C>) -> Self {
+        assert!(!clients.is_empty(), "round robin client without clients");
+        Self {
+            clients: Arc::new(clients),
+            next: Arc::new(AtomicUsize::new(0)),
+            peers: ResponsePeers::default(),
+        }
+    }
+
+    /// Returns the clients.
+    pub fn clients(&self) -> &[C] {
+        &self.clients
+    }
+
+    /// Returns the index of the client for the next request.
+    fn next_client(&self) -> usize {
+        let len = self.clients.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+        (0..len)
+            .map(|offset| (start + offset) % len)
+            .find(|index| self.clients[*index].num_connected_peers() > 0)
+            .unwrap_or(start)
+    }
+}
+
+impl<C: DownloadClient> DownloadClient for RoundRobinClient<C> {
+    fn report_bad_message(&self, peer_id: PeerId) {
+        match self.peers.client(&peer_id) {
+            Some(index) => self.clients[index].report_bad_message(peer_id),
+            // see the fallback client
+            None => debug!(target: "downloaders", %peer_id, "Dropped report of untracked peer"),
+        }
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.clients.iter().map(|client| client.num_connected_peers()).sum()
+    }
+}
+
+impl<C: HeadersClient> HeadersClient for RoundRobinClient<C> {
+    type Output = TrackedRequest<C::Output>;
+
+    fn get_headers_with_priority(
+        &self,
+        request: HeadersRequest,
+        priority: Priority,
+    ) -> Self::Output {
+        let client = self.next_client();
+        let fut = self.clients[client].get_headers_with_priority(request, priority);
+        TrackedRequest { fut, peers: self.peers.clone(), client }
+    }
+}
+
+impl<C: BodiesClient> BodiesClient for RoundRobinClient<C> {
+    type Output = TrackedRequest<C::Output>;
+
+    fn get_block_bodies_with_priority(
+        &self,
+        hashes: Vec<H256>,
+        priority: Priority,
+    ) -> Self::Output {
+        let client = self.next_client();
+        let fut = self.clients[client].get_block_bodies_with_priority(hashes, priority);
+        TrackedRequest { fut, peers: self.peers.clone(), client }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        p2p::full_block::FullBlockClient,
+        test_utils::{ChainFixture, NoopFullBlockClient, TestFullBlockClient},
+    };
+    use reth_primitives::{BlockBody, SealedBlockWithSenders, MAINNET};
+
+    fn chain() -> Vec<SealedBlockWithSenders> {
+        ChainFixture::new(MAINNET.clone()).blocks(9).txs_per_block(1..3).build()
+    }
+
+    fn client(blocks: &[SealedBlockWithSenders]) -> TestFullBlockClient {
+        let client = TestFullBlockClient::default();
+        client.insert_chain(blocks);
+        client
+    }
+
+    /// Returns a body with an ommer, which doesn't match any block of the chain.
+    fn wrong_body(block: &SealedBlockWithSenders) -> BlockBody {
+        let ommers = vec![block.header.header.clone()];
+        BlockBody { transactions: block.body.clone(), ommers, withdrawals: None }
+    }
+
+    #[tokio::test]
+    async fn fallback_fills_gaps() {
+        let blocks = chain();
+        let (even, odd): (Vec<_>, Vec<_>) =
+            blocks.iter().cloned().partition(|block| block.number % 2 == 0);
+        let (primary, fallback) = (client(&even), client(&odd));
+        let full_block_client = FullBlockClient::new(FallbackClient::new(primary, fallback));
+
+        for block in &blocks {
+            assert_eq!(full_block_client.get_full_block(block.hash()).await, block.block);
+        }
+
+        // a primary client without peers isn't asked
+        let client = FallbackClient::new(NoopFullBlockClient::default(), client(&odd));
+        let request = HeadersRequest::rising(1u64.into(), 3);
+        let headers = client.get_headers(request).await.unwrap().into_data();
+        assert_eq!(headers, vec![blocks[1].header.header.clone()]);
+    }
+
+    #[tokio::test]
+    async fn fallback_reports_the_serving_client() {
+        let blocks = chain();
+        let (primary, fallback) = (client(&blocks[..5]), client(&blocks[5..]));
+        primary.push_wrong_body(wrong_body(&blocks[2]));
+        fallback.push_wrong_body(wrong_body(&blocks[7]));
+        let client = FallbackClient::new(primary.clone(), fallback.clone());
+        let full_block_client = FullBlockClient::new(client);
+
+        assert_eq!(full_block_client.get_full_block(blocks[2].hash()).await, blocks[2].block);
+        assert_eq!((primary.bad_messages(), fallback.bad_messages()), (1, 0));
+        assert_eq!(full_block_client.get_full_block(blocks[7].hash()).await, blocks[7].block);
+        assert_eq!((primary.bad_messages(), fallback.bad_messages()), (1, 1));
+    }
+
+    #[test]
+    fn reports_of_untracked_peers_are_dropped() {
+        let blocks = chain();
+        let (primary, fallback) = (client(&blocks), client(&blocks));
+        FallbackClient::new(primary.clone(), fallback.clone()).report_bad_message(PeerId::random());
+        assert_eq!((primary.bad_messages(), fallback.bad_messages()), (0, 0));
+
+        let clients = (0..3).map(|_| client(&blocks)).collect::<Vec<_>>();
+        RoundRobinClient::new(clients.clone()).report_bad_message(PeerId::random());
+        assert!(clients.iter().all(|client| client.bad_messages() == 0));
+    }
+
+    #[tokio::test]
+    async fn round_robin_spreads_requests() {
+        let blocks = chain();
+        let clients = (0..3).map(|_| client(&blocks)).collect::<Vec<_>>();
+        clients[1].push_wrong_body(wrong_body(&blocks[4]));
+        let full_block_client = FullBlockClient::new(RoundRobinClient::new(clients.clone()));
+
+        for block in &blocks {
+            assert_eq!(full_block_client.get_full_block(block.hash()).await, block.block);
+        }
+        let bad_messages = clients.iter().map(|client| client.bad_messages()).collect::<Vec<_>>();
+        assert_eq!(bad_messages, vec![0, 1, 0]);
+    }
+}
@@ -3,7 +3,10 @@ use crate::p2p::{
     error::PeerRequestResult,
     headers::client::{HeadersClient, SingleHeaderRequest},
 };
-use reth_primitives::{BlockBody, Header, SealedBlock, SealedHeader, H256};
+use reth_primitives::{
+    validate_body_against_header, BlockBody, Header, HeaderHashLimit, SealedBlock, SealedHeader,
+    WithPeerId, H256, MAX_HEADER_HASHES_PER_POLL,
+};
 use std::{
     fmt::Debug,
     future::Future,
@@ -33,8 +36,9 @@ where
     ///
     /// Note: this future is cancel safe
     ///
-    /// Caution: This does no validation of body (transactions) response but guarantees that the
-    /// [SealedHeader] matches the requested hash.
+    /// The [SealedHeader] is guaranteed to match the requested hash, and the body is validated
+    /// against the header with [validate_body_against_header]. Bodies that don't match are
+    /// reported and requested again.
     pub fn get_full_block(&self, hash: H256) -> FetchFullBlockFuture<Client> {
         let client = self.client.clone();
         FetchFullBlockFuture {
@@ -60,7 +64,7 @@ where
     hash: H256,
     request: FullBlockRequest<Client>,
     header: Option<SealedHeader>,
-    body: Option<BlockBody>,
+    body: Option<WithPeerId<BlockBody>>,
 }
 
 impl<Client> FetchFullBlockFuture<Client>
@@ -78,14 +82,24 @@ where
     }
 
     /// Returns the [SealedBlock] if the request is complete.
+    ///
+    /// If the body doesn't match the header, the peer is reported and the body is requested again.
     fn take_block(&mut self) -> Option<SealedBlock> {
         if self.header.is_none() || self.body.is_none() {
             return None
         }
         let header = self.header.take().unwrap();
-        let body = self.body.take().unwrap();
+        let resp = self.body.take().unwrap();
+
+        if let Err(err) = validate_body_against_header(resp.data(), &header) {
+            debug!(target: "downloaders", %err, hash=?header.hash(), "Received wrong body");
+            self.client.report_bad_message(resp.peer_id());
+            self.header = Some(header);
+            self.request.body = Some(self.client.get_block_body(self.hash));
+            return None
+        }
 
-        Some(SealedBlock::new(header, body))
+        Some(SealedBlock::new(header, resp.into_data()))
     }
 }
 
@@ -97,6 +111,7 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
+        let _hash_limit = HeaderHashLimit::new(MAX_HEADER_HASHES_PER_POLL);
 
         loop {
             match ready!(this.request.poll(cx)) {
@@ -128,7 +143,8 @@ where
                 ResponseResult::Body(res) => {
                     match res {
                         Ok(maybe_body) => {
-                            this.body = maybe_body.into_data();
+                            let (peer, maybe_body) = maybe_body.split();
+                            this.body = maybe_body.map(|body| WithPeerId::new(peer, body));
                         }
                         Err(err) => {
                             debug!(target: "downloaders", %err, ?this.hash, "Body download failed");
@@ -156,7 +172,7 @@ where
         f.debug_struct("FetchFullBlockFuture")
             .field("hash", &self.hash)
             .field("header", &self.header)
-            .field("body", &self.body)
+            .field("body", &self.body.as_ref().map(|body| body.data()))
             .finish()
     }
 }
@@ -200,85 +216,89 @@ enum ResponseResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::p2p::{
-        download::DownloadClient, headers::client::HeadersRequest, priority::Priority,
+    use crate::{
+        p2p::headers::client::HeadersRequest,
+        test_utils::{ChainFixture, TestFullBlockClient},
     };
-    use parking_lot::Mutex;
-    use reth_primitives::{BlockHashOrNumber, PeerId, WithPeerId};
-    use std::{collections::HashMap, sync::Arc};
-
-    #[derive(Clone, Default, Debug)]
-    struct TestSingleFullBlockClient {
-        headers: Arc<Mutex<HashMap<H256, Header>>>,
-        bodies: Arc<Mutex<HashMap<H256, BlockBody>>>,
-    }
+    use futures::executor::block_on;
+    use proptest::{prelude::*, sample::select};
+    use reth_primitives::{
+        strategies::{consistent_block, corrupted_block, CorruptionKind},
+        ChainSpecBuilder, MAINNET,
+    };
+    use std::sync::Arc;
 
-    impl TestSingleFullBlockClient {
-        fn insert(&self, header: SealedHeader, body: BlockBody) {
-            let hash = header.hash();
-            let header = header.unseal();
-            self.headers.lock().insert(hash, header);
-            self.bodies.lock().insert(hash, body);
-        }
+    /// Splits the block into the sealed header and the body served by the client.
+    fn into_parts(block: SealedBlock) -> (SealedHeader, BlockBody) {
+        let SealedBlock { header, body, ommers, withdrawals } = block;
+        (header, BlockBody { transactions: body, ommers, withdrawals })
     }
 
-    impl DownloadClient for TestSingleFullBlockClient {
-        fn report_bad_message(&self, _peer_id: PeerId) {}
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
 
-        fn num_connected_peers(&self) -> usize {
-            1
+        #[test]
+        fn download_single_full_block(block in consistent_block(MAINNET.clone())) {
+            let client = TestFullBlockClient::default();
+            let (header, body) = into_parts(block.clone());
+            client.insert(header.clone(), body);
+            let client = FullBlockClient::new(client);
+
+            let received = block_on(client.get_full_block(header.hash()));
+            prop_assert_eq!(received, block);
         }
-    }
 
-    impl HeadersClient for TestSingleFullBlockClient {
-        type Output = futures::future::Ready<PeerRequestResult<Vec<Header>>>;
-
-        fn get_headers_with_priority(
-            &self,
-            request: HeadersRequest,
-            _priority: Priority,
-        ) -> Self::Output {
-            let headers = self.headers.lock();
-            let resp = match request.start {
-                BlockHashOrNumber::Hash(hash) => headers.get(&hash).cloned(),
-                BlockHashOrNumber::Number(num) => {
-                    headers.values().find(|h| h.number == num).cloned()
-                }
-            }
-            .map(|h| vec![h])
-            .unwrap_or_default();
-            futures::future::ready(Ok(WithPeerId::new(PeerId::random(), resp)))
+        #[test]
+        fn download_single_full_block_rejects_wrong_body(
+            (block, wrong) in consistent_block(MAINNET.clone()).prop_flat_map(|block| {
+                let original = Just(block.clone());
+                let corrupted = select(CorruptionKind::BODY_ONLY.to_vec())
+                    .prop_flat_map(move |kind| corrupted_block(block.clone(), kind));
+                (original, corrupted)
+            })
+        ) {
+            let client = TestFullBlockClient::default();
+            let (header, body) = into_parts(block.clone());
+            client.insert(header.clone(), body);
+            client.push_wrong_body(into_parts(wrong).1);
+            let full_block_client = FullBlockClient::new(client.clone());
+
+            let received = block_on(full_block_client.get_full_block(header.hash()));
+            prop_assert_eq!(received, block);
+            prop_assert_eq!(client.bad_messages(), 1);
         }
     }
 
-    impl BodiesClient for TestSingleFullBlockClient {
-        type Output = futures::future::Ready<PeerRequestResult<Vec<BlockBody>>>;
-
-        fn get_block_bodies_with_priority(
-            &self,
-            hashes: Vec<H256>,
-            _priority: Priority,
-        ) -> Self::Output {
-            let bodies = self.bodies.lock();
-            let mut all_bodies = Vec::new();
-            for hash in hashes {
-                if let Some(body) = bodies.get(&hash) {
-                    all_bodies.push(body.clone());
-                }
-            }
-            futures::future::ready(Ok(WithPeerId::new(PeerId::random(), all_bodies)))
+    #[tokio::test]
+    async fn download_fixture_blocks() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().shanghai_activated().build());
+        let blocks = ChainFixture::new(chain_spec).blocks(20).txs_per_block(0..5).build();
+        let client = TestFullBlockClient::default();
+        client.insert_chain(&blocks);
+        let full_block_client = FullBlockClient::new(client.clone());
+
+        for block in blocks {
+            let received = full_block_client.get_full_block(block.hash()).await;
+            assert_eq!(received, block.block);
         }
+        assert_eq!(client.bad_messages(), 0);
     }
 
     #[tokio::test]
-    async fn download_single_full_block() {
-        let client = TestSingleFullBlockClient::default();
-        let header = SealedHeader::default();
-        let body = BlockBody::default();
-        client.insert(header.clone(), body.clone());
-        let client = FullBlockClient::new(client);
-
-        let received = client.get_full_block(header.hash()).await;
-        assert_eq!(received, SealedBlock::new(header, body));
+    async fn download_headers_in_both_directions() {
+        let client = TestFullBlockClient::default();
+        let blocks = ChainFixture::new(MAINNET.clone()).blocks(3).build();
+        client.insert_chain(&blocks);
+        let tip = blocks[3].hash();
+        let unsealed =
+            blocks.into_iter().map(|block| block.block.header.unseal()).collect::<Vec<_>>();
+
+        let falling = HeadersRequest::falling(tip.into(), 10);
+        let received = client.get_headers(falling).await.unwrap().into_data();
+        assert_eq!(received, unsealed.iter().rev().cloned().collect::<Vec<_>>());
+
+        let rising = HeadersRequest::rising(1u64.into(), 2);
+        let received = client.get_headers(rising).await.unwrap().into_data();
+        assert_eq!(received, unsealed[1..3]);
     }
 }
@@ -20,6 +20,40 @@ pub struct HeadersRequest {
     pub direction: HeadersDirection,
 }
 
+impl HeadersRequest {
+    /// Creates a request for `limit` headers from `start` towards the genesis block, i.e. for
+    /// `start` and its ancestors.
+    pub fn falling(start: BlockHashOrNumber, limit: u64) -> Self {
+        Self { start, limit, direction: HeadersDirection::Falling }
+    }
+
+    /// Creates a request for `limit` headers from `start` on, with rising block numbers.
+    pub fn rising(start: BlockHashOrNumber, limit: u64) -> Self {
+        Self { start, limit, direction: HeadersDirection::Rising }
+    }
+
+    /// Returns the request for the remaining headers after `received` headers were returned for
+    /// this request, the last of them being `last_header`.
+    ///
+    /// The follow-up request starts at the parent hash of the last header for falling requests,
+    /// and at the number after it for rising ones. Returns `None` if all headers were received or
+    /// there are no more headers in the direction of the request, i.e. the last header of a
+    /// falling request is the genesis header.
+    pub fn next_request(&self, last_header: &Header, received: u64) -> Option<Self> {
+        let limit = self.limit.checked_sub(received).filter(|limit| *limit > 0)?;
+        let start = match self.direction {
+            HeadersDirection::Falling => {
+                if last_header.number == 0 {
+                    return None
+                }
+                last_header.parent_hash.into()
+            }
+            HeadersDirection::Rising => last_header.number.checked_add(1)?.into(),
+        };
+        Some(Self { start, limit, direction: self.direction })
+    }
+}
+
 /// The headers future type
 pub type HeadersFut = Pin<Box<dyn Future<Output = PeerRequestResult<Vec<Header>>> + Send + Sync>>;
 
@@ -54,12 +88,8 @@ pub trait HeadersClient: DownloadClient {
         start: BlockHashOrNumber,
         priority: Priority,
     ) -> SingleHeaderRequest<Self::Output> {
-        let req = HeadersRequest {
-            start,
-            limit: 1,
-            // doesn't matter for a single header
-            direction: HeadersDirection::Rising,
-        };
+        // the direction doesn't matter for a single header
+        let req = HeadersRequest::rising(start, 1);
         let fut = self.get_headers_with_priority(req, priority);
         SingleHeaderRequest { fut }
     }
@@ -84,3 +114,49 @@ where
         Poll::Ready(resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::H256;
+
+    #[test]
+    fn next_request_falling() {
+        let parent_hash = H256::from_low_u64_be(1);
+        let request = HeadersRequest::falling(10u64.into(), 5);
+        let last = Header { number: 8, parent_hash, ..Default::default() };
+        let next = request.next_request(&last, 3).unwrap();
+        assert_eq!(next.start, parent_hash.into());
+        assert_eq!(next.limit, 2);
+        assert!(next.direction.is_falling());
+
+        // complete response
+        assert!(request.next_request(&Header { number: 6, ..last.clone() }, 5).is_none());
+        assert!(request.next_request(&Header { number: 6, ..last }, 6).is_none());
+    }
+
+    #[test]
+    fn next_request_falling_at_genesis() {
+        let request = HeadersRequest::falling(2u64.into(), 10);
+        let genesis = Header::default();
+        assert!(request.next_request(&genesis, 3).is_none());
+
+        let child = Header { number: 1, parent_hash: H256::from_low_u64_be(1), ..genesis };
+        let next = request.next_request(&child, 2).unwrap();
+        assert_eq!(next.start, child.parent_hash.into());
+        assert_eq!(next.limit, 8);
+    }
+
+    #[test]
+    fn next_request_rising() {
+        let request = HeadersRequest::rising(0u64.into(), 10);
+        let genesis = Header::default();
+        let next = request.next_request(&genesis, 1).unwrap();
+        assert_eq!(next.start, 1u64.into());
+        assert_eq!(next.limit, 9);
+        assert!(next.direction.is_rising());
+
+        let last = Header { number: u64::MAX, ..Default::default() };
+        assert!(request.next_request(&last, 1).is_none());
+    }
+}
@@ -7,6 +7,9 @@ pub mod bodies;
 /// A downloader that combines two different downloaders/client implementations.
 pub mod either;
 
+/// Clients that combine several download clients, with failover or in turn.
+pub mod composite;
+
 /// An implementation that uses headers and bodies traits to download full blocks
 pub mod full_block;
 
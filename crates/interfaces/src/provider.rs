@@ -1,4 +1,4 @@
-use reth_primitives::{Address, BlockHash, BlockHashOrNumber, BlockNumber, TxNumber, H256};
+use reth_primitives::{Address, BlockHash, BlockHashOrNumber, BlockNumber, TxNumber, H256, U256};
 
 /// Bundled errors variants thrown by various providers.
 #[allow(missing_docs)]
@@ -34,12 +34,213 @@ pub enum ProviderError {
     /// The total difficulty for a block is missing.
     #[error("Total difficulty not found for block #{number}")]
     TotalDifficultyNotFound { number: BlockNumber },
+    /// A total difficulty does not match the one accumulated from the header difficulties.
+    #[error("Total difficulty of block #{number} is {got}, expected {expected}")]
+    TotalDifficultyMismatch {
+        /// Number of the block
+        number: BlockNumber,
+        /// Total difficulty accumulated from the parent
+        expected: U256,
+        /// Total difficulty that was given
+        got: U256,
+    },
     /// Thrown when required header related data was not found but was required.
     #[error("No header found for {0:?}")]
     HeaderNotFound(BlockHashOrNumber),
     /// Thrown we were unable to find a specific block
     #[error("Block does not exist {0:?}")]
     BlockNotFound(BlockHashOrNumber),
+    /// An entry that other tables refer to is missing.
+    #[error("{table} has no entry for {key}")]
+    MissingEntry {
+        /// Name of the table
+        table: &'static str,
+        /// The missing key
+        key: String,
+    },
+    /// An index table has an entry for a block whose data is missing.
+    #[error("{table} entry of block #{block} has no matching data")]
+    InconsistentIndex {
+        /// Name of the index table
+        table: &'static str,
+        /// Block number of the entry
+        block: BlockNumber,
+    },
+    /// The header or body of the block were pruned from the database.
+    #[error("Block #{block} was pruned, lowest available block is #{lowest_available}")]
+    BlockPruned {
+        /// Requested block number
+        block: BlockNumber,
+        /// Lowest block number whose data is available
+        lowest_available: BlockNumber,
+    },
+    /// The genesis hash of the database does not match the chain spec.
+    #[error("Genesis hash of the database {got:?} does not match the chain spec {expected:?}")]
+    GenesisMismatch {
+        /// Genesis hash of the chain spec
+        expected: H256,
+        /// Genesis hash stored in the database
+        got: H256,
+    },
+    /// A header past the merge still carries proof-of-work fields.
+    #[error(
+        "Header #{number} is past the merge but has difficulty {difficulty} and nonce {nonce}"
+    )]
+    InconsistentHeader {
+        /// Number of the header
+        number: BlockNumber,
+        /// Difficulty of the header, expected to be zero
+        difficulty: U256,
+        /// Nonce of the header, expected to be zero
+        nonce: u64,
+    },
+    /// A header of a batch does not attach to the preceding header of the batch.
+    #[error("Header #{number} does not attach to the preceding header of the batch")]
+    NonContiguousHeaders {
+        /// Number of the detached header
+        number: BlockNumber,
+    },
+    /// The first header of a batch does not attach to the canonical tip.
+    #[error("Header #{number} with parent {parent_hash:?} does not attach to the canonical tip #{tip_number} ({tip_hash:?})")]
+    DetachedHeaders {
+        /// Number of the first header of the batch
+        number: BlockNumber,
+        /// Parent hash of the first header of the batch
+        parent_hash: BlockHash,
+        /// Number of the canonical tip
+        tip_number: BlockNumber,
+        /// Hash of the canonical tip
+        tip_hash: BlockHash,
+    },
+    /// The parent of the first header of a new chain is not a canonical block.
+    #[error("Parent {parent_hash:?} of header #{number} is not a canonical block")]
+    UnknownForkBlock {
+        /// Number of the first header of the new chain
+        number: BlockNumber,
+        /// Parent hash of the first header of the new chain
+        parent_hash: BlockHash,
+    },
+    /// An inserted block does not follow the canonical tip.
+    #[error("Block #{number} does not follow the canonical tip, expected block #{expected}")]
+    NonContiguousBlock {
+        /// Number of the inserted block
+        number: BlockNumber,
+        /// Number of the block expected next
+        expected: BlockNumber,
+    },
+    /// A pending block does not directly follow its pending parent.
+    #[error(
+        "Pending block #{number} does not follow its pending parent, expected block #{expected}"
+    )]
+    NonContiguousPendingBlock {
+        /// Number of the pending block
+        number: BlockNumber,
+        /// Number of the block following the parent
+        expected: BlockNumber,
+    },
+    /// The parent hash of an inserted block is not the hash of the canonical block below it.
+    #[error(
+        "Parent hash {got:?} of block #{number} does not match the canonical hash {expected:?}"
+    )]
+    ParentHashMismatch {
+        /// Number of the inserted block
+        number: BlockNumber,
+        /// Hash of the canonical block below
+        expected: BlockHash,
+        /// Parent hash of the inserted block
+        got: BlockHash,
+    },
+    /// The timestamp of an inserted block is before the timestamp of its parent.
+    #[error("Timestamp {timestamp} of block #{number} is before its parent's {parent_timestamp}")]
+    TimestampBeforeParent {
+        /// Number of the inserted block
+        number: BlockNumber,
+        /// Timestamp of the inserted block
+        timestamp: u64,
+        /// Timestamp of the parent
+        parent_timestamp: u64,
+    },
+    /// Another block is already stored at the height of an inserted block.
+    #[error(
+        "Block #{number} is already stored as {existing:?}, unwind it before inserting {new:?}"
+    )]
+    BlockConflict {
+        /// Number of the block
+        number: BlockNumber,
+        /// Hash of the stored block
+        existing: BlockHash,
+        /// Hash of the inserted block
+        new: BlockHash,
+    },
+    /// A canonical block was requested to be removed as a side-chain block.
+    #[error("Block #{number} ({hash:?}) is canonical and can't be removed by hash")]
+    CanonicalBlockRemoval {
+        /// Number of the block
+        number: BlockNumber,
+        /// Hash of the block
+        hash: BlockHash,
+    },
+    /// A body of a batch does not follow the last stored block body.
+    #[error(
+        "Body of block #{number} does not follow the last stored body, expected block #{expected}"
+    )]
+    DetachedBody {
+        /// Number of the block of the body
+        number: BlockNumber,
+        /// Number of the block whose body is expected next
+        expected: BlockNumber,
+    },
+    /// A root computed from a block body does not match the stored header.
+    #[error("{root} root of the body of block #{number} is {got:?}, header expects {expected:?}")]
+    BodyRootMismatch {
+        /// Number of the block
+        number: BlockNumber,
+        /// Name of the mismatching root
+        root: &'static str,
+        /// Root in the header
+        expected: Option<H256>,
+        /// Root computed from the body
+        got: Option<H256>,
+    },
+    /// A block body does not match the stored header for a reason other than its roots.
+    #[error("Body of block #{number} is invalid: {error}")]
+    InvalidBody {
+        /// Number of the block
+        number: BlockNumber,
+        /// The validation error
+        error: reth_primitives::BlockValidationError,
+    },
+    /// The total of the withdrawn amounts in a range of blocks overflows.
+    #[error(transparent)]
+    WithdrawalAmountOverflow(#[from] reth_primitives::WithdrawalAmountOverflow),
+    /// Withdrawals were written for a block before the Shanghai activation.
+    #[error(
+        "Block #{number} with timestamp {timestamp} is before Shanghai and has no withdrawals"
+    )]
+    WithdrawalsBeforeShanghai {
+        /// Number of the block
+        number: BlockNumber,
+        /// Timestamp of the block
+        timestamp: u64,
+    },
+    /// The number of receipt lists does not match the number of blocks.
+    #[error("Got receipts for {receipts} blocks, expected {blocks}")]
+    ReceiptsLengthMismatch {
+        /// Number of blocks
+        blocks: usize,
+        /// Number of receipt lists
+        receipts: usize,
+    },
+    /// The number of receipts of a block does not match its transaction count.
+    #[error("Got {receipts} receipts for block #{block_number} with {transactions} transactions")]
+    BlockReceiptsCountMismatch {
+        /// Block number
+        block_number: BlockNumber,
+        /// Number of transactions of the block
+        transactions: usize,
+        /// Number of receipts
+        receipts: usize,
+    },
     /// Thrown we were unable to find the best block
     #[error("Best block does not exist")]
     BestBlockNotFound,
@@ -49,9 +250,65 @@ pub enum ProviderError {
     /// Thrown we were unable to find the safe block
     #[error("Safe block does not exist")]
     SafeBlockNotFound,
+    /// Thrown when a finalized or safe block is not part of the canonical chain.
+    #[error("Block #{number} ({hash:?}) is not canonical")]
+    NonCanonicalBlock { number: BlockNumber, hash: BlockHash },
+    /// Thrown when the finalized block would be moved behind the current finalized block.
+    #[error("Finalized block can't move backwards from #{current} to #{new}")]
+    FinalizedBlockMovedBackwards { current: BlockNumber, new: BlockNumber },
     /// Mismatch of sender and transaction
     #[error("Mismatch of sender and transaction id {tx_id}")]
     MismatchOfTransactionAndSenderId { tx_id: TxNumber },
+    /// The senders of the block are not stored and some can't be recovered from the signatures.
+    #[error("Senders of block #{0} can't be recovered")]
+    SenderRecoveryFailed(BlockNumber),
+    /// The transaction is missing.
+    #[error("Transaction #{0} not found")]
+    TransactionNotFound(TxNumber),
+    /// The receipt for the transaction was pruned from the database.
+    #[error("Receipt for transaction #{0} was pruned")]
+    ReceiptPruned(TxNumber),
+    /// The transactions and receipts of a block are not aligned, e.g. because the block was only
+    /// partially executed.
+    #[error("Transactions and receipts of block #{block_number} are misaligned at transaction #{tx_num}")]
+    TransactionReceiptMismatch {
+        /// Block number
+        block_number: BlockNumber,
+        /// First transaction number without a matching receipt or transaction
+        tx_num: TxNumber,
+    },
+    /// The transaction was not found and the lookup entries of transactions were pruned.
+    #[error(
+        "Transaction {hash:?} not found, lookup entries up to block #{prune_height} were pruned"
+    )]
+    TransactionLookupPruned {
+        /// Requested transaction hash
+        hash: TxHash,
+        /// Highest block number whose transaction lookup entries were pruned
+        prune_height: BlockNumber,
+    },
+    /// The receipts of the block were pruned from the database.
+    #[error("Receipts of block #{block} were pruned, prune height is #{prune_height}")]
+    ReceiptsPruned {
+        /// Requested block number
+        block: BlockNumber,
+        /// Highest block number whose receipts were pruned
+        prune_height: BlockNumber,
+    },
+    /// The gas used by the stored receipts of a block does not match the header.
+    #[error("Receipts of block #{block_number} used {got} gas, header expects {expected}")]
+    BlockGasUsedMismatch {
+        /// Block number
+        block_number: BlockNumber,
+        /// Gas used according to the header
+        expected: u64,
+        /// Cumulative gas used of the last receipt
+        got: u64,
+    },
+    /// Receipts before Byzantium contain the intermediate state root and can't be verified against
+    /// the header without re-executing the block.
+    #[error("Receipts of pre-Byzantium block #{0} can't be verified")]
+    PreByzantiumReceipts(BlockNumber),
     /// Block body wrong transaction count
     #[error("Stored block indices does not match transaction count")]
     BlockBodyTransactionCount,
@@ -94,4 +351,18 @@ pub enum ProviderError {
         /// Block hash
         block_hash: BlockHash,
     },
+    /// Failed to write or read back a block file
+    #[error("Block file error: {0}")]
+    BlockFile(String),
+    /// Failed to write a receipt export
+    #[error("Receipt export error: {0}")]
+    ReceiptExport(String),
+    /// A record of a block file can't be read or does not continue the chain.
+    #[error("Bad record at byte {offset} of the block file: {error}")]
+    CorruptBlockFile {
+        /// Byte offset of the record in the file
+        offset: u64,
+        /// Why the record was rejected
+        error: Box<ProviderError>,
+    },
 }
@@ -0,0 +1,227 @@
+use crate::test_utils::generators::sign_tx_with_key_pair;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reth_primitives::{
+    proofs::calculate_receipt_root_ref, Address, BlockBody, ChainSpec, Hardfork, Header, Receipt,
+    SealedBlock, SealedBlockWithSenders, Transaction, TransactionKind, TransactionSigned, TxLegacy,
+    TxType, Withdrawal, EMPTY_OMMER_ROOT, U256,
+};
+use secp256k1::{KeyPair, Secp256k1};
+use std::{ops::Range, sync::Arc};
+
+/// The gas used by each transfer of a [ChainFixture].
+const TRANSFER_GAS: u64 = 21_000;
+
+/// The input of the failing calls of a [ChainFixture], the call reverts.
+const FAILING_CALL_INPUT: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+
+/// The gas used by each failing call of a [ChainFixture], the intrinsic gas of its input.
+const FAILING_CALL_GAS: u64 = TRANSFER_GAS + 16 * FAILING_CALL_INPUT.len() as u64;
+
+/// The lowest gas limit of [ChainFixture] blocks, genesis blocks often have a lower one.
+const MIN_GAS_LIMIT: u64 = 30_000_000;
+
+/// Builds a deterministic chain on top of the genesis block of a [ChainSpec].
+///
+/// Blocks are 12 seconds apart and contain legacy transfers between a handful of accounts, with
+/// increasing nonces and a gas price that covers the base fee. The transactions root, receipts
+/// root, ommers hash and withdrawals root match the bodies, the state root is copied from the
+/// genesis block since the transfers are not executed. Optionally some transactions are calls
+/// that fail, see [ChainFixture::with_failed_txs], and some blocks are empty, see
+/// [ChainFixture::with_empty_blocks].
+///
+/// The fixture follows the forks of the spec: base fees start at London, the difficulty drops to
+/// zero once the parent reached the terminal total difficulty or the Paris block, and blocks get
+/// withdrawals once Shanghai is active.
+///
+/// ```ignore
+/// let blocks = ChainFixture::new(chain_spec).blocks(100).txs_per_block(0..5).build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChainFixture {
+    chain_spec: Arc<ChainSpec>,
+    blocks: u64,
+    txs_per_block: Range<usize>,
+    withdrawals_after: Option<u64>,
+    failed_txs_every: Option<u64>,
+    empty_blocks_every: Option<u64>,
+    seed: u64,
+}
+
+impl ChainFixture {
+    /// Creates a builder for 10 blocks with up to 2 transactions each.
+    pub fn new(chain_spec: Arc<ChainSpec>) -> Self {
+        Self {
+            chain_spec,
+            blocks: 10,
+            txs_per_block: 0..3,
+            withdrawals_after: None,
+            failed_txs_every: None,
+            empty_blocks_every: None,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of blocks after the genesis block.
+    pub fn blocks(mut self, blocks: u64) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    /// Sets the range of the number of transactions in each block.
+    pub fn txs_per_block(mut self, txs_per_block: Range<usize>) -> Self {
+        self.txs_per_block = txs_per_block;
+        self
+    }
+
+    /// Adds withdrawals to the blocks from the given timestamp on, instead of from the Shanghai
+    /// activation of the chain spec.
+    pub fn with_withdrawals_after(mut self, timestamp: u64) -> Self {
+        self.withdrawals_after = Some(timestamp);
+        self
+    }
+
+    /// Makes every `every`-th transaction of the chain, starting with the first one, a call that
+    /// fails instead of a transfer. Its receipt is unsuccessful, see [ChainFixture::receipts].
+    pub fn with_failed_txs(mut self, every: u64) -> Self {
+        self.failed_txs_every = Some(every);
+        self
+    }
+
+    /// Makes every block whose number is a multiple of `every` empty, regardless of the number of
+    /// transactions per block.
+    pub fn with_empty_blocks(mut self, every: u64) -> Self {
+        self.empty_blocks_every = Some(every);
+        self
+    }
+
+    /// Sets the seed of the generated transactions and withdrawals, the same seed always builds
+    /// the same chain.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builds the chain, starting with the genesis block of the chain spec.
+    ///
+    /// Panics if the range of transactions per block is empty.
+    pub fn build(&self) -> Vec<SealedBlockWithSenders> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let secp = Secp256k1::new();
+        let signers = (0..4).map(|_| KeyPair::new(&secp, &mut rng)).collect::<Vec<_>>();
+        let mut nonces = vec![0; signers.len()];
+        let mut next_withdrawal = 0;
+        let mut next_tx = 0;
+
+        let genesis = self.chain_spec.sealed_genesis_header();
+        let mut total_difficulty = genesis.difficulty;
+        let withdrawals = genesis.withdrawals_root.map(|_| Vec::new());
+        let mut blocks = vec![SealedBlockWithSenders {
+            block: SealedBlock { header: genesis, body: vec![], ommers: vec![], withdrawals },
+            senders: vec![],
+        }];
+
+        for number in 1..=self.blocks {
+            let parent = &blocks.last().expect("starts with genesis").header;
+            let timestamp = parent.timestamp + 12;
+            let base_fee_per_gas = self.chain_spec.next_block_base_fee(parent);
+
+            let mut transactions = Vec::new();
+            let mut senders = Vec::new();
+            // the count is drawn for empty blocks too, so they don't shift the rest of the chain
+            let mut tx_count = rng.gen_range(self.txs_per_block.clone());
+            if self.empty_blocks_every.map_or(false, |every| number % every == 0) {
+                tx_count = 0;
+            }
+            for _ in 0..tx_count {
+                let fails = self.failed_txs_every.map_or(false, |every| next_tx % every == 0);
+                next_tx += 1;
+                let signer = rng.gen_range(0..signers.len());
+                let transaction = Transaction::Legacy(TxLegacy {
+                    chain_id: Some(self.chain_spec.chain.id()),
+                    nonce: nonces[signer],
+                    gas_price: base_fee_per_gas.unwrap_or_default() as u128 +
+                        rng.gen_range(1..1_000_000_000),
+                    gas_limit: if fails { FAILING_CALL_GAS } else { TRANSFER_GAS },
+                    to: TransactionKind::Call(Address::random_using(&mut rng)),
+                    value: rng.gen::<u64>().into(),
+                    input: if fails { FAILING_CALL_INPUT.into() } else { Default::default() },
+                });
+                nonces[signer] += 1;
+                let transaction = sign_tx_with_key_pair(signers[signer], transaction);
+                senders.push(transaction.recover_signer().expect("signed by a valid key"));
+                transactions.push(transaction);
+            }
+            let receipts = Self::receipts(&transactions);
+
+            let withdrawals = self.has_withdrawals(timestamp).then(|| {
+                (0..rng.gen_range(0..3))
+                    .map(|_| {
+                        next_withdrawal += 1;
+                        Withdrawal {
+                            index: next_withdrawal - 1,
+                            validator_index: rng.gen_range(0..1000),
+                            address: Address::random_using(&mut rng),
+                            amount: rng.gen_range(1..32_000_000_000),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let body = BlockBody { transactions, ommers: vec![], withdrawals };
+
+            let paris = self.chain_spec.fork(Hardfork::Paris);
+            let merged = paris.active_at_block(number) ||
+                paris.active_at_ttd(total_difficulty, U256::ZERO) ||
+                self.chain_spec.final_paris_total_difficulty(number).is_some();
+            let difficulty = if merged { U256::ZERO } else { parent.difficulty.max(U256::from(1)) };
+            total_difficulty += difficulty;
+
+            let header = Header {
+                parent_hash: parent.hash(),
+                ommers_hash: EMPTY_OMMER_ROOT,
+                beneficiary: parent.beneficiary,
+                state_root: parent.state_root,
+                transactions_root: body.calculate_tx_root(),
+                receipts_root: calculate_receipt_root_ref(&receipts),
+                withdrawals_root: body.calculate_withdrawals_root(),
+                difficulty,
+                number,
+                gas_limit: parent.gas_limit.max(MIN_GAS_LIMIT),
+                gas_used: receipts.last().map_or(0, |receipt| receipt.cumulative_gas_used),
+                timestamp,
+                nonce: if merged { 0 } else { number },
+                base_fee_per_gas,
+                ..Default::default()
+            };
+            let block = SealedBlock::new(header.seal_slow(), body);
+            blocks.push(SealedBlockWithSenders { block, senders });
+        }
+        blocks
+    }
+
+    /// Returns the receipts of the transactions of a [ChainFixture] block.
+    ///
+    /// Transfers succeed, calls fail. Every transaction uses its whole gas limit.
+    pub fn receipts(transactions: &[TransactionSigned]) -> Vec<Receipt> {
+        let mut cumulative_gas_used = 0;
+        transactions
+            .iter()
+            .map(|transaction| {
+                cumulative_gas_used += transaction.gas_limit();
+                Receipt {
+                    tx_type: TxType::Legacy,
+                    success: transaction.input().is_empty(),
+                    cumulative_gas_used,
+                    logs: vec![],
+                }
+            })
+            .collect()
+    }
+
+    /// Whether blocks at the timestamp have withdrawals.
+    fn has_withdrawals(&self, timestamp: u64) -> bool {
+        match self.withdrawals_after {
+            Some(after) => timestamp >= after,
+            None => self.chain_spec.is_shanghai_activated_at_timestamp(timestamp),
+        }
+    }
+}
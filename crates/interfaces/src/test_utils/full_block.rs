@@ -5,7 +5,12 @@ use crate::p2p::{
     headers::client::{HeadersClient, HeadersRequest},
     priority::Priority,
 };
-use reth_primitives::{BlockBody, Header, PeerId, WithPeerId, H256};
+use parking_lot::Mutex;
+use reth_primitives::{
+    BlockBody, BlockHashOrNumber, Header, PeerId, SealedBlockWithSenders, SealedHeader, WithPeerId,
+    H256,
+};
+use std::{collections::HashMap, sync::Arc};
 
 /// A headers+bodies client implementation that does nothing.
 #[derive(Debug, Default, Clone)]
@@ -43,3 +48,105 @@ impl HeadersClient for NoopFullBlockClient {
         futures::future::ready(Ok(WithPeerId::new(PeerId::random(), vec![])))
     }
 }
+
+/// A headers+bodies client that serves the inserted blocks.
+#[derive(Clone, Default, Debug)]
+pub struct TestFullBlockClient {
+    headers: Arc<Mutex<HashMap<H256, Header>>>,
+    bodies: Arc<Mutex<HashMap<H256, BlockBody>>>,
+    /// Bodies that are returned instead of the inserted ones, once each.
+    wrong_bodies: Arc<Mutex<Vec<BlockBody>>>,
+    bad_messages: Arc<Mutex<usize>>,
+}
+
+impl TestFullBlockClient {
+    /// Inserts a block to serve.
+    pub fn insert(&self, header: SealedHeader, body: BlockBody) {
+        let hash = header.hash();
+        let header = header.unseal();
+        self.headers.lock().insert(hash, header);
+        self.bodies.lock().insert(hash, body);
+    }
+
+    /// Inserts the blocks of a chain, e.g. built by a
+    /// [ChainFixture](crate::test_utils::ChainFixture).
+    pub fn insert_chain(&self, blocks: &[SealedBlockWithSenders]) {
+        for block in blocks {
+            let block = block.block.clone();
+            let body = BlockBody {
+                transactions: block.body,
+                ommers: block.ommers,
+                withdrawals: block.withdrawals,
+            };
+            self.insert(block.header, body);
+        }
+    }
+
+    /// Queues a body that is returned instead of the requested one. Queued bodies are returned
+    /// once each, the last queued first.
+    pub fn push_wrong_body(&self, body: BlockBody) {
+        self.wrong_bodies.lock().push(body);
+    }
+
+    /// Returns how often a peer was reported for a bad message.
+    pub fn bad_messages(&self) -> usize {
+        *self.bad_messages.lock()
+    }
+}
+
+impl DownloadClient for TestFullBlockClient {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        *self.bad_messages.lock() += 1;
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        1
+    }
+}
+
+impl HeadersClient for TestFullBlockClient {
+    type Output = futures::future::Ready<PeerRequestResult<Vec<Header>>>;
+
+    fn get_headers_with_priority(
+        &self,
+        request: HeadersRequest,
+        _priority: Priority,
+    ) -> Self::Output {
+        let headers = self.headers.lock();
+        let mut resp = Vec::new();
+        let mut next = (request.limit > 0).then_some(request);
+        while let Some(request) = next.take() {
+            let header = match request.start {
+                BlockHashOrNumber::Hash(hash) => headers.get(&hash).cloned(),
+                BlockHashOrNumber::Number(num) => {
+                    headers.values().find(|h| h.number == num).cloned()
+                }
+            };
+            let Some(header) = header else { break };
+            next = request.next_request(&header, 1);
+            resp.push(header);
+        }
+        futures::future::ready(Ok(WithPeerId::new(PeerId::random(), resp)))
+    }
+}
+
+impl BodiesClient for TestFullBlockClient {
+    type Output = futures::future::Ready<PeerRequestResult<Vec<BlockBody>>>;
+
+    fn get_block_bodies_with_priority(
+        &self,
+        hashes: Vec<H256>,
+        _priority: Priority,
+    ) -> Self::Output {
+        let bodies = self.bodies.lock();
+        let mut all_bodies = Vec::new();
+        for hash in hashes {
+            if let Some(body) = self.wrong_bodies.lock().pop() {
+                all_bodies.push(body);
+            } else if let Some(body) = bodies.get(&hash) {
+                all_bodies.push(body.clone());
+            }
+        }
+        futures::future::ready(Ok(WithPeerId::new(PeerId::random(), all_bodies)))
+    }
+}
@@ -15,8 +15,7 @@ use crate::{
 use futures::{future, Future, FutureExt, Stream, StreamExt};
 use reth_eth_wire::BlockHeaders;
 use reth_primitives::{
-    BlockHash, BlockNumber, Head, Header, HeadersDirection, PeerId, SealedBlock, SealedHeader,
-    WithPeerId, H256, U256,
+    BlockHash, BlockNumber, Head, Header, PeerId, SealedBlock, SealedHeader, WithPeerId, H256, U256,
 };
 use reth_rpc_types::engine::ForkchoiceState;
 use std::{
@@ -119,11 +118,8 @@ struct TestDownload {
 impl TestDownload {
     fn get_or_init_fut(&mut self) -> &mut TestHeadersFut {
         if self.fut.is_none() {
-            let request = HeadersRequest {
-                limit: self.limit,
-                direction: HeadersDirection::Rising,
-                start: reth_primitives::BlockHashOrNumber::Number(0), // ignored
-            };
+            // the start is ignored
+            let request = HeadersRequest::rising(0u64.into(), self.limit);
             let client = self.client.clone();
             self.fut = Some(Box::pin(client.get_headers(request)));
         }
@@ -1,6 +1,7 @@
 #![allow(unused)]
 
 mod bodies;
+mod chain_fixture;
 mod full_block;
 mod headers;
 
@@ -8,5 +9,6 @@ mod headers;
 pub mod generators;
 
 pub use bodies::*;
+pub use chain_fixture::*;
 pub use full_block::*;
 pub use headers::*;
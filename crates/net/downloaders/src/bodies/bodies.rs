@@ -29,6 +29,13 @@ use tracing::info;
 /// The scope for headers downloader metrics.
 pub const BODIES_DOWNLOADER_SCOPE: &str = "downloaders.bodies";
 
+/// The expected size of the bodies of one request, above which peers are likely to truncate their
+/// response.
+///
+/// The size of a body is estimated with
+/// [Header::expected_body_size_hint](reth_primitives::Header::expected_body_size_hint).
+const BODIES_RESPONSE_SOFT_LIMIT: u64 = 2 * 1024 * 1024;
+
 /// Downloads bodies in batches.
 ///
 /// All blocks in a batch are fetched at the same time.
@@ -91,6 +98,7 @@ where
     ///     2. The total number of headers in the batch (both empty and non-empty) is greater than
     ///        or equal to the stream batch size.
     ///     3. Downloader reached the end of the range
+    ///     4. The expected size of the bodies would exceed the soft response limit of peers.
     ///
     /// NOTE: The batches returned have a variable length.
     fn query_headers(
@@ -107,6 +115,8 @@ where
 
         // Non empty headers count
         let mut non_empty_headers = 0;
+        // Expected size of the non empty bodies
+        let mut expected_size = 0;
         let mut current_block_num = *range.start();
 
         // Acquire cursors over canonical and header tables
@@ -134,6 +144,11 @@ where
 
             // If the header is not empty, increment the counter
             if !header.is_empty() {
+                let size_hint = header.expected_body_size_hint();
+                if non_empty_headers > 0 && expected_size + size_hint > BODIES_RESPONSE_SOFT_LIMIT {
+                    break
+                }
+                expected_size += size_hint;
                 non_empty_headers += 1;
             }
 
@@ -597,7 +612,7 @@ mod tests {
     use futures_util::stream::StreamExt;
     use reth_db::test_utils::create_test_rw_db;
     use reth_interfaces::test_utils::{generators, generators::random_block_range, TestConsensus};
-    use reth_primitives::{BlockBody, H256};
+    use reth_primitives::{BlockBody, Header, GAS_PER_BODY_BYTE, H256};
     use std::{collections::HashMap, sync::Arc};
 
     // Check that the blocks are emitted in order of block number, not in order of
@@ -660,6 +675,47 @@ mod tests {
         assert_eq!(client.times_requested(), 20);
     }
 
+    // Check that requests are split so that the expected size of the bodies stays below the soft
+    // response limit of peers.
+    #[tokio::test]
+    async fn splits_requests_by_expected_body_size() {
+        let db = create_test_rw_db();
+        let mut rng = generators::rng();
+        let mut blocks = random_block_range(&mut rng, 0..=9, H256::zero(), 1..2);
+
+        // two bodies fit into one request, three don't
+        let gas_used = BODIES_RESPONSE_SOFT_LIMIT * 2 / 5 * GAS_PER_BODY_BYTE;
+        for block in &mut blocks {
+            block.header = Header { gas_used, ..block.header.clone().unseal() }.seal_slow();
+        }
+
+        let headers = blocks.iter().map(|block| block.header.clone()).collect::<Vec<_>>();
+        let mut bodies = blocks
+            .into_iter()
+            .map(|block| {
+                (
+                    block.hash(),
+                    BlockBody { transactions: block.body, ommers: block.ommers, withdrawals: None },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        insert_headers(&db, &headers);
+
+        let client = Arc::new(TestBodiesClient::default().with_bodies(bodies.clone()));
+        let mut downloader = BodiesDownloaderBuilder::default().with_request_limit(10).build(
+            client.clone(),
+            Arc::new(TestConsensus::default()),
+            db,
+        );
+        downloader.set_download_range(0..=9).expect("failed to set download range");
+
+        let responses = downloader.collect::<Vec<_>>().await;
+        let responses = responses.into_iter().flat_map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(responses, zip_blocks(headers.iter(), &mut bodies));
+        assert_eq!(client.times_requested(), 5);
+    }
+
     // Check that bodies are returned in correct order
     // after resetting the download range multiple times.
     #[tokio::test]
@@ -18,7 +18,8 @@ use reth_interfaces::{
     },
 };
 use reth_primitives::{
-    BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId, SealedHeader, H256,
+    BlockHashOrNumber, BlockNumber, Header, HeaderHashLimit, PeerId, SealedHeader, H256,
+    MAX_HEADER_HASHES_PER_POLL,
 };
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use std::{
@@ -54,9 +55,10 @@ enum ReverseHeadersDownloaderError {
 /// tries to fill the gap between the local head of the node and the chain tip by issuing multiple
 /// requests at a time but yielding them in batches on [Stream::poll_next].
 ///
-/// **Note:** This downloader downloads in reverse, see also [HeadersDirection::Falling], this means
-/// the batches of headers that this downloader yields will start at the chain tip and move towards
-/// the local head: falling block numbers.
+/// **Note:** This downloader downloads in reverse, see also
+/// [HeadersDirection::Falling](reth_primitives::HeadersDirection::Falling), this means the batches
+/// of headers that this downloader yields will start at the chain tip and move towards the local
+/// head: falling block numbers.
 #[must_use = "Stream does nothing unless polled"]
 pub struct ReverseHeadersDownloader<H: HeadersClient> {
     /// Consensus client used to validate headers
@@ -570,7 +572,7 @@ where
 
     /// Returns the request for the `sync_target` header.
     fn get_sync_target_request(&self, start: BlockHashOrNumber) -> HeadersRequest {
-        HeadersRequest { start, limit: 1, direction: HeadersDirection::Falling }
+        HeadersRequest::falling(start, 1)
     }
 
     /// Starts a request future
@@ -747,6 +749,7 @@ where
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
+        let _hash_limit = HeaderHashLimit::new(MAX_HEADER_HASHES_PER_POLL);
 
         // The downloader boundaries (local head and sync target) have to be set in order
         // to start downloading data.
@@ -1203,7 +1206,7 @@ fn calc_next_request(
     let diff = next_request_block_number - local_head;
     let limit = diff.min(request_limit);
     let start = next_request_block_number;
-    HeadersRequest { start: start.into(), limit, direction: HeadersDirection::Falling }
+    HeadersRequest::falling(start.into(), limit)
 }
 
 #[cfg(test)]
@@ -1506,4 +1509,31 @@ mod tests {
 
         assert!(downloader.next().await.is_none());
     }
+
+    #[tokio::test]
+    async fn download_hashes_headers_once() {
+        reth_tracing::init_test_tracing();
+        let mut headers = vec![SealedHeader::default()];
+        for _ in 0..10 {
+            headers.push(child_header(headers.last().unwrap()));
+        }
+        headers.reverse();
+
+        let client = Arc::new(TestHeadersClient::default());
+        let mut downloader = ReverseHeadersDownloaderBuilder::default()
+            .stream_batch_size(3)
+            .request_limit(2)
+            .build(Arc::clone(&client), Arc::new(TestConsensus::default()));
+        downloader.update_local_head(headers.last().unwrap().clone());
+        downloader.update_sync_target(SyncTarget::Tip(headers[0].hash()));
+        client.extend(headers.iter().map(|header| header.as_ref().clone())).await;
+
+        // the limit of the downloader is lowered by the enclosing one
+        let _hash_limit = HeaderHashLimit::new(1);
+        let mut downloaded = Vec::new();
+        while let Some(batch) = downloader.next().await {
+            downloaded.extend(batch.unwrap());
+        }
+        assert_eq!(downloaded, headers[..headers.len() - 1]);
+    }
 }
@@ -6,6 +6,6 @@ use reth_primitives::SealedHeader;
 pub(crate) fn child_header(parent: &SealedHeader) -> SealedHeader {
     let mut child = parent.as_ref().clone();
     child.number += 1;
-    child.parent_hash = parent.hash_slow();
+    child.parent_hash = parent.hash();
     child.seal_slow()
 }
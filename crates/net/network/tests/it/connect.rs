@@ -17,7 +17,7 @@ use reth_network::{
     NetworkConfigBuilder, NetworkEvent, NetworkManager, PeersConfig,
 };
 use reth_network_api::{NetworkInfo, Peers, PeersInfo};
-use reth_primitives::{mainnet_nodes, HeadersDirection, NodeRecord, PeerId};
+use reth_primitives::{mainnet_nodes, NodeRecord, PeerId};
 use reth_provider::test_utils::NoopProvider;
 use reth_transaction_pool::test_utils::testing_pool;
 use secp256k1::SecretKey;
@@ -293,13 +293,7 @@ async fn test_connect_to_trusted_peer() {
 
     let fetcher = handle.fetch_client().await.unwrap();
 
-    let headers = fetcher
-        .get_headers(HeadersRequest {
-            start: 73174u64.into(),
-            limit: 10,
-            direction: HeadersDirection::Falling,
-        })
-        .await;
+    let headers = fetcher.get_headers(HeadersRequest::falling(73174u64.into(), 10)).await;
 
     dbg!(&headers);
 
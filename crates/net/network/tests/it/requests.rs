@@ -8,8 +8,8 @@ use reth_interfaces::p2p::{
 use reth_network::test_utils::{NetworkEventStream, Testnet};
 use reth_network_api::{NetworkInfo, Peers};
 use reth_primitives::{
-    Block, BlockBody, Bytes, Header, HeadersDirection, Signature, Transaction, TransactionKind,
-    TransactionSigned, TxEip2930, H256, U256,
+    Block, BlockBody, Bytes, Header, Signature, Transaction, TransactionKind, TransactionSigned,
+    TxEip2930, H256, U256,
 };
 use reth_provider::test_utils::MockEthProvider;
 use std::sync::Arc;
@@ -109,8 +109,7 @@ async fn test_get_header() {
 
         mock_provider.add_header(hash, header.clone());
 
-        let req =
-            HeadersRequest { start: hash.into(), limit: 1, direction: HeadersDirection::Falling };
+        let req = HeadersRequest::falling(hash.into(), 1);
 
         let res = fetch0.get_headers(req).await;
         assert!(res.is_ok(), "{res:?}");
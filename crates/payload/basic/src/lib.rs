@@ -687,8 +687,7 @@ fn build_payload<Pool, Client>(
             attributes.withdrawals,
         )?;
 
-        let receipts_root = post_state.receipts_root(block_number);
-        let logs_bloom = post_state.logs_bloom(block_number);
+        let (receipts_root, logs_bloom) = post_state.receipts_root_and_logs_bloom(block_number);
 
         // calculate the state root
         let state_root = state.state().state_root(post_state)?;
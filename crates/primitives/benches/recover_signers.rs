@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pprof::criterion::{Output, PProfProfiler};
+use reth_primitives::{
+    sign_message, Address, Transaction, TransactionKind, TransactionSigned, TxLegacy, H256,
+    SENDER_RECOVERY_CHUNK_SIZE,
+};
+
+/// Compares serial and parallel recovery of the signers of a block.
+pub fn recover_signers_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Signers recovery");
+
+    for size in [100, 500, 1_000] {
+        let group_name =
+            |description: &str| format!("recover signers | size: {size} | {description}");
+
+        let (txs, expected) = generate_test_data(size);
+
+        group.bench_function(group_name("serial"), |b| {
+            b.iter(|| {
+                let signers = black_box(
+                    txs.iter().map(TransactionSigned::recover_signer).collect::<Option<Vec<_>>>(),
+                );
+                assert_eq!(signers.as_ref(), Some(&expected));
+            });
+        });
+
+        group.bench_function(group_name("parallel"), |b| {
+            b.iter(|| {
+                let signers = black_box(TransactionSigned::recover_signers_parallel(
+                    &txs,
+                    SENDER_RECOVERY_CHUNK_SIZE,
+                ));
+                assert_eq!(signers.as_ref(), Some(&expected));
+            });
+        });
+    }
+}
+
+/// Returns transactions signed with random keys and their signers.
+fn generate_test_data(size: usize) -> (Vec<TransactionSigned>, Vec<Address>) {
+    let txs = (0..size as u64)
+        .map(|nonce| {
+            let transaction = Transaction::Legacy(TxLegacy {
+                chain_id: Some(1),
+                nonce,
+                gas_price: 1_000_000_000,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(Address::from_low_u64_be(nonce)),
+                value: 1,
+                input: Default::default(),
+            });
+            let secret = H256::from(rand::random::<[u8; 32]>());
+            let signature = sign_message(secret, transaction.signature_hash()).unwrap();
+            TransactionSigned::from_transaction_and_signature(transaction, signature)
+        })
+        .collect::<Vec<_>>();
+    let signers = txs.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+    (txs, signers)
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = recover_signers_benchmark
+}
+criterion_main!(benches);
@@ -1,5 +1,11 @@
 use crate::{
-    Address, BlockHash, BlockNumber, Header, SealedHeader, TransactionSigned, Withdrawal, H256, U64,
+    constants::{EMPTY_OMMER_ROOT, EMPTY_TRANSACTIONS, EMPTY_WITHDRAWALS},
+    proofs::{
+        calculate_ommers_root, calculate_transaction_root, calculate_transaction_root_parallel,
+        calculate_withdrawals_root, PARALLEL_TRANSACTIONS_ROOT_THRESHOLD,
+    },
+    Address, BlockHash, BlockNumber, Header, SealedHeader, TransactionSigned, Withdrawal, H256,
+    U64,
 };
 use fixed_hash::rustc_hex::FromHexError;
 use reth_codecs::derive_arbitrary;
@@ -134,6 +140,11 @@ impl SealedBlock {
         self.header.hash()
     }
 
+    /// Checks that the body of the block matches its header, see [validate_body_against_header].
+    pub fn ensure_well_formed(&self) -> Result<(), BlockValidationError> {
+        validate_body_parts(&self.header, &self.body, &self.ommers, self.withdrawals.as_deref())
+    }
+
     /// Splits the sealed block into underlying components
     pub fn split(self) -> (SealedHeader, Vec<TransactionSigned>, Vec<Header>) {
         (self.header, self.body, self.ommers)
@@ -224,8 +235,13 @@ impl std::ops::DerefMut for SealedBlockWithSenders {
 }
 
 /// Either a block hash _or_ a block number
+///
+/// It's parsed from and displayed as either a `0x`-prefixed hash or a block number, see
+/// [BlockHashOrNumber::from_str]. The serde representation is the hash as a string or the number
+/// as an integer, and numbers are also deserialized from strings. Formats that are not
+/// human-readable store the string representation.
 #[derive_arbitrary(rlp)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BlockHashOrNumber {
     /// A block hash
     Hash(H256),
@@ -296,28 +312,106 @@ impl Decodable for BlockHashOrNumber {
     }
 }
 
+/// Error thrown when parsing a [BlockHashOrNumber] from a string.
 #[derive(Debug, thiserror::Error)]
-#[error("Failed to parse `{input}` as integer: {pares_int_error} or as hex: {hex_error}")]
-pub struct ParseBlockHashOrNumberError {
-    input: String,
-    pares_int_error: ParseIntError,
-    hex_error: FromHexError,
+pub enum ParseBlockHashOrNumberError {
+    /// The input is a `0x`-prefixed string of 64 hex characters, but not a valid hash.
+    #[error("Failed to parse `{input}` as block hash: {error}")]
+    Hash {
+        /// The parsed string
+        input: String,
+        /// The hex error
+        error: FromHexError,
+    },
+    /// The input is neither a hash nor a decimal or `0x`-prefixed hex block number.
+    #[error("Failed to parse `{input}` as block number: {error}")]
+    Number {
+        /// The parsed string
+        input: String,
+        /// The integer error
+        error: ParseIntError,
+    },
 }
 
 impl FromStr for BlockHashOrNumber {
     type Err = ParseBlockHashOrNumberError;
 
+    /// Parses a block hash or number.
+    ///
+    /// A `0x`-prefixed string of 66 characters is parsed as a hash. Anything else is parsed as a
+    /// block number, which is hex if it's `0x`-prefixed and decimal otherwise.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match u64::from_str(s) {
-            Ok(val) => Ok(val.into()),
-            Err(pares_int_error) => match H256::from_str(s) {
-                Ok(val) => Ok(val.into()),
-                Err(hex_error) => Err(ParseBlockHashOrNumberError {
-                    input: s.to_string(),
-                    pares_int_error,
-                    hex_error,
-                }),
-            },
+        let number = match s.strip_prefix("0x") {
+            Some(hex) if s.len() == 66 => {
+                return H256::from_str(hex).map(Self::Hash).map_err(|error| {
+                    ParseBlockHashOrNumberError::Hash { input: s.to_string(), error }
+                })
+            }
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => u64::from_str(s),
+        };
+        number
+            .map(Self::Number)
+            .map_err(|error| ParseBlockHashOrNumberError::Number { input: s.to_string(), error })
+    }
+}
+
+impl fmt::Display for BlockHashOrNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hash(hash) => write!(f, "{hash:?}"),
+            Self::Number(number) => write!(f, "{number}"),
+        }
+    }
+}
+
+impl Serialize for BlockHashOrNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            // formats that are not self-describing can't tell a number from a string
+            _ if !serializer.is_human_readable() => serializer.collect_str(self),
+            Self::Hash(hash) => hash.serialize(serializer),
+            Self::Number(number) => serializer.serialize_u64(*number),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockHashOrNumber {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BlockHashOrNumberVisitor;
+
+        impl<'de> Visitor<'de> for BlockHashOrNumberVisitor {
+            type Value = BlockHashOrNumber;
+
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a block number or a 0x-prefixed block hash")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(BlockHashOrNumber::Number(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BlockHashOrNumberVisitor)
+        } else {
+            deserializer.deserialize_str(BlockHashOrNumberVisitor)
         }
     }
 }
@@ -756,6 +850,130 @@ impl BlockBody {
             withdrawals: self.withdrawals.clone(),
         }
     }
+
+    /// Calculates the transactions root of the body.
+    ///
+    /// The transactions are encoded in parallel if there are at least
+    /// [PARALLEL_TRANSACTIONS_ROOT_THRESHOLD] of them.
+    pub fn calculate_tx_root(&self) -> H256 {
+        transactions_root(&self.transactions)
+    }
+
+    /// Calculates the ommers hash of the body.
+    pub fn calculate_ommers_hash(&self) -> H256 {
+        calculate_ommers_root(&self.ommers)
+    }
+
+    /// Calculates the withdrawals root of the body, `None` if the body has no withdrawals, which
+    /// is the case before Shanghai.
+    pub fn calculate_withdrawals_root(&self) -> Option<H256> {
+        self.withdrawals.as_deref().map(calculate_withdrawals_root)
+    }
+}
+
+/// Calculates the transactions root, encoding the transactions in parallel for large blocks.
+fn transactions_root(transactions: &[TransactionSigned]) -> H256 {
+    if transactions.is_empty() {
+        EMPTY_TRANSACTIONS
+    } else if transactions.len() >= PARALLEL_TRANSACTIONS_ROOT_THRESHOLD {
+        calculate_transaction_root_parallel(transactions)
+    } else {
+        calculate_transaction_root(transactions)
+    }
+}
+
+/// A block body that does not match its header, returned by [validate_body_against_header].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BlockValidationError {
+    /// The transactions root of the body differs from the header.
+    #[error("Transactions root of the body ({got:?}) is different from the header ({expected:?})")]
+    TransactionsRootMismatch {
+        /// Root computed from the body
+        got: H256,
+        /// Root in the header
+        expected: H256,
+    },
+    /// The ommers hash of the body differs from the header.
+    #[error("Ommers hash of the body ({got:?}) is different from the header ({expected:?})")]
+    OmmersHashMismatch {
+        /// Hash computed from the body
+        got: H256,
+        /// Hash in the header
+        expected: H256,
+    },
+    /// The body has ommers, but the header has zero difficulty, i.e. is past the merge.
+    #[error("Body has {count} ommers after the merge")]
+    OmmersAfterMerge {
+        /// Number of ommers in the body
+        count: usize,
+    },
+    /// The withdrawals root of the body differs from the header. Either root is `None` if the
+    /// body has no withdrawals or the header no withdrawals root, which happens before Shanghai.
+    #[error("Withdrawals root of the body ({got:?}) is different from the header ({expected:?})")]
+    WithdrawalsRootMismatch {
+        /// Root computed from the body
+        got: Option<H256>,
+        /// Root in the header
+        expected: Option<H256>,
+    },
+}
+
+/// Checks that the body matches the header.
+///
+/// The transactions root and the ommers hash have to match, headers with zero difficulty, which
+/// are past the merge, must not have ommers, and the body has withdrawals if and only if the
+/// header has a withdrawals root, which has to match. Empty bodies are compared against the
+/// constant roots without hashing.
+///
+/// Whether Shanghai is active for the header is not checked, which needs the chain spec.
+pub fn validate_body_against_header(
+    body: &BlockBody,
+    header: &Header,
+) -> Result<(), BlockValidationError> {
+    validate_body_parts(header, &body.transactions, &body.ommers, body.withdrawals.as_deref())
+}
+
+/// See [validate_body_against_header].
+fn validate_body_parts(
+    header: &Header,
+    transactions: &[TransactionSigned],
+    ommers: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+) -> Result<(), BlockValidationError> {
+    let transactions_root = transactions_root(transactions);
+    if transactions_root != header.transactions_root {
+        return Err(BlockValidationError::TransactionsRootMismatch {
+            got: transactions_root,
+            expected: header.transactions_root,
+        })
+    }
+
+    let ommers_hash =
+        if ommers.is_empty() { EMPTY_OMMER_ROOT } else { calculate_ommers_root(ommers) };
+    if ommers_hash != header.ommers_hash {
+        return Err(BlockValidationError::OmmersHashMismatch {
+            got: ommers_hash,
+            expected: header.ommers_hash,
+        })
+    }
+    if !ommers.is_empty() && header.difficulty.is_zero() {
+        return Err(BlockValidationError::OmmersAfterMerge { count: ommers.len() })
+    }
+
+    let withdrawals_root = withdrawals.map(|withdrawals| {
+        if withdrawals.is_empty() {
+            EMPTY_WITHDRAWALS
+        } else {
+            calculate_withdrawals_root(withdrawals)
+        }
+    });
+    if withdrawals_root != header.withdrawals_root {
+        return Err(BlockValidationError::WithdrawalsRootMismatch {
+            got: withdrawals_root,
+            expected: header.withdrawals_root,
+        })
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -914,4 +1132,174 @@ mod test {
         let err = serde_json::from_str::<BlockNumberOrTag>(s).unwrap_err();
         assert_eq!(err.to_string(), HexStringMissingPrefixError::default().to_string());
     }
+
+    #[test]
+    fn validate_body_against_header_mismatches() {
+        let transaction = TransactionSigned::default();
+        let ommer = Header { number: 1, ..Default::default() };
+        let withdrawal = Withdrawal { index: 1, amount: 2, ..Default::default() };
+        let root = |withdrawals: &[Withdrawal]| Some(calculate_withdrawals_root(withdrawals));
+
+        type Case = (&'static str, Header, BlockBody, Result<(), BlockValidationError>);
+        let cases: Vec<Case> = vec![
+            ("empty", Header::default(), BlockBody::default(), Ok(())),
+            (
+                "transactions",
+                Header {
+                    transactions_root: calculate_transaction_root(&[transaction.clone()]),
+                    ..Default::default()
+                },
+                BlockBody { transactions: vec![transaction.clone()], ..Default::default() },
+                Ok(()),
+            ),
+            (
+                "missing transactions",
+                Header {
+                    transactions_root: calculate_transaction_root(&[transaction.clone()]),
+                    ..Default::default()
+                },
+                BlockBody::default(),
+                Err(BlockValidationError::TransactionsRootMismatch {
+                    got: EMPTY_TRANSACTIONS,
+                    expected: calculate_transaction_root(&[transaction.clone()]),
+                }),
+            ),
+            (
+                "unexpected transactions",
+                Header::default(),
+                BlockBody { transactions: vec![transaction.clone()], ..Default::default() },
+                Err(BlockValidationError::TransactionsRootMismatch {
+                    got: calculate_transaction_root(&[transaction]),
+                    expected: EMPTY_TRANSACTIONS,
+                }),
+            ),
+            (
+                "ommers before the merge",
+                Header {
+                    ommers_hash: calculate_ommers_root(&[ommer.clone()]),
+                    difficulty: U256::from(1),
+                    ..Default::default()
+                },
+                BlockBody { ommers: vec![ommer.clone()], ..Default::default() },
+                Ok(()),
+            ),
+            (
+                "ommers hash",
+                Header { difficulty: U256::from(1), ..Default::default() },
+                BlockBody { ommers: vec![ommer.clone()], ..Default::default() },
+                Err(BlockValidationError::OmmersHashMismatch {
+                    got: calculate_ommers_root(&[ommer.clone()]),
+                    expected: EMPTY_OMMER_ROOT,
+                }),
+            ),
+            (
+                "ommers after the merge",
+                Header {
+                    ommers_hash: calculate_ommers_root(&[ommer.clone()]),
+                    ..Default::default()
+                },
+                BlockBody { ommers: vec![ommer], ..Default::default() },
+                Err(BlockValidationError::OmmersAfterMerge { count: 1 }),
+            ),
+            (
+                "withdrawals",
+                Header { withdrawals_root: root(&[withdrawal.clone()]), ..Default::default() },
+                BlockBody { withdrawals: Some(vec![withdrawal.clone()]), ..Default::default() },
+                Ok(()),
+            ),
+            (
+                "empty withdrawals",
+                Header { withdrawals_root: Some(EMPTY_WITHDRAWALS), ..Default::default() },
+                BlockBody { withdrawals: Some(Vec::new()), ..Default::default() },
+                Ok(()),
+            ),
+            (
+                "withdrawals root",
+                Header { withdrawals_root: Some(EMPTY_WITHDRAWALS), ..Default::default() },
+                BlockBody { withdrawals: Some(vec![withdrawal.clone()]), ..Default::default() },
+                Err(BlockValidationError::WithdrawalsRootMismatch {
+                    got: root(&[withdrawal.clone()]),
+                    expected: Some(EMPTY_WITHDRAWALS),
+                }),
+            ),
+            (
+                "missing withdrawals",
+                Header { withdrawals_root: root(&[withdrawal.clone()]), ..Default::default() },
+                BlockBody::default(),
+                Err(BlockValidationError::WithdrawalsRootMismatch {
+                    got: None,
+                    expected: root(&[withdrawal]),
+                }),
+            ),
+            (
+                "withdrawals before Shanghai",
+                Header::default(),
+                BlockBody { withdrawals: Some(Vec::new()), ..Default::default() },
+                Err(BlockValidationError::WithdrawalsRootMismatch {
+                    got: Some(EMPTY_WITHDRAWALS),
+                    expected: None,
+                }),
+            ),
+        ];
+
+        for (name, header, body, expected) in cases {
+            assert_eq!(validate_body_against_header(&body, &header), expected, "{name}");
+            let block = SealedBlock::new(header.seal_slow(), body);
+            assert_eq!(block.ensure_well_formed(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn block_hash_or_number_from_str() {
+        let hash = H256::from_low_u64_be(0xdead);
+        let cases = [
+            ("0", Some(BlockHashOrNumber::Number(0))),
+            ("100", Some(BlockHashOrNumber::Number(100))),
+            ("0x100", Some(BlockHashOrNumber::Number(0x100))),
+            ("0xffffffffffffffff", Some(BlockHashOrNumber::Number(u64::MAX))),
+            (
+                "0x000000000000000000000000000000000000000000000000000000000000dead",
+                Some(BlockHashOrNumber::Hash(hash)),
+            ),
+            // 64 hex characters without the prefix are not a hash
+            ("000000000000000000000000000000000000000000000000000000000000dead", None),
+            ("0x00000000000000000000000000000000000000000000000000000000000000zz", None),
+            ("0x0000000000000000000000000000000000000000000000000000000000dead", None),
+            ("0x", None),
+            ("-1", None),
+            ("latest", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<BlockHashOrNumber>().ok(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn block_hash_or_number_serde() {
+        let hash = H256::from_low_u64_be(0xdead);
+        let json = format!("\"{hash:?}\"");
+        assert_eq!(serde_json::to_string(&BlockHashOrNumber::Hash(hash)).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<BlockHashOrNumber>(&json).unwrap(),
+            BlockHashOrNumber::Hash(hash)
+        );
+
+        assert_eq!(serde_json::to_string(&BlockHashOrNumber::Number(100)).unwrap(), "100");
+        for json in ["100", "\"100\"", "\"0x64\""] {
+            assert_eq!(
+                serde_json::from_str::<BlockHashOrNumber>(json).unwrap(),
+                BlockHashOrNumber::Number(100)
+            );
+        }
+        assert!(serde_json::from_str::<BlockHashOrNumber>("-1").is_err());
+    }
+
+    #[test]
+    fn block_hash_or_number_roundtrip() {
+        proptest::proptest!(|(id: BlockHashOrNumber)| {
+            assert_eq!(id.to_string().parse::<BlockHashOrNumber>().unwrap(), id);
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(serde_json::from_str::<BlockHashOrNumber>(&json).unwrap(), id);
+        });
+    }
 }
@@ -210,6 +210,17 @@ impl ChainSpec {
         SealedHeader { header: self.genesis_header(), hash: self.genesis_hash() }
     }
 
+    /// Get the [Head] of the genesis block.
+    pub fn genesis_head(&self) -> Head {
+        Head {
+            number: 0,
+            hash: self.genesis_hash(),
+            timestamp: self.genesis.timestamp,
+            difficulty: self.genesis.difficulty,
+            total_difficulty: self.genesis.difficulty,
+        }
+    }
+
     /// Get the initial base fee of the genesis block.
     pub fn initial_base_fee(&self) -> Option<u64> {
         // If London is activated at genesis, we set the initial base fee as per EIP-1559.
@@ -254,6 +265,28 @@ impl ChainSpec {
         self.hardforks.iter().map(|(f, b)| (*f, *b))
     }
 
+    /// Convenience method to check if a fork is active at a given block number.
+    #[inline]
+    pub fn is_fork_active_at_block(&self, fork: Hardfork, block_number: BlockNumber) -> bool {
+        self.fork(fork).active_at_block(block_number)
+    }
+
+    /// Returns the base fee of the block following the given parent header.
+    ///
+    /// The London activation block starts at [EIP1559_INITIAL_BASE_FEE], later blocks adjust the
+    /// parent's base fee according to EIP-1559. Returns `None` if London is not active at the next
+    /// block.
+    pub fn next_block_base_fee(&self, parent: &Header) -> Option<u64> {
+        let london = self.fork(Hardfork::London);
+        if !london.active_at_block(parent.number + 1) {
+            None
+        } else if london.active_at_block(parent.number) {
+            parent.next_block_base_fee()
+        } else {
+            Some(EIP1559_INITIAL_BASE_FEE)
+        }
+    }
+
     /// Convenience method to check if a fork is active at a given timestamp.
     #[inline]
     pub fn is_fork_active_at_timestamp(&self, fork: Hardfork, timestamp: u64) -> bool {
@@ -271,6 +304,12 @@ impl ChainSpec {
 
     /// Creates a [`ForkFilter`](crate::ForkFilter) for the block described by [Head].
     pub fn fork_filter(&self, head: Head) -> ForkFilter {
+        self.fork_filter_with_genesis(head, self.genesis_hash())
+    }
+
+    /// Creates a [`ForkFilter`](crate::ForkFilter) for the block described by [Head] on top of the
+    /// given genesis hash instead of the genesis hash of the chain spec.
+    pub fn fork_filter_with_genesis(&self, head: Head, genesis_hash: H256) -> ForkFilter {
         let forks = self.forks_iter().filter_map(|(_, condition)| {
             // We filter out TTD-based forks w/o a pre-known block since those do not show up in the
             // fork filter.
@@ -282,7 +321,7 @@ impl ChainSpec {
             })
         });
 
-        ForkFilter::new(head, self.genesis_hash(), forks)
+        ForkFilter::new(head, genesis_hash, forks)
     }
 
     /// Compute the [`ForkId`] for the given [`Head`]
@@ -867,8 +906,8 @@ where
 mod tests {
     use crate::{
         Address, AllGenesisFormats, Chain, ChainSpec, ChainSpecBuilder, DisplayHardforks,
-        ForkCondition, ForkHash, ForkId, Genesis, Hardfork, Head, GOERLI, H256, MAINNET, SEPOLIA,
-        U256,
+        ForkCondition, ForkHash, ForkId, Genesis, Hardfork, Head, Header, GOERLI, H256, MAINNET,
+        SEPOLIA, U256,
     };
     use bytes::BytesMut;
     use ethers_core::types as EtherType;
@@ -1558,4 +1597,27 @@ Post-merge hard forks (timestamp based):
             .unwrap();
         assert_eq!(acc.balance, U256::from(1));
     }
+
+    #[test]
+    fn next_block_base_fee_across_london() {
+        let spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::London, ForkCondition::Block(2))
+            .build();
+
+        let parent = |number, base_fee_per_gas| Header {
+            number,
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            base_fee_per_gas,
+            ..Default::default()
+        };
+        assert_eq!(spec.next_block_base_fee(&parent(0, None)), None);
+        // the London activation block starts at the initial base fee
+        assert_eq!(spec.next_block_base_fee(&parent(1, None)), Some(1_000_000_000));
+        // a full parent block raises the base fee by 12.5%
+        assert_eq!(spec.next_block_base_fee(&parent(2, Some(1_000_000_000))), Some(1_125_000_000));
+    }
 }
@@ -9,7 +9,21 @@ use bytes::{Buf, BufMut, BytesMut};
 use reth_codecs::{add_arbitrary_tests, derive_arbitrary, main_codec, Compact};
 use reth_rlp::{length_of_length, Decodable, Encodable, EMPTY_STRING_CODE};
 use serde::{Deserialize, Serialize};
-use std::ops::{Deref, DerefMut};
+#[cfg(debug_assertions)]
+use std::{cell::RefCell, collections::HashMap};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Gas used per byte of a block body, used by [Header::expected_body_size_hint].
+///
+/// This is the calldata cost of a non-zero byte, so bodies of calldata heavy blocks aren't
+/// underestimated. Blocks spending their gas on execution are overestimated instead.
+pub const GAS_PER_BODY_BYTE: u64 = 16;
+
+/// Size of an RLP encoded block body without transactions, ommers and withdrawals.
+const EMPTY_BODY_SIZE: u64 = 3;
 
 /// Describes the current head block.
 ///
@@ -33,6 +47,114 @@ pub struct Head {
     pub timestamp: u64,
 }
 
+impl Head {
+    /// Returns a [HeadBuilder] for the head block with the given number.
+    ///
+    /// The hash of the head block is required, see [HeadBuilder::hash].
+    pub fn builder(number: BlockNumber) -> HeadBuilder<MissingHash> {
+        HeadBuilder {
+            number,
+            hash: MissingHash,
+            difficulty: U256::ZERO,
+            total_difficulty: U256::ZERO,
+            timestamp: 0,
+        }
+    }
+
+    /// Returns the [Head] of the sealed header with the given total difficulty.
+    pub fn from_sealed_header(header: &SealedHeader, total_difficulty: U256) -> Self {
+        Self {
+            number: header.number,
+            hash: header.hash,
+            difficulty: header.difficulty,
+            total_difficulty,
+            timestamp: header.timestamp,
+        }
+    }
+}
+
+/// Marker of a [HeadBuilder] that has no hash yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingHash;
+
+/// Builder of a [Head], returned by [Head::builder].
+///
+/// The difficulty, total difficulty and timestamp default to zero. A head can only be built once
+/// its hash is set, a zero hash has to be requested with [HeadBuilder::allow_unset_hash]:
+///
+/// ```
+/// # use reth_primitives::{Head, H256};
+/// let head = Head::builder(1).hash(H256::repeat_byte(1)).timestamp(12).build();
+/// assert_eq!((head.number, head.timestamp), (1, 12));
+///
+/// let head = Head::builder(1).allow_unset_hash().build();
+/// assert!(head.hash.is_zero());
+/// ```
+///
+/// ```compile_fail
+/// # use reth_primitives::Head;
+/// let head = Head::builder(1).timestamp(12).build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadBuilder<H> {
+    number: BlockNumber,
+    hash: H,
+    difficulty: U256,
+    total_difficulty: U256,
+    timestamp: u64,
+}
+
+impl HeadBuilder<MissingHash> {
+    /// Sets the hash of the head block.
+    ///
+    /// # Panics
+    ///
+    /// If the hash is zero, use [HeadBuilder::allow_unset_hash] if the hash is not needed.
+    pub fn hash(self, hash: H256) -> HeadBuilder<H256> {
+        assert!(!hash.is_zero(), "zero hash of head block #{}", self.number);
+        self.with_hash(hash)
+    }
+
+    /// Leaves the hash of the head block zero, for heads that are only used to determine the
+    /// active forks.
+    pub fn allow_unset_hash(self) -> HeadBuilder<H256> {
+        self.with_hash(H256::zero())
+    }
+
+    fn with_hash(self, hash: H256) -> HeadBuilder<H256> {
+        let Self { number, hash: MissingHash, difficulty, total_difficulty, timestamp } = self;
+        HeadBuilder { number, hash, difficulty, total_difficulty, timestamp }
+    }
+}
+
+impl<H> HeadBuilder<H> {
+    /// Sets the difficulty of the head block.
+    pub fn difficulty(mut self, difficulty: U256) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Sets the total difficulty at the head block.
+    pub fn total_difficulty(mut self, total_difficulty: U256) -> Self {
+        self.total_difficulty = total_difficulty;
+        self
+    }
+
+    /// Sets the timestamp of the head block.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+}
+
+impl HeadBuilder<H256> {
+    /// Returns the [Head].
+    pub fn build(self) -> Head {
+        let Self { number, hash, difficulty, total_difficulty, timestamp } = self;
+        Head { number, hash, difficulty, total_difficulty, timestamp }
+    }
+}
+
 /// Block header
 #[main_codec]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -128,7 +250,9 @@ impl Header {
     pub fn hash_slow(&self) -> H256 {
         let mut out = BytesMut::new();
         self.encode(&mut out);
-        keccak256(&out)
+        let hash = keccak256(&out);
+        HeaderHashLimit::record(hash);
+        hash
     }
 
     /// Checks if the header is empty - has no transactions and no ommers
@@ -141,6 +265,16 @@ impl Header {
         }
     }
 
+    /// Returns a rough estimate of the size of the RLP encoded block body in bytes, based on the
+    /// gas used by the block.
+    ///
+    /// This assumes [GAS_PER_BODY_BYTE] gas per byte, which bounds the size of most bodies from
+    /// above. It's meant for sizing bodies requests, so that the responses stay within the soft
+    /// limits of peers.
+    pub fn expected_body_size_hint(&self) -> u64 {
+        EMPTY_BODY_SIZE + self.gas_used / GAS_PER_BODY_BYTE
+    }
+
     /// Check if the ommers hash equals to empty hash list.
     pub fn ommers_hash_is_empty(&self) -> bool {
         self.ommers_hash == EMPTY_LIST_HASH
@@ -160,12 +294,19 @@ impl Header {
 
     /// Seal the header with a known hash.
     ///
+    /// This should be preferred over [`Header::seal_slow`] whenever the hash is already known,
+    /// e.g. because the header was looked up by its hash or was split off a [`SealedHeader`] with
+    /// [`SealedHeader::split`].
+    ///
     /// WARNING: This method does not perform validation whether the hash is correct.
     pub fn seal(self, hash: H256) -> SealedHeader {
         SealedHeader { header: self, hash }
     }
 
     /// Calculate hash and seal the Header so that it can't be changed.
+    ///
+    /// The hash is computed from the RLP encoding of the header, use [`Header::seal`] if it's
+    /// already known.
     pub fn seal_slow(self) -> SealedHeader {
         let hash = self.hash_slow();
         self.seal(hash)
@@ -309,6 +450,9 @@ impl SealedHeader {
     }
 
     /// This is the inverse of [Header::seal_slow] which returns the raw header and hash.
+    ///
+    /// The header can be sealed again with [Header::seal] without rehashing it, as long as it
+    /// wasn't modified.
     pub fn split(self) -> (Header, BlockHash) {
         (self.header, self.hash)
     }
@@ -366,6 +510,7 @@ impl Decodable for SealedHeader {
         // hash the consumed bytes, the rlp encoded header
         let consumed = started_len - b.len();
         let hash = keccak256(&buf[..consumed]);
+        HeaderHashLimit::record(hash);
 
         // update original buffer
         *buf = *b;
@@ -394,6 +539,85 @@ impl DerefMut for SealedHeader {
     }
 }
 
+/// The number of times the hash of the same header may be computed within one poll of a future
+/// that is guarded by a [HeaderHashLimit].
+///
+/// A header is hashed once when it's received, the limit leaves room for requests that are retried
+/// within the same poll.
+pub const MAX_HEADER_HASHES_PER_POLL: usize = 4;
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// The limit and the number of times each header hash was computed on this thread, if a
+    /// [HeaderHashLimit] is active.
+    static HEADER_HASH_COUNTS: RefCell<Option<(usize, HashMap<H256, usize>)>> =
+        RefCell::new(None);
+}
+
+/// Guard that panics in debug builds if the hash of the same header is computed more than a
+/// given number of times on the current thread while it's alive.
+///
+/// This is used to catch hot paths that discard known header hashes and recompute them with
+/// [Header::hash_slow] or [Header::seal_slow], e.g. within one poll of a downloader. Hashes that
+/// are computed on other threads are not counted. In release builds the guard does nothing.
+#[derive(Debug)]
+#[must_use = "the limit is only active while the guard is alive"]
+pub struct HeaderHashLimit {
+    /// The counts of the enclosing guard, restored when this guard is dropped.
+    #[cfg(debug_assertions)]
+    previous: Option<(usize, HashMap<H256, usize>)>,
+}
+
+impl HeaderHashLimit {
+    /// Limits the number of times the hash of the same header may be computed on the current
+    /// thread to `max`, until the returned guard is dropped.
+    ///
+    /// If a limit is already active, the stricter one applies and hashes are counted anew while
+    /// the returned guard is alive.
+    pub fn new(max: usize) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            let previous = HEADER_HASH_COUNTS.with(|counts| {
+                let mut counts = counts.borrow_mut();
+                let max = counts.as_ref().map_or(max, |(outer, _)| max.min(*outer));
+                counts.replace((max, HashMap::new()))
+            });
+            Self { previous }
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = max;
+            Self {}
+        }
+    }
+
+    /// Counts a computed header hash if a limit is active.
+    ///
+    /// # Panics
+    ///
+    /// If the hash was computed more often than the active limit allows.
+    #[inline]
+    fn record(hash: H256) {
+        #[cfg(debug_assertions)]
+        HEADER_HASH_COUNTS.with(|counts| {
+            if let Some((max, counts)) = counts.borrow_mut().as_mut() {
+                let count = counts.entry(hash).or_default();
+                *count += 1;
+                assert!(*count <= *max, "header {hash:?} was hashed {count} times, limit is {max}");
+            }
+        });
+        #[cfg(not(debug_assertions))]
+        let _ = hash;
+    }
+}
+
+impl Drop for HeaderHashLimit {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        HEADER_HASH_COUNTS.with(|counts| *counts.borrow_mut() = self.previous.take());
+    }
+}
+
 /// Represents the direction for a headers request depending on the `reverse` field of the request.
 /// > The response must contain a number of block headers, of rising number when reverse is 0,
 /// > falling when 1
@@ -440,6 +664,15 @@ impl HeadersDirection {
     }
 }
 
+impl fmt::Display for HeadersDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadersDirection::Falling => f.write_str("falling"),
+            HeadersDirection::Rising => f.write_str("rising"),
+        }
+    }
+}
+
 impl Encodable for HeadersDirection {
     fn encode(&self, out: &mut dyn BufMut) {
         bool::from(*self).encode(out)
@@ -513,8 +746,14 @@ mod ethers_compat {
 
 #[cfg(test)]
 mod tests {
-    use super::{Bytes, Decodable, Encodable, Header, H256};
-    use crate::{Address, HeadersDirection, U256};
+    use super::{
+        Bytes, Decodable, Encodable, Head, Header, HeaderHashLimit, SealedHeader, EMPTY_BODY_SIZE,
+        GAS_PER_BODY_BYTE, H256,
+    };
+    use crate::{
+        proofs::{EMPTY_LIST_HASH, EMPTY_ROOT},
+        Address, BlockBody, HeadersDirection, U256,
+    };
     use ethers_core::utils::hex::{self, FromHex};
     use std::str::FromStr;
 
@@ -663,4 +902,108 @@ mod tests {
         direction.encode(&mut buf);
         assert_eq!(direction, HeadersDirection::decode(&mut buf.as_slice()).unwrap());
     }
+
+    #[test]
+    fn headers_direction_display() {
+        assert_eq!(HeadersDirection::Falling.to_string(), "falling");
+        assert_eq!(HeadersDirection::Rising.to_string(), "rising");
+    }
+
+    #[test]
+    fn header_hash_limit() {
+        let header = Header { number: 1, ..Default::default() };
+        let _limit = HeaderHashLimit::new(2);
+        let sealed = header.clone().seal_slow();
+
+        // resealing with the known hash doesn't count
+        let (header, hash) = sealed.split();
+        assert_eq!(header.clone().seal(hash).hash(), hash);
+
+        let mut encoded = Vec::new();
+        header.encode(&mut encoded);
+        assert_eq!(SealedHeader::decode(&mut encoded.as_slice()).unwrap().hash(), hash);
+
+        // an inner limit doesn't see the counts of the outer one
+        {
+            let _inner = HeaderHashLimit::new(3);
+            assert_eq!(header.hash_slow(), hash);
+            assert_eq!(header.hash_slow(), hash);
+        }
+        assert_eq!(Header::default().hash_slow(), Header::default().hash_slow());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "times, limit is 2")]
+    fn header_hash_limit_exceeded() {
+        let header = Header { number: 1, ..Default::default() };
+        let _limit = HeaderHashLimit::new(2);
+        for _ in 0..3 {
+            header.hash_slow();
+        }
+    }
+
+    #[test]
+    fn empty_body_roots() {
+        assert_eq!(
+            EMPTY_ROOT,
+            H256::from_str("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        );
+        assert_eq!(
+            EMPTY_LIST_HASH,
+            H256::from_str("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+                .unwrap()
+        );
+
+        let mut encoded = Vec::new();
+        BlockBody::default().encode(&mut encoded);
+        assert_eq!(encoded.len() as u64, EMPTY_BODY_SIZE);
+    }
+
+    #[test]
+    fn expected_body_size_hint() {
+        assert_eq!(Header::default().expected_body_size_hint(), EMPTY_BODY_SIZE);
+        let header = Header { gas_used: 30_000_000, ..Default::default() };
+        assert_eq!(
+            header.expected_body_size_hint(),
+            EMPTY_BODY_SIZE + 30_000_000 / GAS_PER_BODY_BYTE
+        );
+    }
+
+    #[test]
+    fn head_builder() {
+        let hash = H256::repeat_byte(0xab);
+        let head = Head::builder(7)
+            .difficulty(U256::from(2))
+            .hash(hash)
+            .total_difficulty(U256::from(9))
+            .timestamp(1_000)
+            .build();
+        assert_eq!(
+            head,
+            Head {
+                number: 7,
+                hash,
+                difficulty: U256::from(2),
+                total_difficulty: U256::from(9),
+                timestamp: 1_000
+            }
+        );
+        assert_eq!(
+            Head::builder(7).allow_unset_hash().build(),
+            Head { number: 7, ..Default::default() }
+        );
+
+        let header =
+            Header { number: 7, difficulty: U256::from(2), timestamp: 1_000, ..Default::default() };
+        let sealed = header.seal(hash);
+        assert_eq!(Head::from_sealed_header(&sealed, U256::from(9)), head);
+    }
+
+    #[test]
+    #[should_panic(expected = "zero hash of head block #7")]
+    fn head_builder_rejects_zero_hash() {
+        let _ = Head::builder(7).hash(H256::zero());
+    }
 }
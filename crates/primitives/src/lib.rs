@@ -43,6 +43,8 @@ mod prune;
 mod receipt;
 pub mod stage;
 mod storage;
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod strategies;
 mod transaction;
 pub mod trie;
 mod withdrawal;
@@ -53,8 +55,9 @@ pub mod proofs;
 pub use account::{Account, Bytecode};
 pub use bits::H512;
 pub use block::{
-    Block, BlockBody, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumberOrTag, BlockWithSenders,
-    ForkBlock, SealedBlock, SealedBlockWithSenders,
+    validate_body_against_header, Block, BlockBody, BlockHashOrNumber, BlockId, BlockNumHash,
+    BlockNumberOrTag, BlockValidationError, BlockWithSenders, ForkBlock, SealedBlock,
+    SealedBlockWithSenders,
 };
 pub use bloom::Bloom;
 pub use chain::{
@@ -68,16 +71,19 @@ pub use constants::{
 pub use forkid::{ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
 pub use genesis::{Genesis, GenesisAccount};
 pub use hardfork::Hardfork;
-pub use header::{Head, Header, HeadersDirection, SealedHeader};
+pub use header::{
+    Head, HeadBuilder, Header, HeaderHashLimit, HeadersDirection, MissingHash, SealedHeader,
+    GAS_PER_BODY_BYTE, MAX_HEADER_HASHES_PER_POLL,
+};
 pub use hex_bytes::Bytes;
 pub use integer_list::IntegerList;
-pub use log::Log;
+pub use log::{Log, LogAddressAndTopicsFilter, LogMeta};
 pub use net::{
     goerli_nodes, mainnet_nodes, sepolia_nodes, NodeRecord, GOERLI_BOOTNODES, MAINNET_BOOTNODES,
     SEPOLIA_BOOTNODES,
 };
 pub use peer::{PeerId, WithPeerId};
-pub use prune::{PruneCheckpoint, PruneMode};
+pub use prune::{PruneCheckpoint, PruneMode, PrunePart};
 pub use receipt::{Receipt, ReceiptWithBloom, ReceiptWithBloomRef};
 pub use revm_primitives::JumpMap;
 pub use serde_helper::JsonU256;
@@ -88,9 +94,12 @@ pub use transaction::{
     IntoRecoveredTransaction, InvalidTransactionError, Signature, Transaction, TransactionKind,
     TransactionMeta, TransactionSigned, TransactionSignedEcRecovered, TransactionSignedNoHash,
     TxEip1559, TxEip2930, TxLegacy, TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID,
-    LEGACY_TX_TYPE_ID,
+    LEGACY_TX_TYPE_ID, SENDER_RECOVERY_CHUNK_SIZE,
+};
+pub use withdrawal::{
+    aggregate_withdrawals, aggregate_withdrawals_by_validator, Withdrawal,
+    WithdrawalAmountOverflow, WithdrawalsSummary,
 };
-pub use withdrawal::Withdrawal;
 
 /// A block hash.
 pub type BlockHash = H256;
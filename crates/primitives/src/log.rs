@@ -1,4 +1,7 @@
-use crate::{Address, Bytes, H256};
+use crate::{
+    bloom::{Bloom, Input},
+    Address, BlockNumber, Bytes, H256,
+};
 use reth_codecs::{main_codec, Compact};
 use reth_rlp::{RlpDecodable, RlpEncodable};
 
@@ -13,3 +16,116 @@ pub struct Log {
     /// Arbitrary length data.
     pub data: Bytes,
 }
+
+/// Block and transaction context of a [Log].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogMeta {
+    /// Hash of the block containing the log.
+    pub block_hash: H256,
+    /// Number of the block containing the log.
+    pub block_number: BlockNumber,
+    /// Hash of the transaction that emitted the log.
+    pub tx_hash: H256,
+    /// Index of the transaction in the block.
+    pub tx_index: u64,
+    /// Index of the log in the block, counting the logs of all preceding transactions.
+    pub log_index: u64,
+}
+
+/// Filter for logs by the emitting address and topics.
+///
+/// An empty address list matches any address. Each topic position matches any of the given
+/// topics, an empty position matches any topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogAddressAndTopicsFilter {
+    /// Addresses the log may be emitted by.
+    pub addresses: Vec<Address>,
+    /// Accepted topics for each of the four topic positions.
+    pub topics: [Vec<H256>; 4],
+}
+
+impl LogAddressAndTopicsFilter {
+    /// Returns `true` if the log matches the filter.
+    pub fn matches(&self, log: &Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false
+        }
+
+        self.topics.iter().enumerate().all(|(idx, topics)| {
+            topics.is_empty() || log.topics.get(idx).map_or(false, |topic| topics.contains(topic))
+        })
+    }
+
+    /// Returns `false` if none of the logs aggregated in the bloom can match the filter.
+    ///
+    /// Blooms have false positives, so `true` only indicates that a matching log may exist.
+    pub fn matches_bloom(&self, bloom: &Bloom) -> bool {
+        let contains = |input: &[u8]| bloom.contains_bloom(&Bloom::from(Input::Raw(input)));
+
+        if !self.addresses.is_empty() &&
+            !self.addresses.iter().any(|address| contains(address.as_bytes()))
+        {
+            return false
+        }
+
+        self.topics.iter().all(|topics| {
+            topics.is_empty() || topics.iter().any(|topic| contains(topic.as_bytes()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom::logs_bloom;
+
+    #[test]
+    fn log_filter_matches() {
+        let log = Log {
+            address: Address::from_low_u64_be(1),
+            topics: vec![H256::from_low_u64_be(10), H256::from_low_u64_be(11)],
+            data: Bytes::default(),
+        };
+        let bloom = logs_bloom([&log]);
+
+        let any = LogAddressAndTopicsFilter::default();
+        assert!(any.matches(&log));
+        assert!(any.matches_bloom(&bloom));
+
+        let by_address = LogAddressAndTopicsFilter {
+            addresses: vec![Address::from_low_u64_be(2), Address::from_low_u64_be(1)],
+            ..Default::default()
+        };
+        assert!(by_address.matches(&log));
+        assert!(by_address.matches_bloom(&bloom));
+
+        let other_address = LogAddressAndTopicsFilter {
+            addresses: vec![Address::from_low_u64_be(2)],
+            ..Default::default()
+        };
+        assert!(!other_address.matches(&log));
+        assert!(!other_address.matches_bloom(&bloom));
+
+        let by_topic = LogAddressAndTopicsFilter {
+            topics: [vec![], vec![H256::from_low_u64_be(11)], vec![], vec![]],
+            ..Default::default()
+        };
+        assert!(by_topic.matches(&log));
+        assert!(by_topic.matches_bloom(&bloom));
+
+        // blooms don't record the topic position
+        let wrong_position = LogAddressAndTopicsFilter {
+            topics: [vec![H256::from_low_u64_be(11)], vec![], vec![], vec![]],
+            ..Default::default()
+        };
+        assert!(!wrong_position.matches(&log));
+        assert!(wrong_position.matches_bloom(&bloom));
+
+        let other_topic = LogAddressAndTopicsFilter {
+            topics: [vec![], vec![], vec![H256::from_low_u64_be(12)], vec![]],
+            ..Default::default()
+        };
+        assert!(!other_topic.matches(&log));
+        assert!(!other_topic.matches_bloom(&bloom));
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
     keccak256,
     trie::{HashBuilder, Nibbles},
-    Address, Bytes, GenesisAccount, Header, Log, ReceiptWithBloom, ReceiptWithBloomRef,
-    TransactionSigned, Withdrawal, H256,
+    Address, Bloom, Bytes, GenesisAccount, Header, Log, Receipt, ReceiptWithBloom,
+    ReceiptWithBloomRef, TransactionSigned, Withdrawal, H256,
 };
 use bytes::{BufMut, BytesMut};
 use hash_db::Hasher;
 use hex_literal::hex;
 use plain_hasher::PlainHasher;
+use rayon::prelude::*;
 use reth_rlp::Encodable;
 use std::collections::HashMap;
 use triehash::sec_trie_root;
@@ -86,12 +87,37 @@ where
     ordered_trie_root_with_encoder(transactions, |tx: &T, buf| tx.as_ref().encode_inner(buf, false))
 }
 
+/// Calculate a transaction root, encoding the transactions in parallel.
+///
+/// This is only faster than [calculate_transaction_root] for large blocks, see
+/// [PARALLEL_TRANSACTIONS_ROOT_THRESHOLD].
+pub fn calculate_transaction_root_parallel<T>(transactions: &[T]) -> H256
+where
+    T: AsRef<TransactionSigned> + Sync,
+{
+    let encoded = transactions
+        .par_iter()
+        .map(|tx| {
+            let mut buf = Vec::new();
+            tx.as_ref().encode_inner(&mut buf, false);
+            buf
+        })
+        .collect::<Vec<_>>();
+    ordered_trie_root_with_encoder(&encoded, |tx, buf| buf.put_slice(tx))
+}
+
+/// Number of transactions from which on
+/// [BlockBody::calculate_tx_root](crate::BlockBody::calculate_tx_root) encodes the transactions in
+/// parallel.
+pub const PARALLEL_TRANSACTIONS_ROOT_THRESHOLD: usize = 128;
+
 /// Calculates the root hash of the withdrawals.
 pub fn calculate_withdrawals_root(withdrawals: &[Withdrawal]) -> H256 {
     ordered_trie_root(withdrawals)
 }
 
 /// Calculates the receipt root for a header.
+#[doc(alias = "receipts_root_from_receipts")]
 pub fn calculate_receipt_root(receipts: &[ReceiptWithBloom]) -> H256 {
     ordered_trie_root_with_encoder(receipts, |r, buf| r.encode_inner(buf, false))
 }
@@ -108,6 +134,19 @@ where
     })
 }
 
+/// Calculates the receipts root and the logs bloom of a block header from its receipts.
+///
+/// The bloom of each receipt is calculated once and used for both. Receipts before Byzantium
+/// (EIP-658) commit to an intermediate state root instead of the status and are not supported.
+pub fn calculate_receipt_root_and_logs_bloom<'a>(
+    receipts: impl IntoIterator<Item = &'a Receipt>,
+) -> (H256, Bloom) {
+    let receipts = receipts.into_iter().map(ReceiptWithBloomRef::from).collect::<Vec<_>>();
+    let logs_bloom = receipts.iter().fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom);
+    let root = ordered_trie_root_with_encoder(&receipts, |r, buf| r.encode_inner(buf, false));
+    (root, logs_bloom)
+}
+
 /// Calculates the log root for headers.
 pub fn calculate_log_root(logs: &[Log]) -> H256 {
     //https://github.com/ethereum/go-ethereum/blob/356bbe343a30789e77bb38f25983c8f2f2bfbb47/cmd/evm/internal/t8ntool/execution.go#L255
@@ -143,13 +182,28 @@ mod tests {
 
     use crate::{
         hex_literal::hex,
-        proofs::{calculate_receipt_root, calculate_transaction_root, genesis_state_root},
-        Address, Block, Bloom, GenesisAccount, Log, Receipt, ReceiptWithBloom, TxType, H160, H256,
-        U256,
+        proofs::{
+            calculate_receipt_root, calculate_receipt_root_and_logs_bloom,
+            calculate_receipt_root_ref, calculate_transaction_root, genesis_state_root,
+        },
+        Address, Block, BlockBody, Bloom, Bytes, GenesisAccount, Log, Receipt, ReceiptWithBloom,
+        TransactionSigned, TxType, H160, H256, U256,
     };
+    use proptest::{collection::vec, prelude::*};
     use reth_rlp::Decodable;
 
-    use super::{calculate_withdrawals_root, EMPTY_ROOT};
+    use super::{
+        calculate_ommers_root, calculate_transaction_root_parallel, calculate_withdrawals_root,
+        EMPTY_LIST_HASH, EMPTY_ROOT, PARALLEL_TRANSACTIONS_ROOT_THRESHOLD,
+    };
+
+    fn body(block: &Block) -> BlockBody {
+        BlockBody {
+            transactions: block.body.clone(),
+            ommers: block.ommers.clone(),
+            withdrawals: block.withdrawals.clone(),
+        }
+    }
 
     #[test]
     fn check_transaction_root() {
@@ -159,6 +213,40 @@ mod tests {
 
         let tx_root = calculate_transaction_root(&block.body);
         assert_eq!(block.transactions_root, tx_root, "Must be the same");
+
+        let body = body(&block);
+        assert_eq!(body.calculate_tx_root(), block.transactions_root);
+        assert_eq!(body.calculate_ommers_hash(), block.ommers_hash);
+        assert_eq!(body.calculate_withdrawals_root(), None);
+        assert_eq!(calculate_transaction_root_parallel(&block.body), block.transactions_root);
+    }
+
+    #[test]
+    fn check_empty_body_roots() {
+        let body = BlockBody::default();
+        assert_eq!(body.calculate_tx_root(), EMPTY_ROOT);
+        assert_eq!(body.calculate_ommers_hash(), EMPTY_LIST_HASH);
+        assert_eq!(calculate_ommers_root(&[]), EMPTY_LIST_HASH);
+        assert_eq!(body.calculate_withdrawals_root(), None);
+
+        let body = BlockBody { withdrawals: Some(Vec::new()), ..Default::default() };
+        assert_eq!(body.calculate_withdrawals_root(), Some(EMPTY_ROOT));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn parallel_transaction_root(
+            transactions in vec(
+                any::<TransactionSigned>(),
+                PARALLEL_TRANSACTIONS_ROOT_THRESHOLD..PARALLEL_TRANSACTIONS_ROOT_THRESHOLD + 16
+            )
+        ) {
+            let body = BlockBody { transactions, ..Default::default() };
+            let expected = calculate_transaction_root(&body.transactions);
+            prop_assert_eq!(body.calculate_tx_root(), expected);
+        }
     }
 
     #[test]
@@ -182,6 +270,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_receipt_root_and_logs_bloom() {
+        assert_eq!(calculate_receipt_root_and_logs_bloom([]), (EMPTY_ROOT, Bloom::zero()));
+
+        // the logs of the hardcoded bloom test, one per receipt
+        let log = |address, topics: Vec<H256>, data: &[u8]| Log {
+            address: H160(address),
+            topics,
+            data: Bytes::from(data.to_vec()),
+        };
+        let receipts = [
+            log(
+                hex!("22341ae42d6dd7384bc8584e50419ea3ac75b83f"),
+                vec![H256(hex!(
+                    "04491edcd115127caedbd478e2e7895ed80c7847e903431f94f9cfa579cad47f"
+                ))],
+                &[],
+            ),
+            log(
+                hex!("e7fb22dfef11920312e4989a3a2b81e2ebf05986"),
+                vec![
+                    H256(hex!("7f1fef85c4b037150d3675218e0cdb7cf38fea354759471e309f3354918a442f")),
+                    H256(hex!("d85629c7eaae9ea4a10234fed31bc0aeda29b2683ebe0c1882499d272621f6b6")),
+                ],
+                &hex!("2d690516512020171c1ec870f6ff45398cc8609250326be89915fb538e7b"),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, log)| Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: 21_000 * (index as u64 + 1),
+            logs: vec![log],
+        })
+        .collect::<Vec<_>>();
+
+        let (root, logs_bloom) = calculate_receipt_root_and_logs_bloom(&receipts);
+        assert_eq!(
+            logs_bloom,
+            Bloom::from(hex!(
+                "000000000000000000810000000000000000000000000000000000020000000000000000000000000000008000"
+                "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+                "000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000"
+                "000000000000000000000000000000000000000000000000000000280000000000400000800000004000000000"
+                "000000000000000000000000000000000000000000000000000000000000100000100000000000000000000000"
+                "00000000001400000000000000008000000000000000000000000000000000"
+            ))
+        );
+        let with_bloom = receipts.iter().cloned().map(Receipt::with_bloom).collect::<Vec<_>>();
+        assert_eq!(root, calculate_receipt_root(&with_bloom));
+        assert_eq!(root, calculate_receipt_root_ref(&receipts));
+    }
+
     #[test]
     fn check_withdrawals_root() {
         // Single withdrawal, amount 0
@@ -193,6 +335,7 @@ mod tests {
         assert_eq!(withdrawals.len(), 1);
         let withdrawals_root = calculate_withdrawals_root(withdrawals);
         assert_eq!(block.withdrawals_root, Some(withdrawals_root));
+        assert_eq!(body(&block).calculate_withdrawals_root(), block.withdrawals_root);
 
         // 4 withdrawals, identical indices
         // https://github.com/ethereum/tests/blob/9760400e667eba241265016b02644ef62ab55de2/BlockchainTests/EIPTests/bc4895-withdrawals/twoIdenticalIndex.json
@@ -203,6 +346,10 @@ mod tests {
         assert_eq!(withdrawals.len(), 4);
         let withdrawals_root = calculate_withdrawals_root(withdrawals);
         assert_eq!(block.withdrawals_root, Some(withdrawals_root));
+        let body = body(&block);
+        assert_eq!(body.calculate_withdrawals_root(), block.withdrawals_root);
+        assert_eq!(body.calculate_tx_root(), block.transactions_root);
+        assert_eq!(body.calculate_ommers_hash(), block.ommers_hash);
     }
 
     #[test]
@@ -1,5 +1,7 @@
 mod checkpoint;
 mod mode;
+mod part;
 
 pub use checkpoint::PruneCheckpoint;
 pub use mode::PruneMode;
+pub use part::PrunePart;
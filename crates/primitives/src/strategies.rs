@@ -0,0 +1,364 @@
+//! [proptest] strategies for blocks that are consistent with a [ChainSpec].
+//!
+//! The transactions root, ommers hash and withdrawals root of generated headers match the bodies,
+//! and the fields and body parts of each block follow the forks active at it. State and receipts
+//! roots are arbitrary, they would need the blocks to be executed.
+
+use crate::{
+    constants::EIP1559_INITIAL_BASE_FEE, proofs::calculate_ommers_root, sign_message, Address,
+    BlockBody, BlockValidationError, ChainSpec, ForkCondition, Hardfork, Header, SealedBlock,
+    Transaction, TransactionSigned, TxType, Withdrawal, H256, U256,
+};
+use proptest::{collection::vec, prelude::*, sample::select};
+use std::sync::Arc;
+
+/// The gas limit of generated blocks.
+const GAS_LIMIT: u64 = 30_000_000;
+
+/// The seconds between two generated blocks.
+const BLOCK_TIME: u64 = 12;
+
+/// Returns a strategy for a single block of the chain spec, see [chain].
+pub fn consistent_block(spec: Arc<ChainSpec>) -> impl Strategy<Value = SealedBlock> {
+    chain(spec, 1).prop_map(|mut blocks| blocks.pop().expect("chain has one block"))
+}
+
+/// Returns a strategy for `len` blocks of the chain spec, each the child of the previous one.
+///
+/// Chains start right after genesis or `len / 2` blocks before a fork activates, so most of them
+/// span a fork boundary. Blocks are 12 seconds apart, timestamps before the timestamp
+/// based forks are derived from the block number.
+///
+/// Transactions of a type that is not active yet are dropped, the others are signed for the chain
+/// id of the spec by keys derived from small integers. Blocks after the merge have no difficulty,
+/// nonce and ommers, blocks after Shanghai have withdrawals.
+pub fn chain(spec: Arc<ChainSpec>, len: usize) -> impl Strategy<Value = Vec<SealedBlock>> {
+    let starts = start_points(&spec, len as u64);
+    (select(starts), any::<H256>(), vec(any::<BlockParts>(), len)).prop_map(
+        move |((first, first_timestamp), mut parent_hash, parts)| {
+            let mut blocks: Vec<SealedBlock> = Vec::with_capacity(parts.len());
+            for (parts, number) in parts.into_iter().zip(first..) {
+                let base_fee_per_gas = match blocks.last() {
+                    Some(parent) => spec.next_block_base_fee(&parent.header),
+                    None => spec
+                        .is_fork_active_at_block(Hardfork::London, number)
+                        .then_some(EIP1559_INITIAL_BASE_FEE),
+                };
+                let timestamp = first_timestamp + (number - first) * BLOCK_TIME;
+                let block =
+                    parts.into_block(&spec, parent_hash, number, timestamp, base_fee_per_gas);
+                parent_hash = block.hash();
+                blocks.push(block);
+            }
+            blocks
+        },
+    )
+}
+
+/// The numbers and timestamps of the first block of generated chains.
+fn start_points(spec: &ChainSpec, len: u64) -> Vec<(u64, u64)> {
+    let genesis_timestamp = spec.genesis().timestamp;
+    let by_number = |number: u64| (number, genesis_timestamp + number * BLOCK_TIME);
+
+    // mainnet like specs only know the paris block from the final difficulty
+    let mut fork_blocks: Vec<u64> =
+        spec.paris_block_and_final_difficulty.map(|(block, _)| block).into_iter().collect();
+    for (_, condition) in spec.forks_iter() {
+        if let ForkCondition::Block(block) | ForkCondition::TTD { fork_block: Some(block), .. } =
+            condition
+        {
+            fork_blocks.push(block);
+        }
+    }
+
+    let mut starts = vec![by_number(1)];
+    for block in &fork_blocks {
+        starts.push(by_number(block.saturating_sub(len / 2).max(1)));
+    }
+    let last_fork_block = fork_blocks.into_iter().max().unwrap_or_default();
+    for (_, condition) in spec.forks_iter() {
+        if let ForkCondition::Timestamp(timestamp) = condition {
+            let timestamp = timestamp.saturating_sub(len / 2 * BLOCK_TIME).max(genesis_timestamp);
+            starts.push((last_fork_block + 1, timestamp));
+        }
+    }
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
+/// The fork independent parts of a generated block.
+#[derive(Debug, Clone)]
+struct BlockParts {
+    beneficiary: Address,
+    difficulty: u64,
+    nonce: u64,
+    mix_hash: H256,
+    state_root: H256,
+    receipts_root: H256,
+    gas_used: u64,
+    /// The transactions and the secrets that sign them.
+    transactions: Vec<(Transaction, u64)>,
+    ommers: Vec<Header>,
+    withdrawals: Vec<Withdrawal>,
+}
+
+impl BlockParts {
+    /// Assembles the block, keeping what is valid at the given block number and timestamp.
+    fn into_block(
+        self,
+        spec: &ChainSpec,
+        parent_hash: H256,
+        number: u64,
+        timestamp: u64,
+        base_fee_per_gas: Option<u64>,
+    ) -> SealedBlock {
+        let berlin = spec.is_fork_active_at_block(Hardfork::Berlin, number);
+        let london = spec.is_fork_active_at_block(Hardfork::London, number);
+        let merged = spec.fork(Hardfork::Paris).active_at_block(number) ||
+            spec.final_paris_total_difficulty(number).is_some();
+
+        let transactions = self
+            .transactions
+            .into_iter()
+            .filter(|(transaction, _)| match transaction.tx_type() {
+                TxType::Legacy => true,
+                TxType::EIP2930 => berlin,
+                TxType::EIP1559 => london,
+            })
+            .map(|(mut transaction, secret)| {
+                if transaction.chain_id().is_some() {
+                    transaction.set_chain_id(spec.chain.id());
+                }
+                let signature =
+                    sign_message(H256::from_low_u64_be(secret), transaction.signature_hash())
+                        .expect("secret is a valid key");
+                TransactionSigned::from_transaction_and_signature(transaction, signature)
+            })
+            .collect();
+        let body = BlockBody {
+            transactions,
+            ommers: if merged { Vec::new() } else { self.ommers },
+            withdrawals: spec
+                .is_shanghai_activated_at_timestamp(timestamp)
+                .then_some(self.withdrawals),
+        };
+
+        let header = Header {
+            parent_hash,
+            ommers_hash: body.calculate_ommers_hash(),
+            beneficiary: self.beneficiary,
+            state_root: self.state_root,
+            transactions_root: body.calculate_tx_root(),
+            receipts_root: self.receipts_root,
+            withdrawals_root: body.calculate_withdrawals_root(),
+            difficulty: if merged { U256::ZERO } else { U256::from(self.difficulty) },
+            number,
+            gas_limit: GAS_LIMIT,
+            gas_used: self.gas_used,
+            timestamp,
+            mix_hash: self.mix_hash,
+            nonce: if merged { 0 } else { self.nonce },
+            base_fee_per_gas,
+            ..Default::default()
+        };
+        SealedBlock::new(header.seal_slow(), body)
+    }
+}
+
+impl Arbitrary for BlockParts {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<BlockParts>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            any::<(Address, H256, H256, H256, u64)>(),
+            1..=u64::MAX,
+            0..=GAS_LIMIT,
+            vec((any::<Transaction>(), 1..100u64), 0..5),
+            vec(any::<Header>(), 0..3),
+            vec(any::<Withdrawal>(), 0..4),
+        )
+            .prop_map(
+                |(
+                    (beneficiary, mix_hash, state_root, receipts_root, nonce),
+                    difficulty,
+                    gas_used,
+                    transactions,
+                    ommers,
+                    withdrawals,
+                )| BlockParts {
+                    beneficiary,
+                    difficulty,
+                    nonce,
+                    mix_hash,
+                    state_root,
+                    receipts_root,
+                    gas_used,
+                    transactions,
+                    ommers,
+                    withdrawals,
+                },
+            )
+            .boxed()
+    }
+}
+
+/// How [corrupted_block] makes a block fail [SealedBlock::ensure_well_formed].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// Appends a transaction to the body.
+    ExtraTransaction,
+    /// Appends an ommer to the body.
+    ExtraOmmer,
+    /// Appends a withdrawal to the body, adding the list if there is none.
+    ExtraWithdrawal,
+    /// Removes the withdrawals of the body, or adds an empty list if there are none.
+    ToggledWithdrawals,
+    /// Replaces the transactions root of the header.
+    TransactionsRoot,
+    /// Adds an ommer to the body and sets the difficulty of the header to zero, with a matching
+    /// ommers hash.
+    OmmersAfterMerge,
+}
+
+impl CorruptionKind {
+    /// All kinds of corruption.
+    pub const ALL: [CorruptionKind; 6] = [
+        CorruptionKind::ExtraTransaction,
+        CorruptionKind::ExtraOmmer,
+        CorruptionKind::ExtraWithdrawal,
+        CorruptionKind::ToggledWithdrawals,
+        CorruptionKind::TransactionsRoot,
+        CorruptionKind::OmmersAfterMerge,
+    ];
+
+    /// The kinds of corruption that only change the body.
+    pub const BODY_ONLY: [CorruptionKind; 4] = [
+        CorruptionKind::ExtraTransaction,
+        CorruptionKind::ExtraOmmer,
+        CorruptionKind::ExtraWithdrawal,
+        CorruptionKind::ToggledWithdrawals,
+    ];
+
+    /// Whether the validation error is the one caused by this kind of corruption.
+    pub fn matches(&self, error: &BlockValidationError) -> bool {
+        matches!(
+            (self, error),
+            (
+                CorruptionKind::ExtraTransaction | CorruptionKind::TransactionsRoot,
+                BlockValidationError::TransactionsRootMismatch { .. }
+            ) | (CorruptionKind::ExtraOmmer, BlockValidationError::OmmersHashMismatch { .. }) |
+                (
+                    CorruptionKind::ExtraWithdrawal | CorruptionKind::ToggledWithdrawals,
+                    BlockValidationError::WithdrawalsRootMismatch { .. }
+                ) |
+                (CorruptionKind::OmmersAfterMerge, BlockValidationError::OmmersAfterMerge { .. })
+        )
+    }
+}
+
+/// Returns a strategy for copies of the well formed block that are corrupted in the given way.
+///
+/// The corrupted blocks fail [SealedBlock::ensure_well_formed] with an error that the kind
+/// [matches](CorruptionKind::matches). Blocks with a changed header are sealed again.
+pub fn corrupted_block(block: SealedBlock, kind: CorruptionKind) -> BoxedStrategy<SealedBlock> {
+    match kind {
+        CorruptionKind::ExtraTransaction => any::<TransactionSigned>()
+            .prop_map(move |transaction| {
+                let mut block = block.clone();
+                block.body.push(transaction);
+                block
+            })
+            .boxed(),
+        CorruptionKind::ExtraOmmer => any::<Header>()
+            .prop_map(move |ommer| {
+                let mut block = block.clone();
+                block.ommers.push(ommer);
+                block
+            })
+            .boxed(),
+        CorruptionKind::ExtraWithdrawal => any::<Withdrawal>()
+            .prop_map(move |withdrawal| {
+                let mut block = block.clone();
+                block.withdrawals.get_or_insert_with(Vec::new).push(withdrawal);
+                block
+            })
+            .boxed(),
+        CorruptionKind::ToggledWithdrawals => {
+            let mut block = block;
+            block.withdrawals = match block.withdrawals {
+                Some(_) => None,
+                None => Some(Vec::new()),
+            };
+            Just(block).boxed()
+        }
+        CorruptionKind::TransactionsRoot => {
+            let root = block.transactions_root;
+            any::<H256>()
+                .prop_filter("root must differ", move |corrupted| *corrupted != root)
+                .prop_map(move |corrupted| {
+                    let mut block = block.clone().unseal();
+                    block.header.transactions_root = corrupted;
+                    block.seal_slow()
+                })
+                .boxed()
+        }
+        CorruptionKind::OmmersAfterMerge => any::<Header>()
+            .prop_map(move |ommer| {
+                let mut block = block.clone().unseal();
+                block.ommers.push(ommer);
+                block.header.ommers_hash = calculate_ommers_root(&block.ommers);
+                block.header.difficulty = U256::ZERO;
+                block.seal_slow()
+            })
+            .boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAINNET;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn chains_are_linked_and_well_formed(blocks in chain(MAINNET.clone(), 4)) {
+            for block in &blocks {
+                prop_assert_eq!(block.ensure_well_formed(), Ok(()));
+                prop_assert_eq!(block.hash(), block.header.hash_slow());
+            }
+            for pair in blocks.windows(2) {
+                prop_assert_eq!(pair[1].parent_hash, pair[0].hash());
+                prop_assert_eq!(pair[1].number, pair[0].number + 1);
+                prop_assert_eq!(pair[1].timestamp, pair[0].timestamp + BLOCK_TIME);
+            }
+        }
+
+        #[test]
+        fn corrupted_blocks_are_rejected(
+            (block, kind) in (
+                consistent_block(MAINNET.clone()),
+                select(CorruptionKind::ALL.to_vec()),
+            ).prop_flat_map(|(block, kind)| (corrupted_block(block, kind), Just(kind)))
+        ) {
+            let error = block.ensure_well_formed().unwrap_err();
+            prop_assert!(kind.matches(&error), "{kind:?} caused {error:?}");
+        }
+
+        #[test]
+        fn body_corruptions_keep_the_header(
+            (original, corrupted) in consistent_block(MAINNET.clone()).prop_flat_map(|block| {
+                let original = Just(block.clone());
+                let corrupted = select(CorruptionKind::BODY_ONLY.to_vec())
+                    .prop_flat_map(move |kind| corrupted_block(block.clone(), kind));
+                (original, corrupted)
+            })
+        ) {
+            prop_assert_eq!(&corrupted.header, &original.header);
+            prop_assert_ne!(corrupted, original);
+        }
+    }
+}
@@ -13,4 +13,8 @@ pub struct TransactionMeta {
     pub block_number: u64,
     /// Base fee of the block.
     pub base_fee: Option<u64>,
+    /// Gas used by the transaction, if its receipt was read.
+    pub gas_used: Option<u64>,
+    /// Whether the transaction succeeded, if its receipt was read.
+    pub success: Option<bool>,
 }
@@ -7,6 +7,7 @@ use bytes::{Buf, BytesMut};
 use derive_more::{AsRef, Deref};
 pub use error::InvalidTransactionError;
 pub use meta::TransactionMeta;
+use rayon::prelude::*;
 use reth_codecs::{add_arbitrary_tests, derive_arbitrary, main_codec, Compact};
 use reth_rlp::{
     length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_LIST_CODE, EMPTY_STRING_CODE,
@@ -804,6 +805,14 @@ impl TransactionSignedNoHash {
     pub fn with_hash(self) -> TransactionSigned {
         self.into()
     }
+
+    /// Recover signer from signature and signature hash, without computing the transaction hash.
+    ///
+    /// Returns `None` if the transaction's signature is invalid.
+    pub fn recover_signer(&self) -> Option<Address> {
+        let signature_hash = self.signature_hash();
+        self.signature.recover_signer(signature_hash)
+    }
 }
 
 impl Compact for TransactionSignedNoHash {
@@ -914,6 +923,10 @@ impl AsRef<Self> for TransactionSigned {
 
 // === impl TransactionSigned ===
 
+/// Default number of transactions recovered by each task of
+/// [TransactionSigned::recover_signers_parallel].
+pub const SENDER_RECOVERY_CHUNK_SIZE: usize = 32;
+
 impl TransactionSigned {
     /// Transaction signature.
     pub fn signature(&self) -> &Signature {
@@ -952,6 +965,33 @@ impl TransactionSigned {
         Some(TransactionSignedEcRecovered { signed_transaction: self.clone(), signer })
     }
 
+    /// Recovers the signers of the transactions in parallel, each task recovering at least `chunk`
+    /// transactions.
+    ///
+    /// The signers are returned in the order of the transactions. Returns `None` if the signature
+    /// of any transaction is invalid, see also [Self::recover_signer].
+    pub fn recover_signers_parallel(
+        txs: &[TransactionSigned],
+        chunk: usize,
+    ) -> Option<Vec<Address>> {
+        txs.par_iter().with_min_len(chunk.max(1)).map(Self::recover_signer).collect()
+    }
+
+    /// Recovers the signers of the transactions in parallel like
+    /// [Self::recover_signers_parallel], but doesn't stop at an invalid signature.
+    ///
+    /// Transactions with an invalid signature get the zero address. The index of the first one is
+    /// returned along with the signers, so every signature is recovered only once.
+    pub fn recover_signers_parallel_or_default(
+        txs: &[TransactionSigned],
+        chunk: usize,
+    ) -> (Vec<Address>, Option<usize>) {
+        let signers =
+            txs.par_iter().with_min_len(chunk.max(1)).map(Self::recover_signer).collect::<Vec<_>>();
+        let invalid = signers.iter().position(Option::is_none);
+        (signers.into_iter().map(Option::unwrap_or_default).collect(), invalid)
+    }
+
     /// Returns the enveloped encoded transactions.
     ///
     /// See also [TransactionSigned::encode_enveloped]
@@ -1534,14 +1574,11 @@ mod tests {
         assert_eq!(tx.recover_signer(), Some(signer), "Recovering signer should pass.");
     }
 
-    #[test]
-    fn recover_signer_legacy() {
+    /// Returns a signed legacy transaction and its signer.
+    fn legacy_tx_with_signer() -> (TransactionSigned, Address) {
         use crate::hex_literal::hex;
 
         let signer: Address = hex!("398137383b3d25c92898c656696e41950e47316b").into();
-        let hash: H256 =
-            hex!("bb3a336e3f823ec18197f1e13ee875700f08f03e2cab75f0d0b118dabb44cba0").into();
-
         let tx = Transaction::Legacy(TxLegacy {
             chain_id: Some(1),
             nonce: 0x18,
@@ -1562,19 +1599,26 @@ mod tests {
             odd_y_parity: false,
         };
 
-        let signed_tx = TransactionSigned::from_transaction_and_signature(tx, sig);
-        assert_eq!(signed_tx.hash(), hash, "Expected same hash");
-        assert_eq!(signed_tx.recover_signer(), Some(signer), "Recovering signer should pass.");
+        (TransactionSigned::from_transaction_and_signature(tx, sig), signer)
     }
 
     #[test]
-    fn recover_signer_eip1559() {
+    fn recover_signer_legacy() {
         use crate::hex_literal::hex;
 
-        let signer: Address = hex!("dd6b8b3dc6b7ad97db52f08a275ff4483e024cea").into();
         let hash: H256 =
-            hex!("0ec0b6a2df4d87424e5f6ad2a654e27aaeb7dac20ae9e8385cc09087ad532ee0").into();
+            hex!("bb3a336e3f823ec18197f1e13ee875700f08f03e2cab75f0d0b118dabb44cba0").into();
 
+        let (signed_tx, signer) = legacy_tx_with_signer();
+        assert_eq!(signed_tx.hash(), hash, "Expected same hash");
+        assert_eq!(signed_tx.recover_signer(), Some(signer), "Recovering signer should pass.");
+    }
+
+    /// Returns a signed EIP-1559 transaction and its signer.
+    fn eip1559_tx_with_signer() -> (TransactionSigned, Address) {
+        use crate::hex_literal::hex;
+
+        let signer: Address = hex!("dd6b8b3dc6b7ad97db52f08a275ff4483e024cea").into();
         let tx = Transaction::Eip1559( TxEip1559 {
             chain_id: 1,
             nonce: 0x42,
@@ -1595,7 +1639,17 @@ mod tests {
             odd_y_parity: false,
         };
 
-        let signed_tx = TransactionSigned::from_transaction_and_signature(tx, sig);
+        (TransactionSigned::from_transaction_and_signature(tx, sig), signer)
+    }
+
+    #[test]
+    fn recover_signer_eip1559() {
+        use crate::hex_literal::hex;
+
+        let hash: H256 =
+            hex!("0ec0b6a2df4d87424e5f6ad2a654e27aaeb7dac20ae9e8385cc09087ad532ee0").into();
+
+        let (signed_tx, signer) = eip1559_tx_with_signer();
         assert_eq!(signed_tx.hash(), hash, "Expected same hash");
         assert_eq!(signed_tx.recover_signer(), Some(signer), "Recovering signer should pass.");
     }
@@ -1649,4 +1703,36 @@ mod tests {
         tx.encode(&mut b);
         assert_eq!(s, hex::encode(&b));
     }
+
+    #[test]
+    fn recover_signers_parallel() {
+        let vectors = [legacy_tx_with_signer(), eip1559_tx_with_signer()];
+        let (txs, signers): (Vec<_>, Vec<_>) = vectors.iter().cycle().take(100).cloned().unzip();
+
+        for chunk in [0, 1, 7, 32, 100, 1000] {
+            assert_eq!(
+                TransactionSigned::recover_signers_parallel(&txs, chunk),
+                Some(signers.clone())
+            );
+        }
+        assert_eq!(TransactionSigned::recover_signers_parallel(&[], 32), Some(vec![]));
+
+        assert_eq!(
+            TransactionSigned::recover_signers_parallel_or_default(&txs, 8),
+            (signers.clone(), None)
+        );
+
+        // a single invalid signature fails the whole batch
+        let mut invalid = txs;
+        invalid[42].signature.r = U256::ZERO;
+        invalid[57].signature.r = U256::ZERO;
+        assert_eq!(TransactionSigned::recover_signers_parallel(&invalid, 8), None);
+        let mut expected = signers;
+        expected[42] = Address::zero();
+        expected[57] = Address::zero();
+        assert_eq!(
+            TransactionSigned::recover_signers_parallel_or_default(&invalid, 8),
+            (expected, Some(42))
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{constants::GWEI_TO_WEI, serde_helper::u64_hex, Address, U256};
 use reth_codecs::{main_codec, Compact};
 use reth_rlp::{RlpDecodable, RlpEncodable};
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
 
 /// Withdrawal represents a validator withdrawal from the consensus layer.
 #[main_codec]
@@ -26,6 +27,100 @@ impl Withdrawal {
     }
 }
 
+/// Error returned when the total of withdrawn amounts in gwei does not fit into a [u128].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Total of withdrawn amounts overflows at withdrawal #{index}")]
+pub struct WithdrawalAmountOverflow {
+    /// Index of the withdrawal whose amount overflowed the total.
+    pub index: u64,
+}
+
+/// Sums the withdrawn amounts per target address.
+///
+/// The totals are in gwei like [Withdrawal::amount].
+pub fn aggregate_withdrawals<W: Borrow<Withdrawal>>(
+    withdrawals: impl IntoIterator<Item = W>,
+) -> Result<HashMap<Address, u128>, WithdrawalAmountOverflow> {
+    aggregate_withdrawals_by(withdrawals, |withdrawal| withdrawal.address)
+}
+
+/// Sums the withdrawn amounts per validator index.
+///
+/// The totals are in gwei like [Withdrawal::amount].
+pub fn aggregate_withdrawals_by_validator<W: Borrow<Withdrawal>>(
+    withdrawals: impl IntoIterator<Item = W>,
+) -> Result<HashMap<u64, u128>, WithdrawalAmountOverflow> {
+    aggregate_withdrawals_by(withdrawals, |withdrawal| withdrawal.validator_index)
+}
+
+fn aggregate_withdrawals_by<K: Eq + Hash, W: Borrow<Withdrawal>>(
+    withdrawals: impl IntoIterator<Item = W>,
+    key: impl Fn(&Withdrawal) -> K,
+) -> Result<HashMap<K, u128>, WithdrawalAmountOverflow> {
+    let mut totals = HashMap::new();
+    for withdrawal in withdrawals {
+        let withdrawal = withdrawal.borrow();
+        add_amount(&mut totals, key(withdrawal), withdrawal)?;
+    }
+    Ok(totals)
+}
+
+/// Adds the amount of the withdrawal to the total of the key.
+fn add_amount<K: Eq + Hash>(
+    totals: &mut HashMap<K, u128>,
+    key: K,
+    withdrawal: &Withdrawal,
+) -> Result<(), WithdrawalAmountOverflow> {
+    let total = totals.entry(key).or_default();
+    *total = total
+        .checked_add(withdrawal.amount as u128)
+        .ok_or(WithdrawalAmountOverflow { index: withdrawal.index })?;
+    Ok(())
+}
+
+/// Totals of the withdrawals of a range of blocks.
+///
+/// All amounts are in gwei like [Withdrawal::amount], see [WithdrawalsSummary::total_wei].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WithdrawalsSummary {
+    /// Number of withdrawals.
+    pub count: u64,
+    /// Total withdrawn amount in gwei.
+    pub total: u128,
+    /// Withdrawn amount in gwei per target address.
+    pub by_address: HashMap<Address, u128>,
+    /// Withdrawn amount in gwei per validator index.
+    pub by_validator: HashMap<u64, u128>,
+}
+
+impl WithdrawalsSummary {
+    /// Adds the withdrawals to the totals.
+    ///
+    /// Withdrawals up to the one that overflows the total are added.
+    pub fn extend<W: Borrow<Withdrawal>>(
+        &mut self,
+        withdrawals: impl IntoIterator<Item = W>,
+    ) -> Result<(), WithdrawalAmountOverflow> {
+        for withdrawal in withdrawals {
+            let withdrawal = withdrawal.borrow();
+            // the totals per address and validator are bounded by the total
+            self.total = self
+                .total
+                .checked_add(withdrawal.amount as u128)
+                .ok_or(WithdrawalAmountOverflow { index: withdrawal.index })?;
+            add_amount(&mut self.by_address, withdrawal.address, withdrawal)?;
+            add_amount(&mut self.by_validator, withdrawal.validator_index, withdrawal)?;
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the total withdrawn amount in wei.
+    pub fn total_wei(&self) -> U256 {
+        U256::from(self.total) * U256::from(GWEI_TO_WEI)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +134,57 @@ mod tests {
         let s = serde_json::to_string(&withdrawals).unwrap();
         assert_eq!(input, s);
     }
+
+    #[test]
+    fn aggregate_amounts_near_max() {
+        let withdrawal = |index, validator_index, address, amount| Withdrawal {
+            index,
+            validator_index,
+            address: Address::from_low_u64_be(address),
+            amount,
+        };
+        let withdrawals = vec![
+            withdrawal(0, 1, 1, u64::MAX),
+            withdrawal(1, 2, 1, u64::MAX),
+            withdrawal(2, 2, 2, u64::MAX - 1),
+            withdrawal(3, 1, 1, 2),
+        ];
+        let max = u64::MAX as u128;
+
+        assert_eq!(
+            aggregate_withdrawals(&withdrawals),
+            Ok(HashMap::from([
+                (Address::from_low_u64_be(1), 2 * max + 2),
+                (Address::from_low_u64_be(2), max - 1)
+            ]))
+        );
+        assert_eq!(
+            aggregate_withdrawals_by_validator(withdrawals.clone()),
+            Ok(HashMap::from([(1, max + 2), (2, 2 * max - 1)]))
+        );
+
+        let mut summary = WithdrawalsSummary::default();
+        summary.extend(&withdrawals).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.total, 3 * max + 1);
+        assert_eq!(summary.total_wei(), U256::from(3 * max + 1) * U256::from(GWEI_TO_WEI));
+        assert_eq!(summary.by_address, aggregate_withdrawals(&withdrawals).unwrap());
+        assert_eq!(summary.by_validator, aggregate_withdrawals_by_validator(&withdrawals).unwrap());
+    }
+
+    #[test]
+    fn aggregate_overflow() {
+        let withdrawal = Withdrawal { index: 7, amount: u64::MAX, ..Default::default() };
+        let overflow = WithdrawalAmountOverflow { index: 7 };
+
+        let mut totals = HashMap::from([(0, u128::MAX - u64::MAX as u128)]);
+        assert_eq!(add_amount(&mut totals, 0, &withdrawal), Ok(()));
+        assert_eq!(totals[&0], u128::MAX);
+        assert_eq!(add_amount(&mut totals, 0, &withdrawal), Err(overflow));
+
+        let mut summary = WithdrawalsSummary { total: u128::MAX - 1, ..Default::default() };
+        let before = summary.clone();
+        assert_eq!(summary.extend([&withdrawal]), Err(overflow));
+        assert_eq!(summary, before);
+    }
 }
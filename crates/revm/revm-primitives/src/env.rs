@@ -27,13 +27,12 @@ pub fn fill_cfg_env(
 ) {
     let spec_id = revm_spec(
         chain_spec,
-        Head {
-            number: header.number,
-            timestamp: header.timestamp,
-            difficulty: header.difficulty,
-            total_difficulty,
-            hash: Default::default(),
-        },
+        Head::builder(header.number)
+            .allow_unset_hash()
+            .timestamp(header.timestamp)
+            .difficulty(header.difficulty)
+            .total_difficulty(total_difficulty)
+            .build(),
     );
 
     cfg_env.chain_id = U256::from(chain_spec.chain().id());
@@ -1,5 +1,5 @@
 use crate::{
-    database::SubState,
+    database::{State, SubState},
     env::{fill_cfg_and_block_env, fill_tx_env},
     eth_dao_fork::{DAO_HARDFORK_BENEFICIARY, DAO_HARDKFORK_ACCOUNTS},
     into_reth_log,
@@ -10,9 +10,9 @@ use reth_consensus_common::calc;
 use reth_interfaces::executor::{BlockExecutionError, BlockValidationError};
 use reth_primitives::{
     Account, Address, Block, BlockNumber, Bloom, Bytecode, ChainSpec, Hardfork, Header, Receipt,
-    ReceiptWithBloom, TransactionSigned, Withdrawal, H256, U256,
+    TransactionSigned, Withdrawal, H256, U256,
 };
-use reth_provider::{BlockExecutor, PostState, StateProvider};
+use reth_provider::{BlockExecutor, ExecutionContext, PostState, StateProvider};
 use revm::{
     db::{AccountState, CacheDB, DatabaseRef},
     primitives::{
@@ -61,6 +61,21 @@ where
         Executor { chain_spec, evm, stack: InspectorStack::new(InspectorStackConfig::default()) }
     }
 
+    /// Creates a new executor on the state of the [ExecutionContext], configured with its
+    /// environments.
+    ///
+    /// Transactions can be run with [Executor::transact] without filling the environments first.
+    pub fn from_context<C>(chain_spec: Arc<ChainSpec>, context: C) -> Self
+    where
+        C: ExecutionContext<State = DB>,
+    {
+        let (cfg, block_env, state) = context.into_parts();
+        let mut executor = Self::new(chain_spec, SubState::new(State::new(state)));
+        executor.evm.env.cfg = cfg;
+        executor.evm.env.block = block_env;
+        executor
+    }
+
     /// Configures the executor with the given inspectors.
     pub fn with_stack(mut self, stack: InspectorStack) -> Self {
         self.stack = stack;
@@ -536,9 +551,10 @@ pub fn verify_receipt<'a>(
     expected_logs_bloom: Bloom,
     receipts: impl Iterator<Item = &'a Receipt> + Clone,
 ) -> Result<(), BlockExecutionError> {
+    let (receipts_root, logs_bloom) =
+        reth_primitives::proofs::calculate_receipt_root_and_logs_bloom(receipts);
+
     // Check receipts root.
-    let receipts_with_bloom = receipts.map(|r| r.clone().into()).collect::<Vec<ReceiptWithBloom>>();
-    let receipts_root = reth_primitives::proofs::calculate_receipt_root(&receipts_with_bloom);
     if receipts_root != expected_receipts_root {
         return Err(BlockValidationError::ReceiptRootDiff {
             got: receipts_root,
@@ -547,8 +563,7 @@ pub fn verify_receipt<'a>(
         .into())
     }
 
-    // Create header log bloom.
-    let logs_bloom = receipts_with_bloom.iter().fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+    // Check header log bloom.
     if logs_bloom != expected_logs_bloom {
         return Err(BlockValidationError::BloomLogDiff {
             expected: Box::new(expected_logs_bloom),
@@ -645,19 +660,24 @@ pub fn insert_post_block_withdrawals_balance_increments(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::database::State;
     use once_cell::sync::Lazy;
     use reth_consensus_common::calc;
     use reth_primitives::{
         constants::ETH_TO_WEI, hex_literal::hex, keccak256, Account, Address, BlockNumber,
-        Bytecode, Bytes, ChainSpecBuilder, ForkCondition, StorageKey, H256, MAINNET, U256,
+        Bytecode, Bytes, ChainSpecBuilder, ForkCondition, Signature, StorageKey, Transaction,
+        TransactionKind, TxLegacy, H256, MAINNET, U256,
     };
     use reth_provider::{
         post_state::{AccountChanges, Storage, StorageTransition, StorageWipe},
-        AccountReader, BlockHashReader, StateProvider, StateRootProvider,
+        AccountReader, BlockHashReader, EnvWithState, StateProvider, StateRootProvider,
     };
     use reth_rlp::Decodable;
-    use std::{collections::HashMap, str::FromStr};
+    use revm::primitives::{BlockEnv, CfgEnv, SpecId};
+    use std::{
+        collections::HashMap,
+        ops::{Bound, RangeBounds},
+        str::FromStr,
+    };
 
     static DEFAULT_REVM_ACCOUNT: Lazy<RevmAccount> = Lazy::new(|| RevmAccount {
         info: AccountInfo::default(),
@@ -707,10 +727,8 @@ mod tests {
 
         fn canonical_hashes_range(
             &self,
-            start: BlockNumber,
-            end: BlockNumber,
+            range: (Bound<BlockNumber>, Bound<BlockNumber>),
         ) -> reth_interfaces::Result<Vec<H256>> {
-            let range = start..end;
             Ok(self
                 .block_hash
                 .iter()
@@ -1333,4 +1351,53 @@ mod tests {
         assert_eq!(post_state_after_state_clear.accounts(), &BTreeMap::default());
         assert_eq!(post_state_after_state_clear.account_changes(), &AccountChanges::default());
     }
+
+    #[test]
+    fn execute_from_context() {
+        let sender = Address::from_low_u64_be(1);
+        let recipient = Address::from_low_u64_be(2);
+
+        // state of the parent block
+        let mut db = StateProviderTest::default();
+        db.insert_account(
+            sender,
+            Account { nonce: 5, balance: U256::from(ETH_TO_WEI), bytecode_hash: None },
+            None,
+            HashMap::new(),
+        );
+        db.insert_account(
+            recipient,
+            Account { nonce: 0, balance: U256::from(1), bytecode_hash: None },
+            None,
+            HashMap::new(),
+        );
+
+        let cfg = CfgEnv { spec_id: SpecId::BERLIN, ..Default::default() };
+        let block_env = BlockEnv { number: U256::from(10), ..Default::default() };
+        let context = EnvWithState { cfg, block_env, state: db };
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().berlin_activated().build());
+        let mut executor = Executor::from_context(chain_spec, context);
+        assert_eq!(executor.evm.env.cfg.spec_id, SpecId::BERLIN);
+        assert_eq!(executor.evm.env.block.number, U256::from(10));
+
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy {
+                chain_id: Some(1),
+                nonce: 5,
+                gas_price: 0,
+                gas_limit: 21_000,
+                to: TransactionKind::Call(recipient),
+                value: 100,
+                input: Bytes::default(),
+            }),
+            Signature::default(),
+        );
+        let ResultAndState { result, state } = executor.transact(&transaction, sender).unwrap();
+        assert!(result.is_success());
+
+        // the transfer is applied on top of the parent state
+        assert_eq!(state[&sender].info.nonce, 6);
+        assert_eq!(state[&sender].info.balance, U256::from(ETH_TO_WEI - 100));
+        assert_eq!(state[&recipient].info.balance, U256::from(101));
+    }
 }
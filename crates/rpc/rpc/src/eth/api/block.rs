@@ -80,6 +80,8 @@ where
                         block_hash,
                         block_number,
                         base_fee,
+                        gas_used: None,
+                        success: None,
                     };
                     build_transaction_receipt_with_block_receipts(tx, meta, receipt, &receipts)
                 })
@@ -13,7 +13,7 @@ use reth_interfaces::{
     p2p::bodies::{downloader::BodyDownloader, response::BlockResponse},
 };
 use reth_primitives::stage::{EntitiesCheckpoint, StageCheckpoint, StageId};
-use reth_provider::DatabaseProviderRW;
+use reth_provider::{BlockReader, DatabaseProviderRW};
 use std::sync::Arc;
 use tracing::*;
 
@@ -166,6 +166,11 @@ impl<DB: Database, D: BodyDownloader> Stage<DB> for BodyStage<D> {
         provider: &DatabaseProviderRW<'_, &DB>,
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
+        // Senders that weren't unwound by the sender recovery stage are still indexed
+        if let Some(block_meta) = provider.block_body_indices(input.unwind_to)? {
+            provider.unwind_sender_index(block_meta.next_tx_num())?;
+        }
+
         let tx = provider.tx_ref();
         // Cursors to unwind bodies, ommers
         let mut body_cursor = tx.cursor_write::<tables::BlockBodyIndices>()?;
@@ -235,8 +240,11 @@ mod tests {
     use crate::test_utils::{
         stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, UnwindStageTestRunner,
     };
+    use crate::stages::SenderRecoveryStage;
     use assert_matches::assert_matches;
-    use reth_primitives::stage::StageUnitCheckpoint;
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::{stage::StageUnitCheckpoint, H256, MAINNET};
+    use reth_provider::{BlockHashReader, BlockWriter, ProviderFactory};
     use test_utils::*;
 
     stage_test_suite_ext!(BodyTestRunner, body);
@@ -443,6 +451,44 @@ mod tests {
         assert_matches!(runner.validate_unwind(input), Ok(_), "unwind validation");
     }
 
+    /// Checks that unwinding the senders and bodies removes the unwound transactions from the
+    /// sender index, so the reused transaction numbers are only indexed for their new senders.
+    #[tokio::test]
+    async fn unwind_prunes_sender_index() {
+        let mut rng = generators::rng();
+        let runner = BodyTestRunner::default();
+        let db = runner.tx.inner_raw();
+        let factory = ProviderFactory::new(db.as_ref(), MAINNET.clone()).with_sender_index();
+
+        let provider = factory.provider_rw().unwrap();
+        for block in random_block_range(&mut rng, 0..=9, H256::zero(), 1..3) {
+            provider.insert_block(block, None).unwrap();
+        }
+        let tip = provider.block_hash(4).unwrap().unwrap();
+
+        // unwind like the pipeline does, the senders before the bodies and the headers last
+        let input =
+            UnwindInput { checkpoint: StageCheckpoint::new(9), unwind_to: 4, bad_block: None };
+        SenderRecoveryStage::default().unwind(&provider, input).await.unwrap();
+        runner.stage().unwind(&provider, input).await.unwrap();
+        provider.unwind_table_by_num::<tables::CanonicalHeaders>(4).unwrap();
+        provider.unwind_table_by_num::<tables::Headers>(4).unwrap();
+        let entries = provider.tx_ref().entries::<tables::TxSenders>().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::TransactionSenders>(), Ok(entries));
+
+        // the reused transaction numbers are indexed for the senders of the new blocks
+        for block in random_block_range(&mut rng, 5..=9, tip, 1..3) {
+            provider.insert_block(block, None).unwrap();
+        }
+        let senders = provider.tx_ref().cursor_read::<tables::TxSenders>().unwrap().walk(None);
+        let senders = senders.unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::TransactionSenders>(), Ok(senders.len()));
+        for (tx_num, sender) in senders {
+            let indexed = provider.transactions_by_sender(sender, tx_num..=tx_num).unwrap();
+            assert_eq!(indexed.len(), 1, "transaction {tx_num}");
+        }
+    }
+
     mod test_utils {
         use crate::{
             stages::bodies::BodyStage,
@@ -535,7 +535,8 @@ mod tests {
             type Seed = Vec<(Address, Account)>;
 
             fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
-                let provider = self.tx.inner_rw();
+                // the seeded blocks start above the empty genesis
+                let provider = self.tx.inner_rw().allow_block_gaps();
                 let res = Ok(AccountHashingStage::seed(
                     &provider,
                     SeedOpts { blocks: 1..=input.target(), accounts: 0..10, txs: 0..3 },
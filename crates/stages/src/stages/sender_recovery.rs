@@ -173,11 +173,11 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
         let (_, unwind_to, _) = input.unwind_block_range_with_threshold(self.commit_threshold);
 
         // Lookup latest tx id that we should unwind to
-        let latest_tx_id = provider
+        let body = provider
             .block_body_indices(unwind_to)?
-            .ok_or(ProviderError::BlockBodyIndicesNotFound(unwind_to))?
-            .last_tx_num();
-        provider.unwind_table_by_num::<tables::TxSenders>(latest_tx_id)?;
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(unwind_to))?;
+        provider.unwind_sender_index(body.next_tx_num())?;
+        provider.unwind_table_by_num::<tables::TxSenders>(body.last_tx_num())?;
 
         Ok(UnwindOutput {
             checkpoint: StageCheckpoint::new(unwind_to)
@@ -238,9 +238,7 @@ mod tests {
         generators,
         generators::{random_block, random_block_range},
     };
-    use reth_primitives::{
-        stage::StageUnitCheckpoint, BlockNumber, SealedBlock, TransactionSigned, H256,
-    };
+    use reth_primitives::{stage::StageUnitCheckpoint, BlockNumber, SealedBlock, H256};
     use reth_provider::TransactionsProvider;
 
     use super::*;
@@ -453,8 +451,9 @@ mod tests {
 
                     while let Some((_, body)) = body_cursor.next()? {
                         for tx_id in body.tx_num_range() {
-                            let transaction: TransactionSigned =
-                                provider.transaction_by_id(tx_id)?.expect("no transaction entry");
+                            let transaction = provider
+                                .transaction_by_id_no_hash(tx_id)?
+                                .expect("no transaction entry");
                             let signer =
                                 transaction.recover_signer().expect("failed to recover signer");
                             assert_eq!(Some(signer), provider.transaction_sender(tx_id)?)
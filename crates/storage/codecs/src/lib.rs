@@ -326,7 +326,8 @@ where
     buf.put_u8(n as u8);
 }
 
-fn decode_varuint(mut buf: &[u8]) -> (usize, &[u8]) {
+/// Decodes the varuint at the start of the buffer, returning it and the remaining buffer.
+pub fn decode_varuint(mut buf: &[u8]) -> (usize, &[u8]) {
     let mut value: usize = 0;
 
     for i in 0..33 {
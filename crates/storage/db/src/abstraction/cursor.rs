@@ -62,6 +62,17 @@ pub trait DbCursorRO<'tx, T: Table> {
     ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, DatabaseError>
     where
         Self: Sized;
+
+    /// Get an iterator that walks over a range of keys in the table in reverse order.
+    ///
+    /// The walker starts at the last entry within the end bound of the range and stops before the
+    /// first entry below the start bound.
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, DatabaseError>
+    where
+        Self: Sized;
 }
 
 /// A read-only cursor over the dup table `T`.
@@ -69,6 +80,12 @@ pub trait DbDupCursorRO<'tx, T: DupSort> {
     /// Positions the cursor at the next KV pair of the table, returning it.
     fn next_dup(&mut self) -> PairResult<T>;
 
+    /// Positions the cursor at the next duplicate of the current key, returning it if its subkey
+    /// is within the end bound.
+    ///
+    /// Subkeys are compared like in [DbDupCursorRO::walk_dup_range].
+    fn next_dup_until(&mut self, end: Bound<&T::SubKey>) -> PairResult<T>;
+
     /// Positions the cursor at the next KV pair of the table, skipping duplicates.
     fn next_no_dup(&mut self) -> PairResult<T>;
 
@@ -101,6 +118,19 @@ pub trait DbDupCursorRO<'tx, T: DupSort> {
     ) -> Result<DupWalker<'cursor, 'tx, T, Self>, DatabaseError>
     where
         Self: Sized;
+
+    /// Get an iterator over the duplicates of `key` whose subkey is within the range.
+    ///
+    /// Subkeys are compared with the start of the stored values, which is how the duplicates are
+    /// sorted. The cursor is positioned at the first duplicate within the range when the walker is
+    /// returned, the walk stops at the first duplicate past the range.
+    fn walk_dup_range<'cursor>(
+        &'cursor mut self,
+        key: T::Key,
+        range: impl RangeBounds<T::SubKey>,
+    ) -> Result<DupRangeWalker<'cursor, 'tx, T, Self>, DatabaseError>
+    where
+        Self: Sized;
 }
 
 /// Read write cursor over table.
@@ -308,6 +338,83 @@ impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRW<'tx, T> + DbCursorRO<'tx, T>>
     }
 }
 
+/// Provides a reverse range iterator to `Cursor` when handling `Table`.
+/// Also check [`RangeWalker`]
+pub struct ReverseRangeWalker<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    cursor: &'cursor mut CURSOR,
+    /// `(key, value)` where to start the walk.
+    start: IterPairResult<T>,
+    /// `key` where to stop the walk.
+    start_key: Bound<T::Key>,
+    /// flag whether is ended
+    is_done: bool,
+    /// Phantom data for 'tx. As it is only used for `DbCursorRO`.
+    _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>> std::iter::Iterator
+    for ReverseRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    type Item = Result<(T::Key, T::Value), DatabaseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None
+        }
+
+        let next_item = self.start.take().or_else(|| self.cursor.prev().transpose());
+
+        match next_item {
+            Some(Ok((key, value))) => match &self.start_key {
+                Bound::Included(start_key) if &key >= start_key => Some(Ok((key, value))),
+                Bound::Excluded(start_key) if &key > start_key => Some(Ok((key, value))),
+                Bound::Unbounded => Some(Ok((key, value))),
+                _ => {
+                    self.is_done = true;
+                    None
+                }
+            },
+            Some(res @ Err(_)) => Some(res),
+            None => {
+                self.is_done = true;
+                None
+            }
+        }
+    }
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRO<'tx, T>>
+    ReverseRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    /// construct ReverseRangeWalker
+    pub fn new(
+        cursor: &'cursor mut CURSOR,
+        start: IterPairResult<T>,
+        start_key: Bound<T::Key>,
+    ) -> Self {
+        // mark done if range is empty.
+        let is_done = match start {
+            Some(Ok((ref end_key, _))) => match &start_key {
+                Bound::Included(start_key) if end_key < start_key => true,
+                Bound::Excluded(start_key) if end_key <= start_key => true,
+                _ => false,
+            },
+            None => true,
+            _ => false,
+        };
+        Self { cursor, start, start_key, is_done, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
+impl<'cursor, 'tx, T: Table, CURSOR: DbCursorRW<'tx, T> + DbCursorRO<'tx, T>>
+    ReverseRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    /// Delete current item that walker points to.
+    pub fn delete_current(&mut self) -> Result<(), DatabaseError> {
+        self.cursor.delete_current()
+    }
+}
+
 /// Provides an iterator to `Cursor` when handling a `DupSort` table.
 ///
 /// Reason why we have two lifetimes is to distinguish between `'cursor` lifetime
@@ -343,3 +450,62 @@ impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> std::iter::Iterato
         self.cursor.next_dup().transpose()
     }
 }
+
+/// Provides an iterator to `Cursor` over the duplicates of a key within a range of subkeys.
+/// Also check [`DupWalker`]
+pub struct DupRangeWalker<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> {
+    /// Cursor to be used to walk through the table.
+    cursor: &'cursor mut CURSOR,
+    /// Value where to start the walk.
+    start: IterPairResult<T>,
+    /// The end bound of the subkeys.
+    end: Bound<T::SubKey>,
+    /// Whether the walk is past the range or failed.
+    done: bool,
+    /// Phantom data for 'tx. As it is only used for `DbDupCursorRO`.
+    _tx_phantom: PhantomData<&'tx T>,
+}
+
+impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>>
+    DupRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    /// construct DupRangeWalker, `start` is the first duplicate within the range and `end` the end
+    /// bound of the subkeys.
+    pub fn new(
+        cursor: &'cursor mut CURSOR,
+        start: IterPairResult<T>,
+        end: Bound<T::SubKey>,
+    ) -> Self {
+        let done = start.is_none();
+        Self { cursor, start, end, done, _tx_phantom: std::marker::PhantomData }
+    }
+}
+
+impl<'cursor, 'tx, T: DupSort, CURSOR: DbCursorRW<'tx, T> + DbDupCursorRO<'tx, T>>
+    DupRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    /// Delete current item that walker points to.
+    pub fn delete_current(&mut self) -> Result<(), DatabaseError> {
+        self.cursor.delete_current()
+    }
+}
+
+impl<'cursor, 'tx, T: DupSort, CURSOR: DbDupCursorRO<'tx, T>> std::iter::Iterator
+    for DupRangeWalker<'cursor, 'tx, T, CURSOR>
+{
+    type Item = Result<(T::Key, T::Value), DatabaseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            self.done = start.is_err();
+            return Some(start)
+        }
+        if self.done {
+            return None
+        }
+
+        let next_item = self.cursor.next_dup_until(self.end.as_ref()).transpose();
+        // the walk ends past the range or at the first error
+        self.done = !matches!(next_item, Some(Ok(_)));
+        next_item
+    }
+}
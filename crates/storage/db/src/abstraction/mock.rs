@@ -1,15 +1,19 @@
 //! Mock database
-use std::{collections::BTreeMap, ops::RangeBounds};
+use parking_lot::Mutex;
+use std::{
+    collections::BTreeMap,
+    ops::{Bound, RangeBounds},
+};
 
 use crate::{
     common::{PairResult, ValueOnlyResult},
     cursor::{
-        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
-        ReverseWalker, Walker,
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupRangeWalker, DupWalker,
+        RangeWalker, ReverseRangeWalker, ReverseWalker, Walker,
     },
     database::{Database, DatabaseGAT},
     table::{DupSort, Table, TableImporter},
-    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT, TableStat},
     DatabaseError,
 };
 
@@ -80,6 +84,18 @@ impl<'a> DbTx<'a> for TxMock {
     fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
         todo!()
     }
+
+    fn stat<T: Table>(&self) -> Result<TableStat, DatabaseError> {
+        todo!()
+    }
+
+    fn freelist(&self) -> Result<usize, DatabaseError> {
+        todo!()
+    }
+
+    fn id(&self) -> u64 {
+        todo!()
+    }
 }
 
 impl<'a> DbTxMut<'a> for TxMock {
@@ -114,6 +130,85 @@ impl<'a> DbTxMut<'a> for TxMock {
 
 impl<'a> TableImporter<'a> for TxMock {}
 
+/// Read only tx that wraps another one and counts the reads of each table, to assert which tables
+/// a query touches.
+#[derive(Debug)]
+pub struct CountingTxMock<TX> {
+    /// The wrapped transaction.
+    pub inner: TX,
+    /// Point reads and opened cursors by table name.
+    reads: Mutex<BTreeMap<&'static str, usize>>,
+}
+
+impl<TX> CountingTxMock<TX> {
+    /// Wraps the transaction.
+    pub fn new(inner: TX) -> Self {
+        Self { inner, reads: Mutex::default() }
+    }
+
+    /// Returns the number of point reads and opened cursors of the table.
+    pub fn reads(&self, table: &str) -> usize {
+        self.reads.lock().get(table).copied().unwrap_or_default()
+    }
+
+    fn record_read<T: Table>(&self) {
+        *self.reads.lock().entry(T::NAME).or_default() += 1;
+    }
+}
+
+impl<'a, TX: DbTxGAT<'a>> DbTxGAT<'a> for CountingTxMock<TX> {
+    type Cursor<T: Table> = <TX as DbTxGAT<'a>>::Cursor<T>;
+    type DupCursor<T: DupSort> = <TX as DbTxGAT<'a>>::DupCursor<T>;
+}
+
+impl<'tx, TX: DbTx<'tx>> DbTx<'tx> for CountingTxMock<TX> {
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, DatabaseError> {
+        self.record_read::<T>();
+        self.inner.get::<T>(key)
+    }
+
+    fn get_many<T: Table>(&self, keys: &[T::Key]) -> Result<Vec<Option<T::Value>>, DatabaseError> {
+        self.record_read::<T>();
+        self.inner.get_many::<T>(keys)
+    }
+
+    fn commit(self) -> Result<bool, DatabaseError> {
+        self.inner.commit()
+    }
+
+    fn drop(self) {
+        self.inner.drop()
+    }
+
+    fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, DatabaseError> {
+        self.record_read::<T>();
+        self.inner.cursor_read::<T>()
+    }
+
+    fn cursor_dup_read<T: DupSort>(
+        &self,
+    ) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, DatabaseError> {
+        self.record_read::<T>();
+        self.inner.cursor_dup_read::<T>()
+    }
+
+    fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
+        self.inner.entries::<T>()
+    }
+
+    fn stat<T: Table>(&self) -> Result<TableStat, DatabaseError> {
+        self.inner.stat::<T>()
+    }
+
+    fn freelist(&self) -> Result<usize, DatabaseError> {
+        self.inner.freelist()
+    }
+
+    fn id(&self) -> u64 {
+        self.inner.id()
+    }
+}
+
 /// Cursor that iterates over table
 pub struct CursorMock {
     _cursor: u32,
@@ -177,6 +272,16 @@ impl<'tx, T: Table> DbCursorRO<'tx, T> for CursorMock {
     {
         todo!()
     }
+
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        _range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, DatabaseError>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
 }
 
 impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for CursorMock {
@@ -184,6 +289,10 @@ impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for CursorMock {
         todo!()
     }
 
+    fn next_dup_until(&mut self, _end: Bound<&<T as DupSort>::SubKey>) -> PairResult<T> {
+        todo!()
+    }
+
     fn next_no_dup(&mut self) -> PairResult<T> {
         todo!()
     }
@@ -210,6 +319,16 @@ impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for CursorMock {
     {
         todo!()
     }
+    fn walk_dup_range<'cursor>(
+        &'cursor mut self,
+        _key: <T>::Key,
+        _range: impl RangeBounds<<T as DupSort>::SubKey>,
+    ) -> Result<DupRangeWalker<'cursor, 'tx, T, Self>, DatabaseError>
+    where
+        Self: Sized,
+    {
+        todo!()
+    }
 }
 
 impl<'tx, T: Table> DbCursorRW<'tx, T> for CursorMock {
@@ -32,10 +32,34 @@ pub trait DbTxMutGAT<'a, __ImplicitBounds: Sealed = Bounds<&'a Self>>: Send + Sy
         + Sync;
 }
 
+/// Page and entry statistics of a table, see [DbTx::stat].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStat {
+    /// Size of a database page in bytes, the same for all tables.
+    pub page_size: u32,
+    /// Depth of the B-tree.
+    pub depth: u32,
+    /// Number of internal pages.
+    pub branch_pages: usize,
+    /// Number of leaf pages.
+    pub leaf_pages: usize,
+    /// Number of overflow pages.
+    pub overflow_pages: usize,
+    /// Number of entries.
+    pub entries: usize,
+}
+
 /// Read only transaction
 pub trait DbTx<'tx>: for<'a> DbTxGAT<'a> {
     /// Get value
     fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, DatabaseError>;
+    /// Get the values of all keys, in the order of the keys.
+    ///
+    /// Missing keys map to `None`, duplicate keys are looked up once per occurrence unless the
+    /// implementation does better.
+    fn get_many<T: Table>(&self, keys: &[T::Key]) -> Result<Vec<Option<T::Value>>, DatabaseError> {
+        keys.iter().map(|key| self.get::<T>(key.clone())).collect()
+    }
     /// Commit for read only transaction will consume and free transaction and allows
     /// freeing of memory pages
     fn commit(self) -> Result<bool, DatabaseError>;
@@ -49,6 +73,13 @@ pub trait DbTx<'tx>: for<'a> DbTxGAT<'a> {
     ) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, DatabaseError>;
     /// Returns number of entries in the table.
     fn entries<T: Table>(&self) -> Result<usize, DatabaseError>;
+    /// Returns the page and entry statistics of the table.
+    fn stat<T: Table>(&self) -> Result<TableStat, DatabaseError>;
+    /// Returns the number of free pages of the database as seen by the transaction.
+    fn freelist(&self) -> Result<usize, DatabaseError>;
+    /// Returns the id of the transaction, which is the id of the last commit it sees for read
+    /// only transactions.
+    fn id(&self) -> u64;
 }
 
 /// Read write transaction that allows writing to database
@@ -5,8 +5,8 @@ use std::{borrow::Cow, collections::Bound, marker::PhantomData, ops::RangeBounds
 use crate::{
     common::{PairResult, ValueOnlyResult},
     cursor::{
-        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
-        ReverseWalker, Walker,
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupRangeWalker, DupWalker,
+        RangeWalker, ReverseRangeWalker, ReverseWalker, Walker,
     },
     table::{Compress, DupSort, Encode, Table},
     tables::utils::*,
@@ -138,6 +138,36 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
 
         Ok(ReverseWalker::new(self, start))
     }
+
+    fn walk_back_range<'cursor>(
+        &'cursor mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<ReverseRangeWalker<'cursor, 'tx, T, Self>, DatabaseError>
+    where
+        Self: Sized,
+    {
+        let start = match range.end_bound().cloned() {
+            Bound::Included(key) => match self.seek(key.clone())? {
+                // move past the duplicates of the key
+                Some((found, _)) if found == key => loop {
+                    match self.next()? {
+                        Some((next, _)) if next == key => {}
+                        Some(_) => break self.prev()?,
+                        None => break self.last()?,
+                    }
+                },
+                Some(_) => self.prev()?,
+                None => self.last()?,
+            },
+            Bound::Excluded(key) => match self.seek(key)? {
+                Some(_) => self.prev()?,
+                None => self.last()?,
+            },
+            Bound::Unbounded => self.last()?,
+        };
+
+        Ok(ReverseRangeWalker::new(self, start.map(Ok), range.start_bound().cloned()))
+    }
 }
 
 impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, K, T> {
@@ -146,6 +176,18 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
         decode!(self.inner.next_dup())
     }
 
+    /// Returns the next `(key, value)` pair of a DUPSORT table if its subkey is within the end
+    /// bound, which is checked on the stored value before decoding it.
+    fn next_dup_until(&mut self, end: Bound<&T::SubKey>) -> PairResult<T> {
+        let end = encode_bound(end);
+        self.inner
+            .next_dup::<Cow<'_, [u8]>, Cow<'_, [u8]>>()
+            .map_err(|e| DatabaseError::Read(e.into()))?
+            .filter(|(_, value)| is_before_end(value, &end))
+            .map(decoder::<T>)
+            .transpose()
+    }
+
     /// Returns the next `(key, value)` pair skipping the duplicates.
     fn next_no_dup(&mut self) -> PairResult<T> {
         decode!(self.inner.next_nodup())
@@ -219,6 +261,68 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
 
         Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
     }
+    fn walk_dup_range<'cursor>(
+        &'cursor mut self,
+        key: T::Key,
+        range: impl RangeBounds<T::SubKey>,
+    ) -> Result<DupRangeWalker<'cursor, 'tx, T, Self>, DatabaseError> {
+        let key = key.encode().as_ref().to_vec();
+        let start = encode_bound(range.start_bound());
+        let first = self
+            .seek_dup_start(&key, &start)?
+            .filter(|value| is_before_end(value, &encode_bound(range.end_bound())))
+            .map(|value| decoder::<T>((Cow::Owned(key), value)));
+        Ok(DupRangeWalker::new(self, first, range.end_bound().cloned()))
+    }
+}
+
+/// Returns `true` if the subkey at the start of the stored duplicate is within the encoded end
+/// bound.
+fn is_before_end(value: &[u8], end: &Bound<Vec<u8>>) -> bool {
+    match end {
+        Bound::Included(end) => &value[..value.len().min(end.len())] <= end.as_slice(),
+        Bound::Excluded(end) => &value[..value.len().min(end.len())] < end.as_slice(),
+        Bound::Unbounded => true,
+    }
+}
+
+/// Encodes the key of the bound.
+fn encode_bound<K: Encode + Clone>(bound: Bound<&K>) -> Bound<Vec<u8>> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key.clone().encode().as_ref().to_vec()),
+        Bound::Excluded(key) => Bound::Excluded(key.clone().encode().as_ref().to_vec()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl<'tx, K: TransactionKind, T: DupSort> Cursor<'tx, K, T> {
+    /// Positions the cursor at the first duplicate of the encoded key within the start bound of
+    /// encoded subkeys, returning its stored value.
+    fn seek_dup_start(
+        &mut self,
+        key: &[u8],
+        start: &Bound<Vec<u8>>,
+    ) -> Result<Option<Cow<'tx, [u8]>>, DatabaseError> {
+        let mut value = match start {
+            Bound::Included(subkey) | Bound::Excluded(subkey) => {
+                self.inner.get_both_range(key, subkey)
+            }
+            Bound::Unbounded => self.inner.set(key),
+        }
+        .map_err(|e| DatabaseError::Read(e.into()))?;
+
+        if let Bound::Excluded(subkey) = start {
+            // skip the duplicates of the excluded subkey
+            while value.as_ref().map_or(false, |value| value.starts_with(subkey)) {
+                value = self
+                    .inner
+                    .next_dup::<Cow<'_, [u8]>, Cow<'_, [u8]>>()
+                    .map_err(|e| DatabaseError::Read(e.into()))?
+                    .map(|(_, value)| value);
+            }
+        }
+        Ok(value)
+    }
 }
 
 impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, RW, T> {
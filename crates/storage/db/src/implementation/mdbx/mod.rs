@@ -136,9 +136,15 @@ mod tests {
         transaction::{DbTx, DbTxMut},
         AccountChangeSet, DatabaseError,
     };
+    use proptest::{collection::btree_set, prelude::ProptestConfig, proptest};
     use reth_libmdbx::{NoWriteMap, WriteMap};
     use reth_primitives::{Account, Address, Header, IntegerList, StorageEntry, H160, H256, U256};
-    use std::{path::Path, str::FromStr, sync::Arc};
+    use std::{
+        ops::{Bound, RangeBounds},
+        path::Path,
+        str::FromStr,
+        sync::Arc,
+    };
     use tempfile::TempDir;
 
     /// Create database for testing
@@ -190,6 +196,36 @@ mod tests {
         tx.commit().expect(ERROR_COMMIT);
     }
 
+    #[test]
+    fn db_get_many() {
+        let env = create_test_db::<NoWriteMap>(EnvKind::RW);
+
+        // PUT (1, 1), (3, 3), (4, 4)
+        let tx = env.tx_mut().expect(ERROR_INIT_TX);
+        for key in [1u64, 3, 4] {
+            tx.put::<CanonicalHeaders>(key, H256::from_low_u64_be(key)).expect(ERROR_PUT);
+        }
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get_many::<CanonicalHeaders>(&[]), Ok(vec![]));
+
+        // values are returned in the order of the keys, missing and duplicate keys included
+        let keys = [4, 0, 1, 4, 2, 2, 3, 10];
+        let values = tx.get_many::<CanonicalHeaders>(&keys).expect(ERROR_GET);
+        assert_eq!(
+            values,
+            keys.iter()
+                .map(|key| tx.get::<CanonicalHeaders>(*key).expect(ERROR_GET))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(values[0], Some(H256::from_low_u64_be(4)));
+        assert_eq!(values[3], Some(H256::from_low_u64_be(4)));
+        assert_eq!(values[4], None);
+        assert_eq!(values[5], None);
+        tx.commit().expect(ERROR_COMMIT);
+    }
+
     #[test]
     fn db_cursor_walk() {
         let env = create_test_db::<NoWriteMap>(EnvKind::RW);
@@ -448,6 +484,142 @@ mod tests {
         assert_eq!(reverse_walker.next(), None);
     }
 
+    #[test]
+    fn db_cursor_walk_back_range() {
+        let db: Arc<Env<WriteMap>> = create_test_db(EnvKind::RW);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        assert_eq!(cursor.walk_back_range(..).unwrap().next(), None);
+        drop(tx);
+
+        // PUT (0, 0), (1, 0), (3, 0), (4, 0)
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        vec![0, 1, 3, 4]
+            .into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, H256::zero()))
+            .expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        let mut keys = |range: (Bound<u64>, Bound<u64>)| {
+            cursor
+                .walk_back_range(range)
+                .unwrap()
+                .map(|entry| entry.map(|(key, _)| key))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        // [1, 3]
+        assert_eq!(keys((Bound::Included(1), Bound::Included(3))), vec![3, 1]);
+        // [1, 3)
+        assert_eq!(keys((Bound::Included(1), Bound::Excluded(3))), vec![1]);
+        // (1, 4], the end key is the last entry
+        assert_eq!(keys((Bound::Excluded(1), Bound::Included(4))), vec![4, 3]);
+        // [0, 2], the end key is missing
+        assert_eq!(keys((Bound::Included(0), Bound::Included(2))), vec![1, 0]);
+        // (∞, 1]
+        assert_eq!(keys((Bound::Unbounded, Bound::Included(1))), vec![1, 0]);
+        // [2, ∞), past the last entry
+        assert_eq!(keys((Bound::Included(2), Bound::Excluded(10))), vec![4, 3]);
+        // (∞, ∞)
+        assert_eq!(keys((Bound::Unbounded, Bound::Unbounded)), vec![4, 3, 1, 0]);
+        // below the first entry
+        assert_eq!(keys((Bound::Unbounded, Bound::Excluded(0))), Vec::<u64>::new());
+        // empty and reversed ranges
+        assert_eq!(keys((Bound::Included(2), Bound::Included(2))), Vec::<u64>::new());
+        assert_eq!(keys((Bound::Included(3), Bound::Excluded(3))), Vec::<u64>::new());
+        assert_eq!(keys((Bound::Included(4), Bound::Included(1))), Vec::<u64>::new());
+
+        let mut walker = cursor.walk_back_range(1..=3).unwrap();
+        assert_eq!(walker.next(), Some(Ok((3, H256::zero()))));
+        assert_eq!(walker.next(), Some(Ok((1, H256::zero()))));
+        assert_eq!(walker.next(), None);
+        // next() returns None after walker is done
+        assert_eq!(walker.next(), None);
+    }
+
+    #[test]
+    fn db_cursor_walk_back_range_on_dup_table() {
+        let db: Arc<Env<WriteMap>> = create_test_db(EnvKind::RW);
+
+        let address0 = Address::zero();
+        let address1 = Address::from_low_u64_be(1);
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        for (block, address) in [(0, address0), (1, address0), (1, address1), (2, address0)] {
+            tx.put::<AccountChangeSet>(block, AccountBeforeTx { address, info: None })
+                .expect(ERROR_PUT);
+        }
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<AccountChangeSet>().unwrap();
+
+        // all duplicates of the end key are returned, last one first
+        let mut walker = cursor.walk_back_range(1..=1).unwrap();
+        assert_eq!(walker.next(), Some(Ok((1, AccountBeforeTx { address: address1, info: None }))));
+        assert_eq!(walker.next(), Some(Ok((1, AccountBeforeTx { address: address0, info: None }))));
+        assert_eq!(walker.next(), None);
+
+        let entries = cursor.walk_back_range(..2).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+
+    /// Returns the bound of the slot, `kind` selects included, excluded or unbounded.
+    fn slot_bound(kind: u8, slot: u64) -> Bound<H256> {
+        match kind {
+            0 => Bound::Included(H256::from_low_u64_be(slot)),
+            1 => Bound::Excluded(H256::from_low_u64_be(slot)),
+            _ => Bound::Unbounded,
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+        #[test]
+        fn db_cursor_walk_dup_range(
+            entries in btree_set((0u64..3, 0u64..16), 0..48),
+            (start_kind, start) in (0u8..3, 0u64..18),
+            (end_kind, end) in (0u8..3, 0u64..18),
+        ) {
+            let db: Arc<Env<WriteMap>> = create_test_db(EnvKind::RW);
+
+            // a few duplicates per slot, their values follow the slot in the stored bytes
+            let tx = db.tx_mut().expect(ERROR_INIT_TX);
+            for (address, slot) in &entries {
+                for value in 0..(slot % 3) + 1 {
+                    let entry = StorageEntry {
+                        key: H256::from_low_u64_be(*slot),
+                        value: U256::from(value),
+                    };
+                    tx.put::<PlainStorageState>(Address::from_low_u64_be(*address), entry)
+                        .expect(ERROR_PUT);
+                }
+            }
+            tx.commit().expect(ERROR_COMMIT);
+
+            let range = (slot_bound(start_kind, start), slot_bound(end_kind, end));
+            let tx = db.tx().expect(ERROR_INIT_TX);
+            let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+            for address in (0..4).map(Address::from_low_u64_be) {
+                let expected = cursor
+                    .walk_dup(Some(address), None)
+                    .unwrap()
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+                    .into_iter()
+                    .filter(|(_, entry)| range.contains(&entry.key))
+                    .collect::<Vec<_>>();
+
+                let walker = cursor.walk_dup_range(address, range).unwrap();
+                assert_eq!(walker.collect::<Result<Vec<_>, _>>().unwrap(), expected);
+            }
+        }
+    }
+
     #[test]
     fn db_cursor_seek_exact_or_previous_key() {
         let db: Arc<Env<WriteMap>> = create_test_db(EnvKind::RW);
@@ -2,9 +2,10 @@
 
 use super::cursor::Cursor;
 use crate::{
+    cursor::DbCursorRO,
     table::{Compress, DupSort, Encode, Table, TableImporter},
     tables::{utils::decode_one, Tables, NUM_TABLES},
-    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT, TableStat},
     DatabaseError,
 };
 use parking_lot::RwLock;
@@ -89,6 +90,28 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
             .transpose()
     }
 
+    fn get_many<T: Table>(&self, keys: &[T::Key]) -> Result<Vec<Option<T::Value>>, DatabaseError> {
+        // seek in key order so a single cursor only ever moves forward
+        let mut sorted = keys.iter().enumerate().collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|(_, key)| *key);
+
+        let mut cursor = self.cursor_read::<T>()?;
+        let mut values = keys.iter().map(|_| None).collect::<Vec<_>>();
+        let mut previous: Option<(&T::Key, bool)> = None;
+        for (index, key) in sorted {
+            values[index] = match previous {
+                // the cursor is still positioned at a duplicate key, no need to seek again
+                Some((previous_key, true)) if previous_key == key => {
+                    cursor.current()?.map(|(_, value)| value)
+                }
+                Some((previous_key, false)) if previous_key == key => None,
+                _ => cursor.seek_exact(key.clone())?.map(|(_, value)| value),
+            };
+            previous = Some((key, values[index].is_some()));
+        }
+        Ok(values)
+    }
+
     fn commit(self) -> Result<bool, DatabaseError> {
         let start = Instant::now();
         let result = self.inner.commit().map_err(|e| DatabaseError::Commit(e.into()));
@@ -120,6 +143,29 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
             .map_err(|e| DatabaseError::Stats(e.into()))?
             .entries())
     }
+
+    fn stat<T: Table>(&self) -> Result<TableStat, DatabaseError> {
+        let stat = self
+            .inner
+            .db_stat_with_dbi(self.get_dbi::<T>()?)
+            .map_err(|e| DatabaseError::Stats(e.into()))?;
+        Ok(TableStat {
+            page_size: stat.page_size(),
+            depth: stat.depth(),
+            branch_pages: stat.branch_pages(),
+            leaf_pages: stat.leaf_pages(),
+            overflow_pages: stat.overflow_pages(),
+            entries: stat.entries(),
+        })
+    }
+
+    fn freelist(&self) -> Result<usize, DatabaseError> {
+        self.inner.freelist().map_err(|e| DatabaseError::Stats(e.into()))
+    }
+
+    fn id(&self) -> u64 {
+        self.inner.id()
+    }
 }
 
 impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
@@ -25,10 +25,11 @@
 //! - **Cursors** ([`DbCursorRO`] / [`DbCursorRW`]) for iterating data in a table. Cursors are
 //!   assumed to resolve data in a sorted manner when iterating from start to finish, and it is safe
 //!   to assume that they are efficient at doing so.
-//! - **Walkers** ([`Walker`] / [`RangeWalker`] / [`ReverseWalker`]) use cursors to walk the entries
-//!   in a table, either fully from a specific point, or over a range.
+//! - **Walkers** ([`Walker`] / [`RangeWalker`] / [`ReverseWalker`] / [`ReverseRangeWalker`]) use
+//!   cursors to walk the entries in a table, either fully from a specific point, or over a range.
 //!
-//! Dup tables (see below) also have corresponding cursors and walkers (e.g. [`DbDupCursorRO`]).
+//! Dup tables (see below) also have corresponding cursors and walkers (e.g. [`DbDupCursorRO`] and
+//! [`DupRangeWalker`], which walks the values of a key within a range of subkeys).
 //! These **should** be preferred when working with dup tables, as they provide additional methods
 //! that are optimized for dup tables.
 //!
@@ -54,7 +55,9 @@
 //! [`Walker`]: crate::abstraction::cursor::Walker
 //! [`RangeWalker`]: crate::abstraction::cursor::RangeWalker
 //! [`ReverseWalker`]: crate::abstraction::cursor::ReverseWalker
+//! [`ReverseRangeWalker`]: crate::abstraction::cursor::ReverseRangeWalker
 //! [`DbDupCursorRO`]: crate::abstraction::cursor::DbDupCursorRO
+//! [`DupRangeWalker`]: crate::abstraction::cursor::DupRangeWalker
 //! [`Encode`]: crate::abstraction::table::Encode
 //! [`Decode`]: crate::abstraction::table::Decode
 //! [`Compress`]: crate::abstraction::table::Compress
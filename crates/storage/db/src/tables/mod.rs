@@ -28,17 +28,17 @@ use crate::{
         codecs::CompactU256,
         models::{
             accounts::{AccountBeforeTx, BlockNumberAddress},
-            blocks::{HeaderHash, StoredBlockOmmers},
+            blocks::{ChainMarker, HeaderHash, StoredBlockNumHash, StoredBlockOmmers},
             storage_sharded_key::StorageShardedKey,
-            ShardedKey, StoredBlockBodyIndices, StoredBlockWithdrawals,
+            SenderTxNumber, ShardedKey, StoredBlockBodyIndices, StoredBlockWithdrawals,
         },
     },
 };
 use reth_primitives::{
     stage::StageCheckpoint,
     trie::{BranchNodeCompact, StorageTrieEntry, StoredNibbles, StoredNibblesSubKey},
-    Account, Address, BlockHash, BlockNumber, Bytecode, Header, IntegerList, Receipt, StorageEntry,
-    TransactionSignedNoHash, TxHash, TxNumber, H256,
+    Account, Address, BlockHash, BlockNumber, Bytecode, Header, IntegerList, PruneCheckpoint,
+    PrunePart, Receipt, StorageEntry, TransactionSignedNoHash, TxHash, TxNumber, H256,
 };
 
 /// Enum for the types of tables present in libmdbx.
@@ -51,7 +51,7 @@ pub enum TableType {
 }
 
 /// Number of tables that should be present inside database.
-pub const NUM_TABLES: usize = 25;
+pub const NUM_TABLES: usize = 28;
 
 /// The general purpose of this is to use with a combination of Tables enum,
 /// by implementing a `TableViewer` trait you can operate on db tables in an abstract way.
@@ -183,7 +183,10 @@ tables!([
     (StoragesTrie, TableType::DupSort),
     (TxSenders, TableType::Table),
     (SyncStage, TableType::Table),
-    (SyncStageProgress, TableType::Table)
+    (SyncStageProgress, TableType::Table),
+    (PruneCheckpoints, TableType::Table),
+    (ChainMarkers, TableType::Table),
+    (TransactionSenders, TableType::DupSort)
 ]);
 
 #[macro_export]
@@ -415,6 +418,22 @@ table!(
     ( SyncStageProgress ) StageId | Vec<u8>
 );
 
+table!(
+    /// Stores the highest pruned block number and prune mode of each prune part.
+    ( PruneCheckpoints ) PrunePart | PruneCheckpoint
+);
+
+table!(
+    /// Stores the finalized and safe blocks of the last fork choice update.
+    ( ChainMarkers ) ChainMarker | StoredBlockNumHash
+);
+
+dupsort!(
+    /// Stores the numbers of the transactions sent by each address, in ascending order.
+    /// Optional index, only written by providers with the sender index enabled.
+    ( TransactionSenders ) Address | [TxNumber] SenderTxNumber
+);
+
 /// Alias Types
 
 /// List with transaction numbers.
@@ -454,6 +473,9 @@ mod tests {
         (TableType::Table, TxSenders::const_name()),
         (TableType::Table, SyncStage::const_name()),
         (TableType::Table, SyncStageProgress::const_name()),
+        (TableType::Table, PruneCheckpoints::const_name()),
+        (TableType::Table, ChainMarkers::const_name()),
+        (TableType::DupSort, TransactionSenders::const_name()),
     ];
 
     #[test]
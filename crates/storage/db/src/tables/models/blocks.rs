@@ -1,7 +1,8 @@
 //! Block related models and types.
 
 use reth_codecs::{main_codec, Compact};
-use reth_primitives::{Header, TxNumber, Withdrawal, H256};
+use reth_primitives::{BlockNumHash, BlockNumber, Header, TxNumber, Withdrawal, H256};
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
 /// Total number of transactions.
@@ -84,9 +85,48 @@ pub struct StoredBlockWithdrawals {
     pub withdrawals: Vec<Withdrawal>,
 }
 
+impl StoredBlockWithdrawals {
+    /// Returns the number of withdrawals in the compressed [StoredBlockWithdrawals] without
+    /// decoding them, the encoding starts with the length of the list.
+    pub fn withdrawals_count(compressed: &[u8]) -> usize {
+        reth_codecs::decode_varuint(compressed).0
+    }
+}
+
 /// Hash of the block header. Value for [`CanonicalHeaders`][crate::tables::CanonicalHeaders]
 pub type HeaderHash = H256;
 
+/// Fork choice marker. Key for [`ChainMarkers`][crate::tables::ChainMarkers]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum ChainMarker {
+    /// The last finalized block.
+    Finalized,
+    /// The last safe block.
+    Safe,
+}
+
+/// The storage representation of a [BlockNumHash].
+#[main_codec]
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct StoredBlockNumHash {
+    /// Block number
+    pub number: BlockNumber,
+    /// Block hash
+    pub hash: H256,
+}
+
+impl From<BlockNumHash> for StoredBlockNumHash {
+    fn from(value: BlockNumHash) -> Self {
+        Self { number: value.number, hash: value.hash }
+    }
+}
+
+impl From<StoredBlockNumHash> for BlockNumHash {
+    fn from(value: StoredBlockNumHash) -> Self {
+        Self { number: value.number, hash: value.hash }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -114,4 +154,25 @@ mod test {
         assert_eq!(block_indices.tx_count(), tx_count);
         assert_eq!(block_indices.tx_num_range(), first_tx_num..first_tx_num + tx_count);
     }
+
+    #[test]
+    fn block_num_hash() {
+        let num_hash = BlockNumHash { number: 7, hash: H256::random() };
+        let stored = StoredBlockNumHash::from(num_hash);
+        let decompressed = StoredBlockNumHash::decompress::<Vec<_>>(stored.compress()).unwrap();
+        assert_eq!(BlockNumHash::from(decompressed), num_hash);
+    }
+
+    #[test]
+    fn withdrawals_count() {
+        for count in [0, 1, 200] {
+            let withdrawals = StoredBlockWithdrawals {
+                withdrawals: (0..count)
+                    .map(|index| Withdrawal { index, amount: index * 2, ..Default::default() })
+                    .collect(),
+            };
+            let compressed: Vec<u8> = withdrawals.compress();
+            assert_eq!(StoredBlockWithdrawals::withdrawals_count(&compressed), count as usize);
+        }
+    }
 }
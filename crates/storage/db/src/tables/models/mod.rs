@@ -6,7 +6,7 @@ use crate::{
 use reth_codecs::Compact;
 use reth_primitives::{
     trie::{StoredNibbles, StoredNibblesSubKey},
-    Address, H256,
+    Address, PrunePart, H256,
 };
 
 pub mod accounts;
@@ -14,10 +14,12 @@ pub mod blocks;
 pub mod integer_list;
 pub mod sharded_key;
 pub mod storage_sharded_key;
+pub mod transactions;
 
 pub use accounts::*;
 pub use blocks::*;
 pub use sharded_key::ShardedKey;
+pub use transactions::{RawTransactionRef, SenderTxNumber};
 
 /// Macro that implements [`Encode`] and [`Decode`] for uint types.
 macro_rules! impl_uints {
@@ -135,3 +137,42 @@ impl Decode for StoredNibblesSubKey {
         Ok(Self::from_compact(buf, buf.len()).0)
     }
 }
+
+impl Encode for ChainMarker {
+    type Encoded = [u8; 1];
+
+    fn encode(self) -> Self::Encoded {
+        [self as u8]
+    }
+}
+
+impl Decode for ChainMarker {
+    fn decode<B: AsRef<[u8]>>(value: B) -> Result<Self, DatabaseError> {
+        match value.as_ref() {
+            [0] => Ok(ChainMarker::Finalized),
+            [1] => Ok(ChainMarker::Safe),
+            _ => Err(DatabaseError::DecodeError),
+        }
+    }
+}
+
+impl Encode for PrunePart {
+    type Encoded = [u8; 1];
+
+    fn encode(self) -> Self::Encoded {
+        [self as u8]
+    }
+}
+
+impl Decode for PrunePart {
+    fn decode<B: AsRef<[u8]>>(value: B) -> Result<Self, DatabaseError> {
+        match value.as_ref() {
+            [0] => Ok(PrunePart::SenderRecovery),
+            [1] => Ok(PrunePart::TransactionLookup),
+            [2] => Ok(PrunePart::Receipts),
+            [3] => Ok(PrunePart::AccountHistory),
+            [4] => Ok(PrunePart::StorageHistory),
+            _ => Err(DatabaseError::DecodeError),
+        }
+    }
+}
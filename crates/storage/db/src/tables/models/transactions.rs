@@ -0,0 +1,141 @@
+//! Transaction related models and types.
+use crate::{
+    table::{Compress, Decompress},
+    DatabaseError,
+};
+use reth_primitives::{TransactionSignedNoHash, TxNumber, TxType};
+use serde::{Deserialize, Serialize};
+
+/// A transaction number as a duplicate value of the
+/// [TransactionSenders][crate::tables::TransactionSenders] table.
+///
+/// Unlike [TxNumber] values of other tables it is stored big-endian, so the duplicates of a sender
+/// are sorted by transaction number.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct SenderTxNumber(pub TxNumber);
+
+impl Compress for SenderTxNumber {
+    type Compressed = Vec<u8>;
+
+    fn compress_to_buf<B: bytes::BufMut + AsMut<[u8]>>(self, buf: &mut B) {
+        buf.put_slice(&self.0.to_be_bytes())
+    }
+}
+
+impl Decompress for SenderTxNumber {
+    fn decompress<B: AsRef<[u8]>>(value: B) -> Result<Self, DatabaseError> {
+        let bytes = value.as_ref().try_into().map_err(|_| DatabaseError::DecodeError)?;
+        Ok(Self(TxNumber::from_be_bytes(bytes)))
+    }
+}
+
+/// A borrowed transaction as stored in the [Transactions][crate::tables::Transactions] table.
+///
+/// The compact encoding of a [TransactionSignedNoHash] starts with a flags byte holding the
+/// signature parity (bit 0), the transaction type (bits 1-2) and whether the transaction is zstd
+/// compressed (bit 3). This allows answering cheap questions about the transaction before paying
+/// for the full decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawTransactionRef<'a> {
+    compressed: &'a [u8],
+}
+
+impl<'a> RawTransactionRef<'a> {
+    /// Wraps the stored bytes of a transaction.
+    pub fn new(compressed: &'a [u8]) -> Self {
+        Self { compressed }
+    }
+
+    /// Returns the type of the transaction, or `None` if the flags byte is missing or holds an
+    /// unknown type.
+    pub fn tx_type(&self) -> Option<TxType> {
+        match (self.flags()? & 0b110) >> 1 {
+            0 => Some(TxType::Legacy),
+            1 => Some(TxType::EIP2930),
+            2 => Some(TxType::EIP1559),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the transaction is zstd compressed, which is the case for transactions
+    /// with an input of at least 32 bytes.
+    pub fn is_compressed(&self) -> bool {
+        self.flags().map_or(false, |flags| flags & 0b1000 != 0)
+    }
+
+    /// Returns the size of the stored transaction in bytes.
+    ///
+    /// For compressed transactions this is smaller than the decoded size.
+    pub fn stored_len(&self) -> usize {
+        self.compressed.len()
+    }
+
+    /// Returns the stored bytes of the transaction.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.compressed
+    }
+
+    /// Decodes the transaction.
+    pub fn decode(&self) -> Result<TransactionSignedNoHash, DatabaseError> {
+        TransactionSignedNoHash::decompress(self.compressed)
+    }
+
+    fn flags(&self) -> Option<u8> {
+        self.compressed.first().copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use reth_primitives::{Bytes, Signature, Transaction, TxEip1559, TxEip2930, TxLegacy};
+
+    #[test]
+    fn raw_transaction_ref() {
+        let small_input = Bytes::from(vec![1; 4]);
+        let large_input = Bytes::from(vec![1; 64]);
+        let transactions = [
+            (Transaction::Legacy(TxLegacy { input: small_input, ..Default::default() }), false),
+            (
+                Transaction::Eip2930(TxEip2930 {
+                    input: large_input.clone(),
+                    ..Default::default()
+                }),
+                true,
+            ),
+            (Transaction::Eip1559(TxEip1559 { input: large_input, ..Default::default() }), true),
+        ];
+
+        for (transaction, compressed) in transactions {
+            let tx_type = transaction.tx_type();
+            let tx = TransactionSignedNoHash {
+                signature: Signature { odd_y_parity: true, ..Default::default() },
+                transaction,
+            };
+            let stored: Vec<u8> = tx.clone().compress();
+
+            let raw = RawTransactionRef::new(&stored);
+            assert_eq!(raw.tx_type(), Some(tx_type));
+            assert_eq!(raw.is_compressed(), compressed);
+            assert_eq!(raw.stored_len(), stored.len());
+            assert_eq!(raw.decode().unwrap(), tx);
+        }
+
+        assert_eq!(RawTransactionRef::new(&[]).tx_type(), None);
+        assert!(!RawTransactionRef::new(&[]).is_compressed());
+    }
+
+    #[test]
+    fn sender_tx_number_order() {
+        let numbers = [0, 1, 255, 256, 65_536, u64::MAX];
+        let compressed = numbers.map(|number| SenderTxNumber(number).compress());
+        // the stored bytes sort like the numbers, unlike little-endian encodings
+        assert!(compressed.windows(2).all(|pair| pair[0] < pair[1]));
+        for (number, compressed) in numbers.into_iter().zip(compressed) {
+            assert_eq!(SenderTxNumber::decompress(compressed), Ok(SenderTxNumber(number)));
+        }
+        assert_eq!(SenderTxNumber::decompress([1u8; 4]), Err(DatabaseError::DecodeError));
+    }
+}
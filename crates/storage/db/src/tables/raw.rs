@@ -54,6 +54,11 @@ impl<K: Key> RawKey<K> {
     pub fn key(&self) -> Result<K, DatabaseError> {
         K::decode(&self.key)
     }
+
+    /// Returns the encoded bytes of the key.
+    pub fn raw_key(&self) -> &[u8] {
+        &self.key
+    }
 }
 
 impl<K: Key> From<K> for RawKey<K> {
@@ -100,6 +105,16 @@ impl<V: Value> RawValue<V> {
     pub fn value(&self) -> Result<V, DatabaseError> {
         V::decompress(&self.value)
     }
+
+    /// Returns the compressed bytes of the value.
+    pub fn raw_value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Consumes the value, returning its compressed bytes.
+    pub fn into_raw_value(self) -> Vec<u8> {
+        self.value
+    }
 }
 
 impl AsRef<[u8]> for RawValue<Vec<u8>> {
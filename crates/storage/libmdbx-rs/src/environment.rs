@@ -1,11 +1,9 @@
 use crate::{
-    database::Database,
     error::{mdbx_result, Error, Result},
     flags::EnvironmentFlags,
     transaction::{RO, RW},
     Mode, Transaction, TransactionKind,
 };
-use byteorder::{ByteOrder, NativeEndian};
 use mem::size_of;
 use std::{
     ffi::CString,
@@ -189,28 +187,10 @@ where
     ///   freelist count is stored at the beginning of the value as `libc::size_t` in the native
     ///   byte order.
     ///
-    /// * It will create a read transaction to traverse the freelist database.
+    /// * It will create a read transaction to traverse the freelist database, see
+    ///   [Transaction::freelist](crate::Transaction::freelist).
     pub fn freelist(&self) -> Result<usize> {
-        let mut freelist: usize = 0;
-        let txn = self.begin_ro_txn()?;
-        let db = Database::freelist_db();
-        let cursor = txn.cursor(&db)?;
-
-        for result in cursor {
-            let (_key, value) = result?;
-            if value.len() < mem::size_of::<usize>() {
-                return Err(Error::Corrupted)
-            }
-
-            let s = &value[..mem::size_of::<usize>()];
-            if cfg!(target_pointer_width = "64") {
-                freelist += NativeEndian::read_u64(s) as usize;
-            } else {
-                freelist += NativeEndian::read_u32(s) as usize;
-            }
-        }
-
-        Ok(freelist)
+        self.begin_ro_txn()?.freelist()
     }
 }
 
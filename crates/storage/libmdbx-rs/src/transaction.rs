@@ -5,6 +5,7 @@ use crate::{
     flags::{DatabaseFlags, WriteFlags},
     Cursor, Error, Stat, TableObject,
 };
+use byteorder::{ByteOrder, NativeEndian};
 use ffi::{MDBX_txn_flags_t, MDBX_TXN_RDONLY, MDBX_TXN_READWRITE};
 use indexmap::IndexSet;
 use libc::{c_uint, c_void};
@@ -216,6 +217,30 @@ where
         }
     }
 
+    /// Retrieves the total number of pages on the freelist as seen by the transaction, see
+    /// [Environment::freelist].
+    pub fn freelist(&self) -> Result<usize> {
+        let mut freelist: usize = 0;
+        let db = Database::freelist_db();
+        let cursor = self.cursor(&db)?;
+
+        for result in cursor {
+            let (_key, value) = result?;
+            if value.len() < size_of::<usize>() {
+                return Err(Error::Corrupted)
+            }
+
+            let s = &value[..size_of::<usize>()];
+            if cfg!(target_pointer_width = "64") {
+                freelist += NativeEndian::read_u64(s) as usize;
+            } else {
+                freelist += NativeEndian::read_u32(s) as usize;
+            }
+        }
+
+        Ok(freelist)
+    }
+
     /// Open a new cursor on the given database.
     pub fn cursor<'txn>(&'txn self, db: &Database<'txn>) -> Result<Cursor<'txn, K>> {
         Cursor::new(self, db.dbi())
@@ -0,0 +1,488 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use reth_db::{
+    models::{RawTransactionRef, StoredBlockBodyIndices},
+    tables,
+    test_utils::create_test_rw_db,
+    transaction::{DbTx, DbTxMut},
+    DatabaseEnv,
+};
+use reth_interfaces::test_utils::generators::{
+    self, random_block_range, random_log, random_signed_tx, Rng,
+};
+use reth_primitives::{ChainSpec, ChainSpecBuilder, Header, Receipt, TxType, H256, MAINNET, U256};
+use reth_provider::{
+    BlockHashReader, BlockNumReader, BlockWriter, EvmEnvProvider, HeaderProvider, ProviderFactory,
+    ReceiptProvider, TransactionsProvider,
+};
+use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
+use std::sync::Arc;
+
+criterion_group!(
+    benches,
+    receipts,
+    headers,
+    parallel_headers,
+    descending_headers,
+    block_numbers,
+    get_many,
+    transactions,
+    transactions_raw,
+    evm_env,
+    insert_blocks
+);
+criterion_main!(benches);
+
+const DEFAULT_NUM_BLOCKS: u64 = 10_000;
+const DEFAULT_NUM_HEADERS: u64 = 100_000;
+const DEFAULT_NUM_HASHES: usize = 256;
+const GET_MANY_NUM_KEYS: usize = 1_000;
+const DEFAULT_NUM_TRANSACTIONS: u64 = 10_000;
+const TRANSACTIONS_PER_BLOCK: u64 = 100;
+const RAW_NUM_TRANSACTIONS: u64 = 50_000;
+const PARALLEL_NUM_HEADERS: u64 = 1_000_000;
+const PARALLEL_CHUNK_SIZE: u64 = 10_000;
+const DESCENDING_NUM_HEADERS: u64 = 64;
+const BATCH_THRESHOLD: usize = 64 * 1024 * 1024;
+
+fn receipts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Receipts");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    let factory = prepare_receipts(DEFAULT_NUM_BLOCKS);
+
+    group.bench_function("receipts_by_block", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for number in 0..DEFAULT_NUM_BLOCKS {
+                black_box(provider.receipts_by_block(number.into()).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("receipts_by_block_range", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.receipts_by_block_range(0..DEFAULT_NUM_BLOCKS).unwrap());
+        })
+    });
+}
+
+fn headers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Headers");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    let factory = prepare_headers(DEFAULT_NUM_HEADERS);
+
+    group.bench_function("headers_range + block_hash", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for (number, header) in (0..DEFAULT_NUM_HEADERS)
+                .zip(provider.headers_range(0..DEFAULT_NUM_HEADERS).unwrap())
+            {
+                black_box(header.seal(provider.block_hash(number).unwrap().unwrap()));
+            }
+        })
+    });
+
+    group.bench_function("sealed_headers_range", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.sealed_headers_range(0..DEFAULT_NUM_HEADERS).unwrap());
+        })
+    });
+}
+
+fn parallel_headers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ParallelHeaders");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    let factory = prepare_headers(PARALLEL_NUM_HEADERS);
+
+    group.bench_function("headers_range", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.headers_range(0..PARALLEL_NUM_HEADERS).unwrap());
+        })
+    });
+
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        group.bench_function(format!("par_headers_range ({threads} threads)"), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    black_box(
+                        factory
+                            .par_headers_range(0..PARALLEL_NUM_HEADERS, PARALLEL_CHUNK_SIZE)
+                            .unwrap(),
+                    );
+                })
+            })
+        });
+    }
+}
+
+fn descending_headers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DescendingHeaders");
+
+    let factory = prepare_headers(DEFAULT_NUM_HEADERS);
+    let tip = DEFAULT_NUM_HEADERS - 1;
+
+    group.bench_function("sealed_headers_range + reverse", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            let start = tip + 1 - DESCENDING_NUM_HEADERS;
+            let mut headers = provider.sealed_headers_range(start..=tip).unwrap();
+            headers.reverse();
+            black_box(headers);
+        })
+    });
+
+    group.bench_function("sealed_headers_descending", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.sealed_headers_descending(tip, DESCENDING_NUM_HEADERS).unwrap());
+        })
+    });
+}
+
+fn block_numbers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BlockNumbers");
+
+    let (factory, hashes) = prepare_header_numbers(DEFAULT_NUM_BLOCKS, DEFAULT_NUM_HASHES);
+
+    group.bench_function("block_number", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for hash in &hashes {
+                black_box(provider.block_number(*hash).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("block_numbers", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.block_numbers(&hashes).unwrap());
+        })
+    });
+}
+
+fn get_many(c: &mut Criterion) {
+    let mut group = c.benchmark_group("GetMany");
+
+    let (factory, hashes) = prepare_header_numbers(DEFAULT_NUM_BLOCKS, GET_MANY_NUM_KEYS);
+
+    group.bench_function("get", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for hash in &hashes {
+                black_box(provider.tx_ref().get::<tables::HeaderNumbers>(*hash).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("get_many", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.tx_ref().get_many::<tables::HeaderNumbers>(&hashes).unwrap());
+        })
+    });
+}
+
+fn transactions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Transactions");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    let factory = prepare_transactions(DEFAULT_NUM_TRANSACTIONS);
+    let num_blocks = DEFAULT_NUM_TRANSACTIONS / TRANSACTIONS_PER_BLOCK;
+
+    group.bench_function("transaction_by_id", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for id in 0..DEFAULT_NUM_TRANSACTIONS {
+                black_box(provider.transaction_by_id(id).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("transaction_by_id_no_hash", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for id in 0..DEFAULT_NUM_TRANSACTIONS {
+                black_box(provider.transaction_by_id_no_hash(id).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("transactions_by_block", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for number in 0..num_blocks {
+                black_box(provider.transactions_by_block(number.into()).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("transactions_by_block_no_hash", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for number in 0..num_blocks {
+                black_box(provider.transactions_by_block_no_hash(number.into()).unwrap());
+            }
+        })
+    });
+}
+
+fn transactions_raw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TransactionsRaw");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    let factory = prepare_transactions(RAW_NUM_TRANSACTIONS);
+    let num_blocks = RAW_NUM_TRANSACTIONS / TRANSACTIONS_PER_BLOCK;
+
+    group.bench_function("transactions_by_block_range", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.transactions_by_block_range(0..num_blocks).unwrap());
+        })
+    });
+
+    group.bench_function("transactions_by_block_range_raw", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            black_box(provider.transactions_by_block_range_raw(0..num_blocks).unwrap());
+        })
+    });
+
+    // only inspects the flags byte of every transaction, nothing is decoded
+    group.bench_function("transactions_by_block_range_raw (tx_type)", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for (_, transactions) in
+                provider.transactions_by_block_range_raw(0..num_blocks).unwrap()
+            {
+                for tx in &transactions {
+                    black_box(RawTransactionRef::new(tx).tx_type());
+                }
+            }
+        })
+    });
+}
+
+fn evm_env(c: &mut Criterion) {
+    let mut group = c.benchmark_group("EvmEnv");
+    // every iteration fills the envs of all headers
+    group.sample_size(10);
+
+    // Paris activated by total difficulty requires a total difficulty lookup per header, with a
+    // known fork block the chain spec alone decides.
+    for (name, chain_spec) in [
+        ("ttd", ChainSpecBuilder::mainnet().build()),
+        ("fork block", ChainSpecBuilder::mainnet().shanghai_activated().build()),
+    ] {
+        let (factory, headers) = prepare_env_headers(chain_spec, DEFAULT_NUM_BLOCKS);
+
+        group.bench_function(format!("fill_env_with_header ({name})"), |b| {
+            b.iter(|| {
+                let provider = factory.provider().unwrap();
+                for header in &headers {
+                    black_box(provider.env_with_header(header).unwrap());
+                }
+            })
+        });
+    }
+
+    // Read-only providers cache the resolved header of recently filled blocks, read-write
+    // providers resolve it on every call.
+    let (factory, _) = prepare_env_headers(ChainSpecBuilder::mainnet().build(), DEFAULT_NUM_BLOCKS);
+    let latest = DEFAULT_NUM_BLOCKS - 1;
+
+    group.bench_function("fill_env_at latest (uncached)", |b| {
+        b.iter(|| {
+            let provider = factory.provider_rw().unwrap();
+            for _ in 0..DEFAULT_NUM_HASHES {
+                let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+                black_box(provider.fill_env_at(&mut cfg, &mut block_env, latest.into()).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("fill_env_at latest (cached)", |b| {
+        b.iter(|| {
+            let provider = factory.provider().unwrap();
+            for _ in 0..DEFAULT_NUM_HASHES {
+                let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+                black_box(provider.fill_env_at(&mut cfg, &mut block_env, latest.into()).unwrap());
+            }
+        })
+    });
+}
+
+fn insert_blocks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InsertBlocks");
+    // every iteration writes all blocks into an empty database
+    group.sample_size(10);
+
+    let mut rng = generators::rng();
+    let blocks = random_block_range(&mut rng, 0..=DEFAULT_NUM_BLOCKS - 1, H256::zero(), 0..10)
+        .into_iter()
+        .map(|block| {
+            let senders = block.senders().unwrap();
+            (block, senders)
+        })
+        .collect::<Vec<_>>();
+
+    for (name, threshold) in [("unbuffered", None), ("batched", Some(BATCH_THRESHOLD))] {
+        group.bench_function(format!("insert_block ({name})"), |b| {
+            b.iter_batched(
+                || ProviderFactory::new(create_test_rw_db(), MAINNET.clone()),
+                |factory| {
+                    let provider = factory.provider_rw().unwrap();
+                    if let Some(threshold) = threshold {
+                        provider.enable_write_batching(threshold);
+                    }
+                    for (block, senders) in &blocks {
+                        provider.insert_block(block.clone(), Some(senders.clone())).unwrap();
+                    }
+                    black_box(provider.commit().unwrap());
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+}
+
+/// Writes hash to number entries for `num_blocks` blocks and returns `num_hashes` of the hashes
+/// in random order.
+fn prepare_header_numbers(
+    num_blocks: u64,
+    num_hashes: usize,
+) -> (ProviderFactory<Arc<DatabaseEnv>>, Vec<H256>) {
+    let mut rng = generators::rng();
+    let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+    let provider = factory.provider_rw().unwrap();
+    let mut hashes = Vec::with_capacity(num_blocks as usize);
+    for number in 0..num_blocks {
+        let hash = rng.gen();
+        provider.tx_ref().put::<tables::HeaderNumbers>(hash, number).unwrap();
+        hashes.push(hash);
+    }
+    provider.commit().unwrap();
+
+    let hashes =
+        (0..num_hashes).map(|_| hashes[rng.gen_range(0..hashes.len())]).collect::<Vec<_>>();
+    (factory, hashes)
+}
+
+/// Writes headers and canonical hashes for `num_headers` blocks.
+fn prepare_headers(num_headers: u64) -> ProviderFactory<Arc<DatabaseEnv>> {
+    let mut rng = generators::rng();
+    let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+    let provider = factory.provider_rw().unwrap();
+    for number in 0..num_headers {
+        let header = Header { number, gas_limit: rng.gen(), ..Default::default() };
+        provider.tx_ref().put::<tables::CanonicalHeaders>(number, rng.gen()).unwrap();
+        provider.tx_ref().put::<tables::Headers>(number, header).unwrap();
+    }
+    provider.commit().unwrap();
+
+    factory
+}
+
+/// Writes block body indices and receipts for `num_blocks` blocks with a random number of
+/// transactions each.
+fn prepare_receipts(num_blocks: u64) -> ProviderFactory<Arc<DatabaseEnv>> {
+    let mut rng = generators::rng();
+    let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+    let provider = factory.provider_rw().unwrap();
+    let mut next_tx_num = 0;
+    for number in 0..num_blocks {
+        let tx_count = rng.gen_range(0..10u64);
+        provider
+            .tx_ref()
+            .put::<tables::BlockBodyIndices>(
+                number,
+                StoredBlockBodyIndices { first_tx_num: next_tx_num, tx_count },
+            )
+            .unwrap();
+
+        let mut cumulative_gas_used = 0;
+        for tx_num in next_tx_num..next_tx_num + tx_count {
+            cumulative_gas_used += rng.gen_range(21_000..100_000u64);
+            let logs_count = rng.gen_range(0..4u8);
+            let receipt = Receipt {
+                tx_type: TxType::EIP1559,
+                success: true,
+                cumulative_gas_used,
+                logs: (0..logs_count).map(|_| random_log(&mut rng, None, Some(2))).collect(),
+            };
+            provider.tx_ref().put::<tables::Receipts>(tx_num, receipt).unwrap();
+        }
+        next_tx_num += tx_count;
+    }
+    provider.commit().unwrap();
+
+    factory
+}
+
+/// Writes `num_transactions` random signed transactions, grouped into blocks of
+/// [TRANSACTIONS_PER_BLOCK] transactions each.
+fn prepare_transactions(num_transactions: u64) -> ProviderFactory<Arc<DatabaseEnv>> {
+    let mut rng = generators::rng();
+    let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+    let provider = factory.provider_rw().unwrap();
+    for number in 0..num_transactions / TRANSACTIONS_PER_BLOCK {
+        provider
+            .tx_ref()
+            .put::<tables::BlockBodyIndices>(
+                number,
+                StoredBlockBodyIndices {
+                    first_tx_num: number * TRANSACTIONS_PER_BLOCK,
+                    tx_count: TRANSACTIONS_PER_BLOCK,
+                },
+            )
+            .unwrap();
+    }
+    for tx_num in 0..num_transactions {
+        let transaction = random_signed_tx(&mut rng);
+        provider.tx_ref().put::<tables::Transactions>(tx_num, transaction.into()).unwrap();
+    }
+    provider.commit().unwrap();
+
+    factory
+}
+
+/// Writes headers, canonical hashes and total difficulties for `num_headers` blocks and returns
+/// the headers.
+fn prepare_env_headers(
+    chain_spec: ChainSpec,
+    num_headers: u64,
+) -> (ProviderFactory<Arc<DatabaseEnv>>, Vec<Header>) {
+    let mut rng = generators::rng();
+    let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+    let provider = factory.provider_rw().unwrap();
+    let mut headers = Vec::with_capacity(num_headers as usize);
+    for number in 0..num_headers {
+        let header = Header { number, gas_limit: rng.gen(), ..Default::default() };
+        provider.tx_ref().put::<tables::CanonicalHeaders>(number, rng.gen()).unwrap();
+        provider.tx_ref().put::<tables::Headers>(number, header.clone()).unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::HeaderTD>(number, U256::from(rng.gen::<u64>()).into())
+            .unwrap();
+        headers.push(header);
+    }
+    provider.commit().unwrap();
+
+    (factory, headers)
+}
@@ -0,0 +1,162 @@
+//! Benchmarks of the provider hot paths against a database with a generated chain.
+//!
+//! The database is populated once with a [ChainFixture] and kept in the temp directory, later
+//! runs reuse it. Set `PROVIDER_BENCH_DB` to keep it somewhere else.
+//!
+//! New cases only need a name and a closure that takes a read-only provider, see
+//! [BenchChain::bench].
+
+use criterion::{
+    black_box, criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion,
+};
+use reth_db::{tables, test_utils::create_test_rw_db_with_path, transaction::DbTxMut, DatabaseEnv};
+use reth_interfaces::test_utils::ChainFixture;
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    BlockNumber, ChainSpecBuilder, H256,
+};
+use reth_provider::{
+    BlockNumReader, BlockReader, BlockWriter, DatabaseProviderRO, EvmEnvProvider, HeaderProvider,
+    ProviderFactory, ReceiptProvider, StageCheckpointWriter, TransactionsProvider,
+};
+use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
+use std::{path::PathBuf, sync::Arc};
+
+criterion_group!(benches, point_lookups, ranges, evm_env);
+criterion_main!(benches);
+
+/// The number of blocks of the generated chain, after genesis.
+const NUM_BLOCKS: u64 = 50_000;
+/// The seed of the generated chain, changing it requires a new database.
+const SEED: u64 = 0;
+/// The number of keys every point lookup iteration queries.
+const POINT_LOOKUPS: u64 = 1_000;
+/// The number of blocks of the transaction and receipt range queries.
+const BLOCK_RANGE: u64 = 1_000;
+
+fn point_lookups(c: &mut Criterion) {
+    let chain = BenchChain::open();
+    let mut group = c.benchmark_group("PointLookups");
+
+    let numbers = (0..POINT_LOOKUPS).map(|i| i * chain.tip / POINT_LOOKUPS).collect::<Vec<_>>();
+    chain.bench(&mut group, "header_by_number", |provider| {
+        for number in &numbers {
+            black_box(provider.header_by_number(*number).unwrap());
+        }
+    });
+    chain.bench(&mut group, "transaction_by_hash", |provider| {
+        for hash in &chain.tx_hashes {
+            black_box(provider.transaction_by_hash(*hash).unwrap());
+        }
+    });
+    chain.bench(&mut group, "transaction_by_hash_with_meta", |provider| {
+        for hash in &chain.tx_hashes {
+            black_box(provider.transaction_by_hash_with_meta(*hash).unwrap());
+        }
+    });
+    chain.bench(&mut group, "receipt_by_hash", |provider| {
+        for hash in &chain.tx_hashes {
+            black_box(provider.receipt_by_hash(*hash).unwrap());
+        }
+    });
+}
+
+fn ranges(c: &mut Criterion) {
+    let chain = BenchChain::open();
+    let mut group = c.benchmark_group("Ranges");
+    // every iteration reads the whole range
+    group.sample_size(10);
+
+    for len in [1_000, 10_000] {
+        let range = chain.tip + 1 - len..=chain.tip;
+        chain.bench(&mut group, &format!("sealed_headers_range ({len})"), |provider| {
+            black_box(provider.sealed_headers_range(range.clone()).unwrap());
+        });
+    }
+
+    let range = chain.tip + 1 - BLOCK_RANGE..=chain.tip;
+    chain.bench(&mut group, &format!("transactions_by_block_range ({BLOCK_RANGE})"), |provider| {
+        black_box(provider.transactions_by_block_range(range.clone()).unwrap());
+    });
+    chain.bench(&mut group, &format!("receipts_by_block_range ({BLOCK_RANGE})"), |provider| {
+        black_box(provider.receipts_by_block_range(range.clone()).unwrap());
+    });
+}
+
+fn evm_env(c: &mut Criterion) {
+    let chain = BenchChain::open();
+    let mut group = c.benchmark_group("EvmEnv");
+
+    chain.bench(&mut group, "fill_env_at latest", |provider| {
+        let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+        black_box(provider.fill_env_at(&mut cfg, &mut block_env, chain.tip.into()).unwrap());
+    });
+}
+
+/// A database with the generated chain that the benchmark cases read from.
+struct BenchChain {
+    factory: ProviderFactory<Arc<DatabaseEnv>>,
+    tip: BlockNumber,
+    /// Hashes of transactions spread over the chain, for point lookups by hash.
+    tx_hashes: Vec<H256>,
+}
+
+impl BenchChain {
+    /// Opens the cached database, populating it first if it is missing or incomplete.
+    fn open() -> Self {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().shanghai_activated().build());
+        let path = std::env::var_os("PROVIDER_BENCH_DB").map(PathBuf::from).unwrap_or_else(|| {
+            std::env::temp_dir().join(format!("reth-provider-bench-{NUM_BLOCKS}-{SEED}"))
+        });
+
+        let mut factory =
+            ProviderFactory::new(create_test_rw_db_with_path(&path), chain_spec.clone());
+        // the sync checkpoint is written last, so its presence marks a complete database
+        if factory.provider().unwrap().best_block_number().unwrap() != NUM_BLOCKS {
+            drop(factory);
+            let _ = std::fs::remove_dir_all(&path);
+            factory = ProviderFactory::new(create_test_rw_db_with_path(&path), chain_spec.clone());
+            populate(&factory, ChainFixture::new(chain_spec));
+        }
+
+        let provider = factory.provider().unwrap();
+        let tx_count = provider.block_body_indices(NUM_BLOCKS).unwrap().unwrap().next_tx_num();
+        let tx_hashes = (0..POINT_LOOKUPS)
+            .map(|i| provider.transaction_by_id(i * tx_count / POINT_LOOKUPS).unwrap().unwrap())
+            .map(|transaction| transaction.hash())
+            .collect();
+        drop(provider);
+
+        Self { factory, tip: NUM_BLOCKS, tx_hashes }
+    }
+
+    /// Adds a case to the group that runs the closure against a new read-only provider in every
+    /// iteration.
+    fn bench<F>(&self, group: &mut BenchmarkGroup<'_, WallTime>, name: &str, f: F)
+    where
+        F: Fn(&DatabaseProviderRO<'_, Arc<DatabaseEnv>>),
+    {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let provider = self.factory.provider().unwrap();
+                f(&provider);
+            })
+        });
+    }
+}
+
+/// Writes the blocks of the fixture, the receipts of their transfers and the sync checkpoint.
+fn populate(factory: &ProviderFactory<Arc<DatabaseEnv>>, fixture: ChainFixture) {
+    let blocks = fixture.blocks(NUM_BLOCKS).txs_per_block(0..5).seed(SEED).build();
+
+    let provider = factory.provider_rw().unwrap();
+    for block in blocks {
+        let receipts = ChainFixture::receipts(&block.body);
+        let body = provider.insert_block(block.block, Some(block.senders)).unwrap();
+        for (tx_num, receipt) in body.tx_num_range().zip(receipts) {
+            provider.tx_ref().put::<tables::Receipts>(tx_num, receipt).unwrap();
+        }
+    }
+    provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(NUM_BLOCKS)).unwrap();
+    provider.commit().unwrap();
+}
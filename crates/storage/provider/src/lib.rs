@@ -16,27 +16,37 @@
 //!
 //! ## Feature Flags
 //!
+//! - `serde`: Export receipts as JSON Lines
 //! - `test-utils`: Export utilities for testing
 
 /// Various provider traits.
 mod traits;
 pub use traits::{
-    AccountExtReader, AccountReader, BlockExecutionWriter, BlockExecutor, BlockHashReader,
-    BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt, BlockSource, BlockWriter,
-    BlockchainTreePendingStateProvider, CanonChainTracker, CanonStateNotification,
-    CanonStateNotificationSender, CanonStateNotifications, CanonStateSubscriptions, EvmEnvProvider,
-    ExecutorFactory, HashingWriter, HeaderProvider, HistoryWriter, PostStateDataProvider,
-    ReceiptProvider, ReceiptProviderIdExt, StageCheckpointReader, StageCheckpointWriter,
-    StateProvider, StateProviderBox, StateProviderFactory, StateRootProvider, StorageReader,
-    TransactionsProvider, WithdrawalsProvider,
+    AccountExtReader, AccountReader, BlockEnvOverrides, BlockExecutionWriter, BlockExecutor,
+    BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt, BlockSource,
+    BlockWriter, BlockchainTreePendingStateProvider, CanonChainTracker, CanonStateNotification,
+    CanonStateNotificationSender, CanonStateNotifications, CanonStateSubscriptions, EnvWithState,
+    EvmEnvProvider, ExecutionContext, ExecutorFactory, HashingWriter, HeaderProvider,
+    HistoryWriter, NextBlockAttributes, PostStateDataProvider, PruneCheckpointReader,
+    PruneCheckpointWriter, ReceiptProvider, ReceiptProviderIdExt, StageCheckpointReader,
+    StageCheckpointWriter, StateProvider, StateProviderBox, StateProviderFactory,
+    StateRootProvider, StorageReader, TransactionsProvider, WithdrawalsProvider,
 };
 
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
-    DatabaseProvider, DatabaseProviderRO, DatabaseProviderRW, HistoricalStateProvider,
-    HistoricalStateProviderRef, LatestStateProvider, LatestStateProviderRef, ProviderFactory,
+    head_from_provider, BlockAvailability, BlockFileExporter, BlockFileImporter, BlockFileReader,
+    ChainInconsistency, CommitMonitor, CommitStats, ConsistencyScope, DatabaseProvider,
+    DatabaseProviderMetrics, DatabaseProviderRO, DatabaseProviderRW, DatabaseStats, ExportChunk,
+    ExportManifest, ForkIdValidation, HeaderSnapshotImport, HistoricalStateProvider,
+    HistoricalStateProviderRef, HistoryAvailability, ImportOptions, ImportOutcome, Inconsistency,
+    LatestStateProvider, LatestStateProviderRef, OverlayProvider, PendingBlock, PendingBlocks,
+    ProviderFactory, PruneProgress, ReceiptVerification, ReceiptsIter, ReorgOutcome,
+    SnapshotProvider, TableStats, TableWriteStats,
 };
+#[cfg(feature = "serde")]
+pub use providers::{ReceiptExportFilter, ReceiptExportOutcome, ReceiptExporter, ReceiptRecord};
 
 /// Execution result
 pub mod post_state;
@@ -51,3 +61,7 @@ pub use reth_interfaces::provider::ProviderError;
 
 pub mod chain;
 pub use chain::{Chain, DisplayBlocksChain};
+
+// Only used by the benchmarks.
+#[cfg(test)]
+use criterion as _;
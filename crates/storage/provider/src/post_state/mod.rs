@@ -7,8 +7,10 @@ use reth_db::{
     DatabaseError as DbError,
 };
 use reth_primitives::{
-    bloom::logs_bloom, keccak256, proofs::calculate_receipt_root_ref, Account, Address,
-    BlockNumber, Bloom, Bytecode, Log, Receipt, StorageEntry, H256, U256,
+    bloom::logs_bloom,
+    keccak256,
+    proofs::{calculate_receipt_root_and_logs_bloom, calculate_receipt_root_ref},
+    Account, Address, BlockNumber, Bloom, Bytecode, Log, Receipt, StorageEntry, H256, U256,
 };
 use reth_trie::{
     hashed_cursor::{HashedPostState, HashedPostStateCursorFactory, HashedStorage},
@@ -181,12 +183,19 @@ impl PostState {
     }
 
     /// Returns the receipt root for all recorded receipts.
-    /// TODO: This function hides an expensive operation (bloom). We should probably make it more
-    /// explicit.
+    ///
+    /// This calculates the bloom of every receipt, use [PostState::receipts_root_and_logs_bloom]
+    /// if the logs bloom is needed as well.
     pub fn receipts_root(&self, block: BlockNumber) -> H256 {
         calculate_receipt_root_ref(self.receipts(block))
     }
 
+    /// Returns the receipt root and the logs bloom for all recorded receipts, calculating the
+    /// bloom of every receipt once.
+    pub fn receipts_root_and_logs_bloom(&self, block: BlockNumber) -> (H256, Bloom) {
+        calculate_receipt_root_and_logs_bloom(self.receipts(block))
+    }
+
     /// Hash all changed accounts and storage entries that are currently stored in the post state.
     ///
     /// # Returns
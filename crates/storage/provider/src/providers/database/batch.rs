@@ -0,0 +1,124 @@
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    table::{Decode, Decompress, Table},
+    transaction::{DbTx, DbTxMut},
+    DatabaseError, RawKey, RawTable, RawValue,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug, Formatter},
+};
+
+/// Writes the encoded rows of a table into the transaction.
+type WriteRows<TX> = fn(&TX, BTreeMap<Vec<u8>, Vec<u8>>) -> Result<(), DatabaseError>;
+
+/// Buffered rows of a table, sorted by their encoded keys.
+struct TableBatch<TX> {
+    rows: BTreeMap<Vec<u8>, Vec<u8>>,
+    write: WriteRows<TX>,
+}
+
+/// Buffers the puts of a read-write [DatabaseProvider](super::DatabaseProvider) per table until
+/// they are written in bulk.
+///
+/// Rows are kept encoded and sorted by their encoded keys, the order of the database, so rows past
+/// the last key of a table are written with append-mode cursors. The buffer is written once the
+/// encoded rows exceed the threshold, or on
+/// [DatabaseProvider::flush](super::DatabaseProvider::flush).
+///
+/// Only tables without duplicate keys can be buffered.
+pub(super) struct BatchWriter<TX> {
+    tables: HashMap<&'static str, TableBatch<TX>>,
+    /// Size of the encoded rows in bytes.
+    size: usize,
+    /// Size in bytes from which on the rows are written.
+    threshold: usize,
+}
+
+impl<TX> BatchWriter<TX> {
+    /// Creates an empty buffer that is written once its rows exceed `threshold` bytes.
+    pub(super) fn new(threshold: usize) -> Self {
+        Self { tables: HashMap::new(), size: 0, threshold }
+    }
+
+    /// Returns `true` if the buffered rows exceed the threshold.
+    pub(super) fn is_full(&self) -> bool {
+        self.size > self.threshold
+    }
+
+    /// Buffers the encoded row, replacing a buffered row with the same key.
+    pub(super) fn put<'a, T: Table>(&mut self, key: RawKey<T::Key>, value: RawValue<T::Value>)
+    where
+        TX: DbTxMut<'a> + DbTx<'a>,
+    {
+        let table = self
+            .tables
+            .entry(T::NAME)
+            .or_insert_with(|| TableBatch { rows: BTreeMap::new(), write: write_rows::<TX, T> });
+        let (key, value) = (key.raw_key().to_vec(), value.into_raw_value());
+        let key_len = key.len();
+        self.size += key_len + value.len();
+        if let Some(replaced) = table.rows.insert(key, value) {
+            self.size -= key_len + replaced.len();
+        }
+    }
+
+    /// Returns the buffered value of the key.
+    pub(super) fn get<T: Table>(&self, key: &RawKey<T::Key>) -> Option<&[u8]> {
+        self.tables.get(T::NAME)?.rows.get(key.raw_key()).map(Vec::as_slice)
+    }
+
+    /// Returns the greatest buffered encoded key of the table.
+    pub(super) fn last_key<T: Table>(&self) -> Option<&[u8]> {
+        self.tables.get(T::NAME)?.rows.keys().next_back().map(Vec::as_slice)
+    }
+
+    /// Writes the buffered rows of the table into the transaction and removes them from the
+    /// buffer.
+    pub(super) fn write_table<T: Table>(&mut self, tx: &TX) -> Result<(), DatabaseError> {
+        let Some(table) = self.tables.remove(T::NAME) else { return Ok(()) };
+        self.size -= table.rows.iter().map(|(key, value)| key.len() + value.len()).sum::<usize>();
+        (table.write)(tx, table.rows)
+    }
+
+    /// Writes all buffered rows into the transaction and empties the buffer.
+    pub(super) fn write(&mut self, tx: &TX) -> Result<(), DatabaseError> {
+        self.size = 0;
+        for (_, table) in self.tables.drain() {
+            (table.write)(tx, table.rows)?;
+        }
+        Ok(())
+    }
+}
+
+impl<TX> Debug for BatchWriter<TX> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rows = self.tables.iter().map(|(name, table)| (name, table.rows.len()));
+        f.debug_struct("BatchWriter")
+            .field("rows", &rows.collect::<HashMap<_, _>>())
+            .field("size", &self.size)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+/// Writes the rows past the last key of the table with an append-mode cursor, and upserts the
+/// others.
+fn write_rows<'a, TX: DbTxMut<'a> + DbTx<'a>, T: Table>(
+    tx: &TX,
+    rows: BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Result<(), DatabaseError> {
+    let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+    let last = cursor.last()?.map(|(key, _)| key.raw_key().to_vec());
+    for (key, value) in rows {
+        let append = last.as_ref().map_or(true, |last| key > *last);
+        let (key, value) =
+            (RawKey::<T::Key>::decode(key)?, RawValue::<T::Value>::decompress(value)?);
+        if append {
+            cursor.append(key, value)?;
+        } else {
+            cursor.upsert(key, value)?;
+        }
+    }
+    Ok(())
+}
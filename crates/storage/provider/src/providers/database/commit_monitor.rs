@@ -0,0 +1,151 @@
+use super::CommitStats;
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::warn;
+
+/// Default duration above which a commit is reported as slow by a [CommitMonitor].
+pub const DEFAULT_SLOW_COMMIT_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Default number of recent commit durations kept by a [CommitMonitor].
+pub const DEFAULT_RECENT_COMMITS: usize = 64;
+
+/// Keeps the durations of the last commits of read-write providers, and warns with the written
+/// rows of commits that take longer than a threshold.
+///
+/// Clones share the same durations, a single instance is handed to every read-write provider of a
+/// [ProviderFactory](super::ProviderFactory).
+#[derive(Debug, Clone)]
+pub struct CommitMonitor {
+    /// Duration above which a commit is slow.
+    slow_threshold: Duration,
+    /// Number of recent commit durations that are kept.
+    capacity: usize,
+    inner: Arc<CommitMonitorInner>,
+}
+
+#[derive(Debug, Default)]
+struct CommitMonitorInner {
+    /// Durations of the last commits, the oldest first.
+    recent: Mutex<VecDeque<Duration>>,
+    /// Number of commits above the threshold.
+    slow_commits: AtomicU64,
+}
+
+impl Default for CommitMonitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_COMMIT_THRESHOLD, DEFAULT_RECENT_COMMITS)
+    }
+}
+
+impl CommitMonitor {
+    /// Creates a monitor that reports commits above `slow_threshold` and keeps the durations of
+    /// the last `capacity` commits.
+    pub fn new(slow_threshold: Duration, capacity: usize) -> Self {
+        let inner = CommitMonitorInner {
+            recent: Mutex::new(VecDeque::with_capacity(capacity)),
+            slow_commits: AtomicU64::new(0),
+        };
+        Self { slow_threshold, capacity, inner: Arc::new(inner) }
+    }
+
+    /// Returns the duration above which a commit is slow.
+    pub fn slow_threshold(&self) -> Duration {
+        self.slow_threshold
+    }
+
+    /// Returns the durations of the last commits, the oldest first.
+    pub fn recent_durations(&self) -> Vec<Duration> {
+        self.inner.recent.lock().iter().copied().collect()
+    }
+
+    /// Returns the number of commits that took longer than the threshold.
+    pub fn slow_commits(&self) -> u64 {
+        self.inner.slow_commits.load(Ordering::Relaxed)
+    }
+
+    /// Records the duration of the commit, warning with its written rows if it is slow.
+    pub(crate) fn record(&self, stats: &CommitStats) {
+        if self.capacity > 0 {
+            let mut recent = self.inner.recent.lock();
+            if recent.len() == self.capacity {
+                recent.pop_front();
+            }
+            recent.push_back(stats.duration);
+        }
+
+        if stats.duration > self.slow_threshold {
+            self.inner.slow_commits.fetch_add(1, Ordering::Relaxed);
+            let mut tables = stats.tables.iter().collect::<Vec<_>>();
+            tables.sort_unstable_by_key(|(table, _)| **table);
+            warn!(
+                target: "provider::commit",
+                duration = ?stats.duration,
+                threshold = ?self.slow_threshold,
+                ?tables,
+                "Slow database commit"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockWriter, ProviderFactory};
+    use reth_db::{tables, test_utils::create_test_rw_db};
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_primitives::{H256, MAINNET};
+
+    #[test]
+    fn records_commits() {
+        let monitor = CommitMonitor::new(Duration::MAX, 2);
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone())
+            .with_commit_monitor(monitor.clone());
+
+        let mut rng = generators::rng();
+        let mut parent = H256::zero();
+        let mut durations = Vec::new();
+        for number in 0..3 {
+            let provider_rw = factory.provider_rw().unwrap();
+            let block = random_block(&mut rng, number, Some(parent), Some(1), Some(0));
+            parent = block.hash();
+            provider_rw.insert_block(block, None).unwrap();
+            durations.push(provider_rw.commit().unwrap().duration);
+        }
+
+        // only the last commits are kept, none of them is slow
+        assert_eq!(factory.commit_monitor().recent_durations(), durations[1..]);
+        assert_eq!(monitor.slow_commits(), 0);
+
+        // every commit is slow without a threshold
+        let factory = factory.with_commit_monitor(CommitMonitor::new(Duration::ZERO, 2));
+        let provider_rw = factory.provider_rw().unwrap();
+        let block = random_block(&mut rng, 3, Some(parent), Some(1), Some(0));
+        provider_rw.insert_block(block, None).unwrap();
+        let stats = provider_rw.commit().unwrap();
+        assert_eq!(stats.tables[tables::Headers::const_name()].puts, 1);
+        assert_eq!(factory.commit_monitor().recent_durations(), vec![stats.duration]);
+        assert_eq!(factory.commit_monitor().slow_commits(), 1);
+        assert_eq!(monitor.recent_durations().len(), 2);
+    }
+
+    #[test]
+    fn slow_commit() {
+        let monitor = CommitMonitor::new(Duration::from_millis(100), 0);
+        let fast = CommitStats { duration: Duration::from_millis(100), ..Default::default() };
+        monitor.record(&fast);
+        assert_eq!(monitor.slow_commits(), 0);
+
+        let slow = CommitStats { duration: Duration::from_secs(2), ..Default::default() };
+        monitor.record(&slow);
+        assert_eq!(monitor.slow_commits(), 1);
+        assert!(monitor.recent_durations().is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+use super::DatabaseProvider;
+use crate::TransactionsProvider;
+use reth_db::transaction::DbTx;
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{keccak256, BlockNumber, SealedBlock, H256};
+use reth_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use std::{
+    io::{self, Read, Write},
+    ops::RangeInclusive,
+};
+
+/// Default number of blocks a [BlockFileExporter] buffers and checksums at once.
+pub const DEFAULT_EXPORT_CHUNK_SIZE: u64 = 128;
+
+/// Checksum of consecutive blocks of a block file.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ExportChunk {
+    /// Number of the first block of the chunk.
+    pub first_block: BlockNumber,
+    /// Number of blocks in the chunk.
+    pub blocks: u64,
+    /// Length of the chunk in the block file, including the length prefixes.
+    pub len: u64,
+    /// Keccak-256 hash of the bytes of the chunk.
+    pub checksum: H256,
+}
+
+/// Description of a block file written by a [BlockFileExporter].
+///
+/// The manifest is RLP encoded and written alongside the block file.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ExportManifest {
+    /// Number of the first exported block.
+    pub first_block: BlockNumber,
+    /// Number of the last exported block.
+    pub last_block: BlockNumber,
+    /// Genesis hash of the chain the blocks belong to.
+    pub genesis_hash: H256,
+    /// Number of exported blocks.
+    pub count: u64,
+    /// The chunks of the block file, in order.
+    pub chunks: Vec<ExportChunk>,
+}
+
+/// Exports a segment of the canonical chain to a block file.
+///
+/// Every block is written as a big-endian `u32` length followed by the RLP encoding of the
+/// [SealedBlock]. The blocks are assembled one at a time from
+/// [DatabaseProvider::sealed_headers_range_iter] and written in chunks of
+/// [DEFAULT_EXPORT_CHUNK_SIZE] blocks, so at most one chunk is held in memory regardless of the
+/// size of the range.
+///
+/// The blocks can be read back with a [BlockFileReader].
+#[derive(Debug)]
+pub struct BlockFileExporter<'a, 'this, TX: DbTx<'this>> {
+    /// The provider the blocks are read from.
+    provider: &'a DatabaseProvider<'this, TX>,
+    /// Number of blocks per chunk.
+    chunk_size: u64,
+}
+
+impl<'a, 'this, TX: DbTx<'this>> BlockFileExporter<'a, 'this, TX> {
+    /// Creates an exporter reading from the provider.
+    pub fn new(provider: &'a DatabaseProvider<'this, TX>) -> Self {
+        Self { provider, chunk_size: DEFAULT_EXPORT_CHUNK_SIZE }
+    }
+
+    /// Sets the number of blocks per chunk, at least one.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Writes the blocks of the range to `blocks` and the RLP encoded [ExportManifest] to
+    /// `manifest`.
+    ///
+    /// `on_progress` is called with the number of the last written block after every chunk.
+    ///
+    /// Fails with [ProviderError::HeaderNotFound] if a block of the range is missing.
+    pub fn export(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        mut blocks: impl Write,
+        mut manifest: impl Write,
+        mut on_progress: impl FnMut(BlockNumber),
+    ) -> Result<ExportManifest> {
+        let mut export = ExportManifest {
+            first_block: *range.start(),
+            last_block: *range.end(),
+            genesis_hash: self.provider.chain_spec().genesis_hash(),
+            count: 0,
+            chunks: Vec::new(),
+        };
+
+        let mut chunk = Vec::new();
+        let mut chunk_start = *range.start();
+        for header in self.provider.sealed_headers_range_iter(range.clone())? {
+            let header = header?;
+            let number = header.number;
+            let expected = range.start() + export.count;
+            if number != expected {
+                return Err(ProviderError::HeaderNotFound(expected.into()).into())
+            }
+
+            let (ommers, withdrawals) =
+                self.provider.read_block_ommers_and_withdrawals(number, header.timestamp)?;
+            let body = self
+                .provider
+                .transactions_by_block(number.into())?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+            let block = SealedBlock { header, body, ommers, withdrawals };
+
+            let len = block.length();
+            let prefix = u32::try_from(len)
+                .map_err(|_| ProviderError::BlockFile(format!("block #{number} is too large")))?;
+            chunk.extend_from_slice(&prefix.to_be_bytes());
+            block.encode(&mut chunk);
+            export.count += 1;
+
+            if number - chunk_start + 1 == self.chunk_size {
+                export.chunks.push(write_chunk(&mut blocks, &mut chunk, chunk_start, number)?);
+                on_progress(number);
+                chunk_start = number + 1;
+            }
+        }
+
+        if !range.is_empty() && export.count < range.end() - range.start() + 1 {
+            let missing = range.start() + export.count;
+            return Err(ProviderError::HeaderNotFound(missing.into()).into())
+        }
+        if !chunk.is_empty() {
+            let last = *range.end();
+            export.chunks.push(write_chunk(&mut blocks, &mut chunk, chunk_start, last)?);
+            on_progress(last);
+        }
+
+        let mut encoded = Vec::new();
+        export.encode(&mut encoded);
+        blocks.flush().map_err(io_error)?;
+        manifest.write_all(&encoded).and_then(|_| manifest.flush()).map_err(io_error)?;
+        Ok(export)
+    }
+}
+
+/// Writes the chunk of blocks `first..=last` and clears the buffer.
+fn write_chunk(
+    out: &mut impl Write,
+    chunk: &mut Vec<u8>,
+    first: BlockNumber,
+    last: BlockNumber,
+) -> Result<ExportChunk> {
+    out.write_all(chunk).map_err(io_error)?;
+    let written = ExportChunk {
+        first_block: first,
+        blocks: last - first + 1,
+        len: chunk.len() as u64,
+        checksum: keccak256(chunk.as_slice()),
+    };
+    chunk.clear();
+    Ok(written)
+}
+
+pub(super) fn io_error(err: io::Error) -> ProviderError {
+    ProviderError::BlockFile(err.to_string())
+}
+
+/// Reads the blocks of a block file written by a [BlockFileExporter], one at a time.
+///
+/// Records that can't be decoded fail with [ProviderError::CorruptBlockFile] and the byte offset
+/// of the record.
+#[derive(Debug)]
+pub struct BlockFileReader<R> {
+    /// The block file.
+    reader: R,
+    /// Buffer of the current block.
+    buf: Vec<u8>,
+    /// Byte offset of the next record.
+    offset: u64,
+}
+
+impl<R: Read> BlockFileReader<R> {
+    /// Creates a reader of the block file.
+    pub fn new(reader: R) -> Self {
+        Self { reader, buf: Vec::new(), offset: 0 }
+    }
+
+    /// Returns the byte offset of the next record in the file.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the encoded block of the last read record, without its length prefix.
+    pub(super) fn last_record(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Reads the next block, `None` at the end of the file.
+    fn read_block(&mut self) -> Result<Option<SealedBlock>> {
+        let mut prefix = [0; 4];
+        let mut filled = 0;
+        while filled < prefix.len() {
+            match self.reader.read(&mut prefix[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(self.corrupt("truncated length prefix")),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(io_error(err).into()),
+            }
+        }
+
+        // don't trust the prefix with the allocation, a corrupt one can be up to 4 GiB
+        let len = u32::from_be_bytes(prefix) as u64;
+        self.buf.clear();
+        (&mut self.reader).take(len).read_to_end(&mut self.buf).map_err(io_error)?;
+        if (self.buf.len() as u64) < len {
+            return Err(self.corrupt("truncated block"))
+        }
+
+        let mut buf = self.buf.as_slice();
+        let block = match SealedBlock::decode(&mut buf) {
+            Ok(block) if buf.is_empty() => block,
+            Ok(_) => return Err(self.corrupt("trailing bytes after block")),
+            Err(err) => return Err(self.corrupt(&format!("invalid block: {err}"))),
+        };
+        self.offset += prefix.len() as u64 + len;
+        Ok(Some(block))
+    }
+
+    /// Returns the error for a bad record at the current offset.
+    fn corrupt(&self, reason: &str) -> reth_interfaces::Error {
+        corrupt_record(self.offset, ProviderError::BlockFile(reason.to_string()))
+    }
+}
+
+/// Returns the error for a bad record at the offset of a block file.
+pub(super) fn corrupt_record(offset: u64, error: ProviderError) -> reth_interfaces::Error {
+    ProviderError::CorruptBlockFile { offset, error: Box::new(error) }.into()
+}
+
+impl<R: Read> Iterator for BlockFileReader<R> {
+    type Item = Result<SealedBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_block().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::blocks::insert_blocks, ProviderFactory};
+    use reth_db::test_utils::create_test_rw_db;
+    use reth_interfaces::test_utils::ChainFixture;
+    use reth_primitives::{ChainSpecBuilder, ForkCondition, Hardfork, MAINNET, U256};
+    use std::sync::Arc;
+
+    #[test]
+    fn export_round_trip() {
+        // the merge happens at block 100 and Shanghai at block 250
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::mainnet()
+                .london_activated()
+                .paris_at_ttd(MAINNET.genesis().difficulty * U256::from(100))
+                .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(250 * 12))
+                .build(),
+        );
+        let blocks = ChainFixture::new(chain_spec.clone()).blocks(500).txs_per_block(0..4).build();
+        let factory = ProviderFactory::new(create_test_rw_db(), chain_spec.clone());
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_blocks(&provider_rw, &blocks).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let (mut file, mut manifest, mut progress) = (Vec::new(), Vec::new(), Vec::new());
+        let export = BlockFileExporter::new(&provider)
+            .with_chunk_size(64)
+            .export(0..=500, &mut file, &mut manifest, |number| progress.push(number))
+            .unwrap();
+
+        assert_eq!(export.genesis_hash, chain_spec.genesis_hash());
+        assert_eq!((export.first_block, export.last_block, export.count), (0, 500, 501));
+        assert_eq!(progress, vec![63, 127, 191, 255, 319, 383, 447, 500]);
+        assert_eq!(ExportManifest::decode(&mut manifest.as_slice()), Ok(export.clone()));
+
+        let mut offset = 0;
+        for chunk in &export.chunks {
+            let end = offset + chunk.len as usize;
+            assert_eq!(keccak256(&file[offset..end]), chunk.checksum);
+            offset = end;
+        }
+        assert_eq!(offset, file.len());
+
+        let read = BlockFileReader::new(file.as_slice()).collect::<Result<Vec<_>>>().unwrap();
+        let expected = blocks.into_iter().map(|block| block.block).collect::<Vec<_>>();
+        assert_eq!(read, expected);
+        assert!(read.iter().any(|block| block.withdrawals.as_ref().is_some_and(|w| !w.is_empty())));
+    }
+
+    #[test]
+    fn export_missing_block() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        let blocks = ChainFixture::new(chain_spec.clone()).blocks(5).build();
+        let factory = ProviderFactory::new(create_test_rw_db(), chain_spec);
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_blocks(&provider_rw, &blocks).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            BlockFileExporter::new(&provider).export(3..=7, io::sink(), io::sink(), |_| {}),
+            Err(ProviderError::HeaderNotFound(6.into()).into())
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use super::{
+    export::{corrupt_record, io_error},
+    DatabaseProvider,
+};
+use crate::{BlockNumReader, HeaderProvider};
+use reth_db::{
+    cursor::DbCursorRO,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{keccak256, BlockNumHash, BlockNumber, Header, SealedHeader, U256};
+use reth_rlp::{Decodable, Encodable};
+use std::{
+    io::{self, Read, Write},
+    ops::RangeInclusive,
+};
+
+/// Magic bytes at the start of a header snapshot, the last byte is the version of the format.
+pub const HEADER_SNAPSHOT_MAGIC: [u8; 4] = *b"RHS\x01";
+
+/// Number of headers [DatabaseProvider::import_header_snapshot] appends at once.
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+/// Result of a [DatabaseProvider::import_header_snapshot].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderSnapshotImport {
+    /// Number of headers of the snapshot that were already stored and matched.
+    pub verified: u64,
+    /// Number of appended headers.
+    pub imported: u64,
+}
+
+impl<'this, TX: DbTx<'this>> DatabaseProvider<'this, TX> {
+    /// Writes the canonical headers of the range and their total difficulties as a header snapshot.
+    ///
+    /// The snapshot starts with the [HEADER_SNAPSHOT_MAGIC], followed by one record per header: a
+    /// big-endian `u32` length, the RLP encoded header and the total difficulty as 32 big-endian
+    /// bytes. The headers are streamed, the memory use doesn't depend on the size of the range.
+    ///
+    /// Returns the number of written headers, fails with [ProviderError::HeaderNotFound] if a
+    /// header of the range is missing.
+    pub fn export_header_snapshot(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        mut writer: impl Write,
+    ) -> Result<u64> {
+        writer.write_all(&HEADER_SNAPSHOT_MAGIC).map_err(io_error)?;
+
+        let mut td_cursor = self.tx_ref().cursor_read::<tables::HeaderTD>()?;
+        let mut tds = td_cursor.walk_range(range.clone())?;
+        let mut record = Vec::new();
+        let mut count = 0;
+        for header in self.headers_range_iter(range.clone())? {
+            let header = header?;
+            let expected = range.start() + count;
+            if header.number != expected {
+                return Err(ProviderError::HeaderNotFound(expected.into()).into())
+            }
+            let td = match tds.next().transpose()? {
+                Some((number, td)) if number == expected => td.0,
+                _ => return Err(ProviderError::TotalDifficultyNotFound { number: expected }.into()),
+            };
+
+            record.clear();
+            header.encode(&mut record);
+            let len = (record.len() as u32).to_be_bytes();
+            record.extend_from_slice(&td.to_be_bytes::<32>());
+            writer.write_all(&len).and_then(|_| writer.write_all(&record)).map_err(io_error)?;
+            count += 1;
+        }
+        if !range.is_empty() && count < range.end() - range.start() + 1 {
+            return Err(ProviderError::HeaderNotFound((range.start() + count).into()).into())
+        }
+
+        writer.flush().map_err(io_error)?;
+        Ok(count)
+    }
+}
+
+impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
+    /// Imports a header snapshot written by [DatabaseProvider::export_header_snapshot].
+    ///
+    /// The snapshot has to start at the genesis header of the chain spec if the database is empty,
+    /// and at or below the canonical tip otherwise. Headers that are already stored have to match
+    /// the stored ones byte for byte, the remaining headers are appended with
+    /// [DatabaseProvider::append_sealed_headers]. The parent hashes are checked and the total
+    /// difficulties of the snapshot are checked against the ones accumulated from the header
+    /// difficulties, so importing the same snapshot again after an interruption resumes it.
+    ///
+    /// Records that are malformed or fail a check are reported with
+    /// [ProviderError::CorruptBlockFile] and their byte offset. Nothing is committed, the headers
+    /// before the bad record are written to the transaction.
+    pub fn import_header_snapshot(&self, reader: impl Read) -> Result<HeaderSnapshotImport> {
+        let mut reader = SnapshotReader::new(reader)?;
+        let tip = self.last_canonical_num_hash()?;
+        let mut outcome = HeaderSnapshotImport::default();
+        let mut parent: Option<(BlockNumHash, U256)> = None;
+        let mut batch = Vec::new();
+
+        while let Some((offset, encoded, td)) = reader.next_record()? {
+            let corrupt = |error| corrupt_record(offset, error);
+            let mut buf = encoded.as_slice();
+            let header = match Header::decode(&mut buf) {
+                Ok(header) if buf.is_empty() => SealedHeader::new(header, keccak256(&encoded)),
+                Ok(_) => return Err(corrupt(ProviderError::BlockFile("trailing bytes".into()))),
+                Err(err) => {
+                    return Err(corrupt(ProviderError::BlockFile(format!("invalid header: {err}"))))
+                }
+            };
+            let number = header.number;
+
+            // the records follow each other, the first one has to attach to the stored chain
+            let expected = match parent {
+                Some((parent, _)) => parent.number + 1,
+                None => tip.map_or(0, |tip| number.min(tip.number + 1)),
+            };
+            if number != expected {
+                return Err(corrupt(ProviderError::NonContiguousBlock { number, expected }))
+            }
+            let parent_td = match parent {
+                Some((parent, _)) if parent.hash != header.parent_hash => {
+                    return Err(corrupt(ProviderError::ParentHashMismatch {
+                        number,
+                        expected: parent.hash,
+                        got: header.parent_hash,
+                    }))
+                }
+                Some((_, td)) => td,
+                None => self.parent_of_first_record(&header).map_err(|error| match error {
+                    reth_interfaces::Error::Provider(error) => corrupt(error),
+                    error => error,
+                })?,
+            };
+            let expected_td = parent_td + header.difficulty;
+            if td != expected_td {
+                return Err(corrupt(ProviderError::TotalDifficultyMismatch {
+                    number,
+                    expected: expected_td,
+                    got: td,
+                }))
+            }
+
+            parent = Some((header.num_hash(), td));
+            if tip.is_some_and(|tip| number <= tip.number) {
+                let stored = self
+                    .header_by_number(number)?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))?;
+                let mut stored_encoded = Vec::with_capacity(encoded.len());
+                stored.encode(&mut stored_encoded);
+                if stored_encoded != encoded {
+                    return Err(corrupt(ProviderError::BlockConflict {
+                        number,
+                        existing: keccak256(&stored_encoded),
+                        new: header.hash(),
+                    }))
+                }
+                outcome.verified += 1;
+            } else {
+                batch.push(header);
+                if batch.len() == IMPORT_BATCH_SIZE {
+                    self.append_sealed_headers(std::mem::take(&mut batch))?;
+                }
+                outcome.imported += 1;
+            }
+        }
+
+        self.append_sealed_headers(batch)?;
+        Ok(outcome)
+    }
+
+    /// Returns the total difficulty of the parent of the first record, which is either stored or
+    /// absent for the genesis header. A genesis header has to be the one of the chain spec.
+    fn parent_of_first_record(&self, header: &SealedHeader) -> Result<U256> {
+        let Some(parent_number) = header.number.checked_sub(1) else {
+            let expected = self.chain_spec.genesis_hash();
+            if header.hash() != expected {
+                return Err(ProviderError::GenesisMismatch { expected, got: header.hash() }.into())
+            }
+            return Ok(U256::ZERO)
+        };
+        let parent_hash = self
+            .tx_ref()
+            .get::<tables::CanonicalHeaders>(parent_number)?
+            .ok_or(ProviderError::HeaderNotFound(parent_number.into()))?;
+        if parent_hash != header.parent_hash {
+            return Err(ProviderError::ParentHashMismatch {
+                number: header.number,
+                expected: parent_hash,
+                got: header.parent_hash,
+            }
+            .into())
+        }
+        Ok(self
+            .tx_ref()
+            .get::<tables::HeaderTD>(parent_number)?
+            .ok_or(ProviderError::TotalDifficultyNotFound { number: parent_number })?
+            .0)
+    }
+}
+
+/// Reads the records of a header snapshot, keeping track of their offsets.
+struct SnapshotReader<R> {
+    reader: R,
+    offset: u64,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Checks the magic bytes at the start of the snapshot.
+    fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0; HEADER_SNAPSHOT_MAGIC.len()];
+        match reader.read_exact(&mut magic) {
+            Ok(()) if magic == HEADER_SNAPSHOT_MAGIC => {}
+            Ok(()) => return Err(corrupt_record(0, ProviderError::BlockFile("bad magic".into()))),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(corrupt_record(0, ProviderError::BlockFile("truncated magic".into())))
+            }
+            Err(err) => return Err(io_error(err).into()),
+        }
+        Ok(Self { reader, offset: magic.len() as u64 })
+    }
+
+    /// Returns the offset, the encoded header and the total difficulty of the next record, `None`
+    /// at the end of the snapshot.
+    fn next_record(&mut self) -> Result<Option<(u64, Vec<u8>, U256)>> {
+        let offset = self.offset;
+        let truncated = || corrupt_record(offset, ProviderError::BlockFile("truncated".into()));
+
+        let mut len = [0; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.reader.read(&mut len[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(truncated()),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(io_error(err).into()),
+            }
+        }
+
+        // the header and the total difficulty, without trusting the length with the allocation
+        let len = u32::from_be_bytes(len) as u64 + 32;
+        let mut record = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut record).map_err(io_error)?;
+        if (record.len() as u64) < len {
+            return Err(truncated())
+        }
+
+        let td = U256::from_be_slice(&record.split_off(record.len() - 32));
+        self.offset += 4 + len;
+        Ok(Some((offset, record, td)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::blocks::insert_blocks, ProviderFactory};
+    use reth_db::{test_utils::create_test_rw_db, DatabaseEnv};
+    use reth_interfaces::test_utils::ChainFixture;
+    use reth_primitives::ChainSpecBuilder;
+    use std::sync::Arc;
+
+    type Factory = ProviderFactory<Arc<DatabaseEnv>>;
+
+    fn empty_database() -> Factory {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        ProviderFactory::new(create_test_rw_db(), chain_spec)
+    }
+
+    /// Returns a database with a chain of 100 blocks built from the seed.
+    fn chain(seed: u64) -> Factory {
+        let factory = empty_database();
+        let fixture = ChainFixture::new(factory.provider().unwrap().chain_spec().clone());
+        let blocks = fixture.blocks(100).txs_per_block(1..3).seed(seed).build();
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_blocks(&provider_rw, &blocks).unwrap();
+        provider_rw.commit().unwrap();
+        factory
+    }
+
+    fn snapshot(factory: &Factory, range: RangeInclusive<u64>) -> Vec<u8> {
+        let provider = factory.provider().unwrap();
+        let mut snapshot = Vec::new();
+        let count = provider.export_header_snapshot(range.clone(), &mut snapshot);
+        assert_eq!(count, Ok(range.count() as u64));
+        snapshot
+    }
+
+    /// Returns the offset of the record of the block in a snapshot starting at genesis.
+    fn offset_of(snapshot: &[u8], number: u64) -> u64 {
+        let mut reader = SnapshotReader::new(snapshot).unwrap();
+        for _ in 0..number {
+            reader.next_record().unwrap().unwrap();
+        }
+        reader.offset
+    }
+
+    fn import(factory: &Factory, snapshot: &[u8]) -> Result<HeaderSnapshotImport> {
+        let provider_rw = factory.provider_rw().unwrap();
+        let outcome = provider_rw.import_header_snapshot(snapshot)?;
+        provider_rw.commit()?;
+        Ok(outcome)
+    }
+
+    /// Returns the offset and the reason of a rejected record.
+    fn rejected(result: Result<HeaderSnapshotImport>) -> (u64, ProviderError) {
+        match result {
+            Err(reth_interfaces::Error::Provider(ProviderError::CorruptBlockFile {
+                offset,
+                error,
+            })) => (offset, *error),
+            other => panic!("expected a rejected record, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn import_header_snapshot() {
+        let source = chain(0);
+        let full = snapshot(&source, 0..=100);
+        let factory = empty_database();
+        let outcome = |verified, imported| Ok(HeaderSnapshotImport { verified, imported });
+
+        // an import interrupted after block 49 is resumed by importing the full snapshot
+        assert_eq!(import(&factory, &snapshot(&source, 0..=49)), outcome(0, 50));
+        assert_eq!(import(&factory, &full), outcome(50, 51));
+        assert_eq!(import(&factory, &full), outcome(101, 0));
+        assert_eq!(import(&factory, &snapshot(&source, 90..=100)), outcome(11, 0));
+
+        for number in [0, 1, 49, 50, 100] {
+            assert_eq!(factory.sealed_header(number), source.sealed_header(number));
+            assert_eq!(factory.header_td_by_number(number), source.header_td_by_number(number));
+        }
+    }
+
+    #[test]
+    fn import_header_snapshot_linkage() {
+        let source = chain(0);
+        let full = snapshot(&source, 0..=100);
+        let factory = empty_database();
+
+        // flip a byte of the parent hash of block 30, which follows the length prefix, the list
+        // header and the string header of the hash
+        let offset = offset_of(&full, 30);
+        let mut corrupted = full.clone();
+        corrupted[offset as usize + 4 + 3 + 1 + 5] ^= 1;
+        let (got, error) = rejected(import(&factory, &corrupted));
+        assert_eq!(got, offset);
+        assert!(matches!(error, ProviderError::ParentHashMismatch { number: 30, .. }));
+        assert_eq!(factory.last_canonical_num_hash(), Ok(None));
+
+        // the total difficulty is recomputed
+        let offset = offset_of(&full, 40);
+        let mut corrupted = full.clone();
+        let td_end = offset_of(&full, 41) as usize;
+        corrupted[td_end - 1] ^= 1;
+        let (got, error) = rejected(import(&factory, &corrupted));
+        assert_eq!(got, offset);
+        assert!(matches!(error, ProviderError::TotalDifficultyMismatch { number: 40, .. }));
+
+        // a snapshot that doesn't attach to the stored chain
+        import(&factory, &snapshot(&source, 0..=10)).unwrap();
+        assert_eq!(
+            rejected(import(&factory, &snapshot(&source, 20..=30))),
+            (4, ProviderError::NonContiguousBlock { number: 20, expected: 11 })
+        );
+
+        // a snapshot of another chain conflicts with the stored headers after genesis
+        let other = snapshot(&chain(1), 0..=100);
+        let (got, error) = rejected(import(&factory, &other));
+        assert_eq!(got, offset_of(&other, 1));
+        assert!(matches!(error, ProviderError::BlockConflict { number: 1, .. }));
+    }
+
+    #[test]
+    fn import_header_snapshot_genesis() {
+        // a chain whose genesis header has a withdrawals root
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().shanghai_activated().build());
+        let source = ProviderFactory::new(create_test_rw_db(), chain_spec.clone());
+        let blocks = ChainFixture::new(chain_spec).blocks(10).seed(0).build();
+        let provider_rw = source.provider_rw().unwrap();
+        insert_blocks(&provider_rw, &blocks).unwrap();
+        provider_rw.commit().unwrap();
+
+        let factory = empty_database();
+        let expected = factory.provider().unwrap().chain_spec().genesis_hash();
+        assert_eq!(
+            rejected(import(&factory, &snapshot(&source, 0..=10))),
+            (4, ProviderError::GenesisMismatch { expected, got: blocks[0].block.hash() })
+        );
+        assert_eq!(factory.last_canonical_num_hash(), Ok(None));
+    }
+}
@@ -0,0 +1,477 @@
+use super::{
+    export::{corrupt_record, BlockFileReader, ExportChunk, ExportManifest},
+    DatabaseProviderRW, ProviderFactory,
+};
+use crate::{BlockNumReader, BlockWriter};
+use rayon::prelude::*;
+use reth_db::database::Database;
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{
+    tiny_keccak::{Hasher, Keccak},
+    BlockNumHash, BlockNumber, SealedBlock, H256,
+};
+use std::{io::Read, slice};
+
+/// Default number of blocks a [BlockFileImporter] inserts per committed transaction.
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 1_000;
+
+/// Options of a [BlockFileImporter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Number of blocks inserted before the transaction is committed, at least one.
+    pub batch_size: usize,
+    /// Trusts the file and doesn't check the bodies against the headers or the parent hashes.
+    pub skip_validation: bool,
+    /// Skips the blocks up to the canonical tip of the database, so that an interrupted import
+    /// can be resumed with the same file.
+    pub continue_from_tip: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_IMPORT_BATCH_SIZE,
+            skip_validation: false,
+            continue_from_tip: false,
+        }
+    }
+}
+
+/// Result of a [BlockFileImporter::import].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportOutcome {
+    /// Number of inserted blocks.
+    pub imported: u64,
+    /// Number of blocks skipped because they were already in the database.
+    pub skipped: u64,
+    /// The canonical tip after the import, `None` if the database and the file are empty.
+    pub tip: Option<BlockNumHash>,
+}
+
+/// Imports a block file written by a [BlockFileExporter](super::BlockFileExporter) into the
+/// database.
+///
+/// The blocks are read one batch at a time. Each batch is validated, has its senders recovered in
+/// parallel and is inserted with [BlockWriter::insert_block] in a read-write transaction, which
+/// is committed before the next batch is read. The first block has to follow the canonical tip of
+/// the database, or be the genesis block of the chain spec if the database is empty.
+///
+/// Stage checkpoints are not touched, the imported blocks still have to be executed. Until then
+/// [BlockNumReader::chain_info] reports the block of the [StageId::Finish] checkpoint and not the
+/// imported tip.
+///
+/// [StageId::Finish]: reth_primitives::stage::StageId::Finish
+#[derive(Debug)]
+pub struct BlockFileImporter<'a, DB> {
+    /// The factory of the read-write providers after a committed batch.
+    factory: &'a ProviderFactory<DB>,
+    /// The manifest the file is checked against.
+    manifest: Option<ExportManifest>,
+}
+
+impl<'a, DB: Database> BlockFileImporter<'a, DB> {
+    /// Creates an importer into the database of the factory.
+    pub fn new(factory: &'a ProviderFactory<DB>) -> Self {
+        Self { factory, manifest: None }
+    }
+
+    /// Checks the file against the [ExportManifest] written alongside it.
+    ///
+    /// The genesis hash of the manifest has to be the one of the chain spec, and every chunk of
+    /// the file has to match its checksum before any of its blocks is inserted. This is checked
+    /// even if [ImportOptions::skip_validation] is set.
+    pub fn with_manifest(mut self, manifest: ExportManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Imports the blocks of the file, starting with the read-write provider.
+    ///
+    /// The first batch is inserted with `provider_rw`, the later ones with new providers of the
+    /// factory, every batch is committed.
+    ///
+    /// Records that can't be decoded, have a body that doesn't match the header, don't follow the
+    /// previous block or don't match the manifest fail with [ProviderError::CorruptBlockFile].
+    /// The batches before the bad record stay committed.
+    pub fn import(
+        &self,
+        file: impl Read,
+        mut provider_rw: DatabaseProviderRW<'a, DB>,
+        options: ImportOptions,
+    ) -> Result<ImportOutcome> {
+        let genesis_hash = provider_rw.chain_spec().genesis_hash();
+        if let Some(manifest) = self.manifest.as_ref().filter(|m| m.genesis_hash != genesis_hash) {
+            return Err(ProviderError::GenesisMismatch {
+                expected: genesis_hash,
+                got: manifest.genesis_hash,
+            }
+            .into())
+        }
+
+        let mut reader = BlockFileReader::new(file);
+        let mut chunks = self.manifest.as_ref().map(ChunkVerifier::new);
+        let db_tip = provider_rw.last_canonical_num_hash()?;
+        let mut outcome = ImportOutcome { tip: db_tip, ..Default::default() };
+
+        loop {
+            let mut batch = Vec::new();
+            let mut end_of_file = false;
+            // a batch ends with a chunk, so that only checked blocks are inserted
+            while batch.len() < options.batch_size.max(1) ||
+                chunks.as_ref().is_some_and(ChunkVerifier::in_chunk)
+            {
+                let offset = reader.offset();
+                let Some(block) = reader.next().transpose()? else {
+                    if let Some(chunks) = &mut chunks {
+                        chunks.finish(reader.offset())?;
+                    }
+                    end_of_file = true;
+                    break
+                };
+                if let Some(chunks) = &mut chunks {
+                    chunks.check(offset, block.number, reader.last_record())?;
+                }
+
+                if options.continue_from_tip && db_tip.is_some_and(|tip| block.number <= tip.number)
+                {
+                    outcome.skipped += 1;
+                    continue
+                }
+                if !options.skip_validation {
+                    validate_block(&block, outcome.tip, genesis_hash)
+                        .map_err(|error| corrupt_record(offset, error))?;
+                }
+                outcome.tip = Some(block.num_hash());
+                batch.push((offset, block));
+            }
+
+            let blocks = batch
+                .into_par_iter()
+                .map(|(offset, block)| {
+                    let number = block.number;
+                    block.try_seal_with_senders().map_err(|_| {
+                        corrupt_record(offset, ProviderError::SenderRecoveryFailed(number))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for block in blocks {
+                provider_rw.insert_block(block.block, Some(block.senders))?;
+                outcome.imported += 1;
+            }
+
+            provider_rw.commit()?;
+
+            if end_of_file {
+                return Ok(outcome)
+            }
+            provider_rw = self.factory.provider_rw()?;
+        }
+    }
+}
+
+/// Checks the records of a block file against the chunks of its [ExportManifest].
+struct ChunkVerifier<'m> {
+    /// The chunks that were not started yet.
+    chunks: slice::Iter<'m, ExportChunk>,
+    /// The chunk being read.
+    current: Option<ChunkState<'m>>,
+}
+
+/// A chunk of a block file that was read partially.
+struct ChunkState<'m> {
+    /// The chunk of the manifest.
+    chunk: &'m ExportChunk,
+    /// Byte offset of the first record of the chunk.
+    offset: u64,
+    /// Number of records of the chunk read so far.
+    blocks: u64,
+    /// Number of bytes of the chunk read so far.
+    len: u64,
+    /// Hasher of the bytes of the chunk read so far.
+    hasher: Keccak,
+}
+
+impl<'m> ChunkVerifier<'m> {
+    fn new(manifest: &'m ExportManifest) -> Self {
+        Self { chunks: manifest.chunks.iter(), current: None }
+    }
+
+    /// Returns whether a chunk was read partially.
+    fn in_chunk(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Adds the record of the block at the offset to its chunk, checking the checksum if it is
+    /// the last record of the chunk.
+    fn check(&mut self, offset: u64, number: BlockNumber, record: &[u8]) -> Result<()> {
+        if self.current.is_none() {
+            let Some(chunk) = self.chunks.next() else {
+                let reason = format!("block #{number} is not in the manifest");
+                return Err(corrupt_record(offset, ProviderError::BlockFile(reason)))
+            };
+            let hasher = Keccak::v256();
+            self.current = Some(ChunkState { chunk, offset, blocks: 0, len: 0, hasher });
+        }
+        let state = self.current.as_mut().expect("the chunk is started");
+
+        let expected = state.chunk.first_block + state.blocks;
+        if number != expected {
+            let error = ProviderError::NonContiguousBlock { number, expected };
+            return Err(corrupt_record(offset, error))
+        }
+        let prefix = (record.len() as u32).to_be_bytes();
+        state.hasher.update(&prefix);
+        state.hasher.update(record);
+        state.blocks += 1;
+        state.len += (prefix.len() + record.len()) as u64;
+        if state.blocks < state.chunk.blocks {
+            return Ok(())
+        }
+
+        let ChunkState { chunk, offset, len, hasher, .. } =
+            self.current.take().expect("the chunk is read");
+        let mut checksum = [0; 32];
+        hasher.finalize(&mut checksum);
+        if len != chunk.len || H256::from(checksum) != chunk.checksum {
+            let last = chunk.first_block + chunk.blocks - 1;
+            let reason = format!("checksum mismatch of blocks #{}..=#{last}", chunk.first_block);
+            return Err(corrupt_record(offset, ProviderError::BlockFile(reason)))
+        }
+        Ok(())
+    }
+
+    /// Checks that the file didn't end before the last chunk.
+    fn finish(&mut self, offset: u64) -> Result<()> {
+        if self.current.is_some() || self.chunks.next().is_some() {
+            let reason = "file ends before the last block of the manifest".to_string();
+            return Err(corrupt_record(offset, ProviderError::BlockFile(reason)))
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the body of the block matches its header and that it follows the parent, or is the
+/// genesis block with the given hash.
+fn validate_block(
+    block: &SealedBlock,
+    parent: Option<BlockNumHash>,
+    genesis_hash: H256,
+) -> std::result::Result<(), ProviderError> {
+    let expected = parent.map_or(0, |parent| parent.number + 1);
+    if block.number != expected {
+        return Err(ProviderError::NonContiguousBlock { number: block.number, expected })
+    }
+    if parent.is_none() && block.hash() != genesis_hash {
+        return Err(ProviderError::GenesisMismatch { expected: genesis_hash, got: block.hash() })
+    }
+    if let Some(parent) = parent.filter(|parent| parent.hash != block.parent_hash) {
+        return Err(ProviderError::ParentHashMismatch {
+            number: block.number,
+            expected: parent.hash,
+            got: block.parent_hash,
+        })
+    }
+    block
+        .ensure_well_formed()
+        .map_err(|error| ProviderError::InvalidBody { number: block.number, error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::blocks::insert_blocks, BlockFileExporter, BlockHashReader, BlockReader,
+        HeaderProvider, StageCheckpointWriter,
+    };
+    use reth_db::{test_utils::create_test_rw_db, DatabaseEnv};
+    use reth_interfaces::test_utils::ChainFixture;
+    use reth_primitives::{
+        stage::{StageCheckpoint, StageId},
+        ChainSpec, ChainSpecBuilder, ForkCondition, Hardfork, MAINNET, U256,
+    };
+    use std::{io, sync::Arc};
+
+    /// A database with a chain of 300 blocks, the merge at block 100 and Shanghai at block 200, and
+    /// the export of the chain.
+    struct Exported {
+        chain_spec: Arc<ChainSpec>,
+        factory: ProviderFactory<Arc<DatabaseEnv>>,
+        file: Vec<u8>,
+        manifest: ExportManifest,
+    }
+
+    impl Exported {
+        fn new() -> Self {
+            let chain_spec = Arc::new(
+                ChainSpecBuilder::mainnet()
+                    .london_activated()
+                    .paris_at_ttd(MAINNET.genesis().difficulty * U256::from(100))
+                    .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(200 * 12))
+                    .build(),
+            );
+            let blocks = ChainFixture::new(chain_spec.clone()).blocks(300).build();
+            let factory = ProviderFactory::new(create_test_rw_db(), chain_spec.clone());
+            let provider_rw = factory.provider_rw().unwrap();
+            insert_blocks(&provider_rw, &blocks).unwrap();
+            provider_rw.commit().unwrap();
+
+            let mut file = Vec::new();
+            let manifest = BlockFileExporter::new(&factory.provider().unwrap())
+                .export(0..=300, &mut file, io::sink(), |_| {})
+                .unwrap();
+            Self { chain_spec, factory, file, manifest }
+        }
+
+        /// Returns the offset of the record of the block.
+        fn offset_of(&self, number: usize) -> u64 {
+            let mut reader = BlockFileReader::new(self.file.as_slice());
+            reader.by_ref().take(number).for_each(|block| {
+                block.unwrap();
+            });
+            reader.offset()
+        }
+
+        fn empty_factory(&self) -> ProviderFactory<Arc<DatabaseEnv>> {
+            ProviderFactory::new(create_test_rw_db(), self.chain_spec.clone())
+        }
+    }
+
+    fn import(
+        importer: &BlockFileImporter<'_, Arc<DatabaseEnv>>,
+        file: &[u8],
+        options: ImportOptions,
+    ) -> Result<ImportOutcome> {
+        importer.import(file, importer.factory.provider_rw()?, options)
+    }
+
+    #[test]
+    fn import_exported_chain() {
+        let exported = Exported::new();
+        let factory = exported.empty_factory();
+        let importer = BlockFileImporter::new(&factory).with_manifest(exported.manifest.clone());
+        let options = ImportOptions { batch_size: 64, ..Default::default() };
+
+        let outcome = import(&importer, &exported.file, options).unwrap();
+        let source = &exported.factory;
+        let tip = source.chain_info().unwrap();
+        assert_eq!(outcome.imported, 301);
+        assert_eq!(outcome.tip, Some(BlockNumHash::new(tip.best_number, tip.best_hash)));
+        assert_eq!(factory.last_canonical_num_hash(), Ok(outcome.tip));
+
+        // the blocks are not executed, so the chain info stays at genesis until the finish
+        // checkpoint is saved
+        let genesis_hash = exported.chain_spec.genesis_hash();
+        let best = factory.chain_info().map(|info| (info.best_number, info.best_hash));
+        assert_eq!(best, Ok((0, genesis_hash)));
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(300)).unwrap();
+        provider_rw.commit().unwrap();
+        assert_eq!(factory.chain_info(), Ok(tip));
+
+        for number in [0, 1, 63, 64, 99, 100, 150, 199, 200, 257, 300] {
+            assert_eq!(factory.block_hash(number), source.block_hash(number));
+            assert_eq!(factory.header_td_by_number(number), source.header_td_by_number(number));
+            assert_eq!(factory.block(number.into()), source.block(number.into()));
+        }
+    }
+
+    #[test]
+    fn import_continue_from_tip() {
+        let exported = Exported::new();
+        let factory = exported.empty_factory();
+        let importer = BlockFileImporter::new(&factory);
+        let options = ImportOptions { batch_size: 50, ..Default::default() };
+
+        // an import interrupted after block 99
+        let interrupted = &exported.file[..exported.offset_of(100) as usize];
+        assert_eq!(import(&importer, interrupted, options).unwrap().imported, 100);
+
+        assert_eq!(
+            import(&importer, &exported.file, options),
+            Err(corrupt_record(0, ProviderError::NonContiguousBlock { number: 0, expected: 100 }))
+        );
+        let options = ImportOptions { continue_from_tip: true, ..options };
+        let outcome = import(&importer, &exported.file, options).unwrap();
+        assert_eq!((outcome.imported, outcome.skipped), (201, 100));
+        assert_eq!(factory.block(300.into()), exported.factory.block(300.into()));
+    }
+
+    #[test]
+    fn import_corrupt_file() {
+        let exported = Exported::new();
+        let offset = exported.offset_of(10);
+
+        // replace the list header of block 10 with an empty string
+        let mut file = exported.file.clone();
+        file[offset as usize + 4] = 0x80;
+        let factory = exported.empty_factory();
+        let options = ImportOptions { batch_size: 5, ..Default::default() };
+        let err = import(&BlockFileImporter::new(&factory), &file, options).unwrap_err();
+        assert!(matches!(
+            err,
+            reth_interfaces::Error::Provider(ProviderError::CorruptBlockFile { offset: got, .. })
+                if got == offset
+        ));
+        // the blocks before the bad record are imported
+        assert_eq!(factory.last_block_number(), Ok(9));
+
+        // a truncated file fails at the offset of the last record
+        let factory = exported.empty_factory();
+        let truncated = &exported.file[..exported.file.len() - 1];
+        assert_eq!(
+            import(&BlockFileImporter::new(&factory), truncated, ImportOptions::default()),
+            Err(corrupt_record(
+                exported.offset_of(300),
+                ProviderError::BlockFile("truncated block".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn import_checks_manifest() {
+        let exported = Exported::new();
+        let options = ImportOptions { batch_size: 5, skip_validation: true, ..Default::default() };
+
+        // the manifest of another chain
+        let factory = exported.empty_factory();
+        let genesis_hash = H256::random();
+        let manifest = ExportManifest { genesis_hash, ..exported.manifest.clone() };
+        let importer = BlockFileImporter::new(&factory).with_manifest(manifest);
+        let expected = exported.chain_spec.genesis_hash();
+        assert_eq!(
+            import(&importer, &exported.file, options),
+            Err(ProviderError::GenesisMismatch { expected, got: genesis_hash }.into())
+        );
+
+        // a changed parent hash of block 10 still decodes, but fails the checksum of the first
+        // chunk before any of its blocks is inserted
+        let importer = BlockFileImporter::new(&factory).with_manifest(exported.manifest.clone());
+        let offset = exported.offset_of(10) as usize;
+        let parent_hash = exported.factory.block_hash(9).unwrap().unwrap();
+        let mut file = exported.file.clone();
+        let position = file[offset..].windows(32).position(|w| w == parent_hash.as_bytes());
+        file[offset + position.unwrap()] ^= 1;
+        assert_eq!(
+            import(&importer, &file, options),
+            Err(corrupt_record(
+                0,
+                ProviderError::BlockFile("checksum mismatch of blocks #0..=#127".to_string())
+            ))
+        );
+        assert_eq!(factory.last_canonical_num_hash(), Ok(None));
+
+        // a file that ends at a record boundary before the last block of the manifest
+        let truncated = &exported.file[..exported.offset_of(200) as usize];
+        assert_eq!(
+            import(&importer, truncated, options),
+            Err(corrupt_record(
+                exported.offset_of(200),
+                ProviderError::BlockFile(
+                    "file ends before the last block of the manifest".to_string()
+                )
+            ))
+        );
+        // the chunks before the end of the file are imported
+        assert_eq!(factory.last_block_number(), Ok(127));
+    }
+}
@@ -0,0 +1,333 @@
+use super::CommitStats;
+use reth_db::tables::Tables;
+use reth_metrics::{
+    metrics::{self, Counter, Histogram},
+    Metrics,
+};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Queries of the [DatabaseProvider](super::DatabaseProvider) that are recorded in the
+/// [DatabaseProviderMetrics].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Block,
+    TransactionByHashWithMeta,
+    ReceiptsByBlock,
+    HeadersRange,
+    SealedHeadersRange,
+    FillEnvAt,
+    FillEnvWithHeader,
+    FillBlockEnvAt,
+    FillBlockEnvWithHeader,
+    FillCfgEnvAt,
+    FillCfgEnvWithHeader,
+}
+
+impl Operation {
+    /// All operations, in the order of their discriminants.
+    const ALL: [Operation; 11] = [
+        Operation::Block,
+        Operation::TransactionByHashWithMeta,
+        Operation::ReceiptsByBlock,
+        Operation::HeadersRange,
+        Operation::SealedHeadersRange,
+        Operation::FillEnvAt,
+        Operation::FillEnvWithHeader,
+        Operation::FillBlockEnvAt,
+        Operation::FillBlockEnvWithHeader,
+        Operation::FillCfgEnvAt,
+        Operation::FillCfgEnvWithHeader,
+    ];
+
+    /// Returns the value of the `operation` label.
+    pub(crate) const fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Block => "block",
+            Operation::TransactionByHashWithMeta => "transaction_by_hash_with_meta",
+            Operation::ReceiptsByBlock => "receipts_by_block",
+            Operation::HeadersRange => "headers_range",
+            Operation::SealedHeadersRange => "sealed_headers_range",
+            Operation::FillEnvAt => "fill_env_at",
+            Operation::FillEnvWithHeader => "fill_env_with_header",
+            Operation::FillBlockEnvAt => "fill_block_env_at",
+            Operation::FillBlockEnvWithHeader => "fill_block_env_with_header",
+            Operation::FillCfgEnvAt => "fill_cfg_env_at",
+            Operation::FillCfgEnvWithHeader => "fill_cfg_env_with_header",
+        }
+    }
+}
+
+/// Call counts and latencies of the expensive queries of database providers, labeled by
+/// operation, the commits of read-write providers with their written rows, labeled by table, and
+/// the snapshots that were held for too long.
+///
+/// Clones share the same metrics, a single instance can be handed to every provider.
+#[derive(Debug, Clone)]
+pub struct DatabaseProviderMetrics {
+    operations: Arc<Vec<OperationMetrics>>,
+    commits: CommitMetrics,
+    tables: Arc<HashMap<String, TableWriteMetrics>>,
+    snapshots: SnapshotMetrics,
+}
+
+impl Default for DatabaseProviderMetrics {
+    fn default() -> Self {
+        let operations = Operation::ALL
+            .iter()
+            .map(|operation| {
+                OperationMetrics::new_with_labels(&[("operation", operation.as_str())])
+            })
+            .collect();
+        let tables = Tables::ALL
+            .iter()
+            .map(|table| {
+                let name = table.name().to_string();
+                (name.clone(), TableWriteMetrics::new_with_labels(&[("table", name)]))
+            })
+            .collect();
+        Self {
+            operations: Arc::new(operations),
+            commits: CommitMetrics::default(),
+            tables: Arc::new(tables),
+            snapshots: SnapshotMetrics::default(),
+        }
+    }
+}
+
+impl DatabaseProviderMetrics {
+    /// Runs the query and records the call and its duration for the operation.
+    ///
+    /// Queries are timed on an already open transaction, so only the query itself is timed and the
+    /// time to open the transaction isn't attributed to the operation.
+    pub(crate) fn record<T>(&self, operation: Operation, query: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let output = query();
+        let metrics = &self.operations[operation as usize];
+        metrics.calls_total.increment(1);
+        metrics.duration_seconds.record(start.elapsed().as_secs_f64());
+        output
+    }
+
+    /// Records the commit and the rows it wrote per table.
+    pub(crate) fn record_commit(&self, stats: &CommitStats) {
+        self.commits.commits_total.increment(1);
+        self.commits.commit_duration_seconds.record(stats.duration.as_secs_f64());
+        for (table, table_stats) in &stats.tables {
+            if let Some(metrics) = self.tables.get(*table) {
+                metrics.puts_total.increment(table_stats.puts);
+                metrics.deletes_total.increment(table_stats.deletes);
+                metrics.written_bytes_total.increment(table_stats.bytes);
+            }
+        }
+    }
+
+    /// Records a snapshot that is held longer than its threshold.
+    pub(crate) fn record_long_lived_snapshot(&self) {
+        self.snapshots.long_lived_snapshots_total.increment(1);
+    }
+
+    /// Records how long a long-lived snapshot was held once it is released.
+    pub(crate) fn record_long_lived_snapshot_release(&self, held: Duration) {
+        self.snapshots.long_lived_snapshot_duration_seconds.record(held.as_secs_f64());
+    }
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "storage.providers.database")]
+struct OperationMetrics {
+    /// The number of calls of the operation.
+    calls_total: Counter,
+    /// The duration of the operation, excluding opening the transaction.
+    duration_seconds: Histogram,
+}
+
+#[derive(Clone, Metrics)]
+#[metrics(scope = "storage.providers.database")]
+struct CommitMetrics {
+    /// The number of commits of read-write providers.
+    commits_total: Counter,
+    /// The duration of the commits of read-write providers.
+    commit_duration_seconds: Histogram,
+}
+
+#[derive(Clone, Metrics)]
+#[metrics(scope = "storage.providers.database")]
+struct SnapshotMetrics {
+    /// The number of snapshots that were held longer than the threshold of their factory.
+    long_lived_snapshots_total: Counter,
+    /// The duration the released long-lived snapshots were held for.
+    long_lived_snapshot_duration_seconds: Histogram,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "storage.providers.database.table")]
+struct TableWriteMetrics {
+    /// The number of rows put into the table by committed read-write providers.
+    puts_total: Counter,
+    /// The number of rows deleted from the table by committed read-write providers.
+    deletes_total: Counter,
+    /// The size of the keys and values put into the table by committed read-write providers.
+    written_bytes_total: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockReader, BlockWriter, EvmEnvProvider, HeaderProvider, ProviderFactory};
+    use parking_lot::Mutex;
+    use reth_db::{tables, test_utils::create_test_rw_db};
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_metrics::metrics::{
+        set_recorder, HistogramFn, Key, KeyName, Label, Recorder, SharedString, Unit,
+    };
+    use reth_primitives::{Chain, ChainSpec, ForkCondition, Genesis, Hardfork, H256};
+    use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    /// Records the values of all registered counters and histograms.
+    #[derive(Default)]
+    struct TestRecorder {
+        counters: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+        histograms: Mutex<HashMap<Key, Arc<TestHistogram>>>,
+    }
+
+    impl TestRecorder {
+        fn key(name: &str, operation: Operation) -> Key {
+            Key::from_parts(
+                format!("storage.providers.database.{name}"),
+                vec![Label::new("operation", operation.as_str())],
+            )
+        }
+
+        fn calls(&self, operation: Operation) -> u64 {
+            self.counters.lock()[&Self::key("calls_total", operation)].load(Ordering::Relaxed)
+        }
+
+        fn counter(&self, name: &str, labels: Vec<Label>) -> u64 {
+            let key = Key::from_parts(format!("storage.providers.database.{name}"), labels);
+            self.counters.lock()[&key].load(Ordering::Relaxed)
+        }
+
+        fn durations(&self, operation: Operation) -> Vec<f64> {
+            self.histograms.lock()[&Self::key("duration_seconds", operation)].0.lock().clone()
+        }
+    }
+
+    #[derive(Default)]
+    struct TestHistogram(Mutex<Vec<f64>>);
+
+    impl HistogramFn for TestHistogram {
+        fn record(&self, value: f64) {
+            self.0.lock().push(value);
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _desc: SharedString) {}
+
+        fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _desc: SharedString) {}
+
+        fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _desc: SharedString) {}
+
+        fn register_counter(&self, key: &Key) -> metrics::Counter {
+            metrics::Counter::from_arc(self.counters.lock().entry(key.clone()).or_default().clone())
+        }
+
+        fn register_gauge(&self, _key: &Key) -> metrics::Gauge {
+            metrics::Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key) -> metrics::Histogram {
+            metrics::Histogram::from_arc(
+                self.histograms.lock().entry(key.clone()).or_default().clone(),
+            )
+        }
+    }
+
+    #[test]
+    fn records_operations() {
+        let recorder: &'static TestRecorder = Box::leak(Box::default());
+        set_recorder(recorder).unwrap();
+
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .build();
+        let chain_spec = Arc::new(chain_spec);
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db.clone(), chain_spec.clone())
+            .with_metrics(DatabaseProviderMetrics::default())
+            .with_snapshot_warn_threshold(Duration::from_millis(5));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut parent = H256::zero();
+        for number in 0..3 {
+            let block = random_block(&mut rng, number, Some(parent), Some(1), Some(0));
+            parent = block.hash();
+            provider_rw.insert_block(block, None).unwrap();
+        }
+        let stats = provider_rw.commit().unwrap();
+
+        assert_eq!(recorder.counter("commits_total", Vec::new()), 1);
+        let headers = stats.tables[tables::Headers::const_name()];
+        let labels = || vec![Label::new("table", tables::Headers::const_name())];
+        assert_eq!(recorder.counter("table.puts_total", labels()), 3);
+        assert_eq!(recorder.counter("table.deletes_total", labels()), 0);
+        assert_eq!(recorder.counter("table.written_bytes_total", labels()), headers.bytes);
+
+        // writes and providers without metrics don't record queries
+        for operation in Operation::ALL {
+            assert_eq!(recorder.calls(operation), 0, "{}", operation.as_str());
+        }
+        let provider = ProviderFactory::new(db, chain_spec).provider().unwrap();
+        provider.block(1.into()).unwrap();
+        assert_eq!(recorder.calls(Operation::Block), 0);
+
+        let provider = factory.provider().unwrap();
+        provider.block(1.into()).unwrap();
+        provider.block(2.into()).unwrap();
+        provider.headers_range(0..3).unwrap();
+        let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+        provider.fill_env_at(&mut cfg, &mut block_env, 2.into()).unwrap();
+
+        for operation in Operation::ALL {
+            let expected = match operation {
+                Operation::Block => 2,
+                Operation::HeadersRange | Operation::FillEnvAt => 1,
+                _ => 0,
+            };
+            assert_eq!(recorder.calls(operation), expected, "{}", operation.as_str());
+            let durations = recorder.durations(operation);
+            assert_eq!(durations.len() as u64, expected, "{}", operation.as_str());
+            assert!(durations.iter().all(|duration| *duration >= 0.0));
+        }
+
+        // snapshots are reported once they are released after the threshold
+        let mut snapshot = factory.snapshot().unwrap();
+        snapshot.refresh().unwrap();
+        assert_eq!(recorder.counter("long_lived_snapshots_total", Vec::new()), 0);
+        std::thread::sleep(Duration::from_millis(10));
+        snapshot.refresh().unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        drop(snapshot);
+        assert_eq!(recorder.counter("long_lived_snapshots_total", Vec::new()), 2);
+
+        // or on the first access after the threshold, while they are still held
+        let snapshot = factory.snapshot().unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        snapshot.block(1.into()).unwrap();
+        assert_eq!(recorder.counter("long_lived_snapshots_total", Vec::new()), 3);
+        snapshot.block(2.into()).unwrap();
+        drop(snapshot);
+        assert_eq!(recorder.counter("long_lived_snapshots_total", Vec::new()), 3);
+    }
+}
@@ -1,23 +1,65 @@
 use crate::{
     providers::state::{historical::HistoricalStateProvider, latest::LatestStateProvider},
     traits::{BlockSource, ReceiptProvider},
-    BlockHashReader, BlockNumReader, BlockReader, EvmEnvProvider, HeaderProvider, ProviderError,
+    BlockEnvOverrides, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, EnvWithState,
+    EvmEnvProvider, HeaderProvider, NextBlockAttributes, ProviderError, PruneCheckpointReader,
     StageCheckpointReader, StateProviderBox, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{database::Database, init_db, models::StoredBlockBodyIndices, DatabaseEnv};
 use reth_interfaces::Result;
 use reth_primitives::{
     stage::{StageCheckpoint, StageId},
-    Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, ChainInfo,
-    ChainSpec, Header, Receipt, SealedBlock, SealedHeader, TransactionMeta, TransactionSigned,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, H256, U256,
+    Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
+    BlockWithSenders, ChainInfo, ChainSpec, Header, PruneCheckpoint, PrunePart, Receipt,
+    SealedBlock, SealedHeader, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash,
+    TxNumber, Withdrawal, H256, U256,
 };
 use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
-use std::{ops::RangeBounds, sync::Arc};
+use std::{
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+    time::Duration,
+};
 use tracing::trace;
 
+mod batch;
+
+mod commit_monitor;
+pub use commit_monitor::{CommitMonitor, DEFAULT_RECENT_COMMITS, DEFAULT_SLOW_COMMIT_THRESHOLD};
+
+mod export;
+pub use export::{
+    BlockFileExporter, BlockFileReader, ExportChunk, ExportManifest, DEFAULT_EXPORT_CHUNK_SIZE,
+};
+
+mod header_snapshot;
+pub use header_snapshot::{HeaderSnapshotImport, HEADER_SNAPSHOT_MAGIC};
+
+mod import;
+pub use import::{BlockFileImporter, ImportOptions, ImportOutcome, DEFAULT_IMPORT_BATCH_SIZE};
+
+mod metrics;
+pub use metrics::DatabaseProviderMetrics;
+
+mod parallel;
+
+#[cfg(feature = "serde")]
+mod receipt_export;
+#[cfg(feature = "serde")]
+pub use receipt_export::{
+    ReceiptExportFilter, ReceiptExportOutcome, ReceiptExporter, ReceiptRecord,
+};
+
+mod snapshot;
+pub use snapshot::{SnapshotProvider, DEFAULT_SNAPSHOT_WARN_THRESHOLD};
+
 mod provider;
-pub use provider::{DatabaseProvider, DatabaseProviderRO, DatabaseProviderRW};
+pub use provider::{
+    BlockAvailability, ChainInconsistency, CommitStats, ConsistencyScope, DatabaseProvider,
+    DatabaseProviderRO, DatabaseProviderRW, DatabaseStats, ForkIdValidation, HistoryAvailability,
+    Inconsistency, PruneProgress, ReceiptVerification, ReceiptsIter, ReorgOutcome, TableStats,
+    TableWriteStats,
+};
 
 /// A common provider that fetches data from a database.
 ///
@@ -28,6 +70,14 @@ pub struct ProviderFactory<DB> {
     db: DB,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// Metrics handed to every provider, if set.
+    metrics: Option<DatabaseProviderMetrics>,
+    /// Monitor of the commits of every read-write provider.
+    commit_monitor: CommitMonitor,
+    /// Whether read-write providers index inserted transactions by sender.
+    index_transaction_senders: bool,
+    /// Duration after which snapshots are reported as long-lived.
+    snapshot_warn_threshold: Duration,
 }
 
 impl<DB: Database> ProviderFactory<DB> {
@@ -35,7 +85,19 @@ impl<DB: Database> ProviderFactory<DB> {
     /// database using different types of providers. Example: [`HeaderProvider`]
     /// [`BlockHashReader`]. This may fail if the inner read database transaction fails to open.
     pub fn provider(&self) -> Result<DatabaseProviderRO<'_, DB>> {
-        Ok(DatabaseProvider::new(self.db.tx()?, self.chain_spec.clone()))
+        let tx = self.db.tx()?;
+        Ok(match &self.metrics {
+            Some(metrics) => {
+                DatabaseProvider::new_with_metrics(tx, self.chain_spec.clone(), metrics.clone())
+            }
+            None => DatabaseProvider::new(tx, self.chain_spec.clone()),
+        })
+    }
+
+    /// Returns a read-only provider for long-running readers, which keeps track of the age of its
+    /// database transaction and can be refreshed to the latest state, see [SnapshotProvider].
+    pub fn snapshot(&self) -> Result<SnapshotProvider<'_, DB>> {
+        SnapshotProvider::new(self)
     }
 
     /// Returns a provider with a created `DbTxMut` inside, which allows fetching and updating
@@ -43,14 +105,66 @@ impl<DB: Database> ProviderFactory<DB> {
     /// [`BlockHashReader`].  This may fail if the inner read/write database transaction fails to
     /// open.
     pub fn provider_rw(&self) -> Result<DatabaseProviderRW<'_, DB>> {
-        Ok(DatabaseProviderRW(DatabaseProvider::new_rw(self.db.tx_mut()?, self.chain_spec.clone())))
+        let tx = self.db.tx_mut()?;
+        let provider = match &self.metrics {
+            Some(metrics) => {
+                DatabaseProvider::new_rw_with_metrics(tx, self.chain_spec.clone(), metrics.clone())
+            }
+            None => DatabaseProvider::new_rw(tx, self.chain_spec.clone()),
+        };
+        let provider = provider.with_commit_monitor(self.commit_monitor.clone());
+        Ok(DatabaseProviderRW(if self.index_transaction_senders {
+            provider.with_sender_index()
+        } else {
+            provider
+        }))
     }
 }
 
 impl<DB> ProviderFactory<DB> {
     /// create new database provider
     pub fn new(db: DB, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { db, chain_spec }
+        Self {
+            db,
+            chain_spec,
+            metrics: None,
+            commit_monitor: CommitMonitor::default(),
+            index_transaction_senders: false,
+            snapshot_warn_threshold: DEFAULT_SNAPSHOT_WARN_THRESHOLD,
+        }
+    }
+
+    /// Records the expensive queries of all read-only providers and the commits of all read-write
+    /// providers in the [DatabaseProviderMetrics].
+    pub fn with_metrics(mut self, metrics: DatabaseProviderMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Records the commits of all read-write providers in the [CommitMonitor] instead of the
+    /// default one, which reports commits above [DEFAULT_SLOW_COMMIT_THRESHOLD].
+    pub fn with_commit_monitor(mut self, monitor: CommitMonitor) -> Self {
+        self.commit_monitor = monitor;
+        self
+    }
+
+    /// Indexes the transactions inserted by all read-write providers by sender, see
+    /// [DatabaseProvider::with_sender_index].
+    pub fn with_sender_index(mut self) -> Self {
+        self.index_transaction_senders = true;
+        self
+    }
+
+    /// Reports snapshots that are held longer than the threshold instead of
+    /// [DEFAULT_SNAPSHOT_WARN_THRESHOLD].
+    pub fn with_snapshot_warn_threshold(mut self, threshold: Duration) -> Self {
+        self.snapshot_warn_threshold = threshold;
+        self
+    }
+
+    /// Returns the monitor with the durations of the last commits of the read-write providers.
+    pub fn commit_monitor(&self) -> &CommitMonitor {
+        &self.commit_monitor
     }
 }
 
@@ -64,13 +178,33 @@ impl<DB: Database> ProviderFactory<DB> {
         Ok(ProviderFactory::<DatabaseEnv> {
             db: init_db(path).map_err(|e| reth_interfaces::Error::Custom(e.to_string()))?,
             chain_spec,
+            metrics: None,
+            commit_monitor: CommitMonitor::default(),
+            index_transaction_senders: false,
+            snapshot_warn_threshold: DEFAULT_SNAPSHOT_WARN_THRESHOLD,
         })
     }
 }
 
+/// Returns true if the block is both the best block and the last canonical block, in which case
+/// its state is the latest state.
+fn is_latest_block_number(
+    provider: &impl BlockNumReader,
+    block_number: BlockNumber,
+) -> Result<bool> {
+    Ok(block_number == provider.best_block_number()? &&
+        block_number == provider.last_block_number()?)
+}
+
 impl<DB: Clone> Clone for ProviderFactory<DB> {
     fn clone(&self) -> Self {
-        Self { db: self.db.clone(), chain_spec: Arc::clone(&self.chain_spec) }
+        Self {
+            db: self.db.clone(),
+            chain_spec: Arc::clone(&self.chain_spec),
+            metrics: self.metrics.clone(),
+            commit_monitor: self.commit_monitor.clone(),
+            index_transaction_senders: self.index_transaction_senders,
+        }
     }
 }
 
@@ -88,9 +222,7 @@ impl<DB: Database> ProviderFactory<DB> {
     ) -> Result<StateProviderBox<'_>> {
         let provider = self.provider()?;
 
-        if block_number == provider.best_block_number().unwrap_or_default() &&
-            block_number == provider.last_block_number().unwrap_or_default()
-        {
+        if is_latest_block_number(&provider, block_number)? {
             return Ok(Box::new(LatestStateProvider::new(provider.into_tx())))
         }
 
@@ -109,9 +241,7 @@ impl<DB: Database> ProviderFactory<DB> {
             .block_number(block_hash)?
             .ok_or(ProviderError::BlockHashNotFound(block_hash))?;
 
-        if block_number == provider.best_block_number().unwrap_or_default() &&
-            block_number == provider.last_block_number().unwrap_or_default()
-        {
+        if is_latest_block_number(&provider, block_number)? {
             return Ok(Box::new(LatestStateProvider::new(provider.into_tx())))
         }
 
@@ -122,6 +252,36 @@ impl<DB: Database> ProviderFactory<DB> {
         trace!(target: "providers::db", ?block_hash, "Returning historical state provider for block hash");
         Ok(Box::new(HistoricalStateProvider::new(provider.into_tx(), block_number)))
     }
+
+    /// Returns the [CfgEnv] and [BlockEnv] of the block together with the state of its parent
+    /// block.
+    ///
+    /// The block is resolved once and both the environments and the state are read from the same
+    /// transaction, so they can't belong to different heights. A hash has to be canonical, the
+    /// state of a block that was reorged out is not stored.
+    pub fn env_with_state_at(
+        &self,
+        at: BlockHashOrNumber,
+    ) -> Result<EnvWithState<StateProviderBox<'_>>> {
+        let provider = self.provider()?;
+
+        let block_number = match at {
+            BlockHashOrNumber::Hash(hash) => provider
+                .canonical_block_number(hash)?
+                .ok_or(ProviderError::BlockHashNotFound(hash))?,
+            BlockHashOrNumber::Number(number) => number,
+        };
+
+        let mut cfg = CfgEnv::default();
+        let mut block_env = BlockEnv::default();
+        provider.fill_env_at(&mut cfg, &mut block_env, block_number.into())?;
+
+        // The changesets of the block hold the values before the block, i.e. the state of its
+        // parent.
+        trace!(target: "providers::db", ?block_number, "Returning environment with parent state");
+        let state = Box::new(HistoricalStateProvider::new(provider.into_tx(), block_number));
+        Ok(EnvWithState { cfg, block_env, state })
+    }
 }
 
 impl<DB: Database> HeaderProvider for ProviderFactory<DB> {
@@ -155,6 +315,10 @@ impl<DB: Database> HeaderProvider for ProviderFactory<DB> {
     fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
         self.provider()?.sealed_header(number)
     }
+
+    fn sealed_header_with_td(&self, id: BlockHashOrNumber) -> Result<Option<(SealedHeader, U256)>> {
+        self.provider()?.sealed_header_with_td(id)
+    }
 }
 
 impl<DB: Database> BlockHashReader for ProviderFactory<DB> {
@@ -162,8 +326,11 @@ impl<DB: Database> BlockHashReader for ProviderFactory<DB> {
         self.provider()?.block_hash(number)
     }
 
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
-        self.provider()?.canonical_hashes_range(start, end)
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
+        self.provider()?.canonical_hashes_range(range)
     }
 }
 
@@ -180,9 +347,31 @@ impl<DB: Database> BlockNumReader for ProviderFactory<DB> {
         self.provider()?.last_block_number()
     }
 
+    fn last_canonical_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.provider()?.last_canonical_num_hash()
+    }
+
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
         self.provider()?.block_number(hash)
     }
+
+    fn block_numbers(&self, hashes: &[H256]) -> Result<Vec<Option<BlockNumber>>> {
+        self.provider()?.block_numbers(hashes)
+    }
+}
+
+impl<DB: Database> BlockIdReader for ProviderFactory<DB> {
+    fn pending_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.provider()?.pending_block_num_hash()
+    }
+
+    fn safe_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.provider()?.safe_block_num_hash()
+    }
+
+    fn finalized_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.provider()?.finalized_block_num_hash()
+    }
 }
 
 impl<DB: Database> BlockReader for ProviderFactory<DB> {
@@ -210,6 +399,13 @@ impl<DB: Database> BlockReader for ProviderFactory<DB> {
         self.provider()?.block_body_indices(number)
     }
 
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        self.provider()?.block_body_indices_range(range)
+    }
+
     fn block_with_senders(&self, number: BlockNumber) -> Result<Option<BlockWithSenders>> {
         self.provider()?.block_with_senders(number)
     }
@@ -224,6 +420,10 @@ impl<DB: Database> TransactionsProvider for ProviderFactory<DB> {
         self.provider()?.transaction_by_id(id)
     }
 
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        self.provider()?.transaction_by_id_no_hash(id)
+    }
+
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
         self.provider()?.transaction_by_hash(hash)
     }
@@ -246,6 +446,13 @@ impl<DB: Database> TransactionsProvider for ProviderFactory<DB> {
         self.provider()?.transactions_by_block(id)
     }
 
+    fn transactions_by_block_no_hash(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        self.provider()?.transactions_by_block_no_hash(id)
+    }
+
     fn transactions_by_block_range(
         &self,
         range: impl RangeBounds<BlockNumber>,
@@ -278,9 +485,23 @@ impl<DB: Database> ReceiptProvider for ProviderFactory<DB> {
         self.provider()?.receipt_by_hash(hash)
     }
 
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        self.provider()?.receipt_by_hash_with_meta(hash)
+    }
+
     fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
         self.provider()?.receipts_by_block(block)
     }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        self.provider()?.receipts_by_block_range(range)
+    }
 }
 
 impl<DB: Database> WithdrawalsProvider for ProviderFactory<DB> {
@@ -305,6 +526,16 @@ impl<DB: Database> StageCheckpointReader for ProviderFactory<DB> {
     fn get_stage_checkpoint_progress(&self, id: StageId) -> Result<Option<Vec<u8>>> {
         self.provider()?.get_stage_checkpoint_progress(id)
     }
+
+    fn get_all_stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>> {
+        self.provider()?.get_all_stage_checkpoints()
+    }
+}
+
+impl<DB: Database> PruneCheckpointReader for ProviderFactory<DB> {
+    fn get_prune_checkpoint(&self, part: PrunePart) -> Result<Option<PruneCheckpoint>> {
+        self.provider()?.get_prune_checkpoint(part)
+    }
 }
 
 impl<DB: Database> EvmEnvProvider for ProviderFactory<DB> {
@@ -317,6 +548,15 @@ impl<DB: Database> EvmEnvProvider for ProviderFactory<DB> {
         self.provider()?.fill_env_at(cfg, block_env, at)
     }
 
+    fn fill_env_at_block_id(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockId,
+    ) -> Result<Option<BlockNumHash>> {
+        self.provider()?.fill_env_at_block_id(cfg, block_env, at)
+    }
+
     fn fill_env_with_header(
         &self,
         cfg: &mut CfgEnv,
@@ -326,14 +566,42 @@ impl<DB: Database> EvmEnvProvider for ProviderFactory<DB> {
         self.provider()?.fill_env_with_header(cfg, block_env, header)
     }
 
+    fn fill_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.provider()?.fill_env_with_header_and_td(cfg, block_env, header, total_difficulty)
+    }
+
     fn fill_block_env_at(&self, block_env: &mut BlockEnv, at: BlockHashOrNumber) -> Result<()> {
         self.provider()?.fill_block_env_at(block_env, at)
     }
 
+    fn fill_block_env_with_overrides(
+        &self,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+        overrides: BlockEnvOverrides,
+    ) -> Result<()> {
+        self.provider()?.fill_block_env_with_overrides(block_env, at, overrides)
+    }
+
     fn fill_block_env_with_header(&self, block_env: &mut BlockEnv, header: &Header) -> Result<()> {
         self.provider()?.fill_block_env_with_header(block_env, header)
     }
 
+    fn fill_block_env_with_header_and_td(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.provider()?.fill_block_env_with_header_and_td(block_env, header, total_difficulty)
+    }
+
     fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> Result<()> {
         self.provider()?.fill_cfg_env_at(cfg, at)
     }
@@ -341,18 +609,89 @@ impl<DB: Database> EvmEnvProvider for ProviderFactory<DB> {
     fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> Result<()> {
         self.provider()?.fill_cfg_env_with_header(cfg, header)
     }
+
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.provider()?.fill_cfg_env_with_header_and_td(cfg, header, total_difficulty)
+    }
+
+    fn fill_envs_for_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>> {
+        self.provider()?.fill_envs_for_range(range)
+    }
+
+    fn fill_env_for_next_block(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        attributes: Option<NextBlockAttributes>,
+    ) -> Result<()> {
+        self.provider()?.fill_env_for_next_block(cfg, block_env, attributes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ProviderFactory;
-    use crate::{BlockHashReader, BlockNumReader};
+    use super::{
+        BlockAvailability, ChainInconsistency, ConsistencyScope, DatabaseProvider, DatabaseStats,
+        HistoryAvailability, Inconsistency, ProviderFactory, PruneProgress, ReceiptVerification,
+        ReorgOutcome, TableStats, TableWriteStats,
+    };
+    use crate::{
+        head_from_provider,
+        post_state::PostState,
+        test_utils::{blocks::insert_blocks, MockEthProvider},
+        AccountReader, BlockEnvOverrides, BlockHashReader, BlockIdReader, BlockNumReader,
+        BlockReader, BlockWriter, EnvWithState, EvmEnvProvider, HeaderProvider, HistoryWriter,
+        NextBlockAttributes, PruneCheckpointReader, PruneCheckpointWriter, ReceiptProvider,
+        StageCheckpointReader, StageCheckpointWriter, TransactionsProvider, WithdrawalsProvider,
+    };
+    use proptest::prelude::*;
     use reth_db::{
+        database::Database,
+        mock::CountingTxMock,
+        models::{
+            RawTransactionRef, StoredBlockBodyIndices, StoredBlockOmmers, StoredBlockWithdrawals,
+        },
+        table::Decompress,
+        tables,
         test_utils::{create_test_rw_db, ERROR_TEMPDIR},
-        DatabaseEnv,
+        transaction::{DbTx, DbTxMut},
+        DatabaseEnv, RawKey, RawTable, RawValue,
+    };
+    use reth_interfaces::{
+        provider::ProviderError,
+        test_utils::{
+            generators::{self, random_block, random_log, Rng},
+            ChainFixture,
+        },
+    };
+    use reth_primitives::{
+        proofs::{
+            calculate_receipt_root_and_logs_bloom, calculate_transaction_root,
+            calculate_withdrawals_root,
+        },
+        stage::{StageCheckpoint, StageId},
+        strategies::chain,
+        Account, Address, BlockBody, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumberOrTag,
+        BlockValidationError, Bloom, Chain, ChainSpec, ChainSpecBuilder, ForkCondition, ForkHash,
+        ForkId, Genesis, Hardfork, Head, Header, Log, LogAddressAndTopicsFilter, LogMeta,
+        PruneCheckpoint, PruneMode, PrunePart, Receipt, RpcBlockHash, SealedBlock,
+        SealedBlockWithSenders, SealedHeader, TransactionMeta, TxNumber, ValidationError,
+        Withdrawal, WithdrawalsSummary, H256, MAINNET, U256,
+    };
+    use reth_revm_primitives::primitives::{BlockEnv, CfgEnv, SpecId};
+    use std::{
+        collections::HashMap,
+        ops::{Bound, Range},
+        sync::Arc,
     };
-    use reth_primitives::{ChainSpecBuilder, H256};
-    use std::sync::Arc;
 
     #[test]
     fn common_history_provider() {
@@ -367,11 +706,38 @@ mod tests {
         let chain_spec = ChainSpecBuilder::mainnet().build();
         let db = create_test_rw_db();
         let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        // without a genesis header there is no chain head to report
         let provider = factory.provider().unwrap();
+        assert_eq!(provider.chain_info(), Err(ProviderError::HeaderNotFound(0.into()).into()));
+        drop(provider);
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let genesis = insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![], None);
+        provider_rw.commit().unwrap();
 
-        let chain_info = provider.chain_info().expect("should be ok");
+        let chain_info = factory.provider().unwrap().chain_info().expect("should be ok");
         assert_eq!(chain_info.best_number, 0);
-        assert_eq!(chain_info.best_hash, H256::zero());
+        assert_eq!(chain_info.best_hash, genesis.hash());
+    }
+
+    #[test]
+    fn chain_info_finish_checkpoint_ahead_of_headers() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..50 {
+            provider_rw.tx_ref().put::<tables::CanonicalHeaders>(number, H256::random()).unwrap();
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(100)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.best_block_number(), Ok(100));
+        assert_eq!(provider.chain_info(), Err(ProviderError::HeaderNotFound(100.into()).into()));
     }
 
     #[test]
@@ -401,4 +767,4212 @@ mod tests {
         provider_rw.block_hash(0).unwrap();
         provider.block_hash(0).unwrap();
     }
+
+    #[test]
+    fn receipts_by_tx_range_with_pruned_gap() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        // receipts of transactions 3 and 4 were pruned
+        let tx_nums = [0, 1, 2, 5, 6, 7];
+        let receipt =
+            |tx_num: u64| Receipt { cumulative_gas_used: tx_num * 21_000, ..Default::default() };
+        let provider_rw = factory.provider_rw().unwrap();
+        for tx_num in tx_nums {
+            provider_rw.tx_ref().put::<tables::Receipts>(tx_num, receipt(tx_num)).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+
+        // contiguous ranges, the missing tail was not executed yet
+        assert_eq!(
+            provider.receipts_by_tx_range(0..3).unwrap(),
+            vec![receipt(0), receipt(1), receipt(2)]
+        );
+        assert_eq!(provider.receipts_by_tx_range(6..=10).unwrap(), vec![receipt(6), receipt(7)]);
+
+        // range straddling the pruned receipts
+        assert_eq!(
+            provider.receipts_by_tx_range(1..7),
+            Err(ProviderError::ReceiptPruned(3).into())
+        );
+        assert_eq!(
+            provider.receipts_by_tx_range(4..7),
+            Err(ProviderError::ReceiptPruned(4).into())
+        );
+
+        // ranges inside or ending in the pruned receipts
+        for range in [3..5, 1..5] {
+            assert_eq!(
+                provider.receipts_by_tx_range(range),
+                Err(ProviderError::ReceiptPruned(3).into())
+            );
+        }
+        assert_eq!(
+            provider.receipts_by_tx_range((Bound::Excluded(1), Bound::Excluded(5))),
+            Err(ProviderError::ReceiptPruned(3).into())
+        );
+
+        // ranges that can't contain a transaction
+        assert_eq!(
+            provider.receipts_by_tx_range((Bound::Excluded(u64::MAX), Bound::Unbounded)),
+            Ok(vec![])
+        );
+        assert_eq!(provider.receipts_by_tx_range(u64::MAX..), Ok(vec![]));
+        assert_eq!(
+            provider
+                .receipts_by_tx_range_with_ids(1..7)
+                .unwrap()
+                .into_iter()
+                .map(|(tx_num, _)| tx_num)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 5, 6]
+        );
+    }
+
+    /// Inserts a block with one transaction for each receipt and writes the receipts.
+    ///
+    /// The block is the child of the stored block below it. The header gas used and receipts root
+    /// match the receipts, the logs bloom is computed from the receipts unless `bloom` is given.
+    fn insert_block_with_receipts<'a, TX: DbTxMut<'a> + DbTx<'a>, R: Rng>(
+        provider: &DatabaseProvider<'a, TX>,
+        rng: &mut R,
+        number: u64,
+        receipts: Vec<Receipt>,
+        bloom: Option<Bloom>,
+    ) -> SealedBlock {
+        let mut block = random_block(rng, number, None, Some(receipts.len() as u8), Some(0));
+        let mut header = block.header.unseal();
+        if let Some(parent) = number.checked_sub(1) {
+            header.parent_hash = provider.block_hash(parent).unwrap().unwrap_or_default();
+            header.timestamp =
+                provider.header_by_number(parent).unwrap().unwrap_or_default().timestamp;
+        }
+        let (receipts_root, logs_bloom) = calculate_receipt_root_and_logs_bloom(&receipts);
+        header.logs_bloom = bloom.unwrap_or(logs_bloom);
+        header.gas_used = receipts.last().map_or(0, |receipt| receipt.cumulative_gas_used);
+        header.receipts_root = receipts_root;
+        block.header = header.seal_slow();
+
+        let body = provider.insert_block(block.clone(), None).unwrap();
+        for (tx_num, receipt) in body.tx_num_range().zip(receipts) {
+            provider.tx_ref().put::<tables::Receipts>(tx_num, receipt).unwrap();
+        }
+        block
+    }
+
+    /// Same as [insert_block_with_receipts], with a receipt for each entry of `logs`.
+    fn insert_block_with_logs<'a, TX: DbTxMut<'a> + DbTx<'a>, R: Rng>(
+        provider: &DatabaseProvider<'a, TX>,
+        rng: &mut R,
+        number: u64,
+        logs: Vec<Vec<Log>>,
+        bloom: Option<Bloom>,
+    ) -> SealedBlock {
+        let receipts = logs.into_iter().map(|logs| Receipt { logs, ..Default::default() });
+        insert_block_with_receipts(provider, rng, number, receipts.collect(), bloom)
+    }
+
+    #[test]
+    fn logs_in_block_range_bloom_filter() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let address = Address::from_low_u64_be(1);
+        let log = random_log(&mut rng, Some(address), Some(1));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let block0 =
+            insert_block_with_logs(&provider_rw, &mut rng, 0, vec![vec![log.clone()]], None);
+        // the bloom of block 1 rules out all logs, its receipts must not be read
+        insert_block_with_logs(
+            &provider_rw,
+            &mut rng,
+            1,
+            vec![vec![log.clone()]],
+            Some(Bloom::zero()),
+        );
+        let block2 =
+            insert_block_with_logs(&provider_rw, &mut rng, 2, vec![vec![log.clone()]], None);
+        // reading the missing receipts of block 1 would end the query before block 2
+        for tx_num in provider_rw.block_body_indices(1).unwrap().unwrap().tx_num_range() {
+            provider_rw.tx_ref().delete::<tables::Receipts>(tx_num, None).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let filter = LogAddressAndTopicsFilter { addresses: vec![address], ..Default::default() };
+        assert_eq!(
+            provider
+                .logs_in_block_range(0..=2, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|(meta, log)| (meta.block_number, meta.block_hash, log))
+                .collect::<Vec<_>>(),
+            vec![(0, block0.hash(), log.clone()), (2, block2.hash(), log)]
+        );
+
+        let filter = LogAddressAndTopicsFilter {
+            addresses: vec![Address::from_low_u64_be(2)],
+            ..Default::default()
+        };
+        assert_eq!(provider.logs_in_block_range(0..=2, &filter).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn logs_in_block_range_log_index() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let address = Address::from_low_u64_be(1);
+        let log = random_log(&mut rng, Some(address), Some(2));
+        let other = (0..3)
+            .map(|_| random_log(&mut rng, Some(Address::from_low_u64_be(2)), None))
+            .collect::<Vec<_>>();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_logs(&provider_rw, &mut rng, 0, vec![vec![log.clone()]], None);
+        let block = insert_block_with_logs(
+            &provider_rw,
+            &mut rng,
+            1,
+            vec![
+                vec![other[0].clone(), other[1].clone()],
+                vec![],
+                vec![log.clone()],
+                vec![other[2].clone(), log.clone()],
+            ],
+            None,
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let filter = LogAddressAndTopicsFilter {
+            addresses: vec![address],
+            topics: [vec![], vec![log.topics[1]], vec![], vec![]],
+        };
+        let meta = |tx_index: usize, log_index| LogMeta {
+            block_hash: block.hash(),
+            block_number: 1,
+            tx_hash: block.body[tx_index].hash(),
+            tx_index: tx_index as u64,
+            log_index,
+        };
+        assert_eq!(
+            provider.logs_in_block_range(1..2, &filter).unwrap(),
+            vec![(meta(2, 2), log.clone()), (meta(3, 4), log)]
+        );
+    }
+
+    #[test]
+    fn logs_in_block_range_pruned_receipts() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        // the first receipt of each block has a log of the retained address
+        let (retained, other) = (Address::random(), Address::random());
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let logs = (0..5)
+            .map(|number| {
+                let log = random_log(&mut rng, Some(retained), Some(1));
+                let logs = vec![
+                    vec![log.clone()],
+                    vec![random_log(&mut rng, Some(other), Some(1))],
+                    vec![],
+                ];
+                insert_block_with_logs(&provider_rw, &mut rng, number, logs, None);
+                log
+            })
+            .collect::<Vec<_>>();
+        // block 4 was not executed yet
+        for tx_num in provider_rw.block_body_indices(4).unwrap().unwrap().tx_num_range() {
+            provider_rw.tx_ref().delete::<tables::Receipts>(tx_num, None).unwrap();
+        }
+        provider_rw.save_stage_checkpoint(StageId::Execution, StageCheckpoint::new(3)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let filter = LogAddressAndTopicsFilter { addresses: vec![retained], ..Default::default() };
+        let logs_in_block_range = |range: Range<u64>| {
+            let provider = factory.provider().unwrap();
+            provider.logs_in_block_range(range, &filter).map(|logs| {
+                logs.into_iter().map(|(meta, log)| (meta.block_number, log)).collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(logs_in_block_range(3..5), Ok(vec![(3, logs[3].clone())]));
+
+        // the limit is reached in the middle of block 1, which is partially retained
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_receipts(2, &[retained], 3), Ok(PruneProgress::HasMoreData));
+        provider_rw.commit().unwrap();
+        assert_eq!(
+            logs_in_block_range(0..5),
+            Err(ProviderError::ReceiptsPruned { block: 0, prune_height: 0 }.into())
+        );
+        assert_eq!(logs_in_block_range(1..5), Err(ProviderError::ReceiptPruned(4).into()));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_receipts(2, &[retained], 10), Ok(PruneProgress::Finished));
+        provider_rw.commit().unwrap();
+        assert_eq!(
+            logs_in_block_range(1..5),
+            Err(ProviderError::ReceiptsPruned { block: 1, prune_height: 2 }.into())
+        );
+        assert_eq!(logs_in_block_range(3..5), Ok(vec![(3, logs[3].clone())]));
+    }
+
+    #[test]
+    fn block_log_index_offsets() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let address = Address::from_low_u64_be(1);
+        let log = random_log(&mut rng, Some(address), Some(1));
+        let other = random_log(&mut rng, Some(Address::from_low_u64_be(2)), Some(1));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        // the first transactions of the block don't emit any logs
+        let block = insert_block_with_logs(
+            &provider_rw,
+            &mut rng,
+            0,
+            vec![vec![], vec![], vec![log.clone()], vec![other, log.clone()], vec![]],
+            None,
+        );
+        insert_block_with_logs(&provider_rw, &mut rng, 1, vec![], None);
+        insert_block_with_logs(&provider_rw, &mut rng, 2, vec![vec![log.clone()]], None);
+        // block 2 was not executed yet
+        provider_rw.tx_ref().delete::<tables::Receipts>(5, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_log_index_offsets(0.into()), Ok(Some(vec![0, 0, 0, 1, 3])));
+        assert_eq!(
+            provider.block_log_index_offsets(block.hash().into()),
+            Ok(Some(vec![0, 0, 0, 1, 3]))
+        );
+        assert_eq!(provider.block_log_index_offsets(1.into()), Ok(Some(vec![])));
+        assert_eq!(provider.block_log_index_offsets(2.into()), Ok(None));
+        assert_eq!(provider.block_log_index_offsets(3.into()), Ok(None));
+
+        let filter = LogAddressAndTopicsFilter { addresses: vec![address], ..Default::default() };
+        let meta = |tx_index: usize, log_index| LogMeta {
+            block_hash: block.hash(),
+            block_number: 0,
+            tx_hash: block.body[tx_index].hash(),
+            tx_index: tx_index as u64,
+            log_index,
+        };
+        assert_eq!(
+            provider.logs_in_block_range(0..=2, &filter).unwrap(),
+            vec![(meta(2, 0), log.clone()), (meta(3, 2), log)]
+        );
+    }
+
+    #[test]
+    fn receipt_by_hash_with_meta() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let receipt = |cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() };
+
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![receipt(21_000)], None);
+        let block = insert_block_with_receipts(
+            &provider_rw,
+            &mut rng,
+            1,
+            vec![receipt(30_000), receipt(51_000), receipt(120_000)],
+            None,
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let meta = |index: usize, gas_used| TransactionMeta {
+            tx_hash: block.body[index].hash(),
+            index: index as u64,
+            block_hash: block.hash(),
+            block_number: 1,
+            base_fee: block.base_fee_per_gas,
+            gas_used: Some(gas_used),
+            success: Some(false),
+        };
+
+        // first transaction of the block
+        assert_eq!(
+            provider.receipt_by_hash_with_meta(block.body[0].hash()).unwrap(),
+            Some((receipt(30_000), meta(0, 30_000), 30_000))
+        );
+        // last transaction of the block
+        assert_eq!(
+            provider.receipt_by_hash_with_meta(block.body[2].hash()).unwrap(),
+            Some((receipt(120_000), meta(2, 69_000), 69_000))
+        );
+        assert_eq!(provider.receipt_by_hash_with_meta(H256::random()).unwrap(), None);
+    }
+
+    #[test]
+    fn block_gas_used_by_tx() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let receipt = |cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() };
+
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![], None);
+        let block = insert_block_with_receipts(
+            &provider_rw,
+            &mut rng,
+            1,
+            vec![receipt(21_000), receipt(21_000), receipt(100_000)],
+            None,
+        );
+        // receipts of block 2 disagree with its header
+        insert_block_with_receipts(&provider_rw, &mut rng, 2, vec![receipt(50_000)], None);
+        provider_rw.tx_ref().put::<tables::Receipts>(3, receipt(40_000)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_gas_used_by_tx(0.into()).unwrap(), Some(vec![]));
+        assert_eq!(provider.block_total_gas_used(0.into()).unwrap(), Some(0));
+
+        let gas_used = provider.block_gas_used_by_tx(block.hash().into()).unwrap().unwrap();
+        assert_eq!(gas_used, vec![(0, 21_000), (1, 0), (2, 79_000)]);
+        assert_eq!(gas_used.iter().map(|(_, gas_used)| gas_used).sum::<u64>(), block.gas_used);
+        assert_eq!(provider.block_total_gas_used(1.into()).unwrap(), Some(block.gas_used));
+
+        let mismatch =
+            ProviderError::BlockGasUsedMismatch { block_number: 2, expected: 50_000, got: 40_000 };
+        assert_eq!(provider.block_gas_used_by_tx(2.into()), Err(mismatch.clone().into()));
+        assert_eq!(provider.block_total_gas_used(2.into()), Err(mismatch.into()));
+
+        assert_eq!(provider.block_gas_used_by_tx(3.into()).unwrap(), None);
+        assert_eq!(provider.block_total_gas_used(3.into()).unwrap(), None);
+    }
+
+    #[test]
+    fn verify_receipts() {
+        let chain_spec = ChainSpecBuilder::mainnet().byzantium_activated().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let receipt = |logs: Vec<Log>| Receipt { logs, ..Default::default() };
+        let log = random_log(&mut rng, None, Some(1));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..4 {
+            insert_block_with_receipts(
+                &provider_rw,
+                &mut rng,
+                number,
+                vec![receipt(vec![]), receipt(vec![log.clone()])],
+                None,
+            );
+        }
+        // block 1 lost the log of its last receipt, block 3 has a corrupted receipt without
+        // affecting the bloom
+        provider_rw.tx_ref().put::<tables::Receipts>(3, receipt(vec![])).unwrap();
+        provider_rw
+            .tx_ref()
+            .put::<tables::Receipts>(
+                7,
+                Receipt { cumulative_gas_used: 1, logs: vec![log.clone()], ..Default::default() },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let block0 = provider.verify_block_receipts(0.into()).unwrap();
+        assert!(block0.is_valid());
+
+        let block1 = provider.verify_block_receipts(1.into()).unwrap();
+        assert_eq!(block1.block_number, 1);
+        assert!(block1.receipts_root_mismatch.is_some());
+        assert_eq!(block1.logs_bloom_mismatch, Some(Bloom::zero()));
+
+        let block3 = provider.verify_block_receipts(3.into()).unwrap();
+        assert!(block3.receipts_root_mismatch.is_some());
+        assert_eq!(block3.logs_bloom_mismatch, None);
+
+        assert_eq!(provider.verify_receipts_range(.., usize::MAX).unwrap(), vec![block1, block3]);
+        assert_eq!(provider.verify_receipts_range(0..=3, 1).unwrap(), vec![block1]);
+        assert_eq!(
+            provider.verify_receipts_range(2..3, 1).unwrap(),
+            Vec::<ReceiptVerification>::new()
+        );
+    }
+
+    #[test]
+    fn verify_receipts_pre_byzantium() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![Receipt::default()], None);
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.verify_block_receipts(0.into()),
+            Err(ProviderError::PreByzantiumReceipts(0).into())
+        );
+        assert_eq!(
+            provider.verify_receipts_range(0..=0, 1),
+            Err(ProviderError::PreByzantiumReceipts(0).into())
+        );
+    }
+
+    #[test]
+    fn pruned_receipts() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..4)
+            .map(|number| {
+                insert_block_with_receipts(
+                    &provider_rw,
+                    &mut rng,
+                    number,
+                    vec![Receipt::default(), Receipt::default()],
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+        // prune the receipts of blocks 0 and 1
+        for tx_num in 0..4 {
+            provider_rw.tx_ref().delete::<tables::Receipts>(tx_num, None).unwrap();
+        }
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::Receipts,
+                PruneCheckpoint { block_number: 1, prune_mode: PruneMode::Before(2) },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let pruned = |block| {
+            reth_interfaces::Error::from(ProviderError::ReceiptsPruned { block, prune_height: 1 })
+        };
+
+        // pruned
+        assert_eq!(provider.receipts_by_block(0.into()), Err(pruned(0)));
+        assert_eq!(provider.receipts_by_block(blocks[1].hash().into()), Err(pruned(1)));
+        assert_eq!(provider.receipts_by_block_range(0..2), Err(pruned(0)));
+        assert_eq!(provider.receipt(3), Err(pruned(1)));
+        assert_eq!(provider.receipt_by_hash(blocks[1].body[0].hash()), Err(pruned(1)));
+        assert_eq!(provider.receipt_by_hash_with_meta(blocks[0].body[1].hash()), Err(pruned(0)));
+
+        // partially pruned
+        assert_eq!(provider.receipts_by_block_range(1..=3), Err(pruned(1)));
+
+        // unpruned
+        assert_eq!(
+            provider.receipts_by_block(2.into()).unwrap().map(|receipts| receipts.len()),
+            Some(2)
+        );
+        assert_eq!(provider.receipts_by_block_range(2..=3).unwrap().len(), 2);
+        assert_eq!(provider.receipt(4).unwrap(), Some(Receipt::default()));
+        assert!(provider.receipt_by_hash_with_meta(blocks[3].body[1].hash()).unwrap().is_some());
+        assert_eq!(provider.receipt(8).unwrap(), None);
+    }
+
+    #[test]
+    fn receipts_with_tx_hashes_by_block() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let receipt = |cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() };
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let small = insert_block_with_receipts(
+            &provider_rw,
+            &mut rng,
+            0,
+            vec![receipt(1), receipt(2), receipt(3)],
+            None,
+        );
+        // enough transactions to be hashed in parallel
+        let large = insert_block_with_receipts(
+            &provider_rw,
+            &mut rng,
+            1,
+            (0..100).map(receipt).collect(),
+            None,
+        );
+        // block 2 was only partially executed, block 3 was not executed at all
+        insert_block_with_receipts(&provider_rw, &mut rng, 2, vec![receipt(1), receipt(2)], None);
+        provider_rw.tx_ref().delete::<tables::Receipts>(104, None).unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 3, vec![receipt(1)], None);
+        provider_rw.tx_ref().delete::<tables::Receipts>(105, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for (number, block) in [(0, small), (1, large)] {
+            let expected = block
+                .body
+                .iter()
+                .map(|tx| tx.hash())
+                .zip(provider.receipts_by_block(number.into()).unwrap().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                provider.receipts_with_tx_hashes_by_block(block.hash().into()).unwrap(),
+                Some(expected)
+            );
+        }
+
+        assert_eq!(
+            provider.receipts_with_tx_hashes_by_block(2.into()),
+            Err(ProviderError::TransactionReceiptMismatch { block_number: 2, tx_num: 104 }.into())
+        );
+        assert_eq!(provider.receipts_with_tx_hashes_by_block(3.into()).unwrap(), None);
+        assert_eq!(provider.receipts_with_tx_hashes_by_block(4.into()).unwrap(), None);
+    }
+
+    #[test]
+    fn headers_range_iter() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..10)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.headers_range_iter(2..5).unwrap().collect::<Result<Vec<_>, _>>().unwrap(),
+            provider.headers_range(2..5).unwrap()
+        );
+        assert_eq!(
+            provider.sealed_headers_range_iter(..).unwrap().collect::<Result<Vec<_>, _>>().unwrap(),
+            blocks.iter().map(|block| block.header.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(provider.headers_range_iter(20..).unwrap().count(), 0);
+
+        // drop the iterators mid-range, the provider must remain usable
+        let mut headers = provider.headers_range_iter(..=8).unwrap();
+        assert_eq!(headers.next().unwrap().unwrap(), blocks[0].header.clone().unseal());
+        let mut sealed_headers = provider.sealed_headers_range_iter(3..).unwrap();
+        assert_eq!(sealed_headers.nth(2).unwrap().unwrap(), blocks[5].header);
+        drop(headers);
+        drop(sealed_headers);
+        assert_eq!(provider.sealed_headers_range(8..).unwrap().len(), 2);
+        provider.into_tx().commit().unwrap();
+    }
+
+    #[test]
+    fn sealed_headers_descending() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..10)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let mut expected = provider.sealed_headers_range(3..=7).unwrap();
+        expected.reverse();
+        assert_eq!(provider.sealed_headers_descending(7, 5).unwrap(), expected);
+
+        // block 0 is returned on its own and ends the walk
+        assert_eq!(
+            provider.sealed_headers_descending(0, 1).unwrap(),
+            vec![blocks[0].header.clone()]
+        );
+        assert_eq!(
+            provider.sealed_headers_descending(0, 5).unwrap(),
+            vec![blocks[0].header.clone()]
+        );
+        assert_eq!(
+            provider.sealed_headers_descending(2, 3).unwrap().last(),
+            Some(&blocks[0].header)
+        );
+        assert_eq!(provider.sealed_headers_descending(2, u64::MAX).unwrap().len(), 3);
+
+        // empty requests
+        assert_eq!(provider.sealed_headers_descending(0, 0).unwrap(), vec![]);
+        assert_eq!(provider.sealed_headers_descending(5, 0).unwrap(), vec![]);
+
+        // only the stored headers below `from` are returned
+        assert_eq!(provider.sealed_headers_descending(20, 5).unwrap(), vec![]);
+        let headers = provider.sealed_headers_descending(11, 4).unwrap();
+        assert_eq!(headers, vec![blocks[9].header.clone(), blocks[8].header.clone()]);
+    }
+
+    #[test]
+    fn sealed_headers_range_iter_missing_hash() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..5 {
+            insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+        }
+        provider_rw.tx_ref().delete::<tables::CanonicalHeaders>(3, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let mut sealed_headers = provider.sealed_headers_range_iter(1..).unwrap();
+        assert!(sealed_headers.next().unwrap().is_ok());
+        assert!(sealed_headers.next().unwrap().is_ok());
+        assert_eq!(
+            sealed_headers.next().unwrap(),
+            Err(ProviderError::HeaderNotFound(3.into()).into())
+        );
+    }
+
+    #[test]
+    fn sealed_headers_range() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..5)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.sealed_headers_range(1..=3).unwrap(),
+            blocks[1..=3].iter().map(|block| block.header.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(provider.sealed_header(4).unwrap(), Some(blocks[4].header.clone()));
+        assert_eq!(provider.sealed_header(5).unwrap(), None);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().delete::<tables::CanonicalHeaders>(2, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.sealed_headers_range(..),
+            Err(ProviderError::HeaderNotFound(2.into()).into())
+        );
+        assert_eq!(provider.sealed_header(2), Err(ProviderError::HeaderNotFound(2.into()).into()));
+
+        // a header above the canonical tip is not sealed
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().put::<tables::Headers>(5, Header::default()).unwrap();
+        provider_rw.commit().unwrap();
+        assert_eq!(factory.provider().unwrap().sealed_header(5), Ok(None));
+    }
+
+    #[test]
+    fn header_td_queries() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+        assert_eq!(factory.provider().unwrap().first_block_with_td_at_least(U256::ZERO), Ok(None));
+
+        // difficulty drops to zero after block 5, so the total difficulty stays flat
+        let tds = (0..16u64).map(|number| U256::from(10 * (number.min(5) + 1))).collect::<Vec<_>>();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, td) in tds.iter().enumerate() {
+            provider_rw.tx_ref().put::<tables::HeaderTD>(number as u64, (*td).into()).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::CanonicalHeaders>(number as u64, H256::zero())
+                .unwrap();
+        }
+        // a total difficulty above the canonical tip is not searched
+        provider_rw.tx_ref().put::<tables::HeaderTD>(16, U256::from(100).into()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.header_td_range(3..8).unwrap(),
+            (3..8).map(|number| (number, tds[number as usize])).collect::<Vec<_>>()
+        );
+        assert_eq!(provider.header_td_range(16..).unwrap(), vec![]);
+
+        assert_eq!(provider.first_block_with_td_at_least(U256::ZERO), Ok(Some(0)));
+        assert_eq!(provider.first_block_with_td_at_least(U256::from(35)), Ok(Some(3)));
+        assert_eq!(provider.first_block_with_td_at_least(U256::from(40)), Ok(Some(3)));
+        assert_eq!(provider.first_block_with_td_at_least(U256::from(60)), Ok(Some(5)));
+        assert_eq!(provider.first_block_with_td_at_least(U256::from(61)), Ok(None));
+    }
+
+    #[test]
+    fn block_number_by_timestamp() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_number_at_or_after_timestamp(0), Ok(None));
+        assert_eq!(provider.block_number_at_or_before_timestamp(u64::MAX), Ok(None));
+        drop(provider);
+
+        // blocks 1 to 3 share a timestamp
+        let timestamps = [100, 112, 112, 112, 124, 136];
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, timestamp) in timestamps.into_iter().enumerate() {
+            let header = Header { number: number as u64, timestamp, ..Default::default() };
+            provider_rw.tx_ref().put::<tables::Headers>(number as u64, header).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::CanonicalHeaders>(number as u64, H256::zero())
+                .unwrap();
+        }
+        // an orphan header above the canonical tip is not searched
+        let orphan = Header { number: 6, timestamp: 148, ..Default::default() };
+        provider_rw.tx_ref().put::<tables::Headers>(6, orphan).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for (timestamp, at_or_after, at_or_before) in [
+            // before the genesis
+            (50, Some(0), None),
+            // exactly at a block
+            (100, Some(0), Some(0)),
+            (124, Some(4), Some(4)),
+            (136, Some(5), Some(5)),
+            // between blocks
+            (101, Some(1), Some(0)),
+            (130, Some(5), Some(4)),
+            // the lowest block of a run of equal timestamps
+            (112, Some(1), Some(1)),
+            (113, Some(4), Some(3)),
+            // after the head
+            (137, None, Some(5)),
+        ] {
+            assert_eq!(provider.block_number_at_or_after_timestamp(timestamp), Ok(at_or_after));
+            assert_eq!(provider.block_number_at_or_before_timestamp(timestamp), Ok(at_or_before));
+        }
+        drop(provider);
+
+        // a gap in the canonical chain is skipped
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().delete::<tables::CanonicalHeaders>(4, None).unwrap();
+        provider_rw.tx_ref().delete::<tables::Headers>(4, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for (timestamp, at_or_after, at_or_before) in
+            [(113, Some(5), Some(3)), (124, Some(5), Some(3)), (136, Some(5), Some(5))]
+        {
+            assert_eq!(provider.block_number_at_or_after_timestamp(timestamp), Ok(at_or_after));
+            assert_eq!(provider.block_number_at_or_before_timestamp(timestamp), Ok(at_or_before));
+        }
+    }
+
+    #[test]
+    fn canonical_hashes_range_bounds() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let hashes = (0..5)
+            .map(|number| {
+                insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None).hash()
+            })
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        // `..=3` includes the block at the end bound
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Unbounded, Bound::Included(3))).unwrap(),
+            hashes[..=3]
+        );
+        // `1..3` excludes it
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Included(1), Bound::Excluded(3))).unwrap(),
+            hashes[1..3]
+        );
+        // `2..` runs to the tip
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Included(2), Bound::Unbounded)).unwrap(),
+            hashes[2..]
+        );
+        // empty ranges
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Included(3), Bound::Excluded(3))).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Included(5), Bound::Unbounded)).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn block_numbers_batch() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let hashes = (0..5)
+            .map(|number| {
+                insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None).hash()
+            })
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_numbers(&[]).unwrap(), vec![]);
+
+        let unknown = H256::random();
+        let query = [hashes[3], unknown, hashes[1], hashes[3], hashes[0], H256::zero()];
+        assert_eq!(
+            provider.block_numbers(&query).unwrap(),
+            vec![Some(3), None, Some(1), Some(3), Some(0), None]
+        );
+        // the batch agrees with the point lookups
+        assert_eq!(
+            provider.block_numbers(&query).unwrap(),
+            query.iter().map(|hash| provider.block_number(*hash).unwrap()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            provider.convert_hashes_to_num_hashes(&query[..3]).unwrap(),
+            vec![
+                Some(BlockNumHash::new(3, hashes[3])),
+                None,
+                Some(BlockNumHash::new(1, hashes[1]))
+            ]
+        );
+    }
+
+    #[test]
+    fn block_numbers_write_batching() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        // the first blocks are stored, the last ones only buffered
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut hashes = Vec::new();
+        for number in 0..4 {
+            if number == 2 {
+                provider_rw.enable_write_batching(usize::MAX);
+            }
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            hashes.push(block.hash());
+        }
+        assert_eq!(provider_rw.tx_ref().entries::<tables::HeaderNumbers>(), Ok(2));
+
+        let query = [hashes[3], H256::random(), hashes[0], hashes[3], hashes[2], hashes[1]];
+        assert_eq!(
+            provider_rw.block_numbers(&query).unwrap(),
+            vec![Some(3), None, Some(0), Some(3), Some(2), Some(1)]
+        );
+    }
+
+    #[test]
+    fn best_and_last_block_number_disagree() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+        assert_eq!(factory.last_canonical_num_hash(), Ok(None));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let hashes = (0..6)
+            .map(|number| {
+                insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None).hash()
+            })
+            .collect::<Vec<_>>();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(3)).unwrap();
+        provider_rw.commit().unwrap();
+
+        // headers were downloaded past the block the pipeline finished
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.best_block_number(), Ok(3));
+        assert_eq!(provider.last_block_number(), Ok(5));
+        assert_eq!(provider.last_canonical_num_hash(), Ok(Some(BlockNumHash::new(5, hashes[5]))));
+        assert_eq!(provider.chain_info().unwrap().best_hash, hashes[3]);
+        drop(provider);
+
+        // the canonical headers were unwound below the finished block
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 2..6 {
+            provider_rw.tx_ref().delete::<tables::CanonicalHeaders>(number, None).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.best_block_number(), Ok(3));
+        assert_eq!(provider.last_block_number(), Ok(1));
+        assert_eq!(provider.last_canonical_num_hash(), Ok(Some(BlockNumHash::new(1, hashes[1]))));
+    }
+
+    #[test]
+    fn sealed_header_with_td() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let td = provider.header_td_by_number(1).unwrap().unwrap();
+        let expected = Some((blocks[1].header.clone(), td));
+        assert_eq!(provider.sealed_header_with_td(1.into()), Ok(expected.clone()));
+        assert_eq!(provider.sealed_header_with_td(blocks[1].hash().into()), Ok(expected));
+        assert_eq!(provider.sealed_header_with_td(3.into()), Ok(None));
+        assert_eq!(provider.sealed_header_with_td(H256::random().into()), Ok(None));
+        drop(provider);
+
+        // a header without total difficulty is an inconsistency
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().delete::<tables::HeaderTD>(2, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.sealed_header_with_td(2.into()),
+            Err(ProviderError::TotalDifficultyNotFound { number: 2 }.into())
+        );
+    }
+
+    #[test]
+    fn check_canonical_consistency() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut hashes: Vec<H256> = Vec::new();
+        for number in 0..10 {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let hash = block.hash();
+            // link the headers to the canonical hash of their parent
+            let mut header = block.header.unseal();
+            if let Some(parent_hash) = hashes.last() {
+                header.parent_hash = *parent_hash;
+            }
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+            hashes.push(hash);
+        }
+        provider_rw.commit().unwrap();
+        assert_eq!(
+            factory.provider().unwrap().check_canonical_consistency(.., usize::MAX).unwrap(),
+            vec![]
+        );
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_ref();
+        tx.delete::<tables::CanonicalHeaders>(2, None).unwrap();
+        tx.delete::<tables::Headers>(4, None).unwrap();
+        tx.put::<tables::HeaderNumbers>(hashes[5], 7).unwrap();
+        let mut header = tx.get::<tables::Headers>(6).unwrap().unwrap();
+        header.parent_hash = H256::random();
+        tx.put::<tables::Headers>(6, header.clone()).unwrap();
+        tx.delete::<tables::HeaderTD>(7, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let findings = vec![
+            ChainInconsistency::CanonicalHashGap { start: 2, end: 2 },
+            ChainInconsistency::MissingHeader { number: 4, hash: hashes[4] },
+            ChainInconsistency::HeaderNumberMismatch { number: 5, hash: hashes[5], got: Some(7) },
+            ChainInconsistency::ParentHashMismatch {
+                number: 6,
+                parent_hash: header.parent_hash,
+                expected: hashes[5],
+            },
+            ChainInconsistency::MissingTotalDifficulty { number: 7 },
+        ];
+        assert_eq!(provider.check_canonical_consistency(.., usize::MAX).unwrap(), findings);
+        assert_eq!(provider.check_canonical_consistency(.., 2).unwrap(), findings[..2]);
+        // the block before the range is used to check the parent hash of the first block
+        assert_eq!(provider.check_canonical_consistency(5.., usize::MAX).unwrap(), findings[2..]);
+        assert_eq!(provider.check_canonical_consistency(8..=9, usize::MAX).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn withdrawals_by_block_id() {
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1_000))
+            .build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut hashes = Vec::new();
+        for (number, timestamp) in [(0, 500), (1, 1_500), (2, 2_000)] {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            hashes.push(block.hash());
+            let mut header = block.header.unseal();
+            header.timestamp = timestamp;
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+        }
+        let withdrawals = vec![Withdrawal { index: 1, amount: 2, ..Default::default() }];
+        provider_rw
+            .tx_ref()
+            .put::<tables::BlockWithdrawals>(
+                2,
+                StoredBlockWithdrawals { withdrawals: withdrawals.clone() },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        // before shanghai
+        assert_eq!(provider.withdrawals_by_block_id(0.into()), Ok(None));
+        // after shanghai without stored withdrawals
+        assert_eq!(provider.withdrawals_by_block_id(1.into()), Ok(Some(vec![])));
+        assert_eq!(provider.block(1.into()).unwrap().unwrap().withdrawals, Some(vec![]));
+        assert_eq!(provider.withdrawals_by_block_id(hashes[2].into()), Ok(Some(withdrawals)));
+        assert_eq!(provider.withdrawals_by_block_id(3.into()), Ok(None));
+        // a wrong timestamp flips the shanghai check
+        assert_eq!(provider.withdrawals_by_block(0.into(), 1_500), Ok(Some(vec![])));
+    }
+
+    #[test]
+    fn withdrawals_range() {
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1_000))
+            .build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        // shanghai activates at block 3, block 4 has no withdrawals
+        let withdrawals = |count: u64| {
+            (0..count).map(|index| Withdrawal { index, ..Default::default() }).collect::<Vec<_>>()
+        };
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..7 {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let mut header = block.header.unseal();
+            header.timestamp = 400 * number;
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+        }
+        for (number, count) in [(3, 2), (5, 3), (6, 1)] {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockWithdrawals>(
+                    number,
+                    StoredBlockWithdrawals { withdrawals: withdrawals(count) },
+                )
+                .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let expected = vec![(3, withdrawals(2)), (5, withdrawals(3))];
+        assert_eq!(provider.withdrawals_range(1..=5).unwrap(), expected);
+        for number in 1..=5 {
+            let by_block = provider.withdrawals_by_block_id(number.into()).unwrap();
+            let in_range = expected.iter().find(|(n, _)| *n == number).map(|(_, w)| w.clone());
+            assert_eq!(by_block.filter(|w| !w.is_empty()), in_range);
+        }
+        assert_eq!(provider.withdrawals_range(..3).unwrap(), vec![]);
+
+        assert_eq!(provider.withdrawals_count_in_range(1..=5), Ok(5));
+        assert_eq!(provider.withdrawals_count_in_range(4..), Ok(4));
+        assert_eq!(provider.withdrawals_count_in_range(..), Ok(6));
+        assert_eq!(provider.withdrawals_count_in_range(..3), Ok(0));
+    }
+
+    #[test]
+    fn withdrawals_summary() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let withdrawal = |index, validator_index, amount| Withdrawal {
+            index,
+            validator_index,
+            address: Address::from_low_u64_be(validator_index % 2),
+            amount,
+        };
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, withdrawals) in [
+            (1, vec![withdrawal(0, 1, u64::MAX), withdrawal(1, 2, 5)]),
+            (2, vec![]),
+            (3, vec![withdrawal(2, 3, u64::MAX), withdrawal(3, 2, u64::MAX)]),
+        ] {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockWithdrawals>(number, StoredBlockWithdrawals { withdrawals })
+                .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let max = u64::MAX as u128;
+        let summary = provider.withdrawals_summary(..).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.total, 3 * max + 5);
+        assert_eq!(
+            summary.by_address,
+            HashMap::from([
+                (Address::from_low_u64_be(0), max + 5),
+                (Address::from_low_u64_be(1), 2 * max)
+            ])
+        );
+        assert_eq!(summary.by_validator, HashMap::from([(1, max), (2, max + 5), (3, max)]));
+
+        let summary = provider.withdrawals_summary(2..).unwrap();
+        assert_eq!((summary.count, summary.total), (2, 2 * max));
+        assert_eq!(provider.withdrawals_summary(4..), Ok(WithdrawalsSummary::default()));
+    }
+
+    #[test]
+    fn latest_withdrawal_and_withdrawal_by_index() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let withdrawal = |index| Withdrawal { index, amount: index + 1, ..Default::default() };
+        let provider_rw = factory.provider_rw().unwrap();
+        // the tip was unwound to block 5, the withdrawals of block 6 linger
+        for (number, indices) in [(1, 0..2), (3, 2..5), (5, 7..9), (6, 9..10)] {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockWithdrawals>(
+                    number,
+                    StoredBlockWithdrawals { withdrawals: indices.map(withdrawal).collect() },
+                )
+                .unwrap();
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(5)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.latest_withdrawal(), Ok(Some(withdrawal(8))));
+        assert_eq!(provider.withdrawal_by_index(0), Ok(Some((1, withdrawal(0)))));
+        assert_eq!(provider.withdrawal_by_index(3), Ok(Some((3, withdrawal(3)))));
+        assert_eq!(provider.withdrawal_by_index(8), Ok(Some((5, withdrawal(8)))));
+        // missing from the chain
+        assert_eq!(provider.withdrawal_by_index(6), Ok(None));
+        // only in the unwound block
+        assert_eq!(provider.withdrawal_by_index(9), Ok(None));
+        assert_eq!(provider.withdrawal_by_index(100), Ok(None));
+    }
+
+    #[test]
+    fn ommers_range() {
+        let mut chain_spec = ChainSpecBuilder::mainnet().build();
+        chain_spec.paris_block_and_final_difficulty = Some((4, U256::from(1_000)));
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let ommer = |number| Header { number, ..Default::default() };
+        let provider_rw = factory.provider_rw().unwrap();
+        // the entry of block 5 is after the merge and must never be read
+        for (number, ommers) in [(1, vec![ommer(0), ommer(1)]), (3, vec![ommer(2)]), (5, vec![])] {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockOmmers>(number, StoredBlockOmmers { ommers })
+                .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let pre_merge = vec![(1, vec![ommer(0), ommer(1)]), (3, vec![ommer(2)])];
+        assert_eq!(provider.ommers_range(..).unwrap(), pre_merge);
+        assert_eq!(provider.ommers_range(2..=5).unwrap(), pre_merge[1..]);
+        assert_eq!(provider.ommers_range(..3).unwrap(), pre_merge[..1]);
+        assert_eq!(provider.ommers_range(4..).unwrap(), vec![]);
+
+        assert_eq!(provider.ommer_by_block_and_index(1.into(), 1), Ok(Some(ommer(1))));
+        assert_eq!(provider.ommer_by_block_and_index(1.into(), 2), Ok(None));
+        assert_eq!(provider.ommer_by_block_and_index(2.into(), 0), Ok(None));
+        assert_eq!(provider.ommer_by_block_and_index(5.into(), 0), Ok(None));
+    }
+
+    #[test]
+    fn shanghai_activation_boundary() {
+        let activation = 1_000;
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(activation))
+            .build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, timestamp) in [(0, activation - 1), (1, activation), (2, activation + 1)] {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let mut header = block.header.unseal();
+            header.timestamp = timestamp;
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+            let ommers = vec![Header { number, ..Default::default() }];
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockOmmers>(number, StoredBlockOmmers { ommers })
+                .unwrap();
+        }
+        let withdrawals = vec![Withdrawal { index: 0, amount: 1, ..Default::default() }];
+        provider_rw
+            .tx_ref()
+            .put::<tables::BlockWithdrawals>(
+                2,
+                StoredBlockWithdrawals { withdrawals: withdrawals.clone() },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let expected = [
+            (vec![Header { number: 0, ..Default::default() }], None),
+            (vec![], Some(vec![])),
+            (vec![], Some(withdrawals)),
+        ];
+        for (number, (ommers, withdrawals)) in expected.into_iter().enumerate() {
+            let number = number as u64;
+            let block = provider.block(number.into()).unwrap().unwrap();
+            assert_eq!((&block.ommers, &block.withdrawals), (&ommers, &withdrawals));
+            let block = provider.block_with_senders(number).unwrap().unwrap();
+            assert_eq!((&block.ommers, &block.withdrawals), (&ommers, &withdrawals));
+            assert_eq!(
+                provider.withdrawals_by_block(number.into(), block.timestamp).unwrap(),
+                withdrawals
+            );
+        }
+    }
+
+    #[test]
+    fn finalized_and_safe_markers() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..=5)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+
+        assert_eq!(provider_rw.finalized_block_num_hash(), Ok(None));
+        assert_eq!(provider_rw.safe_block_num_hash(), Ok(None));
+
+        // forkchoiceUpdated(head: 3, safe: 2, finalized: 1)
+        provider_rw.set_safe_block_num_hash(blocks[2].num_hash()).unwrap();
+        provider_rw.set_finalized_block_num_hash(blocks[1].num_hash()).unwrap();
+
+        // forkchoiceUpdated(head: 5, safe: 4, finalized: 2)
+        provider_rw.set_safe_block_num_hash(blocks[4].num_hash()).unwrap();
+        provider_rw.set_finalized_block_num_hash(blocks[2].num_hash()).unwrap();
+
+        // blocks 4 and 5 are reorged out, the safe block follows the new chain backwards
+        let reorged = H256::random();
+        provider_rw.tx_ref().put::<tables::CanonicalHeaders>(4, reorged).unwrap();
+        provider_rw.tx_ref().delete::<tables::CanonicalHeaders>(5, None).unwrap();
+        assert_eq!(
+            provider_rw.set_safe_block_num_hash(blocks[4].num_hash()),
+            Err(ProviderError::NonCanonicalBlock { number: 4, hash: blocks[4].hash() }.into())
+        );
+        provider_rw.set_safe_block_num_hash(blocks[3].num_hash()).unwrap();
+        assert_eq!(provider_rw.safe_block_num_hash(), Ok(Some(blocks[3].num_hash())));
+
+        // forkchoiceUpdated(head: 4', safe: 4', finalized: 1) can't unfinalize block 2
+        provider_rw.set_safe_block_num_hash(BlockNumHash::new(4, reorged)).unwrap();
+        assert_eq!(
+            provider_rw.set_finalized_block_num_hash(blocks[1].num_hash()),
+            Err(ProviderError::FinalizedBlockMovedBackwards { current: 2, new: 1 }.into())
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.finalized_block_num_hash(), Ok(Some(blocks[2].num_hash())));
+        assert_eq!(provider.safe_block_num_hash(), Ok(Some(BlockNumHash::new(4, reorged))));
+    }
+
+    #[test]
+    fn block_number_for_tag() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+        let tags = [
+            BlockNumberOrTag::Latest,
+            BlockNumberOrTag::Earliest,
+            BlockNumberOrTag::Safe,
+            BlockNumberOrTag::Finalized,
+            BlockNumberOrTag::Pending,
+            BlockNumberOrTag::Number(0),
+            BlockNumberOrTag::Number(1),
+        ];
+
+        // empty database
+        let provider = factory.provider().unwrap();
+        for tag in tags {
+            assert_eq!(provider.block_number_for_tag(tag), Ok(None), "{tag}");
+        }
+        drop(provider);
+
+        // only genesis
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut blocks = vec![insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![], None)];
+        let expected = [Some(0), Some(0), None, None, None, Some(0), None];
+        for (tag, expected) in tags.into_iter().zip(expected) {
+            assert_eq!(provider_rw.block_number_for_tag(tag), Ok(expected), "{tag}");
+        }
+
+        for number in 1..=4 {
+            blocks.push(insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None));
+        }
+        provider_rw.set_safe_block_num_hash(blocks[3].num_hash()).unwrap();
+        provider_rw.set_finalized_block_num_hash(blocks[2].num_hash()).unwrap();
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::Receipts,
+                PruneCheckpoint { block_number: 1, prune_mode: PruneMode::Before(2) },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        // pruned receipts leave the blocks in place
+        let provider = factory.provider().unwrap();
+        let expected = [Some(4), Some(0), Some(3), Some(2), None, Some(0), Some(1)];
+        for (tag, expected) in tags.into_iter().zip(expected) {
+            assert_eq!(provider.block_number_for_tag(tag), Ok(expected), "{tag}");
+        }
+        assert_eq!(provider.block_number_for_tag(BlockNumberOrTag::Number(5)), Ok(None));
+        assert_eq!(
+            provider.block_by_tag(BlockNumberOrTag::Safe).unwrap().map(|block| block.hash_slow()),
+            Some(blocks[3].hash())
+        );
+        assert_eq!(provider.block_by_tag(BlockNumberOrTag::Pending), Ok(None));
+        drop(provider);
+
+        // the finalized block was reorged out and the blocks below it were removed, the history
+        // pruned up to the head doesn't remove blocks
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().put::<tables::CanonicalHeaders>(2, H256::random()).unwrap();
+        for number in 0..=1 {
+            provider_rw.tx_ref().delete::<tables::Headers>(number, None).unwrap();
+            provider_rw.tx_ref().delete::<tables::BlockBodyIndices>(number, None).unwrap();
+        }
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::AccountHistory,
+                PruneCheckpoint { block_number: 4, prune_mode: PruneMode::Before(5) },
+            )
+            .unwrap();
+        assert_eq!(provider_rw.block_number_for_tag(BlockNumberOrTag::Finalized), Ok(None));
+        assert_eq!(provider_rw.block_number_for_tag(BlockNumberOrTag::Earliest), Ok(Some(2)));
+    }
+
+    #[test]
+    fn earliest_history_heights() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+        assert_eq!(
+            factory.provider().unwrap().earliest_history_heights(),
+            Ok(HistoryAvailability::default())
+        );
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..=4 {
+            let receipts = vec![Receipt::default()];
+            insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None);
+        }
+        // every block has a single transaction, the tx number is the block number
+        provider_rw.tx_ref().delete::<tables::Headers>(0, None).unwrap();
+        for number in 0..=1 {
+            provider_rw.tx_ref().delete::<tables::BlockBodyIndices>(number, None).unwrap();
+        }
+        for tx_num in 0..=2 {
+            provider_rw.tx_ref().delete::<tables::Receipts>(tx_num, None).unwrap();
+        }
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::SenderRecovery,
+                PruneCheckpoint { block_number: 1, prune_mode: PruneMode::Before(2) },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.earliest_history_heights(),
+            Ok(HistoryAvailability {
+                headers: Some(1),
+                bodies: Some(2),
+                receipts: Some(3),
+                senders: Some(2),
+            })
+        );
+
+        assert_eq!(
+            provider.block(0.into()),
+            Err(ProviderError::BlockPruned { block: 0, lowest_available: 1 }.into())
+        );
+        assert_eq!(
+            provider.block(1.into()),
+            Err(ProviderError::BlockPruned { block: 1, lowest_available: 2 }.into())
+        );
+        assert!(provider.block(2.into()).unwrap().is_some());
+
+        assert_eq!(
+            provider.receipts_by_block(2.into()),
+            Err(ProviderError::ReceiptsPruned { block: 2, prune_height: 2 }.into())
+        );
+        assert_eq!(provider.receipts_by_block(3.into()).unwrap().map(|r| r.len()), Some(1));
+        assert_eq!(provider.receipts_by_block(5.into()), Ok(None));
+    }
+
+    #[test]
+    fn head() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().build());
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, chain_spec.clone());
+        assert_eq!(factory.provider().unwrap().head(), Ok(chain_spec.genesis_head()));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..=2)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(1)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let total_difficulty = provider.header_td_by_number(1).unwrap().unwrap();
+        assert_eq!(
+            provider.head(),
+            Ok(Head {
+                number: 1,
+                hash: blocks[1].hash(),
+                timestamp: blocks[1].timestamp,
+                difficulty: blocks[1].difficulty,
+                total_difficulty,
+            })
+        );
+        drop(provider);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(3)).unwrap();
+        assert_eq!(provider_rw.head(), Err(ProviderError::HeaderNotFound(3.into()).into()));
+
+        // any provider of headers can look up its head
+        let mock = MockEthProvider::default();
+        mock.extend_headers(
+            blocks.iter().map(|block| (block.hash(), block.header.clone().unseal())),
+        );
+        let tip = &blocks[2];
+        assert_eq!(
+            head_from_provider(&mock, &chain_spec),
+            Ok(Head::from_sealed_header(tip, mock.header_td_by_number(2).unwrap().unwrap()))
+        );
+    }
+
+    #[test]
+    fn fork_id() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().put::<tables::CanonicalHeaders>(0, MAINNET.genesis_hash()).unwrap();
+        let genesis_fork_id = ForkId { hash: ForkHash([0xfc, 0x64, 0xec, 0x04]), next: 1150000 };
+        for (head, timestamp, expected) in [
+            (0, 0, genesis_fork_id),
+            (1920000, 0, ForkId { hash: ForkHash([0x91, 0xd1, 0xf9, 0x48]), next: 2463000 }),
+            (15050000, 0, ForkId { hash: ForkHash([0xf0, 0xaf, 0xd0, 0xe3]), next: 1681338455 }),
+            (20000000, 1681338455, ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 }),
+        ] {
+            assert_eq!(provider_rw.fork_id_at(head, timestamp), Ok(expected), "{head}");
+        }
+        assert_eq!(provider_rw.fork_id(), Ok(genesis_fork_id));
+        drop(provider_rw);
+
+        // the fork hashes build on the stored genesis hash
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Homestead, ForkCondition::Block(10))
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1000))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+        let provider_rw = factory.provider_rw().unwrap();
+        let genesis_hash = H256::random();
+        provider_rw.tx_ref().put::<tables::CanonicalHeaders>(0, genesis_hash).unwrap();
+
+        let genesis = ForkHash::from(genesis_hash);
+        let homestead = genesis + 10u64;
+        let shanghai = homestead + 1000u64;
+        for (head, timestamp, expected) in [
+            (0, 0, ForkId { hash: genesis, next: 10 }),
+            (9, 999, ForkId { hash: genesis, next: 10 }),
+            (10, 999, ForkId { hash: homestead, next: 1000 }),
+            (10, 1000, ForkId { hash: shanghai, next: 0 }),
+        ] {
+            assert_eq!(provider_rw.fork_id_at(head, timestamp), Ok(expected), "{head}");
+        }
+
+        // the best block is the genesis block
+        assert_eq!(
+            provider_rw.validate_peer_fork_id(ForkId { hash: genesis, next: 10 }),
+            Ok(Ok(()))
+        );
+        assert_eq!(
+            provider_rw.validate_peer_fork_id(ForkId { hash: homestead, next: 1000 }),
+            Ok(Ok(()))
+        );
+        let remote = ForkId { hash: ForkHash::from(H256::random()), next: 0 };
+        assert_eq!(
+            provider_rw.validate_peer_fork_id(remote),
+            Ok(Err(ValidationError::LocalIncompatibleOrStale {
+                local: ForkId { hash: genesis, next: 10 },
+                remote,
+            }))
+        );
+    }
+
+    #[test]
+    fn fork_activation_queries() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Homestead, ForkCondition::Block(1))
+            .with_fork(Hardfork::Paris, paris)
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1000))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, total_difficulty, timestamp) in
+            [(0, 10, 0), (1, 50, 0), (2, 100, 999), (3, 100, 1000)]
+        {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header = Header { timestamp, difficulty: U256::ZERO, ..block.header.unseal() };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::HeaderTD>(number, U256::from(total_difficulty).into())
+                .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.fork_activation(Hardfork::Paris), paris);
+        assert_eq!(provider.fork_activation(Hardfork::London), ForkCondition::Never);
+        assert!(!provider.is_fork_active_at_block(Hardfork::Homestead, 0));
+        assert!(provider.is_fork_active_at_block(Hardfork::Homestead, 1));
+        assert!(!provider.is_fork_active_at_timestamp(Hardfork::Shanghai, 999));
+        assert!(provider.is_fork_active_at_timestamp(Hardfork::Shanghai, 1000));
+        // timestamp-activated forks are never active by block number and vice versa
+        assert!(!provider.is_fork_active_at_block(Hardfork::Shanghai, u64::MAX));
+        assert!(!provider.is_fork_active_at_timestamp(Hardfork::Homestead, u64::MAX));
+
+        for (number, spec_id) in [
+            (0, SpecId::FRONTIER),
+            (1, SpecId::HOMESTEAD),
+            (2, SpecId::MERGE),
+            (3, SpecId::SHANGHAI),
+        ] {
+            assert_eq!(provider.spec_id_at_block(number.into()), Ok(spec_id), "{number}");
+        }
+        assert_eq!(
+            provider.spec_id_at_block(4.into()),
+            Err(ProviderError::HeaderNotFound(4.into()).into())
+        );
+    }
+
+    #[test]
+    fn validate_genesis() {
+        let db = create_test_rw_db();
+        let chain_spec = MAINNET.clone();
+        let provider = DatabaseProvider::new_checked(db.tx().unwrap(), chain_spec.clone()).unwrap();
+        assert_eq!(provider.chain_spec(), &chain_spec);
+        drop(provider);
+
+        // a database of another chain
+        let got = H256::random();
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::CanonicalHeaders>(0, got).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(
+            DatabaseProvider::new_checked(db.tx().unwrap(), chain_spec.clone()).err(),
+            Some(ProviderError::GenesisMismatch { expected: MAINNET.genesis_hash(), got }.into())
+        );
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::CanonicalHeaders>(0, MAINNET.genesis_hash()).unwrap();
+        tx.commit().unwrap();
+        let provider = DatabaseProvider::new(db.tx().unwrap(), chain_spec);
+        assert_eq!(provider.validate_genesis(), Ok(()));
+    }
+
+    #[test]
+    fn transaction_by_hash_with_meta_and_receipt() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let receipt = |cumulative_gas_used, success| Receipt {
+            cumulative_gas_used,
+            success,
+            ..Default::default()
+        };
+
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![receipt(21_000, true)], None);
+        let receipts = vec![receipt(30_000, true), receipt(51_000, false), receipt(120_000, true)];
+        let block = insert_block_with_receipts(&provider_rw, &mut rng, 1, receipts.clone(), None);
+        // the receipt of the last transaction is not available
+        provider_rw.tx_ref().delete::<tables::Receipts>(3, None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let meta = |index: usize, gas_used, success| TransactionMeta {
+            tx_hash: block.body[index].hash(),
+            index: index as u64,
+            block_hash: block.hash(),
+            block_number: 1,
+            base_fee: block.base_fee_per_gas,
+            gas_used,
+            success,
+        };
+
+        for (index, gas_used, success, receipt) in [
+            (0, Some(30_000), Some(true), Some(receipts[0].clone())),
+            (1, Some(21_000), Some(false), Some(receipts[1].clone())),
+            (2, None, None, None),
+        ] {
+            let transaction = &block.body[index];
+            assert_eq!(
+                provider.transaction_by_hash_with_meta_and_receipt(transaction.hash()),
+                Ok(Some((transaction.clone(), meta(index, gas_used, success), receipt)))
+            );
+        }
+        assert_eq!(provider.transaction_by_hash_with_meta_and_receipt(H256::random()), Ok(None));
+    }
+
+    #[test]
+    fn transactions_no_hash() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let block = insert_block_with_receipts(
+            &provider_rw,
+            &mut rng,
+            0,
+            vec![Receipt::default(), Receipt::default()],
+            None,
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for (id, transaction) in block.body.iter().enumerate() {
+            let stored = provider.transaction_by_id_no_hash(id as u64).unwrap().unwrap();
+            assert_eq!(stored.recover_signer(), transaction.recover_signer());
+            assert_eq!(stored.with_hash(), *transaction);
+        }
+        assert_eq!(provider.transaction_by_id_no_hash(2), Ok(None));
+
+        let stored = provider.transactions_by_block_no_hash(block.hash().into()).unwrap().unwrap();
+        assert_eq!(
+            stored.into_iter().map(|tx| tx.with_hash()).collect::<Vec<_>>(),
+            provider.transactions_by_block(0.into()).unwrap().unwrap()
+        );
+        assert_eq!(provider.transactions_by_block_no_hash(1.into()), Ok(None));
+    }
+
+    #[test]
+    fn fill_env_for_next_block() {
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::London, ForkCondition::Block(2))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut headers = Vec::new();
+        for (number, base_fee_per_gas) in [(0, None), (1, None), (2, Some(1_000_000_000))] {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header = Header {
+                timestamp: 100 + number,
+                difficulty: U256::from(10),
+                gas_limit: 30_000_000,
+                gas_used: 30_000_000,
+                base_fee_per_gas,
+                ..block.header.unseal()
+            };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header.clone()).unwrap();
+            headers.push(header);
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(1)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let next_env = |attributes| {
+            let mut cfg = CfgEnv::default();
+            let mut block_env = BlockEnv::default();
+            factory.fill_env_for_next_block(&mut cfg, &mut block_env, attributes).unwrap();
+            (cfg.spec_id, block_env)
+        };
+
+        // the next block activates London and starts at the initial base fee
+        let (spec_id, block_env) = next_env(None);
+        assert_eq!(spec_id, SpecId::LONDON);
+        assert_eq!(block_env.number, U256::from(2));
+        assert_eq!(block_env.timestamp, U256::from(102));
+        assert_eq!(block_env.coinbase, headers[1].beneficiary);
+        assert_eq!(block_env.gas_limit, U256::from(30_000_000));
+        assert_eq!(block_env.difficulty, U256::from(10));
+        assert_eq!(block_env.basefee, U256::from(1_000_000_000));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(2)).unwrap();
+        provider_rw.commit().unwrap();
+
+        // the base fee of the block after a full block rises by 12.5%
+        let fee_recipient = Address::random();
+        let (spec_id, block_env) = next_env(Some(NextBlockAttributes {
+            timestamp: Some(200),
+            suggested_fee_recipient: Some(fee_recipient),
+        }));
+        assert_eq!(spec_id, SpecId::LONDON);
+        assert_eq!(block_env.number, U256::from(3));
+        assert_eq!(block_env.timestamp, U256::from(200));
+        assert_eq!(block_env.coinbase, fee_recipient);
+        assert_eq!(block_env.basefee, U256::from(1_125_000_000));
+    }
+
+    #[test]
+    fn fill_envs_for_range() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Homestead, ForkCondition::Block(2))
+            .with_fork(Hardfork::Paris, paris)
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1000))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, total_difficulty, timestamp) in
+            [(0, 10, 0), (1, 50, 0), (2, 90, 0), (3, 100, 999), (4, 100, 1000)]
+        {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header = Header { timestamp, difficulty: U256::ZERO, ..block.header.unseal() };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::HeaderTD>(number, U256::from(total_difficulty).into())
+                .unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        // the range crosses a block, a total difficulty and a timestamp activated fork
+        let envs = provider.fill_envs_for_range(..).unwrap();
+        assert_eq!(
+            envs.iter().map(|(cfg, _)| cfg.spec_id).collect::<Vec<_>>(),
+            vec![
+                SpecId::FRONTIER,
+                SpecId::FRONTIER,
+                SpecId::HOMESTEAD,
+                SpecId::MERGE,
+                SpecId::SHANGHAI
+            ]
+        );
+        for (number, (cfg, block_env)) in envs.into_iter().enumerate() {
+            let mut expected_cfg = CfgEnv::default();
+            let mut expected_block_env = BlockEnv::default();
+            provider
+                .fill_env_at(&mut expected_cfg, &mut expected_block_env, (number as u64).into())
+                .unwrap();
+            assert_eq!(cfg.chain_id, expected_cfg.chain_id);
+            assert_eq!(cfg.spec_id, expected_cfg.spec_id);
+            assert_eq!(block_env.number, expected_block_env.number);
+            assert_eq!(block_env.coinbase, expected_block_env.coinbase);
+            assert_eq!(block_env.timestamp, expected_block_env.timestamp);
+            assert_eq!(block_env.difficulty, expected_block_env.difficulty);
+            assert_eq!(block_env.prevrandao, expected_block_env.prevrandao);
+            assert_eq!(block_env.basefee, expected_block_env.basefee);
+            assert_eq!(block_env.gas_limit, expected_block_env.gas_limit);
+        }
+
+        let envs = provider.fill_envs_for_range(2..4).unwrap();
+        assert_eq!(
+            envs.iter().map(|(cfg, block_env)| (cfg.spec_id, block_env.number)).collect::<Vec<_>>(),
+            vec![(SpecId::HOMESTEAD, U256::from(2)), (SpecId::MERGE, U256::from(3))]
+        );
+        assert!(provider.fill_envs_for_range(5..).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fill_env_with_header_merge_transition() {
+        let paris = ForkCondition::TTD { fork_block: Some(4), total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Paris, paris)
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut headers = Vec::new();
+        // block 2 is the terminal proof of work block, block 3 the first proof of stake block
+        for (number, total_difficulty, difficulty) in
+            [(0, 50, 50), (1, 90, 40), (2, 100, 10), (3, 100, 0)]
+        {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header =
+                Header { difficulty: U256::from(difficulty), nonce: 0, ..block.header.unseal() };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header.clone()).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::HeaderTD>(number, U256::from(total_difficulty).into())
+                .unwrap();
+            headers.push(header);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let spec_id = |header: &Header| {
+            let mut cfg = CfgEnv::default();
+            provider.fill_cfg_env_with_header(&mut cfg, header).map(|_| cfg.spec_id)
+        };
+        for (header, expected) in headers.iter().zip([
+            SpecId::FRONTIER,
+            SpecId::FRONTIER,
+            SpecId::FRONTIER,
+            SpecId::MERGE,
+        ]) {
+            let mut cfg = CfgEnv::default();
+            provider.fill_cfg_env_at(&mut cfg, header.number.into()).unwrap();
+            assert_eq!(cfg.spec_id, expected);
+            assert_eq!(spec_id(header), Ok(expected));
+
+            let (cfg, block_env) = provider.env_with_header(header).unwrap();
+            assert_eq!(cfg.spec_id, expected);
+            assert_eq!(block_env.difficulty, header.difficulty);
+        }
+
+        // headers from the fork block onwards don't need their total difficulty
+        let header = Header { number: 5, mix_hash: H256::random(), ..Default::default() };
+        assert_eq!(spec_id(&header), Ok(SpecId::MERGE));
+        let mut block_env = BlockEnv::default();
+        provider.fill_block_env_with_header(&mut block_env, &header).unwrap();
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert_eq!(block_env.prevrandao, Some(header.mix_hash));
+
+        // headers in the transition window do
+        drop(provider);
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().delete::<tables::HeaderTD>(3, None).unwrap();
+        provider_rw.commit().unwrap();
+        let provider = factory.provider().unwrap();
+        let mut cfg = CfgEnv::default();
+        assert_eq!(
+            provider.fill_cfg_env_with_header(&mut cfg, &headers[3]),
+            Err(ProviderError::HeaderNotFound(3.into()).into())
+        );
+    }
+
+    #[test]
+    fn fill_env_with_header_and_td() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Paris, paris)
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+        let provider = factory.provider().unwrap();
+
+        // the header is not persisted, e.g. a side chain header held in memory
+        let header = Header { number: 1, mix_hash: H256::random(), ..Default::default() };
+        let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+        assert_eq!(
+            provider.fill_env_with_header(&mut cfg, &mut block_env, &header),
+            Err(ProviderError::HeaderNotFound(1.into()).into())
+        );
+
+        for (total_difficulty, spec_id, prevrandao) in
+            [(99, SpecId::FRONTIER, None), (100, SpecId::MERGE, Some(header.mix_hash))]
+        {
+            let total_difficulty = U256::from(total_difficulty);
+            let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+            provider
+                .fill_env_with_header_and_td(&mut cfg, &mut block_env, &header, total_difficulty)
+                .unwrap();
+            assert_eq!(cfg.spec_id, spec_id);
+            assert_eq!(block_env.prevrandao, prevrandao);
+
+            let mut cfg = CfgEnv::default();
+            provider.fill_cfg_env_with_header_and_td(&mut cfg, &header, total_difficulty).unwrap();
+            assert_eq!(cfg.spec_id, spec_id);
+
+            let mut block_env = BlockEnv::default();
+            provider
+                .fill_block_env_with_header_and_td(&mut block_env, &header, total_difficulty)
+                .unwrap();
+            assert_eq!(block_env.prevrandao, prevrandao);
+        }
+    }
+
+    #[test]
+    fn fill_block_env_with_overrides() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Paris, paris)
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(1000))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut headers = Vec::new();
+        for (number, total_difficulty, difficulty, timestamp) in [(0, 50, 50, 0), (1, 100, 0, 999)]
+        {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header = Header {
+                timestamp,
+                difficulty: U256::from(difficulty),
+                mix_hash: H256::random(),
+                ..block.header.unseal()
+            };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header.clone()).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::HeaderTD>(number, U256::from(total_difficulty).into())
+                .unwrap();
+            headers.push(header);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let fill = |number: u64, overrides| {
+            let mut block_env = BlockEnv::default();
+            provider
+                .fill_block_env_with_overrides(&mut block_env, number.into(), overrides)
+                .unwrap();
+            block_env
+        };
+
+        let coinbase = Address::random();
+        let overrides = BlockEnvOverrides {
+            coinbase: Some(coinbase),
+            gas_limit: Some(1_000),
+            base_fee: Some(7),
+            // past the Shanghai activation
+            timestamp: Some(1000),
+            spec_id: None,
+        };
+        let block_env = fill(1, overrides);
+        let mut expected = BlockEnv::default();
+        provider.fill_block_env_at(&mut expected, 1.into()).unwrap();
+        assert_eq!(block_env.number, expected.number);
+        assert_eq!(block_env.difficulty, expected.difficulty);
+        assert_eq!(block_env.prevrandao, expected.prevrandao);
+        assert_eq!(block_env.coinbase, coinbase);
+        assert_eq!(block_env.gas_limit, U256::from(1_000));
+        assert_eq!(block_env.basefee, U256::from(7));
+        assert_eq!(block_env.timestamp, U256::from(1000));
+        // the spec is still chosen from the real header
+        assert_eq!(provider.spec_id_at_block(1.into()), Ok(SpecId::MERGE));
+
+        // without overrides the env is filled from the header only
+        let block_env = fill(0, BlockEnvOverrides::default());
+        assert_eq!(block_env.coinbase, headers[0].beneficiary);
+        assert_eq!(block_env.timestamp, U256::ZERO);
+        assert_eq!(block_env.difficulty, U256::from(50));
+        assert_eq!(block_env.prevrandao, None);
+
+        // the spec can be overridden explicitly
+        let block_env =
+            fill(0, BlockEnvOverrides { spec_id: Some(SpecId::MERGE), ..Default::default() });
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert_eq!(block_env.prevrandao, Some(headers[0].mix_hash));
+        let block_env =
+            fill(1, BlockEnvOverrides { spec_id: Some(SpecId::LONDON), ..Default::default() });
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert_eq!(block_env.prevrandao, None);
+    }
+
+    #[test]
+    fn fill_env_at_cached() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Paris, paris)
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut blocks = Vec::new();
+        for number in 0..8 {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let header = Header { difficulty: U256::from(20), ..block.header.clone().unseal() };
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::HeaderTD>(number, U256::from(20 * (number + 1)).into())
+                .unwrap();
+            blocks.push(block);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let spec_id = |id: BlockHashOrNumber| {
+            let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+            provider.fill_env_at(&mut cfg, &mut block_env, id).unwrap();
+            (cfg.spec_id, block_env.number)
+        };
+        // block 5 is the first block whose parent reached the terminal total difficulty
+        let expected = |number: u64| {
+            (if number < 5 { SpecId::FRONTIER } else { SpecId::MERGE }, U256::from(number))
+        };
+
+        // repeated fills, by number and by hash, and more blocks than fit into the cache
+        for _ in 0..2 {
+            for block in &blocks {
+                assert_eq!(spec_id(block.number.into()), expected(block.number));
+                assert_eq!(spec_id(block.hash().into()), expected(block.number));
+                assert_eq!(spec_id(block.number.into()), expected(block.number));
+                assert_eq!(
+                    provider.spec_id_at_block(block.number.into()),
+                    Ok(expected(block.number).0)
+                );
+            }
+        }
+
+        let mut cfg = CfgEnv::default();
+        assert_eq!(
+            provider.fill_cfg_env_at(&mut cfg, 8.into()),
+            Err(ProviderError::HeaderNotFound(8.into()).into())
+        );
+    }
+
+    #[test]
+    fn fill_block_env_with_header_merge_fields() {
+        let paris = ForkCondition::TTD { fork_block: None, total_difficulty: U256::from(100) };
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .with_fork(Hardfork::Paris, paris)
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+        let provider = factory.provider().unwrap();
+        let (pre_merge, post_merge) = (U256::from(99), U256::from(100));
+
+        // pre-merge headers are accepted with or without a mix hash
+        for mix_hash in [H256::zero(), H256::random()] {
+            let header = Header {
+                number: 1,
+                difficulty: U256::from(10),
+                nonce: 42,
+                mix_hash,
+                ..Default::default()
+            };
+            let mut block_env = BlockEnv::default();
+            provider.fill_block_env_with_header_and_td(&mut block_env, &header, pre_merge).unwrap();
+            assert_eq!(block_env.difficulty, header.difficulty);
+        }
+
+        // post-merge headers without proof-of-work fields are accepted
+        let header = Header { number: 1, mix_hash: H256::random(), ..Default::default() };
+        let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+        provider
+            .fill_env_with_header_and_td(&mut cfg, &mut block_env, &header, post_merge)
+            .unwrap();
+        assert_eq!(block_env.prevrandao, Some(header.mix_hash));
+
+        // post-merge headers with a difficulty or nonce are rejected
+        for (difficulty, nonce) in [(U256::from(10), 0), (U256::ZERO, 42)] {
+            let header = Header { difficulty, nonce, ..header.clone() };
+            let err = ProviderError::InconsistentHeader { number: 1, difficulty, nonce };
+            let mut block_env = BlockEnv::default();
+            assert_eq!(
+                provider.fill_block_env_with_header_and_td(&mut block_env, &header, post_merge),
+                Err(err.clone().into())
+            );
+            let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+            assert_eq!(
+                provider.fill_env_with_header_and_td(&mut cfg, &mut block_env, &header, post_merge),
+                Err(err.into())
+            );
+        }
+
+        // unless the check is disabled
+        let provider = provider.without_merge_field_checks();
+        let header = Header { difficulty: U256::from(10), nonce: 42, ..header };
+        let mut block_env = BlockEnv::default();
+        provider.fill_block_env_with_header_and_td(&mut block_env, &header, post_merge).unwrap();
+        assert_eq!(block_env.prevrandao, Some(header.mix_hash));
+    }
+
+    #[test]
+    fn fill_env_at_block_id() {
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+        let env_at = |at: BlockId| {
+            let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+            let num_hash = factory.fill_env_at_block_id(&mut cfg, &mut block_env, at).unwrap();
+            num_hash.map(|num_hash| (num_hash, block_env.number))
+        };
+        assert_eq!(env_at(BlockNumberOrTag::Latest.into()), None);
+
+        let mut rng = generators::rng();
+        let mut blocks = Vec::new();
+        for range in [0..2, 2..4] {
+            let provider_rw = factory.provider_rw().unwrap();
+            for number in range {
+                blocks.push(insert_block_with_receipts(
+                    &provider_rw,
+                    &mut rng,
+                    number,
+                    vec![],
+                    None,
+                ));
+            }
+            let head = blocks.last().unwrap().num_hash();
+            provider_rw
+                .save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(head.number))
+                .unwrap();
+            provider_rw.commit().unwrap();
+
+            // the latest block moves with the inserted blocks
+            assert_eq!(
+                env_at(BlockNumberOrTag::Latest.into()),
+                Some((head, U256::from(head.number)))
+            );
+            // the pending environment is the one of the block following the latest
+            assert_eq!(
+                env_at(BlockNumberOrTag::Pending.into()),
+                Some((head, U256::from(head.number + 1)))
+            );
+        }
+
+        // headers synced past the executed tip don't move the latest block
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 4, vec![], None);
+        provider_rw.commit().unwrap();
+        assert_eq!(
+            env_at(BlockNumberOrTag::Latest.into()),
+            Some((blocks[3].num_hash(), U256::from(3)))
+        );
+
+        assert_eq!(env_at(blocks[1].hash().into()), Some((blocks[1].num_hash(), U256::from(1))));
+        assert_eq!(
+            env_at(BlockNumberOrTag::Earliest.into()),
+            Some((blocks[0].num_hash(), U256::ZERO))
+        );
+        assert_eq!(env_at(BlockNumberOrTag::Safe.into()), None);
+        assert_eq!(env_at(H256::random().into()), None);
+
+        // a side chain block at the same height doesn't get the environment of the canonical one
+        let side_hash = H256::random();
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(side_hash, 1).unwrap();
+        provider_rw.commit().unwrap();
+        assert_eq!(env_at(side_hash.into()), None);
+        assert_eq!(env_at(BlockId::Hash(RpcBlockHash::from_hash(side_hash, Some(true)))), None);
+        assert_eq!(
+            env_at(BlockId::Hash(RpcBlockHash::from_hash(blocks[1].hash(), Some(true)))),
+            Some((blocks[1].num_hash(), U256::from(1)))
+        );
+    }
+
+    #[test]
+    fn env_with_state_at() {
+        let chain_spec = ChainSpec::builder()
+            .chain(Chain::mainnet())
+            .genesis(Genesis::default())
+            .with_fork(Hardfork::Frontier, ForkCondition::Block(0))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+
+        // the account is created in block 1 and changed in block 2
+        let address = Address::random();
+        let created = Account { balance: U256::from(10), ..Default::default() };
+        let changed = Account { nonce: 1, balance: U256::from(7), ..Default::default() };
+        let mut post_state = PostState::default();
+        post_state.create_account(1, address, created);
+        post_state.change_account(2, address, created, changed);
+        post_state.write_to_db(provider_rw.tx_ref()).unwrap();
+        provider_rw.calculate_history_indices(1..=2).unwrap();
+        // a block at height 2 that was reorged out
+        let reorged = H256::random();
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(reorged, 2).unwrap();
+        provider_rw.commit().unwrap();
+
+        // the state is the one of the parent block
+        for (block, expected) in blocks.iter().zip([None, None, Some(created)]).skip(1) {
+            let ids: [BlockHashOrNumber; 2] = [block.number.into(), block.hash().into()];
+            for id in ids {
+                let EnvWithState { block_env, state, .. } = factory.env_with_state_at(id).unwrap();
+                assert_eq!(block_env.number, U256::from(block.number));
+                assert_eq!(state.basic_account(address), Ok(expected));
+            }
+        }
+        assert_eq!(factory.latest().unwrap().basic_account(address), Ok(Some(changed)));
+
+        for hash in [H256::random(), reorged] {
+            assert_eq!(
+                factory.env_with_state_at(hash.into()).err(),
+                Some(ProviderError::BlockHashNotFound(hash).into())
+            );
+        }
+    }
+
+    #[test]
+    fn block_body_indices_range() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, tx_count) in [(0, 0), (1, 2), (2, 0), (3, 1)] {
+            let receipts = vec![Receipt::default(); tx_count];
+            insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let indices = provider.block_body_indices_range(1..=3).unwrap();
+        assert_eq!(
+            indices.iter().map(|(number, body)| (*number, body.tx_count())).collect::<Vec<_>>(),
+            vec![(1, 2), (2, 0), (3, 1)]
+        );
+        for (number, body) in indices {
+            assert_eq!(provider.block_body_indices(number), Ok(Some(body)));
+        }
+        assert_eq!(
+            provider.block_body_indices_range(3..),
+            Ok(vec![(3, provider.block_body_indices(3).unwrap().unwrap())])
+        );
+        assert_eq!(provider.block_body_indices_range(4..10), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn transaction_by_hash_in_range() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..1000)
+            .map(|number| {
+                let receipts = vec![Receipt::default()];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // the lookup entries of the transactions up to block 899 are pruned
+        for block in &blocks[..900] {
+            provider_rw.tx_ref().delete::<tables::TxHashNumber>(block.body[0].hash, None).unwrap();
+        }
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::TransactionLookup,
+                PruneCheckpoint { block_number: 899, prune_mode: PruneMode::Before(900) },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for number in [0, 500, 899] {
+            let transaction = &blocks[number].body[0];
+            assert_eq!(
+                provider.transaction_by_hash(transaction.hash),
+                Err(ProviderError::TransactionLookupPruned {
+                    hash: transaction.hash,
+                    prune_height: 899
+                }
+                .into())
+            );
+            assert_eq!(
+                provider.transaction_by_hash_in_range(transaction.hash, 0..1000),
+                Ok(Some(transaction.clone()))
+            );
+        }
+
+        // the transaction is outside of the scanned range
+        let pruned = &blocks[100].body[0];
+        assert_eq!(provider.transaction_by_hash_in_range(pruned.hash, 200..1000), Ok(None));
+        assert_eq!(provider.transaction_by_hash_in_range(pruned.hash, ..100), Ok(None));
+        assert_eq!(
+            provider.transaction_by_hash_in_range(pruned.hash, 100..=100),
+            Ok(Some(pruned.clone()))
+        );
+
+        // transactions with lookup entries are found regardless of the range
+        let transaction = &blocks[950].body[0];
+        assert_eq!(
+            provider.transaction_by_hash_in_range(transaction.hash, 0..10),
+            Ok(Some(transaction.clone()))
+        );
+        assert_eq!(provider.transaction_by_hash_in_range(H256::random(), ..), Ok(None));
+    }
+
+    #[test]
+    fn transactions_by_block_range_raw() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for (number, tx_count) in [(0, 0), (1, 2), (2, 0), (3, 1)] {
+            let receipts = vec![Receipt::default(); tx_count];
+            insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let decoded = provider.transactions_by_block_range(1..=3).unwrap();
+        let raw = provider.transactions_by_block_range_raw(1..=3).unwrap();
+        assert_eq!(raw.iter().map(|(number, _)| *number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        for ((_, raw_transactions), transactions) in raw.iter().zip(decoded) {
+            let raw_decoded = raw_transactions
+                .iter()
+                .map(|tx| {
+                    let raw = RawTransactionRef::new(tx);
+                    let tx = raw.decode().unwrap();
+                    assert_eq!(raw.tx_type(), Some(tx.transaction.tx_type()));
+                    tx.with_hash()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(raw_decoded, transactions);
+        }
+    }
+
+    #[test]
+    fn par_range_reads() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..25 {
+            let receipts = vec![Receipt::default(); number as usize % 3];
+            insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for chunk_size in [0, 1, 4, 10, 100] {
+            assert_eq!(
+                factory.par_headers_range(.., chunk_size).unwrap(),
+                provider.headers_range(..).unwrap()
+            );
+            assert_eq!(
+                factory.par_headers_range(3..17, chunk_size).unwrap(),
+                provider.headers_range(3..17).unwrap()
+            );
+            assert_eq!(
+                factory.par_receipts_by_block_range(5..=30, chunk_size).unwrap(),
+                provider.receipts_by_block_range(5..=30).unwrap()
+            );
+        }
+        assert!(factory.par_headers_range(10..10, 4).unwrap().is_empty());
+        assert!(factory.par_headers_range(..0, 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn receipts_iter() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let provider_rw = factory.provider_rw().unwrap();
+        // the receipt of transaction 5 is missing
+        for tx_num in (0..10).filter(|tx_num| *tx_num != 5) {
+            let receipt = Receipt { cumulative_gas_used: tx_num, ..Default::default() };
+            provider_rw.tx_ref().put::<tables::Receipts>(tx_num, receipt).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        fn tx_numbers(
+            iter: impl Iterator<Item = reth_interfaces::Result<(TxNumber, Receipt)>>,
+        ) -> Vec<TxNumber> {
+            iter.map(|entry| {
+                let (tx_num, receipt) = entry.unwrap();
+                assert_eq!(receipt.cumulative_gas_used, tx_num);
+                tx_num
+            })
+            .collect()
+        }
+        assert_eq!(tx_numbers(provider.receipts_iter(2..8).unwrap()), vec![2, 3, 4, 6, 7]);
+        assert_eq!(tx_numbers(provider.receipts_iter(..).unwrap()).len(), 9);
+
+        let mut receipts = provider.receipts_iter(..=8).unwrap();
+        assert_eq!(receipts.next().unwrap().unwrap().0, 0);
+        receipts.seek(5).unwrap();
+        assert_eq!(tx_numbers(receipts), vec![6, 7, 8]);
+
+        let mut receipts = provider.receipts_iter(..=8).unwrap();
+        receipts.seek(9).unwrap();
+        assert!(receipts.next().is_none());
+    }
+
+    #[test]
+    fn orphaned_rows_above_head() {
+        let chain_spec = ChainSpecBuilder::mainnet().shanghai_activated().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..=2 {
+            insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+        }
+        // rows of an unwound block whose canonical hash was already removed
+        let withdrawals = vec![Withdrawal { index: 1, amount: 2, ..Default::default() }];
+        provider_rw
+            .tx_ref()
+            .put::<tables::BlockWithdrawals>(5, StoredBlockWithdrawals { withdrawals })
+            .unwrap();
+        provider_rw
+            .tx_ref()
+            .put::<tables::BlockOmmers>(5, StoredBlockOmmers { ommers: vec![Header::default()] })
+            .unwrap();
+        provider_rw.tx_ref().put::<tables::Headers>(5, Header::default()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.withdrawals_by_block(5.into(), 0), Ok(None));
+        assert_eq!(provider.ommers(5.into()), Ok(None));
+        assert_eq!(provider.block(5.into()), Ok(None));
+        assert_eq!(provider.block(12_345_678.into()), Ok(None));
+
+        assert_eq!(provider.withdrawals_by_block(2.into(), 0), Ok(Some(vec![])));
+        assert!(provider.block(2.into()).unwrap().is_some());
+    }
+
+    #[test]
+    fn canonical_block_number() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..=1)
+            .map(|number| {
+                let receipts = vec![Receipt::default()];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // a reorged-out block at the same height, only its hash to number entry is left
+        let fork_hash = H256::random();
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(fork_hash, 1).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_number(fork_hash), Ok(Some(1)));
+        assert_eq!(provider.canonical_block_number(fork_hash), Ok(None));
+        assert_eq!(provider.canonical_block_number(blocks[1].hash()), Ok(Some(1)));
+        assert_eq!(provider.canonical_block_number(H256::random()), Ok(None));
+
+        assert_eq!(provider.block(fork_hash.into()), Ok(None));
+        assert_eq!(provider.receipts_by_block(fork_hash.into()), Ok(None));
+        assert!(provider.block(blocks[1].hash().into()).unwrap().is_some());
+        assert_eq!(provider.receipts_by_block(blocks[1].hash().into()).unwrap().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn block_availability() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = [1, 1, 1, 0]
+            .into_iter()
+            .enumerate()
+            .map(|(number, tx_count)| {
+                let receipts = vec![Receipt::default(); tx_count];
+                insert_block_with_receipts(&provider_rw, &mut rng, number as u64, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // block 2 was not executed yet and the receipts of block 0 were pruned, every block has a
+        // single transaction
+        provider_rw.tx_ref().delete::<tables::Receipts>(2, None).unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Execution, StageCheckpoint::new(1)).unwrap();
+        provider_rw
+            .save_prune_checkpoint(
+                PrunePart::Receipts,
+                PruneCheckpoint { block_number: 0, prune_mode: PruneMode::Before(1) },
+            )
+            .unwrap();
+        let fork_hash = H256::random();
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(fork_hash, 1).unwrap();
+        // the probes must not decode transactions or receipts, these would fail to decode
+        for tx_num in 0..3 {
+            let garbage = vec![0xff; 3];
+            provider_rw
+                .tx_ref()
+                .put::<RawTable<tables::Transactions>>(
+                    RawKey::new(tx_num),
+                    RawValue::decompress(&garbage).unwrap(),
+                )
+                .unwrap();
+            if tx_num == 1 {
+                provider_rw
+                    .tx_ref()
+                    .put::<RawTable<tables::Receipts>>(
+                        RawKey::new(tx_num),
+                        RawValue::decompress(&garbage).unwrap(),
+                    )
+                    .unwrap();
+            }
+        }
+        provider_rw.commit().unwrap();
+
+        // only index tables and raw receipts are read
+        let provider = DatabaseProvider::new(
+            CountingTxMock::new(factory.db.tx().unwrap()),
+            factory.chain_spec.clone(),
+        );
+        let executed = BlockAvailability { known: true, body: true, ..Default::default() };
+        let availability = provider.block_availability(blocks[1].hash()).unwrap();
+        assert_eq!(availability, BlockAvailability { receipts: true, ..executed });
+        assert!(availability.is_complete());
+
+        let availability = provider.block_availability(blocks[0].hash()).unwrap();
+        assert_eq!(availability, BlockAvailability { receipts_pruned: true, ..executed });
+        assert!(availability.is_complete());
+        assert!(!availability.needs_execution());
+
+        let availability = provider.block_availability(blocks[2].hash()).unwrap();
+        assert_eq!(availability, executed);
+        assert!(availability.needs_execution());
+        // empty blocks are checked against the execution checkpoint
+        assert!(provider.block_availability(blocks[3].hash()).unwrap().needs_execution());
+
+        let availability = provider.block_availability(fork_hash).unwrap();
+        assert_eq!(availability, BlockAvailability { known: true, ..Default::default() });
+        assert!(availability.needs_body());
+        assert_eq!(provider.block_availability(H256::random()), Ok(BlockAvailability::default()));
+
+        assert_eq!(provider.is_known_block_hash(fork_hash), Ok(true));
+        assert_eq!(provider.is_known(&blocks[1].hash()), Ok(true));
+        assert_eq!(provider.is_known(&H256::random()), Ok(false));
+        assert_eq!(provider.has_block_body(1.into()), Ok(true));
+        assert_eq!(provider.has_block_body(fork_hash.into()), Ok(false));
+        assert_eq!(provider.has_block_body(4.into()), Ok(false));
+        assert_eq!(provider.has_receipts(blocks[1].hash().into()), Ok(true));
+        assert_eq!(provider.has_receipts(2.into()), Ok(false));
+        assert_eq!(
+            provider.has_receipts(0.into()),
+            Err(ProviderError::ReceiptsPruned { block: 0, prune_height: 0 }.into())
+        );
+
+        for table in [
+            tables::Transactions::const_name(),
+            tables::TxSenders::const_name(),
+            tables::Headers::const_name(),
+            tables::BlockOmmers::const_name(),
+            tables::BlockWithdrawals::const_name(),
+        ] {
+            assert_eq!(provider.tx_ref().reads(table), 0, "{table}");
+        }
+        assert!(provider.tx_ref().reads(tables::Receipts::const_name()) > 0);
+    }
+
+    #[test]
+    fn append_sealed_headers() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut parent_hash = H256::zero();
+        let headers = (0..6)
+            .map(|number| {
+                let header = Header {
+                    number,
+                    parent_hash,
+                    difficulty: U256::from(10),
+                    ..Default::default()
+                }
+                .seal_slow();
+                parent_hash = header.hash();
+                header
+            })
+            .collect::<Vec<_>>();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.append_sealed_headers(headers[..3].to_vec()).unwrap();
+        provider_rw.append_sealed_headers(headers[3..].to_vec()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.sealed_headers_range(..).unwrap(), headers);
+        assert_eq!(provider.block_number(headers[4].hash()), Ok(Some(4)));
+        assert_eq!(
+            provider.tx_ref().get::<tables::HeaderTD>(5).unwrap().map(|td| td.0),
+            Some(U256::from(60))
+        );
+        drop(provider);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let child = Header { number: 6, parent_hash: headers[5].hash(), ..Default::default() };
+        // the parent hash doesn't match the canonical tip
+        let detached = Header { parent_hash: H256::random(), ..child.clone() }.seal_slow();
+        assert_eq!(
+            provider_rw.append_sealed_headers(vec![detached.clone()]),
+            Err(ProviderError::DetachedHeaders {
+                number: 6,
+                parent_hash: detached.parent_hash,
+                tip_number: 5,
+                tip_hash: headers[5].hash(),
+            }
+            .into())
+        );
+        // the batch skips a block
+        let child = child.seal_slow();
+        let gap = Header { number: 8, parent_hash: child.hash(), ..Default::default() }.seal_slow();
+        assert_eq!(
+            provider_rw.append_sealed_headers(vec![child, gap]),
+            Err(ProviderError::NonContiguousHeaders { number: 8 }.into())
+        );
+        assert_eq!(provider_rw.last_block_number(), Ok(5));
+    }
+
+    #[test]
+    fn take_block_range() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..5)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        let taken = provider_rw.take_block_range(3..=4).unwrap();
+        assert_eq!(
+            taken.into_iter().map(|block| block.block).collect::<Vec<_>>(),
+            blocks[3..].to_vec()
+        );
+        provider_rw.remove_block_range(2..=2).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        // every block has two transactions
+        for block in &blocks[2..] {
+            for tx in &block.body {
+                assert_eq!(provider.tx_ref().get::<tables::TxHashNumber>(tx.hash()), Ok(None));
+            }
+            assert_eq!(provider.tx_ref().get::<tables::HeaderNumbers>(block.hash()), Ok(None));
+        }
+        assert_eq!(provider.tx_ref().entries::<tables::TransactionBlock>(), Ok(2));
+        assert_eq!(provider.tx_ref().entries::<tables::TxHashNumber>(), Ok(4));
+        for (table, entries) in [
+            (provider.tx_ref().entries::<tables::Transactions>(), 4),
+            (provider.tx_ref().entries::<tables::TxSenders>(), 4),
+            (provider.tx_ref().entries::<tables::Receipts>(), 4),
+            (provider.tx_ref().entries::<tables::Headers>(), 2),
+            (provider.tx_ref().entries::<tables::CanonicalHeaders>(), 2),
+            (provider.tx_ref().entries::<tables::HeaderNumbers>(), 2),
+            (provider.tx_ref().entries::<tables::HeaderTD>(), 2),
+            (provider.tx_ref().entries::<tables::BlockBodyIndices>(), 2),
+        ] {
+            assert_eq!(table, Ok(entries));
+        }
+        assert_eq!(
+            provider.block(1.into()).unwrap().map(|block| block.seal_slow()),
+            Some(blocks[1].clone())
+        );
+    }
+
+    #[test]
+    fn append_blocks_with_receipts() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let mut parent = H256::zero();
+        let blocks = (0..3)
+            .map(|number| {
+                let block = random_block(&mut rng, number, Some(parent), Some(2), Some(0));
+                parent = block.hash();
+                let senders = block.body.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+                SealedBlockWithSenders { block, senders }
+            })
+            .collect::<Vec<_>>();
+        let receipts = blocks
+            .iter()
+            .map(|block| {
+                (0..block.body.len() as u64)
+                    .map(|index| Receipt { cumulative_gas_used: index + 1, ..Default::default() })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(
+            provider_rw.append_blocks_with_receipts(blocks.clone(), receipts[..2].to_vec()),
+            Err(ProviderError::ReceiptsLengthMismatch { blocks: 3, receipts: 2 }.into())
+        );
+        let mut short_receipts = receipts.clone();
+        short_receipts[1].pop();
+        assert_eq!(
+            provider_rw.append_blocks_with_receipts(blocks.clone(), short_receipts),
+            Err(ProviderError::BlockReceiptsCountMismatch {
+                block_number: 1,
+                transactions: 2,
+                receipts: 1
+            }
+            .into())
+        );
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(0));
+
+        // dropping the provider before the commit leaves nothing behind
+        provider_rw.append_blocks_with_receipts(blocks.clone(), receipts.clone()).unwrap();
+        drop(provider_rw);
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block(0.into()), Ok(None));
+        assert_eq!(provider.tx_ref().entries::<tables::Receipts>(), Ok(0));
+        assert_eq!(provider.get_stage_checkpoint(StageId::Execution), Ok(None));
+        drop(provider);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.append_blocks_with_receipts(blocks.clone(), receipts.clone()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for (block, receipts) in blocks.iter().zip(receipts) {
+            assert_eq!(
+                provider.block(block.number.into()).unwrap(),
+                Some(block.block.clone().unseal())
+            );
+            assert_eq!(provider.receipts_by_block(block.number.into()), Ok(Some(receipts)));
+        }
+        for stage_id in [StageId::Bodies, StageId::Execution] {
+            assert_eq!(
+                provider
+                    .get_stage_checkpoint(stage_id)
+                    .unwrap()
+                    .map(|checkpoint| checkpoint.block_number),
+                Some(2)
+            );
+        }
+    }
+
+    #[test]
+    fn update_canonical_chain() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let chain = |parent: Option<&SealedHeader>, numbers: Range<u64>, extra_data: &[u8]| {
+            let mut parent_hash = parent.map(|parent| parent.hash()).unwrap_or_default();
+            numbers
+                .map(|number| {
+                    let header = Header {
+                        number,
+                        parent_hash,
+                        difficulty: U256::from(10 * extra_data.len()),
+                        extra_data: extra_data.to_vec().into(),
+                        ..Default::default()
+                    }
+                    .seal_slow();
+                    parent_hash = header.hash();
+                    header
+                })
+                .collect::<Vec<_>>()
+        };
+        let old_chain = chain(None, 0..10, b"a");
+        let new_branch = chain(Some(&old_chain[4]), 5..10, b"bb");
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.append_sealed_headers(old_chain.clone()).unwrap();
+        for number in 0..10 {
+            provider_rw
+                .tx_ref()
+                .put::<tables::BlockBodyIndices>(number, StoredBlockBodyIndices::default())
+                .unwrap();
+        }
+        assert_eq!(
+            provider_rw.update_canonical_chain(chain(Some(&old_chain[0]), 5..7, b"bb")),
+            Err(ProviderError::UnknownForkBlock { number: 5, parent_hash: old_chain[0].hash() }
+                .into())
+        );
+
+        // headers that are already canonical are skipped
+        let new_chain = [&old_chain[3..5], &new_branch[..]].concat();
+        assert_eq!(
+            provider_rw.update_canonical_chain(new_chain.clone()),
+            Ok(ReorgOutcome {
+                fork_block: Some(old_chain[4].num_hash()),
+                changed: (5..10).collect()
+            })
+        );
+        // the appended headers and the new branch are counted, the old branch is removed
+        let stats = provider_rw.commit().unwrap();
+        for table in [
+            tables::CanonicalHeaders::const_name(),
+            tables::Headers::const_name(),
+            tables::HeaderNumbers::const_name(),
+            tables::HeaderTD::const_name(),
+        ] {
+            let table_stats = stats.tables[table];
+            assert_eq!((table_stats.puts, table_stats.deletes), (15, 5), "{table}");
+        }
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.sealed_headers_range(..).unwrap(),
+            [&old_chain[..5], &new_branch].concat()
+        );
+        for (old, new) in old_chain[5..].iter().zip(&new_branch) {
+            assert_eq!(provider.block_number(old.hash()), Ok(None));
+            assert_eq!(provider.canonical_block_number(old.hash()), Ok(None));
+            assert_eq!(provider.canonical_block_number(new.hash()), Ok(Some(new.number)));
+        }
+        assert_eq!(provider.header_td_by_number(9), Ok(Some(U256::from(150))));
+        // the bodies of the replaced blocks are removed with them
+        assert_eq!(provider.block_body_indices_range(..).unwrap().len(), 5);
+        assert_eq!(provider.block_body_indices(5), Ok(None));
+        drop(provider);
+
+        // switching to a shorter chain removes the blocks above its tip
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(
+            provider_rw.update_canonical_chain(old_chain[..7].to_vec()),
+            Ok(ReorgOutcome {
+                fork_block: Some(old_chain[4].num_hash()),
+                changed: (5..10).collect()
+            })
+        );
+        assert_eq!(provider_rw.sealed_headers_range(..).unwrap(), old_chain[..7]);
+        assert_eq!(provider_rw.last_block_number(), Ok(6));
+        assert_eq!(provider_rw.header_td_by_number(6), Ok(Some(U256::from(70))));
+        assert_eq!(provider_rw.header_td_by_number(7), Ok(None));
+        assert_eq!(
+            provider_rw.update_canonical_chain(old_chain[..7].to_vec()),
+            Ok(ReorgOutcome { fork_block: Some(old_chain[6].num_hash()), changed: Vec::new() })
+        );
+    }
+
+    #[test]
+    fn commit_stats() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut parent = H256::zero();
+        for number in 0..3 {
+            let block = random_block(&mut rng, number, Some(parent), Some(2), Some(0));
+            parent = block.hash();
+            provider_rw.insert_block(block, None).unwrap();
+        }
+        provider_rw.remove_block_range(2..=2).unwrap();
+        let stats = provider_rw.commit().unwrap();
+
+        let table_stats = |name: &str| stats.tables.get(name).copied().unwrap_or_default();
+        for (name, puts, deletes) in [
+            (tables::CanonicalHeaders::const_name(), 3, 1),
+            (tables::Headers::const_name(), 3, 1),
+            (tables::HeaderNumbers::const_name(), 3, 1),
+            (tables::HeaderTD::const_name(), 3, 1),
+            (tables::Transactions::const_name(), 6, 2),
+            (tables::TxSenders::const_name(), 6, 2),
+            (tables::TxHashNumber::const_name(), 6, 2),
+            (tables::TransactionBlock::const_name(), 3, 1),
+            (tables::BlockBodyIndices::const_name(), 3, 1),
+        ] {
+            let table_stats = table_stats(name);
+            assert_eq!((table_stats.puts, table_stats.deletes), (puts, deletes), "{name}");
+        }
+        assert_eq!(table_stats(tables::BlockOmmers::const_name()), TableWriteStats::default());
+        assert_eq!(table_stats(tables::Receipts::const_name()), TableWriteStats::default());
+        // block numbers are encoded as 8 bytes and hashes as 32 bytes
+        assert_eq!(table_stats(tables::CanonicalHeaders::const_name()).bytes, 3 * (8 + 32));
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::Transactions>(), Ok(4));
+    }
+
+    #[test]
+    fn stage_checkpoints() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+        let checkpoint = |block_number| StageCheckpoint { block_number, ..Default::default() };
+
+        // checkpoints written with the plain stage names by earlier versions
+        let provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_ref();
+        tx.put::<tables::SyncStage>("Finish".to_string(), checkpoint(5)).unwrap();
+        tx.put::<tables::SyncStage>("Headers".to_string(), checkpoint(10)).unwrap();
+        tx.put::<tables::SyncStage>("CustomStage".to_string(), checkpoint(3)).unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Bodies, checkpoint(7)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.get_stage_checkpoint(StageId::Headers), Ok(Some(checkpoint(10))));
+        assert_eq!(provider.get_stage_checkpoint(StageId::Execution), Ok(None));
+        assert_eq!(
+            provider.get_stage_checkpoint(StageId::Other("CustomStage")),
+            Ok(Some(checkpoint(3)))
+        );
+        assert_eq!(
+            provider.tx_ref().get::<tables::SyncStage>("Bodies".to_string()),
+            Ok(Some(checkpoint(7)))
+        );
+        assert_eq!(
+            provider.get_all_stage_checkpoints(),
+            Ok(vec![
+                (StageId::Headers, checkpoint(10)),
+                (StageId::Bodies, checkpoint(7)),
+                (StageId::Finish, checkpoint(5)),
+            ])
+        );
+        assert_eq!(provider.best_block_number(), Ok(5));
+    }
+
+    #[test]
+    fn prune_transaction_lookup() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..5)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+        let prune_height = |provider: &DatabaseProvider<'_, _>| {
+            provider
+                .get_prune_checkpoint(PrunePart::TransactionLookup)
+                .unwrap()
+                .map(|checkpoint| checkpoint.block_number)
+        };
+
+        // the limit is reached in the middle of block 2
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_transaction_lookup(2, 5), Ok(PruneProgress::HasMoreData));
+        assert_eq!(prune_height(&*provider_rw), Some(1));
+        provider_rw.commit().unwrap();
+
+        // an interrupted call leaves nothing behind
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_transaction_lookup(2, 1), Ok(PruneProgress::HasMoreData));
+        drop(provider_rw);
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TxHashNumber>(), Ok(5));
+        assert_eq!(provider_rw.prune_transaction_lookup(2, 5), Ok(PruneProgress::Finished));
+        assert_eq!(prune_height(&*provider_rw), Some(2));
+        assert_eq!(provider_rw.prune_transaction_lookup(2, 5), Ok(PruneProgress::Finished));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::TxHashNumber>(), Ok(4));
+        for block in &blocks[..3] {
+            for transaction in &block.body {
+                assert_eq!(
+                    provider.transaction_by_hash(transaction.hash),
+                    Err(ProviderError::TransactionLookupPruned {
+                        hash: transaction.hash,
+                        prune_height: 2
+                    }
+                    .into())
+                );
+            }
+        }
+        for block in &blocks[3..] {
+            for transaction in &block.body {
+                assert_eq!(
+                    provider.transaction_by_hash(transaction.hash),
+                    Ok(Some(transaction.clone()))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prune_transaction_lookup_remapped_hash() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // the hash of the first transaction is mapped to a transaction of the last block
+        let remapped = blocks[0].body[0].hash();
+        provider_rw.tx_ref().put::<tables::TxHashNumber>(remapped, 4).unwrap();
+
+        assert_eq!(
+            provider_rw.prune_transaction_lookup(1, usize::MAX),
+            Ok(PruneProgress::Finished)
+        );
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::TxHashNumber>(), Ok(3));
+        assert_eq!(provider.transaction_id(remapped), Ok(Some(4)));
+        for transaction in &blocks[2].body {
+            assert!(provider.transaction_id(transaction.hash()).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn prune_receipts() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        // the first receipt of each block has a log of the retained address
+        let (retained, other) = (Address::random(), Address::random());
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let receipts = (0..4)
+            .map(|number| {
+                let receipts = vec![
+                    Receipt {
+                        logs: vec![random_log(&mut rng, Some(retained), Some(1))],
+                        ..Default::default()
+                    },
+                    Receipt {
+                        logs: vec![random_log(&mut rng, Some(other), Some(1))],
+                        ..Default::default()
+                    },
+                    Receipt::default(),
+                ];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts.clone(), None);
+                receipts
+            })
+            .collect::<Vec<_>>();
+        provider_rw.commit().unwrap();
+
+        // the limit is reached in the middle of block 1
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_receipts(2, &[retained], 3), Ok(PruneProgress::HasMoreData));
+        assert_eq!(
+            provider_rw.get_prune_checkpoint(PrunePart::Receipts).unwrap().map(|c| c.block_number),
+            Some(0)
+        );
+        provider_rw.commit().unwrap();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(provider_rw.prune_receipts(2, &[retained], 10), Ok(PruneProgress::Finished));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::Receipts>(), Ok(3 + 3));
+        for block in 0..3 {
+            let tx_num = block * 3;
+            assert_eq!(provider.receipt(tx_num), Ok(Some(receipts[block as usize][0].clone())));
+            assert_eq!(
+                provider.receipt(tx_num + 1),
+                Err(ProviderError::ReceiptsPruned { block, prune_height: 2 }.into())
+            );
+            assert_eq!(
+                provider.receipts_by_block(block.into()),
+                Err(ProviderError::ReceiptsPruned { block, prune_height: 2 }.into())
+            );
+            assert_eq!(
+                provider.receipts_by_block_sparse(block.into()),
+                Ok(Some([(0, receipts[block as usize][0].clone())].into_iter().collect()))
+            );
+        }
+
+        // receipts above the prune height are untouched
+        assert_eq!(provider.receipts_by_block(3.into()), Ok(Some(receipts[3].clone())));
+        assert_eq!(
+            provider.receipts_by_block_sparse(3.into()),
+            Ok(Some((0..).zip(receipts[3].clone()).collect()))
+        );
+        assert_eq!(provider.receipts_by_block_sparse(4.into()), Ok(None));
+    }
+
+    #[test]
+    fn insert_block_bodies() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let mut blocks = (0..3)
+            .map(|number| random_block(&mut rng, number, None, Some(2), Some(number as u8 % 2)))
+            .collect::<Vec<_>>();
+        let bodies = blocks
+            .iter()
+            .map(|block| {
+                let body = BlockBody {
+                    transactions: block.body.clone(),
+                    ommers: block.ommers.clone(),
+                    withdrawals: block.withdrawals.clone(),
+                };
+                (block.number, body)
+            })
+            .collect::<Vec<_>>();
+
+        // only the headers are stored
+        let provider_rw = factory.provider_rw().unwrap();
+        for block in &blocks {
+            provider_rw
+                .tx_ref()
+                .put::<tables::CanonicalHeaders>(block.number, block.hash())
+                .unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::Headers>(block.number, block.header.clone().unseal())
+                .unwrap();
+        }
+
+        assert_eq!(
+            provider_rw.insert_block_bodies(bodies[1..].to_vec()),
+            Err(ProviderError::DetachedBody { number: 1, expected: 0 }.into())
+        );
+        let mut mismatching = bodies.clone();
+        mismatching[1].1.transactions.pop();
+        let got = calculate_transaction_root(&mismatching[1].1.transactions);
+        assert_eq!(
+            provider_rw.insert_block_bodies(mismatching),
+            Err(ProviderError::BodyRootMismatch {
+                number: 1,
+                root: "Transactions",
+                expected: Some(blocks[1].transactions_root),
+                got: Some(got)
+            }
+            .into())
+        );
+        // withdrawals before Shanghai
+        let mut mismatching = bodies.clone();
+        mismatching[2].1.withdrawals = Some(Vec::new());
+        assert_eq!(
+            provider_rw.insert_block_bodies(mismatching),
+            Err(ProviderError::BodyRootMismatch {
+                number: 2,
+                root: "Withdrawals",
+                expected: None,
+                got: Some(calculate_withdrawals_root(&[]))
+            }
+            .into())
+        );
+        // ommers after the merge
+        assert_eq!(
+            provider_rw.insert_block_bodies(bodies.clone()),
+            Err(ProviderError::InvalidBody {
+                number: 1,
+                error: BlockValidationError::OmmersAfterMerge { count: 1 }
+            }
+            .into())
+        );
+        assert_eq!(provider_rw.tx_ref().entries::<tables::BlockBodyIndices>(), Ok(0));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(0));
+
+        for block in blocks.iter_mut().filter(|block| !block.ommers.is_empty()) {
+            block.header =
+                Header { difficulty: U256::from(1), ..block.header.clone().unseal() }.seal_slow();
+            provider_rw
+                .tx_ref()
+                .put::<tables::CanonicalHeaders>(block.number, block.hash())
+                .unwrap();
+            provider_rw
+                .tx_ref()
+                .put::<tables::Headers>(block.number, block.header.clone().unseal())
+                .unwrap();
+        }
+        provider_rw.insert_block_bodies(bodies[..2].to_vec()).unwrap();
+        provider_rw.insert_block_bodies(bodies[2..].to_vec()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        for block in &blocks {
+            assert_eq!(provider.block(block.number.into()), Ok(Some(block.clone().unseal())));
+            let body = provider.block_body_indices(block.number).unwrap().unwrap();
+            assert_eq!(body.first_tx_num(), block.number * 2);
+            assert_eq!(provider.transaction_block(body.last_tx_num()), Ok(Some(block.number)));
+            for (tx_num, transaction) in body.tx_num_range().zip(&block.body) {
+                assert_eq!(provider.transaction_id(transaction.hash), Ok(Some(tx_num)));
+                assert_eq!(provider.transaction_sender(tx_num), Ok(transaction.recover_signer()));
+            }
+        }
+        assert_eq!(provider.tx_ref().entries::<tables::BlockOmmers>(), Ok(1));
+    }
+
+    #[test]
+    fn recompute_td_range() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        // the merge happens at block 5, from which on the difficulty is zero
+        let mut parent_hash = H256::zero();
+        let headers = (0..10)
+            .map(|number| {
+                let difficulty = if number < 5 { U256::from(10) } else { U256::ZERO };
+                let header =
+                    Header { number, parent_hash, difficulty, ..Default::default() }.seal_slow();
+                parent_hash = header.hash();
+                header
+            })
+            .collect::<Vec<_>>();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.append_sealed_headers(headers).unwrap();
+        assert_eq!(provider_rw.verify_td_consistency(0..=9), Ok(Vec::new()));
+        assert_eq!(provider_rw.header_td_by_number(9), Ok(Some(U256::from(50))));
+
+        provider_rw.tx_ref().delete::<tables::HeaderTD>(3, None).unwrap();
+        provider_rw.tx_ref().put::<tables::HeaderTD>(7, U256::from(60).into()).unwrap();
+        assert_eq!(
+            provider_rw.verify_td_consistency(0..=9),
+            Ok(vec![
+                ChainInconsistency::MissingTotalDifficulty { number: 3 },
+                ChainInconsistency::TotalDifficultyMismatch {
+                    number: 7,
+                    expected: U256::from(50),
+                    got: U256::from(60)
+                },
+            ])
+        );
+        // the parent of the range is missing
+        assert_eq!(
+            provider_rw.verify_td_consistency(4..=6),
+            Ok(vec![ChainInconsistency::MissingTotalDifficulty { number: 3 }])
+        );
+
+        assert_eq!(
+            provider_rw.recompute_td_range(4..=9),
+            Err(ProviderError::TotalDifficultyNotFound { number: 3 }.into())
+        );
+        assert_eq!(
+            provider_rw.recompute_td_range(3..=10),
+            Err(ProviderError::HeaderNotFound(10.into()).into())
+        );
+        provider_rw.recompute_td_range(3..=9).unwrap();
+        assert_eq!(provider_rw.verify_td_consistency(0..=9), Ok(Vec::new()));
+        for number in 5..10 {
+            assert_eq!(provider_rw.header_td_by_number(number), Ok(Some(U256::from(50))));
+        }
+    }
+
+    #[test]
+    fn tx_hash_lookups_after_unwind() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..4)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // left behind past the tip by an earlier unwind
+        provider_rw.tx_ref().put::<tables::TransactionBlock>(100, 5).unwrap();
+        // the same transaction is also included in block 1
+        let shared = blocks[3].body[0].hash();
+        provider_rw.tx_ref().put::<tables::TxHashNumber>(shared, 2).unwrap();
+
+        provider_rw.take_block_range(2..=3).unwrap();
+        assert_eq!(provider_rw.transaction_id(shared), Ok(Some(2)));
+        provider_rw.tx_ref().delete::<tables::TxHashNumber>(shared, None).unwrap();
+        assert_eq!(provider_rw.tx_ref().get::<tables::TransactionBlock>(100), Ok(None));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TransactionBlock>(), Ok(2));
+
+        let new_blocks = (2..4)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        for tx in blocks[2..].iter().flat_map(|block| &block.body) {
+            assert_eq!(provider_rw.transaction_id(tx.hash()), Ok(None));
+            assert_eq!(provider_rw.transaction_by_hash(tx.hash()), Ok(None));
+        }
+        for (tx_num, tx) in (4..).zip(new_blocks.iter().flat_map(|block| &block.body)) {
+            assert_eq!(provider_rw.transaction_id(tx.hash()), Ok(Some(tx_num)));
+            assert_eq!(provider_rw.transaction_by_hash(tx.hash()), Ok(Some(tx.clone())));
+        }
+
+        // a stale lookup for a reused transaction number and a missing one
+        let stale = blocks[2].body[0].hash();
+        let missing = new_blocks[1].body[0].hash();
+        provider_rw.tx_ref().put::<tables::TxHashNumber>(stale, 4).unwrap();
+        provider_rw.tx_ref().delete::<tables::TxHashNumber>(missing, None).unwrap();
+
+        provider_rw.repair_tx_hash_index(2..=3).unwrap();
+        assert_eq!(provider_rw.transaction_id(stale), Ok(None));
+        assert_eq!(provider_rw.transaction_id(missing), Ok(Some(6)));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TxHashNumber>(), Ok(8));
+    }
+
+    #[test]
+    fn remove_block_by_hash() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        // a fork block next to the canonical block and one above the tip
+        let fork = random_block(&mut rng, 1, Some(blocks[0].hash()), Some(2), Some(0));
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(fork.hash(), 1).unwrap();
+        let fork_tip = random_block(&mut rng, 3, Some(fork.hash()), Some(0), Some(0));
+        provider_rw.tx_ref().put::<tables::HeaderNumbers>(fork_tip.hash(), 3).unwrap();
+        provider_rw.tx_ref().put::<tables::Headers>(3, fork_tip.header.clone().unseal()).unwrap();
+        provider_rw.tx_ref().put::<tables::HeaderTD>(3, U256::from(1).into()).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        assert_eq!(
+            provider_rw.remove_block_by_hash(blocks[1].hash()),
+            Err(ProviderError::CanonicalBlockRemoval { number: 1, hash: blocks[1].hash() }.into())
+        );
+        assert_eq!(provider_rw.remove_block_by_hash(fork.hash()), Ok(true));
+        assert_eq!(provider_rw.remove_block_by_hash(fork_tip.hash()), Ok(true));
+        assert_eq!(provider_rw.remove_block_by_hash(fork.hash()), Ok(false));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_number(fork.hash()), Ok(None));
+        assert_eq!(provider.header_by_number(3), Ok(None));
+        assert_eq!(provider.header_td_by_number(3), Ok(None));
+        for (tx_num, tx) in (0..).zip(blocks.iter().flat_map(|block| &block.body)) {
+            assert_eq!(provider.transaction_id(tx.hash()), Ok(Some(tx_num)));
+        }
+        for block in &blocks {
+            assert_eq!(provider.block_number(block.hash()), Ok(Some(block.number)));
+            assert_eq!(
+                provider.block(block.number.into()).unwrap().map(|block| block.seal_slow()),
+                Some(block.clone())
+            );
+        }
+        assert_eq!(provider.tx_ref().entries::<tables::HeaderNumbers>(), Ok(3));
+        assert_eq!(provider.tx_ref().entries::<tables::HeaderTD>(), Ok(3));
+    }
+
+    #[test]
+    fn insert_block_withdrawals() {
+        let activation = 1_000;
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(activation))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let withdrawals = vec![Withdrawal { index: 0, amount: 1, ..Default::default() }];
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..4 {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let mut header = block.header.unseal();
+            header.timestamp = activation + number - 1;
+            header.withdrawals_root =
+                (number == 2).then(|| calculate_withdrawals_root(&withdrawals));
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+        }
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(3)).unwrap();
+
+        assert_eq!(
+            provider_rw.insert_block_withdrawals(0, vec![]),
+            Err(ProviderError::WithdrawalsBeforeShanghai { number: 0, timestamp: activation - 1 }
+                .into())
+        );
+        assert_eq!(
+            provider_rw.insert_block_withdrawals(2, vec![]),
+            Err(ProviderError::BodyRootMismatch {
+                number: 2,
+                root: "Withdrawals",
+                expected: Some(calculate_withdrawals_root(&withdrawals)),
+                got: Some(calculate_withdrawals_root(&[])),
+            }
+            .into())
+        );
+        assert_eq!(
+            provider_rw.insert_block_withdrawals(4, vec![]),
+            Err(ProviderError::HeaderNotFound(4.into()).into())
+        );
+        provider_rw.insert_block_withdrawals(2, withdrawals.clone()).unwrap();
+        assert_eq!(provider_rw.tx_ref().entries::<tables::BlockWithdrawals>(), Ok(1));
+
+        // the first block from the activation on and the tip have no row yet
+        assert_eq!(provider_rw.ensure_empty_withdrawal_rows(0..=3), Ok(2));
+        assert_eq!(provider_rw.ensure_empty_withdrawal_rows(0..=3), Ok(0));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().get::<tables::BlockWithdrawals>(0), Ok(None));
+        for number in [1, 3] {
+            assert_eq!(
+                provider.tx_ref().get::<tables::BlockWithdrawals>(number),
+                Ok(Some(StoredBlockWithdrawals::default()))
+            );
+        }
+        assert_eq!(provider.withdrawals_range(0..=3), Ok(vec![(2, withdrawals.clone())]));
+        assert_eq!(provider.latest_withdrawal(), Ok(withdrawals.last().cloned()));
+        let expected = [None, Some(vec![]), Some(withdrawals), Some(vec![])];
+        for (number, withdrawals) in (0..).zip(expected) {
+            let block = provider.block(number.into()).unwrap().unwrap();
+            assert_eq!(block.withdrawals, withdrawals);
+        }
+    }
+
+    #[test]
+    fn insert_block_empty_withdrawals() {
+        let activation = 1_000;
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(activation))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let withdrawals = vec![Withdrawal { index: 0, amount: 1, ..Default::default() }];
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut parent_hash = H256::zero();
+        // the last block before the activation, the first one without withdrawals and one with
+        for (number, block_withdrawals) in
+            (0..).zip([None, Some(vec![]), Some(withdrawals.clone())])
+        {
+            let mut block = random_block(&mut rng, number, Some(parent_hash), Some(0), Some(0));
+            let mut header = block.header.unseal();
+            header.timestamp = activation + number - 1;
+            header.withdrawals_root = block_withdrawals.as_deref().map(calculate_withdrawals_root);
+            block.header = header.seal_slow();
+            block.withdrawals = block_withdrawals;
+            parent_hash = block.hash();
+            provider_rw.insert_block(block, None).unwrap();
+        }
+        assert_eq!(provider_rw.ensure_empty_withdrawal_rows(0..=2), Ok(0));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().get::<tables::BlockWithdrawals>(0), Ok(None));
+        assert_eq!(
+            provider.tx_ref().get::<tables::BlockWithdrawals>(1),
+            Ok(Some(StoredBlockWithdrawals::default()))
+        );
+        assert_eq!(
+            provider.tx_ref().get::<tables::BlockWithdrawals>(2),
+            Ok(Some(StoredBlockWithdrawals { withdrawals }))
+        );
+    }
+
+    #[test]
+    fn ensure_empty_withdrawal_rows_write_batching() {
+        let activation = 1_000;
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(activation))
+            .build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let withdrawals = vec![Withdrawal { index: 0, amount: 1, ..Default::default() }];
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..2 {
+            let block = insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None);
+            let mut header = block.header.unseal();
+            header.timestamp = activation;
+            header.withdrawals_root =
+                (number == 1).then(|| calculate_withdrawals_root(&withdrawals));
+            provider_rw.tx_ref().put::<tables::Headers>(number, header).unwrap();
+        }
+
+        // the buffered withdrawals are written before the rows are looked up, and not replaced
+        provider_rw.enable_write_batching(usize::MAX);
+        provider_rw.insert_block_withdrawals(1, withdrawals.clone()).unwrap();
+        assert_eq!(provider_rw.tx_ref().entries::<tables::BlockWithdrawals>(), Ok(0));
+        assert_eq!(provider_rw.ensure_empty_withdrawal_rows(0..=1), Ok(1));
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            provider.tx_ref().get::<tables::BlockWithdrawals>(0),
+            Ok(Some(StoredBlockWithdrawals::default()))
+        );
+        assert_eq!(
+            provider.tx_ref().get::<tables::BlockWithdrawals>(1),
+            Ok(Some(StoredBlockWithdrawals { withdrawals }))
+        );
+    }
+
+    #[test]
+    fn write_batching() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.enable_write_batching(usize::MAX);
+        let blocks = (0..4)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(0));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(0));
+
+        // point lookups read through the buffer
+        let mut td = U256::ZERO;
+        for block in &blocks {
+            td += block.difficulty;
+            assert_eq!(provider_rw.block_hash(block.number), Ok(Some(block.hash())));
+            assert_eq!(provider_rw.block_number(block.hash()), Ok(Some(block.number)));
+            assert_eq!(provider_rw.is_known_block_hash(block.hash()), Ok(true));
+            assert_eq!(provider_rw.header_by_number(block.number), Ok(Some(block.header.unseal())));
+            assert_eq!(provider_rw.header_td_by_number(block.number), Ok(Some(td)));
+            let body = provider_rw.block_body_indices(block.number).unwrap().unwrap();
+            assert_eq!(body.first_tx_num(), block.number * 2);
+            for (tx_num, tx) in body.tx_num_range().zip(&block.body) {
+                assert_eq!(provider_rw.transaction_id(tx.hash()), Ok(Some(tx_num)));
+                assert_eq!(provider_rw.transaction_by_id(tx_num), Ok(Some(tx.clone())));
+            }
+        }
+
+        // cursors opened by the provider write the buffered rows of their table first
+        let headers = blocks.iter().map(|block| block.header.clone().unseal()).collect::<Vec<_>>();
+        assert_eq!(provider_rw.headers_range(0..=3), Ok(headers));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(4));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(0));
+
+        provider_rw.recompute_td_range(0..=3).unwrap();
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(4));
+        assert_eq!(provider_rw.header_td_by_number(3), Ok(Some(td)));
+
+        let taken = provider_rw.take_block_range(3..=3).unwrap();
+        assert_eq!(
+            taken.into_iter().map(|block| block.block).collect::<Vec<_>>(),
+            blocks[3..].to_vec()
+        );
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(3));
+
+        let block = insert_block_with_receipts(&provider_rw, &mut rng, 3, vec![], None);
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(3));
+        provider_rw.commit().unwrap();
+
+        // the buffer is written on commit
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.tx_ref().entries::<tables::Headers>(), Ok(4));
+        for block in blocks[..3].iter().chain([&block]) {
+            assert_eq!(
+                provider.block(block.number.into()).unwrap().map(|block| block.seal_slow()),
+                Some(block.clone())
+            );
+        }
+
+        // the buffer is written as soon as it exceeds the threshold
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.enable_write_batching(0);
+        insert_block_with_receipts(&provider_rw, &mut rng, 4, vec![], None);
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Headers>(), Ok(5));
+    }
+
+    #[test]
+    fn insert_block_again() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+
+        // the same block is not written again
+        let body = provider_rw.block_body_indices(1).unwrap().unwrap();
+        assert_eq!(provider_rw.insert_block(blocks[1].clone(), None), Ok(body));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(6));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TxHashNumber>(), Ok(6));
+
+        let other = random_block(&mut rng, 1, Some(blocks[0].hash()), Some(1), Some(0));
+        assert_eq!(
+            provider_rw.insert_block(other.clone(), None),
+            Err(ProviderError::BlockConflict {
+                number: 1,
+                existing: blocks[1].hash(),
+                new: other.hash()
+            }
+            .into())
+        );
+        assert_eq!(provider_rw.block_hash(1), Ok(Some(blocks[1].hash())));
+
+        // the header was written before an interruption, the body is completed
+        let next = random_block(&mut rng, 3, Some(blocks[2].hash()), Some(2), Some(0));
+        provider_rw.append_sealed_headers(vec![next.header.clone()]).unwrap();
+        let body = provider_rw.insert_block(next.clone(), None).unwrap();
+        assert_eq!((body.first_tx_num(), body.tx_count), (6, 2));
+        assert_eq!(provider_rw.insert_block(next.clone(), None), Ok(body));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(8));
+        assert_eq!(provider_rw.block(3.into()).unwrap().map(|block| block.seal_slow()), Some(next));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn insert_block_round_trip(blocks in chain(MAINNET.clone(), 4)) {
+            let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+            // chains can start at any height
+            let provider_rw = factory.provider_rw().unwrap().allow_block_gaps();
+            let mut next_tx = 0;
+            for block in &blocks {
+                let body = provider_rw.insert_block(block.clone(), None).unwrap();
+                prop_assert_eq!(
+                    (body.first_tx_num(), body.tx_count),
+                    (next_tx, block.body.len() as u64)
+                );
+                next_tx += body.tx_count;
+                // the same block is not written again
+                prop_assert_eq!(provider_rw.insert_block(block.clone(), None), Ok(body));
+            }
+            provider_rw.commit().unwrap();
+
+            let provider = factory.provider().unwrap();
+            for block in blocks {
+                let stored = provider.block(block.number.into()).unwrap();
+                prop_assert_eq!(stored.map(|block| block.seal_slow()), Some(block.clone()));
+                let senders = block.senders().unwrap();
+                prop_assert_eq!(
+                    provider.block_with_senders(block.number).unwrap(),
+                    Some(block.unseal().with_senders(senders))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn insert_chain_fixture() {
+        // the merge happens at block 10 and Shanghai at block 15
+        let difficulty = MAINNET.genesis().difficulty;
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::mainnet()
+                .london_activated()
+                .paris_at_ttd(difficulty * U256::from(10))
+                .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(15 * 12))
+                .build(),
+        );
+        let blocks = ChainFixture::new(chain_spec.clone()).blocks(20).txs_per_block(0..5).build();
+        let factory = ProviderFactory::new(create_test_rw_db(), chain_spec);
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_blocks(&provider_rw, &blocks).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.best_block_number(), Ok(20));
+        for block in &blocks {
+            assert_eq!(block.difficulty.is_zero(), block.number >= 10);
+            assert_eq!(block.withdrawals.is_some(), block.number >= 15);
+            assert_eq!(
+                provider.header_td_by_number(block.number),
+                Ok(Some(difficulty * U256::from(block.number.min(9) + 1)))
+            );
+            assert_eq!(
+                provider.block_with_senders(block.number),
+                Ok(Some(block.block.clone().unseal().with_senders(block.senders.clone())))
+            );
+        }
+    }
+
+    #[test]
+    fn validate_block_insertion() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..3)
+            .map(|number| insert_block_with_receipts(&provider_rw, &mut rng, number, vec![], None))
+            .collect::<Vec<_>>();
+
+        let ahead = random_block(&mut rng, 5, Some(blocks[2].hash()), Some(1), Some(0));
+        assert_eq!(
+            provider_rw.insert_block(ahead, None),
+            Err(ProviderError::NonContiguousBlock { number: 5, expected: 3 }.into())
+        );
+
+        let detached = random_block(&mut rng, 3, Some(H256::random()), Some(1), Some(0));
+        assert_eq!(
+            provider_rw.insert_block(detached.clone(), None),
+            Err(ProviderError::ParentHashMismatch {
+                number: 3,
+                expected: blocks[2].hash(),
+                got: detached.parent_hash
+            }
+            .into())
+        );
+
+        let mut child = random_block(&mut rng, 3, Some(blocks[2].hash()), Some(1), Some(0));
+        child.header = Header { timestamp: 10, ..child.header.unseal() }.seal_slow();
+        provider_rw.insert_block(child.clone(), None).unwrap();
+        let mut early = random_block(&mut rng, 4, Some(child.hash()), Some(1), Some(0));
+        early.header = Header { timestamp: 5, ..early.header.unseal() }.seal_slow();
+        assert_eq!(
+            provider_rw.insert_block(early, None),
+            Err(ProviderError::TimestampBeforeParent {
+                number: 4,
+                timestamp: 5,
+                parent_timestamp: 10
+            }
+            .into())
+        );
+        assert_eq!(provider_rw.block_hash(4), Ok(None));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::Transactions>(), Ok(1));
+        provider_rw.commit().unwrap();
+
+        // with gaps the block is only checked against a stored parent
+        let provider_rw = factory.provider_rw().unwrap().allow_block_gaps();
+        let ahead = random_block(&mut rng, 10, None, Some(1), Some(0));
+        provider_rw.insert_block(ahead.clone(), None).unwrap();
+        assert_eq!(provider_rw.block_hash(10), Ok(Some(ahead.hash())));
+        assert_eq!(provider_rw.header_td_by_number(10), Ok(Some(ahead.difficulty)));
+        let detached = random_block(&mut rng, 4, Some(H256::random()), Some(1), Some(0));
+        assert_eq!(
+            provider_rw.insert_block(detached.clone(), None),
+            Err(ProviderError::ParentHashMismatch {
+                number: 4,
+                expected: child.hash(),
+                got: detached.parent_hash
+            }
+            .into())
+        );
+
+        // a stored parent without a total difficulty is not treated as a gap
+        provider_rw.tx_ref().delete::<tables::HeaderTD>(3, None).unwrap();
+        let mut next = random_block(&mut rng, 4, Some(child.hash()), Some(1), Some(0));
+        next.header = Header { timestamp: 20, ..next.header.unseal() }.seal_slow();
+        assert_eq!(
+            provider_rw.insert_block(next, None),
+            Err(ProviderError::TotalDifficultyNotFound { number: 3 }.into())
+        );
+        assert_eq!(provider_rw.block_hash(4), Ok(None));
+    }
+
+    #[test]
+    fn consistency_check() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = (0..4)
+            .map(|number| {
+                let receipts = vec![Receipt::default(); 2];
+                insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        provider_rw.save_stage_checkpoint(StageId::Execution, StageCheckpoint::new(3)).unwrap();
+        assert_eq!(provider_rw.consistency_check(ConsistencyScope::All, 10), Ok(vec![]));
+
+        // every block has two transactions
+        let tx = provider_rw.tx_ref();
+        tx.delete::<tables::Transactions>(2, None).unwrap();
+        let hash = blocks[2].body[1].hash();
+        tx.delete::<tables::TxHashNumber>(hash, None).unwrap();
+        tx.put::<tables::TransactionBlock>(1, 5).unwrap();
+        tx.delete::<tables::TxSenders>(3, None).unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Execution, StageCheckpoint::new(2)).unwrap();
+
+        let findings = vec![
+            Inconsistency::TransactionBlockMismatch { block: 0, tx_number: 0, got: Some(5) },
+            Inconsistency::MissingTransaction { block: 1, tx_number: 2 },
+            Inconsistency::SendersCountMismatch { block: 1, tx_count: 2, senders: 1 },
+            Inconsistency::TxHashNumberMismatch { block: 2, tx_number: 5, hash, got: None },
+            Inconsistency::ReceiptsPastCheckpoint { block: 3, tx_number: 6, checkpoint: Some(2) },
+        ];
+        assert_eq!(provider_rw.consistency_check(ConsistencyScope::All, 10), Ok(findings.clone()));
+        assert_eq!(
+            provider_rw.consistency_check(ConsistencyScope::All, 2),
+            Ok(findings[..2].to_vec())
+        );
+        assert_eq!(
+            provider_rw.consistency_check(ConsistencyScope::Blocks(2..=3), 10),
+            Ok(findings[3..].to_vec())
+        );
+
+        // blocks without senders above the sender recovery checkpoint are not recovered yet
+        tx.delete::<tables::TxSenders>(2, None).unwrap();
+        assert_eq!(
+            provider_rw.consistency_check(ConsistencyScope::Blocks(1..=1), 10),
+            Ok(vec![Inconsistency::MissingTransaction { block: 1, tx_number: 2 }])
+        );
+        provider_rw
+            .save_stage_checkpoint(StageId::SenderRecovery, StageCheckpoint::new(1))
+            .unwrap();
+        assert_eq!(
+            provider_rw.consistency_check(ConsistencyScope::Blocks(1..=1), 10),
+            Ok(vec![
+                Inconsistency::MissingTransaction { block: 1, tx_number: 2 },
+                Inconsistency::SendersCountMismatch { block: 1, tx_count: 2, senders: 0 },
+            ])
+        );
+    }
+
+    #[test]
+    fn rebuild_transaction_block_index() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let blocks = [2, 0, 2, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(number, tx_count)| {
+                let receipts = vec![Receipt::default(); tx_count];
+                insert_block_with_receipts(&provider_rw, &mut rng, number as u64, receipts, None)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(provider_rw.rebuild_transaction_block_index(..), Ok(0));
+        provider_rw.save_stage_checkpoint(StageId::Execution, StageCheckpoint::new(3)).unwrap();
+        provider_rw.commit().unwrap();
+
+        // the entry of block 2 is missing, a stale entry resolves the transactions of block 3 to
+        // block 2 and the entry of block 3 points to an unknown block
+        let provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_ref();
+        tx.delete::<tables::TransactionBlock>(3, None).unwrap();
+        tx.put::<tables::TransactionBlock>(4, 2).unwrap();
+        tx.put::<tables::TransactionBlock>(5, 9).unwrap();
+        let hash = blocks[3].body[0].hash();
+        let (_, meta) = provider_rw.transaction_by_hash_with_meta(hash).unwrap().unwrap();
+        assert_eq!(meta.block_number, 2);
+        assert_eq!(provider_rw.transaction_by_hash_with_meta(blocks[3].body[1].hash()), Ok(None));
+
+        // blocks outside of the range are not repaired
+        assert_eq!(provider_rw.rebuild_transaction_block_index(..=2), Ok(1));
+        assert_eq!(provider_rw.rebuild_transaction_block_index(..), Ok(2));
+        assert_eq!(provider_rw.rebuild_transaction_block_index(..), Ok(0));
+        assert_eq!(provider_rw.consistency_check(ConsistencyScope::All, 10), Ok(vec![]));
+
+        for (number, block) in blocks.iter().enumerate() {
+            for (index, transaction) in block.body.iter().enumerate() {
+                let (_, meta) =
+                    provider_rw.transaction_by_hash_with_meta(transaction.hash()).unwrap().unwrap();
+                assert_eq!((meta.block_number, meta.index), (number as u64, index as u64));
+                assert_eq!(meta.block_hash, block.hash());
+            }
+        }
+    }
+
+    #[test]
+    fn table_stats() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory = ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec));
+
+        let provider = factory.provider().unwrap();
+        let stats = provider.table_stats().unwrap();
+        assert_eq!(stats.len(), tables::Tables::ALL.len());
+        assert!(stats.windows(2).all(|stats| stats[0].table < stats[1].table));
+        assert!(stats.iter().all(|stats| stats.entries == 0 && stats.key_bytes.is_none()));
+        drop(provider);
+
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        for number in 0..3 {
+            let receipts = vec![Receipt::default(); 2];
+            insert_block_with_receipts(&provider_rw, &mut rng, number, receipts, None);
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let stats = provider.table_stats().unwrap();
+        let table = |name| *stats.iter().find(|stats| stats.table == name).unwrap();
+        let headers = table(tables::Headers::const_name());
+        assert_eq!((headers.entries, headers.key_bytes, headers.value_bytes), (3, None, None));
+        assert_eq!(headers.leaf_pages, 1);
+        assert_eq!(headers.size(), headers.page_size);
+        assert_eq!(table(tables::Transactions::const_name()).entries, 6);
+        assert_eq!(table(tables::Receipts::const_name()).entries, 6);
+
+        // the exact sizes are only summed on request
+        let sized = provider.table_stats_with_sizes().unwrap();
+        let sized_table = |name| *sized.iter().find(|stats| stats.table == name).unwrap();
+        let sized_headers = sized_table(tables::Headers::const_name());
+        assert_eq!(TableStats { key_bytes: None, value_bytes: None, ..sized_headers }, headers);
+        assert_eq!(sized_headers.key_bytes, Some(3 * 8));
+        assert!(sized_headers.value_bytes > Some(0));
+        assert_eq!(sized_table(tables::Transactions::const_name()).key_bytes, Some(6 * 8));
+        assert_eq!(sized_table(tables::TxHashNumber::const_name()).key_bytes, Some(6 * 32));
+
+        let db_stats = provider.db_stats().unwrap();
+        assert_eq!(
+            DatabaseStats { freelist_pages: 0, ..db_stats },
+            DatabaseStats {
+                entries: stats.iter().map(|stats| stats.entries).sum(),
+                pages: stats.iter().map(|stats| stats.pages()).sum(),
+                freelist_pages: 0,
+                page_size: headers.page_size,
+            }
+        );
+    }
+
+    #[test]
+    fn transactions_by_sender() {
+        let chain_spec = ChainSpecBuilder::mainnet().build();
+        let factory =
+            ProviderFactory::new(create_test_rw_db(), Arc::new(chain_spec)).with_sender_index();
+        let (alice, bob) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+
+        // every third block has no transaction from alice
+        let mut rng = generators::rng();
+        let provider_rw = factory.provider_rw().unwrap();
+        let mut parent = H256::zero();
+        let mut sent = Vec::new();
+        for number in 0..20 {
+            let block = random_block(&mut rng, number, Some(parent), Some(2), Some(0));
+            parent = block.hash();
+            let senders = if number % 3 == 0 { vec![bob, bob] } else { vec![alice, bob] };
+            let body = provider_rw.insert_block(block.clone(), Some(senders.clone())).unwrap();
+            for ((tx_num, tx), sender) in body.tx_num_range().zip(block.body).zip(senders) {
+                sent.push((number, sender, tx_num, tx));
+            }
+        }
+        provider_rw.commit().unwrap();
+
+        let from = |address: Address, blocks: Range<u64>| {
+            sent.iter()
+                .filter(|(number, sender, ..)| *sender == address && blocks.contains(number))
+                .map(|(_, _, tx_num, tx)| (*tx_num, tx.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        // a query spanning all blocks
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.transactions_by_sender(alice, ..).unwrap(), from(alice, 0..20));
+        assert_eq!(provider.transactions_by_sender(bob, ..).unwrap().len(), 27);
+        assert_eq!(provider.transactions_by_sender(Address::random(), ..).unwrap(), vec![]);
+        // blocks 5 to 9 hold the transactions 10 to 19
+        assert_eq!(provider.transactions_by_sender(alice, 10..20).unwrap(), from(alice, 5..10));
+        assert_eq!(
+            provider.transactions_by_sender(alice, (Bound::Excluded(9), Bound::Included(19))),
+            Ok(from(alice, 5..10))
+        );
+        assert_eq!(provider.transactions_by_sender(alice, 40..).unwrap(), vec![]);
+
+        assert_eq!(provider.sender_transaction_count_before(alice, 0), Ok(0));
+        assert_eq!(provider.sender_transaction_count_before(alice, 10), Ok(6));
+        assert_eq!(provider.sender_transaction_count_before(bob, 10), Ok(14));
+        // the block after the tip has no body yet
+        assert_eq!(provider.sender_transaction_count_before(alice, 20), Ok(13));
+        assert_eq!(
+            provider.sender_transaction_count_before(alice, 21),
+            Err(ProviderError::BlockBodyIndicesNotFound(21).into())
+        );
+        drop(provider);
+
+        // removed blocks are removed from the index
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.take_block_range(15..=19).unwrap();
+        provider_rw.remove_block_range(10..=14).unwrap();
+        assert_eq!(provider_rw.transactions_by_sender(alice, ..).unwrap(), from(alice, 0..10));
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TransactionSenders>(), Ok(20));
+        provider_rw.commit().unwrap();
+
+        // the index is only written if enabled
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_block_with_receipts(&provider_rw, &mut rng, 0, vec![Receipt::default()], None);
+        assert_eq!(provider_rw.tx_ref().entries::<tables::TransactionSenders>(), Ok(0));
+        assert_eq!(provider_rw.sender_transaction_count_before(alice, 1), Ok(0));
+    }
+
+    #[test]
+    fn block_with_senders_recovers_missing_senders() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+
+        let mut rng = generators::rng();
+        let block = random_block(&mut rng, 0, None, Some(100), Some(0));
+        let senders = block.body.iter().map(|tx| tx.recover_signer().unwrap()).collect::<Vec<_>>();
+
+        // senders are recovered when the block is inserted without them
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.insert_block(block, None).unwrap();
+        let stored = provider_rw.block_with_senders(0).unwrap().unwrap();
+        assert_eq!(stored.senders, senders);
+        assert_eq!(provider_rw.senders_by_tx_range(..).unwrap(), senders);
+
+        // and once more from the signatures if they are pruned
+        provider_rw.tx_ref().clear::<tables::TxSenders>().unwrap();
+        assert_eq!(provider_rw.senders_by_tx_range(..).unwrap(), vec![]);
+        let stored = provider_rw.block_with_senders(0).unwrap().unwrap();
+        assert_eq!(stored.senders, senders);
+    }
+
+    /// Returns the spec and the block environment filled at the given block.
+    fn fill_env(provider: &impl EvmEnvProvider, number: u64) -> (SpecId, BlockEnv) {
+        let mut cfg = CfgEnv::default();
+        let mut block_env = BlockEnv::default();
+        provider.fill_env_at(&mut cfg, &mut block_env, number.into()).unwrap();
+        (cfg.spec_id, block_env)
+    }
+
+    #[test]
+    fn mock_provider_matches_database() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().build());
+        let db = create_test_rw_db();
+        let factory = ProviderFactory::new(db, chain_spec.clone());
+        let mock = MockEthProvider::default().with_chain_spec(chain_spec);
+
+        let mut rng = generators::rng();
+        let receipt = |cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() };
+        let provider_rw = factory.provider_rw().unwrap();
+        let receipts = [vec![], vec![receipt(21_000), receipt(50_000)], vec![receipt(30_000)]];
+        // insert the blocks into the mock in reverse order, the indices must not depend on it
+        let blocks = receipts
+            .into_iter()
+            .enumerate()
+            .map(|(number, receipts)| {
+                let block = insert_block_with_receipts(
+                    &provider_rw,
+                    &mut rng,
+                    number as u64,
+                    receipts.clone(),
+                    None,
+                );
+                (block, receipts)
+            })
+            .collect::<Vec<_>>();
+        for (block, receipts) in blocks.iter().rev() {
+            mock.add_block(block.hash(), block.clone().unseal());
+            mock.add_receipts(block.hash(), receipts.clone());
+        }
+        provider_rw.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(
+            mock.block_body_indices_range(..).unwrap(),
+            provider.block_body_indices_range(..).unwrap()
+        );
+        assert_eq!(
+            mock.transactions_by_tx_range(..).unwrap(),
+            provider.transactions_by_tx_range(..).unwrap()
+        );
+        assert_eq!(
+            mock.senders_by_tx_range(..).unwrap(),
+            provider.senders_by_tx_range(..).unwrap()
+        );
+        assert_eq!(
+            mock.receipts_by_block_range(..).unwrap(),
+            provider.receipts_by_block_range(..).unwrap()
+        );
+        for tx in blocks.iter().flat_map(|(block, _)| block.body.iter()) {
+            let tx_num = provider.transaction_id(tx.hash()).unwrap();
+            assert!(tx_num.is_some());
+            assert_eq!(mock.transaction_id(tx.hash()).unwrap(), tx_num);
+            assert_eq!(
+                mock.transaction_block(tx_num.unwrap()).unwrap(),
+                provider.transaction_block(tx_num.unwrap()).unwrap()
+            );
+            assert_eq!(
+                mock.transaction_by_hash_with_meta(tx.hash()).unwrap(),
+                provider.transaction_by_hash_with_meta(tx.hash()).unwrap()
+            );
+            assert_eq!(
+                mock.receipt_by_hash_with_meta(tx.hash()).unwrap(),
+                provider.receipt_by_hash_with_meta(tx.hash()).unwrap()
+            );
+        }
+        assert_eq!(mock.transaction_id(H256::random()).unwrap(), None);
+
+        for number in 0..=2 {
+            let (block, _) = &blocks[number as usize];
+            assert_eq!(
+                mock.block_with_senders(number).unwrap().map(|block| block.senders),
+                provider.block_with_senders(number).unwrap().map(|block| block.senders)
+            );
+            assert_eq!(
+                mock.ommers(number.into()).unwrap(),
+                provider.ommers(number.into()).unwrap()
+            );
+            assert_eq!(
+                mock.withdrawals_by_block(number.into(), block.timestamp).unwrap(),
+                provider.withdrawals_by_block(number.into(), block.timestamp).unwrap()
+            );
+            assert_eq!(fill_env(&mock, number), fill_env(&provider, number));
+        }
+
+        // the new blocks continue the transaction numbers of the existing blocks
+        let extended = mock.extend_chain(2);
+        assert_eq!(extended.iter().map(|block| block.number).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(extended[0].parent_hash, blocks[2].0.hash());
+        assert_eq!(
+            mock.block_body_indices(4).unwrap(),
+            Some(StoredBlockBodyIndices { first_tx_num: 3, tx_count: 0 })
+        );
+        assert_eq!(mock.receipts_by_block(BlockHashOrNumber::Number(4)).unwrap(), Some(vec![]));
+        assert_eq!(mock.chain_info().unwrap().best_hash, extended[1].hash());
+    }
 }
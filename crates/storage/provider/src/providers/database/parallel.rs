@@ -0,0 +1,108 @@
+use super::{DatabaseProviderRO, ProviderFactory};
+use crate::{HeaderProvider, ReceiptProvider};
+use rayon::prelude::*;
+use reth_db::{cursor::DbCursorRO, database::Database, table::Table, tables, transaction::DbTx};
+use reth_interfaces::Result;
+use reth_primitives::{BlockNumber, Header, Receipt};
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+impl<DB: Database> ProviderFactory<DB> {
+    /// Get headers in range of block numbers, reading chunks of `chunk_size` blocks in parallel.
+    ///
+    /// See [ProviderFactory::par_range_read] for the consistency guarantees.
+    pub fn par_headers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        chunk_size: u64,
+    ) -> Result<Vec<Header>> {
+        self.par_range_read::<tables::Headers, _, _>(range, chunk_size, |provider, chunk| {
+            provider.headers_range(chunk)
+        })
+    }
+
+    /// Get receipts of the blocks in the range, reading chunks of `chunk_size` blocks in parallel.
+    ///
+    /// See [ProviderFactory::par_range_read] for the consistency guarantees.
+    pub fn par_receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        chunk_size: u64,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        self.par_range_read::<tables::BlockBodyIndices, _, _>(
+            range,
+            chunk_size,
+            |provider, chunk| provider.receipts_by_block_range(chunk),
+        )
+    }
+
+    /// Splits the range into chunks of `chunk_size` blocks and reads them in parallel on the rayon
+    /// thread pool, with one read-only transaction per worker thread. The results are returned in
+    /// the order of the range.
+    ///
+    /// An unbounded end is resolved to the last entry of the table `T`.
+    ///
+    /// All transactions are opened before any chunk is read, but MDBX does not allow opening them
+    /// at the same snapshot: a write committed while they are being opened is only seen by some
+    /// of the workers. The results are only consistent if the range isn't written concurrently,
+    /// which holds for blocks below the finalized block.
+    pub fn par_range_read<T, R, F>(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        chunk_size: u64,
+        read: F,
+    ) -> Result<Vec<R>>
+    where
+        T: Table<Key = BlockNumber>,
+        R: Send,
+        F: Fn(&DatabaseProviderRO<'_, DB>, RangeInclusive<BlockNumber>) -> Result<Vec<R>> + Sync,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&number) => number,
+            Bound::Excluded(&number) => number.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&number) => number,
+            Bound::Excluded(&0) => return Ok(Vec::new()),
+            Bound::Excluded(&number) => number - 1,
+            Bound::Unbounded => match self.provider()?.tx_ref().cursor_read::<T>()?.last()? {
+                Some((number, _)) => number,
+                None => return Ok(Vec::new()),
+            },
+        };
+        if start > end {
+            return Ok(Vec::new())
+        }
+
+        let chunk_size = chunk_size.max(1);
+        let chunks = (start..=end)
+            .step_by(chunk_size as usize)
+            .map(|chunk_start| chunk_start..=chunk_start.saturating_add(chunk_size - 1).min(end))
+            .collect::<Vec<_>>();
+
+        // open all transactions upfront, so no worker starts reading while others are still
+        // waiting for their transaction
+        let workers = rayon::current_num_threads().min(chunks.len());
+        let providers = (0..workers).map(|_| self.provider()).collect::<Result<Vec<_>>>()?;
+
+        let mut results = providers
+            .into_par_iter()
+            .enumerate()
+            .map(|(worker, provider)| {
+                chunks
+                    .iter()
+                    .enumerate()
+                    .skip(worker)
+                    .step_by(workers)
+                    .map(|(index, chunk)| Ok((index, read(&provider, chunk.clone())?)))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        results.sort_unstable_by_key(|(index, _)| *index);
+
+        Ok(results.into_iter().flat_map(|(_, chunk)| chunk).collect())
+    }
+}
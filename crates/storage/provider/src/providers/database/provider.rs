@@ -1,45 +1,391 @@
+use super::{
+    batch::BatchWriter,
+    commit_monitor::CommitMonitor,
+    metrics::{DatabaseProviderMetrics, Operation},
+};
 use crate::{
+    head_from_provider,
     post_state::StorageChangeset,
     traits::{AccountExtReader, BlockSource, ReceiptProvider, StageCheckpointWriter},
-    AccountReader, BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
-    EvmEnvProvider, HashingWriter, HeaderProvider, HistoryWriter, PostState, ProviderError,
-    StageCheckpointReader, StorageReader, TransactionsProvider, WithdrawalsProvider,
+    AccountReader, BlockEnvOverrides, BlockExecutionWriter, BlockHashReader, BlockIdReader,
+    BlockNumReader, BlockReader, BlockWriter, EvmEnvProvider, HashingWriter, HeaderProvider,
+    HistoryWriter, NextBlockAttributes, PostState, ProviderError, PruneCheckpointReader,
+    PruneCheckpointWriter, StageCheckpointReader, StorageReader, TransactionsProvider,
+    WithdrawalsProvider,
 };
 use itertools::{izip, Itertools};
+use parking_lot::Mutex;
+use rayon::prelude::*;
 use reth_db::{
     common::KeyValue,
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
     database::{Database, DatabaseGAT},
     models::{
         sharded_key, storage_sharded_key::StorageShardedKey, AccountBeforeTx, BlockNumberAddress,
-        ShardedKey, StoredBlockBodyIndices, StoredBlockOmmers, StoredBlockWithdrawals,
+        ChainMarker, SenderTxNumber, ShardedKey, StoredBlockBodyIndices, StoredBlockOmmers,
+        StoredBlockWithdrawals,
     },
-    table::Table,
+    table::{Decode, Decompress, DupSort, Table},
     tables,
-    transaction::{DbTx, DbTxMut},
-    BlockNumberList, DatabaseError,
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    BlockNumberList, DatabaseError, RawKey, RawTable, RawValue, TableViewer, Tables,
 };
 use reth_interfaces::Result;
 use reth_primitives::{
     keccak256,
+    proofs::{calculate_receipt_root_and_logs_bloom, calculate_withdrawals_root},
     stage::{StageCheckpoint, StageId},
-    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
-    ChainInfo, ChainSpec, Hardfork, Head, Header, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, StorageEntry, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, H256, U256,
+    validate_body_against_header, Account, Address, Block, BlockBody, BlockHash, BlockHashOrNumber,
+    BlockId, BlockNumHash, BlockNumber, BlockNumberOrTag, BlockValidationError, BlockWithSenders,
+    Bloom, Bytes, ChainInfo, ChainSpec, ForkCondition, ForkFilter, ForkId, Hardfork, Head, Header,
+    Log, LogAddressAndTopicsFilter, LogMeta, PruneCheckpoint, PruneMode, PrunePart, Receipt,
+    SealedBlock, SealedBlockWithSenders, SealedHeader, StorageEntry, TransactionMeta,
+    TransactionSigned, TransactionSignedEcRecovered, TransactionSignedNoHash, TxHash, TxNumber,
+    ValidationError, Withdrawal, WithdrawalsSummary, H256, SENDER_RECOVERY_CHUNK_SIZE, U256,
 };
 use reth_revm_primitives::{
     config::revm_spec,
-    env::{fill_block_env, fill_cfg_and_block_env, fill_cfg_env},
+    env::{fill_block_env, fill_block_env_with_coinbase, fill_cfg_and_block_env, fill_cfg_env},
     primitives::{BlockEnv, CfgEnv, SpecId},
 };
 use reth_trie::StateRoot;
 use std::{
-    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, VecDeque},
     fmt::Debug,
-    ops::{Deref, DerefMut, Range, RangeBounds, RangeInclusive},
+    ops::{Bound, Deref, DerefMut, Range, RangeBounds, RangeInclusive},
     sync::Arc,
+    time::{Duration, Instant},
 };
+use tracing::warn;
+
+/// Result of verifying the stored receipts of a block against its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiptVerification {
+    /// Number of the verified block.
+    pub block_number: BlockNumber,
+    /// Receipts root computed from the stored receipts, if it doesn't match the header.
+    pub receipts_root_mismatch: Option<H256>,
+    /// Logs bloom aggregated from the stored receipts, if it doesn't match the header.
+    pub logs_bloom_mismatch: Option<Bloom>,
+}
+
+impl ReceiptVerification {
+    /// Returns `true` if the stored receipts match the header.
+    pub fn is_valid(&self) -> bool {
+        self.receipts_root_mismatch.is_none() && self.logs_bloom_mismatch.is_none()
+    }
+}
+
+/// Result of validating the fork id of a peer, see [DatabaseProvider::validate_peer_fork_id].
+pub type ForkIdValidation = std::result::Result<(), ValidationError>;
+
+/// Lowest block numbers for which the data is present in the database, see
+/// [DatabaseProvider::earliest_history_heights].
+///
+/// `None` means that there is no data of the kind at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryAvailability {
+    /// Lowest block with a header.
+    pub headers: Option<BlockNumber>,
+    /// Lowest block with body indices.
+    pub bodies: Option<BlockNumber>,
+    /// Lowest block with receipts.
+    pub receipts: Option<BlockNumber>,
+    /// Lowest block with transaction senders.
+    pub senders: Option<BlockNumber>,
+}
+
+/// What the database holds of a block, see [DatabaseProvider::block_availability].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockAvailability {
+    /// The hash of the block is known.
+    pub known: bool,
+    /// The block is canonical and its body indices are stored.
+    pub body: bool,
+    /// The block is canonical and was executed, see [DatabaseProvider::has_receipts].
+    pub receipts: bool,
+    /// The block is canonical and was executed, but its receipts were pruned.
+    pub receipts_pruned: bool,
+}
+
+impl BlockAvailability {
+    /// Returns `true` if the block is fully stored and executed, it can be skipped.
+    ///
+    /// Blocks whose receipts were pruned are complete.
+    pub fn is_complete(&self) -> bool {
+        self.known && self.body && (self.receipts || self.receipts_pruned)
+    }
+
+    /// Returns `true` if the block is known but its body is missing.
+    pub fn needs_body(&self) -> bool {
+        self.known && !self.body
+    }
+
+    /// Returns `true` if the body of the block is stored but it wasn't executed yet.
+    pub fn needs_execution(&self) -> bool {
+        self.body && !self.receipts && !self.receipts_pruned
+    }
+}
+
+/// Rows written to a table by a read-write [DatabaseProvider], see [CommitStats].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableWriteStats {
+    /// Number of inserted or overwritten rows.
+    pub puts: u64,
+    /// Number of deleted rows.
+    pub deletes: u64,
+    /// Total size of the encoded keys and compressed values that were put.
+    pub bytes: u64,
+}
+
+/// Writes of a read-write [DatabaseProvider], returned by [DatabaseProvider::commit].
+///
+/// Only the writes of the block write helpers of the provider are counted, like
+/// [BlockWriter::insert_block] and [DatabaseProvider::remove_block_range], not the ones made
+/// directly on the transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitStats {
+    /// Written rows by table name.
+    pub tables: HashMap<&'static str, TableWriteStats>,
+    /// Duration of the commit of the transaction.
+    pub duration: Duration,
+}
+
+/// Size of a table, see [DatabaseProvider::table_stats].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableStats {
+    /// Name of the table.
+    pub table: &'static str,
+    /// Number of entries.
+    pub entries: u64,
+    /// Total size of the encoded keys in bytes, only summed by
+    /// [DatabaseProvider::table_stats_with_sizes].
+    pub key_bytes: Option<u64>,
+    /// Total size of the compressed values in bytes, only summed by
+    /// [DatabaseProvider::table_stats_with_sizes].
+    pub value_bytes: Option<u64>,
+    /// Number of internal pages of the B-tree.
+    pub branch_pages: u64,
+    /// Number of leaf pages of the B-tree.
+    pub leaf_pages: u64,
+    /// Number of pages holding values too large for a leaf page.
+    pub overflow_pages: u64,
+    /// Size of a database page in bytes.
+    pub page_size: u64,
+}
+
+impl TableStats {
+    /// Returns the number of pages of the table.
+    pub fn pages(&self) -> u64 {
+        self.branch_pages + self.leaf_pages + self.overflow_pages
+    }
+
+    /// Returns the size of the pages of the table in bytes.
+    pub fn size(&self) -> u64 {
+        self.pages() * self.page_size
+    }
+}
+
+/// Totals of all tables and the free pages of the database, see [DatabaseProvider::db_stats].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatabaseStats {
+    /// Number of entries of all tables.
+    pub entries: u64,
+    /// Number of pages of all tables.
+    pub pages: u64,
+    /// Number of free pages, which are reused by later writes.
+    pub freelist_pages: u64,
+    /// Size of a database page in bytes.
+    pub page_size: u64,
+}
+
+impl DatabaseStats {
+    /// Returns the size of the pages of all tables in bytes.
+    pub fn size(&self) -> u64 {
+        self.pages * self.page_size
+    }
+
+    /// Returns the size of the free pages in bytes.
+    pub fn free_size(&self) -> u64 {
+        self.freelist_pages * self.page_size
+    }
+}
+
+/// Collects the [TableStats] of a table, see [DatabaseProvider::table_stats].
+struct TableStatsViewer<'a, TX> {
+    tx: &'a TX,
+    /// Whether the key and value sizes are summed over every entry.
+    sizes: bool,
+}
+
+impl<'a, 'tx, TX: DbTx<'tx>> TableViewer<TableStats> for TableStatsViewer<'a, TX> {
+    type Error = reth_interfaces::Error;
+
+    fn view<T: Table>(&self) -> Result<TableStats> {
+        let stat = self.tx.stat::<T>()?;
+        let (key_bytes, value_bytes) = if self.sizes {
+            let (mut key_bytes, mut value_bytes) = (0, 0);
+            for entry in self.tx.cursor_read::<RawTable<T>>()?.walk(None)? {
+                let (key, value) = entry?;
+                key_bytes += key.raw_key().len() as u64;
+                value_bytes += value.raw_value().len() as u64;
+            }
+            (Some(key_bytes), Some(value_bytes))
+        } else {
+            (None, None)
+        };
+        Ok(TableStats {
+            table: T::NAME,
+            entries: stat.entries as u64,
+            key_bytes,
+            value_bytes,
+            branch_pages: stat.branch_pages as u64,
+            leaf_pages: stat.leaf_pages as u64,
+            overflow_pages: stat.overflow_pages as u64,
+            page_size: stat.page_size as u64,
+        })
+    }
+}
+
+/// Progress of a pruning call, see [DatabaseProvider::prune_transaction_lookup] and
+/// [DatabaseProvider::prune_receipts].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneProgress {
+    /// The entry limit was reached before everything up to the target block was pruned.
+    HasMoreData,
+    /// Everything up to the target block is pruned.
+    Finished,
+}
+
+/// The canonical blocks replaced by [DatabaseProvider::update_canonical_chain].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReorgOutcome {
+    /// The last block shared by the old and the new canonical chain, `None` if the genesis block
+    /// was replaced.
+    pub fork_block: Option<BlockNumHash>,
+    /// Numbers of the blocks whose canonical hash changed or was removed, in ascending order.
+    pub changed: Vec<BlockNumber>,
+}
+
+/// Inconsistency between the canonical chain tables, see
+/// [DatabaseProvider::check_canonical_consistency].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainInconsistency {
+    /// There are no canonical hashes for the blocks in the inclusive range.
+    CanonicalHashGap {
+        /// First block without a canonical hash.
+        start: BlockNumber,
+        /// Last block without a canonical hash.
+        end: BlockNumber,
+    },
+    /// There is no header for the canonical block.
+    MissingHeader {
+        /// Number of the canonical block.
+        number: BlockNumber,
+        /// Canonical hash of the block.
+        hash: BlockHash,
+    },
+    /// The hash to number entry of the canonical block is missing or points to another block.
+    HeaderNumberMismatch {
+        /// Number of the canonical block.
+        number: BlockNumber,
+        /// Canonical hash of the block.
+        hash: BlockHash,
+        /// Block number stored for the hash.
+        got: Option<BlockNumber>,
+    },
+    /// The parent hash of the canonical header is not the canonical hash of the previous block.
+    ParentHashMismatch {
+        /// Number of the canonical block.
+        number: BlockNumber,
+        /// Parent hash in the header.
+        parent_hash: BlockHash,
+        /// Canonical hash of the previous block.
+        expected: BlockHash,
+    },
+    /// There is no total difficulty for the canonical block.
+    MissingTotalDifficulty {
+        /// Number of the canonical block.
+        number: BlockNumber,
+    },
+    /// The stored total difficulty is not the one of the parent plus the difficulty of the
+    /// header.
+    TotalDifficultyMismatch {
+        /// Number of the block.
+        number: BlockNumber,
+        /// Total difficulty accumulated from the headers.
+        expected: U256,
+        /// Stored total difficulty.
+        got: U256,
+    },
+}
+
+/// Blocks checked by [DatabaseProvider::consistency_check].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyScope {
+    /// All blocks with stored body indices.
+    All,
+    /// The blocks in the inclusive range.
+    Blocks(RangeInclusive<BlockNumber>),
+}
+
+/// Inconsistency between the block body and transaction tables, see
+/// [DatabaseProvider::consistency_check].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// The transaction of the block's range has no `Transactions` row.
+    MissingTransaction {
+        /// Number of the block.
+        block: BlockNumber,
+        /// Number of the missing transaction.
+        tx_number: TxNumber,
+    },
+    /// The `TxHashNumber` entry of the transaction is missing or points to another transaction.
+    TxHashNumberMismatch {
+        /// Number of the block.
+        block: BlockNumber,
+        /// Number of the transaction.
+        tx_number: TxNumber,
+        /// Hash of the transaction.
+        hash: TxHash,
+        /// Transaction number stored for the hash.
+        got: Option<TxNumber>,
+    },
+    /// The `TransactionBlock` shards resolve the transaction to another block, or to none.
+    TransactionBlockMismatch {
+        /// Number of the block.
+        block: BlockNumber,
+        /// Number of the first transaction that is resolved wrongly.
+        tx_number: TxNumber,
+        /// Block the transaction resolves to.
+        got: Option<BlockNumber>,
+    },
+    /// There are receipts for the block, which is above the `Execution` stage checkpoint.
+    ReceiptsPastCheckpoint {
+        /// Number of the block.
+        block: BlockNumber,
+        /// Number of the first transaction with a receipt.
+        tx_number: TxNumber,
+        /// Block of the `Execution` stage checkpoint, if there is one.
+        checkpoint: Option<BlockNumber>,
+    },
+    /// The number of `TxSenders` rows of the block differs from its transaction count.
+    SendersCountMismatch {
+        /// Number of the block.
+        block: BlockNumber,
+        /// Number of transactions of the block.
+        tx_count: u64,
+        /// Number of stored senders.
+        senders: u64,
+    },
+}
+
+/// Number of transactions from which on transactions are hashed in parallel.
+const PARALLEL_TX_HASHING_THRESHOLD: usize = 64;
+
+/// Number of transactions hashed at once while scanning for a transaction whose hash lookup entry
+/// was pruned.
+const TX_HASH_SCAN_CHUNK_SIZE: usize = 10_000;
 
 /// A [`DatabaseProvider`] that holds a read-only database transaction.
 pub type DatabaseProviderRO<'this, DB> = DatabaseProvider<'this, <DB as DatabaseGAT<'this>>::TX>;
@@ -68,11 +414,23 @@ impl<'this, DB: Database> DerefMut for DatabaseProviderRW<'this, DB> {
 }
 
 impl<'this, DB: Database> DatabaseProviderRW<'this, DB> {
-    /// Commit database transaction
-    pub fn commit(self) -> Result<bool> {
+    /// Commit database transaction, see [DatabaseProvider::commit].
+    pub fn commit(self) -> Result<CommitStats> {
         self.0.commit()
     }
 
+    /// Allows inserting blocks that don't follow the canonical tip, see
+    /// [DatabaseProvider::allow_block_gaps].
+    pub fn allow_block_gaps(self) -> Self {
+        Self(self.0.allow_block_gaps())
+    }
+
+    /// Indexes the transactions of inserted blocks by sender, see
+    /// [DatabaseProvider::with_sender_index].
+    pub fn with_sender_index(self) -> Self {
+        Self(self.0.with_sender_index())
+    }
+
     /// Consume `DbTx` or `DbTxMut`.
     pub fn into_tx(self) -> <DB as DatabaseGAT<'this>>::TXMut {
         self.0.into_tx()
@@ -90,13 +448,143 @@ where
     tx: TX,
     /// Chain spec
     chain_spec: Arc<ChainSpec>,
+    /// Recently resolved headers for filling EVM environments, only kept by read-only providers.
+    env_cache: Option<EnvCache>,
+    /// Whether headers passed in for filling block environments are checked for proof-of-work
+    /// fields past the merge.
+    check_merge_fields: bool,
+    /// Records the expensive queries and the commits if set.
+    metrics: Option<DatabaseProviderMetrics>,
+    /// Keeps the commit durations and reports slow commits if set.
+    commit_monitor: Option<CommitMonitor>,
+    /// Rows written by the write helpers of the provider, only updated by read-write providers.
+    write_stats: Mutex<HashMap<&'static str, TableWriteStats>>,
+    /// Buffered puts of the write helpers, only set for read-write providers that enabled
+    /// batching.
+    batch: Mutex<Option<BatchWriter<TX>>>,
+    /// Whether inserted blocks may skip heights above the canonical tip.
+    allow_block_gaps: bool,
+    /// Whether inserted transactions are indexed by sender in
+    /// [TransactionSenders](tables::TransactionSenders).
+    index_transaction_senders: bool,
     _phantom_data: std::marker::PhantomData<&'this TX>,
 }
 
+/// Number of blocks whose header, total difficulty and spec are kept by the [EnvCache].
+const ENV_CACHE_SIZE: usize = 4;
+
+/// The header of a block resolved for filling EVM environments, with its total difficulty and
+/// spec.
+#[derive(Debug, Clone)]
+struct EnvHeader {
+    header: SealedHeader,
+    total_difficulty: U256,
+    spec_id: SpecId,
+}
+
+/// Cache of the most recently resolved [EnvHeader]s of a provider.
+///
+/// The provider wraps a single read-only transaction, so the entries can't go stale within its
+/// lifetime.
+#[derive(Debug, Default)]
+struct EnvCache {
+    entries: Mutex<VecDeque<EnvHeader>>,
+}
+
+impl EnvCache {
+    /// Returns the cached entry of the block.
+    fn get(&self, id: BlockHashOrNumber) -> Option<EnvHeader> {
+        self.entries
+            .lock()
+            .iter()
+            .find(|entry| match id {
+                BlockHashOrNumber::Hash(hash) => entry.header.hash == hash,
+                BlockHashOrNumber::Number(number) => entry.header.number == number,
+            })
+            .cloned()
+    }
+
+    /// Inserts the entry, evicting the least recently inserted one if the cache is full.
+    fn insert(&self, entry: EnvHeader) {
+        let mut entries = self.entries.lock();
+        if entries.len() == ENV_CACHE_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
 impl<'this, TX: DbTxMut<'this>> DatabaseProvider<'this, TX> {
     /// Creates a provider with an inner read-write transaction.
     pub fn new_rw(tx: TX, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { tx, chain_spec, _phantom_data: std::marker::PhantomData }
+        // writes within the transaction could invalidate cached headers
+        Self {
+            tx,
+            chain_spec,
+            env_cache: None,
+            check_merge_fields: true,
+            metrics: None,
+            commit_monitor: None,
+            write_stats: Mutex::default(),
+            batch: Mutex::new(None),
+            allow_block_gaps: false,
+            index_transaction_senders: false,
+            _phantom_data: std::marker::PhantomData,
+        }
+    }
+
+    /// Writes the rows buffered since [DatabaseProvider::enable_write_batching], batching stays
+    /// enabled.
+    pub fn flush(&self) -> std::result::Result<(), DatabaseError> {
+        if let Some(batch) = self.batch.lock().as_mut() {
+            batch.write(&self.tx)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a provider with an inner read-write transaction that records its commits in the
+    /// [DatabaseProviderMetrics].
+    pub fn new_rw_with_metrics(
+        tx: TX,
+        chain_spec: Arc<ChainSpec>,
+        metrics: DatabaseProviderMetrics,
+    ) -> Self {
+        Self { metrics: Some(metrics), ..Self::new_rw(tx, chain_spec) }
+    }
+
+    /// Records the duration of the commit in the [CommitMonitor], which warns if it is slow.
+    pub fn with_commit_monitor(mut self, monitor: CommitMonitor) -> Self {
+        self.commit_monitor = Some(monitor);
+        self
+    }
+
+    /// Disables the check that headers past the merge have a zero difficulty and nonce when
+    /// filling block environments from a given header.
+    ///
+    /// Useful for knowingly replaying test chains that don't follow the post-merge header rules.
+    pub fn without_merge_field_checks(mut self) -> Self {
+        self.check_merge_fields = false;
+        self
+    }
+
+    /// Allows inserting blocks at any free height instead of only on top of the canonical tip,
+    /// e.g. for snap sync style insertion.
+    ///
+    /// Blocks are still checked against their parent if it is stored. The total difficulty of a
+    /// block without a stored parent starts at its own difficulty.
+    pub fn allow_block_gaps(mut self) -> Self {
+        self.allow_block_gaps = true;
+        self
+    }
+
+    /// Indexes the transactions of inserted blocks by sender, which allows querying them with
+    /// [DatabaseProvider::transactions_by_sender].
+    ///
+    /// The index is optional since it costs an entry per transaction. Entries of removed blocks
+    /// are deleted whether or not the index is enabled.
+    pub fn with_sender_index(mut self) -> Self {
+        self.index_transaction_senders = true;
+        self
     }
 }
 
@@ -127,64 +615,2336 @@ where
     while let Some((sharded_key, list)) = item {
         // If the shard does not belong to the key, break.
         if !shard_belongs_to_key(&sharded_key) {
-            break
+            break;
         }
         cursor.delete_current()?;
 
-        // Check the first item.
-        // If it is greater or eq to the block number, delete it.
-        let first = list.iter(0).next().expect("List can't be empty");
-        if first >= block_number as usize {
-            item = cursor.prev()?;
-            continue
-        } else if block_number <= sharded_key.as_ref().highest_block_number {
-            // Filter out all elements greater than block number.
-            return Ok(list.iter(0).take_while(|i| *i < block_number as usize).collect::<Vec<_>>())
-        } else {
-            return Ok(list.iter(0).collect::<Vec<_>>())
+        // Check the first item.
+        // If it is greater or eq to the block number, delete it.
+        let first = list.iter(0).next().expect("List can't be empty");
+        if first >= block_number as usize {
+            item = cursor.prev()?;
+            continue;
+        } else if block_number <= sharded_key.as_ref().highest_block_number {
+            // Filter out all elements greater than block number.
+            return Ok(list.iter(0).take_while(|i| *i < block_number as usize).collect::<Vec<_>>());
+        } else {
+            return Ok(list.iter(0).collect::<Vec<_>>());
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Returns a [ProviderError::MissingEntry] for the key of the table `T`.
+fn missing_entry<T: Table>(key: impl std::fmt::Display) -> ProviderError {
+    ProviderError::MissingEntry { table: T::NAME, key: key.to_string() }
+}
+
+/// Seals the header of the given block with its canonical hash entry.
+///
+/// Returns [ProviderError::HeaderNotFound] if the hash entry is missing or belongs to another
+/// block.
+fn seal_header(
+    number: BlockNumber,
+    header: Header,
+    hash_entry: Option<(BlockNumber, BlockHash)>,
+) -> Result<SealedHeader> {
+    match hash_entry {
+        Some((hash_number, hash)) if hash_number == number => Ok(header.seal(hash)),
+        _ => Err(ProviderError::HeaderNotFound(number.into()).into()),
+    }
+}
+
+/// Returns `true` if the receipts of every transaction of the block are stored.
+///
+/// Receipts are written by the execution stage and can lag behind the block bodies, readers of
+/// block ranges stop at the first block that was not executed yet.
+fn has_all_receipts(body: &StoredBlockBodyIndices, receipts: usize) -> bool {
+    receipts as u64 == body.tx_count()
+}
+
+/// Returns the log index of the first log of each receipt, a prefix sum over the log counts of
+/// the preceding receipts.
+fn log_index_offsets<'a>(receipts: impl IntoIterator<Item = &'a Receipt>) -> Vec<u64> {
+    let mut log_index = 0;
+    receipts
+        .into_iter()
+        .map(|receipt| {
+            let offset = log_index;
+            log_index += receipt.logs.len() as u64;
+            offset
+        })
+        .collect()
+}
+
+/// Walks the cursor over the entries between the given bounds.
+///
+/// Unlike [DbCursorRO::walk_range] the returned iterator owns the cursor, so it can be handed out
+/// without materializing the range. Entries are read lazily, iteration stops after the first error.
+fn walk_range_owned<'a, T, C>(
+    mut cursor: C,
+    start: Bound<T::Key>,
+    end: Bound<T::Key>,
+) -> Result<impl Iterator<Item = Result<KeyValue<T>>> + 'a>
+where
+    T: Table,
+    C: DbCursorRO<'a, T> + 'a,
+{
+    let mut next = match start {
+        Bound::Included(key) => cursor.seek(key)?,
+        Bound::Excluded(key) => match cursor.seek(key.clone())? {
+            Some((found, _)) if found == key => cursor.next()?,
+            entry => entry,
+        },
+        Bound::Unbounded => cursor.first()?,
+    }
+    .map(Ok);
+
+    Ok(std::iter::from_fn(move || {
+        let entry = next.take()?;
+        if let Ok((key, _)) = &entry {
+            let in_range = match &end {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
+            };
+            if !in_range {
+                return None;
+            }
+            next = cursor.next().transpose();
+        }
+        Some(entry.map_err(Into::into))
+    }))
+}
+
+/// Lazy iterator over the [Receipts](tables::Receipts) of a range of transactions, returned by
+/// [DatabaseProvider::receipts_iter].
+///
+/// The iterator holds a cursor of the provider's database transaction, it borrows the provider and
+/// has to be dropped before the transaction is committed.
+pub struct ReceiptsIter<'a, TX: DbTxGAT<'a>> {
+    /// Cursor over the receipts table.
+    cursor: <TX as DbTxGAT<'a>>::Cursor<tables::Receipts>,
+    /// The next entry to return, `None` once the walk is done.
+    next: Option<std::result::Result<(TxNumber, Receipt), DatabaseError>>,
+    /// The transaction number where the walk stops.
+    end: Bound<TxNumber>,
+}
+
+impl<'a, TX: DbTxGAT<'a>> ReceiptsIter<'a, TX> {
+    /// Skips to the receipt of the given transaction, or the next stored one if it has none.
+    ///
+    /// The end of the range is kept, seeking past it ends the iteration.
+    pub fn seek(&mut self, tx_number: TxNumber) -> Result<()> {
+        self.next = None;
+        self.next = self.cursor.seek(tx_number)?.map(Ok);
+        Ok(())
+    }
+}
+
+impl<'a, TX: DbTxGAT<'a>> Iterator for ReceiptsIter<'a, TX> {
+    type Item = Result<(TxNumber, Receipt)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.next.take()?;
+        if let Ok((tx_number, _)) = &entry {
+            let in_range = match &self.end {
+                Bound::Included(end) => tx_number <= end,
+                Bound::Excluded(end) => tx_number < end,
+                Bound::Unbounded => true,
+            };
+            if !in_range {
+                return None;
+            }
+            self.next = self.cursor.next().transpose();
+        }
+        Some(entry.map_err(Into::into))
+    }
+}
+
+impl<'a, TX: DbTxGAT<'a>> Debug for ReceiptsIter<'a, TX> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReceiptsIter").field("next", &self.next).field("end", &self.end).finish()
+    }
+}
+
+impl<'this, TX: DbTx<'this>> DatabaseProvider<'this, TX> {
+    /// Creates a provider with an inner read-only transaction.
+    pub fn new(tx: TX, chain_spec: Arc<ChainSpec>) -> Self {
+        Self {
+            tx,
+            chain_spec,
+            env_cache: Some(EnvCache::default()),
+            check_merge_fields: true,
+            metrics: None,
+            commit_monitor: None,
+            write_stats: Mutex::default(),
+            batch: Mutex::new(None),
+            allow_block_gaps: false,
+            index_transaction_senders: false,
+            _phantom_data: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a provider with an inner read-only transaction that records its expensive queries
+    /// in the [DatabaseProviderMetrics].
+    pub fn new_with_metrics(
+        tx: TX,
+        chain_spec: Arc<ChainSpec>,
+        metrics: DatabaseProviderMetrics,
+    ) -> Self {
+        Self { metrics: Some(metrics), ..Self::new(tx, chain_spec) }
+    }
+
+    /// Creates a provider with an inner read-only transaction, failing with
+    /// [ProviderError::GenesisMismatch] if the database belongs to another chain.
+    pub fn new_checked(tx: TX, chain_spec: Arc<ChainSpec>) -> Result<Self> {
+        let provider = Self::new(tx, chain_spec);
+        provider.validate_genesis()?;
+        Ok(provider)
+    }
+
+    /// Runs the query, recording it for the operation if the provider has metrics, see
+    /// [DatabaseProviderMetrics::record].
+    fn record<T>(&self, operation: Operation, query: impl FnOnce() -> T) -> T {
+        match &self.metrics {
+            Some(metrics) => metrics.record(operation, query),
+            None => query(),
+        }
+    }
+
+    /// Returns the chain spec of the provider.
+    pub fn chain_spec(&self) -> &Arc<ChainSpec> {
+        &self.chain_spec
+    }
+
+    /// Checks that the stored genesis hash matches the genesis hash of the chain spec.
+    ///
+    /// A database without a genesis block is always valid.
+    pub fn validate_genesis(&self) -> Result<()> {
+        if let Some(got) = self.block_hash(0)? {
+            let expected = self.chain_spec.genesis_hash();
+            if got != expected {
+                return Err(ProviderError::GenesisMismatch { expected, got }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume `DbTx` or `DbTxMut`.
+    pub fn into_tx(self) -> TX {
+        self.tx
+    }
+
+    /// Pass `DbTx` or `DbTxMut` mutable reference.
+    pub fn tx_mut(&mut self) -> &mut TX {
+        &mut self.tx
+    }
+
+    /// Pass `DbTx` or `DbTxMut` immutable reference.
+    pub fn tx_ref(&self) -> &TX {
+        &self.tx
+    }
+
+    /// Return full table as Vec
+    pub fn table<T: Table>(&self) -> std::result::Result<Vec<KeyValue<T>>, DatabaseError>
+    where
+        T::Key: Default + Ord,
+    {
+        self.cursor_read::<T>()?
+            .walk(Some(T::Key::default()))?
+            .collect::<std::result::Result<Vec<_>, DatabaseError>>()
+    }
+
+    /// Lazily iterate over the duplicates of the key in a dup-sorted table whose subkeys are within
+    /// the given range.
+    ///
+    /// See [DbDupCursorRO::walk_dup_range] for how the subkeys are compared. The iterator holds a
+    /// cursor of the provider's database transaction, it has to be dropped before the transaction
+    /// is committed.
+    pub fn walk_dup<T: DupSort>(
+        &self,
+        key: T::Key,
+        range: impl RangeBounds<T::SubKey>,
+    ) -> Result<impl Iterator<Item = Result<KeyValue<T>>> + '_> {
+        let mut cursor = self.cursor_dup_read::<T>()?;
+        let end = range.end_bound().cloned();
+        let mut next = cursor.walk_dup_range(key, range)?.next();
+
+        Ok(std::iter::from_fn(move || {
+            let entry = next.take()?;
+            if entry.is_ok() {
+                next = cursor.next_dup_until(end.as_ref()).transpose();
+            }
+            Some(entry.map_err(Into::into))
+        }))
+    }
+
+    /// Lazily iterate over the headers in the given range.
+    ///
+    /// In contrast to [HeaderProvider::headers_range] the headers are read on demand. The iterator
+    /// holds a cursor of the provider's database transaction, it has to be dropped before the
+    /// transaction is committed.
+    pub fn headers_range_iter(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<impl Iterator<Item = Result<Header>> + '_> {
+        let headers = walk_range_owned(
+            self.cursor_read::<tables::Headers>()?,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        )?;
+        Ok(headers.map(|entry| entry.map(|(_, header)| header)))
+    }
+
+    /// Lazily iterate over the sealed headers in the given range.
+    ///
+    /// The header hashes are read by walking the `CanonicalHeaders` table in lockstep with the
+    /// headers, see [DatabaseProvider::headers_range_iter] for the borrow of the transaction.
+    pub fn sealed_headers_range_iter(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<impl Iterator<Item = Result<SealedHeader>> + '_> {
+        let (start, end) = (range.start_bound().cloned(), range.end_bound().cloned());
+        let mut hashes =
+            walk_range_owned(self.cursor_read::<tables::CanonicalHeaders>()?, start, end)?;
+        let headers = walk_range_owned(self.cursor_read::<tables::Headers>()?, start, end)?;
+        Ok(headers.map(move |entry| {
+            let (number, header) = entry?;
+            seal_header(number, header, hashes.next().transpose()?)
+        }))
+    }
+
+    /// Returns up to `count` sealed headers ending at block `from`, the highest first.
+    ///
+    /// Walks the `Headers` and `CanonicalHeaders` tables backwards in lockstep, which avoids
+    /// collecting [HeaderProvider::sealed_headers_range] and reversing it. Fewer headers are
+    /// returned if the walk reaches the genesis block, and none if `count` is zero.
+    pub fn sealed_headers_descending(
+        &self,
+        from: BlockNumber,
+        count: u64,
+    ) -> Result<Vec<SealedHeader>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let range = from.saturating_sub(count - 1)..=from;
+        let mut hashes_cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
+        let mut hashes = hashes_cursor.walk_back_range(range.clone())?;
+
+        let mut headers = Vec::new();
+        for entry in self.cursor_read::<tables::Headers>()?.walk_back_range(range)? {
+            let (number, header) = entry?;
+            match hashes.next().transpose()? {
+                // walking backwards, a higher hash has no header
+                Some((hash_number, _)) if hash_number > number => {
+                    return Err(ProviderError::InconsistentIndex {
+                        table: tables::CanonicalHeaders::NAME,
+                        block: hash_number,
+                    }
+                    .into())
+                }
+                Some((hash_number, hash)) if hash_number == number => {
+                    headers.push(header.seal(hash))
+                }
+                _ => return Err(ProviderError::HeaderNotFound(number.into()).into()),
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Lazily iterate over the receipts of the transactions in the given range.
+    ///
+    /// Transactions without a stored receipt, e.g. because they were pruned or not executed yet,
+    /// are skipped. [ReceiptsIter::seek] jumps ahead without reading the receipts in between.
+    ///
+    /// ```
+    /// # use reth_db::test_utils::create_test_rw_db;
+    /// # use reth_primitives::MAINNET;
+    /// # use reth_provider::ProviderFactory;
+    /// # fn main() -> reth_interfaces::Result<()> {
+    /// let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+    /// let provider = factory.provider()?;
+    ///
+    /// let mut receipts = provider.receipts_iter(0..1_000)?;
+    /// // skip the transactions that were already processed
+    /// receipts.seek(500)?;
+    /// for entry in receipts {
+    ///     let (_tx_number, _receipt) = entry?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The iterator borrows the provider, so it can't outlive it:
+    ///
+    /// ```compile_fail
+    /// # use reth_db::test_utils::create_test_rw_db;
+    /// # use reth_primitives::MAINNET;
+    /// # use reth_provider::ProviderFactory;
+    /// # fn main() -> reth_interfaces::Result<()> {
+    /// let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+    /// let receipts = {
+    ///     let provider = factory.provider()?;
+    ///     provider.receipts_iter(..)?
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn receipts_iter(&self, range: impl RangeBounds<TxNumber>) -> Result<ReceiptsIter<'_, TX>> {
+        let mut cursor = self.cursor_read::<tables::Receipts>()?;
+        let next = match range.start_bound() {
+            Bound::Included(&tx_number) => cursor.seek(tx_number)?,
+            Bound::Excluded(&tx_number) => match cursor.seek(tx_number)? {
+                Some((found, _)) if found == tx_number => cursor.next()?,
+                entry => entry,
+            },
+            Bound::Unbounded => cursor.first()?,
+        }
+        .map(Ok);
+        Ok(ReceiptsIter { cursor, next, end: range.end_bound().cloned() })
+    }
+
+    /// Get the total difficulty of every block in the given range.
+    ///
+    /// Like [HeaderProvider::header_td_by_number], blocks past the final paris(merge) block report
+    /// the final paris difficulty.
+    pub fn header_td_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, U256)>> {
+        let mut cursor = self.cursor_read::<tables::HeaderTD>()?;
+        cursor
+            .walk_range(range)?
+            .map(|entry| {
+                let (number, td) = entry?;
+                let td = self.chain_spec.final_paris_total_difficulty(number).unwrap_or(td.0);
+                Ok((number, td))
+            })
+            .collect()
+    }
+
+    /// Get the first canonical block whose total difficulty is at least the given one.
+    ///
+    /// Total difficulty never decreases along the chain, so this does a binary search over the
+    /// canonical blocks with point lookups. Returns `None` if no canonical block reaches `td`.
+    pub fn first_block_with_td_at_least(&self, td: U256) -> Result<Option<BlockNumber>> {
+        self.first_canonical_block_where(|number| {
+            let block_td = self
+                .header_td_by_number(number)?
+                .ok_or(ProviderError::TotalDifficultyNotFound { number })?;
+            Ok(block_td >= td)
+        })
+    }
+
+    /// Get the lowest canonical block whose timestamp is at or after the given one.
+    ///
+    /// Timestamps never decrease along the chain, so this does a binary search over the canonical
+    /// headers with point lookups. Of a run of blocks with the same timestamp the lowest one is
+    /// returned. Returns `None` if all canonical blocks are earlier.
+    pub fn block_number_at_or_after_timestamp(
+        &self,
+        timestamp: u64,
+    ) -> Result<Option<BlockNumber>> {
+        self.first_canonical_block_where(|number| Ok(self.header_timestamp(number)? >= timestamp))
+    }
+
+    /// Get the highest canonical block whose timestamp is at or before the given one.
+    ///
+    /// Mirrors [DatabaseProvider::block_number_at_or_after_timestamp]: if blocks have exactly the
+    /// timestamp, the lowest of them is returned. Returns `None` if all canonical blocks are later.
+    pub fn block_number_at_or_before_timestamp(
+        &self,
+        timestamp: u64,
+    ) -> Result<Option<BlockNumber>> {
+        let first_at_or_after = self.block_number_at_or_after_timestamp(timestamp)?;
+        let mut cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
+        Ok(match first_at_or_after {
+            Some(number) if self.header_timestamp(number)? == timestamp => Some(number),
+            // the canonical block below is the last one before the timestamp
+            Some(number) => {
+                cursor.seek_exact(number)?;
+                cursor.prev()?.map(|(number, _)| number)
+            }
+            None => cursor.last()?.map(|(number, _)| number),
+        })
+    }
+
+    /// Returns the timestamp of the stored header, failing if it is missing.
+    fn header_timestamp(&self, number: BlockNumber) -> Result<u64> {
+        Ok(self
+            .get::<tables::Headers>(number)?
+            .ok_or(ProviderError::HeaderNotFound(number.into()))?
+            .timestamp)
+    }
+
+    /// Binary search for the lowest canonical block that satisfies `reached`, which has to hold
+    /// for every canonical block after the first one it holds for.
+    ///
+    /// The search is bounded by the canonical headers, so header rows above the canonical tip
+    /// and gaps in the canonical chain are never looked up. Returns `None` if `reached` doesn't
+    /// hold for the canonical tip.
+    fn first_canonical_block_where(
+        &self,
+        mut reached: impl FnMut(BlockNumber) -> Result<bool>,
+    ) -> Result<Option<BlockNumber>> {
+        let mut cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
+        let (Some((mut low, _)), Some((mut high, _))) = (cursor.first()?, cursor.last()?) else {
+            return Ok(None)
+        };
+        if !reached(high)? {
+            return Ok(None)
+        }
+
+        // invariant: `found` satisfies `reached` and the only canonical blocks below it that may
+        // as well are in `low..high`
+        let mut found = high;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match cursor.seek(mid)? {
+                Some((number, _)) if number < high => {
+                    if reached(number)? {
+                        found = number;
+                        high = number;
+                    } else {
+                        low = number + 1;
+                    }
+                }
+                // no canonical block in `mid..high`
+                _ => high = mid,
+            }
+        }
+        Ok(Some(found))
+    }
+
+    /// Get the withdrawals of the block, deciding whether Shanghai is active by the timestamp of
+    /// the stored header.
+    ///
+    /// Returns `None` if the block doesn't exist or is before Shanghai. Use
+    /// [WithdrawalsProvider::withdrawals_by_block] if the header is already at hand.
+    pub fn withdrawals_by_block_id(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<Withdrawal>>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(header) = self.header_by_number(number)? else { return Ok(None) };
+        self.withdrawals_by_block(number.into(), header.timestamp)
+    }
+
+    /// Get the ommers of all blocks in the range that have any.
+    ///
+    /// Like [BlockReader::ommers], blocks after the Paris (Merge) hardfork block have no ommers
+    /// and the table is not read for them.
+    pub fn ommers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, Vec<Header>)>> {
+        let start = range.start_bound().cloned();
+        let mut end = range.end_bound().cloned();
+        if let Some((paris_block, _)) = self.chain_spec.paris_block_and_final_difficulty {
+            let first = match start {
+                Bound::Included(number) => number,
+                Bound::Excluded(number) => number.saturating_add(1),
+                Bound::Unbounded => 0,
+            };
+            if first >= paris_block {
+                return Ok(Vec::new());
+            }
+            // stop before the paris block
+            end = match end {
+                Bound::Included(number) if number < paris_block => end,
+                Bound::Excluded(number) if number <= paris_block => end,
+                _ => Bound::Excluded(paris_block),
+            };
+        }
+
+        let mut cursor = self.cursor_read::<tables::BlockOmmers>()?;
+        cursor
+            .walk_range((start, end))?
+            .map(|entry| entry.map(|(number, stored)| (number, stored.ommers)).map_err(Into::into))
+            .collect()
+    }
+
+    /// Get the ommer at the given index of the block.
+    ///
+    /// Returns `None` if the block doesn't exist, is after the Paris (Merge) hardfork block or
+    /// has no ommer at the index.
+    pub fn ommer_by_block_and_index(
+        &self,
+        id: BlockHashOrNumber,
+        index: usize,
+    ) -> Result<Option<Header>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        if self.chain_spec.final_paris_total_difficulty(number).is_some() {
+            return Ok(None);
+        }
+        Ok(self
+            .tx
+            .get::<tables::BlockOmmers>(number)?
+            .and_then(|stored| stored.ommers.into_iter().nth(index)))
+    }
+
+    /// Get the withdrawal with the given global withdrawal index and the number of the block that
+    /// contains it.
+    ///
+    /// Scans the blocks backwards from the best block until the withdrawal indices drop to the
+    /// given index. Blocks past the best block are ignored.
+    pub fn withdrawal_by_index(&self, index: u64) -> Result<Option<(BlockNumber, Withdrawal)>> {
+        let best_block = self.best_block_number()?;
+        let mut cursor = self.cursor_read::<tables::BlockWithdrawals>()?;
+        for entry in cursor.walk_back(Some(best_block))? {
+            let (number, block_withdrawal) = entry?;
+            if number > best_block {
+                continue;
+            }
+
+            let withdrawals = block_withdrawal.withdrawals;
+            let (Some(first), Some(last)) = (withdrawals.first(), withdrawals.last()) else {
+                continue;
+            };
+            // indices only decrease from here on
+            if last.index < index {
+                return Ok(None);
+            }
+            if first.index <= index {
+                return Ok(withdrawals
+                    .into_iter()
+                    .find(|withdrawal| withdrawal.index == index)
+                    .map(|withdrawal| (number, withdrawal)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get the withdrawals of all blocks in the range that have any.
+    ///
+    /// Blocks before Shanghai and blocks without withdrawals are absent from the output.
+    pub fn withdrawals_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, Vec<Withdrawal>)>> {
+        let mut cursor = self.cursor_read::<tables::BlockWithdrawals>()?;
+        cursor
+            .walk_range(range)?
+            .map(|entry| {
+                entry.map(|(number, stored)| (number, stored.withdrawals)).map_err(Into::into)
+            })
+            .filter(|entry| entry.as_ref().map_or(true, |(_, withdrawals)| !withdrawals.is_empty()))
+            .collect()
+    }
+
+    /// Sum the withdrawals of all blocks in the range, in total and per target address and
+    /// validator.
+    ///
+    /// The amounts of the summary are in gwei. The withdrawals are added block by block, without
+    /// collecting the whole range first.
+    pub fn withdrawals_summary(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<WithdrawalsSummary> {
+        let mut summary = WithdrawalsSummary::default();
+        let mut cursor = self.cursor_read::<tables::BlockWithdrawals>()?;
+        for entry in cursor.walk_range(range)? {
+            let (_, stored) = entry?;
+            summary.extend(stored.withdrawals).map_err(ProviderError::from)?;
+        }
+        Ok(summary)
+    }
+
+    /// Count the withdrawals of all blocks in the range.
+    ///
+    /// Only the length prefix of the stored withdrawals is read, the withdrawals themselves are
+    /// not decoded.
+    pub fn withdrawals_count_in_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<usize> {
+        let raw_bound = |bound: Bound<&BlockNumber>| match bound {
+            Bound::Included(number) => Bound::Included(RawKey::new(*number)),
+            Bound::Excluded(number) => Bound::Excluded(RawKey::new(*number)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let range = (raw_bound(range.start_bound()), raw_bound(range.end_bound()));
+
+        let mut cursor = self.cursor_read::<RawTable<tables::BlockWithdrawals>>()?;
+        cursor.walk_range(range)?.try_fold(0, |count, entry| {
+            let (_, value) = entry?;
+            Ok(count + StoredBlockWithdrawals::withdrawals_count(value.raw_value()))
+        })
+    }
+
+    /// Get receipts by transaction number range.
+    ///
+    /// Returns [ProviderError::ReceiptPruned] for the first missing transaction if receipts of the
+    /// range were pruned, i.e. a later receipt is stored. Receipts missing at the end of the range
+    /// with no later receipt belong to transactions that were not executed yet and are not
+    /// considered an error.
+    pub fn receipts_by_tx_range(&self, range: impl RangeBounds<TxNumber>) -> Result<Vec<Receipt>> {
+        let next_tx_num = match range.start_bound() {
+            Bound::Included(&tx_num) => Some(tx_num),
+            Bound::Excluded(&tx_num) => tx_num.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let Some(mut next_tx_num) = next_tx_num else { return Ok(Vec::new()) };
+        let end = range.end_bound().cloned();
+
+        let mut receipts = Vec::new();
+        let mut cursor = self.cursor_read::<tables::Receipts>()?;
+        for entry in cursor.walk_range(range)? {
+            let (tx_num, receipt) = entry?;
+            if tx_num != next_tx_num {
+                return Err(ProviderError::ReceiptPruned(next_tx_num).into());
+            }
+            receipts.push(receipt);
+            let Some(next) = tx_num.checked_add(1) else { return Ok(receipts) };
+            next_tx_num = next;
+        }
+
+        let ends_in_range = match end {
+            Bound::Included(end) => next_tx_num <= end,
+            Bound::Excluded(end) => next_tx_num < end,
+            Bound::Unbounded => true,
+        };
+        if ends_in_range && cursor.seek(next_tx_num)?.is_some() {
+            return Err(ProviderError::ReceiptPruned(next_tx_num).into());
+        }
+        Ok(receipts)
+    }
+
+    /// Get receipts together with their transaction numbers by transaction number range.
+    ///
+    /// Unlike [DatabaseProvider::receipts_by_tx_range] this does not fail on pruned receipts, gaps
+    /// in the returned transaction numbers indicate receipts that were pruned.
+    pub fn receipts_by_tx_range_with_ids(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> Result<Vec<(TxNumber, Receipt)>> {
+        Ok(self
+            .cursor_read::<tables::Receipts>()?
+            .walk_range(range)?
+            .collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Get all logs matching the filter in the given block range.
+    ///
+    /// Blocks whose header logs bloom rules out the filter are skipped without reading their
+    /// receipts. The log index of [LogMeta] counts all logs of the block, including the ones that
+    /// didn't match the filter. The result ends before the first block that was not executed yet.
+    ///
+    /// Returns [ProviderError::ReceiptsPruned] for a range reaching into the blocks up to the
+    /// [PrunePart::Receipts] checkpoint, and [ProviderError::ReceiptPruned] if an executed block is
+    /// missing receipts, instead of returning the logs of the remaining receipts only.
+    pub fn logs_in_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        filter: &LogAddressAndTopicsFilter,
+    ) -> Result<Vec<(LogMeta, Log)>> {
+        let prune_height = self
+            .get_prune_checkpoint(PrunePart::Receipts)?
+            .map(|checkpoint| checkpoint.block_number);
+        let executed = self
+            .get_stage_checkpoint(StageId::Execution)?
+            .map(|checkpoint| checkpoint.block_number);
+
+        let mut logs = Vec::new();
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        for entry in self.cursor_read::<tables::Headers>()?.walk_range(range)? {
+            let (block_number, header) = entry?;
+            if let Some(prune_height) =
+                prune_height.filter(|prune_height| block_number <= *prune_height)
+            {
+                return Err(
+                    ProviderError::ReceiptsPruned { block: block_number, prune_height }.into()
+                );
+            }
+            // The header bloom is the stored union of the receipt blooms, recomputing a bloom per
+            // receipt would cost more than matching its logs directly.
+            if !filter.matches_bloom(&header.logs_bloom) {
+                continue;
+            }
+
+            let body = self
+                .block_body_indices(block_number)?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(block_number))?;
+            if body.is_empty() {
+                continue;
+            }
+            let block_hash = self
+                .tx
+                .get::<tables::CanonicalHeaders>(block_number)?
+                .ok_or_else(|| missing_entry::<tables::CanonicalHeaders>(block_number))?;
+
+            let receipts = receipts_cursor
+                .walk_range(body.tx_num_range())?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            if !has_all_receipts(&body, receipts.len()) {
+                if executed.map_or(true, |executed| block_number > executed) {
+                    break;
+                }
+                let mut tx_nums = body.tx_num_range();
+                let gap = receipts
+                    .iter()
+                    .zip(tx_nums.by_ref())
+                    .find_map(|((tx_num, _), expected)| (*tx_num != expected).then_some(expected));
+                let missing = gap.or_else(|| tx_nums.next()).unwrap_or(body.first_tx_num);
+                return Err(ProviderError::ReceiptPruned(missing).into());
+            }
+
+            let offsets = log_index_offsets(receipts.iter().map(|(_, receipt)| receipt));
+            for ((tx_num, receipt), first_log_index) in receipts.into_iter().zip(offsets) {
+                let mut tx_hash = None;
+                for (log_index, log) in (first_log_index..).zip(receipt.logs) {
+                    if filter.matches(&log) {
+                        // only hash transactions that emitted a matching log
+                        let tx_hash = match tx_hash {
+                            Some(tx_hash) => tx_hash,
+                            None => *tx_hash.insert(
+                                self.tx
+                                    .get::<tables::Transactions>(tx_num)?
+                                    .ok_or(ProviderError::TransactionNotFound(tx_num))?
+                                    .hash(),
+                            ),
+                        };
+                        let meta = LogMeta {
+                            block_hash,
+                            block_number,
+                            tx_hash,
+                            tx_index: tx_num - body.first_tx_num,
+                            log_index,
+                        };
+                        logs.push((meta, log));
+                    }
+                }
+            }
+        }
+        Ok(logs)
+    }
+
+    /// Get the receipts of the block together with the hashes of their transactions.
+    ///
+    /// Transactions and receipts are walked in lockstep, a block whose receipts don't line up with
+    /// its transactions returns [ProviderError::TransactionReceiptMismatch]. Returns `None` if the
+    /// block is not found or was not executed yet.
+    pub fn receipts_with_tx_hashes_by_block(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<(TxHash, Receipt)>>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(number)? else { return Ok(None) };
+        self.ensure_receipts_not_pruned(number)?;
+
+        let mut transactions = Vec::with_capacity(body.tx_count() as usize);
+        let mut receipts = Vec::with_capacity(body.tx_count() as usize);
+        let mut tx_cursor = self.cursor_read::<tables::Transactions>()?;
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        let mut tx_walker = tx_cursor.walk_range(body.tx_num_range())?;
+        let mut receipts_walker = receipts_cursor.walk_range(body.tx_num_range())?;
+        let mismatch = |tx_num| {
+            Err(ProviderError::TransactionReceiptMismatch { block_number: number, tx_num }.into())
+        };
+        loop {
+            match (tx_walker.next().transpose()?, receipts_walker.next().transpose()?) {
+                (None, None) => break,
+                (Some((tx_num, transaction)), Some((receipt_tx_num, receipt)))
+                    if tx_num == receipt_tx_num =>
+                {
+                    transactions.push(transaction);
+                    receipts.push(receipt);
+                }
+                // the block was not executed yet
+                (Some(_), None) if receipts.is_empty() => return Ok(None),
+                (Some((tx_num, _)), Some((receipt_tx_num, _))) => {
+                    return mismatch(tx_num.min(receipt_tx_num))
+                }
+                (Some((tx_num, _)), None) | (None, Some((tx_num, _))) => return mismatch(tx_num),
+            }
+        }
+
+        let tx_hashes: Vec<TxHash> = if transactions.len() < PARALLEL_TX_HASHING_THRESHOLD {
+            transactions.iter().map(|tx| tx.hash()).collect()
+        } else {
+            transactions.par_iter().map(|tx| tx.hash()).collect()
+        };
+        Ok(Some(tx_hashes.into_iter().zip(receipts).collect()))
+    }
+
+    /// Get the gas used by each transaction of the block, derived from the cumulative gas used of
+    /// consecutive receipts.
+    ///
+    /// Returns `None` if the block is not found or was not executed yet.
+    pub fn block_gas_used_by_tx(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<(TxNumber, u64)>>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(header) = self.header_by_number(number)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(number)? else { return Ok(None) };
+
+        let mut gas_used = Vec::with_capacity(body.tx_count() as usize);
+        let mut cumulative_gas_used = 0;
+        for entry in self.cursor_read::<tables::Receipts>()?.walk_range(body.tx_num_range())? {
+            let (tx_num, receipt) = entry?;
+            gas_used.push((tx_num, tx_gas_used(&receipt, cumulative_gas_used)));
+            cumulative_gas_used = receipt.cumulative_gas_used;
+        }
+
+        if !has_all_receipts(&body, gas_used.len()) {
+            return Ok(None);
+        }
+        ensure_block_gas_used(number, &header, cumulative_gas_used)?;
+        Ok(Some(gas_used))
+    }
+
+    /// Get the total gas used by the block, read from the last receipt of the block.
+    ///
+    /// Returns `None` if the block is not found or was not executed yet.
+    pub fn block_total_gas_used(&self, id: BlockHashOrNumber) -> Result<Option<u64>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(header) = self.header_by_number(number)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(number)? else { return Ok(None) };
+
+        let gas_used = if body.is_empty() {
+            0
+        } else {
+            match self.tx.get::<tables::Receipts>(body.last_tx_num())? {
+                Some(receipt) => receipt.cumulative_gas_used,
+                None => return Ok(None),
+            }
+        };
+
+        ensure_block_gas_used(number, &header, gas_used)?;
+        Ok(Some(gas_used))
+    }
+
+    /// Get the log index of the first log of each transaction in the block, counting the logs of
+    /// all preceding transactions of the block.
+    ///
+    /// The offsets are computed in a single walk over the receipts of the block. Returns `None` if
+    /// the block is not found or was not executed yet.
+    pub fn block_log_index_offsets(&self, id: BlockHashOrNumber) -> Result<Option<Vec<u64>>> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(number)? else { return Ok(None) };
+        self.ensure_receipts_not_pruned(number)?;
+
+        let receipts = self
+            .cursor_read::<tables::Receipts>()?
+            .walk_range(body.tx_num_range())?
+            .map(|result| result.map(|(_, receipt)| receipt))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if !has_all_receipts(&body, receipts.len()) {
+            return Ok(None);
+        }
+        Ok(Some(log_index_offsets(&receipts)))
+    }
+
+    /// Verify the stored receipts of the block against the receipts root and logs bloom of its
+    /// header.
+    ///
+    /// Only executed blocks should be verified, missing receipts are reported as a mismatch.
+    /// Returns [ProviderError::PreByzantiumReceipts] for blocks before Byzantium.
+    pub fn verify_block_receipts(&self, id: BlockHashOrNumber) -> Result<ReceiptVerification> {
+        let number = self.convert_hash_or_number(id)?.ok_or(ProviderError::BlockNotFound(id))?;
+        self.ensure_post_byzantium(number)?;
+
+        let header =
+            self.header_by_number(number)?.ok_or(ProviderError::HeaderNotFound(number.into()))?;
+        let body = self
+            .block_body_indices(number)?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+        let receipts = self
+            .cursor_read::<tables::Receipts>()?
+            .walk_range(body.tx_num_range())?
+            .map(|entry| entry.map(|(_, receipt)| receipt))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(verify_receipts(number, &header, receipts))
+    }
+
+    /// Verify the stored receipts of all blocks in the range against their headers, see
+    /// [DatabaseProvider::verify_block_receipts].
+    ///
+    /// Returns the first `limit` blocks whose receipts don't match the header.
+    pub fn verify_receipts_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        limit: usize,
+    ) -> Result<Vec<ReceiptVerification>> {
+        let mut mismatches = Vec::new();
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        for entry in self.cursor_read::<tables::Headers>()?.walk_range(range)? {
+            if mismatches.len() >= limit {
+                break;
+            }
+
+            let (number, header) = entry?;
+            self.ensure_post_byzantium(number)?;
+
+            let body = self
+                .block_body_indices(number)?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+            let receipts = receipts_cursor
+                .walk_range(body.tx_num_range())?
+                .map(|entry| entry.map(|(_, receipt)| receipt))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let verification = verify_receipts(number, &header, receipts);
+            if !verification.is_valid() {
+                mismatches.push(verification);
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Check that the `CanonicalHeaders`, `Headers`, `HeaderNumbers` and `HeaderTD` tables agree
+    /// on the canonical blocks in the range.
+    ///
+    /// The tables are walked in a single pass and only the findings are kept in memory. Returns
+    /// the first `limit` inconsistencies in block order.
+    pub fn check_canonical_consistency(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        limit: usize,
+    ) -> Result<Vec<ChainInconsistency>> {
+        let mut findings = Vec::new();
+        let mut headers_cursor = self.cursor_read::<tables::Headers>()?;
+        let mut numbers_cursor = self.cursor_read::<tables::HeaderNumbers>()?;
+        let mut td_cursor = self.cursor_read::<tables::HeaderTD>()?;
+
+        // the canonical block before the current one, seeded with the block before the range
+        let mut previous = match range.start_bound() {
+            Bound::Included(&number) if number > 0 => {
+                self.block_hash(number - 1)?.map(|hash| (number - 1, hash))
+            }
+            Bound::Excluded(&number) => self.block_hash(number)?.map(|hash| (number, hash)),
+            _ => None,
+        };
+
+        for entry in self.cursor_read::<tables::CanonicalHeaders>()?.walk_range(range)? {
+            if findings.len() >= limit {
+                break;
+            }
+
+            let (number, hash) = entry?;
+            let expected_parent = match previous {
+                Some((previous_number, previous_hash)) if previous_number + 1 == number => {
+                    Some(previous_hash)
+                }
+                Some((previous_number, _)) => {
+                    findings.push(ChainInconsistency::CanonicalHashGap {
+                        start: previous_number + 1,
+                        end: number - 1,
+                    });
+                    None
+                }
+                None => None,
+            };
+            previous = Some((number, hash));
+
+            match headers_cursor.seek_exact(number)? {
+                Some((_, header)) => {
+                    if let Some(expected) = expected_parent.filter(|h| *h != header.parent_hash) {
+                        findings.push(ChainInconsistency::ParentHashMismatch {
+                            number,
+                            parent_hash: header.parent_hash,
+                            expected,
+                        });
+                    }
+                }
+                None => findings.push(ChainInconsistency::MissingHeader { number, hash }),
+            }
+
+            let got = numbers_cursor.seek_exact(hash)?.map(|(_, number)| number);
+            if got != Some(number) {
+                findings.push(ChainInconsistency::HeaderNumberMismatch { number, hash, got });
+            }
+
+            if td_cursor.seek_exact(number)?.is_none() {
+                findings.push(ChainInconsistency::MissingTotalDifficulty { number });
+            }
+        }
+
+        findings.truncate(limit);
+        Ok(findings)
+    }
+
+    /// Check that the stored total difficulties of the headers in the range are accumulated from
+    /// the difficulties of the headers, starting at the total difficulty of the parent of the
+    /// first header.
+    ///
+    /// Returns [ChainInconsistency::MissingTotalDifficulty] for holes and
+    /// [ChainInconsistency::TotalDifficultyMismatch] for wrong values, in block order. The
+    /// accumulation continues past the findings, so a single wrong value is reported once. If
+    /// the total difficulty of the parent of the first header, or of a header after a gap in the
+    /// headers, is missing it is reported and the accumulation starts from the next stored value.
+    pub fn verify_td_consistency(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<Vec<ChainInconsistency>> {
+        let mut findings = Vec::new();
+        let mut td_cursor = self.cursor_read::<tables::HeaderTD>()?;
+        let mut previous: Option<(BlockNumber, Option<U256>)> = None;
+
+        for entry in self.cursor_read::<tables::Headers>()?.walk_range(range)? {
+            let (number, header) = entry?;
+            let parent_td = match previous {
+                Some((previous_number, td)) if previous_number + 1 == number => td,
+                _ if number == 0 => Some(U256::ZERO),
+                _ => {
+                    let td = td_cursor.seek_exact(number - 1)?.map(|(_, td)| td.0);
+                    if td.is_none() {
+                        findings.push(ChainInconsistency::MissingTotalDifficulty {
+                            number: number - 1,
+                        });
+                    }
+                    td
+                }
+            };
+
+            let expected = parent_td.map(|td| td + header.difficulty);
+            let td = match (td_cursor.seek_exact(number)?.map(|(_, td)| td.0), expected) {
+                (Some(got), Some(expected)) if got != expected => {
+                    findings.push(ChainInconsistency::TotalDifficultyMismatch {
+                        number,
+                        expected,
+                        got,
+                    });
+                    Some(expected)
+                }
+                (None, expected) => {
+                    findings.push(ChainInconsistency::MissingTotalDifficulty { number });
+                    expected
+                }
+                (got, expected) => expected.or(got),
+            };
+            previous = Some((number, td));
+        }
+        Ok(findings)
+    }
+
+    /// Check that the body indices, transaction, transaction lookup, transaction block, receipt
+    /// and sender tables agree on the blocks in the scope.
+    ///
+    /// The transactions in the range of every block have to be stored, have a
+    /// [TxHashNumber](tables::TxHashNumber) entry pointing back to them unless it was pruned, and
+    /// resolve to the block through the [TransactionBlock](tables::TransactionBlock) shards. Blocks
+    /// above the `Execution` stage checkpoint can't have receipts. The number of senders has to
+    /// match the transaction count, unless they were pruned or the block has no senders and is
+    /// above the `SenderRecovery` stage checkpoint.
+    ///
+    /// Every transaction is hashed, the cost grows with the number of transactions in the scope.
+    /// Returns the first `limit` inconsistencies in block order.
+    pub fn consistency_check(
+        &self,
+        scope: ConsistencyScope,
+        limit: usize,
+    ) -> Result<Vec<Inconsistency>> {
+        let range = match scope {
+            ConsistencyScope::All => (Bound::Unbounded, Bound::Unbounded),
+            ConsistencyScope::Blocks(range) => {
+                (Bound::Included(*range.start()), Bound::Included(*range.end()))
+            }
+        };
+        let executed = self
+            .get_stage_checkpoint(StageId::Execution)?
+            .map(|checkpoint| checkpoint.block_number);
+        let recovered = self
+            .get_stage_checkpoint(StageId::SenderRecovery)?
+            .map(|checkpoint| checkpoint.block_number);
+        let lookup_pruned = self
+            .get_prune_checkpoint(PrunePart::TransactionLookup)?
+            .map(|checkpoint| checkpoint.block_number);
+        let senders_pruned = self
+            .get_prune_checkpoint(PrunePart::SenderRecovery)?
+            .map(|checkpoint| checkpoint.block_number);
+        let is_above = |block, checkpoint: Option<BlockNumber>| {
+            checkpoint.map_or(true, |checkpoint| block > checkpoint)
+        };
+
+        let mut findings = Vec::new();
+        let mut transactions_cursor = self.cursor_read::<tables::Transactions>()?;
+        let mut hashes_cursor = self.cursor_read::<tables::TxHashNumber>()?;
+        let mut blocks_cursor = self.cursor_read::<tables::TransactionBlock>()?;
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        let mut senders_cursor = self.cursor_read::<tables::TxSenders>()?;
+
+        for entry in self.cursor_read::<tables::BlockBodyIndices>()?.walk_range(range)? {
+            if findings.len() >= limit {
+                break;
+            }
+
+            let (block, body) = entry?;
+            if body.is_empty() {
+                continue;
+            }
+            let tx_range = body.tx_num_range();
+
+            let mut next = body.first_tx_num();
+            for entry in transactions_cursor.walk_range(tx_range.clone())? {
+                if findings.len() >= limit {
+                    break;
+                }
+
+                let (tx_number, transaction) = entry?;
+                findings.extend(
+                    (next..tx_number)
+                        .take(limit - findings.len())
+                        .map(|tx_number| Inconsistency::MissingTransaction { block, tx_number }),
+                );
+                next = tx_number + 1;
+
+                if is_above(block, lookup_pruned) {
+                    let hash = transaction.hash();
+                    let got = hashes_cursor.seek_exact(hash)?.map(|(_, tx_number)| tx_number);
+                    if got != Some(tx_number) {
+                        findings.push(Inconsistency::TxHashNumberMismatch {
+                            block,
+                            tx_number,
+                            hash,
+                            got,
+                        });
+                    }
+                }
+            }
+            findings.extend(
+                (next..tx_range.end)
+                    .take(limit.saturating_sub(findings.len()))
+                    .map(|tx_number| Inconsistency::MissingTransaction { block, tx_number }),
+            );
+
+            // every shard covers the transactions up to its key
+            let mut tx_number = body.first_tx_num();
+            while tx_number <= body.last_tx_num() {
+                match blocks_cursor.seek(tx_number)? {
+                    Some((last, got)) if got == block => tx_number = last + 1,
+                    entry => {
+                        findings.push(Inconsistency::TransactionBlockMismatch {
+                            block,
+                            tx_number,
+                            got: entry.map(|(_, got)| got),
+                        });
+                        break;
+                    }
+                }
+            }
+
+            if is_above(block, executed) {
+                let receipt = receipts_cursor.seek(body.first_tx_num())?;
+                if let Some((tx_number, _)) = receipt.filter(|(n, _)| *n <= body.last_tx_num()) {
+                    findings.push(Inconsistency::ReceiptsPastCheckpoint {
+                        block,
+                        tx_number,
+                        checkpoint: executed,
+                    });
+                }
+            }
+
+            if is_above(block, senders_pruned) {
+                let senders = senders_cursor
+                    .walk_range(tx_range)?
+                    .try_fold(0, |senders, entry| entry.map(|_| senders + 1))?;
+                let unrecovered = senders == 0 && is_above(block, recovered);
+                if senders != body.tx_count && !unrecovered {
+                    findings.push(Inconsistency::SendersCountMismatch {
+                        block,
+                        tx_count: body.tx_count,
+                        senders,
+                    });
+                }
+            }
+        }
+
+        findings.truncate(limit);
+        Ok(findings)
+    }
+
+    /// Returns the [TableStats] of every table, sorted by table name.
+    ///
+    /// The entry and page counts come from the database statistics, the key and value sizes are
+    /// left out. Rows buffered by [DatabaseProvider::enable_write_batching] are not counted.
+    pub fn table_stats(&self) -> Result<Vec<TableStats>> {
+        self.collect_table_stats(false)
+    }
+
+    /// Returns the [TableStats] of every table like [DatabaseProvider::table_stats], including the
+    /// exact key and value sizes.
+    ///
+    /// The sizes are summed over every entry, which reads the whole database.
+    pub fn table_stats_with_sizes(&self) -> Result<Vec<TableStats>> {
+        self.collect_table_stats(true)
+    }
+
+    /// Collects the [TableStats] of every table, summing the key and value sizes if `sizes` is set.
+    fn collect_table_stats(&self, sizes: bool) -> Result<Vec<TableStats>> {
+        let viewer = TableStatsViewer { tx: &self.tx, sizes };
+        let mut stats =
+            Tables::ALL.iter().map(|table| table.view(&viewer)).collect::<Result<Vec<_>>>()?;
+        stats.sort_unstable_by_key(|stats| stats.table);
+        Ok(stats)
+    }
+
+    /// Returns the totals of [DatabaseProvider::table_stats] and the number of free pages of the
+    /// database.
+    pub fn db_stats(&self) -> Result<DatabaseStats> {
+        let mut stats =
+            DatabaseStats { freelist_pages: self.tx.freelist()? as u64, ..Default::default() };
+        for table in self.table_stats()? {
+            stats.entries += table.entries;
+            stats.pages += table.pages();
+            stats.page_size = table.page_size;
+        }
+        Ok(stats)
+    }
+
+    /// Returns the [Head] of the best block.
+    ///
+    /// If nothing was synced yet and the genesis block is missing, the genesis head of the chain
+    /// spec is returned.
+    pub fn head(&self) -> Result<Head> {
+        head_from_provider(self, &self.chain_spec)
+    }
+
+    /// Returns the fork id of the best block.
+    pub fn fork_id(&self) -> Result<ForkId> {
+        Ok(self.fork_filter(self.head()?)?.current())
+    }
+
+    /// Returns the fork id of the block with the given number and timestamp.
+    pub fn fork_id_at(&self, head: BlockNumber, timestamp: u64) -> Result<ForkId> {
+        let head = Head::builder(head).allow_unset_hash().timestamp(timestamp).build();
+        Ok(self.fork_filter(head)?.current())
+    }
+
+    /// Validates the fork id of a peer against the best block according to the `EIP-2124` rules.
+    pub fn validate_peer_fork_id(&self, remote: ForkId) -> Result<ForkIdValidation> {
+        Ok(self.fork_filter(self.head()?)?.validate(remote))
+    }
+
+    /// Returns the [ForkFilter] of the head on top of the stored genesis hash, the genesis hash of
+    /// the chain spec if the genesis block is missing.
+    fn fork_filter(&self, head: Head) -> Result<ForkFilter> {
+        let genesis_hash = match self.block_hash(0)? {
+            Some(hash) => hash,
+            None => self.chain_spec.genesis_hash(),
+        };
+        Ok(self.chain_spec.fork_filter_with_genesis(head, genesis_hash))
+    }
+
+    /// Returns the activation condition of the fork.
+    pub fn fork_activation(&self, fork: Hardfork) -> ForkCondition {
+        self.chain_spec.fork(fork)
+    }
+
+    /// Returns `true` if the block-activated fork is active at the block number.
+    pub fn is_fork_active_at_block(&self, fork: Hardfork, block_number: BlockNumber) -> bool {
+        self.chain_spec.is_fork_active_at_block(fork, block_number)
+    }
+
+    /// Returns `true` if the timestamp-activated fork is active at the timestamp.
+    pub fn is_fork_active_at_timestamp(&self, fork: Hardfork, timestamp: u64) -> bool {
+        self.chain_spec.is_fork_active_at_timestamp(fork, timestamp)
+    }
+
+    /// Returns the [SpecId] of the block, taking its total difficulty into account for the merge.
+    pub fn spec_id_at_block(&self, id: BlockHashOrNumber) -> Result<SpecId> {
+        Ok(self.env_header(id)?.spec_id)
+    }
+
+    /// Get the transaction by hash, falling back to scanning the transactions of the blocks in the
+    /// range if its [TxHashNumber](tables::TxHashNumber) entry could have been pruned.
+    ///
+    /// Only blocks up to the [PrunePart::TransactionLookup] checkpoint are scanned, the
+    /// transactions of later blocks still have their lookup entries. Scanned transactions are
+    /// hashed in parallel, the cost grows with the number of transactions in the range.
+    pub fn transaction_by_hash_in_range(
+        &self,
+        hash: TxHash,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Option<TransactionSigned>> {
+        if let Some(transaction) = self.lookup_transaction(hash)? {
+            return Ok(Some(transaction));
+        }
+
+        let Some(prune_height) = self
+            .get_prune_checkpoint(PrunePart::TransactionLookup)?
+            .map(|checkpoint| checkpoint.block_number)
+        else {
+            return Ok(None);
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&number) if number <= prune_height => Bound::Included(number),
+            Bound::Excluded(&number) if number <= prune_height => Bound::Excluded(number),
+            _ => Bound::Included(prune_height),
+        };
+        let bodies = self.block_body_indices_range((range.start_bound().cloned(), end))?;
+        let (Some((_, first)), Some((_, last))) = (bodies.first(), bodies.last()) else {
+            return Ok(None);
+        };
+
+        let mut cursor = self.cursor_read::<tables::Transactions>()?;
+        let mut walker =
+            cursor.walk_range(first.first_tx_num()..last.first_tx_num() + last.tx_count())?;
+        let mut chunk = Vec::with_capacity(TX_HASH_SCAN_CHUNK_SIZE);
+        loop {
+            chunk.clear();
+            for entry in walker.by_ref().take(TX_HASH_SCAN_CHUNK_SIZE) {
+                chunk.push(entry?.1);
+            }
+            if chunk.is_empty() {
+                return Ok(None);
+            }
+            if let Some(transaction) = chunk.par_iter().find_any(|tx| tx.hash() == hash) {
+                return Ok(Some(transaction.clone().with_hash()));
+            }
+        }
+    }
+
+    /// Get the transaction by hash, failing with [ProviderError::TransactionLookupPruned] instead
+    /// of returning `None` if it's not found and [TxHashNumber](tables::TxHashNumber) entries were
+    /// pruned, so unknown transactions can be told apart from ones that may have been pruned.
+    ///
+    /// This is what [TransactionsProvider::transaction_by_hash] does.
+    pub fn transaction_by_hash_checked(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        if let Some(transaction) = self.lookup_transaction(hash)? {
+            return Ok(Some(transaction));
+        }
+        match self.get_prune_checkpoint(PrunePart::TransactionLookup)? {
+            Some(checkpoint) => Err(ProviderError::TransactionLookupPruned {
+                hash,
+                prune_height: checkpoint.block_number,
+            }
+            .into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the transaction by hash through its [TxHashNumber](tables::TxHashNumber) entry alone.
+    fn lookup_transaction(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        let Some(id) = self.transaction_id(hash)? else { return Ok(None) };
+        self.transaction_by_id(id)
+    }
+
+    /// Get the stored, still compact-encoded transactions of each block in the range.
+    ///
+    /// Unlike [TransactionsProvider::transactions_by_block_range] the transactions are not decoded,
+    /// callers that only need a subset can inspect them with
+    /// [RawTransactionRef](reth_db::models::RawTransactionRef) and decode lazily.
+    pub fn transactions_by_block_range_raw(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, Vec<Bytes>)>> {
+        let mut results = Vec::new();
+        let mut tx_cursor = self.cursor_read::<RawTable<tables::Transactions>>()?;
+        for (number, body) in self.block_body_indices_range(range)? {
+            let tx_num_range = body.tx_num_range();
+            if tx_num_range.is_empty() {
+                results.push((number, Vec::new()));
+            } else {
+                let transactions = tx_cursor
+                    .walk_range(RawKey::new(tx_num_range.start)..RawKey::new(tx_num_range.end))?
+                    .map(|result| result.map(|(_, tx)| tx.into_raw_value().into()))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                results.push((number, transactions));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Get the transactions sent by the address within the range of transaction numbers, in
+    /// ascending order.
+    ///
+    /// Reads the [TransactionSenders](tables::TransactionSenders) index, only transactions
+    /// inserted by providers with [DatabaseProvider::with_sender_index] are found.
+    pub fn transactions_by_sender(
+        &self,
+        address: Address,
+        range: impl RangeBounds<TxNumber>,
+    ) -> Result<Vec<(TxNumber, TransactionSigned)>> {
+        let mut transactions = Vec::new();
+        for entry in self.walk_dup::<tables::TransactionSenders>(address, range)? {
+            let (_, SenderTxNumber(tx_num)) = entry?;
+            let transaction = self
+                .get::<tables::Transactions>(tx_num)?
+                .ok_or_else(|| missing_entry::<tables::Transactions>(tx_num))?;
+            transactions.push((tx_num, transaction.into()));
+        }
+        Ok(transactions)
+    }
+
+    /// Get the number of transactions sent by the address in the blocks below the block, which is
+    /// the nonce of an account that only sends transactions.
+    ///
+    /// The block has to have a body, or be the one after the last block with a body. Reads the
+    /// [TransactionSenders](tables::TransactionSenders) index, see
+    /// [DatabaseProvider::transactions_by_sender].
+    pub fn sender_transaction_count_before(
+        &self,
+        address: Address,
+        block: BlockNumber,
+    ) -> Result<u64> {
+        let end = match self.block_body_indices(block)? {
+            Some(body) => body.first_tx_num(),
+            None => block
+                .checked_sub(1)
+                .map(|parent| self.block_body_indices(parent))
+                .transpose()?
+                .flatten()
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(block))?
+                .next_tx_num(),
+        };
+
+        let mut count = 0;
+        let mut cursor = self.cursor_dup_read::<tables::TransactionSenders>()?;
+        for entry in cursor.walk_dup(Some(address), None)? {
+            let (_, SenderTxNumber(tx_num)) = entry?;
+            if tx_num >= end {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Get the transaction by hash together with its [TransactionMeta] and receipt in one pass.
+    ///
+    /// The receipt and the gas used and status of the meta are `None` if the receipt was pruned or
+    /// the block of the transaction was not executed yet.
+    pub fn transaction_by_hash_with_meta_and_receipt(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta, Option<Receipt>)>> {
+        let Some(tx_num) = self.transaction_id(hash)? else { return Ok(None) };
+        let Some(transaction) = self.transaction_by_id(tx_num)? else { return Ok(None) };
+        let Some(block_number) = self.transaction_block(tx_num)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(block_number)? else { return Ok(None) };
+        let Some(sealed_header) = self.sealed_header(block_number)? else { return Ok(None) };
+
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        let receipt = receipts_cursor.seek_exact(tx_num)?.map(|(_, receipt)| receipt);
+        // the gas used by the transaction is the difference to the cumulative gas used by the
+        // previous transaction in the block
+        let gas_used = match &receipt {
+            Some(receipt) if tx_num == body.first_tx_num() => Some(tx_gas_used(receipt, 0)),
+            Some(receipt) => receipts_cursor
+                .prev()?
+                .filter(|(prev_tx_num, _)| *prev_tx_num == tx_num - 1)
+                .map(|(_, prev_receipt)| tx_gas_used(receipt, prev_receipt.cumulative_gas_used)),
+            None => None,
+        };
+
+        let (header, block_hash) = sealed_header.split();
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: tx_num - body.first_tx_num(),
+            block_hash,
+            block_number,
+            base_fee: header.base_fee_per_gas,
+            gas_used,
+            success: receipt.as_ref().map(|receipt| receipt.success),
+        };
+        Ok(Some((transaction, meta, receipt)))
+    }
+
+    /// Returns the block of the marker persisted by the last fork choice update.
+    fn chain_marker(&self, marker: ChainMarker) -> Result<Option<BlockNumHash>> {
+        Ok(self.tx.get::<tables::ChainMarkers>(marker)?.map(Into::into))
+    }
+
+    /// Resolve the block tag to a canonical block number.
+    ///
+    /// Returns `None` if the tag doesn't point to a canonical block:
+    /// - `Latest` is `None` only if the database has no canonical headers.
+    /// - `Earliest` is the lowest block whose header and body are stored.
+    /// - `Safe` and `Finalized` are `None` if not set or no longer canonical.
+    /// - `Pending` is always `None`, the database has no pending block.
+    /// - Numbers above the canonical head are `None`.
+    pub fn block_number_for_tag(&self, tag: BlockNumberOrTag) -> Result<Option<BlockNumber>> {
+        let num_hash = match tag {
+            BlockNumberOrTag::Latest => {
+                return Ok(self.last_canonical_num_hash()?.map(|num_hash| num_hash.number))
+            }
+            BlockNumberOrTag::Earliest => {
+                let number = self.lowest_unpruned_block()?;
+                return Ok(self.block_hash(number)?.map(|_| number));
+            }
+            BlockNumberOrTag::Number(number) => {
+                return Ok(self.block_hash(number)?.map(|_| number))
+            }
+            BlockNumberOrTag::Pending => return Ok(None),
+            BlockNumberOrTag::Safe => self.safe_block_num_hash()?,
+            BlockNumberOrTag::Finalized => self.finalized_block_num_hash()?,
+        };
+
+        match num_hash {
+            Some(num_hash) if self.block_hash(num_hash.number)? == Some(num_hash.hash) => {
+                Ok(Some(num_hash.number))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the canonical block the tag resolves to, see
+    /// [block_number_for_tag](DatabaseProvider::block_number_for_tag).
+    pub fn block_by_tag(&self, tag: BlockNumberOrTag) -> Result<Option<Block>> {
+        match self.block_number_for_tag(tag)? {
+            Some(number) => self.block(number.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the lowest block whose header and body indices are stored.
+    ///
+    /// The prune parts only delete lookups, senders, receipts and history, the headers and bodies
+    /// of the blocks below their checkpoints are still stored.
+    fn lowest_unpruned_block(&self) -> Result<BlockNumber> {
+        let HistoryAvailability { headers, bodies, .. } = self.earliest_history_heights()?;
+        Ok(headers.max(bodies).unwrap_or_default())
+    }
+
+    /// Returns `true` if the hash belongs to a stored header, canonical or not.
+    ///
+    /// Only the [HeaderNumbers](tables::HeaderNumbers) entry is read, the header isn't decoded.
+    pub fn is_known_block_hash(&self, hash: BlockHash) -> Result<bool> {
+        Ok(self.get::<tables::HeaderNumbers>(hash)?.is_some())
+    }
+
+    /// Returns `true` if the body indices of the canonical block are stored.
+    ///
+    /// The transactions of the block aren't read.
+    pub fn has_block_body(&self, id: BlockHashOrNumber) -> Result<bool> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(false) };
+        Ok(self.block_body_indices(number)?.is_some())
+    }
+
+    /// Returns `true` if the canonical block was executed and its receipts are stored.
+    ///
+    /// Only the presence of the receipt of the last transaction is checked, without decoding it.
+    /// Blocks without transactions have no receipts and are checked against the `Execution`
+    /// stage checkpoint instead. Returns [ProviderError::ReceiptsPruned] if the receipt is missing
+    /// because the block is at or below the [PrunePart::Receipts] checkpoint.
+    pub fn has_receipts(&self, id: BlockHashOrNumber) -> Result<bool> {
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(false) };
+        let Some(body) = self.block_body_indices(number)? else { return Ok(false) };
+        self.has_block_receipts(number, &body)
+    }
+
+    /// Returns what the database holds of the block, to decide between skipping it, downloading
+    /// its body or executing it with one call.
+    ///
+    /// Like the single probes, only small index entries are read.
+    pub fn block_availability(&self, hash: BlockHash) -> Result<BlockAvailability> {
+        if !self.is_known_block_hash(hash)? {
+            return Ok(BlockAvailability::default());
+        }
+        let Some(number) = self.canonical_block_number(hash)? else {
+            return Ok(BlockAvailability { known: true, ..Default::default() });
+        };
+        let Some(body) = self.block_body_indices(number)? else {
+            return Ok(BlockAvailability { known: true, ..Default::default() });
+        };
+        let availability = BlockAvailability { known: true, body: true, ..Default::default() };
+        match self.has_block_receipts(number, &body) {
+            Ok(receipts) => Ok(BlockAvailability { receipts, ..availability }),
+            Err(reth_interfaces::Error::Provider(ProviderError::ReceiptsPruned { .. })) => {
+                Ok(BlockAvailability { receipts_pruned: true, ..availability })
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// See [DatabaseProvider::has_receipts].
+    fn has_block_receipts(
+        &self,
+        number: BlockNumber,
+        body: &StoredBlockBodyIndices,
+    ) -> Result<bool> {
+        if body.is_empty() {
+            return Ok(self
+                .get_stage_checkpoint(StageId::Execution)?
+                .map_or(false, |checkpoint| checkpoint.block_number >= number));
+        }
+        if self.tx.get::<RawTable<tables::Receipts>>(RawKey::new(body.last_tx_num()))?.is_some() {
+            return Ok(true)
+        }
+        match self.get_prune_checkpoint(PrunePart::Receipts)? {
+            Some(checkpoint) if number <= checkpoint.block_number => {
+                Err(ProviderError::ReceiptsPruned {
+                    block: number,
+                    prune_height: checkpoint.block_number,
+                }
+                .into())
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Get the stored receipts of the block by their transaction index within the block.
+    ///
+    /// Unlike [ReceiptProvider::receipts_by_block] this doesn't fail for blocks whose receipts were
+    /// pruned: receipts retained by [DatabaseProvider::prune_receipts] are returned and the pruned
+    /// ones are missing from the map. Returns `None` if the block is unknown or wasn't executed
+    /// yet.
+    pub fn receipts_by_block_sparse(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<BTreeMap<u64, Receipt>>> {
+        let Some(number) = self.convert_hash_or_number(block)? else { return Ok(None) };
+        let pruned = self
+            .get_prune_checkpoint(PrunePart::Receipts)?
+            .map_or(false, |checkpoint| number <= checkpoint.block_number);
+        if !pruned {
+            return Ok(self
+                .receipts_by_block(number.into())?
+                .map(|receipts| (0..).zip(receipts).collect()));
+        }
+
+        let Some(body) = self.block_body_indices(number)? else { return Ok(None) };
+        self.cursor_read::<tables::Receipts>()?
+            .walk_range(body.tx_num_range())?
+            .map(|entry| -> Result<_> {
+                let (tx_num, receipt) = entry?;
+                Ok((tx_num - body.first_tx_num(), receipt))
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    /// Returns the lowest block for which headers, bodies, receipts and senders are present.
+    ///
+    /// Receipts and senders are taken from their prune checkpoints, all other data and unpruned
+    /// receipts and senders are probed from the first entry of their tables.
+    pub fn earliest_history_heights(&self) -> Result<HistoryAvailability> {
+        Ok(HistoryAvailability {
+            headers: self.cursor_read::<tables::Headers>()?.first()?.map(|(number, _)| number),
+            bodies: self
+                .cursor_read::<tables::BlockBodyIndices>()?
+                .first()?
+                .map(|(number, _)| number),
+            receipts: self.earliest_tx_data_height::<tables::Receipts>(PrunePart::Receipts)?,
+            senders: self
+                .earliest_tx_data_height::<tables::TxSenders>(PrunePart::SenderRecovery)?,
+        })
+    }
+
+    /// Get the entry of the key, preferring a row buffered by
+    /// [DatabaseProvider::enable_write_batching].
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>> {
+        let batch = self.batch.lock();
+        let Some(batch) = batch.as_ref() else { return Ok(self.tx.get::<T>(key)?) };
+        let key = RawKey::new(key);
+        match batch.get::<T>(&key) {
+            Some(value) => Ok(Some(T::Value::decompress(value)?)),
+            None => Ok(self.tx.get::<RawTable<T>>(key)?.map(|value| value.value()).transpose()?),
+        }
+    }
+
+    /// Get the entries of the keys in their order, preferring rows buffered by
+    /// [DatabaseProvider::enable_write_batching].
+    fn get_many<T: Table>(&self, keys: &[T::Key]) -> Result<Vec<Option<T::Value>>> {
+        let batch = self.batch.lock();
+        let Some(batch) = batch.as_ref() else { return Ok(self.tx.get_many::<T>(keys)?) };
+
+        // only the keys without a buffered row are read from the transaction
+        let mut buffered = Vec::with_capacity(keys.len());
+        let mut missing = Vec::new();
+        for key in keys {
+            let key = RawKey::new(key.clone());
+            match batch.get::<T>(&key) {
+                Some(value) => buffered.push(Some(T::Value::decompress(value)?)),
+                None => {
+                    buffered.push(None);
+                    missing.push(key);
+                }
+            }
+        }
+        let mut stored = self.tx.get_many::<RawTable<T>>(&missing)?.into_iter();
+        buffered
+            .into_iter()
+            .map(|value| match value {
+                Some(value) => Ok(Some(value)),
+                None => Ok(stored.next().flatten().map(|value| value.value()).transpose()?),
+            })
+            .collect()
+    }
+
+    /// Get the last key of the table, including rows buffered by
+    /// [DatabaseProvider::enable_write_batching].
+    fn last_key<T: Table>(&self) -> Result<Option<T::Key>> {
+        let stored = self.tx.cursor_read::<RawTable<T>>()?.last()?.map(|(key, _)| key);
+        let batch = self.batch.lock();
+        let buffered = batch.as_ref().and_then(|batch| batch.last_key::<T>());
+        let last = match (stored.as_ref().map(RawKey::raw_key), buffered) {
+            (stored, Some(buffered)) if stored.map_or(true, |stored| buffered > stored) => buffered,
+            (Some(stored), _) => stored,
+            (None, _) => return Ok(None),
+        };
+        Ok(Some(T::Key::decode(last)?))
+    }
+
+    /// Writes the rows of the table buffered by [DatabaseProvider::enable_write_batching], so
+    /// cursors opened on the table see them.
+    fn flush_table<T: Table>(&self) -> std::result::Result<(), DatabaseError> {
+        if let Some(batch) = self.batch.lock().as_mut() {
+            batch.write_table::<T>(&self.tx)?;
+        }
+        Ok(())
+    }
+
+    /// Opens a read cursor on the table, after writing its buffered rows.
+    fn cursor_read<T: Table>(
+        &self,
+    ) -> std::result::Result<<TX as DbTxGAT<'_>>::Cursor<T>, DatabaseError> {
+        self.flush_table::<T>()?;
+        self.tx.cursor_read::<T>()
+    }
+
+    /// Opens a read cursor on the dup sorted table, after writing its buffered rows.
+    fn cursor_dup_read<T: DupSort>(
+        &self,
+    ) -> std::result::Result<<TX as DbTxGAT<'_>>::DupCursor<T>, DatabaseError> {
+        self.flush_table::<T>()?;
+        self.tx.cursor_dup_read::<T>()
+    }
+
+    /// Returns the lowest block with transaction data in the table `T`, the block after the prune
+    /// checkpoint of the part if the data was pruned.
+    fn earliest_tx_data_height<T: Table<Key = TxNumber>>(
+        &self,
+        part: PrunePart,
+    ) -> Result<Option<BlockNumber>> {
+        if let Some(checkpoint) = self.get_prune_checkpoint(part)? {
+            return Ok(Some(checkpoint.block_number + 1));
+        }
+        match self.cursor_read::<T>()?.first()? {
+            Some((tx_num, _)) => self.transaction_block(tx_num),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the ommers and withdrawals of the block with the given timestamp.
+    ///
+    /// Blocks from the Shanghai activation block on, inclusive, have no ommers and always have a
+    /// withdrawals list, even if empty. Earlier blocks have no withdrawals.
+    pub(crate) fn read_block_ommers_and_withdrawals(
+        &self,
+        number: BlockNumber,
+        timestamp: u64,
+    ) -> Result<(Vec<Header>, Option<Vec<Withdrawal>>)> {
+        if self.chain_spec.is_shanghai_activated_at_timestamp(timestamp) {
+            Ok((Vec::new(), self.withdrawals_by_block(number.into(), timestamp)?))
+        } else {
+            Ok((self.ommers(number.into())?.unwrap_or_default(), None))
+        }
+    }
+
+    /// Returns [ProviderError::ReceiptsPruned] if the receipts of the block were pruned.
+    fn ensure_receipts_not_pruned(&self, block: BlockNumber) -> Result<()> {
+        let earliest = self.earliest_tx_data_height::<tables::Receipts>(PrunePart::Receipts)?;
+        if let Some(earliest) = earliest.filter(|earliest| block < *earliest) {
+            return Err(ProviderError::ReceiptsPruned { block, prune_height: earliest - 1 }.into());
+        }
+        Ok(())
+    }
+
+    /// Returns [ProviderError::BlockPruned] if the block is below the lowest available block.
+    fn ensure_block_not_pruned(
+        &self,
+        block: BlockNumber,
+        earliest: Option<BlockNumber>,
+    ) -> Result<()> {
+        if let Some(earliest) = earliest.filter(|earliest| block < *earliest) {
+            return Err(ProviderError::BlockPruned { block, lowest_available: earliest }.into());
+        }
+        Ok(())
+    }
+
+    /// Returns [ProviderError::PreByzantiumReceipts] if Byzantium is not active at the block.
+    fn ensure_post_byzantium(&self, block_number: BlockNumber) -> Result<()> {
+        if !self.is_fork_active_at_block(Hardfork::Byzantium, block_number) {
+            return Err(ProviderError::PreByzantiumReceipts(block_number).into());
+        }
+        Ok(())
+    }
+}
+
+/// Recomputes the receipts root and logs bloom of the receipts and compares them against the
+/// header.
+fn verify_receipts(
+    block_number: BlockNumber,
+    header: &Header,
+    receipts: Vec<Receipt>,
+) -> ReceiptVerification {
+    let (receipts_root, logs_bloom) = calculate_receipt_root_and_logs_bloom(&receipts);
+    ReceiptVerification {
+        block_number,
+        receipts_root_mismatch: (receipts_root != header.receipts_root).then_some(receipts_root),
+        logs_bloom_mismatch: (logs_bloom != header.logs_bloom).then_some(logs_bloom),
+    }
+}
+
+/// Returns the gas used by the transaction of the receipt alone, given the cumulative gas used by
+/// the previous transaction of the block.
+///
+/// Inconsistent receipts with a decreasing cumulative gas used yield `0` instead of underflowing.
+fn tx_gas_used(receipt: &Receipt, previous_cumulative_gas_used: u64) -> u64 {
+    receipt.cumulative_gas_used.saturating_sub(previous_cumulative_gas_used)
+}
+
+/// Returns [ProviderError::BlockGasUsedMismatch] if the gas used according to the receipts of the
+/// block does not match the gas used of its header.
+fn ensure_block_gas_used(block_number: BlockNumber, header: &Header, got: u64) -> Result<()> {
+    if header.gas_used != got {
+        return Err(ProviderError::BlockGasUsedMismatch {
+            block_number,
+            expected: header.gas_used,
+            got,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
+    /// Commit database transaction, returning the rows written by the write helpers of the
+    /// provider.
+    ///
+    /// The statistics are recorded in the [DatabaseProviderMetrics] if the provider has metrics,
+    /// and the duration in the [CommitMonitor] if the provider has one.
+    ///
+    /// Rows buffered by [DatabaseProvider::enable_write_batching] are written first.
+    pub fn commit(self) -> Result<CommitStats> {
+        self.flush()?;
+        let Self { tx, metrics, commit_monitor, write_stats, .. } = self;
+        let start = Instant::now();
+        tx.commit()?;
+        let stats = CommitStats { tables: write_stats.into_inner(), duration: start.elapsed() };
+
+        if let Some(metrics) = metrics {
+            metrics.record_commit(&stats);
+        }
+        if let Some(monitor) = commit_monitor {
+            monitor.record(&stats);
+        }
+        Ok(stats)
+    }
+
+    /// Buffers the rows written by the block, header and body insertion helpers from now on, and
+    /// writes them in bulk once their encoded size exceeds `threshold` bytes, on
+    /// [DatabaseProvider::flush] or on commit.
+    ///
+    /// Rows past the last key of a table are written with append-mode cursors, which is much
+    /// cheaper than a put per row when inserting many blocks. Point lookups of headers, hashes,
+    /// total difficulties, body indices and transactions through the provider see buffered rows.
+    /// Opening a cursor through the provider writes the buffered rows of its table first, direct
+    /// reads and writes through [DatabaseProvider::tx_ref] have to flush the buffer before.
+    pub fn enable_write_batching(&self, threshold: usize) {
+        let mut batch = self.batch.lock();
+        if batch.is_none() {
+            *batch = Some(BatchWriter::new(threshold));
+        }
+    }
+
+    /// Puts the entry and records it in the write statistics.
+    ///
+    /// The entry is buffered if batching is enabled.
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<()> {
+        let (key, value) = (RawKey::new(key), RawValue::new(value));
+        self.record_put::<T>(&key, &value);
+        match self.batch.lock().as_mut() {
+            Some(batch) => {
+                batch.put::<T>(key, value);
+                if batch.is_full() {
+                    batch.write(&self.tx)?;
+                }
+            }
+            None => self.tx.put::<RawTable<T>>(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Puts a duplicate into the dup sorted table, directly into the transaction since the batch
+    /// of [DatabaseProvider::enable_write_batching] keeps a single value per key.
+    fn put_dup<T: DupSort>(&self, key: T::Key, value: T::Value) -> Result<()> {
+        let (key, value) = (RawKey::new(key), RawValue::new(value));
+        self.record_put::<T>(&key, &value);
+        Ok(self.tx.put::<RawTable<T>>(key, value)?)
+    }
+
+    /// Opens a write cursor on the table, after writing its buffered rows so they are neither
+    /// missed by the cursor nor written over its changes later.
+    fn cursor_write<T: Table>(
+        &self,
+    ) -> std::result::Result<<TX as DbTxMutGAT<'_>>::CursorMut<T>, DatabaseError> {
+        self.flush_table::<T>()?;
+        self.tx.cursor_write::<T>()
+    }
+
+    /// Opens a write cursor on the dup sorted table, after writing its buffered rows.
+    fn cursor_dup_write<T: DupSort>(
+        &self,
+    ) -> std::result::Result<<TX as DbTxMutGAT<'_>>::DupCursorMut<T>, DatabaseError> {
+        self.flush_table::<T>()?;
+        self.tx.cursor_dup_write::<T>()
+    }
+
+    /// Records a put of the encoded entry into the table in the write statistics.
+    fn record_put<T: Table>(&self, key: &RawKey<T::Key>, value: &RawValue<T::Value>) {
+        let mut write_stats = self.write_stats.lock();
+        let stats = write_stats.entry(T::NAME).or_default();
+        stats.puts += 1;
+        stats.bytes += (key.raw_key().len() + value.raw_value().len()) as u64;
+    }
+
+    /// Records deleted rows of the table in the write statistics.
+    fn record_deletes<T: Table>(&self, deletes: u64) {
+        if deletes > 0 {
+            self.write_stats.lock().entry(T::NAME).or_default().deletes += deletes;
+        }
+    }
+
+    /// Appends the entry with the cursor and records it in the write statistics.
+    fn append_entry<T: Table>(
+        &self,
+        cursor: &mut impl DbCursorRW<'_, RawTable<T>>,
+        key: T::Key,
+        value: T::Value,
+    ) -> Result<()> {
+        let (key, value) = (RawKey::new(key), RawValue::new(value));
+        self.record_put::<T>(&key, &value);
+        Ok(cursor.append(key, value)?)
+    }
+
+    /// Upserts the entry with the cursor and records it in the write statistics.
+    fn upsert_entry<T: Table>(
+        &self,
+        cursor: &mut impl DbCursorRW<'_, RawTable<T>>,
+        key: T::Key,
+        value: T::Value,
+    ) -> Result<()> {
+        let (key, value) = (RawKey::new(key), RawValue::new(value));
+        self.record_put::<T>(&key, &value);
+        Ok(cursor.upsert(key, value)?)
+    }
+
+    /// Appends the headers on top of the canonical tip, writing the headers, their canonical
+    /// hashes, hash to number entries and total difficulties.
+    ///
+    /// The batch has to be ascending and contiguous, and its first header has to be the child of
+    /// the last canonical header, or the genesis header if there is none. The whole batch is
+    /// validated before anything is written. The total difficulty is accumulated from the one of
+    /// the tip, and all tables but [HeaderNumbers](tables::HeaderNumbers) are written with
+    /// append-mode cursors.
+    pub fn append_sealed_headers(&self, headers: Vec<SealedHeader>) -> Result<()> {
+        let Some(first) = headers.first() else { return Ok(()) };
+        // the headers are appended directly
+        self.flush()?;
+
+        let mut td = match self.last_canonical_num_hash()? {
+            Some(tip) => {
+                if first.number != tip.number + 1 || first.parent_hash != tip.hash {
+                    return Err(ProviderError::DetachedHeaders {
+                        number: first.number,
+                        parent_hash: first.parent_hash,
+                        tip_number: tip.number,
+                        tip_hash: tip.hash,
+                    }
+                    .into());
+                }
+                self.header_td_by_number(tip.number)?
+                    .ok_or(ProviderError::TotalDifficultyNotFound { number: tip.number })?
+            }
+            None if first.number == 0 => U256::ZERO,
+            None => {
+                return Err(ProviderError::DetachedHeaders {
+                    number: first.number,
+                    parent_hash: first.parent_hash,
+                    tip_number: 0,
+                    tip_hash: H256::zero(),
+                }
+                .into())
+            }
+        };
+        for window in headers.windows(2) {
+            if window[1].number != window[0].number + 1 || window[1].parent_hash != window[0].hash()
+            {
+                return Err(ProviderError::NonContiguousHeaders { number: window[1].number }.into());
+            }
+        }
+
+        let mut headers_cursor = self.cursor_write::<RawTable<tables::Headers>>()?;
+        let mut canonical_cursor = self.cursor_write::<RawTable<tables::CanonicalHeaders>>()?;
+        let mut td_cursor = self.cursor_write::<RawTable<tables::HeaderTD>>()?;
+        for header in headers {
+            let (number, hash) = (header.number, header.hash());
+            td += header.difficulty;
+
+            // NOTE: HeaderNumbers are keyed by hash and can't be appended.
+            self.put::<tables::HeaderNumbers>(hash, number)?;
+            self.append_entry::<tables::CanonicalHeaders>(&mut canonical_cursor, number, hash)?;
+            self.append_entry::<tables::Headers>(&mut headers_cursor, number, header.unseal())?;
+            self.append_entry::<tables::HeaderTD>(&mut td_cursor, number, td.into())?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the block attaches to the stored chain before it is inserted.
+    ///
+    /// The block has to be the child of the canonical tip, or the genesis block of an empty
+    /// database, and its timestamp can't be before the one of its parent. With
+    /// [DatabaseProvider::allow_block_gaps] the block can be at any height, and is only checked
+    /// against its parent if that is stored.
+    pub fn validate_block_insertion(&self, block: &SealedBlock) -> Result<()> {
+        let number = block.number;
+        if !self.allow_block_gaps {
+            let expected = self.last_key::<tables::CanonicalHeaders>()?.map_or(0, |tip| tip + 1);
+            if number != expected {
+                return Err(ProviderError::NonContiguousBlock { number, expected }.into());
+            }
+        }
+
+        let Some(parent_number) = number.checked_sub(1) else { return Ok(()) };
+        // without gaps the parent is the canonical tip
+        let Some(parent_hash) = self.get::<tables::CanonicalHeaders>(parent_number)? else {
+            return Ok(());
+        };
+        if block.parent_hash != parent_hash {
+            return Err(ProviderError::ParentHashMismatch {
+                number,
+                expected: parent_hash,
+                got: block.parent_hash,
+            }
+            .into());
+        }
+        let parent = self
+            .header_by_number(parent_number)?
+            .ok_or(ProviderError::HeaderNotFound(parent_number.into()))?;
+        if block.timestamp < parent.timestamp {
+            return Err(ProviderError::TimestampBeforeParent {
+                number,
+                timestamp: block.timestamp,
+                parent_timestamp: parent.timestamp,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Inserts the bodies of blocks whose headers are already stored, without touching the header
+    /// tables.
+    ///
+    /// The bodies have to be ascending and the first one has to follow the last stored body. Each
+    /// body is checked against its stored header with [validate_body_against_header], the whole
+    /// batch is validated before anything is written. Transaction numbers continue from
+    /// the last stored transaction and senders are recovered from the signatures.
+    pub fn insert_block_bodies(&self, bodies: Vec<(BlockNumber, BlockBody)>) -> Result<()> {
+        let mut expected =
+            self.last_key::<tables::BlockBodyIndices>()?.map_or(0, |number| number + 1);
+        for (number, body) in &bodies {
+            if *number != expected {
+                return Err(ProviderError::DetachedBody { number: *number, expected }.into());
+            }
+            let header = self
+                .header_by_number(*number)?
+                .ok_or(ProviderError::HeaderNotFound((*number).into()))?;
+            self.verify_body_roots(&header, body)?;
+            expected += 1;
+        }
+
+        for (number, body) in bodies {
+            self.insert_body(number, body, None)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the withdrawals of a block from the Shanghai activation on.
+    ///
+    /// The stored header of the block decides whether Shanghai is active, withdrawals for earlier
+    /// blocks are rejected, and the withdrawals have to match its withdrawals root if it has one.
+    /// A row is written even if there are no withdrawals, so an empty list can be told apart from
+    /// withdrawals that were never written.
+    pub fn insert_block_withdrawals(
+        &self,
+        number: BlockNumber,
+        withdrawals: Vec<Withdrawal>,
+    ) -> Result<()> {
+        let header =
+            self.header_by_number(number)?.ok_or(ProviderError::HeaderNotFound(number.into()))?;
+        if !self.chain_spec.is_shanghai_activated_at_timestamp(header.timestamp) {
+            return Err(ProviderError::WithdrawalsBeforeShanghai {
+                number,
+                timestamp: header.timestamp,
+            }
+            .into());
+        }
+        let root = calculate_withdrawals_root(&withdrawals);
+        if header.withdrawals_root.map_or(false, |expected| expected != root) {
+            return Err(ProviderError::BodyRootMismatch {
+                number,
+                root: "Withdrawals",
+                expected: header.withdrawals_root,
+                got: Some(root),
+            }
+            .into());
+        }
+
+        self.put::<tables::BlockWithdrawals>(number, StoredBlockWithdrawals { withdrawals })
+    }
+
+    /// Writes an empty withdrawals row for every block in the range from the Shanghai activation
+    /// on that has a body but no row, and returns the number of rows written.
+    ///
+    /// Bodies without withdrawals used to be stored without a row, this normalizes them to what
+    /// [DatabaseProvider::insert_block_withdrawals] and the block insertion write now.
+    pub fn ensure_empty_withdrawal_rows(&self, range: RangeInclusive<BlockNumber>) -> Result<u64> {
+        let mut withdrawals_cursor = self.cursor_read::<tables::BlockWithdrawals>()?;
+        let mut headers_cursor = self.cursor_read::<tables::Headers>()?;
+        let mut written = 0;
+        for entry in self.cursor_read::<tables::BlockBodyIndices>()?.walk_range(range)? {
+            let (number, _) = entry?;
+            let Some((_, header)) = headers_cursor.seek_exact(number)? else { continue };
+            if self.chain_spec.is_shanghai_activated_at_timestamp(header.timestamp)
+                && withdrawals_cursor.seek_exact(number)?.is_none()
+            {
+                self.put::<tables::BlockWithdrawals>(number, StoredBlockWithdrawals::default())?;
+                written += 1;
+            }
         }
+        Ok(written)
     }
 
-    Ok(Vec::new())
-}
+    /// Compares the roots of the body against the header. Blocks from the Shanghai activation on
+    /// must have withdrawals, earlier blocks must not.
+    fn verify_body_roots(&self, header: &Header, body: &BlockBody) -> Result<()> {
+        let number = header.number;
+        let mismatch = |root, expected, got| -> Result<()> {
+            Err(ProviderError::BodyRootMismatch { number, root, expected, got }.into())
+        };
 
-impl<'this, TX: DbTx<'this>> DatabaseProvider<'this, TX> {
-    /// Creates a provider with an inner read-only transaction.
-    pub fn new(tx: TX, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { tx, chain_spec, _phantom_data: std::marker::PhantomData }
-    }
+        match validate_body_against_header(body, header) {
+            Ok(()) => {}
+            Err(BlockValidationError::TransactionsRootMismatch { got, expected }) => {
+                return mismatch("Transactions", Some(expected), Some(got))
+            }
+            Err(BlockValidationError::OmmersHashMismatch { got, expected }) => {
+                return mismatch("Ommers", Some(expected), Some(got))
+            }
+            Err(BlockValidationError::WithdrawalsRootMismatch { got, expected }) => {
+                return mismatch("Withdrawals", expected, got)
+            }
+            Err(error) => return Err(ProviderError::InvalidBody { number, error }.into()),
+        }
 
-    /// Consume `DbTx` or `DbTxMut`.
-    pub fn into_tx(self) -> TX {
-        self.tx
+        let shanghai = self.chain_spec.is_shanghai_activated_at_timestamp(header.timestamp);
+        if body.withdrawals.is_some() != shanghai {
+            let withdrawals_root = body.withdrawals.as_deref().map(calculate_withdrawals_root);
+            return mismatch("Withdrawals", header.withdrawals_root, withdrawals_root);
+        }
+        Ok(())
     }
 
-    /// Pass `DbTx` or `DbTxMut` mutable reference.
-    pub fn tx_mut(&mut self) -> &mut TX {
-        &mut self.tx
-    }
+    /// Writes the body of the block, assigning transaction numbers from the last stored
+    /// transaction on. Senders are recovered if they are not given for every transaction.
+    fn insert_body(
+        &self,
+        number: BlockNumber,
+        body: BlockBody,
+        senders: Option<Vec<Address>>,
+    ) -> Result<StoredBlockBodyIndices> {
+        // insert body ommers data
+        if !body.ommers.is_empty() {
+            self.put::<tables::BlockOmmers>(number, StoredBlockOmmers { ommers: body.ommers })?;
+        }
 
-    /// Pass `DbTx` or `DbTxMut` immutable reference.
-    pub fn tx_ref(&self) -> &TX {
-        &self.tx
-    }
+        let mut next_tx_num =
+            self.last_key::<tables::Transactions>()?.map(|n| n + 1).unwrap_or_default();
+        let first_tx_num = next_tx_num;
 
-    /// Return full table as Vec
-    pub fn table<T: Table>(&self) -> std::result::Result<Vec<KeyValue<T>>, DatabaseError>
-    where
-        T::Key: Default + Ord,
-    {
-        self.tx
-            .cursor_read::<T>()?
-            .walk(Some(T::Key::default()))?
-            .collect::<std::result::Result<Vec<_>, DatabaseError>>()
+        let tx_count = body.transactions.len() as u64;
+
+        let senders_len = senders.as_ref().map(|s| s.len());
+        let tx_iter = if Some(body.transactions.len()) == senders_len {
+            body.transactions.into_iter().zip(senders.unwrap().into_iter()).collect::<Vec<(_, _)>>()
+        } else {
+            // transactions with an invalid signature are stored with the zero address
+            let (senders, invalid) = TransactionSigned::recover_signers_parallel_or_default(
+                &body.transactions,
+                SENDER_RECOVERY_CHUNK_SIZE,
+            );
+            if let Some(index) = invalid {
+                warn!(
+                    target: "provider::db",
+                    number,
+                    index,
+                    "Storing transaction with an invalid signature with the zero sender"
+                );
+            }
+            body.transactions.into_iter().zip(senders).collect::<Vec<(_, _)>>()
+        };
+
+        for (transaction, sender) in tx_iter {
+            let hash = transaction.hash();
+            self.put::<tables::TxSenders>(next_tx_num, sender)?;
+            self.put::<tables::Transactions>(next_tx_num, transaction.into())?;
+            self.put::<tables::TxHashNumber>(hash, next_tx_num)?;
+            if self.index_transaction_senders {
+                self.put_dup::<tables::TransactionSenders>(sender, SenderTxNumber(next_tx_num))?;
+            }
+            next_tx_num += 1;
+        }
+
+        // a row is written even if there are no withdrawals, see
+        // [DatabaseProvider::insert_block_withdrawals]
+        if let Some(withdrawals) = body.withdrawals {
+            self.put::<tables::BlockWithdrawals>(number, StoredBlockWithdrawals { withdrawals })?;
+        }
+
+        let block_indices = StoredBlockBodyIndices { first_tx_num, tx_count };
+        self.put::<tables::BlockBodyIndices>(number, block_indices.clone())?;
+
+        if !block_indices.is_empty() {
+            self.put::<tables::TransactionBlock>(block_indices.last_tx_num(), number)?;
+        }
+
+        Ok(block_indices)
     }
-}
 
-impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
-    /// Commit database transaction.
-    pub fn commit(self) -> Result<bool> {
-        Ok(self.tx.commit()?)
+    /// Rewrites the total difficulties of the headers in the range, accumulated from their
+    /// difficulties on top of the total difficulty of the parent of the first block.
+    ///
+    /// Repairs holes and wrong values found by [DatabaseProvider::verify_td_consistency]. Fails
+    /// without writing anything if the total difficulty of the parent or a header in the range is
+    /// missing.
+    pub fn recompute_td_range(&self, range: RangeInclusive<BlockNumber>) -> Result<()> {
+        self.flush()?;
+        let (start, end) = range.into_inner();
+        if start > end {
+            return Ok(());
+        }
+        let mut td = match start.checked_sub(1) {
+            Some(parent) => self
+                .header_td_by_number(parent)?
+                .ok_or(ProviderError::TotalDifficultyNotFound { number: parent })?,
+            None => U256::ZERO,
+        };
+
+        let headers = self.headers_range(start..=end)?;
+        let mut next = start;
+        for header in &headers {
+            if header.number != next {
+                break;
+            }
+            next += 1;
+        }
+        if next <= end {
+            return Err(ProviderError::HeaderNotFound(next.into()).into());
+        }
+
+        for header in headers {
+            td += header.difficulty;
+            self.put::<tables::HeaderTD>(header.number, td.into())?;
+        }
+        Ok(())
     }
 
     // TODO(joshie) TEMPORARY should be moved to trait providers
@@ -216,12 +2976,13 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> Result<Vec<PostState>> {
+        self.flush()?;
         if range.is_empty() {
-            return Ok(Vec::new())
+            return Ok(Vec::new());
         }
 
         // We are not removing block meta as it is used to get block changesets.
-        let block_bodies = self.get_or_take::<tables::BlockBodyIndices, false>(range.clone())?;
+        let block_bodies = self.block_body_indices_range(range.clone())?;
 
         // get transaction receipts
         let from_transaction_num =
@@ -250,8 +3011,8 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         let mut block_states =
             BTreeMap::from_iter(block_bodies.iter().map(|(num, _)| (*num, PostState::default())));
 
-        let mut plain_accounts_cursor = self.tx.cursor_write::<tables::PlainAccountState>()?;
-        let mut plain_storage_cursor = self.tx.cursor_dup_write::<tables::PlainStorageState>()?;
+        let mut plain_accounts_cursor = self.cursor_write::<tables::PlainAccountState>()?;
+        let mut plain_storage_cursor = self.cursor_dup_write::<tables::PlainStorageState>()?;
 
         // add account changeset changes
         for (block_number, account_before) in account_changeset.into_iter().rev() {
@@ -379,7 +3140,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         range: impl RangeBounds<T::Key>,
     ) -> std::result::Result<Vec<KeyValue<T>>, DatabaseError> {
         if TAKE {
-            let mut cursor_write = self.tx.cursor_write::<T>()?;
+            let mut cursor_write = self.cursor_write::<T>()?;
             let mut walker = cursor_write.walk_range(range)?;
             let mut items = Vec::new();
             while let Some(i) = walker.next().transpose()? {
@@ -388,11 +3149,42 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
             }
             Ok(items)
         } else {
-            self.tx
-                .cursor_read::<T>()?
-                .walk_range(range)?
-                .collect::<std::result::Result<Vec<_>, _>>()
+            self.cursor_read::<T>()?.walk_range(range)?.collect::<std::result::Result<Vec<_>, _>>()
+        }
+    }
+
+    /// Deletes the [TransactionSenders](tables::TransactionSenders) entries of the transactions,
+    /// given with their senders.
+    ///
+    /// Nothing is read if the index is empty, e.g. because it was never enabled.
+    fn remove_sender_index(
+        &self,
+        senders: impl IntoIterator<Item = std::result::Result<(TxNumber, Address), DatabaseError>>,
+    ) -> Result<()> {
+        let mut cursor = self.cursor_dup_write::<tables::TransactionSenders>()?;
+        if cursor.first()?.is_none() {
+            return Ok(());
+        }
+
+        let mut deletes = 0;
+        for entry in senders {
+            let (tx_num, sender) = entry?;
+            if cursor.seek_by_key_subkey(sender, tx_num)? == Some(SenderTxNumber(tx_num)) {
+                cursor.delete_current()?;
+                deletes += 1;
+            }
         }
+        self.record_deletes::<tables::TransactionSenders>(deletes);
+        Ok(())
+    }
+
+    /// Deletes the [TransactionSenders](tables::TransactionSenders) entries of the transactions
+    /// starting at `first_tx_num`, with their senders read from [TxSenders](tables::TxSenders).
+    ///
+    /// Used by the stages unwinding transactions or their senders, which don't go through the
+    /// block removal of the provider.
+    pub fn unwind_sender_index(&self, first_tx_num: TxNumber) -> Result<()> {
+        self.remove_sender_index(self.cursor_read::<tables::TxSenders>()?.walk(Some(first_tx_num))?)
     }
 
     /// Get requested blocks transaction with signer
@@ -400,11 +3192,12 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         &self,
         range: impl RangeBounds<BlockNumber> + Clone,
     ) -> Result<Vec<(BlockNumber, Vec<TransactionSignedEcRecovered>)>> {
+        self.flush()?;
         // Raad range of block bodies to get all transactions id's of this range.
-        let block_bodies = self.get_or_take::<tables::BlockBodyIndices, false>(range)?;
+        let block_bodies = self.block_body_indices_range(range)?;
 
         if block_bodies.is_empty() {
-            return Ok(Vec::new())
+            return Ok(Vec::new());
         }
 
         // Compute the first and last tx ID in the range
@@ -413,7 +3206,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
 
         // If this is the case then all of the blocks in the range are empty
         if last_transaction < first_transaction {
-            return Ok(block_bodies.into_iter().map(|(n, _)| (n, Vec::new())).collect())
+            return Ok(block_bodies.into_iter().map(|(n, _)| (n, Vec::new())).collect());
         }
 
         // Get transactions and senders
@@ -428,13 +3221,16 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
 
         if TAKE {
             // Remove TxHashNumber
-            let mut tx_hash_cursor = self.tx.cursor_write::<tables::TxHashNumber>()?;
-            for (_, tx) in transactions.iter() {
-                if tx_hash_cursor.seek_exact(tx.hash())?.is_some() {
+            let mut tx_hash_cursor = self.cursor_write::<tables::TxHashNumber>()?;
+            for (id, tx) in transactions.iter() {
+                // the hash could be mapped to the same transaction in another block
+                if tx_hash_cursor.seek_exact(tx.hash())?.filter(|(_, num)| num == id).is_some() {
                     tx_hash_cursor.delete_current()?;
                 }
             }
 
+            self.remove_sender_index(senders.iter().map(|entry| Ok(*entry)))?;
+
             // Remove TransactionBlock index if there are transaction present
             if !transactions.is_empty() {
                 let tx_id_range = transactions.first().unwrap().0..=transactions.last().unwrap().0;
@@ -479,11 +3275,12 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         chain_spec: &ChainSpec,
         range: impl RangeBounds<BlockNumber> + Clone,
     ) -> Result<Vec<SealedBlockWithSenders>> {
+        self.flush()?;
         // For block we need Headers, Bodies, Uncles, withdrawals, Transactions, Signers
 
         let block_headers = self.get_or_take::<tables::Headers, TAKE>(range.clone())?;
         if block_headers.is_empty() {
-            return Ok(Vec::new())
+            return Ok(Vec::new());
         }
 
         let block_header_hashes =
@@ -498,7 +3295,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
             // rm HeaderTD
             self.get_or_take::<tables::HeaderTD, TAKE>(range)?;
             // rm HeaderNumbers
-            let mut header_number_cursor = self.tx.cursor_write::<tables::HeaderNumbers>()?;
+            let mut header_number_cursor = self.cursor_write::<tables::HeaderNumbers>()?;
             for (_, hash) in block_header_hashes.iter() {
                 if header_number_cursor.seek_exact(*hash)?.is_some() {
                     header_number_cursor.delete_current()?;
@@ -538,7 +3335,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
 
             // withdrawal can be missing
             let shanghai_is_active =
-                chain_spec.fork(Hardfork::Shanghai).active_at_timestamp(header.timestamp);
+                chain_spec.is_shanghai_activated_at_timestamp(header.timestamp);
             let mut withdrawals = Some(Vec::new());
             if shanghai_is_active {
                 if let Some((block_number, _)) = block_withdrawals.as_ref() {
@@ -560,6 +3357,462 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         Ok(blocks)
     }
 
+    /// Inserts the executed blocks together with their receipts and moves the `Bodies` and
+    /// `Execution` stage checkpoints to the last block.
+    ///
+    /// The receipts of each block are keyed by the transaction numbers assigned by
+    /// [BlockWriter::insert_block]. Mismatching lengths are rejected before anything is written.
+    /// Nothing is visible to other transactions until the provider is committed, so blocks and
+    /// receipts are persisted together or not at all.
+    pub fn append_blocks_with_receipts(
+        &self,
+        blocks: Vec<SealedBlockWithSenders>,
+        receipts: Vec<Vec<Receipt>>,
+    ) -> Result<()> {
+        self.flush()?;
+        if blocks.len() != receipts.len() {
+            return Err(ProviderError::ReceiptsLengthMismatch {
+                blocks: blocks.len(),
+                receipts: receipts.len(),
+            }
+            .into());
+        }
+        for (block, receipts) in blocks.iter().zip(&receipts) {
+            if block.body.len() != receipts.len() {
+                return Err(ProviderError::BlockReceiptsCountMismatch {
+                    block_number: block.number,
+                    transactions: block.body.len(),
+                    receipts: receipts.len(),
+                }
+                .into());
+            }
+        }
+        let Some(tip) = blocks.last().map(|block| block.number) else { return Ok(()) };
+
+        for (block, receipts) in blocks.into_iter().zip(receipts) {
+            let (block, senders) = block.into_components();
+            let body = self.insert_block(block, Some(senders))?;
+
+            let mut receipts_cursor = self.cursor_write::<RawTable<tables::Receipts>>()?;
+            for (tx_num, receipt) in body.tx_num_range().zip(receipts) {
+                let (key, value) = (RawKey::new(tx_num), RawValue::new(receipt));
+                self.record_put::<tables::Receipts>(&key, &value);
+                receipts_cursor.append(key, value)?;
+            }
+        }
+
+        for stage_id in [StageId::Bodies, StageId::Execution] {
+            let checkpoint = self.get_stage_checkpoint(stage_id)?.unwrap_or_default();
+            self.save_stage_checkpoint(
+                stage_id,
+                StageCheckpoint { block_number: tip, ..checkpoint },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the blocks in the range and returns them.
+    ///
+    /// The blocks are read before any entry is deleted, see
+    /// [DatabaseProvider::remove_block_range] for the deletion. Execution results and stage
+    /// checkpoints are left untouched.
+    pub fn take_block_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> Result<Vec<SealedBlockWithSenders>> {
+        let blocks = self.get_take_block_range::<false>(&self.chain_spec, range.clone())?;
+        self.remove_block_range(range)?;
+        Ok(blocks)
+    }
+
+    /// Removes the blocks in the range without reading them.
+    ///
+    /// Deletes the headers, hashes, total difficulties, ommers, withdrawals, transactions, senders,
+    /// receipts and all lookups of the blocks, including the sender index. The hash lookups go
+    /// first, they are derived from the data, and the body indices go last: they locate the
+    /// transactions of the blocks, so an interrupted removal never leaves transaction data that
+    /// can't be found anymore.
+    ///
+    /// Hash lookups are only deleted if they point to a removed transaction. If the range includes
+    /// the last stored body, all [TransactionBlock](tables::TransactionBlock) entries from the
+    /// first removed transaction on are deleted, including ones left behind past the tip.
+    pub fn remove_block_range(&self, range: RangeInclusive<BlockNumber>) -> Result<()> {
+        self.flush()?;
+        let bodies = self.block_body_indices_range(range.clone())?;
+        let tx_range = match (bodies.first(), bodies.last()) {
+            (Some((_, first)), Some((_, last))) if first.first_tx_num() < last.next_tx_num() => {
+                Some(first.first_tx_num()..=last.next_tx_num() - 1)
+            }
+            _ => None,
+        };
+        let removes_tip = self
+            .cursor_read::<tables::BlockBodyIndices>()?
+            .last()?
+            .map_or(false, |(number, _)| number <= *range.end());
+
+        if let Some(tx_range) = tx_range {
+            let mut hash_cursor = self.cursor_write::<tables::TxHashNumber>()?;
+            let mut deletes = 0;
+            for entry in self.cursor_read::<tables::Transactions>()?.walk_range(tx_range.clone())? {
+                let (tx_num, transaction) = entry?;
+                if hash_cursor
+                    .seek_exact(transaction.hash())?
+                    .filter(|(_, num)| *num == tx_num)
+                    .is_some()
+                {
+                    hash_cursor.delete_current()?;
+                    deletes += 1;
+                }
+            }
+            self.record_deletes::<tables::TxHashNumber>(deletes);
+            self.remove_sender_index(
+                self.cursor_read::<tables::TxSenders>()?.walk_range(tx_range.clone())?,
+            )?;
+            self.remove_range::<tables::Receipts>(tx_range.clone())?;
+            self.remove_range::<tables::TxSenders>(tx_range.clone())?;
+            self.remove_range::<tables::Transactions>(tx_range.clone())?;
+            self.remove_range::<tables::TransactionBlock>(tx_range)?;
+        }
+        if let Some((_, first)) = bodies.first().filter(|_| removes_tip) {
+            self.remove_range::<tables::TransactionBlock>(first.first_tx_num()..=TxNumber::MAX)?;
+        }
+
+        let mut numbers_cursor = self.cursor_write::<tables::HeaderNumbers>()?;
+        let mut deletes = 0;
+        for entry in self.cursor_read::<tables::CanonicalHeaders>()?.walk_range(range.clone())? {
+            if numbers_cursor.seek_exact(entry?.1)?.is_some() {
+                numbers_cursor.delete_current()?;
+                deletes += 1;
+            }
+        }
+        self.record_deletes::<tables::HeaderNumbers>(deletes);
+        self.remove_range::<tables::BlockOmmers>(range.clone())?;
+        self.remove_range::<tables::BlockWithdrawals>(range.clone())?;
+        self.remove_range::<tables::HeaderTD>(range.clone())?;
+        self.remove_range::<tables::CanonicalHeaders>(range.clone())?;
+        self.remove_range::<tables::Headers>(range.clone())?;
+        self.remove_range::<tables::BlockBodyIndices>(range)?;
+
+        Ok(())
+    }
+
+    /// Removes what is stored for a block that is not canonical, e.g. an invalid side-chain
+    /// block, and returns whether anything was removed.
+    ///
+    /// Returns an error if the block is canonical. Headers are stored by number, so the header,
+    /// total difficulty, ommers and withdrawals at the height are only deleted if they were
+    /// written for this block. Body indices and transactions are not touched, the transaction
+    /// numbers are shared with the canonical chain.
+    pub fn remove_block_by_hash(&self, hash: H256) -> Result<bool> {
+        self.flush()?;
+        let Some(number) = self.tx.get::<tables::HeaderNumbers>(hash)? else { return Ok(false) };
+        if self.tx.get::<tables::CanonicalHeaders>(number)? == Some(hash) {
+            return Err(ProviderError::CanonicalBlockRemoval { number, hash }.into());
+        }
+
+        self.tx.delete::<tables::HeaderNumbers>(hash, None)?;
+        self.record_deletes::<tables::HeaderNumbers>(1);
+        if self.tx.get::<tables::Headers>(number)?.map(|header| header.hash_slow()) == Some(hash) {
+            self.remove_range::<tables::BlockOmmers>(number..=number)?;
+            self.remove_range::<tables::BlockWithdrawals>(number..=number)?;
+            self.remove_range::<tables::HeaderTD>(number..=number)?;
+            self.remove_range::<tables::Headers>(number..=number)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Makes the headers the canonical chain, replacing the stored canonical blocks from the
+    /// first header that differs from them.
+    ///
+    /// The headers have to be ascending and contiguous, and the first header that is not
+    /// canonical yet has to be the child of a canonical block, the fork block. The blocks above
+    /// the fork block are removed with [DatabaseProvider::remove_block_range], including their
+    /// bodies, then the canonical hashes, headers and total difficulties of the new chain are
+    /// written and the last header becomes the canonical tip. The bodies of the new blocks have
+    /// to be inserted afterwards.
+    ///
+    /// Everything is written in the transaction of the provider, other transactions see the
+    /// switch only after the commit.
+    pub fn update_canonical_chain(&self, new_chain: Vec<SealedHeader>) -> Result<ReorgOutcome> {
+        self.flush()?;
+        for window in new_chain.windows(2) {
+            if window[1].number != window[0].number + 1 || window[1].parent_hash != window[0].hash()
+            {
+                return Err(ProviderError::NonContiguousHeaders { number: window[1].number }.into());
+            }
+        }
+
+        // skip the headers that are already canonical
+        let mut canonical_cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
+        let mut fork_index = 0;
+        for header in &new_chain {
+            if canonical_cursor.seek_exact(header.number)?.map(|(_, hash)| hash)
+                != Some(header.hash())
+            {
+                break;
+            }
+            fork_index += 1;
+        }
+        let fork_block = match new_chain.get(fork_index) {
+            Some(first) if first.number == 0 => None,
+            Some(first) => match canonical_cursor.seek_exact(first.number - 1)? {
+                Some((number, hash)) if hash == first.parent_hash => {
+                    Some(BlockNumHash::new(number, hash))
+                }
+                _ => {
+                    return Err(ProviderError::UnknownForkBlock {
+                        number: first.number,
+                        parent_hash: first.parent_hash,
+                    }
+                    .into())
+                }
+            },
+            None => match new_chain.last() {
+                Some(tip) => Some(tip.num_hash()),
+                None => return Ok(ReorgOutcome::default()),
+            },
+        };
+
+        // the replaced blocks are collected before they are removed
+        let first_changed = fork_block.map_or(0, |block| block.number + 1);
+        let changed = canonical_cursor
+            .walk_range(first_changed..)?
+            .map(|entry| entry.map(|(number, _)| number))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        // bodies can be left above the canonical tip, e.g. by an interrupted unwind
+        let last_removed =
+            changed.last().copied().max(self.last_key::<tables::BlockBodyIndices>()?);
+        if let Some(last_removed) = last_removed.filter(|last| *last >= first_changed) {
+            self.remove_block_range(first_changed..=last_removed)?;
+        }
+
+        let mut td = match fork_block {
+            Some(block) => self
+                .header_td_by_number(block.number)?
+                .ok_or(ProviderError::TotalDifficultyNotFound { number: block.number })?,
+            None => U256::ZERO,
+        };
+        let mut numbers_cursor = self.cursor_write::<RawTable<tables::HeaderNumbers>>()?;
+        let mut headers_cursor = self.cursor_write::<RawTable<tables::Headers>>()?;
+        let mut canonical_cursor = self.cursor_write::<RawTable<tables::CanonicalHeaders>>()?;
+        let mut td_cursor = self.cursor_write::<RawTable<tables::HeaderTD>>()?;
+        for header in new_chain.into_iter().skip(fork_index) {
+            let (number, hash) = (header.number, header.hash());
+            td += header.difficulty;
+
+            self.upsert_entry::<tables::HeaderNumbers>(&mut numbers_cursor, hash, number)?;
+            self.append_entry::<tables::CanonicalHeaders>(&mut canonical_cursor, number, hash)?;
+            self.append_entry::<tables::Headers>(&mut headers_cursor, number, header.unseal())?;
+            self.append_entry::<tables::HeaderTD>(&mut td_cursor, number, td.into())?;
+        }
+
+        Ok(ReorgOutcome { fork_block, changed })
+    }
+
+    /// Deletes the [TxHashNumber](tables::TxHashNumber) entries of the transactions of all blocks
+    /// up to and including `to_block`, at most `limit` entries per call.
+    ///
+    /// The entries are keyed by hash, so the hashes are recovered from the stored transactions.
+    /// Pruning resumes after the [PrunePart::TransactionLookup] checkpoint, which is saved for the
+    /// last fully pruned block before returning. A block that was only partially pruned is walked
+    /// again by the next call, its missing entries are skipped.
+    pub fn prune_transaction_lookup(
+        &self,
+        to_block: BlockNumber,
+        limit: usize,
+    ) -> Result<PruneProgress> {
+        self.flush()?;
+        let start_block = self
+            .get_prune_checkpoint(PrunePart::TransactionLookup)?
+            .map_or(0, |checkpoint| checkpoint.block_number + 1);
+        if start_block > to_block {
+            return Ok(PruneProgress::Finished);
+        }
+
+        let mut tx_cursor = self.cursor_read::<tables::Transactions>()?;
+        let mut hash_cursor = self.cursor_write::<tables::TxHashNumber>()?;
+        let mut deletes = 0;
+        let mut pruned_block = None;
+        let mut progress = PruneProgress::Finished;
+        'blocks: for (number, body) in self.block_body_indices_range(start_block..=to_block)? {
+            for entry in tx_cursor.walk_range(body.tx_num_range())? {
+                if deletes == limit {
+                    progress = PruneProgress::HasMoreData;
+                    break 'blocks;
+                }
+                let (tx_num, transaction) = entry?;
+                // the hash can be mapped to a later transaction, e.g. after an unwind
+                if hash_cursor
+                    .seek_exact(transaction.hash())?
+                    .filter(|(_, num)| *num == tx_num)
+                    .is_some()
+                {
+                    hash_cursor.delete_current()?;
+                    deletes += 1;
+                }
+            }
+            pruned_block = Some(number);
+        }
+        self.record_deletes::<tables::TxHashNumber>(deletes as u64);
+
+        if let Some(block_number) = pruned_block {
+            self.save_prune_checkpoint(
+                PrunePart::TransactionLookup,
+                PruneCheckpoint { block_number, prune_mode: PruneMode::Before(to_block + 1) },
+            )?;
+        }
+        Ok(progress)
+    }
+
+    /// Deletes the receipts of all blocks up to and including `to_block` that have no log emitted
+    /// by one of the `retain_log_addresses`, at most `limit` receipts per call.
+    ///
+    /// Pruning resumes after the [PrunePart::Receipts] checkpoint, which is saved for the last
+    /// fully pruned block before returning. Receipts of blocks up to the checkpoint are reported as
+    /// pruned by [ReceiptProvider::receipts_by_block], the retained ones can still be read by
+    /// transaction or with [DatabaseProvider::receipts_by_block_sparse].
+    pub fn prune_receipts(
+        &self,
+        to_block: BlockNumber,
+        retain_log_addresses: &[Address],
+        limit: usize,
+    ) -> Result<PruneProgress> {
+        self.flush()?;
+        let start_block = self
+            .get_prune_checkpoint(PrunePart::Receipts)?
+            .map_or(0, |checkpoint| checkpoint.block_number + 1);
+        if start_block > to_block {
+            return Ok(PruneProgress::Finished);
+        }
+
+        let mut cursor = self.cursor_write::<tables::Receipts>()?;
+        let mut deletes = 0;
+        let mut pruned_block = None;
+        let mut progress = PruneProgress::Finished;
+        'blocks: for (number, body) in self.block_body_indices_range(start_block..=to_block)? {
+            let mut walker = cursor.walk_range(body.tx_num_range())?;
+            while let Some((_, receipt)) = walker.next().transpose()? {
+                if deletes == limit {
+                    progress = PruneProgress::HasMoreData;
+                    break 'blocks;
+                }
+                if !receipt.logs.iter().any(|log| retain_log_addresses.contains(&log.address)) {
+                    walker.delete_current()?;
+                    deletes += 1;
+                }
+            }
+            pruned_block = Some(number);
+        }
+        self.record_deletes::<tables::Receipts>(deletes as u64);
+
+        if let Some(block_number) = pruned_block {
+            self.save_prune_checkpoint(
+                PrunePart::Receipts,
+                PruneCheckpoint { block_number, prune_mode: PruneMode::Before(to_block + 1) },
+            )?;
+        }
+        Ok(progress)
+    }
+
+    /// Rebuilds the [TxHashNumber](tables::TxHashNumber) entries of the transactions of the blocks
+    /// in the range from the stored transactions.
+    ///
+    /// Entries that map another hash to one of the transactions, e.g. left behind by an earlier
+    /// unwind whose transaction numbers were reused, are deleted. Finding them walks the whole
+    /// table, this is meant for repairs.
+    pub fn repair_tx_hash_index(&self, range: RangeInclusive<BlockNumber>) -> Result<()> {
+        self.flush()?;
+        let bodies = self.block_body_indices_range(range)?;
+        let (Some((_, first)), Some((_, last))) = (bodies.first(), bodies.last()) else {
+            return Ok(());
+        };
+        let tx_range = first.first_tx_num()..last.next_tx_num();
+        let hashes = self
+            .cursor_read::<tables::Transactions>()?
+            .walk_range(tx_range.clone())?
+            .map(|entry| entry.map(|(tx_num, transaction)| (tx_num, transaction.hash())))
+            .collect::<std::result::Result<HashMap<_, _>, _>>()?;
+
+        let mut cursor = self.cursor_write::<tables::TxHashNumber>()?;
+        let mut walker = cursor.walk(None)?;
+        let mut deletes = 0;
+        while let Some((hash, tx_num)) = walker.next().transpose()? {
+            if tx_range.contains(&tx_num) && hashes.get(&tx_num) != Some(&hash) {
+                walker.delete_current()?;
+                deletes += 1;
+            }
+        }
+        self.record_deletes::<tables::TxHashNumber>(deletes);
+
+        for (tx_num, hash) in hashes {
+            self.put::<tables::TxHashNumber>(hash, tx_num)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the [TransactionBlock](tables::TransactionBlock) shards of the blocks in the range
+    /// from their body indices, one entry per block with transactions at its last transaction.
+    ///
+    /// Entries within the transactions of the blocks that aren't the last transaction of a block,
+    /// e.g. left behind by an interrupted unwind, are deleted. Returns the number of entries that
+    /// were deleted or written, entries that are already correct are left untouched.
+    pub fn rebuild_transaction_block_index(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<u64> {
+        self.flush()?;
+        let bodies = self.block_body_indices_range(range)?;
+        let (Some((_, first)), Some((_, last))) = (bodies.first(), bodies.last()) else {
+            return Ok(0);
+        };
+        let tx_range = first.first_tx_num()..last.next_tx_num();
+        let mut expected = bodies
+            .iter()
+            .filter(|(_, body)| !body.is_empty())
+            .map(|(number, body)| (body.last_tx_num(), *number))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut cursor = self.cursor_write::<tables::TransactionBlock>()?;
+        let mut walker = cursor.walk_range(tx_range)?;
+        let mut deletes = 0;
+        while let Some((tx_num, number)) = walker.next().transpose()? {
+            match expected.get(&tx_num) {
+                Some(expected_number) if *expected_number == number => {
+                    expected.remove(&tx_num);
+                }
+                // overwritten below
+                Some(_) => {}
+                None => {
+                    walker.delete_current()?;
+                    deletes += 1;
+                }
+            }
+        }
+        self.record_deletes::<tables::TransactionBlock>(deletes);
+
+        let puts = expected.len() as u64;
+        for (tx_num, number) in expected {
+            self.put::<tables::TransactionBlock>(tx_num, number)?;
+        }
+        Ok(deletes + puts)
+    }
+
+    /// Deletes the entries of the table in the key range without decoding their values.
+    fn remove_range<T: Table>(&self, range: RangeInclusive<T::Key>) -> Result<()> {
+        let (start, end) = range.into_inner();
+        let mut cursor = self.cursor_write::<RawTable<T>>()?;
+        let mut walker = cursor.walk_range(RawKey::new(start)..=RawKey::new(end))?;
+        let mut deletes = 0;
+        while walker.next().transpose()?.is_some() {
+            walker.delete_current()?;
+            deletes += 1;
+        }
+        self.record_deletes::<T>(deletes);
+        Ok(())
+    }
+
     /// Unwind table by some number key.
     /// Returns number of rows unwound.
     ///
@@ -585,13 +3838,13 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         T: Table,
         F: FnMut(T::Key) -> u64,
     {
-        let mut cursor = self.tx.cursor_write::<T>()?;
+        let mut cursor = self.cursor_write::<T>()?;
         let mut reverse_walker = cursor.walk_back(None)?;
         let mut deleted = 0;
 
         while let Some(Ok((entry_key, _))) = reverse_walker.next() {
             if selector(entry_key.clone()) <= key {
-                break
+                break;
             }
             reverse_walker.delete_current()?;
             deleted += 1;
@@ -609,7 +3862,8 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         T1: Table,
         T2: Table<Key = T1::Value>,
     {
-        let mut cursor = self.tx.cursor_write::<T1>()?;
+        self.flush()?;
+        let mut cursor = self.cursor_write::<T1>()?;
         let mut walker = cursor.walk(Some(start_at))?;
         while let Some((_, value)) = walker.next().transpose()? {
             self.tx.delete::<T2>(value, None)?;
@@ -617,19 +3871,57 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         Ok(())
     }
 
+    /// Persist the finalized block of a fork choice update.
+    ///
+    /// The block must be canonical and the finalized block can never move backwards, otherwise
+    /// [ProviderError::FinalizedBlockMovedBackwards] is returned.
+    pub fn set_finalized_block_num_hash(&self, num_hash: BlockNumHash) -> Result<()> {
+        if let Some(current) = self.finalized_block_num_hash()? {
+            if num_hash.number < current.number {
+                return Err(ProviderError::FinalizedBlockMovedBackwards {
+                    current: current.number,
+                    new: num_hash.number,
+                }
+                .into());
+            }
+        }
+        self.save_chain_marker(ChainMarker::Finalized, num_hash)
+    }
+
+    /// Persist the safe block of a fork choice update.
+    ///
+    /// The block must be canonical. Unlike the finalized block, the safe block can move backwards
+    /// when it is reorged.
+    pub fn set_safe_block_num_hash(&self, num_hash: BlockNumHash) -> Result<()> {
+        self.save_chain_marker(ChainMarker::Safe, num_hash)
+    }
+
+    /// Returns [ProviderError::NonCanonicalBlock] if the block is not canonical.
+    fn save_chain_marker(&self, marker: ChainMarker, num_hash: BlockNumHash) -> Result<()> {
+        if self.block_hash(num_hash.number)? != Some(num_hash.hash) {
+            return Err(ProviderError::NonCanonicalBlock {
+                number: num_hash.number,
+                hash: num_hash.hash,
+            }
+            .into());
+        }
+        Ok(self.tx.put::<tables::ChainMarkers>(marker, num_hash.into())?)
+    }
+
     /// Load shard and remove it. If list is empty, last shard was full or
     /// there are no shards at all.
     fn take_shard<T>(&self, key: T::Key) -> Result<Vec<u64>>
     where
         T: Table<Value = BlockNumberList>,
     {
-        let mut cursor = self.tx.cursor_read::<T>()?;
+        self.flush()?;
+        let mut cursor = self.cursor_read::<T>()?;
         let shard = cursor.seek_exact(key)?;
         if let Some((shard_key, list)) = shard {
             // delete old shard so new one can be inserted.
             self.tx.delete::<T>(shard_key, None)?;
             let list = list.iter(0).map(|i| i as u64).collect::<Vec<_>>();
-            return Ok(list)
+            return Ok(list);
         }
         Ok(Vec::new())
     }
@@ -650,6 +3942,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> DatabaseProvider<'this, TX> {
         P: Copy,
         T: Table<Value = BlockNumberList>,
     {
+        self.flush()?;
         for (partial_key, indices) in index_updates {
             let last_shard = self.take_shard::<T>(sharded_key_factory(partial_key, u64::MAX))?;
             // chunk indices and insert them in shards of N size.
@@ -689,8 +3982,7 @@ impl<'this, TX: DbTx<'this>> AccountExtReader for DatabaseProvider<'this, TX> {
         &self,
         range: impl RangeBounds<BlockNumber>,
     ) -> Result<BTreeSet<Address>> {
-        self.tx
-            .cursor_read::<tables::AccountChangeSet>()?
+        self.cursor_read::<tables::AccountChangeSet>()?
             .walk_range(range)?
             .map(|entry| {
                 entry.map(|(_, account_before)| account_before.address).map_err(Into::into)
@@ -702,7 +3994,7 @@ impl<'this, TX: DbTx<'this>> AccountExtReader for DatabaseProvider<'this, TX> {
         &self,
         iter: impl IntoIterator<Item = Address>,
     ) -> Result<Vec<(Address, Option<Account>)>> {
-        let mut plain_accounts = self.tx.cursor_read::<tables::PlainAccountState>()?;
+        let mut plain_accounts = self.cursor_read::<tables::PlainAccountState>()?;
         Ok(iter
             .into_iter()
             .map(|address| plain_accounts.seek_exact(address).map(|a| (address, a.map(|(_, v)| v))))
@@ -713,7 +4005,7 @@ impl<'this, TX: DbTx<'this>> AccountExtReader for DatabaseProvider<'this, TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> Result<BTreeMap<Address, Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::AccountChangeSet>()?;
+        let mut changeset_cursor = self.cursor_read::<tables::AccountChangeSet>()?;
 
         let account_transitions = changeset_cursor.walk_range(range)?.try_fold(
             BTreeMap::new(),
@@ -729,6 +4021,10 @@ impl<'this, TX: DbTx<'this>> AccountExtReader for DatabaseProvider<'this, TX> {
 }
 
 impl<'this, TX: DbTx<'this>> HeaderProvider for DatabaseProvider<'this, TX> {
+    fn is_known(&self, block_hash: &BlockHash) -> Result<bool> {
+        self.is_known_block_hash(*block_hash)
+    }
+
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
         if let Some(num) = self.block_number(*block_hash)? {
             Ok(self.header_by_number(num)?)
@@ -738,7 +4034,7 @@ impl<'this, TX: DbTx<'this>> HeaderProvider for DatabaseProvider<'this, TX> {
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
-        Ok(self.tx.get::<tables::Headers>(num)?)
+        self.get::<tables::Headers>(num)
     }
 
     fn header_td(&self, block_hash: &BlockHash) -> Result<Option<U256>> {
@@ -753,55 +4049,73 @@ impl<'this, TX: DbTx<'this>> HeaderProvider for DatabaseProvider<'this, TX> {
         if let Some(td) = self.chain_spec.final_paris_total_difficulty(number) {
             // if this block is higher than the final paris(merge) block, return the final paris
             // difficulty
-            return Ok(Some(td))
+            return Ok(Some(td));
         }
 
-        Ok(self.tx.get::<tables::HeaderTD>(number)?.map(|td| td.0))
+        Ok(self.get::<tables::HeaderTD>(number)?.map(|td| td.0))
     }
 
     fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
-        let mut cursor = self.tx.cursor_read::<tables::Headers>()?;
-        cursor
-            .walk_range(range)?
-            .map(|result| result.map(|(_, header)| header).map_err(Into::into))
-            .collect::<Result<Vec<_>>>()
+        self.record(Operation::HeadersRange, || {
+            let mut cursor = self.cursor_read::<tables::Headers>()?;
+            cursor
+                .walk_range(range)?
+                .map(|result| result.map(|(_, header)| header).map_err(Into::into))
+                .collect::<Result<Vec<_>>>()
+        })
     }
 
     fn sealed_headers_range(
         &self,
         range: impl RangeBounds<BlockNumber>,
     ) -> Result<Vec<SealedHeader>> {
-        let mut headers = vec![];
-        for entry in self.tx.cursor_read::<tables::Headers>()?.walk_range(range)? {
-            let (number, header) = entry?;
-            let hash = self
-                .block_hash(number)?
-                .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
-            headers.push(header.seal(hash));
-        }
-        Ok(headers)
+        self.record(Operation::SealedHeadersRange, || {
+            let range = (range.start_bound().cloned(), range.end_bound().cloned());
+            let mut hashes_cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
+            let mut hashes = hashes_cursor.walk_range(range)?;
+
+            // walk the canonical hashes in lockstep with the headers instead of a lookup per header
+            let mut headers = vec![];
+            for entry in self.cursor_read::<tables::Headers>()?.walk_range(range)? {
+                let (number, header) = entry?;
+                headers.push(seal_header(number, header, hashes.next().transpose()?)?);
+            }
+            Ok(headers)
+        })
     }
 
     fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
-        if let Some(header) = self.header_by_number(number)? {
-            let hash = self
-                .block_hash(number)?
-                .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
-            Ok(Some(header.seal(hash)))
-        } else {
-            Ok(None)
+        let Some(header) = self.header_by_number(number)? else { return Ok(None) };
+        match self.block_hash(number)? {
+            Some(hash) => Ok(Some(header.seal(hash))),
+            // a header above the canonical tip, e.g. left behind by an interrupted unwind, is not
+            // part of the chain
+            None if number > self.last_block_number()? => Ok(None),
+            None => Err(ProviderError::HeaderNotFound(number.into()).into()),
         }
     }
+
+    fn sealed_header_with_td(&self, id: BlockHashOrNumber) -> Result<Option<(SealedHeader, U256)>> {
+        // resolve the number once, everything else is keyed by it
+        let Some(number) = self.convert_hash_or_number(id)? else { return Ok(None) };
+        let Some(header) = self.sealed_header(number)? else { return Ok(None) };
+        let td = self
+            .header_td_by_number(number)?
+            .ok_or(ProviderError::TotalDifficultyNotFound { number })?;
+        Ok(Some((header, td)))
+    }
 }
 
 impl<'this, TX: DbTx<'this>> BlockHashReader for DatabaseProvider<'this, TX> {
     fn block_hash(&self, number: u64) -> Result<Option<H256>> {
-        Ok(self.tx.get::<tables::CanonicalHeaders>(number)?)
+        self.get::<tables::CanonicalHeaders>(number)
     }
 
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
-        let range = start..end;
-        let mut cursor = self.tx.cursor_read::<tables::CanonicalHeaders>()?;
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
+        let mut cursor = self.cursor_read::<tables::CanonicalHeaders>()?;
         cursor
             .walk_range(range)?
             .map(|result| result.map(|(_, hash)| hash).map_err(Into::into))
@@ -812,11 +4126,16 @@ impl<'this, TX: DbTx<'this>> BlockHashReader for DatabaseProvider<'this, TX> {
 impl<'this, TX: DbTx<'this>> BlockNumReader for DatabaseProvider<'this, TX> {
     fn chain_info(&self) -> Result<ChainInfo> {
         let best_number = self.best_block_number()?;
-        let best_hash = self.block_hash(best_number)?.unwrap_or_default();
+        // the finish checkpoint can be ahead of the canonical headers after an interrupted unwind,
+        // the zero hash must not be reported as the chain head in that case
+        let best_hash = self
+            .block_hash(best_number)?
+            .ok_or(ProviderError::HeaderNotFound(best_number.into()))?;
         Ok(ChainInfo { best_hash, best_number })
     }
 
     fn best_block_number(&self) -> Result<BlockNumber> {
+        // no finish checkpoint means that nothing past genesis was synced yet
         Ok(self
             .get_stage_checkpoint(StageId::Finish)?
             .map(|checkpoint| checkpoint.block_number)
@@ -824,11 +4143,37 @@ impl<'this, TX: DbTx<'this>> BlockNumReader for DatabaseProvider<'this, TX> {
     }
 
     fn last_block_number(&self) -> Result<BlockNumber> {
-        Ok(self.tx.cursor_read::<tables::CanonicalHeaders>()?.last()?.unwrap_or_default().0)
+        Ok(self.cursor_read::<tables::CanonicalHeaders>()?.last()?.unwrap_or_default().0)
+    }
+
+    fn last_canonical_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        Ok(self
+            .cursor_read::<tables::CanonicalHeaders>()?
+            .last()?
+            .map(|(number, hash)| BlockNumHash::new(number, hash)))
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
-        Ok(self.tx.get::<tables::HeaderNumbers>(hash)?)
+        self.get::<tables::HeaderNumbers>(hash)
+    }
+
+    fn block_numbers(&self, hashes: &[H256]) -> Result<Vec<Option<BlockNumber>>> {
+        self.get_many::<tables::HeaderNumbers>(hashes)
+    }
+}
+
+impl<'this, TX: DbTx<'this>> BlockIdReader for DatabaseProvider<'this, TX> {
+    fn pending_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        // the database has no pending block
+        Ok(None)
+    }
+
+    fn safe_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.chain_marker(ChainMarker::Safe)
+    }
+
+    fn finalized_block_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.chain_marker(ChainMarker::Finalized)
     }
 }
 
@@ -842,19 +4187,24 @@ impl<'this, TX: DbTx<'this>> BlockReader for DatabaseProvider<'this, TX> {
     }
 
     fn block(&self, id: BlockHashOrNumber) -> Result<Option<Block>> {
-        if let Some(number) = self.convert_hash_or_number(id)? {
-            if let Some(header) = self.header_by_number(number)? {
-                let withdrawals = self.withdrawals_by_block(number.into(), header.timestamp)?;
-                let ommers = self.ommers(number.into())?.unwrap_or_default();
-                let transactions = self
-                    .transactions_by_block(number.into())?
-                    .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+        self.record(Operation::Block, || {
+            if let Some(number) = self.convert_hash_or_number_checked(id)? {
+                let Some(header) = self.header_by_number(number)? else {
+                    self.ensure_block_not_pruned(number, self.earliest_history_heights()?.headers)?;
+                    return Ok(None);
+                };
+                let (ommers, withdrawals) =
+                    self.read_block_ommers_and_withdrawals(number, header.timestamp)?;
+                let Some(transactions) = self.transactions_by_block(number.into())? else {
+                    self.ensure_block_not_pruned(number, self.earliest_history_heights()?.bodies)?;
+                    return Err(ProviderError::BlockBodyIndicesNotFound(number).into());
+                };
 
-                return Ok(Some(Block { header, body: transactions, ommers, withdrawals }))
+                return Ok(Some(Block { header, body: transactions, ommers, withdrawals }));
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
     }
 
     fn pending_block(&self) -> Result<Option<SealedBlock>> {
@@ -866,22 +4216,32 @@ impl<'this, TX: DbTx<'this>> BlockReader for DatabaseProvider<'this, TX> {
     }
 
     fn ommers(&self, id: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
-        if let Some(number) = self.convert_hash_or_number(id)? {
+        if let Some(number) = self.convert_hash_or_number_checked(id)? {
             // If the Paris (Merge) hardfork block is known and block is after it, return empty
             // ommers.
             if self.chain_spec.final_paris_total_difficulty(number).is_some() {
-                return Ok(Some(Vec::new()))
+                return Ok(Some(Vec::new()));
             }
 
             let ommers = self.tx.get::<tables::BlockOmmers>(number)?.map(|o| o.ommers);
-            return Ok(ommers)
+            return Ok(ommers);
         }
 
         Ok(None)
     }
 
     fn block_body_indices(&self, num: u64) -> Result<Option<StoredBlockBodyIndices>> {
-        Ok(self.tx.get::<tables::BlockBodyIndices>(num)?)
+        self.get::<tables::BlockBodyIndices>(num)
+    }
+
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        Ok(self
+            .cursor_read::<tables::BlockBodyIndices>()?
+            .walk_range(range)?
+            .collect::<std::result::Result<Vec<_>, _>>()?)
     }
 
     /// Returns the block with senders with matching number from database.
@@ -889,14 +4249,17 @@ impl<'this, TX: DbTx<'this>> BlockReader for DatabaseProvider<'this, TX> {
     /// **NOTE: The transactions have invalid hashes, since they would need to be calculated on the
     /// spot, and we want fast querying.**
     ///
+    /// If the senders of the block are pruned or not recovered yet, they are recovered from the
+    /// signatures in parallel.
+    ///
     /// Returns `None` if block is not found.
     fn block_with_senders(&self, block_number: BlockNumber) -> Result<Option<BlockWithSenders>> {
         let header = self
             .header_by_number(block_number)?
             .ok_or_else(|| ProviderError::HeaderNotFound(block_number.into()))?;
 
-        let ommers = self.ommers(block_number.into())?.unwrap_or_default();
-        let withdrawals = self.withdrawals_by_block(block_number.into(), header.timestamp)?;
+        let (ommers, withdrawals) =
+            self.read_block_ommers_and_withdrawals(block_number, header.timestamp)?;
 
         // Get the block body
         let body = self
@@ -921,7 +4284,15 @@ impl<'this, TX: DbTx<'this>> BlockReader for DatabaseProvider<'this, TX> {
                     transaction: tx.transaction,
                 }
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        // the senders are pruned or not recovered yet
+        let senders = if senders.len() == body.len() {
+            senders
+        } else {
+            TransactionSigned::recover_signers_parallel(&body, SENDER_RECOVERY_CHUNK_SIZE)
+                .ok_or(ProviderError::SenderRecoveryFailed(block_number))?
+        };
 
         Ok(Some(Block { header, body, ommers, withdrawals }.with_senders(senders)))
     }
@@ -929,61 +4300,64 @@ impl<'this, TX: DbTx<'this>> BlockReader for DatabaseProvider<'this, TX> {
 
 impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX> {
     fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>> {
-        Ok(self.tx.get::<tables::TxHashNumber>(tx_hash)?)
+        self.get::<tables::TxHashNumber>(tx_hash)
     }
 
     fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
-        Ok(self.tx.get::<tables::Transactions>(id)?.map(Into::into))
+        Ok(self.transaction_by_id_no_hash(id)?.map(Into::into))
+    }
+
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        self.get::<tables::Transactions>(id)
     }
 
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
-        if let Some(id) = self.transaction_id(hash)? {
-            Ok(self.transaction_by_id(id)?)
-        } else {
-            Ok(None)
-        }
-        .map(|tx| tx.map(Into::into))
+        self.transaction_by_hash_checked(hash)
     }
 
     fn transaction_by_hash_with_meta(
         &self,
         tx_hash: TxHash,
     ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
-        let mut transaction_cursor = self.tx.cursor_read::<tables::TransactionBlock>()?;
-        if let Some(transaction_id) = self.transaction_id(tx_hash)? {
-            if let Some(transaction) = self.transaction_by_id(transaction_id)? {
-                if let Some(block_number) =
-                    transaction_cursor.seek(transaction_id).map(|b| b.map(|(_, bn)| bn))?
-                {
-                    if let Some(sealed_header) = self.sealed_header(block_number)? {
-                        let (header, block_hash) = sealed_header.split();
-                        if let Some(block_body) = self.block_body_indices(block_number)? {
-                            // the index of the tx in the block is the offset:
-                            // len([start..tx_id])
-                            // SAFETY: `transaction_id` is always `>=` the block's first
-                            // index
-                            let index = transaction_id - block_body.first_tx_num();
-
-                            let meta = TransactionMeta {
-                                tx_hash,
-                                index,
-                                block_hash,
-                                block_number,
-                                base_fee: header.base_fee_per_gas,
-                            };
-
-                            return Ok(Some((transaction, meta)))
+        self.record(Operation::TransactionByHashWithMeta, || {
+            let mut transaction_cursor = self.cursor_read::<tables::TransactionBlock>()?;
+            if let Some(transaction_id) = self.transaction_id(tx_hash)? {
+                if let Some(transaction) = self.transaction_by_id(transaction_id)? {
+                    if let Some(block_number) =
+                        transaction_cursor.seek(transaction_id).map(|b| b.map(|(_, bn)| bn))?
+                    {
+                        if let Some(sealed_header) = self.sealed_header(block_number)? {
+                            let (header, block_hash) = sealed_header.split();
+                            if let Some(block_body) = self.block_body_indices(block_number)? {
+                                // the index of the tx in the block is the offset:
+                                // len([start..tx_id])
+                                // SAFETY: `transaction_id` is always `>=` the block's first
+                                // index
+                                let index = transaction_id - block_body.first_tx_num();
+
+                                let meta = TransactionMeta {
+                                    tx_hash,
+                                    index,
+                                    block_hash,
+                                    block_number,
+                                    base_fee: header.base_fee_per_gas,
+                                    gas_used: None,
+                                    success: None,
+                                };
+
+                                return Ok(Some((transaction, meta)));
+                            }
                         }
                     }
                 }
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
     }
 
     fn transaction_block(&self, id: TxNumber) -> Result<Option<BlockNumber>> {
-        let mut cursor = self.tx.cursor_read::<tables::TransactionBlock>()?;
+        let mut cursor = self.cursor_read::<tables::TransactionBlock>()?;
         Ok(cursor.seek(id)?.map(|(_, bn)| bn))
     }
 
@@ -991,7 +4365,16 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
         &self,
         id: BlockHashOrNumber,
     ) -> Result<Option<Vec<TransactionSigned>>> {
-        let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
+        Ok(self
+            .transactions_by_block_no_hash(id)?
+            .map(|transactions| transactions.into_iter().map(Into::into).collect()))
+    }
+
+    fn transactions_by_block_no_hash(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        let mut tx_cursor = self.cursor_read::<tables::Transactions>()?;
         if let Some(block_number) = self.convert_hash_or_number(id)? {
             if let Some(body) = self.block_body_indices(block_number)? {
                 let tx_range = body.tx_num_range();
@@ -1000,10 +4383,10 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
                 } else {
                     let transactions = tx_cursor
                         .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx.into()))
+                        .map(|result| result.map(|(_, tx)| tx))
                         .collect::<std::result::Result<Vec<_>, _>>()?;
                     Ok(Some(transactions))
-                }
+                };
             }
         }
         Ok(None)
@@ -1014,10 +4397,8 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
         range: impl RangeBounds<BlockNumber>,
     ) -> Result<Vec<Vec<TransactionSigned>>> {
         let mut results = Vec::new();
-        let mut body_cursor = self.tx.cursor_read::<tables::BlockBodyIndices>()?;
-        let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
-        for entry in body_cursor.walk_range(range)? {
-            let (_, body) = entry?;
+        let mut tx_cursor = self.cursor_read::<tables::Transactions>()?;
+        for (_, body) in self.block_body_indices_range(range)? {
             let tx_num_range = body.tx_num_range();
             if tx_num_range.is_empty() {
                 results.push(Vec::new());
@@ -1038,7 +4419,6 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
         range: impl RangeBounds<TxNumber>,
     ) -> Result<Vec<TransactionSignedNoHash>> {
         Ok(self
-            .tx
             .cursor_read::<tables::Transactions>()?
             .walk_range(range)?
             .map(|entry| entry.map(|tx| tx.1))
@@ -1047,7 +4427,6 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
 
     fn senders_by_tx_range(&self, range: impl RangeBounds<TxNumber>) -> Result<Vec<Address>> {
         Ok(self
-            .tx
             .cursor_read::<tables::TxSenders>()?
             .walk_range(range)?
             .map(|entry| entry.map(|sender| sender.1))
@@ -1055,13 +4434,21 @@ impl<'this, TX: DbTx<'this>> TransactionsProvider for DatabaseProvider<'this, TX
     }
 
     fn transaction_sender(&self, id: TxNumber) -> Result<Option<Address>> {
-        Ok(self.tx.get::<tables::TxSenders>(id)?)
+        self.get::<tables::TxSenders>(id)
     }
 }
 
 impl<'this, TX: DbTx<'this>> ReceiptProvider for DatabaseProvider<'this, TX> {
     fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
-        Ok(self.tx.get::<tables::Receipts>(id)?)
+        if let Some(receipt) = self.tx.get::<tables::Receipts>(id)? {
+            return Ok(Some(receipt));
+        }
+
+        // the receipt is missing because it was either pruned or not executed yet
+        if let Some(block_number) = self.transaction_block(id)? {
+            self.ensure_receipts_not_pruned(block_number)?;
+        }
+        Ok(None)
     }
 
     fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
@@ -1072,23 +4459,103 @@ impl<'this, TX: DbTx<'this>> ReceiptProvider for DatabaseProvider<'this, TX> {
         }
     }
 
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        let Some(tx_num) = self.transaction_id(hash)? else { return Ok(None) };
+        let Some(block_number) = self.transaction_block(tx_num)? else { return Ok(None) };
+        let Some(body) = self.block_body_indices(block_number)? else { return Ok(None) };
+        let Some(sealed_header) = self.sealed_header(block_number)? else { return Ok(None) };
+
+        let mut receipts_cursor = self.cursor_read::<tables::Receipts>()?;
+        let Some((_, receipt)) = receipts_cursor.seek_exact(tx_num)? else {
+            self.ensure_receipts_not_pruned(block_number)?;
+            return Ok(None);
+        };
+
+        // the gas used by the transaction is the difference to the cumulative gas used by the
+        // previous transaction in the block
+        let previous_cumulative_gas_used = if tx_num == body.first_tx_num() {
+            0
+        } else {
+            let (_, prev_receipt) = receipts_cursor
+                .prev()?
+                .filter(|(prev_tx_num, _)| *prev_tx_num == tx_num - 1)
+                .ok_or(ProviderError::ReceiptPruned(tx_num - 1))?;
+            prev_receipt.cumulative_gas_used
+        };
+        let gas_used = tx_gas_used(&receipt, previous_cumulative_gas_used);
+
+        let (header, block_hash) = sealed_header.split();
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: tx_num - body.first_tx_num(),
+            block_hash,
+            block_number,
+            base_fee: header.base_fee_per_gas,
+            gas_used: Some(gas_used),
+            success: Some(receipt.success),
+        };
+        Ok(Some((receipt, meta, gas_used)))
+    }
+
     fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
-        if let Some(number) = self.convert_hash_or_number(block)? {
-            if let Some(body) = self.block_body_indices(number)? {
-                let tx_range = body.tx_num_range();
-                return if tx_range.is_empty() {
-                    Ok(Some(Vec::new()))
-                } else {
-                    let mut tx_cursor = self.tx.cursor_read::<tables::Receipts>()?;
-                    let transactions = tx_cursor
-                        .walk_range(tx_range)?
-                        .map(|result| result.map(|(_, tx)| tx))
-                        .collect::<std::result::Result<Vec<_>, _>>()?;
-                    Ok(Some(transactions))
+        self.record(Operation::ReceiptsByBlock, || {
+            if let Some(number) = self.convert_hash_or_number(block)? {
+                if let Some(receipts) = self.receipts_by_block_range(number..=number)?.pop() {
+                    return Ok(Some(receipts));
                 }
+                // the receipts are missing because they were either pruned or not executed yet
+                self.ensure_receipts_not_pruned(number)?;
+            }
+            Ok(None)
+        })
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        let prune_height = self
+            .get_prune_checkpoint(PrunePart::Receipts)?
+            .map(|checkpoint| checkpoint.block_number);
+
+        let bodies = self.block_body_indices_range(range)?;
+        let (Some((_, first)), Some((_, last))) = (bodies.first(), bodies.last()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut results = Vec::new();
+        let mut receipts_iter =
+            self.receipts_iter(first.first_tx_num()..last.first_tx_num() + last.tx_count())?;
+        for (block, body) in bodies {
+            if let Some(prune_height) = prune_height.filter(|prune_height| block <= *prune_height) {
+                return Err(ProviderError::ReceiptsPruned { block, prune_height }.into());
+            }
+
+            let tx_num_range = body.tx_num_range();
+            if tx_num_range.is_empty() {
+                results.push(Vec::new());
+                continue;
+            }
+
+            receipts_iter.seek(tx_num_range.start)?;
+            let receipts = receipts_iter
+                .by_ref()
+                .take(body.tx_count() as usize)
+                .take_while(|entry| {
+                    entry.as_ref().map_or(true, |(tx_num, _)| tx_num_range.contains(tx_num))
+                })
+                .map(|entry| entry.map(|(_, receipt)| receipt))
+                .collect::<Result<Vec<_>>>()?;
+
+            if !has_all_receipts(&body, receipts.len()) {
+                break;
             }
+            results.push(receipts);
         }
-        Ok(None)
+        Ok(results)
     }
 }
 
@@ -1099,7 +4566,7 @@ impl<'this, TX: DbTx<'this>> WithdrawalsProvider for DatabaseProvider<'this, TX>
         timestamp: u64,
     ) -> Result<Option<Vec<Withdrawal>>> {
         if self.chain_spec.is_shanghai_activated_at_timestamp(timestamp) {
-            if let Some(number) = self.convert_hash_or_number(id)? {
+            if let Some(number) = self.convert_hash_or_number_checked(id)? {
                 // If we are past shanghai, then all blocks should have a withdrawal list, even if
                 // empty
                 let withdrawals = self
@@ -1107,16 +4574,26 @@ impl<'this, TX: DbTx<'this>> WithdrawalsProvider for DatabaseProvider<'this, TX>
                     .get::<tables::BlockWithdrawals>(number)
                     .map(|w| w.map(|w| w.withdrawals))?
                     .unwrap_or_default();
-                return Ok(Some(withdrawals))
+                return Ok(Some(withdrawals));
             }
         }
         Ok(None)
     }
 
     fn latest_withdrawal(&self) -> Result<Option<Withdrawal>> {
-        let latest_block_withdrawal = self.tx.cursor_read::<tables::BlockWithdrawals>()?.last()?;
-        Ok(latest_block_withdrawal
-            .and_then(|(_, mut block_withdrawal)| block_withdrawal.withdrawals.pop()))
+        let best_block = self.best_block_number()?;
+        let mut cursor = self.cursor_read::<tables::BlockWithdrawals>()?;
+        for entry in cursor.walk_back(Some(best_block))? {
+            let (number, mut block_withdrawal) = entry?;
+            // rows past the best block are leftovers of an unwound tip
+            if number > best_block {
+                continue;
+            }
+            if let Some(withdrawal) = block_withdrawal.withdrawals.pop() {
+                return Ok(Some(withdrawal));
+            }
+        }
+        Ok(None)
     }
 }
 
@@ -1127,9 +4604,42 @@ impl<'this, TX: DbTx<'this>> EvmEnvProvider for DatabaseProvider<'this, TX> {
         block_env: &mut BlockEnv,
         at: BlockHashOrNumber,
     ) -> Result<()> {
-        let hash = self.convert_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
-        let header = self.header(&hash)?.ok_or(ProviderError::HeaderNotFound(at))?;
-        self.fill_env_with_header(cfg, block_env, &header)
+        self.record(Operation::FillEnvAt, || {
+            let EnvHeader { header, total_difficulty, spec_id } = self.env_header(at)?;
+            fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+            fill_block_env(block_env, &self.chain_spec, &header, spec_id >= SpecId::MERGE);
+            Ok(())
+        })
+    }
+
+    fn fill_env_at_block_id(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockId,
+    ) -> Result<Option<BlockNumHash>> {
+        let number = match at {
+            // the environment is read from the canonical header at the height, a reorged-out hash
+            // doesn't resolve
+            BlockId::Hash(hash) => self.canonical_block_number(hash.block_hash)?,
+            BlockId::Number(BlockNumberOrTag::Pending) => {
+                let head = self.best_block_number()?;
+                let Some(hash) = self.block_hash(head)? else { return Ok(None) };
+                self.fill_env_for_next_block(cfg, block_env, None)?;
+                return Ok(Some(BlockNumHash::new(head, hash)));
+            }
+            // the executed tip, which the latest state is read from, not the last synced header
+            BlockId::Number(BlockNumberOrTag::Latest) => {
+                self.convert_hash_or_number_checked(self.best_block_number()?.into())?
+            }
+            BlockId::Number(tag) => self.block_number_for_tag(tag)?,
+        };
+        let Some(number) = number else { return Ok(None) };
+
+        let EnvHeader { header, total_difficulty, spec_id } = self.env_header(number.into())?;
+        fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+        fill_block_env(block_env, &self.chain_spec, &header, spec_id >= SpecId::MERGE);
+        Ok(Some(header.num_hash()))
     }
 
     fn fill_env_with_header(
@@ -1138,53 +4648,245 @@ impl<'this, TX: DbTx<'this>> EvmEnvProvider for DatabaseProvider<'this, TX> {
         block_env: &mut BlockEnv,
         header: &Header,
     ) -> Result<()> {
-        let total_difficulty = self
-            .header_td_by_number(header.number)?
-            .ok_or_else(|| ProviderError::HeaderNotFound(header.number.into()))?;
+        self.record(Operation::FillEnvWithHeader, || {
+            let total_difficulty = self.env_total_difficulty(header)?;
+            self.fill_env_with_header_and_td(cfg, block_env, header, total_difficulty)
+        })
+    }
+
+    fn fill_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        let after_merge = self.spec_id_with_td(header, total_difficulty) >= SpecId::MERGE;
+        self.check_merge_fields(header, after_merge)?;
         fill_cfg_and_block_env(cfg, block_env, &self.chain_spec, header, total_difficulty);
         Ok(())
     }
 
     fn fill_block_env_at(&self, block_env: &mut BlockEnv, at: BlockHashOrNumber) -> Result<()> {
-        let hash = self.convert_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
-        let header = self.header(&hash)?.ok_or(ProviderError::HeaderNotFound(at))?;
+        self.record(Operation::FillBlockEnvAt, || {
+            let EnvHeader { header, spec_id, .. } = self.env_header(at)?;
+            fill_block_env(block_env, &self.chain_spec, &header, spec_id >= SpecId::MERGE);
+            Ok(())
+        })
+    }
 
-        self.fill_block_env_with_header(block_env, &header)
+    fn fill_block_env_with_overrides(
+        &self,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+        overrides: BlockEnvOverrides,
+    ) -> Result<()> {
+        let EnvHeader { header, spec_id, .. } = self.env_header(at)?;
+        // the spec is chosen from the real header, unless it is overridden explicitly
+        let spec_id = overrides.spec_id.unwrap_or(spec_id);
+        fill_block_env(block_env, &self.chain_spec, &header, spec_id >= SpecId::MERGE);
+        overrides.apply(block_env);
+        Ok(())
     }
 
     fn fill_block_env_with_header(&self, block_env: &mut BlockEnv, header: &Header) -> Result<()> {
-        let total_difficulty = self
-            .header_td_by_number(header.number)?
-            .ok_or_else(|| ProviderError::HeaderNotFound(header.number.into()))?;
-        let spec_id = revm_spec(
-            &self.chain_spec,
-            Head {
-                number: header.number,
-                timestamp: header.timestamp,
-                difficulty: header.difficulty,
-                total_difficulty,
-                // Not required
-                hash: Default::default(),
-            },
-        );
-        let after_merge = spec_id >= SpecId::MERGE;
+        self.record(Operation::FillBlockEnvWithHeader, || {
+            let total_difficulty = self.env_total_difficulty(header)?;
+            self.fill_block_env_with_header_and_td(block_env, header, total_difficulty)
+        })
+    }
+
+    fn fill_block_env_with_header_and_td(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        let after_merge = self.spec_id_with_td(header, total_difficulty) >= SpecId::MERGE;
+        self.check_merge_fields(header, after_merge)?;
         fill_block_env(block_env, &self.chain_spec, header, after_merge);
         Ok(())
     }
 
     fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> Result<()> {
-        let hash = self.convert_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
-        let header = self.header(&hash)?.ok_or(ProviderError::HeaderNotFound(at))?;
-        self.fill_cfg_env_with_header(cfg, &header)
+        self.record(Operation::FillCfgEnvAt, || {
+            let EnvHeader { header, total_difficulty, .. } = self.env_header(at)?;
+            fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+            Ok(())
+        })
     }
 
     fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> Result<()> {
-        let total_difficulty = self
-            .header_td_by_number(header.number)?
-            .ok_or_else(|| ProviderError::HeaderNotFound(header.number.into()))?;
+        self.record(Operation::FillCfgEnvWithHeader, || {
+            let total_difficulty = self.env_total_difficulty(header)?;
+            self.fill_cfg_env_with_header_and_td(cfg, header, total_difficulty)
+        })
+    }
+
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
         fill_cfg_env(cfg, &self.chain_spec, header, total_difficulty);
         Ok(())
     }
+
+    fn fill_envs_for_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>> {
+        let mut td_cursor = self.cursor_read::<tables::HeaderTD>()?;
+        // the chain dependent fields are the same for all blocks, only the spec id changes
+        let mut template: Option<CfgEnv> = None;
+
+        let mut envs = Vec::new();
+        for entry in self.cursor_read::<tables::Headers>()?.walk_range(range)? {
+            let (number, header) = entry?;
+            let total_difficulty = match self.chain_spec.final_paris_total_difficulty(number) {
+                Some(td) => td,
+                None => td_cursor
+                    .seek_exact(number)?
+                    .map(|(_, td)| td.0)
+                    .ok_or(ProviderError::TotalDifficultyNotFound { number })?,
+            };
+
+            let mut cfg = template
+                .get_or_insert_with(|| {
+                    let mut cfg = CfgEnv::default();
+                    fill_cfg_env(&mut cfg, &self.chain_spec, &header, total_difficulty);
+                    cfg
+                })
+                .clone();
+            cfg.spec_id = self.spec_id_with_td(&header, total_difficulty);
+
+            let mut block_env = BlockEnv::default();
+            fill_block_env(&mut block_env, &self.chain_spec, &header, cfg.spec_id >= SpecId::MERGE);
+            envs.push((cfg, block_env));
+        }
+        Ok(envs)
+    }
+
+    fn fill_env_for_next_block(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        attributes: Option<NextBlockAttributes>,
+    ) -> Result<()> {
+        let head = self.best_block_number()?;
+        let (parent, parent_td) = self
+            .sealed_header_with_td(head.into())?
+            .ok_or(ProviderError::HeaderNotFound(head.into()))?;
+        let attributes = attributes.unwrap_or_default();
+
+        let mut header = Header {
+            parent_hash: parent.hash,
+            number: parent.number + 1,
+            timestamp: attributes.timestamp.unwrap_or(parent.timestamp + 1),
+            beneficiary: attributes.suggested_fee_recipient.unwrap_or(parent.beneficiary),
+            gas_limit: parent.gas_limit,
+            mix_hash: parent.mix_hash,
+            base_fee_per_gas: self.chain_spec.next_block_base_fee(&parent),
+            ..Default::default()
+        };
+        // The difficulty of the next block is not known, whether it is past the merge only depends
+        // on the total difficulty of its parent.
+        let after_merge = self.spec_id_with_td(&header, parent_td) >= SpecId::MERGE;
+        if !after_merge {
+            header.difficulty = parent.difficulty;
+        }
+
+        fill_cfg_env(cfg, &self.chain_spec, &header, parent_td + header.difficulty);
+        fill_block_env_with_coinbase(block_env, &header, after_merge, header.beneficiary);
+        Ok(())
+    }
+}
+
+impl<'this, TX: DbTx<'this>> DatabaseProvider<'this, TX> {
+    /// Resolves the header, total difficulty and spec of the block for filling EVM environments.
+    ///
+    /// Read-only providers cache the result for the most recently requested blocks, so repeated
+    /// fills for the same block don't read the database again.
+    fn env_header(&self, at: BlockHashOrNumber) -> Result<EnvHeader> {
+        if let Some(entry) = self.env_cache.as_ref().and_then(|cache| cache.get(at)) {
+            return Ok(entry);
+        }
+
+        let (header, total_difficulty) =
+            self.sealed_header_with_td(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
+        let spec_id = self.spec_id_with_td(&header, total_difficulty);
+        let entry = EnvHeader { header, total_difficulty, spec_id };
+        if let Some(cache) = &self.env_cache {
+            cache.insert(entry.clone());
+        }
+        Ok(entry)
+    }
+
+    /// Returns the total difficulty to configure the EVM environment of the header with.
+    ///
+    /// The total difficulty only decides whether the header is past the merge. It is only read from
+    /// the database for headers in the transition window of a total difficulty activated
+    /// [Hardfork::Paris], for all other headers the chain spec alone decides.
+    fn env_total_difficulty(&self, header: &Header) -> Result<U256> {
+        if let Some(total_difficulty) = self.chain_spec.final_paris_total_difficulty(header.number)
+        {
+            return Ok(total_difficulty);
+        }
+
+        match self.chain_spec.fork(Hardfork::Paris) {
+            // blocks from the fork block onwards are known to be past the transition
+            ForkCondition::TTD { fork_block: Some(fork_block), total_difficulty }
+                if header.number >= fork_block =>
+            {
+                Ok(total_difficulty + header.difficulty)
+            }
+            ForkCondition::TTD { .. } => Ok(self
+                .header_td_by_number(header.number)?
+                .ok_or_else(|| ProviderError::HeaderNotFound(header.number.into()))?),
+            // the spec doesn't depend on the total difficulty
+            _ => Ok(U256::ZERO),
+        }
+    }
+
+    /// Returns the [SpecId] of the header with its already known total difficulty.
+    fn spec_id_with_td(&self, header: &Header, total_difficulty: U256) -> SpecId {
+        revm_spec(
+            &self.chain_spec,
+            Head::builder(header.number)
+                .allow_unset_hash()
+                .timestamp(header.timestamp)
+                .difficulty(header.difficulty)
+                .total_difficulty(total_difficulty)
+                .build(),
+        )
+    }
+
+    /// Checks that the proof-of-work fields of the header match which side of the merge it is on.
+    ///
+    /// Headers past the merge must have a zero difficulty and nonce, unless the check was disabled
+    /// with [DatabaseProvider::without_merge_field_checks]. Headers before the merge without a mix
+    /// hash are only warned about.
+    fn check_merge_fields(&self, header: &Header, after_merge: bool) -> Result<()> {
+        if after_merge {
+            if self.check_merge_fields && (header.difficulty != U256::ZERO || header.nonce != 0) {
+                return Err(ProviderError::InconsistentHeader {
+                    number: header.number,
+                    difficulty: header.difficulty,
+                    nonce: header.nonce,
+                }
+                .into());
+            }
+        } else if header.number != 0 && header.mix_hash.is_zero() {
+            // the genesis header commonly has no mix hash
+            warn!(
+                target: "provider::evm_env",
+                number = header.number,
+                "Pre-merge header without a mix hash"
+            );
+        }
+        Ok(())
+    }
 }
 
 impl<'this, TX: DbTx<'this>> StageCheckpointReader for DatabaseProvider<'this, TX> {
@@ -1196,6 +4898,29 @@ impl<'this, TX: DbTx<'this>> StageCheckpointReader for DatabaseProvider<'this, T
     fn get_stage_checkpoint_progress(&self, id: StageId) -> Result<Option<Vec<u8>>> {
         Ok(self.tx.get::<tables::SyncStageProgress>(id.to_string())?)
     }
+
+    fn get_all_stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>> {
+        let mut cursor = self.cursor_read::<tables::SyncStage>()?;
+        let mut checkpoints = Vec::new();
+        for id in StageId::ALL {
+            if let Some((_, checkpoint)) = cursor.seek_exact(id.to_string())? {
+                checkpoints.push((id, checkpoint));
+            }
+        }
+        Ok(checkpoints)
+    }
+}
+
+impl<'this, TX: DbTx<'this>> PruneCheckpointReader for DatabaseProvider<'this, TX> {
+    fn get_prune_checkpoint(&self, part: PrunePart) -> Result<Option<PruneCheckpoint>> {
+        Ok(self.tx.get::<tables::PruneCheckpoints>(part)?)
+    }
+}
+
+impl<'this, TX: DbTxMut<'this>> PruneCheckpointWriter for DatabaseProvider<'this, TX> {
+    fn save_prune_checkpoint(&self, part: PrunePart, checkpoint: PruneCheckpoint) -> Result<()> {
+        Ok(self.tx.put::<tables::PruneCheckpoints>(part, checkpoint)?)
+    }
 }
 
 impl<'this, TX: DbTxMut<'this>> StageCheckpointWriter for DatabaseProvider<'this, TX> {
@@ -1214,6 +4939,7 @@ impl<'this, TX: DbTxMut<'this>> StageCheckpointWriter for DatabaseProvider<'this
         block_number: BlockNumber,
         drop_stage_checkpoint: bool,
     ) -> Result<()> {
+        self.flush()?;
         // iterate over all existing stages in the table and update its progress.
         let mut cursor = self.tx.cursor_write::<tables::SyncStage>()?;
         while let Some((stage_name, checkpoint)) = cursor.next()? {
@@ -1235,7 +4961,7 @@ impl<'this, TX: DbTx<'this>> StorageReader for DatabaseProvider<'this, TX> {
         &self,
         addresses_with_keys: impl IntoIterator<Item = (Address, impl IntoIterator<Item = H256>)>,
     ) -> Result<Vec<(Address, Vec<StorageEntry>)>> {
-        let mut plain_storage = self.tx.cursor_dup_read::<tables::PlainStorageState>()?;
+        let mut plain_storage = self.cursor_dup_read::<tables::PlainStorageState>()?;
 
         addresses_with_keys
             .into_iter()
@@ -1258,8 +4984,7 @@ impl<'this, TX: DbTx<'this>> StorageReader for DatabaseProvider<'this, TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> Result<BTreeMap<Address, BTreeSet<H256>>> {
-        self.tx
-            .cursor_read::<tables::StorageChangeSet>()?
+        self.cursor_read::<tables::StorageChangeSet>()?
             .walk_range(BlockNumberAddress::range(range))?
             // fold all storages and save its old state so we can remove it from HashedStorage
             // it is needed as it is dup table.
@@ -1274,7 +4999,7 @@ impl<'this, TX: DbTx<'this>> StorageReader for DatabaseProvider<'this, TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> Result<BTreeMap<(Address, H256), Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::StorageChangeSet>()?;
+        let mut changeset_cursor = self.cursor_read::<tables::StorageChangeSet>()?;
 
         let storage_changeset_lists =
             changeset_cursor.walk_range(BlockNumberAddress::range(range))?.try_fold(
@@ -1326,7 +5051,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HashingWriter for DatabaseProvider
                     block_number: *range.end(),
                     block_hash: end_block_hash,
                 }
-                .into())
+                .into());
             }
             trie_updates.flush(&self.tx)?;
         }
@@ -1334,11 +5059,11 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HashingWriter for DatabaseProvider
     }
 
     fn unwind_storage_hashing(&self, range: Range<BlockNumberAddress>) -> Result<()> {
-        let mut hashed_storage = self.tx.cursor_dup_write::<tables::HashedStorage>()?;
+        self.flush()?;
+        let mut hashed_storage = self.cursor_dup_write::<tables::HashedStorage>()?;
 
         // Aggregate all block changesets and make list of accounts that have been changed.
-        self.tx
-            .cursor_read::<tables::StorageChangeSet>()?
+        self.cursor_read::<tables::StorageChangeSet>()?
             .walk_range(range)?
             .collect::<std::result::Result<Vec<_>, _>>()?
             .into_iter()
@@ -1391,7 +5116,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HashingWriter for DatabaseProvider
             map
         });
 
-        let mut hashed_storage = self.tx.cursor_dup_write::<tables::HashedStorage>()?;
+        let mut hashed_storage = self.cursor_dup_write::<tables::HashedStorage>()?;
         // Hash the address and key and apply them to HashedStorage (if Storage is None
         // just remove it);
         hashed.into_iter().try_for_each(|(hashed_address, storage)| {
@@ -1414,11 +5139,11 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HashingWriter for DatabaseProvider
     }
 
     fn unwind_account_hashing(&self, range: RangeInclusive<BlockNumber>) -> Result<()> {
-        let mut hashed_accounts = self.tx.cursor_write::<tables::HashedAccount>()?;
+        self.flush()?;
+        let mut hashed_accounts = self.cursor_write::<tables::HashedAccount>()?;
 
         // Aggregate all block changesets and make a list of accounts that have been changed.
-        self.tx
-            .cursor_read::<tables::AccountChangeSet>()?
+        self.cursor_read::<tables::AccountChangeSet>()?
             .walk_range(range)?
             .collect::<std::result::Result<Vec<_>, _>>()?
             .into_iter()
@@ -1454,7 +5179,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HashingWriter for DatabaseProvider
         &self,
         accounts: impl IntoIterator<Item = (Address, Option<Account>)>,
     ) -> Result<()> {
-        let mut hashed_accounts = self.tx.cursor_write::<tables::HashedAccount>()?;
+        let mut hashed_accounts = self.cursor_write::<tables::HashedAccount>()?;
 
         let hashes_accounts = accounts.into_iter().fold(
             BTreeMap::new(),
@@ -1513,8 +5238,8 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HistoryWriter for DatabaseProvider
     }
 
     fn unwind_storage_history_indices(&self, range: Range<BlockNumberAddress>) -> Result<usize> {
+        self.flush()?;
         let storage_changesets = self
-            .tx
             .cursor_read::<tables::StorageChangeSet>()?
             .walk_range(range)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -1534,15 +5259,15 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HistoryWriter for DatabaseProvider
                 },
             );
 
-        let mut cursor = self.tx.cursor_write::<tables::StorageHistory>()?;
+        let mut cursor = self.cursor_write::<tables::StorageHistory>()?;
         for ((address, storage_key), rem_index) in last_indices {
             let partial_shard = unwind_history_shards::<_, tables::StorageHistory, _>(
                 &mut cursor,
                 StorageShardedKey::last(address, storage_key),
                 rem_index,
                 |storage_sharded_key| {
-                    storage_sharded_key.address == address &&
-                        storage_sharded_key.sharded_key.key == storage_key
+                    storage_sharded_key.address == address
+                        && storage_sharded_key.sharded_key.key == storage_key
                 },
             )?;
 
@@ -1560,8 +5285,8 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HistoryWriter for DatabaseProvider
     }
 
     fn unwind_account_history_indices(&self, range: RangeInclusive<BlockNumber>) -> Result<usize> {
+        self.flush()?;
         let account_changeset = self
-            .tx
             .cursor_read::<tables::AccountChangeSet>()?
             .walk_range(range)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -1579,7 +5304,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> HistoryWriter for DatabaseProvider
             });
 
         // Unwind the account history index.
-        let mut cursor = self.tx.cursor_write::<tables::AccountHistory>()?;
+        let mut cursor = self.cursor_write::<tables::AccountHistory>()?;
         for (address, rem_index) in last_indices {
             let partial_shard = unwind_history_shards::<_, tables::AccountHistory, _>(
                 &mut cursor,
@@ -1632,14 +5357,14 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> BlockExecutionWriter for DatabaseP
             if new_state_root != parent_state_root {
                 let parent_hash = self
                     .block_hash(parent_number)?
-                    .ok_or_else(|| ProviderError::HeaderNotFound(parent_number.into()))?;
+                    .ok_or_else(|| missing_entry::<tables::CanonicalHeaders>(parent_number))?;
                 return Err(ProviderError::UnwindStateRootMismatch {
                     got: new_state_root,
                     expected: parent_state_root,
                     block_number: parent_number,
                     block_hash: parent_hash,
                 }
-                .into())
+                .into());
             }
             trie_updates.flush(&self.tx)?;
         }
@@ -1676,79 +5401,62 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> BlockWriter for DatabaseProvider<'
         senders: Option<Vec<Address>>,
     ) -> Result<StoredBlockBodyIndices> {
         let block_number = block.number;
-        self.tx.put::<tables::CanonicalHeaders>(block.number, block.hash())?;
-        // Put header with canonical hashes.
-        self.tx.put::<tables::Headers>(block.number, block.header.as_ref().clone())?;
-        self.tx.put::<tables::HeaderNumbers>(block.hash(), block.number)?;
-
-        // total difficulty
-        let ttd = if block.number == 0 {
-            block.difficulty
-        } else {
-            let parent_block_number = block.number - 1;
-            let parent_ttd = self.header_td_by_number(parent_block_number)?.unwrap_or_default();
-            parent_ttd + block.difficulty
-        };
-
-        self.tx.put::<tables::HeaderTD>(block.number, ttd.into())?;
-
-        // insert body ommers data
-        if !block.ommers.is_empty() {
-            self.tx.put::<tables::BlockOmmers>(
-                block.number,
-                StoredBlockOmmers { ommers: block.ommers },
-            )?;
-        }
-
-        let mut next_tx_num = self
-            .tx
-            .cursor_read::<tables::Transactions>()?
-            .last()?
-            .map(|(n, _)| n + 1)
-            .unwrap_or_default();
-        let first_tx_num = next_tx_num;
-
-        let tx_count = block.body.len() as u64;
-
-        let senders_len = senders.as_ref().map(|s| s.len());
-        let tx_iter = if Some(block.body.len()) == senders_len {
-            block.body.into_iter().zip(senders.unwrap().into_iter()).collect::<Vec<(_, _)>>()
-        } else {
-            block
-                .body
-                .into_iter()
-                .map(|tx| {
-                    let signer = tx.recover_signer();
-                    (tx, signer.unwrap_or_default())
-                })
-                .collect::<Vec<(_, _)>>()
+        // the block could have been written before a crash, without the stage checkpoints
+        let existing = match self.get::<tables::CanonicalHeaders>(block_number)? {
+            Some(hash) => Some(hash),
+            None => self.get::<tables::Headers>(block_number)?.map(|header| header.hash_slow()),
         };
-
-        for (transaction, sender) in tx_iter {
-            let hash = transaction.hash();
-            self.tx.put::<tables::TxSenders>(next_tx_num, sender)?;
-            self.tx.put::<tables::Transactions>(next_tx_num, transaction.into())?;
-            self.tx.put::<tables::TxHashNumber>(hash, next_tx_num)?;
-            next_tx_num += 1;
-        }
-
-        if let Some(withdrawals) = block.withdrawals {
-            if !withdrawals.is_empty() {
-                self.tx.put::<tables::BlockWithdrawals>(
-                    block_number,
-                    StoredBlockWithdrawals { withdrawals },
-                )?;
+        if let Some(existing) = existing {
+            if existing != block.hash() {
+                return Err(ProviderError::BlockConflict {
+                    number: block_number,
+                    existing,
+                    new: block.hash(),
+                }
+                .into());
             }
+            // a header without a body is completed below
+            if let Some(body) = self.get::<tables::BlockBodyIndices>(block_number)? {
+                if body.tx_count != block.body.len() as u64 {
+                    return Err(ProviderError::InconsistentIndex {
+                        table: tables::BlockBodyIndices::NAME,
+                        block: block_number,
+                    }
+                    .into());
+                }
+                return Ok(body);
+            }
+        } else {
+            self.validate_block_insertion(&block)?;
         }
 
-        let block_indices = StoredBlockBodyIndices { first_tx_num, tx_count };
-        self.tx.put::<tables::BlockBodyIndices>(block_number, block_indices.clone())?;
-
-        if !block_indices.is_empty() {
-            self.tx.put::<tables::TransactionBlock>(block_indices.last_tx_num(), block_number)?;
-        }
+        // total difficulty
+        let ttd = match block.number.checked_sub(1) {
+            None => block.difficulty,
+            Some(parent) => match self.header_td_by_number(parent)? {
+                Some(parent_ttd) => parent_ttd + block.difficulty,
+                // past a gap there is no parent to accumulate from
+                None if self.allow_block_gaps && self.get::<tables::Headers>(parent)?.is_none() => {
+                    block.difficulty
+                }
+                None => {
+                    return Err(ProviderError::TotalDifficultyNotFound { number: parent }.into())
+                }
+            },
+        };
 
-        Ok(block_indices)
+        self.put::<tables::CanonicalHeaders>(block.number, block.hash())?;
+        // Put header with canonical hashes.
+        self.put::<tables::Headers>(block.number, block.header.as_ref().clone())?;
+        self.put::<tables::HeaderNumbers>(block.hash(), block.number)?;
+        self.put::<tables::HeaderTD>(block.number, ttd.into())?;
+
+        let body = BlockBody {
+            transactions: block.body,
+            ommers: block.ommers,
+            withdrawals: block.withdrawals,
+        };
+        self.insert_body(block_number, body, senders)
     }
 
     fn append_blocks_with_post_state(
@@ -1757,7 +5465,7 @@ impl<'this, TX: DbTxMut<'this> + DbTx<'this>> BlockWriter for DatabaseProvider<'
         state: PostState,
     ) -> Result<()> {
         if blocks.is_empty() {
-            return Ok(())
+            return Ok(());
         }
         let new_tip = blocks.last().unwrap();
         let new_tip_number = new_tip.number;
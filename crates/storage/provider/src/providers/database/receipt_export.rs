@@ -0,0 +1,286 @@
+use super::DatabaseProvider;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx};
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{BlockNumber, Log, LogAddressAndTopicsFilter, TxHash, TxNumber};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    ops::RangeInclusive,
+};
+
+/// A receipt with its transaction context, one line of a [ReceiptExporter] export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptRecord {
+    /// Number of the block of the transaction.
+    pub block_number: BlockNumber,
+    /// Number of the transaction in the database, see [ReceiptExportFilter::start_after].
+    pub tx_number: TxNumber,
+    /// Hash of the transaction.
+    pub tx_hash: TxHash,
+    /// Index of the transaction in the block.
+    pub tx_index: u64,
+    /// Whether the transaction was executed successfully.
+    pub status: bool,
+    /// Gas used by the transaction alone, not the cumulative gas used of the block.
+    pub gas_used: u64,
+    /// Logs of the transaction, only the matching ones if the export filters logs.
+    pub logs: Vec<Log>,
+}
+
+/// Which receipts a [ReceiptExporter] writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiptExportFilter {
+    /// Only writes receipts with a matching log, and of these only the matching logs.
+    pub logs: Option<LogAddressAndTopicsFilter>,
+    /// Skips the receipts up to and including this transaction number, to resume an export at
+    /// the [ReceiptExportOutcome::last_tx_number] of an earlier one.
+    pub start_after: Option<TxNumber>,
+}
+
+/// Result of a [ReceiptExporter::export_jsonl].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReceiptExportOutcome {
+    /// Number of written receipts.
+    pub written: u64,
+    /// The last transaction whose receipt was considered, whether it matched the filter or not.
+    pub last_tx_number: Option<TxNumber>,
+    /// The first block of the range that was not executed yet, where the export stopped. `None`
+    /// if the export reached the end of the range or of the stored blocks.
+    pub first_unexecuted_block: Option<BlockNumber>,
+}
+
+/// Exports receipts as [JSON Lines](https://jsonlines.org), one [ReceiptRecord] per line.
+///
+/// The receipts are read a block at a time with
+/// [DatabaseProvider::receipts_with_tx_hashes_by_block], so the export holds at most the receipts
+/// of one block in memory.
+#[derive(Debug)]
+pub struct ReceiptExporter<'a, 'this, TX: DbTx<'this>> {
+    /// The provider the receipts are read from.
+    provider: &'a DatabaseProvider<'this, TX>,
+}
+
+impl<'a, 'this, TX: DbTx<'this>> ReceiptExporter<'a, 'this, TX> {
+    /// Creates an exporter reading from the provider.
+    pub fn new(provider: &'a DatabaseProvider<'this, TX>) -> Self {
+        Self { provider }
+    }
+
+    /// Writes the receipts of the blocks in the range that pass the filter.
+    ///
+    /// The export stops at the end of the stored blocks or at the first block that was not
+    /// executed yet, which is reported as [ReceiptExportOutcome::first_unexecuted_block].
+    pub fn export_jsonl(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        mut writer: impl Write,
+        filter: &ReceiptExportFilter,
+    ) -> Result<ReceiptExportOutcome> {
+        let mut outcome = ReceiptExportOutcome::default();
+        let mut cursor = self.provider.tx_ref().cursor_read::<tables::BlockBodyIndices>()?;
+        for entry in cursor.walk_range(range)? {
+            let (number, body) = entry?;
+            if body.tx_count() == 0 ||
+                filter.start_after.is_some_and(|after| body.last_tx_num() <= after)
+            {
+                continue
+            }
+            let Some(receipts) = self.provider.receipts_with_tx_hashes_by_block(number.into())?
+            else {
+                outcome.first_unexecuted_block = Some(number);
+                break
+            };
+
+            let mut cumulative_gas_used = 0;
+            for (tx_index, (tx_hash, receipt)) in receipts.into_iter().enumerate() {
+                let gas_used = receipt.cumulative_gas_used.saturating_sub(cumulative_gas_used);
+                cumulative_gas_used = receipt.cumulative_gas_used;
+                let tx_number = body.first_tx_num() + tx_index as u64;
+                if filter.start_after.is_some_and(|after| tx_number <= after) {
+                    continue
+                }
+
+                let logs = match &filter.logs {
+                    Some(logs_filter) => {
+                        let logs = receipt
+                            .logs
+                            .into_iter()
+                            .filter(|log| logs_filter.matches(log))
+                            .collect::<Vec<_>>();
+                        if logs.is_empty() {
+                            continue
+                        }
+                        logs
+                    }
+                    None => receipt.logs,
+                };
+                let record = ReceiptRecord {
+                    block_number: number,
+                    tx_number,
+                    tx_hash,
+                    tx_index: tx_index as u64,
+                    status: receipt.success,
+                    gas_used,
+                    logs,
+                };
+                serde_json::to_writer(&mut writer, &record).map_err(|err| {
+                    ProviderError::ReceiptExport(format!("receipt of tx {tx_number}: {err}"))
+                })?;
+                writer.write_all(b"\n").map_err(io_error)?;
+                outcome.written += 1;
+            }
+            outcome.last_tx_number = Some(body.last_tx_num());
+        }
+
+        writer.flush().map_err(io_error)?;
+        Ok(outcome)
+    }
+}
+
+fn io_error(err: io::Error) -> ProviderError {
+    ProviderError::ReceiptExport(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockWriter, ProviderFactory};
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut, DatabaseEnv};
+    use reth_primitives::{
+        hex_literal::hex, Address, Bytes, Header, Receipt, SealedBlock, TransactionSigned, TxType,
+        H256, MAINNET,
+    };
+    use reth_rlp::Decodable;
+    use std::sync::Arc;
+
+    const ALL: &str = include_str!("../../../test_data/receipts/all.jsonl");
+    const FILTERED: &str = include_str!("../../../test_data/receipts/filtered.jsonl");
+    const RESUMED: &str = include_str!("../../../test_data/receipts/resumed.jsonl");
+
+    /// Creates a chain with two transactions with known hashes in block 1, an empty block 2 and
+    /// the receipts of the transactions.
+    fn fixture_chain() -> ProviderFactory<Arc<DatabaseEnv>> {
+        // a ropsten legacy and a mainnet EIP-1559 transaction
+        let raw_transactions = [
+            &hex!("f88b8212b085028fa6ae00830f424094aad593da0c8116ef7d2d594dd6a63241bccfc26c80a48318b64b000000000000000000000000641c5d790f862a58ec7abcfd644c0442e9c201b32aa0a6ef9e170bca5ffb7ac05433b13b7043de667fbb0b4a5e45d3b54fb2d6efcc63a0037ec2c05c3d60c5f5f78244ce0a3859e3a18a36c61efb061b383507d3ce19d2")[..],
+            &hex!("02f871018302a90f808504890aef60826b6c94ddf4c5025d1a5742cf12f74eec246d4432c295e487e09c3bbcc12b2b80c080a0f21a4eacd0bf8fea9c5105c543be5a1d8c796516875710fafafdf16d16d8ee23a001280915021bb446d1973501a67f93d2b38894a514b976e7b46dc2fe54598d76")[..],
+        ];
+        let transactions = raw_transactions
+            .into_iter()
+            .map(|raw| TransactionSigned::decode(&mut &raw[..]).unwrap())
+            .collect::<Vec<_>>();
+
+        let genesis = Header::default().seal_slow();
+        let block = Header { number: 1, parent_hash: genesis.hash(), ..Default::default() };
+        let block = block.seal_slow();
+        let empty = Header { number: 2, parent_hash: block.hash(), ..Default::default() };
+        let blocks = [
+            SealedBlock { header: genesis, ..Default::default() },
+            SealedBlock { header: block, body: transactions, ..Default::default() },
+            SealedBlock { header: empty.seal_slow(), ..Default::default() },
+        ];
+
+        let log = |address: u64, topics: Vec<H256>, data: &[u8]| Log {
+            address: Address::from_low_u64_be(address),
+            topics,
+            data: Bytes::from(data.to_vec()),
+        };
+        let receipts = [
+            Receipt {
+                tx_type: TxType::Legacy,
+                success: true,
+                cumulative_gas_used: 50_000,
+                logs: vec![log(1, vec![H256::from_low_u64_be(0xaa)], &[1, 2]), log(2, vec![], &[])],
+            },
+            Receipt {
+                tx_type: TxType::EIP1559,
+                success: false,
+                cumulative_gas_used: 80_000,
+                logs: vec![log(2, vec![H256::from_low_u64_be(0xbb)], &[])],
+            },
+        ];
+
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let provider_rw = factory.provider_rw().unwrap();
+        for block in blocks {
+            let senders = vec![Address::random(); block.body.len()];
+            provider_rw.insert_block(block, Some(senders)).unwrap();
+        }
+        for (tx_number, receipt) in receipts.into_iter().enumerate() {
+            provider_rw.tx_ref().put::<tables::Receipts>(tx_number as u64, receipt).unwrap();
+        }
+        provider_rw.commit().unwrap();
+        factory
+    }
+
+    fn export(
+        factory: &ProviderFactory<Arc<DatabaseEnv>>,
+        filter: ReceiptExportFilter,
+    ) -> (String, ReceiptExportOutcome) {
+        let provider = factory.provider().unwrap();
+        let mut out = Vec::new();
+        let outcome =
+            ReceiptExporter::new(&provider).export_jsonl(0..=2, &mut out, &filter).unwrap();
+        (String::from_utf8(out).unwrap(), outcome)
+    }
+
+    #[test]
+    fn export_receipts_jsonl() {
+        let factory = fixture_chain();
+
+        let (all, outcome) = export(&factory, ReceiptExportFilter::default());
+        assert_eq!(all, ALL);
+        assert_eq!(
+            outcome,
+            ReceiptExportOutcome {
+                written: 2,
+                last_tx_number: Some(1),
+                first_unexecuted_block: None,
+            }
+        );
+        let records = all
+            .lines()
+            .map(|line| serde_json::from_str::<ReceiptRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records.iter().map(|record| record.gas_used).collect::<Vec<_>>(),
+            [50_000, 30_000]
+        );
+
+        let by_address = LogAddressAndTopicsFilter {
+            addresses: vec![Address::from_low_u64_be(1)],
+            ..Default::default()
+        };
+        let filter = ReceiptExportFilter { logs: Some(by_address), ..Default::default() };
+        assert_eq!(export(&factory, filter).0, FILTERED);
+
+        let filter = ReceiptExportFilter { start_after: Some(0), ..Default::default() };
+        let resumed = ReceiptExportOutcome {
+            written: 1,
+            last_tx_number: Some(1),
+            first_unexecuted_block: None,
+        };
+        assert_eq!(export(&factory, filter), (RESUMED.to_string(), resumed));
+    }
+
+    #[test]
+    fn export_receipts_stops_at_unexecuted_block() {
+        let factory = fixture_chain();
+        let provider_rw = factory.provider_rw().unwrap();
+        for tx_number in 0..2 {
+            provider_rw.tx_ref().delete::<tables::Receipts>(tx_number, None).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let (out, outcome) = export(&factory, ReceiptExportFilter::default());
+        assert_eq!(out, "");
+        assert_eq!(
+            outcome,
+            ReceiptExportOutcome {
+                written: 0,
+                last_tx_number: None,
+                first_unexecuted_block: Some(1),
+            }
+        );
+    }
+}
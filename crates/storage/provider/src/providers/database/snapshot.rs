@@ -0,0 +1,187 @@
+use super::{DatabaseProviderRO, ProviderFactory};
+use reth_db::{database::Database, transaction::DbTx};
+use reth_interfaces::Result;
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Default duration after which a [SnapshotProvider] is reported as long-lived.
+pub const DEFAULT_SNAPSHOT_WARN_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// A read-only provider for long-running readers, returned by [ProviderFactory::snapshot].
+///
+/// The read transaction of the provider pins the state of the database at the time it was opened,
+/// and the pages freed by later commits can't be reused while it is open. The snapshot keeps the
+/// time it was opened at and the id of the last commit it sees, so that readers can check whether
+/// it is stale and [refresh](SnapshotProvider::refresh) it.
+///
+/// A snapshot that is held longer than the threshold of its factory is reported in the
+/// [DatabaseProviderMetrics](super::DatabaseProviderMetrics) once, on the first access, refresh
+/// or drop past the threshold. How long it was held is recorded when it is refreshed or dropped.
+#[derive(Debug)]
+pub struct SnapshotProvider<'this, DB: Database> {
+    /// The factory the provider is reopened from.
+    factory: &'this ProviderFactory<DB>,
+    /// The provider with the pinned read transaction.
+    provider: DatabaseProviderRO<'this, DB>,
+    /// When the read transaction was opened.
+    opened_at: Instant,
+    /// Id of the last commit seen by the read transaction.
+    commit_id: u64,
+    /// Whether the read transaction was reported as long-lived.
+    reported: AtomicBool,
+}
+
+impl<'this, DB: Database> SnapshotProvider<'this, DB> {
+    /// Opens a read transaction of the factory.
+    pub(crate) fn new(factory: &'this ProviderFactory<DB>) -> Result<Self> {
+        let provider = factory.provider()?;
+        let commit_id = provider.tx_ref().id();
+        Ok(Self {
+            factory,
+            provider,
+            opened_at: Instant::now(),
+            commit_id,
+            reported: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the id of the last commit seen by the snapshot.
+    pub fn commit_id(&self) -> u64 {
+        self.commit_id
+    }
+
+    /// Returns how long the read transaction has been open.
+    pub fn age(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    /// Returns `true` if the read transaction has been open for longer than `max_age`.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+
+    /// Returns `true` if the database was committed to since the snapshot was opened.
+    ///
+    /// Opens a short-lived read transaction to look up the id of the latest commit.
+    pub fn has_new_commits(&self) -> Result<bool> {
+        Ok(self.factory.db.tx()?.id() > self.commit_id)
+    }
+
+    /// Drops the read transaction and opens a new one, which sees the latest state of the
+    /// database.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.release();
+        self.provider = self.factory.provider()?;
+        self.commit_id = self.provider.tx_ref().id();
+        self.opened_at = Instant::now();
+        self.reported = AtomicBool::new(false);
+        Ok(())
+    }
+
+    /// Warns and records the metrics the first time the read transaction is found open for longer
+    /// than the threshold.
+    fn report_if_long_lived(&self) {
+        let held = self.age();
+        if held <= self.factory.snapshot_warn_threshold {
+            return
+        }
+        if self.reported.swap(true, Ordering::Relaxed) {
+            return
+        }
+
+        warn!(
+            target: "provider::snapshot",
+            ?held,
+            threshold = ?self.factory.snapshot_warn_threshold,
+            commit_id = self.commit_id,
+            "Long-lived database snapshot"
+        );
+        if let Some(metrics) = &self.factory.metrics {
+            metrics.record_long_lived_snapshot();
+        }
+    }
+
+    /// Reports the read transaction before it is released, recording how long it was held if it
+    /// was long-lived.
+    fn release(&mut self) {
+        self.report_if_long_lived();
+        if let (true, Some(metrics)) = (*self.reported.get_mut(), &self.factory.metrics) {
+            metrics.record_long_lived_snapshot_release(self.age());
+        }
+    }
+}
+
+impl<'this, DB: Database> Deref for SnapshotProvider<'this, DB> {
+    type Target = DatabaseProviderRO<'this, DB>;
+
+    fn deref(&self) -> &Self::Target {
+        self.report_if_long_lived();
+        &self.provider
+    }
+}
+
+impl<'this, DB: Database> Drop for SnapshotProvider<'this, DB> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockNumReader, BlockWriter, HeaderProvider};
+    use reth_db::test_utils::create_test_rw_db;
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_primitives::MAINNET;
+
+    #[test]
+    fn refresh_sees_new_commits() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let mut rng = generators::rng();
+
+        let genesis = random_block(&mut rng, 0, None, Some(1), Some(0));
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.insert_block(genesis.clone(), None).unwrap();
+        provider_rw.commit().unwrap();
+
+        let original = factory.snapshot().unwrap();
+        let mut snapshot = factory.snapshot().unwrap();
+        assert_eq!(snapshot.commit_id(), original.commit_id());
+        assert!(!snapshot.has_new_commits().unwrap());
+
+        let block = random_block(&mut rng, 1, Some(genesis.hash()), Some(1), Some(0));
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.insert_block(block.clone(), None).unwrap();
+        provider_rw.commit().unwrap();
+
+        // the snapshots are behind until they are refreshed
+        assert!(snapshot.has_new_commits().unwrap());
+        assert_eq!(snapshot.last_block_number().unwrap(), 0);
+        snapshot.refresh().unwrap();
+        assert!(!snapshot.has_new_commits().unwrap());
+        assert!(snapshot.commit_id() > original.commit_id());
+        assert_eq!(snapshot.last_block_number().unwrap(), 1);
+        assert_eq!(snapshot.header_by_number(1).unwrap(), Some(block.header.unseal()));
+
+        assert!(original.has_new_commits().unwrap());
+        assert_eq!(original.last_block_number().unwrap(), 0);
+        assert_eq!(original.header_by_number(1).unwrap(), None);
+    }
+
+    #[test]
+    fn refresh_resets_age() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let mut snapshot = factory.snapshot().unwrap();
+
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(snapshot.is_stale(Duration::from_millis(5)));
+        assert!(!snapshot.is_stale(Duration::MAX));
+
+        snapshot.refresh().unwrap();
+        assert!(!snapshot.is_stale(Duration::from_secs(60)));
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    BlockchainTreePendingStateProvider, CanonChainTracker, CanonStateNotifications,
-    CanonStateSubscriptions, EvmEnvProvider, HeaderProvider, PostStateDataProvider, ProviderError,
+    BlockEnvOverrides, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader,
+    BlockReaderIdExt, BlockchainTreePendingStateProvider, CanonChainTracker,
+    CanonStateNotifications, CanonStateSubscriptions, EvmEnvProvider, HeaderProvider,
+    NextBlockAttributes, PostStateDataProvider, ProviderError, PruneCheckpointReader,
     ReceiptProvider, ReceiptProviderIdExt, StageCheckpointReader, StateProviderBox,
     StateProviderFactory, TransactionsProvider, WithdrawalsProvider,
 };
@@ -14,9 +15,9 @@ use reth_interfaces::{
 use reth_primitives::{
     stage::{StageCheckpoint, StageId},
     Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
-    BlockNumberOrTag, BlockWithSenders, ChainInfo, Header, Receipt, SealedBlock,
-    SealedBlockWithSenders, SealedHeader, TransactionMeta, TransactionSigned,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, H256, U256,
+    BlockNumberOrTag, BlockWithSenders, ChainInfo, ChainSpec, Head, Header, PruneCheckpoint,
+    PrunePart, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, TransactionMeta,
+    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, H256, U256,
 };
 use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
 pub use state::{
@@ -25,17 +26,19 @@ pub use state::{
 };
 use std::{
     collections::{BTreeMap, HashSet},
-    ops::RangeBounds,
+    ops::{Bound, RangeBounds},
     time::Instant,
 };
-use tracing::trace;
+use tracing::{trace, warn};
 
 mod chain_info;
 mod database;
+mod overlay;
 mod post_state_provider;
 mod state;
 use crate::{providers::chain_info::ChainInfoTracker, traits::BlockSource};
 pub use database::*;
+pub use overlay::{OverlayProvider, PendingBlock, PendingBlocks};
 pub use post_state_provider::PostStateProvider;
 use reth_interfaces::blockchain_tree::{
     error::InsertBlockError, CanonicalOutcome, InsertPayloadOk,
@@ -70,15 +73,49 @@ where
 {
     /// Create a new provider using only the database and the tree, fetching the latest header from
     /// the database to initialize the provider.
+    ///
+    /// The safe and finalized blocks are restored from the markers persisted by the last fork
+    /// choice update.
     pub fn new(database: ProviderFactory<DB>, tree: Tree) -> Result<Self> {
         let provider = database.provider()?;
         let best: ChainInfo = provider.chain_info()?;
-        match provider.header_by_number(best.best_number)? {
-            Some(header) => {
-                drop(provider);
-                Ok(Self::with_latest(database, tree, header.seal(best.best_hash)))
-            }
-            None => Err(Error::Provider(ProviderError::HeaderNotFound(best.best_number.into()))),
+        let Some(header) = provider.header_by_number(best.best_number)? else {
+            return Err(Error::Provider(ProviderError::HeaderNotFound(best.best_number.into())))
+        };
+        let chain_info = ChainInfoTracker::new(header.seal(best.best_hash));
+        let marker_header = |num_hash: Option<BlockNumHash>| -> Result<Option<SealedHeader>> {
+            let Some(num_hash) = num_hash else { return Ok(None) };
+            Ok(provider.header_by_number(num_hash.number)?.map(|header| header.seal(num_hash.hash)))
+        };
+        if let Some(header) = marker_header(provider.safe_block_num_hash()?)? {
+            chain_info.set_safe(header);
+        }
+        if let Some(header) = marker_header(provider.finalized_block_num_hash()?)? {
+            chain_info.set_finalized(header);
+        }
+        drop(provider);
+        Ok(Self { database, tree, chain_info })
+    }
+
+    /// Persists a fork choice marker with the write. A marker that can't be persisted is only
+    /// logged, the in-memory chain info stays authoritative for the running node.
+    fn persist_chain_marker(
+        &self,
+        marker: &'static str,
+        write: impl FnOnce(&DatabaseProviderRW<'_, DB>) -> Result<()>,
+    ) {
+        let persisted = self.database.provider_rw().and_then(|provider| {
+            write(&provider)?;
+            provider.commit()?;
+            Ok(())
+        });
+        if let Err(err) = persisted {
+            warn!(
+                target: "providers::blockchain",
+                marker,
+                ?err,
+                "Failed to persist fork choice marker"
+            );
         }
     }
 }
@@ -142,6 +179,10 @@ where
     fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
         self.database.provider()?.sealed_header(number)
     }
+
+    fn sealed_header_with_td(&self, id: BlockHashOrNumber) -> Result<Option<(SealedHeader, U256)>> {
+        self.database.provider()?.sealed_header_with_td(id)
+    }
 }
 
 impl<DB, Tree> BlockHashReader for BlockchainProvider<DB, Tree>
@@ -153,8 +194,11 @@ where
         self.database.provider()?.block_hash(number)
     }
 
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
-        self.database.provider()?.canonical_hashes_range(start, end)
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
+        self.database.provider()?.canonical_hashes_range(range)
     }
 }
 
@@ -175,9 +219,17 @@ where
         self.database.provider()?.last_block_number()
     }
 
+    fn last_canonical_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        self.database.provider()?.last_canonical_num_hash()
+    }
+
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
         self.database.provider()?.block_number(hash)
     }
+
+    fn block_numbers(&self, hashes: &[H256]) -> Result<Vec<Option<BlockNumber>>> {
+        self.database.provider()?.block_numbers(hashes)
+    }
 }
 
 impl<DB, Tree> BlockIdReader for BlockchainProvider<DB, Tree>
@@ -245,6 +297,13 @@ where
         self.database.provider()?.block_body_indices(number)
     }
 
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        self.database.provider()?.block_body_indices_range(range)
+    }
+
     /// Returns the block with senders with matching number from database.
     ///
     /// **NOTE: The transactions have invalid hashes, since they would need to be calculated on the
@@ -269,6 +328,10 @@ where
         self.database.provider()?.transaction_by_id(id)
     }
 
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        self.database.provider()?.transaction_by_id_no_hash(id)
+    }
+
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
         self.database.provider()?.transaction_by_hash(hash)
     }
@@ -291,6 +354,13 @@ where
         self.database.provider()?.transactions_by_block(id)
     }
 
+    fn transactions_by_block_no_hash(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        self.database.provider()?.transactions_by_block_no_hash(id)
+    }
+
     fn transactions_by_block_range(
         &self,
         range: impl RangeBounds<BlockNumber>,
@@ -327,9 +397,23 @@ where
         self.database.provider()?.receipt_by_hash(hash)
     }
 
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        self.database.provider()?.receipt_by_hash_with_meta(hash)
+    }
+
     fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
         self.database.provider()?.receipts_by_block(block)
     }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        self.database.provider()?.receipts_by_block_range(range)
+    }
 }
 impl<DB, Tree> ReceiptProviderIdExt for BlockchainProvider<DB, Tree>
 where
@@ -389,6 +473,20 @@ where
     fn get_stage_checkpoint_progress(&self, id: StageId) -> Result<Option<Vec<u8>>> {
         self.database.provider()?.get_stage_checkpoint_progress(id)
     }
+
+    fn get_all_stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>> {
+        self.database.provider()?.get_all_stage_checkpoints()
+    }
+}
+
+impl<DB, Tree> PruneCheckpointReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn get_prune_checkpoint(&self, part: PrunePart) -> Result<Option<PruneCheckpoint>> {
+        self.database.provider()?.get_prune_checkpoint(part)
+    }
 }
 
 impl<DB, Tree> EvmEnvProvider for BlockchainProvider<DB, Tree>
@@ -405,6 +503,24 @@ where
         self.database.provider()?.fill_env_at(cfg, block_env, at)
     }
 
+    fn fill_env_at_block_id(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockId,
+    ) -> Result<Option<BlockNumHash>> {
+        // tags are resolved with the tracked chain info, the database only reads the environment
+        // of the resolved block
+        let at = match at {
+            BlockId::Number(tag) if !tag.is_number() && !tag.is_pending() => {
+                let Some(hash) = self.block_hash_for_id(at)? else { return Ok(None) };
+                hash.into()
+            }
+            at => at,
+        };
+        self.database.provider()?.fill_env_at_block_id(cfg, block_env, at)
+    }
+
     fn fill_env_with_header(
         &self,
         cfg: &mut CfgEnv,
@@ -414,14 +530,51 @@ where
         self.database.provider()?.fill_env_with_header(cfg, block_env, header)
     }
 
+    fn fill_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.database.provider()?.fill_env_with_header_and_td(
+            cfg,
+            block_env,
+            header,
+            total_difficulty,
+        )
+    }
+
     fn fill_block_env_at(&self, block_env: &mut BlockEnv, at: BlockHashOrNumber) -> Result<()> {
         self.database.provider()?.fill_block_env_at(block_env, at)
     }
 
+    fn fill_block_env_with_overrides(
+        &self,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+        overrides: BlockEnvOverrides,
+    ) -> Result<()> {
+        self.database.provider()?.fill_block_env_with_overrides(block_env, at, overrides)
+    }
+
     fn fill_block_env_with_header(&self, block_env: &mut BlockEnv, header: &Header) -> Result<()> {
         self.database.provider()?.fill_block_env_with_header(block_env, header)
     }
 
+    fn fill_block_env_with_header_and_td(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.database.provider()?.fill_block_env_with_header_and_td(
+            block_env,
+            header,
+            total_difficulty,
+        )
+    }
+
     fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> Result<()> {
         self.database.provider()?.fill_cfg_env_at(cfg, at)
     }
@@ -429,6 +582,31 @@ where
     fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> Result<()> {
         self.database.provider()?.fill_cfg_env_with_header(cfg, header)
     }
+
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        self.database.provider()?.fill_cfg_env_with_header_and_td(cfg, header, total_difficulty)
+    }
+
+    fn fill_envs_for_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>> {
+        self.database.provider()?.fill_envs_for_range(range)
+    }
+
+    fn fill_env_for_next_block(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        attributes: Option<NextBlockAttributes>,
+    ) -> Result<()> {
+        self.database.provider()?.fill_env_for_next_block(cfg, block_env, attributes)
+    }
 }
 
 impl<DB, Tree> StateProviderFactory for BlockchainProvider<DB, Tree>
@@ -643,7 +821,7 @@ where
 
 impl<DB, Tree> CanonChainTracker for BlockchainProvider<DB, Tree>
 where
-    DB: Send + Sync,
+    DB: Database,
     Tree: Send + Sync,
     Self: BlockReader,
 {
@@ -669,11 +847,17 @@ where
     }
 
     fn set_safe(&self, header: SealedHeader) {
+        let num_hash = header.num_hash();
         self.chain_info.set_safe(header);
+        self.persist_chain_marker("safe", |provider| provider.set_safe_block_num_hash(num_hash));
     }
 
     fn set_finalized(&self, header: SealedHeader) {
+        let num_hash = header.num_hash();
         self.chain_info.set_finalized(header);
+        self.persist_chain_marker("finalized", |provider| {
+            provider.set_finalized_block_num_hash(num_hash)
+        });
     }
 }
 
@@ -732,7 +916,7 @@ where
         match id {
             BlockId::Number(num) => self.sealed_header_by_number_or_tag(num),
             BlockId::Hash(hash) => {
-                self.header(&hash.block_hash)?.map_or_else(|| Ok(None), |h| Ok(Some(h.seal_slow())))
+                Ok(self.header(&hash.block_hash)?.map(|h| h.seal(hash.block_hash)))
             }
         }
     }
@@ -778,3 +962,19 @@ where
         self.tree.subscribe_to_canonical_state()
     }
 }
+
+/// Returns the [Head] of the best block of the provider.
+///
+/// If nothing was synced yet and the genesis block is missing, the genesis head of the chain spec
+/// is returned.
+pub fn head_from_provider<P>(provider: &P, chain_spec: &ChainSpec) -> Result<Head>
+where
+    P: BlockNumReader + HeaderProvider,
+{
+    let number = provider.best_block_number()?;
+    match provider.sealed_header_with_td(number.into())? {
+        Some((header, total_difficulty)) => Ok(Head::from_sealed_header(&header, total_difficulty)),
+        None if number == 0 => Ok(chain_spec.genesis_head()),
+        None => Err(ProviderError::HeaderNotFound(number.into()).into()),
+    }
+}
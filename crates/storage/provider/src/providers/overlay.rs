@@ -0,0 +1,773 @@
+use crate::{
+    traits::BlockSource, BlockHashReader, BlockNumReader, BlockReader, HeaderProvider,
+    ReceiptProvider, TransactionsProvider, WithdrawalsProvider,
+};
+use parking_lot::RwLock;
+use reth_db::models::StoredBlockBodyIndices;
+use reth_interfaces::{provider::ProviderError, Result};
+use reth_primitives::{
+    Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, ChainInfo, Header,
+    Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, H256, U256,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Bound, RangeBounds, RangeInclusive},
+    sync::Arc,
+};
+
+/// A block that is held in memory by its producer and is not yet in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingBlock {
+    /// The block and the senders of its transactions.
+    pub block: SealedBlockWithSenders,
+    /// The receipts of the transactions of the block.
+    pub receipts: Vec<Receipt>,
+}
+
+impl PendingBlock {
+    /// Returns the metadata of the transaction at the index, with the gas used and the status
+    /// taken from its receipt.
+    fn transaction_meta(&self, index: usize) -> TransactionMeta {
+        let receipt = self.receipts.get(index);
+        TransactionMeta {
+            tx_hash: self.block.body[index].hash(),
+            index: index as u64,
+            block_hash: self.block.hash(),
+            block_number: self.block.number,
+            base_fee: self.block.base_fee_per_gas,
+            gas_used: receipt.map(|receipt| self.gas_used(index, receipt)),
+            success: receipt.map(|receipt| receipt.success),
+        }
+    }
+
+    /// Returns the gas used by the transaction at the index alone.
+    fn gas_used(&self, index: usize, receipt: &Receipt) -> u64 {
+        let previous = index.checked_sub(1).and_then(|index| self.receipts.get(index));
+        receipt
+            .cumulative_gas_used
+            .saturating_sub(previous.map_or(0, |previous| previous.cumulative_gas_used))
+    }
+}
+
+/// The chain of [PendingBlock]s on top of the database, shared between the producer of the blocks
+/// and the [OverlayProvider]s that serve them.
+///
+/// The pending blocks always form a single contiguous chain. Inserting a block whose parent is
+/// pending replaces the blocks at and above its height, inserting any other block starts a new
+/// chain, which is expected to attach to the canonical tip of the database. Blocks are removed
+/// with [PendingBlocks::canonicalized] once they are committed to the database.
+#[derive(Debug, Default)]
+pub struct PendingBlocks {
+    /// The pending chain by block number.
+    blocks: BTreeMap<BlockNumber, PendingBlock>,
+    /// The numbers of the pending blocks by hash.
+    numbers: HashMap<BlockHash, BlockNumber>,
+    /// The block number and the index in the block of the pending transactions by hash.
+    transactions: HashMap<TxHash, (BlockNumber, usize)>,
+}
+
+impl PendingBlocks {
+    /// Adds a block with the receipts of its transactions to the pending chain.
+    ///
+    /// Fails without changing the pending chain if the parent of the block is pending but the
+    /// block isn't at the height above it.
+    pub fn insert(&mut self, block: SealedBlockWithSenders, receipts: Vec<Receipt>) -> Result<()> {
+        match self.numbers.get(&block.parent_hash).copied() {
+            Some(parent) if block.number != parent + 1 => {
+                return Err(ProviderError::NonContiguousPendingBlock {
+                    number: block.number,
+                    expected: parent + 1,
+                }
+                .into())
+            }
+            Some(_) => {
+                let replaced = self.blocks.split_off(&block.number);
+                self.numbers.retain(|_, number| !replaced.contains_key(number));
+                self.transactions.retain(|_, (number, _)| !replaced.contains_key(number));
+            }
+            None => self.clear(),
+        }
+
+        let number = block.number;
+        self.numbers.insert(block.hash(), number);
+        for (index, transaction) in block.body.iter().enumerate() {
+            self.transactions.insert(transaction.hash(), (number, index));
+        }
+        self.blocks.insert(number, PendingBlock { block, receipts });
+        Ok(())
+    }
+
+    /// Removes the blocks up to and including the number, once they are committed to the
+    /// database.
+    pub fn canonicalized(&mut self, number: BlockNumber) {
+        self.blocks.retain(|n, _| *n > number);
+        self.numbers.retain(|_, n| *n > number);
+        self.transactions.retain(|_, (n, _)| *n > number);
+    }
+
+    /// Removes all pending blocks.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.numbers.clear();
+        self.transactions.clear();
+    }
+
+    /// Returns `true` if there are no pending blocks.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Returns the number of pending blocks.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns the numbers of the first and the last pending block.
+    pub fn range(&self) -> Option<RangeInclusive<BlockNumber>> {
+        let first = self.blocks.keys().next()?;
+        let last = self.blocks.keys().next_back()?;
+        Some(*first..=*last)
+    }
+
+    /// Returns the highest pending block.
+    pub fn tip(&self) -> Option<&PendingBlock> {
+        self.blocks.values().next_back()
+    }
+
+    /// Returns the pending block with the number.
+    pub fn get(&self, number: BlockNumber) -> Option<&PendingBlock> {
+        self.blocks.get(&number)
+    }
+
+    /// Returns the pending block with the hash.
+    pub fn by_hash(&self, hash: &BlockHash) -> Option<&PendingBlock> {
+        self.numbers.get(hash).and_then(|number| self.blocks.get(number))
+    }
+
+    /// Returns the pending block with the hash or number.
+    pub fn block(&self, id: BlockHashOrNumber) -> Option<&PendingBlock> {
+        match id {
+            BlockHashOrNumber::Hash(hash) => self.by_hash(&hash),
+            BlockHashOrNumber::Number(number) => self.get(number),
+        }
+    }
+
+    /// Returns the pending block of the transaction and the index of the transaction in it.
+    pub fn transaction(&self, hash: &TxHash) -> Option<(&PendingBlock, usize)> {
+        let (number, index) = self.transactions.get(hash)?;
+        Some((self.blocks.get(number)?, *index))
+    }
+}
+
+/// A provider that serves the [PendingBlocks] on top of another provider, usually backed by the
+/// database.
+///
+/// Lookups check the pending blocks first and fall through to the wrapped provider, so a pending
+/// block shadows a stored block at the same height for lookups by number. Lookups by hash are
+/// unambiguous, [BlockReader::find_block_by_hash] returns stored blocks only for
+/// [BlockSource::Database] and pending blocks only for [BlockSource::Pending].
+///
+/// Pending transactions don't have a transaction number yet, lookups by [TxNumber] and the block
+/// body indices are served by the wrapped provider alone, as is the [ChainInfo]. Pending blocks
+/// are not canonical, so the canonical hashes of [BlockHashReader] are served by the wrapped
+/// provider alone as well.
+///
+/// The node doesn't use the overlay: the engine keeps its pending blocks in the blockchain tree,
+/// which the [BlockchainProvider](super::BlockchainProvider) already serves for
+/// [BlockSource::Pending]. The overlay is meant for producers of blocks that run on a database
+/// provider without a tree.
+#[derive(Debug, Clone)]
+pub struct OverlayProvider<P> {
+    /// The provider of the stored blocks.
+    database: P,
+    /// The blocks that are not stored yet.
+    pending: Arc<RwLock<PendingBlocks>>,
+}
+
+impl<P> OverlayProvider<P> {
+    /// Creates a provider serving the pending blocks on top of the database provider.
+    pub fn new(database: P, pending: Arc<RwLock<PendingBlocks>>) -> Self {
+        Self { database, pending }
+    }
+
+    /// Returns the wrapped provider.
+    pub fn database(&self) -> &P {
+        &self.database
+    }
+
+    /// Returns the shared pending blocks.
+    pub fn pending_blocks(&self) -> &Arc<RwLock<PendingBlocks>> {
+        &self.pending
+    }
+}
+
+impl<P: BlockReader> OverlayProvider<P> {
+    /// Returns the pending block with the hash or number mapped by `f`.
+    fn pending<T>(&self, id: BlockHashOrNumber, f: impl FnOnce(&PendingBlock) -> T) -> Option<T> {
+        self.pending.read().block(id).map(f)
+    }
+
+    /// Returns the items of the blocks in the range, taking the heights of the pending blocks
+    /// from `pending` and the heights below and above them from `database`.
+    ///
+    /// Like the database ranges, the result ends early if the database is missing blocks below
+    /// the pending ones, or at a missing pending block.
+    fn overlay_range<T>(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        mut database: impl FnMut(RangeInclusive<BlockNumber>) -> Result<Vec<T>>,
+        pending: impl Fn(&PendingBlock) -> T,
+    ) -> Result<Vec<T>> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => match start.checked_add(1) {
+                Some(start) => start,
+                None => return Ok(Vec::new()),
+            },
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => *end,
+            Bound::Excluded(0) => return Ok(Vec::new()),
+            Bound::Excluded(end) => end - 1,
+            Bound::Unbounded => BlockNumber::MAX,
+        };
+        if start > end {
+            return Ok(Vec::new())
+        }
+
+        let blocks = self.pending.read();
+        let Some((first, last)) = blocks.range().map(RangeInclusive::into_inner) else {
+            return database(start..=end)
+        };
+
+        let mut items = Vec::new();
+        if start < first {
+            let below = start..=end.min(first - 1);
+            let len = below.end() - below.start() + 1;
+            items = database(below)?;
+            if end < first || (items.len() as u64) < len {
+                return Ok(items)
+            }
+        }
+        for number in start.max(first)..=end.min(last) {
+            let Some(block) = blocks.get(number) else { return Ok(items) };
+            items.push(pending(block));
+        }
+        if end > last {
+            items.extend(database(start.max(last + 1)..=end)?);
+        }
+        Ok(items)
+    }
+
+    /// Returns the total difficulty of the pending block with the number, accumulated from the
+    /// stored parent of the pending chain.
+    fn pending_td(&self, number: BlockNumber) -> Result<Option<U256>> {
+        let blocks = self.pending.read();
+        let Some(first) = blocks.blocks.values().next() else { return Ok(None) };
+        if !blocks.blocks.contains_key(&number) {
+            return Ok(None)
+        }
+        let parent_td = if first.block.number == 0 {
+            U256::ZERO
+        } else {
+            let Some(td) = self.database.header_td(&first.block.parent_hash)? else {
+                return Ok(None)
+            };
+            td
+        };
+        Ok(Some(
+            blocks
+                .blocks
+                .range(..=number)
+                .fold(parent_td, |td, (_, pending)| td + pending.block.difficulty),
+        ))
+    }
+}
+
+impl<P: BlockReader> HeaderProvider for OverlayProvider<P> {
+    fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
+        match self.pending((*block_hash).into(), |pending| pending.block.header.header.clone()) {
+            Some(header) => Ok(Some(header)),
+            None => self.database.header(block_hash),
+        }
+    }
+
+    fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
+        match self.pending(num.into(), |pending| pending.block.header.header.clone()) {
+            Some(header) => Ok(Some(header)),
+            None => self.database.header_by_number(num),
+        }
+    }
+
+    fn header_td(&self, hash: &BlockHash) -> Result<Option<U256>> {
+        match self.pending((*hash).into(), |pending| pending.block.number) {
+            Some(number) => self.pending_td(number),
+            None => self.database.header_td(hash),
+        }
+    }
+
+    fn header_td_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
+        match self.pending(number.into(), |pending| pending.block.number) {
+            Some(number) => self.pending_td(number),
+            None => self.database.header_td_by_number(number),
+        }
+    }
+
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> Result<Vec<Header>> {
+        self.overlay_range(
+            range,
+            |range| self.database.headers_range(range),
+            |pending| pending.block.header.header.clone(),
+        )
+    }
+
+    fn sealed_headers_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<SealedHeader>> {
+        self.overlay_range(
+            range,
+            |range| self.database.sealed_headers_range(range),
+            |pending| pending.block.header.clone(),
+        )
+    }
+
+    fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>> {
+        match self.pending(number.into(), |pending| pending.block.header.clone()) {
+            Some(header) => Ok(Some(header)),
+            None => self.database.sealed_header(number),
+        }
+    }
+
+    fn sealed_header_with_td(&self, id: BlockHashOrNumber) -> Result<Option<(SealedHeader, U256)>> {
+        let Some(header) = self.pending(id, |pending| pending.block.header.clone()) else {
+            return self.database.sealed_header_with_td(id)
+        };
+        Ok(self.pending_td(header.number)?.map(|td| (header, td)))
+    }
+}
+
+impl<P: BlockReader> BlockHashReader for OverlayProvider<P> {
+    fn block_hash(&self, number: BlockNumber) -> Result<Option<H256>> {
+        self.database.block_hash(number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
+        self.database.canonical_hashes_range(range)
+    }
+}
+
+impl<P: BlockReader> BlockNumReader for OverlayProvider<P> {
+    fn chain_info(&self) -> Result<ChainInfo> {
+        self.database.chain_info()
+    }
+
+    fn best_block_number(&self) -> Result<BlockNumber> {
+        self.database.best_block_number()
+    }
+
+    fn last_block_number(&self) -> Result<BlockNumber> {
+        self.database.last_block_number()
+    }
+
+    fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
+        match self.pending(hash.into(), |pending| pending.block.number) {
+            Some(number) => Ok(Some(number)),
+            None => self.database.block_number(hash),
+        }
+    }
+}
+
+impl<P: BlockReader> BlockReader for OverlayProvider<P> {
+    fn find_block_by_hash(&self, hash: H256, source: BlockSource) -> Result<Option<Block>> {
+        if source.is_pending() {
+            // the caller already has the hash, the block doesn't need to be sealed
+            if let Some(block) = self.pending(hash.into(), |pending| pending.block.block.clone()) {
+                return Ok(Some(block.unseal()))
+            }
+        }
+        if source.is_database() {
+            return self.database.find_block_by_hash(hash, source)
+        }
+        Ok(None)
+    }
+
+    fn block(&self, id: BlockHashOrNumber) -> Result<Option<Block>> {
+        match self.pending(id, |pending| pending.block.block.clone()) {
+            Some(block) => Ok(Some(block.unseal())),
+            None => self.database.block(id),
+        }
+    }
+
+    fn pending_block(&self) -> Result<Option<SealedBlock>> {
+        match self.pending.read().tip() {
+            Some(pending) => Ok(Some(pending.block.block.clone())),
+            None => self.database.pending_block(),
+        }
+    }
+
+    fn pending_block_and_receipts(&self) -> Result<Option<(SealedBlock, Vec<Receipt>)>> {
+        match self.pending.read().tip() {
+            Some(pending) => Ok(Some((pending.block.block.clone(), pending.receipts.clone()))),
+            None => self.database.pending_block_and_receipts(),
+        }
+    }
+
+    fn ommers(&self, id: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
+        match self.pending(id, |pending| pending.block.ommers.clone()) {
+            Some(ommers) => Ok(Some(ommers)),
+            None => self.database.ommers(id),
+        }
+    }
+
+    fn block_body_indices(&self, num: BlockNumber) -> Result<Option<StoredBlockBodyIndices>> {
+        self.database.block_body_indices(num)
+    }
+
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        self.database.block_body_indices_range(range)
+    }
+
+    fn block_with_senders(&self, number: BlockNumber) -> Result<Option<BlockWithSenders>> {
+        let pending = self.pending(number.into(), |pending| {
+            let (block, senders) = pending.block.clone().into_components();
+            BlockWithSenders { block: block.unseal(), senders }
+        });
+        match pending {
+            Some(block) => Ok(Some(block)),
+            None => self.database.block_with_senders(number),
+        }
+    }
+}
+
+impl<P: BlockReader> TransactionsProvider for OverlayProvider<P> {
+    fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>> {
+        self.database.transaction_id(tx_hash)
+    }
+
+    fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
+        self.database.transaction_by_id(id)
+    }
+
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        self.database.transaction_by_id_no_hash(id)
+    }
+
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        let pending = self.pending.read();
+        match pending.transaction(&hash) {
+            Some((pending, index)) => Ok(Some(pending.block.body[index].clone())),
+            None => self.database.transaction_by_hash(hash),
+        }
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let pending = self.pending.read();
+        let Some((pending, index)) = pending.transaction(&hash) else {
+            return self.database.transaction_by_hash_with_meta(hash)
+        };
+        Ok(Some((pending.block.body[index].clone(), pending.transaction_meta(index))))
+    }
+
+    fn transaction_block(&self, id: TxNumber) -> Result<Option<BlockNumber>> {
+        self.database.transaction_block(id)
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        match self.pending(block, |pending| pending.block.body.clone()) {
+            Some(transactions) => Ok(Some(transactions)),
+            None => self.database.transactions_by_block(block),
+        }
+    }
+
+    fn transactions_by_block_no_hash(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        let pending = self
+            .pending(block, |pending| pending.block.body.iter().cloned().map(Into::into).collect());
+        match pending {
+            Some(transactions) => Ok(Some(transactions)),
+            None => self.database.transactions_by_block_no_hash(block),
+        }
+    }
+
+    fn transactions_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<TransactionSigned>>> {
+        self.overlay_range(
+            range,
+            |range| self.database.transactions_by_block_range(range),
+            |pending| pending.block.body.clone(),
+        )
+    }
+
+    fn transactions_by_tx_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> Result<Vec<TransactionSignedNoHash>> {
+        self.database.transactions_by_tx_range(range)
+    }
+
+    fn senders_by_tx_range(&self, range: impl RangeBounds<TxNumber>) -> Result<Vec<Address>> {
+        self.database.senders_by_tx_range(range)
+    }
+
+    fn transaction_sender(&self, id: TxNumber) -> Result<Option<Address>> {
+        self.database.transaction_sender(id)
+    }
+}
+
+impl<P: BlockReader> ReceiptProvider for OverlayProvider<P> {
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        self.database.receipt(id)
+    }
+
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        let pending = self.pending.read();
+        match pending.transaction(&hash) {
+            Some((pending, index)) => Ok(pending.receipts.get(index).cloned()),
+            None => self.database.receipt_by_hash(hash),
+        }
+    }
+
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        let pending = self.pending.read();
+        let Some((pending, index)) = pending.transaction(&hash) else {
+            return self.database.receipt_by_hash_with_meta(hash)
+        };
+        Ok(pending.receipts.get(index).map(|receipt| {
+            (receipt.clone(), pending.transaction_meta(index), pending.gas_used(index, receipt))
+        }))
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        match self.pending(block, |pending| pending.receipts.clone()) {
+            Some(receipts) => Ok(Some(receipts)),
+            None => self.database.receipts_by_block(block),
+        }
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        self.overlay_range(
+            range,
+            |range| self.database.receipts_by_block_range(range),
+            |pending| pending.receipts.clone(),
+        )
+    }
+}
+
+impl<P: BlockReader> WithdrawalsProvider for OverlayProvider<P> {
+    fn withdrawals_by_block(
+        &self,
+        id: BlockHashOrNumber,
+        timestamp: u64,
+    ) -> Result<Option<Vec<Withdrawal>>> {
+        match self.pending(id, |pending| pending.block.withdrawals.clone()) {
+            Some(withdrawals) => Ok(withdrawals),
+            None => self.database.withdrawals_by_block(id, timestamp),
+        }
+    }
+
+    fn latest_withdrawal(&self) -> Result<Option<Withdrawal>> {
+        let pending = self.pending.read();
+        let latest = pending.blocks.values().rev().find_map(|pending| {
+            pending.block.withdrawals.as_ref().and_then(|withdrawals| withdrawals.last().cloned())
+        });
+        match latest {
+            Some(withdrawal) => Ok(Some(withdrawal)),
+            None => self.database.latest_withdrawal(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::blocks::insert_blocks, ProviderFactory};
+    use reth_db::{test_utils::create_test_rw_db, DatabaseEnv};
+    use reth_interfaces::test_utils::ChainFixture;
+    use reth_primitives::{Bytes, ChainSpecBuilder};
+
+    type Factory = ProviderFactory<Arc<DatabaseEnv>>;
+
+    /// Builds the blocks `0..=8` of the chain of the seed.
+    fn chain(seed: u64) -> Vec<SealedBlockWithSenders> {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        ChainFixture::new(chain_spec).blocks(8).txs_per_block(1..3).seed(seed).build()
+    }
+
+    fn database(blocks: &[SealedBlockWithSenders]) -> Factory {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().london_activated().build());
+        let factory = ProviderFactory::new(create_test_rw_db(), chain_spec);
+        let provider_rw = factory.provider_rw().unwrap();
+        insert_blocks(&provider_rw, blocks).unwrap();
+        provider_rw.commit().unwrap();
+        factory
+    }
+
+    /// Returns the receipts of the fixture transfers of the block.
+    fn receipts(block: &SealedBlockWithSenders) -> Vec<Receipt> {
+        ChainFixture::receipts(&block.body)
+    }
+
+    fn pending(blocks: &[SealedBlockWithSenders]) -> Arc<RwLock<PendingBlocks>> {
+        let mut pending = PendingBlocks::default();
+        for block in blocks {
+            pending.insert(block.clone(), receipts(block)).unwrap();
+        }
+        Arc::new(RwLock::new(pending))
+    }
+
+    /// Returns a sibling of the block with a different hash.
+    fn sibling(block: &SealedBlockWithSenders) -> SealedBlockWithSenders {
+        let mut header = block.header.header.clone();
+        header.extra_data = Bytes::from(b"sibling".to_vec());
+        SealedBlockWithSenders {
+            block: SealedBlock { header: header.seal_slow(), ..block.block.clone() },
+            senders: block.senders.clone(),
+        }
+    }
+
+    #[test]
+    fn overlay_falls_through_to_database() {
+        let blocks = chain(0);
+        let stored = database(&blocks);
+        let overlay = OverlayProvider::new(database(&blocks[..6]), pending(&blocks[6..]));
+
+        for number in 0..=8 {
+            assert_eq!(overlay.sealed_header(number), stored.sealed_header(number));
+            assert_eq!(overlay.header_td_by_number(number), stored.header_td_by_number(number));
+            assert_eq!(overlay.block(number.into()), stored.block(number.into()));
+        }
+        // pending blocks are not canonical
+        assert_eq!(overlay.block_hash(5), stored.block_hash(5));
+        assert_eq!(overlay.block_hash(6), Ok(None));
+        assert_eq!(
+            overlay.canonical_hashes_range((Bound::Unbounded, Bound::Unbounded)),
+            stored.canonical_hashes_range((Bound::Unbounded, Bound::Excluded(6)))
+        );
+        assert_eq!(overlay.headers_range(..), stored.headers_range(..));
+        assert_eq!(
+            overlay.transactions_by_block_range(3..=7),
+            stored.transactions_by_block_range(3..=7)
+        );
+        assert_eq!(overlay.best_block_number(), Ok(5));
+
+        let tip = &blocks[8];
+        let found = |hash, source| overlay.find_block_by_hash(hash, source).unwrap();
+        assert_eq!(found(tip.hash(), BlockSource::Pending), Some(tip.block.clone().unseal()));
+        assert_eq!(found(tip.hash(), BlockSource::Any), Some(tip.block.clone().unseal()));
+        assert_eq!(found(tip.hash(), BlockSource::Database), None);
+        assert_eq!(found(blocks[3].hash(), BlockSource::Pending), None);
+        assert_eq!(
+            found(blocks[3].hash(), BlockSource::Any),
+            Some(blocks[3].block.clone().unseal())
+        );
+        assert_eq!(overlay.block_number(tip.hash()), Ok(Some(8)));
+        assert_eq!(overlay.pending_block(), Ok(Some(tip.block.clone())));
+        assert_eq!(overlay.database().pending_block(), Ok(None));
+
+        // pending transactions are found by hash but have no transaction number
+        let index = blocks[7].body.len() - 1;
+        let transaction = &blocks[7].body[index];
+        let (receipt, meta, gas_used) =
+            overlay.receipt_by_hash_with_meta(transaction.hash()).unwrap().unwrap();
+        assert_eq!(overlay.transaction_by_hash(transaction.hash()), Ok(Some(transaction.clone())));
+        assert_eq!(overlay.transaction_id(transaction.hash()), Ok(None));
+        assert_eq!(receipt, receipts(&blocks[7])[index]);
+        assert_eq!((meta.block_number, meta.index, gas_used), (7, index as u64, 21_000));
+        assert_eq!(overlay.receipts_by_block(tip.hash().into()), Ok(Some(receipts(tip))));
+    }
+
+    #[test]
+    fn pending_block_shadows_database() {
+        let (blocks, other) = (chain(0), chain(1));
+        let (stored, shadowing) = (&blocks[6], &other[6]);
+        let pending = pending(&other[6..7]);
+        let overlay = OverlayProvider::new(database(&blocks[..7]), pending.clone());
+
+        // lookups by number prefer the pending block, lookups by hash find both, the canonical
+        // hash stays the stored one
+        assert_eq!(overlay.header_by_number(6), Ok(Some(shadowing.header.header.clone())));
+        assert_eq!(overlay.block_hash(6), Ok(Some(stored.hash())));
+        assert_eq!(
+            overlay.headers_range(5..=6),
+            Ok(vec![blocks[5].header.header.clone(), shadowing.header.header.clone()])
+        );
+        let found = |hash, source| overlay.find_block_by_hash(hash, source).unwrap();
+        assert_eq!(found(stored.hash(), BlockSource::Any), Some(stored.block.clone().unseal()));
+        assert_eq!(found(shadowing.hash(), BlockSource::Database), None);
+        // the parent of the pending block isn't stored
+        assert_eq!(overlay.header_td(&shadowing.hash()), Ok(None));
+
+        // the database is consulted again once the pending block is canonicalized
+        pending.write().canonicalized(6);
+        assert_eq!(overlay.header_by_number(6), Ok(Some(stored.header.header.clone())));
+        assert_eq!(found(shadowing.hash(), BlockSource::Any), None);
+        assert_eq!(overlay.pending_block(), Ok(None));
+    }
+
+    #[test]
+    fn pending_blocks_stay_contiguous() {
+        let blocks = chain(0);
+        let pending = pending(&blocks[6..]);
+        let mut pending = pending.write();
+        assert_eq!(pending.range(), Some(6..=8));
+
+        // a sibling of the tip replaces it
+        let tip = sibling(&blocks[8]);
+        pending.insert(tip.clone(), receipts(&tip)).unwrap();
+        assert_eq!(pending.by_hash(&blocks[8].hash()), None);
+        assert_eq!(pending.tip().map(|pending| pending.block.hash()), Some(tip.hash()));
+
+        // a sibling of a lower block removes its descendants
+        let fork = sibling(&blocks[7]);
+        pending.insert(fork.clone(), receipts(&fork)).unwrap();
+        assert_eq!(pending.range(), Some(6..=7));
+        assert_eq!(pending.by_hash(&tip.hash()), None);
+        assert_eq!(pending.transaction(&blocks[8].body[0].hash()), None);
+        assert_eq!(pending.get(7).map(|pending| pending.block.hash()), Some(fork.hash()));
+
+        // a block that skips the height above its pending parent is rejected
+        let mut header = blocks[8].header.header.clone();
+        header.parent_hash = fork.hash();
+        header.number = 9;
+        let detached = SealedBlockWithSenders {
+            block: SealedBlock { header: header.seal_slow(), ..blocks[8].block.clone() },
+            senders: blocks[8].senders.clone(),
+        };
+        assert_eq!(
+            pending.insert(detached.clone(), receipts(&detached)),
+            Err(ProviderError::NonContiguousPendingBlock { number: 9, expected: 8 }.into())
+        );
+        assert_eq!(pending.range(), Some(6..=7));
+
+        // a block that doesn't attach to the pending chain starts a new one
+        pending.insert(blocks[3].clone(), receipts(&blocks[3])).unwrap();
+        assert_eq!(pending.range(), Some(3..=3));
+        pending.canonicalized(3);
+        assert!(pending.is_empty());
+    }
+}
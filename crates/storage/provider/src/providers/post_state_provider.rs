@@ -4,6 +4,7 @@ use crate::{
 };
 use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{Account, Address, BlockNumber, Bytecode, Bytes, H256, U256};
+use std::ops::Bound;
 
 /// A state provider that either resolves to data in a wrapped [`crate::PostState`], or an
 /// underlying state provider.
@@ -34,7 +35,10 @@ impl<SP: StateProvider, PSDP: PostStateDataProvider> BlockHashReader
         self.state_provider.block_hash(block_number)
     }
 
-    fn canonical_hashes_range(&self, _start: BlockNumber, _end: BlockNumber) -> Result<Vec<H256>> {
+    fn canonical_hashes_range(
+        &self,
+        _range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
         unimplemented!()
     }
 }
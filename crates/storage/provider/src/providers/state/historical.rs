@@ -12,7 +12,7 @@ use reth_interfaces::Result;
 use reth_primitives::{
     Account, Address, BlockNumber, Bytecode, Bytes, StorageKey, StorageValue, H256,
 };
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Bound};
 
 /// State provider for a given transition id which takes a tx reference.
 ///
@@ -128,8 +128,10 @@ impl<'a, 'b, TX: DbTx<'a>> BlockHashReader for HistoricalStateProviderRef<'a, 'b
         self.tx.get::<tables::CanonicalHeaders>(number).map_err(Into::into)
     }
 
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
-        let range = start..end;
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
         self.tx
             .cursor_read::<tables::CanonicalHeaders>()
             .map(|mut cursor| {
@@ -38,7 +38,7 @@ macro_rules! delegate_provider_impls {
             }
             BlockHashReader $(where [$($generics)*])? {
                 fn block_hash(&self, number: u64) -> reth_interfaces::Result<Option<reth_primitives::H256>>;
-                fn canonical_hashes_range(&self, start: reth_primitives::BlockNumber, end: reth_primitives::BlockNumber) -> reth_interfaces::Result<Vec<reth_primitives::H256>>;
+                fn canonical_hashes_range(&self, range: (std::ops::Bound<reth_primitives::BlockNumber>, std::ops::Bound<reth_primitives::BlockNumber>)) -> reth_interfaces::Result<Vec<reth_primitives::H256>>;
             }
             StateProvider $(where [$($generics)*])?{
                 fn storage(&self, account: reth_primitives::Address, storage_key: reth_primitives::StorageKey) -> reth_interfaces::Result<Option<reth_primitives::StorageValue>>;
@@ -1,10 +1,17 @@
 //! Dummy blocks and data for tests
 
-use crate::{post_state::PostState, DatabaseProviderRW};
-use reth_db::{database::Database, models::StoredBlockBodyIndices, tables};
+use crate::{post_state::PostState, BlockWriter, DatabaseProviderRW, StageCheckpointWriter};
+use reth_db::{
+    database::Database,
+    models::{StoredBlockBodyIndices, StoredBlockWithdrawals},
+    tables,
+};
+use reth_interfaces::Result;
 use reth_primitives::{
-    hex_literal::hex, Account, BlockNumber, Bytes, Header, Log, Receipt, SealedBlock,
-    SealedBlockWithSenders, TxType, Withdrawal, H160, H256, U256,
+    hex_literal::hex,
+    stage::{StageCheckpoint, StageId},
+    Account, BlockNumber, Bytes, Header, Log, Receipt, SealedBlock, SealedBlockWithSenders, TxType,
+    Withdrawal, H160, H256, U256,
 };
 use reth_rlp::Decodable;
 use std::collections::BTreeMap;
@@ -26,7 +33,13 @@ pub fn assert_genesis_block<DB: Database>(provider: &DatabaseProviderRW<'_, DB>,
         vec![(0, StoredBlockBodyIndices::default())]
     );
     assert_eq!(tx.table::<tables::BlockOmmers>().unwrap(), vec![]);
-    assert_eq!(tx.table::<tables::BlockWithdrawals>().unwrap(), vec![]);
+    // the genesis block has a withdrawals row if it has a withdrawals list, even an empty one
+    let withdrawals =
+        g.withdrawals.clone().map(|withdrawals| StoredBlockWithdrawals { withdrawals });
+    assert_eq!(
+        tx.table::<tables::BlockWithdrawals>().unwrap(),
+        withdrawals.into_iter().map(|withdrawals| (n, withdrawals)).collect::<Vec<_>>()
+    );
     assert_eq!(tx.table::<tables::Transactions>().unwrap(), vec![]);
     assert_eq!(tx.table::<tables::TransactionBlock>().unwrap(), vec![]);
     assert_eq!(tx.table::<tables::TxHashNumber>().unwrap(), vec![]);
@@ -47,6 +60,19 @@ pub fn assert_genesis_block<DB: Database>(provider: &DatabaseProviderRW<'_, DB>,
     // SyncStage is not updated in tests
 }
 
+/// Inserts the blocks, e.g. built by a `ChainFixture`, with their senders and marks the last one
+/// as fully synced.
+pub fn insert_blocks<DB: Database>(
+    provider: &DatabaseProviderRW<'_, DB>,
+    blocks: &[SealedBlockWithSenders],
+) -> Result<()> {
+    let Some(tip) = blocks.last() else { return Ok(()) };
+    for block in blocks {
+        provider.insert_block(block.block.clone(), Some(block.senders.clone()))?;
+    }
+    provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(tip.number))
+}
+
 /// Test chain with genesis, blocks, execution results
 /// that have valid changesets.
 pub struct BlockChainTestData {
@@ -0,0 +1,694 @@
+//! Behavioral tests shared by all provider implementations.
+//!
+//! Every check takes a provider that was loaded with a [TestChain] and asserts the results of the
+//! read traits against the chain. The [provider_conformance](crate::provider_conformance) macro
+//! generates a test per check for a provider implementation:
+//!
+//! ```ignore
+//! provider_conformance!(mock, |chain: &TestChain| chain.mock_provider());
+//! ```
+//!
+//! New provider methods should come with a check here, so all implementations agree on empty
+//! blocks, missing rows and range bounds.
+
+use crate::{
+    test_utils::MockEthProvider, BlockReader, BlockWriter, DatabaseProviderRW, EvmEnvProvider,
+    StageCheckpointWriter,
+};
+use reth_db::{database::Database, models::StoredBlockBodyIndices, tables, transaction::DbTxMut};
+use reth_interfaces::{test_utils::ChainFixture, Result};
+use reth_primitives::{
+    stage::{StageCheckpoint, StageId},
+    Address, BlockHashOrNumber, BlockNumber, ChainSpec, ChainSpecBuilder, ForkCondition, Genesis,
+    Hardfork, Receipt, SealedBlock, SealedBlockWithSenders, TransactionMeta, TxNumber, H256, U256,
+};
+use reth_revm_primitives::primitives::{BlockEnv, CfgEnv, SpecId};
+use std::{
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
+
+/// Generates a test module running all conformance checks against a provider.
+///
+/// The first argument is the name of the module, the second one a closure that returns the
+/// provider loaded with the given [TestChain]. A subset of the checks is run with
+/// `provider_conformance!(@tests name, setup, [checks])`.
+#[macro_export]
+macro_rules! provider_conformance {
+    ($name:ident, $setup:expr) => {
+        $crate::provider_conformance!(
+            @tests $name, $setup,
+            [headers, block_numbers, blocks, transactions, receipts, withdrawals, evm_env]
+        );
+    };
+    (@tests $name:ident, $setup:expr, [$($check:ident),*]) => {
+        mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                fn $check() {
+                    let chain =
+                        $crate::test_utils::conformance::TestChainBuilder::default().build();
+                    let provider = ($setup)(&chain);
+                    $crate::test_utils::conformance::$check(&provider, &chain);
+                }
+            )*
+        }
+    };
+}
+
+/// Timestamp of the genesis block of the [TestChain], each block is 12 seconds after its parent.
+const GENESIS_TIMESTAMP: u64 = 1_000;
+
+/// Gas limit of the genesis block of the [TestChain].
+const GAS_LIMIT: u64 = 30_000_000;
+
+/// Builder of a deterministic [TestChain].
+///
+/// By default the chain has 100 blocks and Shanghai activates at block 50. Blocks have up to four
+/// transactions, every fifth block is empty and every seventh transaction fails.
+#[derive(Debug, Clone, Copy)]
+pub struct TestChainBuilder {
+    len: u64,
+    shanghai_block: BlockNumber,
+}
+
+impl Default for TestChainBuilder {
+    fn default() -> Self {
+        Self { len: 100, shanghai_block: 50 }
+    }
+}
+
+impl TestChainBuilder {
+    /// Sets the number of blocks, including the genesis block.
+    pub fn len(mut self, len: u64) -> Self {
+        self.len = len;
+        self
+    }
+
+    /// Sets the first block past Shanghai.
+    pub fn shanghai_block(mut self, shanghai_block: BlockNumber) -> Self {
+        self.shanghai_block = shanghai_block;
+        self
+    }
+
+    /// Builds the chain.
+    ///
+    /// The chain is a [ChainFixture] that is past the merge from genesis, so the chain is the
+    /// same on every build.
+    ///
+    /// # Panics
+    ///
+    /// If the length is zero, the chain always has a genesis block.
+    pub fn build(self) -> TestChain {
+        assert!(self.len >= 1, "the test chain needs a genesis block");
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::mainnet()
+                .genesis(
+                    Genesis::default().with_timestamp(GENESIS_TIMESTAMP).with_gas_limit(GAS_LIMIT),
+                )
+                .paris_activated()
+                .with_fork(
+                    Hardfork::Shanghai,
+                    ForkCondition::Timestamp(block_timestamp(self.shanghai_block)),
+                )
+                .build(),
+        );
+
+        let fixture = ChainFixture::new(chain_spec.clone())
+            .blocks(self.len - 1)
+            .txs_per_block(0..5)
+            .with_empty_blocks(5)
+            .with_failed_txs(7);
+        let (blocks, senders): (Vec<_>, Vec<_>) =
+            fixture.build().into_iter().map(SealedBlockWithSenders::into_components).unzip();
+        let receipts = blocks.iter().map(|block| ChainFixture::receipts(&block.body)).collect();
+        TestChain { chain_spec, blocks, senders, receipts, shanghai_block: self.shanghai_block }
+    }
+}
+
+/// Returns the timestamp of the block of a [TestChain].
+fn block_timestamp(number: BlockNumber) -> u64 {
+    GENESIS_TIMESTAMP + number * 12
+}
+
+/// A deterministic chain that conformance checks are run against, see [TestChainBuilder].
+#[derive(Debug, Clone)]
+pub struct TestChain {
+    /// The chain spec of the chain.
+    pub chain_spec: Arc<ChainSpec>,
+    /// The blocks, starting at genesis.
+    pub blocks: Vec<SealedBlock>,
+    /// The senders of the transactions of each block.
+    pub senders: Vec<Vec<Address>>,
+    /// The receipts of each block.
+    pub receipts: Vec<Vec<Receipt>>,
+    /// The first block past Shanghai.
+    pub shanghai_block: BlockNumber,
+}
+
+impl TestChain {
+    /// Returns the last block of the chain.
+    pub fn tip(&self) -> &SealedBlock {
+        self.blocks.last().expect("chain is not empty")
+    }
+
+    /// Returns the number of the block after the tip, which no provider knows.
+    pub fn next_number(&self) -> BlockNumber {
+        self.blocks.len() as BlockNumber
+    }
+
+    /// Returns the total number of transactions of the chain.
+    pub fn tx_count(&self) -> TxNumber {
+        self.blocks.iter().map(|block| block.body.len() as TxNumber).sum()
+    }
+
+    /// Returns the body indices of all blocks.
+    pub fn body_indices(&self) -> Vec<StoredBlockBodyIndices> {
+        let mut first_tx_num = 0;
+        self.blocks
+            .iter()
+            .map(|block| {
+                let tx_count = block.body.len() as u64;
+                let indices = StoredBlockBodyIndices { first_tx_num, tx_count };
+                first_tx_num += tx_count;
+                indices
+            })
+            .collect()
+    }
+
+    /// Returns all transactions with their transaction number, their block and their index in
+    /// the block.
+    pub fn transactions(&self) -> impl Iterator<Item = (TxNumber, &SealedBlock, usize)> {
+        self.blocks
+            .iter()
+            .flat_map(|block| (0..block.body.len()).map(move |index| (block, index)))
+            .enumerate()
+            .map(|(tx_num, (block, index))| (tx_num as TxNumber, block, index))
+    }
+
+    /// Inserts the blocks and receipts into the database and marks the tip as fully synced.
+    pub fn insert_into<DB: Database>(&self, provider: &DatabaseProviderRW<'_, DB>) -> Result<()> {
+        for (block, receipts) in self.blocks.iter().zip(&self.receipts) {
+            let body = provider.insert_block(block.clone(), None)?;
+            for (tx_num, receipt) in body.tx_num_range().zip(receipts.iter().cloned()) {
+                provider.tx_ref().put::<tables::Receipts>(tx_num, receipt)?;
+            }
+        }
+        provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(self.tip().number))
+    }
+
+    /// Returns a [MockEthProvider] with the blocks and receipts.
+    pub fn mock_provider(&self) -> MockEthProvider {
+        let provider = MockEthProvider::default().with_chain_spec(self.chain_spec.clone());
+        for (block, receipts) in self.blocks.iter().zip(&self.receipts) {
+            provider.add_block(block.hash(), block.clone().unseal());
+            provider.add_receipts(block.hash(), receipts.clone());
+        }
+        provider
+    }
+}
+
+/// Returns the block ranges every range query is checked with, including empty ranges and ranges
+/// past the tip.
+fn block_ranges(chain: &TestChain) -> Vec<(Bound<u64>, Bound<u64>)> {
+    let tip = chain.tip().number;
+    vec![
+        (Bound::Unbounded, Bound::Unbounded),
+        (Bound::Included(0), Bound::Included(0)),
+        (Bound::Included(10), Bound::Excluded(20)),
+        (Bound::Included(10), Bound::Included(20)),
+        (Bound::Unbounded, Bound::Excluded(5)),
+        (Bound::Unbounded, Bound::Included(5)),
+        (Bound::Included(tip - 4), Bound::Unbounded),
+        (Bound::Included(tip - 4), Bound::Included(tip + 10)),
+        (Bound::Included(20), Bound::Excluded(20)),
+        (Bound::Included(20), Bound::Excluded(10)),
+        (Bound::Included(tip + 1), Bound::Included(tip + 10)),
+    ]
+}
+
+/// Returns the items of the chain whose number is in the range.
+fn in_range<T>(
+    items: impl IntoIterator<Item = (u64, T)>,
+    range: &(Bound<u64>, Bound<u64>),
+) -> Vec<T> {
+    items.into_iter().filter(|(number, _)| range.contains(number)).map(|(_, item)| item).collect()
+}
+
+/// Returns a hash that no block or transaction of the chain has.
+fn unknown_hash() -> H256 {
+    H256::repeat_byte(0xff)
+}
+
+/// Checks the [HeaderProvider](crate::HeaderProvider) methods.
+pub fn headers<P: BlockReader>(provider: &P, chain: &TestChain) {
+    for block in &chain.blocks {
+        assert_eq!(
+            provider.header_by_number(block.number).unwrap().as_ref(),
+            Some(&block.header.header)
+        );
+        assert_eq!(provider.header(&block.hash()).unwrap().as_ref(), Some(&block.header.header));
+        assert_eq!(provider.sealed_header(block.number).unwrap().as_ref(), Some(&block.header));
+        assert!(provider.is_known(&block.hash()).unwrap());
+    }
+    assert_eq!(provider.header_by_number(chain.next_number()).unwrap(), None);
+    assert_eq!(provider.header(&unknown_hash()).unwrap(), None);
+    assert_eq!(provider.sealed_header(chain.next_number()).unwrap(), None);
+
+    for range in block_ranges(chain) {
+        let headers = chain.blocks.iter().map(|block| (block.number, block.header.clone()));
+        let expected = in_range(headers, &range);
+        assert_eq!(provider.sealed_headers_range(range).unwrap(), expected, "{range:?}");
+        let expected = expected.into_iter().map(|header| header.header).collect::<Vec<_>>();
+        assert_eq!(provider.headers_range(range).unwrap(), expected, "{range:?}");
+    }
+}
+
+/// Checks the [BlockHashReader](crate::BlockHashReader) and
+/// [BlockNumReader](crate::BlockNumReader) methods.
+pub fn block_numbers<P: BlockReader>(provider: &P, chain: &TestChain) {
+    let tip = chain.tip();
+    let chain_info = provider.chain_info().unwrap();
+    assert_eq!((chain_info.best_number, chain_info.best_hash), (tip.number, tip.hash()));
+    assert_eq!(provider.best_block_number().unwrap(), tip.number);
+    assert_eq!(provider.last_block_number().unwrap(), tip.number);
+
+    for block in &chain.blocks {
+        assert_eq!(provider.block_hash(block.number).unwrap(), Some(block.hash()));
+        assert_eq!(provider.block_number(block.hash()).unwrap(), Some(block.number));
+    }
+    assert_eq!(provider.block_hash(chain.next_number()).unwrap(), None);
+    assert_eq!(provider.block_number(unknown_hash()).unwrap(), None);
+
+    for range in block_ranges(chain) {
+        let hashes = chain.blocks.iter().map(|block| (block.number, block.hash()));
+        assert_eq!(
+            provider.canonical_hashes_range(range).unwrap(),
+            in_range(hashes, &range),
+            "{range:?}"
+        );
+    }
+}
+
+/// Checks the [BlockReader] methods.
+pub fn blocks<P: BlockReader>(provider: &P, chain: &TestChain) {
+    let body_indices = chain.body_indices();
+    for (block, senders) in chain.blocks.iter().zip(&chain.senders) {
+        let expected = block.clone().unseal();
+        assert_eq!(provider.block(block.number.into()).unwrap().as_ref(), Some(&expected));
+        assert_eq!(provider.block(block.hash().into()).unwrap().as_ref(), Some(&expected));
+        assert_eq!(
+            provider.block_body_indices(block.number).unwrap(),
+            Some(body_indices[block.number as usize])
+        );
+        // the test chain has no ommers, whether that is an empty list or no entry is up to the
+        // provider
+        assert_eq!(provider.ommers(block.number.into()).unwrap().unwrap_or_default(), vec![]);
+
+        let with_senders = provider.block_with_senders(block.number).unwrap().unwrap();
+        assert_eq!(with_senders.block.header, block.header.header);
+        assert_eq!(&with_senders.senders, senders);
+    }
+    assert_eq!(provider.block(chain.next_number().into()).unwrap(), None);
+    assert_eq!(provider.block(unknown_hash().into()).unwrap(), None);
+    assert_eq!(provider.block_body_indices(chain.next_number()).unwrap(), None);
+    assert_eq!(provider.ommers(chain.next_number().into()).unwrap(), None);
+
+    for range in block_ranges(chain) {
+        let indices = body_indices
+            .iter()
+            .enumerate()
+            .map(|(number, indices)| (number as u64, (number as u64, *indices)));
+        assert_eq!(
+            provider.block_body_indices_range(range).unwrap(),
+            in_range(indices, &range),
+            "{range:?}"
+        );
+    }
+}
+
+/// Checks the [TransactionsProvider](crate::TransactionsProvider) methods.
+pub fn transactions<P: BlockReader>(provider: &P, chain: &TestChain) {
+    for (tx_num, block, index) in chain.transactions() {
+        let tx = &block.body[index];
+        assert_eq!(provider.transaction_id(tx.hash()).unwrap(), Some(tx_num));
+        assert_eq!(provider.transaction_by_id(tx_num).unwrap().as_ref(), Some(tx));
+        assert_eq!(provider.transaction_by_id_no_hash(tx_num).unwrap(), Some(tx.clone().into()));
+        assert_eq!(provider.transaction_by_hash(tx.hash()).unwrap().as_ref(), Some(tx));
+        assert_eq!(provider.transaction_block(tx_num).unwrap(), Some(block.number));
+        assert_eq!(
+            provider.transaction_sender(tx_num).unwrap(),
+            Some(chain.senders[block.number as usize][index])
+        );
+
+        let meta = TransactionMeta {
+            tx_hash: tx.hash(),
+            index: index as u64,
+            block_hash: block.hash(),
+            block_number: block.number,
+            base_fee: block.base_fee_per_gas,
+            gas_used: None,
+            success: None,
+        };
+        assert_eq!(
+            provider.transaction_by_hash_with_meta(tx.hash()).unwrap(),
+            Some((tx.clone(), meta))
+        );
+    }
+    let tx_count = chain.tx_count();
+    assert_eq!(provider.transaction_id(unknown_hash()).unwrap(), None);
+    assert_eq!(provider.transaction_by_id(tx_count).unwrap(), None);
+    assert_eq!(provider.transaction_by_hash(unknown_hash()).unwrap(), None);
+    assert_eq!(provider.transaction_by_hash_with_meta(unknown_hash()).unwrap(), None);
+    assert_eq!(provider.transaction_block(tx_count).unwrap(), None);
+
+    for block in &chain.blocks {
+        // empty blocks have an empty list of transactions
+        assert_eq!(
+            provider.transactions_by_block(block.number.into()).unwrap(),
+            Some(block.body.clone())
+        );
+        assert_eq!(
+            provider.transactions_by_block(block.hash().into()).unwrap(),
+            Some(block.body.clone())
+        );
+    }
+    assert_eq!(provider.transactions_by_block(chain.next_number().into()).unwrap(), None);
+
+    for range in block_ranges(chain) {
+        let transactions = chain.blocks.iter().map(|block| (block.number, block.body.clone()));
+        assert_eq!(
+            provider.transactions_by_block_range(range).unwrap(),
+            in_range(transactions, &range),
+            "{range:?}"
+        );
+
+        // the block ranges are reused as transaction ranges
+        let transactions =
+            chain.transactions().map(|(tx_num, block, index)| (tx_num, block.body[index].clone()));
+        let expected = in_range(transactions, &range);
+        assert_eq!(
+            provider.transactions_by_tx_range(range).unwrap(),
+            expected.iter().cloned().map(Into::into).collect::<Vec<_>>(),
+            "{range:?}"
+        );
+        let senders = chain
+            .transactions()
+            .map(|(tx_num, block, index)| (tx_num, chain.senders[block.number as usize][index]));
+        assert_eq!(
+            provider.senders_by_tx_range(range).unwrap(),
+            in_range(senders, &range),
+            "{range:?}"
+        );
+    }
+}
+
+/// Checks the [ReceiptProvider](crate::ReceiptProvider) methods.
+pub fn receipts<P: BlockReader>(provider: &P, chain: &TestChain) {
+    for (tx_num, block, index) in chain.transactions() {
+        let tx = &block.body[index];
+        let receipts = &chain.receipts[block.number as usize];
+        let receipt = &receipts[index];
+        assert_eq!(provider.receipt(tx_num).unwrap().as_ref(), Some(receipt));
+        assert_eq!(provider.receipt_by_hash(tx.hash()).unwrap().as_ref(), Some(receipt));
+
+        let gas_used = match index.checked_sub(1) {
+            Some(prev) => receipt.cumulative_gas_used - receipts[prev].cumulative_gas_used,
+            None => receipt.cumulative_gas_used,
+        };
+        let meta = TransactionMeta {
+            tx_hash: tx.hash(),
+            index: index as u64,
+            block_hash: block.hash(),
+            block_number: block.number,
+            base_fee: block.base_fee_per_gas,
+            gas_used: Some(gas_used),
+            success: Some(receipt.success),
+        };
+        assert_eq!(
+            provider.receipt_by_hash_with_meta(tx.hash()).unwrap(),
+            Some((receipt.clone(), meta, gas_used))
+        );
+    }
+    assert_eq!(provider.receipt(chain.tx_count()).unwrap(), None);
+    assert_eq!(provider.receipt_by_hash(unknown_hash()).unwrap(), None);
+    assert_eq!(provider.receipt_by_hash_with_meta(unknown_hash()).unwrap(), None);
+
+    for (block, receipts) in chain.blocks.iter().zip(&chain.receipts) {
+        // empty blocks have an empty list of receipts
+        assert_eq!(
+            provider.receipts_by_block(block.number.into()).unwrap().as_ref(),
+            Some(receipts)
+        );
+        assert_eq!(
+            provider.receipts_by_block(block.hash().into()).unwrap().as_ref(),
+            Some(receipts)
+        );
+    }
+    assert_eq!(provider.receipts_by_block(chain.next_number().into()).unwrap(), None);
+
+    for range in block_ranges(chain) {
+        let receipts = chain.blocks.iter().map(|block| block.number).zip(chain.receipts.clone());
+        assert_eq!(
+            provider.receipts_by_block_range(range).unwrap(),
+            in_range(receipts, &range),
+            "{range:?}"
+        );
+    }
+}
+
+/// Checks the [WithdrawalsProvider](crate::WithdrawalsProvider) methods.
+pub fn withdrawals<P: BlockReader>(provider: &P, chain: &TestChain) {
+    for block in &chain.blocks {
+        // blocks before Shanghai have no withdrawals, blocks after it a possibly empty list
+        assert_eq!(
+            provider.withdrawals_by_block(block.number.into(), block.timestamp).unwrap(),
+            block.withdrawals,
+            "block #{}",
+            block.number
+        );
+        assert_eq!(
+            provider.withdrawals_by_block(block.hash().into(), block.timestamp).unwrap(),
+            block.withdrawals
+        );
+    }
+    let id = BlockHashOrNumber::Number(chain.next_number());
+    assert_eq!(
+        provider.withdrawals_by_block(id, block_timestamp(chain.next_number())).unwrap(),
+        None
+    );
+
+    let latest = chain.blocks.iter().rev().find_map(|block| block.withdrawals.as_ref()?.last());
+    assert_eq!(provider.latest_withdrawal().unwrap().as_ref(), latest);
+}
+
+/// Checks the [EvmEnvProvider] methods.
+pub fn evm_env<P: BlockReader + EvmEnvProvider>(provider: &P, chain: &TestChain) {
+    let mut envs = Vec::new();
+    for block in &chain.blocks {
+        let mut cfg = CfgEnv::default();
+        let mut block_env = BlockEnv::default();
+        provider.fill_env_at(&mut cfg, &mut block_env, block.number.into()).unwrap();
+
+        let spec_id =
+            if block.number >= chain.shanghai_block { SpecId::SHANGHAI } else { SpecId::MERGE };
+        assert_eq!(cfg.spec_id, spec_id, "block #{}", block.number);
+        assert_eq!(cfg.chain_id, U256::from(chain.chain_spec.chain.id()));
+        assert_eq!(block_env.number, U256::from(block.number));
+        assert_eq!(block_env.timestamp, U256::from(block.timestamp));
+        assert_eq!(block_env.coinbase, block.beneficiary);
+        assert_eq!(block_env.gas_limit, U256::from(block.gas_limit));
+        assert_eq!(block_env.basefee, U256::from(block.base_fee_per_gas.unwrap()));
+        assert_eq!(block_env.prevrandao, Some(block.mix_hash));
+
+        let (mut by_hash_cfg, mut by_hash_block_env) = (CfgEnv::default(), BlockEnv::default());
+        provider
+            .fill_env_at(&mut by_hash_cfg, &mut by_hash_block_env, block.hash().into())
+            .unwrap();
+        assert_eq!((by_hash_cfg.spec_id, &by_hash_block_env), (spec_id, &block_env));
+        envs.push((block.number, (spec_id, block_env)));
+    }
+    let (mut cfg, mut block_env) = (CfgEnv::default(), BlockEnv::default());
+    assert!(provider.fill_env_at(&mut cfg, &mut block_env, chain.next_number().into()).is_err());
+
+    for range in block_ranges(chain) {
+        let filled = provider
+            .fill_envs_for_range(range)
+            .unwrap()
+            .into_iter()
+            .map(|(cfg, block_env)| (cfg.spec_id, block_env))
+            .collect::<Vec<_>>();
+        assert_eq!(filled, in_range(envs.clone(), &range), "{range:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TestChain, TestChainBuilder};
+    use crate::{
+        BlockHashReader, BlockNumReader, OverlayProvider, PendingBlocks, ProviderFactory,
+        ReceiptProvider, TransactionsProvider,
+    };
+    use parking_lot::RwLock;
+    use reth_db::{test_utils::create_test_rw_db, DatabaseEnv};
+    use reth_primitives::SealedBlockWithSenders;
+    use std::{ops::Bound, sync::Arc};
+
+    fn factory(chain: &TestChain) -> ProviderFactory<Arc<DatabaseEnv>> {
+        let factory = ProviderFactory::new(create_test_rw_db(), chain.chain_spec.clone());
+        let provider_rw = factory.provider_rw().unwrap();
+        chain.insert_into(&provider_rw).unwrap();
+        provider_rw.commit().unwrap();
+        factory
+    }
+
+    /// Returns an [OverlayProvider] with the first `stored` blocks of the chain in the database
+    /// and the others pending.
+    fn overlay(
+        chain: &TestChain,
+        stored: usize,
+    ) -> OverlayProvider<ProviderFactory<Arc<DatabaseEnv>>> {
+        let mut stored_chain = chain.clone();
+        stored_chain.blocks.truncate(stored);
+        stored_chain.senders.truncate(stored);
+        stored_chain.receipts.truncate(stored);
+
+        let mut pending = PendingBlocks::default();
+        for (index, block) in chain.blocks.iter().enumerate().skip(stored) {
+            let senders = chain.senders[index].clone();
+            let block = SealedBlockWithSenders { block: block.clone(), senders };
+            pending.insert(block, chain.receipts[index].clone()).unwrap();
+        }
+        OverlayProvider::new(factory(&stored_chain), Arc::new(RwLock::new(pending)))
+    }
+
+    crate::provider_conformance!(database, |chain: &TestChain| factory(chain));
+
+    crate::provider_conformance!(mock, |chain: &TestChain| chain.mock_provider());
+
+    // the overlay provider doesn't fill evm environments
+    crate::provider_conformance!(
+        @tests overlay, |chain: &TestChain| overlay(chain, chain.blocks.len()),
+        [headers, block_numbers, blocks, transactions, receipts, withdrawals]
+    );
+
+    // pending blocks have no transaction numbers and body indices, and the chain info is the one
+    // of the database, the other checks are pinned by the `overlay_pending_*` tests below
+    crate::provider_conformance!(
+        @tests overlay_pending, |chain: &TestChain| overlay(chain, chain.blocks.len() - 5),
+        [headers, withdrawals]
+    );
+
+    /// Returns the test chain and an [OverlayProvider] with its last five blocks pending.
+    fn overlay_pending() -> (TestChain, OverlayProvider<ProviderFactory<Arc<DatabaseEnv>>>, u64) {
+        let chain = TestChainBuilder::default().build();
+        let stored = chain.blocks.len() - 5;
+        let provider = overlay(&chain, stored);
+        (chain, provider, stored as u64)
+    }
+
+    #[test]
+    fn test_chain_has_empty_blocks_and_failed_receipts() {
+        let chain = TestChainBuilder::default().build();
+        assert!(chain.blocks.iter().step_by(5).all(|block| block.body.is_empty()));
+        assert!(chain.receipts.iter().flatten().any(|receipt| !receipt.success));
+        assert!(chain.receipts.iter().flatten().any(|receipt| receipt.success));
+    }
+
+    #[test]
+    #[should_panic(expected = "genesis block")]
+    fn test_chain_without_blocks() {
+        TestChainBuilder::default().len(0).build();
+    }
+
+    #[test]
+    fn overlay_pending_block_numbers() {
+        let (chain, provider, stored) = overlay_pending();
+
+        // the chain info is the one of the database
+        let stored_tip = &chain.blocks[stored as usize - 1];
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!(
+            (chain_info.best_number, chain_info.best_hash),
+            (stored_tip.number, stored_tip.hash())
+        );
+        assert_eq!(provider.best_block_number().unwrap(), stored_tip.number);
+        assert_eq!(provider.last_block_number().unwrap(), stored_tip.number);
+
+        // pending blocks are found by hash but are not canonical
+        for block in &chain.blocks {
+            let canonical = (block.number < stored).then_some(block.hash());
+            assert_eq!(provider.block_number(block.hash()).unwrap(), Some(block.number));
+            assert_eq!(provider.block_hash(block.number).unwrap(), canonical);
+        }
+        assert_eq!(
+            provider.canonical_hashes_range((Bound::Unbounded, Bound::Unbounded)).unwrap(),
+            chain.blocks[..stored as usize].iter().map(|block| block.hash()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn overlay_pending_transactions() {
+        let (chain, provider, stored) = overlay_pending();
+
+        for (tx_num, block, index) in chain.transactions() {
+            let tx = &block.body[index];
+            let pending = block.number >= stored;
+            assert_eq!(provider.transaction_by_hash(tx.hash()).unwrap().as_ref(), Some(tx));
+
+            // pending transactions have no transaction number
+            assert_eq!(provider.transaction_id(tx.hash()).unwrap(), (!pending).then_some(tx_num));
+            assert_eq!(provider.transaction_by_id(tx_num).unwrap().is_some(), !pending);
+            assert_eq!(
+                provider.transaction_block(tx_num).unwrap(),
+                (!pending).then_some(block.number)
+            );
+
+            // the metadata of pending transactions has the outcome of their receipt
+            let receipt = &chain.receipts[block.number as usize][index];
+            let (_, meta) = provider.transaction_by_hash_with_meta(tx.hash()).unwrap().unwrap();
+            assert_eq!((meta.block_hash, meta.index), (block.hash(), index as u64));
+            assert_eq!(meta.success, pending.then_some(receipt.success));
+        }
+
+        for block in &chain.blocks {
+            assert_eq!(
+                provider.transactions_by_block(block.number.into()).unwrap().as_ref(),
+                Some(&block.body)
+            );
+        }
+        assert_eq!(
+            provider.transactions_by_block_range(..).unwrap(),
+            chain.blocks.iter().map(|block| block.body.clone()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn overlay_pending_receipts() {
+        let (chain, provider, stored) = overlay_pending();
+
+        for (tx_num, block, index) in chain.transactions() {
+            let tx = &block.body[index];
+            let receipt = &chain.receipts[block.number as usize][index];
+            assert_eq!(provider.receipt_by_hash(tx.hash()).unwrap().as_ref(), Some(receipt));
+
+            // pending receipts are only found by hash or block
+            let pending = block.number >= stored;
+            assert_eq!(provider.receipt(tx_num).unwrap().as_ref(), (!pending).then_some(receipt));
+
+            let (_, meta, _) = provider.receipt_by_hash_with_meta(tx.hash()).unwrap().unwrap();
+            assert_eq!(meta.success, Some(receipt.success));
+        }
+
+        for (block, receipts) in chain.blocks.iter().zip(&chain.receipts) {
+            assert_eq!(
+                provider.receipts_by_block(block.hash().into()).unwrap().as_ref(),
+                Some(receipts)
+            );
+        }
+        assert_eq!(provider.receipts_by_block_range(..).unwrap(), chain.receipts);
+    }
+}
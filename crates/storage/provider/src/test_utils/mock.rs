@@ -1,34 +1,61 @@
 use crate::{
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    EvmEnvProvider, HeaderProvider, PostState, PostStateDataProvider, ReceiptProviderIdExt,
-    StateProvider, StateProviderBox, StateProviderFactory, StateRootProvider, TransactionsProvider,
-    WithdrawalsProvider,
+    AccountReader, BlockEnvOverrides, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader,
+    BlockReaderIdExt, EvmEnvProvider, HeaderProvider, NextBlockAttributes, PostState,
+    PostStateDataProvider, ReceiptProviderIdExt, StateProvider, StateProviderBox,
+    StateProviderFactory, StateRootProvider, TransactionsProvider, WithdrawalsProvider,
 };
 use parking_lot::Mutex;
 use reth_db::models::StoredBlockBodyIndices;
 use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{
-    keccak256, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber,
-    BlockWithSenders, Bytecode, Bytes, ChainInfo, Header, Receipt, SealedBlock, SealedHeader,
-    StorageKey, StorageValue, TransactionMeta, TransactionSigned, TxHash, TxNumber, H256, U256,
+    constants::{EMPTY_RECEIPTS, EMPTY_TRANSACTIONS, EMPTY_WITHDRAWALS},
+    keccak256, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash,
+    BlockNumber, BlockNumberOrTag, BlockWithSenders, Bytecode, Bytes, ChainInfo, ChainSpec, Head,
+    Header, Receipt, SealedBlock, SealedHeader, StorageKey, StorageValue, TransactionMeta,
+    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, EMPTY_OMMER_ROOT,
+    H256, MAINNET, SENDER_RECOVERY_CHUNK_SIZE, U256,
+};
+use reth_revm_primitives::{
+    config::revm_spec,
+    env::{fill_block_env, fill_block_env_with_coinbase, fill_cfg_and_block_env, fill_cfg_env},
+    primitives::{BlockEnv, CfgEnv, SpecId},
 };
-use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
 use std::{
     collections::{BTreeMap, HashMap},
-    ops::RangeBounds,
+    ops::{Bound, RangeBounds},
     sync::Arc,
 };
 
 /// A mock implementation for Provider interfaces.
-#[derive(Debug, Clone, Default)]
+///
+/// Transaction numbers and body indices are not stored, they are derived from the stored blocks
+/// ordered by number, so they stay consistent with the blocks no matter in which order blocks are
+/// added. Like in the database, only one block per number is expected.
+#[derive(Debug, Clone)]
 pub struct MockEthProvider {
     /// Local block store
     pub blocks: Arc<Mutex<HashMap<H256, Block>>>,
     /// Local header store
     pub headers: Arc<Mutex<HashMap<H256, Header>>>,
+    /// Local receipt store, by block hash
+    pub receipts: Arc<Mutex<HashMap<H256, Vec<Receipt>>>>,
     /// Local account store
     pub accounts: Arc<Mutex<HashMap<Address, ExtendedAccount>>>,
+    /// The chain spec used for withdrawals, ommers and the EVM environment
+    pub chain_spec: Arc<ChainSpec>,
+}
+
+impl Default for MockEthProvider {
+    fn default() -> Self {
+        Self {
+            blocks: Default::default(),
+            headers: Default::default(),
+            receipts: Default::default(),
+            accounts: Default::default(),
+            chain_spec: MAINNET.clone(),
+        }
+    }
 }
 
 /// An extended account for local store
@@ -69,6 +96,12 @@ impl ExtendedAccount {
 }
 
 impl MockEthProvider {
+    /// Sets the chain spec of the provider.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
     /// Add block to local block store
     pub fn add_block(&self, hash: H256, block: Block) {
         self.add_header(hash, block.header.clone());
@@ -95,6 +128,160 @@ impl MockEthProvider {
         }
     }
 
+    /// Add the receipts of the block with the given hash to the local receipt store.
+    pub fn add_receipts(&self, hash: H256, receipts: Vec<Receipt>) {
+        self.receipts.lock().insert(hash, receipts);
+    }
+
+    /// Extends the chain by `n` empty blocks on top of the best block, starting at the genesis
+    /// block of the chain spec if there are no blocks yet.
+    ///
+    /// The new blocks copy the difficulty and gas limit of their parent, so proof-of-work chains
+    /// stay before the merge. Returns the new blocks.
+    pub fn extend_chain(&self, n: u64) -> Vec<SealedBlock> {
+        let best = self
+            .blocks
+            .lock()
+            .iter()
+            .max_by_key(|(_, block)| block.number)
+            .map(|(hash, block)| block.header.clone().seal(*hash));
+        let mut parent = match best {
+            Some(header) => header,
+            None => {
+                let genesis = self.chain_spec.sealed_genesis_header();
+                let withdrawals = genesis.withdrawals_root.map(|_| Vec::new());
+                let block =
+                    Block { header: genesis.header.clone(), withdrawals, ..Default::default() };
+                self.add_block(genesis.hash, block);
+                self.add_receipts(genesis.hash, Vec::new());
+                genesis
+            }
+        };
+
+        let mut blocks = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            let timestamp = parent.timestamp + 12;
+            let shanghai = self.chain_spec.is_shanghai_activated_at_timestamp(timestamp);
+            let header = Header {
+                parent_hash: parent.hash,
+                ommers_hash: EMPTY_OMMER_ROOT,
+                beneficiary: parent.beneficiary,
+                state_root: parent.state_root,
+                transactions_root: EMPTY_TRANSACTIONS,
+                receipts_root: EMPTY_RECEIPTS,
+                withdrawals_root: shanghai.then_some(EMPTY_WITHDRAWALS),
+                difficulty: parent.difficulty,
+                number: parent.number + 1,
+                gas_limit: parent.gas_limit,
+                timestamp,
+                base_fee_per_gas: self.chain_spec.next_block_base_fee(&parent),
+                ..Default::default()
+            }
+            .seal_slow();
+            let block = SealedBlock {
+                header: header.clone(),
+                withdrawals: shanghai.then(Vec::new),
+                ..Default::default()
+            };
+            self.add_block(header.hash, block.clone().unseal());
+            self.add_receipts(header.hash, Vec::new());
+            blocks.push(block);
+            parent = header;
+        }
+        blocks
+    }
+
+    /// Calls `f` for every stored transaction in the order of the transaction numbers, until it
+    /// returns a value.
+    ///
+    /// `f` is called with the transaction number, the hash of the block, the block, and the index
+    /// of the transaction in the block.
+    fn find_transaction<T>(
+        &self,
+        mut f: impl FnMut(TxNumber, &H256, &Block, usize) -> Option<T>,
+    ) -> Option<T> {
+        let lock = self.blocks.lock();
+        let mut blocks: Vec<_> = lock.iter().collect();
+        blocks.sort_by_key(|(_, block)| block.number);
+
+        let mut tx_num = 0;
+        for (hash, block) in blocks {
+            for index in 0..block.body.len() {
+                if let Some(value) = f(tx_num, hash, block, index) {
+                    return Some(value)
+                }
+                tx_num += 1;
+            }
+        }
+        None
+    }
+
+    /// Returns the transaction with the given hash and its meta data.
+    fn transaction_with_meta(&self, hash: TxHash) -> Option<(TransactionSigned, TransactionMeta)> {
+        self.find_transaction(|_, block_hash, block, index| {
+            let tx = &block.body[index];
+            (tx.hash() == hash).then(|| {
+                let meta = TransactionMeta {
+                    tx_hash: hash,
+                    index: index as u64,
+                    block_hash: *block_hash,
+                    block_number: block.number,
+                    base_fee: block.base_fee_per_gas,
+                    gas_used: None,
+                    success: None,
+                };
+                (tx.clone(), meta)
+            })
+        })
+    }
+
+    /// Returns the total difficulty used to fill the environment for the header.
+    fn env_total_difficulty(&self, header: &Header) -> Result<U256> {
+        match self.chain_spec.final_paris_total_difficulty(header.number) {
+            Some(total_difficulty) => Ok(total_difficulty),
+            None => Ok(self.header_td_by_number(header.number)?.unwrap_or_default()),
+        }
+    }
+
+    /// Returns the header at the given block and the total difficulty used to fill the
+    /// environment for it.
+    fn env_header(&self, at: BlockHashOrNumber) -> Result<(Header, U256)> {
+        let header = match at {
+            BlockHashOrNumber::Hash(hash) => self.header(&hash)?,
+            BlockHashOrNumber::Number(number) => self.header_by_number(number)?,
+        }
+        .ok_or(ProviderError::HeaderNotFound(at))?;
+        let total_difficulty = self.env_total_difficulty(&header)?;
+        Ok((header, total_difficulty))
+    }
+
+    /// Returns the [SpecId] of the header with its total difficulty.
+    fn spec_id_with_td(&self, header: &Header, total_difficulty: U256) -> SpecId {
+        revm_spec(
+            &self.chain_spec,
+            Head::builder(header.number)
+                .allow_unset_hash()
+                .timestamp(header.timestamp)
+                .difficulty(header.difficulty)
+                .total_difficulty(total_difficulty)
+                .build(),
+        )
+    }
+
+    /// Checks that headers past the merge have a zero difficulty and nonce, like the database
+    /// provider does.
+    fn check_merge_fields(&self, header: &Header, after_merge: bool) -> Result<()> {
+        if after_merge && (header.difficulty != U256::ZERO || header.nonce != 0) {
+            return Err(ProviderError::InconsistentHeader {
+                number: header.number,
+                difficulty: header.difficulty,
+                nonce: header.nonce,
+            }
+            .into())
+        }
+        Ok(())
+    }
+
     /// Add account to local account store
     pub fn add_account(&self, address: Address, account: ExtendedAccount) {
         self.accounts.lock().insert(address, account);
@@ -160,12 +347,20 @@ impl HeaderProvider for MockEthProvider {
 }
 
 impl TransactionsProvider for MockEthProvider {
-    fn transaction_id(&self, _tx_hash: TxHash) -> Result<Option<TxNumber>> {
-        todo!()
+    fn transaction_id(&self, tx_hash: TxHash) -> Result<Option<TxNumber>> {
+        Ok(self.find_transaction(|tx_num, _, block, index| {
+            (block.body[index].hash() == tx_hash).then_some(tx_num)
+        }))
     }
 
-    fn transaction_by_id(&self, _id: TxNumber) -> Result<Option<TransactionSigned>> {
-        Ok(None)
+    fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>> {
+        Ok(self.find_transaction(|tx_num, _, block, index| {
+            (tx_num == id).then(|| block.body[index].clone())
+        }))
+    }
+
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        Ok(self.transaction_by_id(id)?.map(Into::into))
     }
 
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
@@ -178,13 +373,13 @@ impl TransactionsProvider for MockEthProvider {
 
     fn transaction_by_hash_with_meta(
         &self,
-        _hash: TxHash,
+        hash: TxHash,
     ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
-        Ok(None)
+        Ok(self.transaction_with_meta(hash))
     }
 
-    fn transaction_block(&self, _id: TxNumber) -> Result<Option<BlockNumber>> {
-        unimplemented!()
+    fn transaction_block(&self, id: TxNumber) -> Result<Option<BlockNumber>> {
+        Ok(self.find_transaction(|tx_num, _, block, _| (tx_num == id).then_some(block.number)))
     }
 
     fn transactions_by_block(
@@ -194,6 +389,13 @@ impl TransactionsProvider for MockEthProvider {
         Ok(self.block(id)?.map(|b| b.body))
     }
 
+    fn transactions_by_block_no_hash(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        Ok(self.block(id)?.map(|b| b.body.into_iter().map(Into::into).collect()))
+    }
+
     fn transactions_by_block_range(
         &self,
         range: impl RangeBounds<reth_primitives::BlockNumber>,
@@ -211,31 +413,86 @@ impl TransactionsProvider for MockEthProvider {
 
     fn transactions_by_tx_range(
         &self,
-        _range: impl RangeBounds<TxNumber>,
-    ) -> Result<Vec<reth_primitives::TransactionSignedNoHash>> {
-        unimplemented!()
+        range: impl RangeBounds<TxNumber>,
+    ) -> Result<Vec<TransactionSignedNoHash>> {
+        let mut transactions = Vec::new();
+        self.find_transaction(|tx_num, _, block, index| {
+            if range.contains(&tx_num) {
+                transactions.push(block.body[index].clone().into());
+            }
+            None::<()>
+        });
+        Ok(transactions)
     }
 
-    fn senders_by_tx_range(&self, _range: impl RangeBounds<TxNumber>) -> Result<Vec<Address>> {
-        unimplemented!()
+    /// Like the senders stored by the database provider, transactions with an invalid signature
+    /// have the zero address as sender.
+    fn senders_by_tx_range(&self, range: impl RangeBounds<TxNumber>) -> Result<Vec<Address>> {
+        Ok(self
+            .transactions_by_tx_range(range)?
+            .iter()
+            .map(|tx| tx.recover_signer().unwrap_or_default())
+            .collect())
     }
 
-    fn transaction_sender(&self, _id: TxNumber) -> Result<Option<Address>> {
-        unimplemented!()
+    fn transaction_sender(&self, id: TxNumber) -> Result<Option<Address>> {
+        Ok(self.transaction_by_id(id)?.map(|tx| tx.recover_signer().unwrap_or_default()))
     }
 }
 
 impl ReceiptProvider for MockEthProvider {
-    fn receipt(&self, _id: TxNumber) -> Result<Option<Receipt>> {
-        Ok(None)
+    fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>> {
+        Ok(self
+            .find_transaction(|tx_num, hash, _, index| {
+                (tx_num == id).then(|| self.receipts.lock().get(hash)?.get(index).cloned())
+            })
+            .flatten())
     }
 
-    fn receipt_by_hash(&self, _hash: TxHash) -> Result<Option<Receipt>> {
-        Ok(None)
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        Ok(self.receipt_by_hash_with_meta(hash)?.map(|(receipt, _, _)| receipt))
     }
 
-    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
-        Ok(None)
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        let Some((_, mut meta)) = self.transaction_with_meta(hash) else { return Ok(None) };
+        let lock = self.receipts.lock();
+        let Some(receipts) = lock.get(&meta.block_hash) else { return Ok(None) };
+        let index = meta.index as usize;
+        let Some(receipt) = receipts.get(index).cloned() else { return Ok(None) };
+
+        // the gas used by the transaction is the difference to the cumulative gas used by the
+        // previous transaction in the block
+        let gas_used = match index.checked_sub(1) {
+            Some(prev) => receipt.cumulative_gas_used - receipts[prev].cumulative_gas_used,
+            None => receipt.cumulative_gas_used,
+        };
+        meta.gas_used = Some(gas_used);
+        meta.success = Some(receipt.success);
+        Ok(Some((receipt, meta, gas_used)))
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let hash = match block {
+            BlockHashOrNumber::Hash(hash) => hash,
+            BlockHashOrNumber::Number(number) => {
+                let Some(hash) = self.block_hash(number)? else { return Ok(None) };
+                hash
+            }
+        };
+        Ok(self.receipts.lock().get(&hash).cloned())
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        let hashes = self
+            .canonical_hashes_range((range.start_bound().cloned(), range.end_bound().cloned()))?;
+        let lock = self.receipts.lock();
+        Ok(hashes.into_iter().filter_map(|hash| lock.get(&hash).cloned()).collect())
     }
 }
 
@@ -249,8 +506,10 @@ impl BlockHashReader for MockEthProvider {
         Ok(hash)
     }
 
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>> {
-        let range = start..end;
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
         let lock = self.blocks.lock();
 
         let mut hashes: Vec<_> =
@@ -328,16 +587,50 @@ impl BlockReader for MockEthProvider {
         Ok(None)
     }
 
-    fn ommers(&self, _id: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
-        Ok(None)
+    fn ommers(&self, id: BlockHashOrNumber) -> Result<Option<Vec<Header>>> {
+        let Some(block) = self.block(id)? else { return Ok(None) };
+        // blocks after the Paris (Merge) hardfork block have no ommers
+        if self.chain_spec.final_paris_total_difficulty(block.number).is_some() {
+            return Ok(Some(Vec::new()))
+        }
+        // like in the database, only non-empty ommers are stored
+        Ok((!block.ommers.is_empty()).then_some(block.ommers))
     }
 
-    fn block_body_indices(&self, _num: u64) -> Result<Option<StoredBlockBodyIndices>> {
-        Ok(None)
+    fn block_body_indices(&self, num: u64) -> Result<Option<StoredBlockBodyIndices>> {
+        Ok(self.block_body_indices_range(num..=num)?.pop().map(|(_, indices)| indices))
     }
 
-    fn block_with_senders(&self, _number: BlockNumber) -> Result<Option<BlockWithSenders>> {
-        Ok(None)
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        let lock = self.blocks.lock();
+        let mut blocks: Vec<_> = lock.values().collect();
+        blocks.sort_by_key(|block| block.number);
+
+        let mut indices = Vec::new();
+        let mut first_tx_num = 0;
+        for block in blocks {
+            let tx_count = block.body.len() as u64;
+            if range.contains(&block.number) {
+                indices.push((block.number, StoredBlockBodyIndices { first_tx_num, tx_count }));
+            }
+            first_tx_num += tx_count;
+        }
+        Ok(indices)
+    }
+
+    /// Returns the block with senders with matching number, the senders are recovered from the
+    /// signatures.
+    ///
+    /// Returns `None` if block is not found.
+    fn block_with_senders(&self, number: BlockNumber) -> Result<Option<BlockWithSenders>> {
+        let Some(block) = self.block(number.into())? else { return Ok(None) };
+        let senders =
+            TransactionSigned::recover_signers_parallel(&block.body, SENDER_RECOVERY_CHUNK_SIZE)
+                .ok_or(ProviderError::SenderRecoveryFailed(number))?;
+        Ok(Some(block.with_senders(senders)))
     }
 }
 
@@ -410,40 +703,172 @@ impl StateProvider for MockEthProvider {
 impl EvmEnvProvider for MockEthProvider {
     fn fill_env_at(
         &self,
-        _cfg: &mut CfgEnv,
-        _block_env: &mut BlockEnv,
-        _at: BlockHashOrNumber,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
     ) -> Result<()> {
-        unimplemented!()
+        let (header, total_difficulty) = self.env_header(at)?;
+        let after_merge = self.spec_id_with_td(&header, total_difficulty) >= SpecId::MERGE;
+        fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+        fill_block_env(block_env, &self.chain_spec, &header, after_merge);
+        Ok(())
+    }
+
+    fn fill_env_at_block_id(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockId,
+    ) -> Result<Option<BlockNumHash>> {
+        if matches!(at, BlockId::Number(BlockNumberOrTag::Pending)) {
+            let head = self.best_block_number()?;
+            let Some(hash) = self.block_hash(head)? else { return Ok(None) };
+            self.fill_env_for_next_block(cfg, block_env, None)?;
+            return Ok(Some(BlockNumHash::new(head, hash)))
+        }
+        let Some(number) = self.block_number_for_id(at)? else { return Ok(None) };
+
+        let (header, total_difficulty) = self.env_header(number.into())?;
+        let after_merge = self.spec_id_with_td(&header, total_difficulty) >= SpecId::MERGE;
+        fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+        fill_block_env(block_env, &self.chain_spec, &header, after_merge);
+        Ok(Some(BlockNumHash::new(header.number, header.hash_slow())))
     }
 
     fn fill_env_with_header(
         &self,
-        _cfg: &mut CfgEnv,
-        _block_env: &mut BlockEnv,
-        _header: &Header,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
     ) -> Result<()> {
-        unimplemented!()
+        let total_difficulty = self.env_total_difficulty(header)?;
+        self.fill_env_with_header_and_td(cfg, block_env, header, total_difficulty)
     }
 
-    fn fill_block_env_at(&self, _block_env: &mut BlockEnv, _at: BlockHashOrNumber) -> Result<()> {
-        unimplemented!()
+    fn fill_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        let after_merge = self.spec_id_with_td(header, total_difficulty) >= SpecId::MERGE;
+        self.check_merge_fields(header, after_merge)?;
+        fill_cfg_and_block_env(cfg, block_env, &self.chain_spec, header, total_difficulty);
+        Ok(())
+    }
+
+    fn fill_block_env_at(&self, block_env: &mut BlockEnv, at: BlockHashOrNumber) -> Result<()> {
+        let (header, total_difficulty) = self.env_header(at)?;
+        let after_merge = self.spec_id_with_td(&header, total_difficulty) >= SpecId::MERGE;
+        fill_block_env(block_env, &self.chain_spec, &header, after_merge);
+        Ok(())
     }
 
-    fn fill_block_env_with_header(
+    fn fill_block_env_with_overrides(
         &self,
-        _block_env: &mut BlockEnv,
-        _header: &Header,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+        overrides: BlockEnvOverrides,
     ) -> Result<()> {
-        unimplemented!()
+        let (header, total_difficulty) = self.env_header(at)?;
+        let spec_id =
+            overrides.spec_id.unwrap_or_else(|| self.spec_id_with_td(&header, total_difficulty));
+        fill_block_env(block_env, &self.chain_spec, &header, spec_id >= SpecId::MERGE);
+        overrides.apply(block_env);
+        Ok(())
     }
 
-    fn fill_cfg_env_at(&self, _cfg: &mut CfgEnv, _at: BlockHashOrNumber) -> Result<()> {
-        unimplemented!()
+    fn fill_block_env_with_header(&self, block_env: &mut BlockEnv, header: &Header) -> Result<()> {
+        let total_difficulty = self.env_total_difficulty(header)?;
+        self.fill_block_env_with_header_and_td(block_env, header, total_difficulty)
+    }
+
+    fn fill_block_env_with_header_and_td(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        let after_merge = self.spec_id_with_td(header, total_difficulty) >= SpecId::MERGE;
+        self.check_merge_fields(header, after_merge)?;
+        fill_block_env(block_env, &self.chain_spec, header, after_merge);
+        Ok(())
     }
 
-    fn fill_cfg_env_with_header(&self, _cfg: &mut CfgEnv, _header: &Header) -> Result<()> {
-        unimplemented!()
+    fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> Result<()> {
+        let (header, total_difficulty) = self.env_header(at)?;
+        fill_cfg_env(cfg, &self.chain_spec, &header, total_difficulty);
+        Ok(())
+    }
+
+    fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> Result<()> {
+        let total_difficulty = self.env_total_difficulty(header)?;
+        self.fill_cfg_env_with_header_and_td(cfg, header, total_difficulty)
+    }
+
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()> {
+        fill_cfg_env(cfg, &self.chain_spec, header, total_difficulty);
+        Ok(())
+    }
+
+    fn fill_envs_for_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>> {
+        self.headers_range(range)?
+            .into_iter()
+            .map(|header| {
+                let total_difficulty = self.env_total_difficulty(&header)?;
+                let mut cfg = CfgEnv::default();
+                let mut block_env = BlockEnv::default();
+                fill_cfg_env(&mut cfg, &self.chain_spec, &header, total_difficulty);
+                fill_block_env(
+                    &mut block_env,
+                    &self.chain_spec,
+                    &header,
+                    cfg.spec_id >= SpecId::MERGE,
+                );
+                Ok((cfg, block_env))
+            })
+            .collect()
+    }
+
+    fn fill_env_for_next_block(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        attributes: Option<NextBlockAttributes>,
+    ) -> Result<()> {
+        let head = self.best_block_number()?;
+        let (parent, parent_td) = self.env_header(head.into())?;
+        let attributes = attributes.unwrap_or_default();
+
+        let mut header = Header {
+            parent_hash: parent.hash_slow(),
+            number: parent.number + 1,
+            timestamp: attributes.timestamp.unwrap_or(parent.timestamp + 1),
+            beneficiary: attributes.suggested_fee_recipient.unwrap_or(parent.beneficiary),
+            gas_limit: parent.gas_limit,
+            mix_hash: parent.mix_hash,
+            base_fee_per_gas: self.chain_spec.next_block_base_fee(&parent),
+            ..Default::default()
+        };
+        // whether the next block is past the merge only depends on the total difficulty of its
+        // parent
+        let after_merge = self.spec_id_with_td(&header, parent_td) >= SpecId::MERGE;
+        if !after_merge {
+            header.difficulty = parent.difficulty;
+        }
+
+        fill_cfg_env(cfg, &self.chain_spec, &header, parent_td + header.difficulty);
+        fill_block_env_with_coinbase(block_env, &header, after_merge, header.beneficiary);
+        Ok(())
     }
 }
 
@@ -514,14 +939,25 @@ impl StateProviderFactory for Arc<MockEthProvider> {
 }
 
 impl WithdrawalsProvider for MockEthProvider {
-    fn latest_withdrawal(&self) -> Result<Option<reth_primitives::Withdrawal>> {
-        unimplemented!()
+    fn latest_withdrawal(&self) -> Result<Option<Withdrawal>> {
+        let lock = self.blocks.lock();
+        let mut blocks: Vec<_> = lock.values().collect();
+        blocks.sort_by_key(|block| block.number);
+        Ok(blocks
+            .into_iter()
+            .rev()
+            .find_map(|block| block.withdrawals.as_ref().and_then(|w| w.last()).cloned()))
     }
+
     fn withdrawals_by_block(
         &self,
-        _id: BlockHashOrNumber,
-        _timestamp: u64,
-    ) -> Result<Option<Vec<reth_primitives::Withdrawal>>> {
-        unimplemented!()
+        id: BlockHashOrNumber,
+        timestamp: u64,
+    ) -> Result<Option<Vec<Withdrawal>>> {
+        if !self.chain_spec.is_shanghai_activated_at_timestamp(timestamp) {
+            return Ok(None)
+        }
+        // past shanghai all blocks have a withdrawal list, even if empty
+        Ok(self.block(id)?.map(|block| block.withdrawals.unwrap_or_default()))
     }
 }
@@ -1,4 +1,5 @@
 pub mod blocks;
+pub mod conformance;
 mod events;
 mod executor;
 mod mock;
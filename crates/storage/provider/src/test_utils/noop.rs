@@ -1,8 +1,9 @@
 use crate::{
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    EvmEnvProvider, HeaderProvider, PostState, ReceiptProviderIdExt, StageCheckpointReader,
-    StateProvider, StateProviderBox, StateProviderFactory, StateRootProvider, TransactionsProvider,
+    AccountReader, BlockEnvOverrides, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader,
+    BlockReaderIdExt, EvmEnvProvider, HeaderProvider, NextBlockAttributes, PostState,
+    PruneCheckpointReader, ReceiptProviderIdExt, StageCheckpointReader, StateProvider,
+    StateProviderBox, StateProviderFactory, StateRootProvider, TransactionsProvider,
     WithdrawalsProvider,
 };
 use reth_db::models::StoredBlockBodyIndices;
@@ -10,11 +11,12 @@ use reth_interfaces::Result;
 use reth_primitives::{
     stage::{StageCheckpoint, StageId},
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber, Bytecode, Bytes,
-    ChainInfo, Header, Receipt, SealedBlock, SealedHeader, StorageKey, StorageValue,
-    TransactionMeta, TransactionSigned, TxHash, TxNumber, H256, KECCAK_EMPTY, U256,
+    ChainInfo, Header, PruneCheckpoint, PrunePart, Receipt, SealedBlock, SealedHeader, StorageKey,
+    StorageValue, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
+    H256, KECCAK_EMPTY, U256,
 };
 use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 
 /// Supports various api interfaces for testing purposes.
 #[derive(Debug, Clone, Default, Copy)]
@@ -27,7 +29,10 @@ impl BlockHashReader for NoopProvider {
         Ok(None)
     }
 
-    fn canonical_hashes_range(&self, _start: BlockNumber, _end: BlockNumber) -> Result<Vec<H256>> {
+    fn canonical_hashes_range(
+        &self,
+        _range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>> {
         Ok(vec![])
     }
 }
@@ -75,6 +80,13 @@ impl BlockReader for NoopProvider {
         Ok(None)
     }
 
+    fn block_body_indices_range(
+        &self,
+        _range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>> {
+        Ok(Vec::new())
+    }
+
     fn block_with_senders(
         &self,
         _number: BlockNumber,
@@ -124,6 +136,10 @@ impl TransactionsProvider for NoopProvider {
         Ok(None)
     }
 
+    fn transaction_by_id_no_hash(&self, _id: TxNumber) -> Result<Option<TransactionSignedNoHash>> {
+        Ok(None)
+    }
+
     fn transaction_by_hash(&self, _hash: TxHash) -> Result<Option<TransactionSigned>> {
         Ok(None)
     }
@@ -146,6 +162,13 @@ impl TransactionsProvider for NoopProvider {
         Ok(None)
     }
 
+    fn transactions_by_block_no_hash(
+        &self,
+        _block_id: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>> {
+        Ok(None)
+    }
+
     fn transactions_by_block_range(
         &self,
         _range: impl RangeBounds<BlockNumber>,
@@ -160,7 +183,7 @@ impl TransactionsProvider for NoopProvider {
     fn transactions_by_tx_range(
         &self,
         _range: impl RangeBounds<TxNumber>,
-    ) -> Result<Vec<reth_primitives::TransactionSignedNoHash>> {
+    ) -> Result<Vec<TransactionSignedNoHash>> {
         Ok(Vec::default())
     }
 
@@ -178,9 +201,23 @@ impl ReceiptProvider for NoopProvider {
         Ok(None)
     }
 
+    fn receipt_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>> {
+        Ok(None)
+    }
+
     fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
         Ok(None)
     }
+
+    fn receipts_by_block_range(
+        &self,
+        _range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>> {
+        Ok(Vec::new())
+    }
 }
 
 impl ReceiptProviderIdExt for NoopProvider {}
@@ -258,6 +295,15 @@ impl EvmEnvProvider for NoopProvider {
         Ok(())
     }
 
+    fn fill_env_at_block_id(
+        &self,
+        _cfg: &mut CfgEnv,
+        _block_env: &mut BlockEnv,
+        _at: BlockId,
+    ) -> Result<Option<reth_primitives::BlockNumHash>> {
+        Ok(None)
+    }
+
     fn fill_env_with_header(
         &self,
         _cfg: &mut CfgEnv,
@@ -267,10 +313,29 @@ impl EvmEnvProvider for NoopProvider {
         Ok(())
     }
 
+    fn fill_env_with_header_and_td(
+        &self,
+        _cfg: &mut CfgEnv,
+        _block_env: &mut BlockEnv,
+        _header: &Header,
+        _total_difficulty: U256,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     fn fill_block_env_at(&self, _block_env: &mut BlockEnv, _at: BlockHashOrNumber) -> Result<()> {
         Ok(())
     }
 
+    fn fill_block_env_with_overrides(
+        &self,
+        _block_env: &mut BlockEnv,
+        _at: BlockHashOrNumber,
+        _overrides: BlockEnvOverrides,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     fn fill_block_env_with_header(
         &self,
         _block_env: &mut BlockEnv,
@@ -279,6 +344,15 @@ impl EvmEnvProvider for NoopProvider {
         Ok(())
     }
 
+    fn fill_block_env_with_header_and_td(
+        &self,
+        _block_env: &mut BlockEnv,
+        _header: &Header,
+        _total_difficulty: U256,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     fn fill_cfg_env_at(&self, _cfg: &mut CfgEnv, _at: BlockHashOrNumber) -> Result<()> {
         Ok(())
     }
@@ -286,6 +360,31 @@ impl EvmEnvProvider for NoopProvider {
     fn fill_cfg_env_with_header(&self, _cfg: &mut CfgEnv, _header: &Header) -> Result<()> {
         Ok(())
     }
+
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        _cfg: &mut CfgEnv,
+        _header: &Header,
+        _total_difficulty: U256,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn fill_envs_for_range(
+        &self,
+        _range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>> {
+        Ok(Vec::new())
+    }
+
+    fn fill_env_for_next_block(
+        &self,
+        _cfg: &mut CfgEnv,
+        _block_env: &mut BlockEnv,
+        _attributes: Option<NextBlockAttributes>,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl StateProviderFactory for NoopProvider {
@@ -329,6 +428,16 @@ impl StageCheckpointReader for NoopProvider {
     fn get_stage_checkpoint_progress(&self, _id: StageId) -> Result<Option<Vec<u8>>> {
         Ok(None)
     }
+
+    fn get_all_stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>> {
+        Ok(Vec::new())
+    }
+}
+
+impl PruneCheckpointReader for NoopProvider {
+    fn get_prune_checkpoint(&self, _part: PrunePart) -> Result<Option<PruneCheckpoint>> {
+        Ok(None)
+    }
 }
 
 impl WithdrawalsProvider for NoopProvider {
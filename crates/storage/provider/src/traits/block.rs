@@ -9,7 +9,7 @@ use reth_primitives::{
     Address, Block, BlockHashOrNumber, BlockId, BlockNumber, BlockNumberOrTag, BlockWithSenders,
     ChainSpec, Header, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, H256,
 };
-use std::ops::RangeInclusive;
+use std::ops::{RangeBounds, RangeInclusive};
 
 /// A helper enum that represents the origin of the requested block.
 ///
@@ -101,6 +101,14 @@ pub trait BlockReader:
     /// Returns `None` if block is not found.
     fn block_body_indices(&self, num: u64) -> Result<Option<StoredBlockBodyIndices>>;
 
+    /// Returns the block body indices of all blocks in the range, in ascending block order.
+    ///
+    /// Blocks without body indices are skipped.
+    fn block_body_indices_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(BlockNumber, StoredBlockBodyIndices)>>;
+
     /// Returns the block with senders with matching number from database.
     ///
     /// Returns `None` if block is not found.
@@ -238,6 +246,10 @@ pub trait BlockWriter: Send + Sync {
     ///
     /// Return [StoredBlockBodyIndices] that contains indices of the first and last transactions and
     /// transition in the block.
+    ///
+    /// Inserting a block whose header and body are already stored writes nothing and returns the
+    /// stored indices, a different block at a stored height has to be unwound first. A new block
+    /// has to be the child of the canonical tip.
     fn insert_block(
         &self,
         block: SealedBlock,
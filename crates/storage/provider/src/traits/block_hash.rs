@@ -1,6 +1,7 @@
 use auto_impl::auto_impl;
 use reth_interfaces::Result;
 use reth_primitives::{BlockHashOrNumber, BlockNumber, H256};
+use std::ops::Bound;
 
 /// Client trait for fetching block hashes by number.
 #[auto_impl(&, Arc, Box)]
@@ -18,6 +19,13 @@ pub trait BlockHashReader: Send + Sync {
         }
     }
 
-    /// Get headers in range of block hashes or numbers
-    fn canonical_hashes_range(&self, start: BlockNumber, end: BlockNumber) -> Result<Vec<H256>>;
+    /// Get the canonical hashes of the blocks within the given bounds, in ascending block order.
+    ///
+    /// The bounds are applied as given, e.g. `(Bound::Included(start), Bound::Included(end))`
+    /// includes the block at `end`. The range is a pair of bounds rather than
+    /// `impl RangeBounds` so the trait stays usable as a trait object.
+    fn canonical_hashes_range(
+        &self,
+        range: (Bound<BlockNumber>, Bound<BlockNumber>),
+    ) -> Result<Vec<H256>>;
 }
@@ -1,6 +1,8 @@
 use super::BlockHashReader;
 use reth_interfaces::Result;
-use reth_primitives::{BlockHashOrNumber, BlockId, BlockNumber, BlockNumberOrTag, ChainInfo, H256};
+use reth_primitives::{
+    BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber, BlockNumberOrTag, ChainInfo, H256,
+};
 
 /// Client trait for getting important block numbers (such as the latest block number), converting
 /// block hashes to numbers, and fetching a block hash from its block number.
@@ -12,23 +14,80 @@ pub trait BlockNumReader: BlockHashReader + Send + Sync {
     fn chain_info(&self) -> Result<ChainInfo>;
 
     /// Returns the best block number in the chain.
+    ///
+    /// For the database this is the block of the `Finish` stage checkpoint, i.e. the last block
+    /// the whole pipeline has processed. It can differ from [BlockNumReader::last_block_number],
+    /// e.g. while the headers of a new sync target are downloaded, or after an interrupted unwind.
     fn best_block_number(&self) -> Result<BlockNumber>;
 
     /// Returns the last block number associated with the last canonical header in the database.
+    ///
+    /// This is the tip of the `CanonicalHeaders` table, regardless of how far the other stages
+    /// have processed it, see [BlockNumReader::best_block_number].
     fn last_block_number(&self) -> Result<BlockNumber>;
 
+    /// Returns the number and hash of the last canonical header in the database, `None` if there
+    /// are no canonical headers.
+    ///
+    /// See [BlockNumReader::last_block_number].
+    fn last_canonical_num_hash(&self) -> Result<Option<BlockNumHash>> {
+        let number = self.last_block_number()?;
+        Ok(self.block_hash(number)?.map(|hash| BlockNumHash::new(number, hash)))
+    }
+
     /// Gets the `BlockNumber` for the given hash. Returns `None` if no block with this hash exists.
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>>;
 
-    /// Gets the block number for the given `BlockHashOrNumber`. Returns `None` if no block with
-    /// this hash exists. If the `BlockHashOrNumber` is a `Number`, it is returned as is.
+    /// Gets the `BlockNumber` for each of the given hashes, in the order of the input. Unknown
+    /// hashes resolve to `None`.
+    fn block_numbers(&self, hashes: &[H256]) -> Result<Vec<Option<BlockNumber>>> {
+        hashes.iter().map(|hash| self.block_number(*hash)).collect()
+    }
+
+    /// Resolves each of the given hashes to its [BlockNumHash], in the order of the input. Unknown
+    /// hashes resolve to `None`.
+    fn convert_hashes_to_num_hashes(&self, hashes: &[H256]) -> Result<Vec<Option<BlockNumHash>>> {
+        Ok(self
+            .block_numbers(hashes)?
+            .into_iter()
+            .zip(hashes)
+            .map(|(number, hash)| number.map(|number| BlockNumHash::new(number, *hash)))
+            .collect())
+    }
+
+    /// Gets the `BlockNumber` for the given hash if the block is canonical. Returns `None` if no
+    /// block with this hash exists or it was reorged out.
+    ///
+    /// Unlike [BlockNumReader::block_number], which also knows the numbers of blocks that are no
+    /// longer canonical, the number is verified against the canonical hash at its height.
+    fn canonical_block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
+        let Some(number) = self.block_number(hash)? else { return Ok(None) };
+        Ok((self.block_hash(number)? == Some(hash)).then_some(number))
+    }
+
+    /// Gets the block number for the given `BlockHashOrNumber`. Returns `None` if no canonical
+    /// block with this hash exists, see [BlockNumReader::canonical_block_number]. If the
+    /// `BlockHashOrNumber` is a `Number`, it is returned as is.
     fn convert_hash_or_number(&self, id: BlockHashOrNumber) -> Result<Option<BlockNumber>> {
         match id {
-            BlockHashOrNumber::Hash(hash) => self.block_number(hash),
+            BlockHashOrNumber::Hash(hash) => self.canonical_block_number(hash),
             BlockHashOrNumber::Number(num) => Ok(Some(num)),
         }
     }
 
+    /// Gets the block number for the given `BlockHashOrNumber`, like
+    /// [BlockNumReader::convert_hash_or_number], but a `Number` is only returned if the block has a
+    /// canonical hash.
+    ///
+    /// Rows above the canonical chain, e.g. left behind by an interrupted unwind, are not part of
+    /// the chain, callers reading them by number should resolve the number with this first.
+    fn convert_hash_or_number_checked(&self, id: BlockHashOrNumber) -> Result<Option<BlockNumber>> {
+        match id {
+            BlockHashOrNumber::Hash(hash) => self.canonical_block_number(hash),
+            BlockHashOrNumber::Number(num) => Ok(self.block_hash(num)?.map(|_| num)),
+        }
+    }
+
     /// Gets the block hash for the given `BlockHashOrNumber`. Returns `None` if no block with this
     /// number exists. If the `BlockHashOrNumber` is a `Hash`, it is returned as is.
     fn convert_number(&self, id: BlockHashOrNumber) -> Result<Option<H256>> {
@@ -1,6 +1,9 @@
 use reth_interfaces::Result;
-use reth_primitives::{BlockHashOrNumber, Header};
-use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
+use reth_primitives::{
+    Address, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber, Header, U256,
+};
+use reth_revm_primitives::primitives::{BlockEnv, CfgEnv, SpecId};
+use std::ops::RangeBounds;
 
 /// A provider type that knows chain specific information required to configure an
 /// [Env](reth_revm_primitives::primitives::Env)
@@ -17,6 +20,22 @@ pub trait EvmEnvProvider: Send + Sync {
         at: BlockHashOrNumber,
     ) -> Result<()>;
 
+    /// Fills the [CfgEnv] and [BlockEnv] fields for the block the [BlockId] resolves to.
+    ///
+    /// Unlike resolving the block first and calling [EvmEnvProvider::fill_env_at] afterwards, the
+    /// returned block is always the one the environment was read for, even if the head moves in
+    /// between. `Latest` is the executed tip, `Pending` fills the environment of the block
+    /// following it, see [EvmEnvProvider::fill_env_for_next_block].
+    ///
+    /// Returns the block the environment was filled for, for `Pending` the latest block it builds
+    /// on, or `None` if the [BlockId] doesn't resolve to a canonical block.
+    fn fill_env_at_block_id(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockId,
+    ) -> Result<Option<BlockNumHash>>;
+
     /// Fills the default [CfgEnv] and [BlockEnv] fields with values specific to the given [Header].
     fn env_with_header(&self, header: &Header) -> Result<(CfgEnv, BlockEnv)> {
         let mut cfg = CfgEnv::default();
@@ -33,15 +52,126 @@ pub trait EvmEnvProvider: Send + Sync {
         header: &Header,
     ) -> Result<()>;
 
+    /// Fills the [CfgEnv] and [BlockEnv] fields with values specific to the given [Header] and its
+    /// total difficulty.
+    ///
+    /// Unlike [EvmEnvProvider::fill_env_with_header] the total difficulty is not read from the
+    /// database, so this can be used for headers that are not persisted yet.
+    fn fill_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()>;
+
     /// Fills the [BlockEnv] fields with values specific to the given [BlockHashOrNumber].
     fn fill_block_env_at(&self, block_env: &mut BlockEnv, at: BlockHashOrNumber) -> Result<()>;
 
+    /// Fills the [BlockEnv] fields with values specific to the given [BlockHashOrNumber] and
+    /// applies the [BlockEnvOverrides] afterwards.
+    ///
+    /// The spec the environment is filled for is chosen from the real header, overriding the
+    /// timestamp across a fork boundary does not change it. Use [BlockEnvOverrides::spec_id] to
+    /// fill the environment for another spec.
+    fn fill_block_env_with_overrides(
+        &self,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+        overrides: BlockEnvOverrides,
+    ) -> Result<()>;
+
     /// Fills the [BlockEnv] fields with values specific to the given [Header].
     fn fill_block_env_with_header(&self, block_env: &mut BlockEnv, header: &Header) -> Result<()>;
 
+    /// Fills the [BlockEnv] fields with values specific to the given [Header] and its total
+    /// difficulty, without reading the total difficulty from the database.
+    fn fill_block_env_with_header_and_td(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()>;
+
     /// Fills the [CfgEnv] fields with values specific to the given [BlockHashOrNumber].
     fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> Result<()>;
 
     /// Fills the [CfgEnv] fields with values specific to the given [Header].
     fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> Result<()>;
+
+    /// Fills the [CfgEnv] fields with values specific to the given [Header] and its total
+    /// difficulty, without reading the total difficulty from the database.
+    fn fill_cfg_env_with_header_and_td(
+        &self,
+        cfg: &mut CfgEnv,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<()>;
+
+    /// Returns the [CfgEnv] and [BlockEnv] of every block in the range, in ascending block order.
+    ///
+    /// This is equivalent to calling [EvmEnvProvider::fill_env_at] for each block of the range.
+    fn fill_envs_for_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<(CfgEnv, BlockEnv)>>;
+
+    /// Fills the [CfgEnv] and [BlockEnv] fields for the block that would follow the current head.
+    ///
+    /// The next block has the head's number plus one, the head's gas limit, prevrandao and fee
+    /// recipient, a timestamp one second after the head's and the EIP-1559 base fee derived from
+    /// the head. The given [NextBlockAttributes] override the timestamp and fee recipient.
+    fn fill_env_for_next_block(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        attributes: Option<NextBlockAttributes>,
+    ) -> Result<()>;
+}
+
+/// Optional overrides for the environment of the next block, see
+/// [EvmEnvProvider::fill_env_for_next_block].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NextBlockAttributes {
+    /// Timestamp of the next block, defaults to the head's timestamp plus one.
+    pub timestamp: Option<u64>,
+    /// Fee recipient of the next block, defaults to the head's beneficiary.
+    pub suggested_fee_recipient: Option<Address>,
+}
+
+/// Overrides applied to a [BlockEnv] after it was filled from a header, see
+/// [EvmEnvProvider::fill_block_env_with_overrides].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockEnvOverrides {
+    /// Overrides the coinbase.
+    pub coinbase: Option<Address>,
+    /// Overrides the gas limit.
+    pub gas_limit: Option<u64>,
+    /// Overrides the base fee.
+    pub base_fee: Option<u64>,
+    /// Overrides the timestamp. The spec is still chosen from the header's timestamp.
+    pub timestamp: Option<u64>,
+    /// Overrides the spec the environment is filled for, which decides whether the block is
+    /// filled with a difficulty or with prevrandao.
+    pub spec_id: Option<SpecId>,
+}
+
+impl BlockEnvOverrides {
+    /// Applies the set overrides to the [BlockEnv].
+    ///
+    /// The spec id override is not applied here, it is taken into account while filling.
+    pub fn apply(&self, block_env: &mut BlockEnv) {
+        if let Some(coinbase) = self.coinbase {
+            block_env.coinbase = coinbase;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block_env.gas_limit = U256::from(gas_limit);
+        }
+        if let Some(base_fee) = self.base_fee {
+            block_env.basefee = U256::from(base_fee);
+        }
+        if let Some(timestamp) = self.timestamp {
+            block_env.timestamp = U256::from(timestamp);
+        }
+    }
 }
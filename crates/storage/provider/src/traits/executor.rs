@@ -3,6 +3,7 @@
 use crate::{post_state::PostState, StateProvider};
 use reth_interfaces::executor::BlockExecutionError;
 use reth_primitives::{Address, Block, ChainSpec, U256};
+use reth_revm_primitives::primitives::{BlockEnv, CfgEnv};
 
 /// Executor factory that would create the EVM with particular state provider.
 ///
@@ -43,3 +44,45 @@ pub trait BlockExecutor<SP: StateProvider> {
         senders: Option<Vec<Address>>,
     ) -> Result<PostState, BlockExecutionError>;
 }
+
+/// The environments and the state a block is executed with.
+pub trait ExecutionContext {
+    /// The state the block is executed on, i.e. the state of its parent block.
+    type State: StateProvider;
+
+    /// Returns the [CfgEnv] the block is executed with.
+    fn cfg(&self) -> &CfgEnv;
+
+    /// Returns the [BlockEnv] the block is executed with.
+    fn block_env(&self) -> &BlockEnv;
+
+    /// Consumes the context, returning the environments and the state.
+    fn into_parts(self) -> (CfgEnv, BlockEnv, Self::State);
+}
+
+/// The [CfgEnv] and [BlockEnv] of a block paired with the state of its parent block, see
+/// [ProviderFactory::env_with_state_at](crate::ProviderFactory::env_with_state_at).
+pub struct EnvWithState<SP> {
+    /// The [CfgEnv] of the block.
+    pub cfg: CfgEnv,
+    /// The [BlockEnv] of the block.
+    pub block_env: BlockEnv,
+    /// The state of the parent block.
+    pub state: SP,
+}
+
+impl<SP: StateProvider> ExecutionContext for EnvWithState<SP> {
+    type State = SP;
+
+    fn cfg(&self) -> &CfgEnv {
+        &self.cfg
+    }
+
+    fn block_env(&self) -> &BlockEnv {
+        &self.block_env
+    }
+
+    fn into_parts(self) -> (CfgEnv, BlockEnv, SP) {
+        (self.cfg, self.block_env, self.state)
+    }
+}
@@ -1,5 +1,5 @@
 use auto_impl::auto_impl;
-use reth_interfaces::Result;
+use reth_interfaces::{provider::ProviderError, Result};
 use reth_primitives::{BlockHash, BlockHashOrNumber, BlockNumber, Header, SealedHeader, U256};
 use std::ops::RangeBounds;
 
@@ -42,4 +42,21 @@ pub trait HeaderProvider: Send + Sync {
 
     /// Get a single sealed header by block number
     fn sealed_header(&self, number: BlockNumber) -> Result<Option<SealedHeader>>;
+
+    /// Get the sealed header and its total difficulty by block hash or number.
+    ///
+    /// Returns [ProviderError::TotalDifficultyNotFound] if the header exists but its total
+    /// difficulty does not.
+    fn sealed_header_with_td(&self, id: BlockHashOrNumber) -> Result<Option<(SealedHeader, U256)>> {
+        let Some(header) = self.header_by_hash_or_number(id)? else { return Ok(None) };
+        let number = header.number;
+        let header = match id {
+            BlockHashOrNumber::Hash(hash) => header.seal(hash),
+            BlockHashOrNumber::Number(_) => header.seal_slow(),
+        };
+        let td = self
+            .header_td_by_number(number)?
+            .ok_or(ProviderError::TotalDifficultyNotFound { number })?;
+        Ok(Some((header, td)))
+    }
 }
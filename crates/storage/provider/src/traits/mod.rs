@@ -16,7 +16,7 @@ mod block_id;
 pub use block_id::{BlockIdReader, BlockNumReader};
 
 mod evm_env;
-pub use evm_env::EvmEnvProvider;
+pub use evm_env::{BlockEnvOverrides, EvmEnvProvider, NextBlockAttributes};
 
 mod chain_info;
 pub use chain_info::CanonChainTracker;
@@ -40,7 +40,7 @@ mod withdrawals;
 pub use withdrawals::WithdrawalsProvider;
 
 mod executor;
-pub use executor::{BlockExecutor, ExecutorFactory};
+pub use executor::{BlockExecutor, EnvWithState, ExecutionContext, ExecutorFactory};
 
 mod chain;
 pub use chain::{
@@ -51,6 +51,9 @@ pub use chain::{
 mod stage_checkpoint;
 pub use stage_checkpoint::{StageCheckpointReader, StageCheckpointWriter};
 
+mod prune_checkpoint;
+pub use prune_checkpoint::{PruneCheckpointReader, PruneCheckpointWriter};
+
 mod hashing;
 pub use hashing::HashingWriter;
 
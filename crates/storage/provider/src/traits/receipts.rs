@@ -1,5 +1,9 @@
 use reth_interfaces::Result;
-use reth_primitives::{BlockHashOrNumber, BlockId, BlockNumberOrTag, Receipt, TxHash, TxNumber};
+use reth_primitives::{
+    BlockHashOrNumber, BlockId, BlockNumber, BlockNumberOrTag, Receipt, TransactionMeta, TxHash,
+    TxNumber,
+};
+use std::ops::RangeBounds;
 
 use crate::BlockIdReader;
 
@@ -8,18 +12,38 @@ use crate::BlockIdReader;
 pub trait ReceiptProvider: Send + Sync {
     /// Get receipt by transaction number
     ///
-    /// Returns `None` if the transaction is not found.
+    /// Returns `None` if the transaction is not found and an error if the receipt was pruned.
     fn receipt(&self, id: TxNumber) -> Result<Option<Receipt>>;
 
     /// Get receipt by transaction hash.
     ///
-    /// Returns `None` if the transaction is not found.
+    /// Returns `None` if the transaction is not found and an error if the receipt was pruned.
     fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>>;
 
+    /// Get receipt by transaction hash together with the transaction's [TransactionMeta] and the
+    /// gas used by the transaction alone.
+    ///
+    /// Returns `None` if the transaction is not found.
+    fn receipt_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(Receipt, TransactionMeta, u64)>>;
+
     /// Get receipts by block num or hash.
     ///
-    /// Returns `None` if the block is not found.
+    /// Returns `None` if the block is not found and an error if the receipts were pruned.
     fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>>;
+
+    /// Get receipts by block range.
+    ///
+    /// Returns the receipts of each block in the range, blocks without transactions yield an empty
+    /// list. Receipts can lag behind the stored block bodies, so the result ends at the last block
+    /// whose receipts are fully available and may be shorter than the requested range. Returns an
+    /// error if the receipts of any block in the range were pruned.
+    fn receipts_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> Result<Vec<Vec<Receipt>>>;
 }
 
 /// Trait extension for `ReceiptProvider`, for types that implement `BlockId` conversion.
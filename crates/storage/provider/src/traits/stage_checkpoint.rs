@@ -12,6 +12,11 @@ pub trait StageCheckpointReader: Send + Sync {
 
     /// Get stage checkpoint progress.
     fn get_stage_checkpoint_progress(&self, id: StageId) -> Result<Option<Vec<u8>>>;
+
+    /// Fetch the checkpoints of all stages in [StageId::ALL] that have one, in pipeline order.
+    ///
+    /// Checkpoints of custom stages are not included.
+    fn get_all_stage_checkpoints(&self) -> Result<Vec<(StageId, StageCheckpoint)>>;
 }
 
 /// The trait for updating stage checkpoint related data.
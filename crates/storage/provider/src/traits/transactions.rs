@@ -18,7 +18,13 @@ pub trait TransactionsProvider: BlockNumReader + Send + Sync {
     /// Get transaction by id.
     fn transaction_by_id(&self, id: TxNumber) -> Result<Option<TransactionSigned>>;
 
+    /// Get transaction by id in its stored form, without computing its hash.
+    fn transaction_by_id_no_hash(&self, id: TxNumber) -> Result<Option<TransactionSignedNoHash>>;
+
     /// Get transaction by transaction hash.
+    ///
+    /// Database providers fail with a `TransactionLookupPruned` error instead of returning `None`
+    /// if the transaction is not found and the hash lookups were pruned.
     fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>>;
 
     /// Get transaction by transaction hash and additional metadata of the block the transaction was
@@ -37,6 +43,12 @@ pub trait TransactionsProvider: BlockNumReader + Send + Sync {
         block: BlockHashOrNumber,
     ) -> Result<Option<Vec<TransactionSigned>>>;
 
+    /// Get transactions by block id in their stored form, without computing their hashes.
+    fn transactions_by_block_no_hash(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSignedNoHash>>>;
+
     /// Get transactions by block range.
     fn transactions_by_block_range(
         &self,
@@ -11,6 +11,10 @@ pub trait WithdrawalsProvider: Send + Sync {
         timestamp: u64,
     ) -> Result<Option<Vec<Withdrawal>>>;
 
-    /// Get latest withdrawal from this block or earlier .
+    /// Get the last withdrawal of the latest block up to the best block that has any
+    /// withdrawals.
+    ///
+    /// Since withdrawal indices increase monotonically, this is also the withdrawal with the
+    /// highest index on the canonical chain.
     fn latest_withdrawal(&self) -> Result<Option<Withdrawal>>;
 }
@@ -5,7 +5,7 @@ use std::fmt::Debug;
 
 #[cfg(test)]
 mod test {
-    use crate::{test_utils::blocks::*, ProviderFactory, TransactionsProvider};
+    use crate::{test_utils::blocks::*, ProviderFactory, ReceiptProvider, TransactionsProvider};
     use reth_db::{
         models::{storage_sharded_key::StorageShardedKey, ShardedKey},
         tables,
@@ -194,4 +194,37 @@ mod test {
         // assert genesis state
         assert_genesis_block(&provider, genesis);
     }
+
+    #[test]
+    fn receipts_by_block_range_stops_at_unexecuted_block() {
+        let db = create_test_rw_db();
+
+        let chain_spec = ChainSpecBuilder::default()
+            .chain(MAINNET.chain)
+            .genesis(MAINNET.genesis.clone())
+            .shanghai_activated()
+            .build();
+
+        let factory = ProviderFactory::new(db.as_ref(), Arc::new(chain_spec));
+        let provider = factory.provider_rw().unwrap();
+
+        let data = BlockChainTestData::default();
+        let (block1, exec_res1) = data.blocks[0].clone();
+        let (block2, _) = data.blocks[1].clone();
+
+        provider.insert_block(data.genesis.clone(), None).unwrap();
+        provider.append_blocks_with_post_state(vec![block1], exec_res1.clone()).unwrap();
+        // block two has a body but was never executed
+        provider.insert_block(block2.block, Some(block2.senders)).unwrap();
+
+        assert_eq!(
+            provider.receipts_by_block_range(0..=2).unwrap(),
+            vec![vec![], exec_res1.receipts(1).to_vec()]
+        );
+        assert_eq!(
+            provider.receipts_by_block(1.into()).unwrap(),
+            Some(exec_res1.receipts(1).to_vec())
+        );
+        assert_eq!(provider.receipts_by_block(2.into()).unwrap(), None);
+    }
 }